@@ -1,38 +1,320 @@
-use bonsaidb::core::connection::Connection;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use bonsaidb::client::url::Url;
+use bonsaidb::client::{AsyncClient, AsyncRemoteDatabase};
+use bonsaidb::core::connection::{AsyncConnection, AsyncStorageConnection, Connection};
+use bonsaidb::core::networking::PayloadCompression;
+use bonsaidb::core::schema::{Collection, SerializedCollection};
 use bonsaidb::core::test_util::TestDirectory;
+use bonsaidb::core::transaction::{Operation, Transaction};
 #[cfg(feature = "compression")]
 use bonsaidb::local::config::Compression;
 use bonsaidb::local::config::{Builder, StorageConfiguration};
 use bonsaidb::local::Database;
+use bonsaidb::server::{CompressionMode, DefaultPermissions, Server, ServerConfiguration};
 use criterion::measurement::WallTime;
-use criterion::{BenchmarkGroup, BenchmarkId};
+use criterion::{BenchmarkGroup, BenchmarkId, Throughput};
+use tokio::runtime::Runtime;
 use ubyte::ToByteUnit;
 
-use crate::collections::ResizableDocument;
+use crate::collections::{Latencies, ResizableDocument, ResizableDocumentsBySize};
 
-fn save_document(doc: &ResizableDocument, db: &Database) {
-    db.collection::<ResizableDocument>().push(doc).unwrap();
-}
+/// The number of documents stored before benchmarking reads.
+const READ_DOCUMENT_COUNT: usize = 1_000;
 
 #[cfg_attr(not(feature = "compression"), allow(unused_mut))]
-pub(super) fn save_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
-    let path = TestDirectory::absolute("benches-basics.bonsaidb");
-    let mut configs = vec![("bonsaidb-local", StorageConfiguration::new(&path))];
+fn configurations(path: &Path) -> Vec<(&'static str, StorageConfiguration)> {
+    let mut configs = vec![("bonsaidb-local", StorageConfiguration::new(path))];
     #[cfg(feature = "compression")]
     {
         configs.push((
             "bonsaidb-local+lz4",
-            StorageConfiguration::new(&path).default_compression(Compression::Lz4),
+            StorageConfiguration::new(path).default_compression(Compression::Lz4),
         ))
     }
-    for (label, config) in configs {
+    configs
+}
+
+/// The minimum size of a response that the server compresses when connection
+/// compression is enabled.
+const COMPRESSION_THRESHOLD: usize = 512;
+
+/// The connection compression used by the server and the clients in a
+/// networked benchmark.
+fn connection_compressions() -> [(&'static str, CompressionMode, Option<PayloadCompression>); 3] {
+    let enabled = CompressionMode::Enabled {
+        threshold: COMPRESSION_THRESHOLD,
+    };
+    [
+        ("bonsaidb", CompressionMode::Disabled, None),
+        ("bonsaidb+lz4", enabled, Some(PayloadCompression::Lz4)),
+        ("bonsaidb+zstd", enabled, Some(PayloadCompression::Zstd)),
+    ]
+}
+
+fn save_document(doc: &ResizableDocument, db: &Database) {
+    db.collection::<ResizableDocument>().push(doc).unwrap();
+}
+
+fn save_documents_for_reading(doc: &ResizableDocument, db: &Database) -> Vec<u64> {
+    (0..READ_DOCUMENT_COUNT)
+        .map(|_| db.collection::<ResizableDocument>().push(doc).unwrap().id)
+        .collect()
+}
+
+pub(super) fn save_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
+    let path = TestDirectory::absolute("benches-basics.bonsaidb");
+    for (label, config) in configurations(&path) {
         group.bench_function(BenchmarkId::new(label, doc.data.len().bytes()), |b| {
             let db = Database::open::<ResizableDocument>(config.clone()).unwrap();
             b.iter(|| save_document(doc, &db));
         });
     }
+}
+
+pub(super) fn get_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
+    let path = TestDirectory::absolute("benches-get.bonsaidb");
+    for (label, config) in configurations(&path) {
+        let size = doc.data.len().bytes();
+        let db = Database::open::<ResizableDocument>(config).unwrap();
+        let ids = save_documents_for_reading(doc, &db);
+        let latencies = Latencies::default();
+        group.bench_function(BenchmarkId::new(label, size), |b| {
+            let mut ids = ids.iter().cycle();
+            b.iter(|| {
+                let start = Instant::now();
+                let document = db
+                    .collection::<ResizableDocument>()
+                    .get(ids.next().unwrap())
+                    .unwrap();
+                latencies.record(start.elapsed());
+                document
+            });
+        });
+        latencies.report(&format!("get_documents/{label}/{size}"));
+    }
+}
+
+pub(super) fn query_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
+    let path = TestDirectory::absolute("benches-query.bonsaidb");
+    for (label, config) in configurations(&path) {
+        let db = Database::open::<ResizableDocument>(config).unwrap();
+        save_documents_for_reading(doc, &db);
+        // Smaller documents are added so that each query has to filter out
+        // other keys.
+        save_documents_for_reading(&ResizableDocument::random(16), &db);
+        let size = doc.data.len() as u64;
+        // Bring the view up to date so that the first sample doesn't include
+        // the time it takes to map every document.
+        db.view::<ResizableDocumentsBySize>()
+            .with_key(&size)
+            .query()
+            .unwrap();
+        let latencies = Latencies::default();
+        group.bench_function(BenchmarkId::new(label, doc.data.len().bytes()), |b| {
+            b.iter(|| {
+                let start = Instant::now();
+                let mappings = db
+                    .view::<ResizableDocumentsBySize>()
+                    .with_key(&size)
+                    .limit(10)
+                    .query()
+                    .unwrap();
+                latencies.record(start.elapsed());
+                mappings
+            });
+        });
+        latencies.report(&format!(
+            "query_documents/{label}/{}",
+            doc.data.len().bytes()
+        ));
+    }
+}
+
+pub(super) fn mixed_workload(
+    group: &mut BenchmarkGroup<WallTime>,
+    doc: &ResizableDocument,
+    readers: u64,
+    writers: u64,
+) {
+    let path = TestDirectory::absolute("benches-mixed.bonsaidb");
+    for (label, config) in configurations(&path) {
+        let threads = format!("{readers}r-{writers}w");
+        let db = Database::open::<ResizableDocument>(config).unwrap();
+        let ids = save_documents_for_reading(doc, &db);
+        let read_latencies = Latencies::default();
+        let write_latencies = Latencies::default();
+        group.bench_function(BenchmarkId::new(label, &threads), |b| {
+            b.iter_custom(|iterations| {
+                let next_id = AtomicUsize::new(0);
+                let start = Instant::now();
+                std::thread::scope(|scope| {
+                    for _ in 0..readers {
+                        scope.spawn(|| {
+                            for _ in 0..iterations {
+                                let index = next_id.fetch_add(1, Ordering::Relaxed) % ids.len();
+                                let started_at = Instant::now();
+                                db.collection::<ResizableDocument>()
+                                    .get(&ids[index])
+                                    .unwrap();
+                                read_latencies.record(started_at.elapsed());
+                            }
+                        });
+                    }
+                    for _ in 0..writers {
+                        scope.spawn(|| {
+                            for _ in 0..iterations {
+                                let started_at = Instant::now();
+                                save_document(doc, &db);
+                                write_latencies.record(started_at.elapsed());
+                            }
+                        });
+                    }
+                });
+                start.elapsed()
+            });
+        });
+        read_latencies.report(&format!("mixed_workload/{label}/{threads}/reads"));
+        write_latencies.report(&format!("mixed_workload/{label}/{threads}/writes"));
+    }
+}
+
+pub(super) fn save_documents_in_batches(
+    group: &mut BenchmarkGroup<WallTime>,
+    doc: &ResizableDocument,
+    batch_size: u64,
+) {
+    let path = TestDirectory::absolute("benches-batches.bonsaidb");
+    let contents = ResizableDocument::serialize(doc).unwrap();
+    for (label, config) in configurations(&path) {
+        let db = Database::open::<ResizableDocument>(config).unwrap();
+        let latencies = Latencies::default();
+        group.bench_function(BenchmarkId::new(label, batch_size), |b| {
+            b.iter(|| {
+                let mut transaction = Transaction::new();
+                for _ in 0..batch_size {
+                    transaction.push(Operation::insert(
+                        ResizableDocument::collection_name(),
+                        None,
+                        contents.clone(),
+                    ));
+                }
+                let start = Instant::now();
+                db.apply_transaction(transaction).unwrap();
+                latencies.record(start.elapsed());
+            });
+        });
+        latencies.report(&format!("save_documents_in_batches/{label}/{batch_size}"));
+    }
+}
+
+pub(super) fn list_documents_networked(
+    group: &mut BenchmarkGroup<WallTime>,
+    doc: &ResizableDocument,
+    counts: &[u32],
+) {
+    for (index, (label, mode, compression)) in connection_compressions().into_iter().enumerate() {
+        let path = TestDirectory::absolute(format!("benches-networked-{index}.bonsaidb"));
+        let runtime = Runtime::new().unwrap();
+        let port = 7024 + u16::try_from(index).unwrap() * 2;
+        let configuration = ServerConfiguration::new(&path)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .compression(mode)
+            .with_schema::<ResizableDocument>()
+            .unwrap();
+        let (quic_database, ws_database) =
+            initialize_networked_server(&runtime, configuration, compression, port);
+        let largest = counts.iter().copied().max().unwrap_or_default();
+        runtime.block_on(async {
+            for _ in 0..largest {
+                quic_database
+                    .collection::<ResizableDocument>()
+                    .push(doc)
+                    .await
+                    .unwrap();
+            }
+        });
+
+        for &count in counts {
+            group.throughput(Throughput::Bytes(u64::from(count) * doc.data.len() as u64));
+            for (transport, database) in [("quic", &quic_database), ("ws", &ws_database)] {
+                group.bench_function(
+                    BenchmarkId::new(format!("{label}-{transport}"), count),
+                    |b| {
+                        b.iter(|| {
+                            runtime.block_on(async {
+                                database
+                                    .collection::<ResizableDocument>()
+                                    .all()
+                                    .limit(count)
+                                    .await
+                                    .unwrap()
+                            })
+                        });
+                    },
+                );
+            }
+        }
+    }
+}
 
-    // TODO bench read performance
-    // TODO bench read + write performance (with different numbers of readers/writers)
-    // TODO (once supported) bench batch saving
+fn initialize_networked_server(
+    runtime: &Runtime,
+    configuration: ServerConfiguration,
+    compression: Option<PayloadCompression>,
+    port: u16,
+) -> (AsyncRemoteDatabase, AsyncRemoteDatabase) {
+    let build_client = |url: Url| {
+        let builder = AsyncClient::build(url);
+        match compression {
+            Some(compression) => builder.with_compression(compression),
+            None => builder,
+        }
+    };
+    let server = runtime.block_on(async {
+        let server = Server::open(configuration).await.unwrap();
+        server.install_self_signed_certificate(false).await.unwrap();
+        server
+            .create_database::<ResizableDocument>("collections", false)
+            .await
+            .unwrap();
+        server
+    });
+    let certificate = runtime
+        .block_on(async { server.certificate_chain().await.unwrap() })
+        .into_end_entity_certificate();
+    let quic_server = server.clone();
+    runtime.spawn(async move {
+        quic_server.listen_on(port).await.unwrap();
+    });
+    runtime.spawn(async move {
+        server
+            .listen_for_websockets_on(format!("0.0.0.0:{}", port + 1), false)
+            .await
+            .unwrap();
+    });
+    let quic_database = runtime.block_on(async {
+        // Allow the server time to start listening
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        let url = Url::parse(&format!("bonsaidb://localhost:{port}")).unwrap();
+        let client = build_client(url)
+            .with_certificate(certificate)
+            .build()
+            .unwrap();
+        client
+            .database::<ResizableDocument>("collections")
+            .await
+            .unwrap()
+    });
+    let ws_database = runtime.block_on(async {
+        let url = Url::parse(&format!("ws://localhost:{}", port + 1)).unwrap();
+        let client = build_client(url).build().unwrap();
+        client
+            .database::<ResizableDocument>("collections")
+            .await
+            .unwrap()
+    });
+    (quic_database, ws_database)
 }
@@ -1,32 +1,68 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
 use bonsaidb::core::arc_bytes::serde::Bytes;
-use bonsaidb::core::schema::Collection;
+use bonsaidb::core::document::{CollectionDocument, Emit};
+use bonsaidb::core::schema::{Collection, CollectionMapReduce, View, ViewMapResult, ViewSchema};
 use criterion::{Criterion, Throughput};
 use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 
 mod bonsai;
+mod nebari;
 #[cfg(feature = "sqlite")]
 mod rusqlite;
 
+static KB: usize = 1024;
+
 #[derive(Serialize, Deserialize, Debug, Collection)]
-#[collection(name = "resizable-docs")]
+#[collection(name = "resizable-docs", views = [ResizableDocumentsBySize])]
 struct ResizableDocument {
     data: Bytes,
 }
 
-pub fn save_documents(c: &mut Criterion) {
-    static KB: usize = 1024;
+impl ResizableDocument {
+    fn random(size: usize) -> Self {
+        let mut rng = thread_rng();
+        let mut data = (0..size).map(|_| rng.gen()).collect::<Vec<_>>();
+        data.resize_with(size, || 7u8);
+        Self {
+            data: Bytes::from(data),
+        }
+    }
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = ResizableDocument, key = u64, value = (), name = "by-size")]
+struct ResizableDocumentsBySize;
+
+impl CollectionMapReduce for ResizableDocumentsBySize {
+    fn map<'doc>(
+        &self,
+        document: CollectionDocument<ResizableDocument>,
+    ) -> ViewMapResult<'doc, Self::View> {
+        document
+            .header
+            .emit_key(document.contents.data.len() as u64)
+    }
+}
+
+pub fn benches(c: &mut Criterion) {
+    save_documents(c);
+    get_documents(c);
+    query_documents(c);
+    mixed_workload(c);
+    save_documents_in_batches(c);
+    storage_overhead(c);
+    list_documents_networked(c);
+}
 
+pub fn save_documents(c: &mut Criterion) {
     // First set of benchmarks tests inserting documents
     let mut group = c.benchmark_group("save_documents");
     for size in [KB, 2 * KB, 8 * KB, 32 * KB, KB * KB] {
-        let mut rng = thread_rng();
         group.throughput(Throughput::Bytes(size as u64));
-        let mut data = (0..size).map(|_| rng.gen()).collect::<Vec<_>>();
-        data.resize_with(size, || 7u8);
-        let doc = ResizableDocument {
-            data: Bytes::from(data),
-        };
+        let doc = ResizableDocument::random(size);
 
         bonsai::save_documents(&mut group, &doc);
         #[cfg(feature = "sqlite")]
@@ -34,3 +70,112 @@ pub fn save_documents(c: &mut Criterion) {
     }
     group.finish();
 }
+
+pub fn get_documents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_documents");
+    for size in [KB, 8 * KB, 32 * KB] {
+        group.throughput(Throughput::Bytes(size as u64));
+        let doc = ResizableDocument::random(size);
+
+        bonsai::get_documents(&mut group, &doc);
+    }
+    group.finish();
+}
+
+pub fn query_documents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_documents");
+    for size in [KB, 8 * KB, 32 * KB] {
+        group.throughput(Throughput::Elements(1));
+        let doc = ResizableDocument::random(size);
+
+        bonsai::query_documents(&mut group, &doc);
+    }
+    group.finish();
+}
+
+pub fn mixed_workload(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mixed_workload");
+    let doc = ResizableDocument::random(2 * KB);
+    for (readers, writers) in [(1, 1), (4, 1), (1, 4), (8, 2)] {
+        // Each iteration performs one operation on every thread.
+        group.throughput(Throughput::Elements(readers + writers));
+
+        bonsai::mixed_workload(&mut group, &doc, readers, writers);
+    }
+    group.finish();
+}
+
+pub fn save_documents_in_batches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("save_documents_in_batches");
+    let doc = ResizableDocument::random(2 * KB);
+    for batch_size in [10, 100, 1_000] {
+        group.throughput(Throughput::Elements(batch_size));
+
+        bonsai::save_documents_in_batches(&mut group, &doc, batch_size);
+    }
+    group.finish();
+}
+
+/// Compares storing and retrieving a single document through BonsaiDb with
+/// storing and retrieving the same bytes directly in the underlying Nebari
+/// tree, quantifying the per-operation overhead of BonsaiDb.
+pub fn storage_overhead(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_overhead_insert");
+    for size in [KB, 32 * KB] {
+        group.throughput(Throughput::Bytes(size as u64));
+        let doc = ResizableDocument::random(size);
+
+        bonsai::save_documents(&mut group, &doc);
+        nebari::save_documents(&mut group, &doc);
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("storage_overhead_get");
+    for size in [KB, 32 * KB] {
+        group.throughput(Throughput::Bytes(size as u64));
+        let doc = ResizableDocument::random(size);
+
+        bonsai::get_documents(&mut group, &doc);
+        nebari::get_documents(&mut group, &doc);
+    }
+    group.finish();
+}
+
+/// Measures the throughput of retrieving increasingly large sets of documents
+/// over QUIC and WebSockets, with connection compression disabled and enabled
+/// using each supported algorithm.
+pub fn list_documents_networked(c: &mut Criterion) {
+    let mut group = c.benchmark_group("list_documents_networked");
+    let doc = ResizableDocument::random(KB);
+    bonsai::list_documents_networked(&mut group, &doc, &[1, 10, 100, 1_000]);
+    group.finish();
+}
+
+/// Records the latency of individual operations so that percentiles can be
+/// reported alongside criterion's per-iteration statistics.
+#[derive(Default)]
+struct Latencies(Mutex<Vec<Duration>>);
+
+impl Latencies {
+    fn record(&self, latency: Duration) {
+        self.0.lock().unwrap().push(latency);
+    }
+
+    fn report(&self, label: &str) {
+        let mut latencies = std::mem::take(&mut *self.0.lock().unwrap());
+        if latencies.is_empty() {
+            return;
+        }
+
+        latencies.sort_unstable();
+        let percentile = |percentile: usize| latencies[(latencies.len() - 1) * percentile / 100];
+        println!(
+            "{label}: {} operations, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+            latencies.len(),
+            percentile(50),
+            percentile(90),
+            percentile(99),
+            percentile(100),
+        );
+    }
+}
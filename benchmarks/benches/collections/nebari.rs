@@ -0,0 +1,80 @@
+use bonsaidb::core::schema::SerializedCollection;
+use bonsaidb::core::test_util::TestDirectory;
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, BenchmarkId};
+use nebari::io::fs::StdFile;
+use nebari::tree::Versioned;
+use nebari::{Config, Tree};
+use ubyte::ToByteUnit;
+
+use crate::collections::ResizableDocument;
+
+/// The number of documents stored before benchmarking reads. Matches the
+/// number stored by the BonsaiDb read benchmarks.
+const READ_DOCUMENT_COUNT: u64 = 1_000;
+
+fn open_tree(path: &TestDirectory) -> Tree<Versioned, StdFile> {
+    let roots = Config::default_for(path).open().unwrap();
+    roots.tree(Versioned::tree("documents")).unwrap()
+}
+
+pub(super) fn save_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
+    let path = TestDirectory::absolute("benches-nebari-insert.nebari");
+    let tree = open_tree(&path);
+    let mut next_id = 0_u64;
+
+    // Storing the raw bytes measures Nebari alone.
+    group.bench_function(BenchmarkId::new("nebari", doc.data.len().bytes()), |b| {
+        b.iter(|| {
+            next_id += 1;
+            tree.set(next_id.to_be_bytes().to_vec(), doc.data.to_vec())
+                .unwrap();
+        });
+    });
+
+    // Serializing the document as BonsaiDb does isolates the cost of the
+    // remaining work performed by BonsaiDb: headers, transactions, and views.
+    group.bench_function(
+        BenchmarkId::new("nebari+serialization", doc.data.len().bytes()),
+        |b| {
+            b.iter(|| {
+                next_id += 1;
+                let contents = ResizableDocument::serialize(doc).unwrap();
+                tree.set(next_id.to_be_bytes().to_vec(), contents).unwrap();
+            });
+        },
+    );
+}
+
+pub(super) fn get_documents(group: &mut BenchmarkGroup<WallTime>, doc: &ResizableDocument) {
+    let path = TestDirectory::absolute("benches-nebari-get.nebari");
+    let tree = open_tree(&path);
+    let contents = ResizableDocument::serialize(doc).unwrap();
+    for id in 0..READ_DOCUMENT_COUNT {
+        tree.set(id.to_be_bytes().to_vec(), contents.clone())
+            .unwrap();
+    }
+
+    group.bench_function(BenchmarkId::new("nebari", doc.data.len().bytes()), |b| {
+        let mut ids = (0..READ_DOCUMENT_COUNT).cycle();
+        b.iter(|| {
+            tree.get(&ids.next().unwrap().to_be_bytes())
+                .unwrap()
+                .unwrap()
+        });
+    });
+
+    group.bench_function(
+        BenchmarkId::new("nebari+deserialization", doc.data.len().bytes()),
+        |b| {
+            let mut ids = (0..READ_DOCUMENT_COUNT).cycle();
+            b.iter(|| {
+                let bytes = tree
+                    .get(&ids.next().unwrap().to_be_bytes())
+                    .unwrap()
+                    .unwrap();
+                ResizableDocument::deserialize(&bytes).unwrap()
+            });
+        },
+    );
+}
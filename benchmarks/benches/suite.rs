@@ -4,7 +4,7 @@ mod key_value;
 
 fn all_benches(c: &mut Criterion) {
     env_logger::init();
-    collections::save_documents(c);
+    collections::benches(c);
     key_value::benches(c);
 }
 
@@ -5,7 +5,7 @@ use std::time::Duration;
 
 use bonsaidb_core::api;
 use bonsaidb_core::api::ApiName;
-use bonsaidb_core::networking::CURRENT_PROTOCOL_VERSION;
+use bonsaidb_core::networking::{PayloadCompression, CURRENT_PROTOCOL_VERSION};
 #[cfg(not(target_arch = "wasm32"))]
 use fabruic::Certificate;
 #[cfg(not(target_arch = "wasm32"))]
@@ -29,6 +29,7 @@ pub struct Builder<AsyncMode> {
     custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
     connect_timeout: Option<Duration>,
     request_timeout: Option<Duration>,
+    accept_compression: Option<PayloadCompression>,
     #[cfg(not(target_arch = "wasm32"))]
     certificate: Option<fabruic::Certificate>,
     #[cfg(not(target_arch = "wasm32"))]
@@ -45,6 +46,7 @@ impl<AsyncMode> Builder<AsyncMode> {
             custom_apis: HashMap::new(),
             request_timeout: None,
             connect_timeout: None,
+            accept_compression: None,
             #[cfg(not(target_arch = "wasm32"))]
             certificate: None,
             #[cfg(not(target_arch = "wasm32"))]
@@ -112,6 +114,17 @@ impl<AsyncMode> Builder<AsyncMode> {
         self
     }
 
+    /// Requests that the server compress responses using `compression`.
+    ///
+    /// The server only compresses responses if it has compression enabled and
+    /// the algorithm is supported by both the server and this client. If
+    /// `compression` isn't enabled in this build, responses will not be
+    /// compressed.
+    pub fn with_compression(mut self, compression: PayloadCompression) -> Self {
+        self.accept_compression = Some(compression).filter(|c| c.is_available());
+        self
+    }
+
     fn finish_internal(self) -> Result<AsyncClient, Error> {
         AsyncClient::new_from_parts(
             self.url,
@@ -119,6 +132,7 @@ impl<AsyncMode> Builder<AsyncMode> {
             self.custom_apis,
             self.connect_timeout,
             self.request_timeout,
+            self.accept_compression,
             #[cfg(not(target_arch = "wasm32"))]
             self.certificate,
             #[cfg(not(target_arch = "wasm32"))]
@@ -18,8 +18,10 @@ use bonsaidb_core::connection::{
 };
 use bonsaidb_core::networking::{
     AlterUserPermissionGroupMembership, AlterUserRoleMembership, AssumeIdentity, CreateDatabase,
-    CreateUser, DeleteDatabase, DeleteUser, ListAvailableSchemas, ListDatabases, LogOutSession,
-    MessageReceived, Payload, UnregisterSubscriber, CURRENT_PROTOCOL_VERSION,
+    CreateUser, DatabaseSchema, DeleteDatabase, DeleteUser, ListAvailableSchemas, ListDatabases,
+    LogOutSession, MessageReceived, Payload, PayloadCompression, RenameDatabase,
+    ServerShuttingDown, UnregisterSubscriber, CURRENT_PROTOCOL_VERSION,
+    DEFAULT_DESERIALIZATION_LIMIT,
 };
 use bonsaidb_core::permissions::Permissions;
 use bonsaidb_core::schema::{Nameable, Schema, SchemaName, SchemaSummary, Schematic};
@@ -44,6 +46,8 @@ mod quic_worker;
 mod remote_database;
 #[cfg(not(target_arch = "wasm32"))]
 mod sync;
+#[cfg(all(feature = "tcp", not(target_arch = "wasm32")))]
+mod tcp_worker;
 #[cfg(all(feature = "websockets", not(target_arch = "wasm32")))]
 mod tungstenite_worker;
 #[cfg(all(feature = "websockets", target_arch = "wasm32"))]
@@ -159,6 +163,36 @@ pub type WebSocketError = wasm_websocket_worker::WebSocketError;
 /// # }
 /// ```
 ///
+/// ## Connecting via plain TCP
+///
+/// When feature `tcp` is enabled, clients can connect to a server listening
+/// with `listen_for_framed_tcp_on()` using the `bonsaidb+tcp` URL scheme. If
+/// no port is specified, port 5645 is assumed.
+///
+/// This transport is not encrypted, and should only be used to connect
+/// through a proxy that terminates TLS or over a trusted network.
+///
+/// ```rust
+/// # use bonsaidb_client::{AsyncClient, url::Url};
+/// # async fn test_fn() -> anyhow::Result<()> {
+/// let client = AsyncClient::build(Url::parse("bonsaidb+tcp://localhost")?).build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Concurrent requests
+///
+/// Requests are pipelined over a single connection. Each request is assigned
+/// an id, and the server's response carries the same id, so responses can
+/// arrive in any order and a slow request does not delay other requests from
+/// the same client. Clones of a client share its connection.
+///
+/// The server processes at most
+/// `ServerConfiguration::client_simultaneous_request_limit` requests (16 by
+/// default) from a single client at once. Additional requests wait on the
+/// server until an earlier request completes. The client does not impose a
+/// limit of its own.
+///
 /// ## Using a `Api`
 ///
 /// Our user guide has a [section on creating and
@@ -259,6 +293,7 @@ pub struct Data {
     connection_counter: Arc<AtomicU32>,
     request_id: AtomicU32,
     subscribers: SubscriberMap,
+    accept_compression: Option<PayloadCompression>,
     #[cfg(feature = "test-util")]
     background_task_running: Arc<AtomicBool>,
 }
@@ -290,6 +325,7 @@ impl AsyncClient {
             HashMap::default(),
             None,
             None,
+            None,
             #[cfg(not(target_arch = "wasm32"))]
             None,
             #[cfg(not(target_arch = "wasm32"))]
@@ -318,6 +354,7 @@ impl AsyncClient {
         mut custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
         connect_timeout: Option<Duration>,
         request_timeout: Option<Duration>,
+        accept_compression: Option<PayloadCompression>,
         #[cfg(not(target_arch = "wasm32"))] certificate: Option<fabruic::Certificate>,
         #[cfg(not(target_arch = "wasm32"))] tokio: Option<Handle>,
     ) -> Result<Self, Error> {
@@ -345,12 +382,25 @@ impl AsyncClient {
                 },
             ))),
         );
+        custom_apis
+            .entry(ServerShuttingDown::name())
+            .or_insert_with(|| {
+                Some(Arc::new(ApiCallback::<ServerShuttingDown>::new(
+                    |notice: ServerShuttingDown| async move {
+                        log::info!(
+                            "server is shutting down, outstanding requests have {:?} to complete",
+                            notice.timeout
+                        );
+                    },
+                )))
+            });
         // Default timeouts to 1 minute.
         let connection = ConnectionInfo {
             url,
             subscribers,
             connect_timeout: connect_timeout.unwrap_or(Duration::from_secs(60)),
             request_timeout: request_timeout.unwrap_or(Duration::from_secs(60)),
+            accept_compression,
         };
         match connection.url.scheme() {
             #[cfg(not(target_arch = "wasm32"))]
@@ -361,6 +411,13 @@ impl AsyncClient {
                 custom_apis,
                 tokio,
             )),
+            #[cfg(all(feature = "tcp", not(target_arch = "wasm32")))]
+            "bonsaidb+tcp" => Ok(Self::new_tcp_client(
+                connection,
+                protocol_version,
+                custom_apis,
+                tokio,
+            )),
             #[cfg(feature = "websockets")]
             "wss" | "ws" => Ok(Self::new_websocket_client(
                 connection,
@@ -385,6 +442,7 @@ impl AsyncClient {
         let connection_counter = Arc::new(AtomicU32::default());
         let request_timeout = server.request_timeout;
         let subscribers = server.subscribers.clone();
+        let accept_compression = server.accept_compression;
 
         let worker = sync::spawn_client(
             quic_worker::reconnecting_client_loop(
@@ -414,6 +472,56 @@ impl AsyncClient {
                 request_id: AtomicU32::default(),
                 effective_permissions: Mutex::default(),
                 subscribers,
+                accept_compression,
+                #[cfg(feature = "test-util")]
+                background_task_running,
+            }),
+            session: ClientSession::default(),
+            request_timeout,
+        }
+    }
+
+    #[cfg(all(feature = "tcp", not(target_arch = "wasm32")))]
+    fn new_tcp_client(
+        server: ConnectionInfo,
+        protocol_version: &'static str,
+        custom_apis: HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+        tokio: Option<Handle>,
+    ) -> Self {
+        let (request_sender, request_receiver) = flume::unbounded();
+        let connection_counter = Arc::new(AtomicU32::default());
+        let request_timeout = server.request_timeout;
+        let subscribers = server.subscribers.clone();
+        let accept_compression = server.accept_compression;
+
+        let worker = sync::spawn_client(
+            tcp_worker::reconnecting_client_loop(
+                server,
+                protocol_version,
+                request_receiver,
+                Arc::new(custom_apis),
+                connection_counter.clone(),
+            ),
+            tokio,
+        );
+
+        #[cfg(feature = "test-util")]
+        let background_task_running = Arc::new(AtomicBool::new(true));
+
+        Self {
+            data: Arc::new(Data {
+                request_sender,
+                _worker: CancellableHandle {
+                    worker,
+                    #[cfg(feature = "test-util")]
+                    background_task_running: background_task_running.clone(),
+                },
+                schemas: Mutex::default(),
+                request_id: AtomicU32::default(),
+                connection_counter,
+                effective_permissions: Mutex::default(),
+                subscribers,
+                accept_compression,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
@@ -433,6 +541,7 @@ impl AsyncClient {
         let connection_counter = Arc::new(AtomicU32::default());
         let request_timeout = server.request_timeout;
         let subscribers = server.subscribers.clone();
+        let accept_compression = server.accept_compression;
 
         let worker = sync::spawn_client(
             tungstenite_worker::reconnecting_client_loop(
@@ -462,6 +571,7 @@ impl AsyncClient {
                 connection_counter,
                 effective_permissions: Mutex::default(),
                 subscribers,
+                accept_compression,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
@@ -507,6 +617,7 @@ impl AsyncClient {
                 connection_counter,
                 effective_permissions: Mutex::default(),
                 subscribers: server.subscribers,
+                accept_compression: server.accept_compression,
                 #[cfg(feature = "test-util")]
                 background_task_running,
             }),
@@ -528,6 +639,8 @@ impl AsyncClient {
                 id: Some(id),
                 name,
                 value: Ok(bytes),
+                compression: None,
+                accept_compression: self.data.accept_compression,
             },
             responder: result_sender,
         })?;
@@ -737,10 +850,34 @@ impl AsyncStorageConnection for AsyncClient {
         Ok(())
     }
 
+    async fn rename_database(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&RenameDatabase {
+            name: name.to_string(),
+            new_name: new_name.to_string(),
+        })
+        .await?;
+        Ok(())
+    }
+
     async fn list_databases(&self) -> Result<Vec<Database>, bonsaidb_core::Error> {
         Ok(self.send_api_request(&ListDatabases).await?)
     }
 
+    async fn database_schema(
+        &self,
+        name: &str,
+    ) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        Ok(self
+            .send_api_request(&DatabaseSchema {
+                name: name.to_string(),
+            })
+            .await?)
+    }
+
     async fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         Ok(self.send_api_request(&ListAvailableSchemas).await?)
     }
@@ -918,10 +1055,13 @@ impl<T> Drop for CancellableHandle<T> {
 }
 
 async fn process_response_payload(
-    payload: Payload,
+    mut payload: Payload,
     outstanding_requests: &OutstandingRequestMapHandle,
     custom_apis: &HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
 ) {
+    if let Err(err) = payload.decompress(DEFAULT_DESERIALIZATION_LIMIT) {
+        payload.value = Err(err);
+    }
     if let Some(payload_id) = payload.id {
         if let Some(outstanding_request) = {
             let mut outstanding_requests = fast_async_lock!(outstanding_requests);
@@ -1052,4 +1192,5 @@ struct ConnectionInfo {
     #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
     pub connect_timeout: Duration,
     pub request_timeout: Duration,
+    pub accept_compression: Option<PayloadCompression>,
 }
@@ -2,19 +2,21 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
     AccessPolicy, AsyncConnection, AsyncLowLevelConnection, HasSchema, HasSession, Range,
-    SerializedQueryKey, Session, Sort,
+    SerializedQueryKey, SerializedViewPage, SerializedViewQuery, Session, Sort, ViewCursor,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
 use bonsaidb_core::networking::{
-    ApplyTransaction, Compact, CompactCollection, CompactKeyValueStore, Count, DeleteDocs, Get,
-    GetMultiple, LastTransactionId, List, ListExecutedTransactions, ListHeaders, Query,
-    QueryWithDocs, Reduce, ReduceGrouped,
+    ApplyTransaction, Compact, CompactCollection, CompactKeyValueStore, Count, CountView,
+    DeleteDocs, ExistingIds, FacetCounts, Get, GetMultiple, GetRevision, GetWithMappings,
+    GetWithViewValue, History, LastTransactionId, List, ListExecutedTransactions, ListHeaders,
+    Query, QueryMultiple, QueryPage, QueryWithDocs, Reduce, ReduceGrouped, ReduceWithStaleness,
 };
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
 use bonsaidb_core::schema::{self, CollectionName, Schematic, ViewName};
-use bonsaidb_core::transaction::{Executed, OperationResult, Transaction};
+use bonsaidb_core::transaction::{AppliedTransaction, Executed, Transaction};
 
 use crate::AsyncClient;
 
@@ -113,10 +115,10 @@ impl AsyncConnection for AsyncRemoteDatabase {
 
 #[async_trait]
 impl AsyncLowLevelConnection for AsyncRemoteDatabase {
-    async fn apply_transaction(
+    async fn apply_transaction_with_id(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+    ) -> Result<AppliedTransaction, bonsaidb_core::Error> {
         Ok(self
             .client
             .send_api_request(&ApplyTransaction {
@@ -141,6 +143,38 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
             .await?)
     }
 
+    async fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&History {
+                database: self.name.to_string(),
+                collection: collection.clone(),
+                id,
+            })
+            .await?)
+    }
+
+    async fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&GetRevision {
+                database: self.name.to_string(),
+                collection: collection.clone(),
+                id,
+                revision,
+            })
+            .await?)
+    }
+
     async fn get_multiple_from_collection(
         &self,
         ids: &[DocumentId],
@@ -156,6 +190,21 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
             .await?)
     }
 
+    async fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&ExistingIds {
+                database: self.name.to_string(),
+                collection: collection.clone(),
+                ids: ids.to_vec(),
+            })
+            .await?)
+    }
+
     async fn list_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -242,6 +291,44 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
             .await?)
     }
 
+    async fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&QueryPage {
+                query: Query {
+                    database: self.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    order,
+                    limit,
+                    access_policy,
+                },
+                after,
+            })
+            .await?)
+    }
+
+    async fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&QueryMultiple {
+                database: self.name.to_string(),
+                queries,
+            })
+            .await?)
+    }
+
     async fn query_by_name_with_docs(
         &self,
         view: &ViewName,
@@ -285,16 +372,107 @@ impl AsyncLowLevelConnection for AsyncRemoteDatabase {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         Ok(self
             .client
-            .send_api_request(&ReduceGrouped(Reduce {
+            .send_api_request(&ReduceGrouped {
+                reduce: Reduce {
+                    database: self.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    access_policy,
+                },
+                group_level,
+            })
+            .await?)
+    }
+
+    async fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&ReduceWithStaleness {
+                reduce: Reduce {
+                    database: self.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    access_policy,
+                },
+            })
+            .await?)
+    }
+
+    async fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&CountView {
                 database: self.name.to_string(),
                 view: view.clone(),
                 key,
                 access_policy,
-            }))
+            })
+            .await?)
+    }
+
+    async fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&FacetCounts {
+                database: self.name.to_string(),
+                view: view.clone(),
+                key,
+                access_policy,
+            })
+            .await?)
+    }
+
+    async fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&GetWithViewValue {
+                database: self.name.to_string(),
+                view: view.clone(),
+                id,
+                access_policy,
+            })
+            .await?)
+    }
+
+    async fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&GetWithMappings {
+                database: self.name.to_string(),
+                collection: collection.clone(),
+                id,
+                access_policy,
+            })
             .await?)
     }
 
@@ -3,7 +3,8 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::networking::{
-    CreateSubscriber, Publish, PublishToAll, SubscribeTo, UnsubscribeFrom,
+    CreateSubscriber, Publish, PublishAndFlush, PublishRetained, PublishToAll, SubscribeTo,
+    SubscribeToPattern, UnsubscribeFrom, UnsubscribeFromPattern,
 };
 use bonsaidb_core::pubsub::{AsyncPubSub, AsyncSubscriber, Receiver};
 
@@ -48,6 +49,36 @@ impl AsyncPubSub for super::AsyncRemoteDatabase {
         Ok(())
     }
 
+    async fn publish_bytes_and_flush(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self
+            .client
+            .send_api_request(&PublishAndFlush {
+                database: self.name.to_string(),
+                topic: Bytes::from(topic),
+                payload: Bytes::from(payload),
+            })
+            .await?)
+    }
+
+    async fn publish_bytes_retained(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.client
+            .send_api_request(&PublishRetained {
+                database: self.name.to_string(),
+                topic: Bytes::from(topic),
+                payload: Bytes::from(payload),
+            })
+            .await?;
+        Ok(())
+    }
+
     async fn publish_bytes_to_all(
         &self,
         topics: impl IntoIterator<Item = Vec<u8>> + Send + 'async_trait,
@@ -100,6 +131,28 @@ impl AsyncSubscriber for AsyncRemoteSubscriber {
         Ok(())
     }
 
+    async fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        self.client
+            .send_api_request(&SubscribeToPattern {
+                database: self.database.to_string(),
+                subscriber_id: self.id,
+                pattern: pattern.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        self.client
+            .send_api_request(&UnsubscribeFromPattern {
+                database: self.database.to_string(),
+                subscriber_id: self.id,
+                pattern: pattern.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
     fn receiver(&self) -> &Receiver {
         &self.receiver
     }
@@ -8,17 +8,21 @@ use bonsaidb_core::api;
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
     AccessPolicy, Connection, Database, HasSchema, HasSession, IdentityReference,
-    LowLevelConnection, Range, SerializedQueryKey, Sort, StorageConnection,
+    LowLevelConnection, Range, SerializedQueryKey, SerializedViewPage, SerializedViewQuery, Sort,
+    StorageConnection, ViewCursor,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
 use bonsaidb_core::keyvalue::KeyValue;
 use bonsaidb_core::networking::{
     AlterUserPermissionGroupMembership, AlterUserRoleMembership, ApplyTransaction, AssumeIdentity,
-    Compact, CompactCollection, CompactKeyValueStore, Count, CreateDatabase, CreateSubscriber,
-    CreateUser, DeleteDatabase, DeleteDocs, DeleteUser, ExecuteKeyOperation, Get, GetMultiple,
-    LastTransactionId, List, ListAvailableSchemas, ListDatabases, ListExecutedTransactions,
-    ListHeaders, Publish, PublishToAll, Query, QueryWithDocs, Reduce, ReduceGrouped, SubscribeTo,
-    UnsubscribeFrom, CURRENT_PROTOCOL_VERSION,
+    Compact, CompactCollection, CompactKeyValueStore, Count, CountView, CreateDatabase,
+    CreateSubscriber, CreateUser, DatabaseSchema, DeleteDatabase, DeleteDocs, DeleteUser,
+    ExecuteKeyOperation, ExistingIds, FacetCounts, Get, GetMultiple, GetRevision, GetWithMappings,
+    GetWithViewValue, History, LastTransactionId, List, ListAvailableSchemas, ListDatabases,
+    ListExecutedTransactions, ListHeaders, Publish, PublishAndFlush, PublishRetained, PublishToAll,
+    Query, QueryMultiple, QueryPage, QueryWithDocs, Reduce, ReduceGrouped, ReduceWithStaleness,
+    RenameDatabase, SubscribeTo, SubscribeToPattern, UnsubscribeFrom, UnsubscribeFromPattern,
+    CURRENT_PROTOCOL_VERSION,
 };
 use bonsaidb_core::pubsub::{AsyncSubscriber, PubSub, Receiver, Subscriber};
 use bonsaidb_core::schema::view::map;
@@ -64,6 +68,7 @@ impl BlockingClient {
             HashMap::default(),
             None,
             None,
+            None,
             #[cfg(not(target_arch = "wasm32"))]
             None,
             #[cfg(not(target_arch = "wasm32"))]
@@ -160,12 +165,29 @@ impl StorageConnection for BlockingClient {
         Ok(())
     }
 
+    fn rename_database(&self, name: &str, new_name: &str) -> Result<(), bonsaidb_core::Error> {
+        self.send_api_request(&RenameDatabase {
+            name: name.to_string(),
+            new_name: new_name.to_string(),
+        })?;
+        Ok(())
+    }
+
     fn list_databases(
         &self,
     ) -> Result<Vec<bonsaidb_core::connection::Database>, bonsaidb_core::Error> {
         Ok(self.send_api_request(&ListDatabases)?)
     }
 
+    fn database_schema(
+        &self,
+        name: &str,
+    ) -> Result<Option<bonsaidb_core::schema::SchemaName>, bonsaidb_core::Error> {
+        Ok(self.send_api_request(&DatabaseSchema {
+            name: name.to_string(),
+        })?)
+    }
+
     fn list_available_schemas(
         &self,
     ) -> Result<Vec<bonsaidb_core::schema::SchemaSummary>, bonsaidb_core::Error> {
@@ -364,10 +386,10 @@ impl Connection for BlockingRemoteDatabase {
 }
 
 impl LowLevelConnection for BlockingRemoteDatabase {
-    fn apply_transaction(
+    fn apply_transaction_with_id(
         &self,
         transaction: bonsaidb_core::transaction::Transaction,
-    ) -> Result<Vec<bonsaidb_core::transaction::OperationResult>, bonsaidb_core::Error> {
+    ) -> Result<bonsaidb_core::transaction::AppliedTransaction, bonsaidb_core::Error> {
         Ok(self.0.client.send_blocking_api_request(&ApplyTransaction {
             database: self.0.name.to_string(),
             transaction,
@@ -386,6 +408,32 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         })?)
     }
 
+    fn history_from_collection(
+        &self,
+        id: bonsaidb_core::document::DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&History {
+            database: self.0.name.to_string(),
+            collection: collection.clone(),
+            id,
+        })?)
+    }
+
+    fn get_revision_from_collection(
+        &self,
+        id: bonsaidb_core::document::DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&GetRevision {
+            database: self.0.name.to_string(),
+            collection: collection.clone(),
+            id,
+            revision,
+        })?)
+    }
+
     fn get_multiple_from_collection(
         &self,
         ids: &[bonsaidb_core::document::DocumentId],
@@ -398,6 +446,18 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         })?)
     }
 
+    fn existing_ids_in_collection(
+        &self,
+        ids: &[bonsaidb_core::document::DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<bonsaidb_core::document::DocumentId>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&ExistingIds {
+            database: self.0.name.to_string(),
+            collection: collection.clone(),
+            ids: ids.to_vec(),
+        })?)
+    }
+
     fn list_from_collection(
         &self,
         ids: Range<bonsaidb_core::document::DocumentId>,
@@ -471,6 +531,38 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         })?)
     }
 
+    fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&QueryPage {
+            query: Query {
+                database: self.0.name.to_string(),
+                view: view.clone(),
+                key,
+                order,
+                limit,
+                access_policy,
+            },
+            after,
+        })?)
+    }
+
+    fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<map::Serialized>>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&QueryMultiple {
+            database: self.0.name.to_string(),
+            queries,
+        })?)
+    }
+
     fn query_by_name_with_docs(
         &self,
         view: &bonsaidb_core::schema::ViewName,
@@ -515,18 +607,94 @@ impl LowLevelConnection for BlockingRemoteDatabase {
         &self,
         view: &bonsaidb_core::schema::ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<bonsaidb_core::schema::view::map::MappedSerializedValue>, bonsaidb_core::Error>
     {
-        Ok(self
-            .0
-            .client
-            .send_blocking_api_request(&ReduceGrouped(Reduce {
+        Ok(self.0.client.send_blocking_api_request(&ReduceGrouped {
+            reduce: Reduce {
                 database: self.0.name.to_string(),
                 view: view.clone(),
                 key,
                 access_policy,
-            }))?)
+            },
+            group_level,
+        })?)
+    }
+
+    fn reduce_with_staleness_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<map::Reduction<Bytes>, bonsaidb_core::Error> {
+        Ok(self
+            .0
+            .client
+            .send_blocking_api_request(&ReduceWithStaleness {
+                reduce: Reduce {
+                    database: self.0.name.to_string(),
+                    view: view.clone(),
+                    key,
+                    access_policy,
+                },
+            })?)
+    }
+
+    fn count_view_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&CountView {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            key,
+            access_policy,
+        })?)
+    }
+
+    fn facet_counts_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&FacetCounts {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            key,
+            access_policy,
+        })?)
+    }
+
+    fn get_with_view_value_by_name(
+        &self,
+        view: &bonsaidb_core::schema::ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&GetWithViewValue {
+            database: self.0.name.to_string(),
+            view: view.clone(),
+            id,
+            access_policy,
+        })?)
+    }
+
+    fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<map::EmittedKey>)>, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&GetWithMappings {
+            database: self.0.name.to_string(),
+            collection: collection.clone(),
+            id,
+            access_policy,
+        })?)
     }
 
     fn delete_docs_by_name(
@@ -584,6 +752,31 @@ impl PubSub for BlockingRemoteDatabase {
         Ok(())
     }
 
+    fn publish_bytes_and_flush(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        Ok(self.0.client.send_blocking_api_request(&PublishAndFlush {
+            database: self.0.name.to_string(),
+            topic: Bytes::from(topic),
+            payload: Bytes::from(payload),
+        })?)
+    }
+
+    fn publish_bytes_retained(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.0.client.send_blocking_api_request(&PublishRetained {
+            database: self.0.name.to_string(),
+            topic: Bytes::from(topic),
+            payload: Bytes::from(payload),
+        })?;
+        Ok(())
+    }
+
     fn publish_bytes_to_all(
         &self,
         topics: impl IntoIterator<Item = Vec<u8>> + Send,
@@ -623,6 +816,28 @@ impl Subscriber for BlockingRemoteSubscriber {
         Ok(())
     }
 
+    fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        self.0
+            .client
+            .send_blocking_api_request(&SubscribeToPattern {
+                database: self.0.database.to_string(),
+                subscriber_id: self.0.id,
+                pattern: pattern.to_string(),
+            })?;
+        Ok(())
+    }
+
+    fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        self.0
+            .client
+            .send_blocking_api_request(&UnsubscribeFromPattern {
+                database: self.0.database.to_string(),
+                subscriber_id: self.0.id,
+                pattern: pattern.to_string(),
+            })?;
+        Ok(())
+    }
+
     fn receiver(&self) -> &Receiver {
         AsyncSubscriber::receiver(&self.0)
     }
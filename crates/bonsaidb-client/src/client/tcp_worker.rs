@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use bonsaidb_core::api::ApiName;
+use bonsaidb_core::networking::{self, Payload, DEFAULT_DESERIALIZATION_LIMIT};
+use bonsaidb_utils::fast_async_lock;
+use flume::Receiver;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+use super::PendingRequest;
+use crate::client::{
+    disconnect_pending_requests, AnyApiCallback, ConnectionInfo, OutstandingRequestMapHandle,
+};
+use crate::Error;
+
+/// The longest protocol version the server may reply with when connecting.
+const MAX_PROTOCOL_VERSION_LENGTH: usize = 256;
+
+pub(super) async fn reconnecting_client_loop(
+    mut server: ConnectionInfo,
+    protocol_version: &str,
+    request_receiver: Receiver<PendingRequest>,
+    custom_apis: Arc<HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>>,
+    connection_counter: Arc<AtomicU32>,
+) -> Result<(), Error> {
+    if server.url.port().is_none() {
+        let _: Result<_, _> = server.url.set_port(Some(5645));
+    }
+
+    let mut pending_error = None;
+    while let Ok(request) = {
+        server.subscribers.clear();
+        request_receiver.recv_async().await
+    } {
+        if let Some(pending_error) = pending_error.take() {
+            drop(request.responder.send(Err(pending_error)));
+            continue;
+        }
+
+        connection_counter.fetch_add(1, Ordering::SeqCst);
+        let (receiver, mut sender) =
+            match tokio::time::timeout(server.connect_timeout, connect(&server, protocol_version))
+                .await
+            {
+                Ok(Ok(result)) => result,
+                Ok(Err(err)) => {
+                    drop(request.responder.send(Err(err)));
+                    continue;
+                }
+                Err(_) => {
+                    drop(request.responder.send(Err(Error::connect_timeout())));
+                    continue;
+                }
+            };
+
+        let outstanding_requests = OutstandingRequestMapHandle::default();
+        {
+            let mut outstanding_requests = fast_async_lock!(outstanding_requests);
+            if let Err(err) = write_payload(&mut sender, &request.request).await {
+                drop(request.responder.send(Err(err)));
+                continue;
+            }
+            outstanding_requests.insert(
+                request.request.id.expect("all requests must have ids"),
+                request,
+            );
+        }
+
+        if let Err(err) = tokio::try_join!(
+            request_sender(&request_receiver, sender, outstanding_requests.clone()),
+            response_processor(receiver, outstanding_requests.clone(), &custom_apis)
+        ) {
+            // Our socket was disconnected, clear the outstanding requests before returning.
+            log::error!("Error on socket {:?}", err);
+            pending_error = Some(err);
+            disconnect_pending_requests(&outstanding_requests, &mut pending_error).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Connects to the server and negotiates the protocol version.
+async fn connect(
+    server: &ConnectionInfo,
+    protocol_version: &str,
+) -> Result<(BufReader<OwnedReadHalf>, BufWriter<OwnedWriteHalf>), Error> {
+    let host = server
+        .url
+        .host_str()
+        .ok_or_else(|| Error::InvalidUrl(String::from("no host")))?;
+    let port = server.url.port().expect("port is always set");
+    let (reader, writer) = TcpStream::connect((host, port)).await?.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut writer = BufWriter::new(writer);
+
+    write_frame(&mut writer, protocol_version.as_bytes()).await?;
+    if read_frame(&mut reader, MAX_PROTOCOL_VERSION_LENGTH).await? == protocol_version.as_bytes() {
+        Ok((reader, writer))
+    } else {
+        Err(Error::ProtocolVersionMismatch)
+    }
+}
+
+async fn request_sender(
+    request_receiver: &Receiver<PendingRequest>,
+    mut sender: BufWriter<OwnedWriteHalf>,
+    outstanding_requests: OutstandingRequestMapHandle,
+) -> Result<(), Error> {
+    while let Ok(pending) = request_receiver.recv_async().await {
+        let mut outstanding_requests = fast_async_lock!(outstanding_requests);
+        write_payload(&mut sender, &pending.request).await?;
+
+        outstanding_requests.insert(
+            pending.request.id.expect("all requests must have ids"),
+            pending,
+        );
+    }
+
+    Err(Error::disconnected())
+}
+
+async fn response_processor(
+    mut receiver: BufReader<OwnedReadHalf>,
+    outstanding_requests: OutstandingRequestMapHandle,
+    custom_apis: &HashMap<ApiName, Option<Arc<dyn AnyApiCallback>>>,
+) -> Result<(), Error> {
+    loop {
+        let response = read_frame(&mut receiver, DEFAULT_DESERIALIZATION_LIMIT).await?;
+        let payload = bincode::deserialize::<Payload>(&response)?;
+
+        super::process_response_payload(payload, &outstanding_requests, custom_apis).await;
+    }
+}
+
+async fn write_payload<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    payload: &Payload,
+) -> Result<(), Error> {
+    write_frame(writer, &bincode::serialize(payload)?).await
+}
+
+/// Reads a single frame, which is prefixed by its length as a big-endian
+/// `u32`. Frames longer than `max_length` are rejected before any space is
+/// allocated for them.
+async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_length: usize,
+) -> Result<Vec<u8>, Error> {
+    let length = reader.read_u32().await? as usize;
+    if length > max_length {
+        return Err(Error::Core(bonsaidb_core::Error::Networking(
+            networking::Error::PayloadTooLarge(max_length),
+        )));
+    }
+
+    let mut frame = vec![0; length];
+    reader.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+/// Writes `frame` prefixed by its length as a big-endian `u32`.
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<(), Error> {
+    let length = u32::try_from(frame.len())
+        .map_err(|_| Error::Core(bonsaidb_core::Error::other("tcp", "frame too long")))?;
+    writer.write_u32(length).await?;
+    writer.write_all(frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
@@ -87,13 +87,20 @@ impl From<Error> for bonsaidb_core::Error {
     }
 }
 
-#[cfg(feature = "websockets")]
+#[cfg(any(feature = "websockets", feature = "tcp"))]
 impl From<bincode::Error> for Error {
     fn from(other: bincode::Error) -> Self {
         Self::Core(bonsaidb_core::Error::other("bincode", other))
     }
 }
 
+#[cfg(all(feature = "tcp", not(target_arch = "wasm32")))]
+impl From<std::io::Error> for Error {
+    fn from(other: std::io::Error) -> Self {
+        Self::Core(bonsaidb_core::Error::other("tcp", other))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod fabruic_impls {
     macro_rules! impl_from_fabruic {
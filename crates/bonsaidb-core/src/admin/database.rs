@@ -12,6 +12,10 @@ pub struct Database {
     pub name: String,
     /// The schema defining the database.
     pub schema: SchemaName,
+    /// The version of the schema's [migrations](crate::schema::Migrations)
+    /// this database has been upgraded to.
+    #[serde(default)]
+    pub schema_version: u32,
 }
 
 define_basic_unique_mapped_view!(
@@ -1,36 +1,46 @@
 use std::borrow::Borrow;
+use std::collections::{BTreeMap, VecDeque};
 use std::convert::Infallible;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::string::FromUtf8Error;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use actionable::{Action, Identifier};
 use arc_bytes::serde::Bytes;
 use async_trait::async_trait;
 use futures::future::BoxFuture;
-use futures::{Future, FutureExt};
+use futures::stream::{self, BoxStream};
+use futures::{Future, FutureExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 use crate::admin::{Role, User};
 use crate::document::{
-    CollectionDocument, CollectionHeader, Document, HasHeader, Header, OwnedDocument,
+    CollectionDocument, CollectionHeader, Document, DocumentId, HasHeader, Header, OwnedDocument,
+};
+use crate::key::{
+    next_byte_sequence, ByteSource, IntoPrefixRange, Key, KeyDescription, KeyEncoding, KeyKind,
+    KeyVisitor,
 };
-use crate::key::{ByteSource, IntoPrefixRange, Key, KeyEncoding, KeyKind, KeyVisitor};
 use crate::permissions::Permissions;
-use crate::schema::view::map::MappedDocuments;
+use crate::pubsub::{AsyncPubSub, AsyncSubscriber, TransactionsTopic};
+use crate::schema::view::map::{MappedDocuments, OwnedMappedDocument, Reduction};
 use crate::schema::{
     self, Map, MappedValue, Nameable, NamedReference, Schema, SchemaName, SchemaSummary,
-    SerializedCollection,
+    SerializedCollection, ViewName,
 };
 use crate::{transaction, Error};
 
 mod has_session;
 mod lowlevel;
+mod retry;
 
 pub use self::has_session::HasSession;
 pub use self::lowlevel::{AsyncLowLevelConnection, HasSchema, LowLevelConnection};
+pub use self::retry::{RetryPolicy, RetryingConnection};
 
 /// A connection to a database's [`Schema`](schema::Schema), giving access to
 /// [`Collection`s](crate::schema::Collection) and
@@ -54,6 +64,47 @@ pub trait Connection: LowLevelConnection + Sized + Send + Sync {
         View::new(self)
     }
 
+    /// Returns a builder for executing multiple view queries together.
+    fn multi_query(&self) -> MultiQuery<'_, Self> {
+        MultiQuery::new(self)
+    }
+
+    /// Returns a builder for applying a [`Transaction`](transaction::Transaction)
+    /// composed of typed operations.
+    fn transaction(&self) -> TransactionBuilder<'_, Self> {
+        TransactionBuilder::new(self)
+    }
+
+    /// Applies `transaction`, attempting it again according to `policy` if it
+    /// is rejected before being executed because a
+    /// [rate limit](Error::RateLimited) was exceeded. The current thread
+    /// sleeps between attempts, waiting at least as long as requested by the
+    /// server.
+    ///
+    /// Other errors are returned without retrying. Transactions are applied
+    /// while holding locks on the affected collections, so they never fail
+    /// because of transient conflicts with other transactions, and conflicts
+    /// such as [`Error::DocumentConflict`] would fail the same way if the
+    /// transaction were applied again. [Transient errors](Error::is_transient)
+    /// such as disconnections are not retried either, because the transaction
+    /// may have been committed before the error occurred.
+    fn apply_transaction_with_retry(
+        &self,
+        transaction: transaction::Transaction,
+        policy: &RetryPolicy,
+    ) -> Result<Vec<transaction::OperationResult>, Error> {
+        let mut attempts = 1;
+        loop {
+            match self.apply_transaction(transaction.clone()) {
+                Err(Error::RateLimited { retry_after }) if attempts < policy.max_attempts => {
+                    std::thread::sleep(policy.backoff(attempts).max(retry_after));
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Lists [executed transactions](transaction::Executed) from this
     /// [`Schema`](schema::Schema). By default, a maximum of 1000 entries will
     /// be returned, but that limit can be overridden by setting `result_limit`.
@@ -219,6 +270,48 @@ where
             .insert::<Cl, _, B>(Option::<&Cl::PrimaryKey>::None, contents)
     }
 
+    /// Adds a new `Document<Cl>` for each entry in `contents` using a single
+    /// transaction. If successful, the headers of the inserted documents are
+    /// returned in the same order as `contents`. If an error occurs, no
+    /// documents will be inserted.
+    ///
+    /// ## Automatic ID Assignment
+    ///
+    /// An id will be automatically assigned, if possible, by the storage backend, which uses
+    /// the [`Key`] trait to assign ids.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: &C) -> Result<(), Error> {
+    /// let inserted_headers = db
+    ///     .collection::<MyCollection>()
+    ///     .push_all_bytes([vec![], vec![]])?;
+    /// for header in inserted_headers {
+    ///     println!(
+    ///         "Inserted id {} with revision {}",
+    ///         header.id, header.revision
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn push_all_bytes<Contents, B>(
+        &self,
+        contents: Contents,
+    ) -> Result<Vec<CollectionHeader<Cl::PrimaryKey>>, crate::Error>
+    where
+        Contents: IntoIterator<Item = B>,
+        B: Into<Bytes> + Send,
+    {
+        let transaction = push_all_transaction::<Cl, _, _>(contents);
+        if transaction.operations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        pushed_headers(transaction.apply(self.connection)?)
+    }
+
     /// Adds a new `Document<Cl>` with the given `id` and contents `item`.
     ///
     /// ```rust
@@ -662,6 +755,10 @@ where
     /// The maximum number of results to return.
     pub limit: Option<u32>,
 
+    /// The number of key components to group by when reducing grouped
+    /// results. `None` groups by the entire key.
+    pub group_level: Option<usize>,
+
     _view: PhantomData<V>,
 }
 
@@ -679,6 +776,7 @@ where
             access_policy: AccessPolicy::UpdateBefore,
             sort: Sort::Ascending,
             limit: None,
+            group_level: None,
             _view: PhantomData,
         }
     }
@@ -709,6 +807,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -742,6 +841,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -772,6 +872,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -806,10 +907,45 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
 
+    /// Filters for entries in the view with serialized keys that begin with
+    /// `prefix`.
+    ///
+    /// This allows querying by the leading fields of a composite key by
+    /// serializing only those fields. Because variable length fields store
+    /// their lengths at the end of a composite key, only leading fields with a
+    /// fixed length can be matched this way.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # use bonsaidb_core::key::KeyEncoding;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// #[derive(View, Debug, Clone)]
+    /// #[view(name = "by-rank-and-name", key = (u32, String), collection = MyCollection)]
+    /// # #[view(core = bonsaidb_core)]
+    /// struct ByRankAndName;
+    ///
+    /// let prefix = (1_u32,).as_ord_bytes().expect("u32 is always encodable");
+    /// for mapping in ByRankAndName::entries(&db)
+    ///     .with_key_bytes_prefix(prefix.to_vec())
+    ///     .query()?
+    /// {
+    ///     assert_eq!(mapping.key.0, 1);
+    ///     println!("{} in document {:?}", mapping.key.1, mapping.source);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_key_bytes_prefix(mut self, prefix: impl Into<Bytes>) -> Self {
+        self.key = Some(QueryKey::Prefix(prefix.into()));
+        self
+    }
+
     /// Sets the access policy for queries.
     ///
     /// ```rust
@@ -895,6 +1031,34 @@ where
         self
     }
 
+    /// Groups the results of [`reduce_grouped()`](Self::reduce_grouped) by the
+    /// first `group_level` components of each key, rather than by the entire
+    /// key.
+    ///
+    /// Only tuple keys are made up of multiple components. All other key
+    /// types are treated as a single component, which means a `group_level`
+    /// greater than zero groups by the entire key. A `group_level` of zero
+    /// reduces all matching entries into a single group.
+    ///
+    /// The key returned for each group is the first key within the group.
+    /// Only its first `group_level` components are shared by the rest of the
+    /// group.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// let reductions = ScoresByRank::entries(&db).group_level(0).reduce_grouped()?;
+    /// assert!(reductions.len() <= 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub const fn group_level(mut self, group_level: usize) -> Self {
+        self.group_level = Some(group_level);
+        self
+    }
+
     /// Executes the query and retrieves the results.
     ///
     /// ```rust
@@ -913,6 +1077,67 @@ where
             .query::<V, Key>(self.key, self.sort, self.limit, self.access_policy)
     }
 
+    /// Executes the query and retrieves the results, grouped by key. Each
+    /// distinct key is returned once, in the query's sort order, along with
+    /// every mapping that was emitted with that key.
+    ///
+    /// [`limit`](Self::limit) limits the number of mappings, not the number
+    /// of keys.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// for (rank, mappings) in ScoresByRank::entries(&db).query_grouped()? {
+    ///     println!("Rank {rank} has {} scores", mappings.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_grouped(self) -> Result<GroupedMappings<V>, Error> {
+        self.connection
+            .query_grouped::<V, Key>(self.key, self.sort, self.limit, self.access_policy)
+    }
+
+    /// Executes the query and retrieves a page of at most
+    /// [`limit`](Self::limit) results that are ordered after `after`.
+    ///
+    /// Unlike [`query()`](Self::query), the mappings within the page are
+    /// ordered by their key and then by the id of their source document. The
+    /// [`next_cursor`](ViewPage::next_cursor) of the returned page can be
+    /// passed to a subsequent query to retrieve the next page. Because the
+    /// query resumes from the position of the last mapping rather than
+    /// skipping a number of results, mappings inserted or removed between
+    /// pages don't cause results to be skipped or repeated.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page = ScoresByRank::entries(&db).limit(50).query_page(cursor)?;
+    ///     for mapping in &page.mappings {
+    ///         println!("Rank {} has a score of {:3}", mapping.key, mapping.value);
+    ///     }
+    ///     cursor = page.next_cursor;
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_page(self, after: Option<ViewCursor>) -> Result<ViewPage<V>, Error> {
+        self.connection.query_page::<V, Key>(
+            self.key,
+            self.sort,
+            self.limit,
+            after,
+            self.access_policy,
+        )
+    }
+
     /// Executes the query and retrieves the results with the associated [`Document`s](crate::document::OwnedDocument).
     ///
     /// ```rust
@@ -990,6 +1215,54 @@ where
             .reduce::<V, Key>(self.key, self.access_policy)
     }
 
+    /// Executes a reduce over the results of the query, returning whether the
+    /// view had changes that were not yet reflected in the reduced value.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let reduction = ScoresByRank::entries(&db)
+    ///     .with_access_policy(AccessPolicy::NoUpdate)
+    ///     .reduce_with_staleness()?;
+    /// if reduction.was_stale {
+    ///     println!("Average score (may be outdated): {:3}", reduction.value);
+    /// } else {
+    ///     println!("Average score: {:3}", reduction.value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reduce_with_staleness(self) -> Result<Reduction<V::Value>, Error> {
+        self.connection
+            .reduce_with_staleness::<V, Key>(self.key, self.access_policy)
+    }
+
+    /// Executes a reduce over the results of the query, combining the result
+    /// with `initial`. This allows folding newly matched entries into a
+    /// previously reduced value. If no entries match, `initial` is returned.
+    ///
+    /// The view's reduce function must be associative, and should not depend
+    /// on the keys it is given when `rereduce` is true. See
+    /// [`LowLevelConnection::reduce_with_initial()`] for more information.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C, previous_score: f32) -> Result<(), Error> {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries(&db)
+    ///     .with_key_range(42..)
+    ///     .reduce_with_initial(previous_score)?;
+    /// println!("Combined score: {:3}", score);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reduce_with_initial(self, initial: V::Value) -> Result<V::Value, Error> {
+        self.connection
+            .reduce_with_initial::<V, Key>(self.key, initial, self.access_policy)
+    }
+
     /// Executes a reduce over the results of the query, grouping by key.
     ///
     /// ```rust
@@ -1008,7 +1281,43 @@ where
     /// ```
     pub fn reduce_grouped(self) -> Result<GroupedReductions<V>, Error> {
         self.connection
-            .reduce_grouped::<V, Key>(self.key, self.access_policy)
+            .reduce_grouped::<V, Key>(self.key, self.group_level, self.access_policy)
+    }
+
+    /// Counts the number of mappings that match this view query, without
+    /// loading the mappings themselves.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let count = ScoresByRank::entries(&db).with_key_range(42..).count()?;
+    /// println!("{count} entries have a rank of 42 or higher");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn count(self) -> Result<u64, Error> {
+        self.connection
+            .count_view::<V, Key>(self.key, self.access_policy)
+    }
+
+    /// Counts the number of mappings for each distinct key that matches this
+    /// view query. The counts are returned in ascending key order. The view
+    /// does not need to implement a reduce function.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// for (rank, count) in ScoresByRank::entries(&db).facet_counts()? {
+    ///     println!("{count} entries have a rank of {rank}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn facet_counts(self) -> Result<Vec<(V::Key, u64)>, Error> {
+        self.connection
+            .facet_counts::<V, Key>(self.key, self.access_policy)
     }
 
     /// Deletes all of the associated documents that match this view query.
@@ -1025,66 +1334,442 @@ where
         self.connection
             .delete_docs::<V, Key>(self.key, self.access_policy)
     }
+
+    /// Deletes all of the associated documents that match this view query,
+    /// skipping any documents that are changed or deleted between querying the
+    /// view and deleting the documents. Returns the number of documents
+    /// deleted.
+    ///
+    /// [`delete_docs()`](Self::delete_docs) aborts the entire deletion when
+    /// such a conflict is encountered.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let deleted = ScoresByRank::entries(&db)
+    ///     .with_key(&42)
+    ///     .delete_docs_skipping_conflicts()?;
+    /// println!("Deleted {deleted} documents");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn delete_docs_skipping_conflicts(self) -> Result<u64, Error> {
+        let mappings =
+            self.connection
+                .query::<V, Key>(self.key, Sort::Ascending, None, self.access_policy)?;
+        let mut deletes = PendingDeletes::from_mappings(mappings);
+        loop {
+            if deletes.is_empty() {
+                return Ok(0);
+            }
+
+            match self
+                .connection
+                .apply_transaction(deletes.transaction::<V::Collection>())
+            {
+                Ok(results) => return Ok(results.len() as u64),
+                Err(err) => deletes.skip_conflicting(err)?,
+            }
+        }
+    }
 }
 
-/// This type is the result of `query()`. It is a list of mappings, which
-/// contains:
+/// A batch of view queries that are executed together. Created using
+/// [`Connection::multi_query()`].
 ///
-/// - The key emitted during the map function.
-/// - The value emitted during the map function.
-/// - The source document header that the mappings originated from.
-pub type ViewMappings<V> = Vec<Map<<V as schema::View>::Key, <V as schema::View>::Value>>;
-/// This type is the result of `reduce_grouped()`. It is a list of all matching
-/// keys and the reduced value of all mapped entries for that key.
-pub type GroupedReductions<V> =
-    Vec<MappedValue<<V as schema::View>::Key, <V as schema::View>::Value>>;
+/// Networked connections send all of the queries to the server in a single
+/// request, avoiding a round trip per query.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::connection::Connection;
+/// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+/// let mut queries = db.multi_query();
+/// let low = queries.add(ScoresByRank::entries(&db).with_key_range(..10))?;
+/// let high = queries.add(ScoresByRank::entries(&db).with_key_range(90..))?;
+/// let mut results = queries.execute()?;
+/// println!("{} low ranks", results.take(low)?.len());
+/// println!("{} high ranks", results.take(high)?.len());
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+pub struct MultiQuery<'a, Cn> {
+    connection: &'a Cn,
+    batch: u64,
+    queries: Vec<SerializedViewQuery>,
+}
 
-/// A connection to a database's [`Schema`](schema::Schema), giving access to
-/// [`Collection`s](crate::schema::Collection) and
-/// [`Views`s](crate::schema::View). All functions on this trait are safe to use
-/// in an asynchronous context.
-#[async_trait]
-pub trait AsyncConnection: AsyncLowLevelConnection + Sized + Send + Sync {
-    /// The [`AsyncStorageConnection`] type that is paired with this type.
-    type Storage: AsyncStorageConnection<Database = Self>;
+impl<'a, Cn> MultiQuery<'a, Cn>
+where
+    Cn: Connection,
+{
+    fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            batch: next_multi_query_batch(),
+            queries: Vec::new(),
+        }
+    }
 
-    /// Returns the [`StorageConnection`] implementor that this database belongs
-    /// to.
-    fn storage(&self) -> Self::Storage;
+    /// Adds `query` to this batch. The returned [`MultiQueryIndex`] retrieves
+    /// the query's results from the [`MultiQueryResults`] returned by
+    /// [`Self::execute()`].
+    pub fn add<V, Key>(&mut self, query: View<'_, Cn, V, Key>) -> Result<MultiQueryIndex<V>, Error>
+    where
+        V: schema::SerializedView,
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        self.queries.push(SerializedViewQuery::for_view::<V, Key>(
+            self.connection.schematic(),
+            query.key,
+            query.sort,
+            query.limit,
+            query.access_policy,
+        )?);
+        Ok(MultiQueryIndex::new(self.batch, self.queries.len() - 1))
+    }
+
+    /// Executes all of the queries in this batch.
+    pub fn execute(self) -> Result<MultiQueryResults, Error> {
+        let batch = self.batch;
+        self.connection
+            .query_multiple_by_name(self.queries)
+            .map(|results| MultiQueryResults::new(batch, results))
+    }
+}
 
-    /// Accesses a collection for the connected [`Schema`](schema::Schema).
-    fn collection<C: schema::Collection>(&self) -> AsyncCollection<'_, Self, C> {
-        AsyncCollection::new(self)
+/// A builder for a [`Transaction`](transaction::Transaction) that is applied
+/// to a connection. Created using [`Connection::transaction()`].
+///
+/// Each function infers the [`CollectionName`](schema::CollectionName) from its
+/// collection type parameter and serializes the contents it is given. If
+/// serializing an operation fails, the error is returned by
+/// [`Self::apply()`]. As with any transaction, either all operations are
+/// applied or none are.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::connection::Connection;
+/// # fn test_fn<C: Connection>(db: &C) -> Result<(), Error> {
+/// let renamed = MyCollection::get(&1, db)?.expect("document missing");
+/// let removed = MyCollection::get(&2, db)?.expect("document missing");
+/// let results = db
+///     .transaction()
+///     .push::<MyCollection>(&MyCollection::named("new"))
+///     .update::<MyCollection>(renamed.header, &MyCollection::named("renamed"))
+///     .delete::<MyCollection, _>(&removed)
+///     .apply()?;
+/// assert_eq!(results.len(), 3);
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+pub struct TransactionBuilder<'a, Cn> {
+    connection: &'a Cn,
+    transaction: Result<transaction::Transaction, Error>,
+}
+
+impl<'a, Cn> TransactionBuilder<'a, Cn>
+where
+    Cn: Connection,
+{
+    fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            transaction: Ok(transaction::Transaction::new()),
+        }
     }
 
-    /// Accesses a [`schema::View`] from this connection.
-    fn view<V: schema::SerializedView>(&'_ self) -> AsyncView<'_, Self, V, V::Key> {
-        AsyncView::new(self)
+    /// Adds `operation` to the transaction.
+    pub fn with(self, operation: transaction::Operation) -> Self {
+        self.with_operation(|| Ok(operation))
     }
 
-    /// Lists [executed transactions](transaction::Executed) from this [`Schema`](schema::Schema). By default, a maximum of
-    /// 1000 entries will be returned, but that limit can be overridden by
-    /// setting `result_limit`. A hard limit of 100,000 results will be
-    /// returned. To begin listing after another known `transaction_id`, pass
-    /// `transaction_id + 1` into `starting_id`.
-    async fn list_executed_transactions(
-        &self,
-        starting_id: Option<u64>,
-        result_limit: Option<u32>,
-    ) -> Result<Vec<transaction::Executed>, Error>;
+    /// Adds an operation pushing a new document containing `contents` into
+    /// `C`. See [`Operation::push_serialized()`](transaction::Operation::push_serialized)
+    /// for how the document's id is assigned.
+    pub fn push<C: SerializedCollection>(self, contents: &C::Contents) -> Self {
+        self.with_operation(|| transaction::Operation::push_serialized::<C>(contents))
+    }
 
-    /// Fetches the last transaction id that has been committed, if any.
-    async fn last_transaction_id(&self) -> Result<Option<u64>, Error>;
+    /// Adds an operation inserting a new document containing `contents` into
+    /// `C` with `id`. If a document already exists with `id`, the transaction
+    /// will fail with a conflict error.
+    pub fn insert<C, PrimaryKey>(self, id: &PrimaryKey, contents: &C::Contents) -> Self
+    where
+        C: SerializedCollection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.with_operation(|| {
+            Ok(transaction::Operation::insert(
+                C::collection_name(),
+                Some(DocumentId::new(id)?),
+                C::serialize(contents)?,
+            ))
+        })
+    }
 
-    /// Compacts the entire database to reclaim unused disk space.
-    ///
-    /// This process is done by writing data to a new file and swapping the file
-    /// once the process completes. This ensures that if a hardware failure,
-    /// power outage, or crash occurs that the original collection data is left
-    /// untouched.
-    ///
-    /// ## Errors
-    ///
+    /// Adds an operation updating the document identified by `header` in `C`
+    /// to contain `contents`. If `header`'s revision is not the document's
+    /// current revision, the transaction will fail with a conflict error.
+    pub fn update<C: SerializedCollection>(
+        self,
+        header: CollectionHeader<C::PrimaryKey>,
+        contents: &C::Contents,
+    ) -> Self {
+        self.with_operation(|| transaction::Operation::update_serialized::<C>(header, contents))
+    }
+
+    /// Adds an operation overwriting the document with `id` in `C` to contain
+    /// `contents`. If no document exists with `id`, it will be created.
+    pub fn overwrite<C, PrimaryKey>(self, id: &PrimaryKey, contents: &C::Contents) -> Self
+    where
+        C: SerializedCollection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.with_operation(|| transaction::Operation::overwrite_serialized::<C, _>(id, contents))
+    }
+
+    /// Adds an operation deleting the document identified by `doc_or_header`
+    /// from `C`. If the header's revision is not the document's current
+    /// revision, the transaction will fail with a conflict error.
+    pub fn delete<C: schema::Collection, H: HasHeader>(self, doc_or_header: &H) -> Self {
+        self.with_operation(|| {
+            Ok(transaction::Operation::delete(
+                C::collection_name(),
+                doc_or_header.header()?,
+            ))
+        })
+    }
+
+    fn with_operation(
+        mut self,
+        operation: impl FnOnce() -> Result<transaction::Operation, Error>,
+    ) -> Self {
+        if let Ok(transaction) = &mut self.transaction {
+            match operation() {
+                Ok(operation) => transaction.push(operation),
+                Err(err) => self.transaction = Err(err),
+            }
+        }
+        self
+    }
+
+    /// Applies the transaction, returning the results of the operations. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    pub fn apply(self) -> Result<Vec<transaction::OperationResult>, Error> {
+        self.connection.apply_transaction(self.transaction?)
+    }
+}
+
+/// Returns a unique id for a new [`MultiQuery`] or [`AsyncMultiQuery`], which
+/// tags the indexes it returns.
+fn next_multi_query_batch() -> u64 {
+    static NEXT_BATCH: AtomicU64 = AtomicU64::new(0);
+    NEXT_BATCH.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Identifies a query added to a [`MultiQuery`] or [`AsyncMultiQuery`].
+#[must_use]
+pub struct MultiQueryIndex<V> {
+    batch: u64,
+    index: usize,
+    _view: PhantomData<V>,
+}
+
+impl<V> MultiQueryIndex<V> {
+    const fn new(batch: u64, index: usize) -> Self {
+        Self {
+            batch,
+            index,
+            _view: PhantomData,
+        }
+    }
+}
+
+/// The results of executing a [`MultiQuery`] or [`AsyncMultiQuery`].
+#[derive(Debug)]
+pub struct MultiQueryResults {
+    batch: u64,
+    results: Vec<Option<Vec<schema::view::map::Serialized>>>,
+}
+
+impl MultiQueryResults {
+    fn new(batch: u64, results: Vec<Vec<schema::view::map::Serialized>>) -> Self {
+        Self {
+            batch,
+            results: results.into_iter().map(Some).collect(),
+        }
+    }
+
+    /// Removes and deserializes the results of the query identified by
+    /// `index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidMultiQueryIndex`] if `index` was returned from
+    /// a different batch of queries.
+    pub fn take<V: schema::SerializedView>(
+        &mut self,
+        index: MultiQueryIndex<V>,
+    ) -> Result<ViewMappings<V>, Error> {
+        if index.batch != self.batch {
+            return Err(Error::InvalidMultiQueryIndex);
+        }
+        let mappings = self
+            .results
+            .get_mut(index.index)
+            .and_then(Option::take)
+            .ok_or(Error::InvalidMultiQueryIndex)?;
+        lowlevel::deserialize_mappings::<V>(mappings)
+    }
+}
+
+/// This type is the result of `query()`. It is a list of mappings, which
+/// contains:
+///
+/// - The key emitted during the map function.
+/// - The value emitted during the map function.
+/// - The source document header that the mappings originated from.
+pub type ViewMappings<V> = Vec<Map<<V as schema::View>::Key, <V as schema::View>::Value>>;
+/// This type is the result of `query_grouped()`. It is a list of each distinct
+/// key and the mappings that were emitted with that key.
+pub type GroupedMappings<V> = Vec<(<V as schema::View>::Key, ViewMappings<V>)>;
+/// This type is the result of `query_page()`. It contains a page of mappings
+/// and the cursor to continue the query from.
+pub struct ViewPage<V: schema::View> {
+    /// The mappings in this page.
+    pub mappings: ViewMappings<V>,
+    /// The cursor to pass to the next `query_page()` call to retrieve the
+    /// mappings following this page. `None` if no more mappings match the
+    /// query.
+    pub next_cursor: Option<ViewCursor>,
+}
+
+/// This type is the result of `reduce_grouped()`. It is a list of all matching
+/// keys and the reduced value of all mapped entries for that key.
+pub type GroupedReductions<V> =
+    Vec<MappedValue<<V as schema::View>::Key, <V as schema::View>::Value>>;
+
+/// A connection to a database's [`Schema`](schema::Schema), giving access to
+/// [`Collection`s](crate::schema::Collection) and
+/// [`Views`s](crate::schema::View). All functions on this trait are safe to use
+/// in an asynchronous context.
+#[async_trait]
+pub trait AsyncConnection: AsyncLowLevelConnection + Sized + Send + Sync {
+    /// The [`AsyncStorageConnection`] type that is paired with this type.
+    type Storage: AsyncStorageConnection<Database = Self>;
+
+    /// Returns the [`StorageConnection`] implementor that this database belongs
+    /// to.
+    fn storage(&self) -> Self::Storage;
+
+    /// Accesses a collection for the connected [`Schema`](schema::Schema).
+    fn collection<C: schema::Collection>(&self) -> AsyncCollection<'_, Self, C> {
+        AsyncCollection::new(self)
+    }
+
+    /// Accesses a [`schema::View`] from this connection.
+    fn view<V: schema::SerializedView>(&'_ self) -> AsyncView<'_, Self, V, V::Key> {
+        AsyncView::new(self)
+    }
+
+    /// Returns a builder for executing multiple view queries together.
+    fn multi_query(&self) -> AsyncMultiQuery<'_, Self> {
+        AsyncMultiQuery::new(self)
+    }
+
+    /// Returns a builder for applying a [`Transaction`](transaction::Transaction)
+    /// composed of typed operations.
+    fn transaction(&self) -> AsyncTransactionBuilder<'_, Self> {
+        AsyncTransactionBuilder::new(self)
+    }
+
+    /// Lists [executed transactions](transaction::Executed) from this [`Schema`](schema::Schema). By default, a maximum of
+    /// 1000 entries will be returned, but that limit can be overridden by
+    /// setting `result_limit`. A hard limit of 100,000 results will be
+    /// returned. To begin listing after another known `transaction_id`, pass
+    /// `transaction_id + 1` into `starting_id`.
+    async fn list_executed_transactions(
+        &self,
+        starting_id: Option<u64>,
+        result_limit: Option<u32>,
+    ) -> Result<Vec<transaction::Executed>, Error>;
+
+    /// Fetches the last transaction id that has been committed, if any.
+    async fn last_transaction_id(&self) -> Result<Option<u64>, Error>;
+
+    /// Returns a stream of the [executed transactions](transaction::Executed)
+    /// in this database, beginning with `starting_id`. Pass `None` to begin
+    /// with the first transaction in the transaction log.
+    ///
+    /// The stream first yields the transactions that are already in the
+    /// transaction log, then waits for new transactions to be committed and
+    /// yields them in order. The stream only ends if the subscriber it uses
+    /// to watch for new transactions is disconnected.
+    ///
+    /// New transactions are discovered by subscribing to
+    /// [`TransactionsTopic`], which the id of every committed transaction is
+    /// published to. The transactions themselves are read using
+    /// [`Self::list_executed_transactions()`], so a consumer that falls behind
+    /// catches up by reading the log rather than by buffering transactions in
+    /// memory.
+    fn watch_transactions(
+        &self,
+        starting_id: Option<u64>,
+    ) -> BoxStream<'_, Result<transaction::Executed, Error>>
+    where
+        Self: AsyncPubSub,
+    {
+        let state = TransactionWatchState {
+            subscriber: None,
+            next_id: starting_id,
+            pending: VecDeque::new(),
+        };
+        stream::try_unfold(state, move |mut state| async move {
+            loop {
+                if let Some(executed) = state.pending.pop_front() {
+                    return Ok(Some((executed, state)));
+                }
+
+                if let Some(subscriber) = &state.subscriber {
+                    if subscriber.receiver().receive_async().await.is_err() {
+                        return Ok(None);
+                    }
+                    // Notifications that are already queued are covered by
+                    // the batch read below.
+                    while subscriber.receiver().try_receive().is_ok() {}
+                } else {
+                    // Subscribe before reading the log so that transactions
+                    // committed while reading are not missed.
+                    let subscriber = self.create_subscriber().await?;
+                    subscriber.subscribe_to_topic(&TransactionsTopic).await?;
+                    state.subscriber = Some(subscriber);
+                }
+
+                let batch = self.list_executed_transactions(state.next_id, None).await?;
+                if let Some(last) = batch.last() {
+                    state.next_id = Some(last.id + 1);
+                }
+                state.pending.extend(batch);
+            }
+        })
+        .boxed()
+    }
+
+    /// Compacts the entire database to reclaim unused disk space.
+    ///
+    /// This process is done by writing data to a new file and swapping the file
+    /// once the process completes. This ensures that if a hardware failure,
+    /// power outage, or crash occurs that the original collection data is left
+    /// untouched.
+    ///
+    /// ## Errors
+    ///
     /// * [`Error::Other`]: an error occurred while compacting the database.
     async fn compact(&self) -> Result<(), crate::Error>;
 
@@ -1232,6 +1917,51 @@ where
             .await
     }
 
+    /// Adds a new `Document<Cl>` for each entry in `contents` using a single
+    /// transaction. If successful, the headers of the inserted documents are
+    /// returned in the same order as `contents`. If an error occurs, no
+    /// documents will be inserted.
+    ///
+    /// ## Automatic ID Assignment
+    ///
+    /// An id will be automatically assigned, if possible, by the storage backend, which uses
+    /// the [`Key`] trait to assign ids.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: &C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let inserted_headers = db
+    ///     .collection::<MyCollection>()
+    ///     .push_all_bytes([vec![], vec![]])
+    ///     .await?;
+    /// for header in inserted_headers {
+    ///     println!(
+    ///         "Inserted id {} with revision {}",
+    ///         header.id, header.revision
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn push_all_bytes<Contents, B>(
+        &self,
+        contents: Contents,
+    ) -> Result<Vec<CollectionHeader<Cl::PrimaryKey>>, crate::Error>
+    where
+        Contents: IntoIterator<Item = B>,
+        B: Into<Bytes> + Send,
+    {
+        let transaction = push_all_transaction::<Cl, _, _>(contents);
+        if transaction.operations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        pushed_headers(transaction.apply_async(self.connection).await?)
+    }
+
     /// Adds a new `Document<Cl>` with the given `id` and contents `item`.
     ///
     /// ```rust
@@ -1841,6 +2571,10 @@ where
     /// The maximum number of results to return.
     pub limit: Option<u32>,
 
+    /// The number of key components to group by when reducing grouped
+    /// results. `None` groups by the entire key.
+    pub group_level: Option<usize>,
+
     _view: PhantomData<V>,
 }
 
@@ -1858,6 +2592,7 @@ where
             access_policy: AccessPolicy::UpdateBefore,
             sort: Sort::Ascending,
             limit: None,
+            group_level: None,
             _view: PhantomData,
         }
     }
@@ -1894,6 +2629,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -1933,6 +2669,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -1971,6 +2708,7 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
@@ -2011,10 +2749,48 @@ where
             access_policy: self.access_policy,
             sort: self.sort,
             limit: self.limit,
+            group_level: self.group_level,
             _view: PhantomData,
         }
     }
 
+    /// Filters for entries in the view with serialized keys that begin with
+    /// `prefix`.
+    ///
+    /// This allows querying by the leading fields of a composite key by
+    /// serializing only those fields. Because variable length fields store
+    /// their lengths at the end of a composite key, only leading fields with a
+    /// fixed length can be matched this way.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use bonsaidb_core::key::KeyEncoding;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// #[derive(View, Debug, Clone)]
+    /// #[view(name = "by-rank-and-name", key = (u32, String), collection = MyCollection)]
+    /// # #[view(core = bonsaidb_core)]
+    /// struct ByRankAndName;
+    ///
+    /// let prefix = (1_u32,).as_ord_bytes().expect("u32 is always encodable");
+    /// for mapping in ByRankAndName::entries_async(&db)
+    ///     .with_key_bytes_prefix(prefix.to_vec())
+    ///     .query()
+    ///     .await?
+    /// {
+    ///     assert_eq!(mapping.key.0, 1);
+    ///     println!("{} in document {:?}", mapping.key.1, mapping.source);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub fn with_key_bytes_prefix(mut self, prefix: impl Into<Bytes>) -> Self {
+        self.key = Some(QueryKey::Prefix(prefix.into()));
+        self
+    }
+
     /// Sets the access policy for queries.
     ///
     /// ```rust
@@ -2113,6 +2889,39 @@ where
         self
     }
 
+    /// Groups the results of [`reduce_grouped()`](Self::reduce_grouped) by the
+    /// first `group_level` components of each key, rather than by the entire
+    /// key.
+    ///
+    /// Only tuple keys are made up of multiple components. All other key
+    /// types are treated as a single component, which means a `group_level`
+    /// greater than zero groups by the entire key. A `group_level` of zero
+    /// reduces all matching entries into a single group.
+    ///
+    /// The key returned for each group is the first key within the group.
+    /// Only its first `group_level` components are shared by the rest of the
+    /// group.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// let reductions = ScoresByRank::entries_async(&db)
+    ///     .group_level(0)
+    ///     .reduce_grouped()
+    ///     .await?;
+    /// assert!(reductions.len() <= 1);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub const fn group_level(mut self, group_level: usize) -> Self {
+        self.group_level = Some(group_level);
+        self
+    }
+
     /// Executes the query and retrieves the results.
     ///
     /// ```rust
@@ -2134,6 +2943,71 @@ where
             .await
     }
 
+    /// Executes the query and retrieves the results, grouped by key. Each
+    /// distinct key is returned once, in the query's sort order, along with
+    /// every mapping that was emitted with that key.
+    ///
+    /// [`limit`](Self::limit) limits the number of mappings, not the number
+    /// of keys.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// for (rank, mappings) in ScoresByRank::entries_async(&db).query_grouped().await? {
+    ///     println!("Rank {rank} has {} scores", mappings.len());
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn query_grouped(self) -> Result<GroupedMappings<V>, Error> {
+        self.connection
+            .query_grouped::<V, Key>(self.key, self.sort, self.limit, self.access_policy)
+            .await
+    }
+
+    /// Executes the query and retrieves a page of at most
+    /// [`limit`](Self::limit) results that are ordered after `after`.
+    ///
+    /// Unlike [`query()`](Self::query), the mappings within the page are
+    /// ordered by their key and then by the id of their source document. The
+    /// [`next_cursor`](ViewPage::next_cursor) of the returned page can be
+    /// passed to a subsequent query to retrieve the next page. Because the
+    /// query resumes from the position of the last mapping rather than
+    /// skipping a number of results, mappings inserted or removed between
+    /// pages don't cause results to be skipped or repeated.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page = ScoresByRank::entries_async(&db)
+    ///         .limit(50)
+    ///         .query_page(cursor)
+    ///         .await?;
+    ///     for mapping in &page.mappings {
+    ///         println!("Rank {} has a score of {:3}", mapping.key, mapping.value);
+    ///     }
+    ///     cursor = page.next_cursor;
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn query_page(self, after: Option<ViewCursor>) -> Result<ViewPage<V>, Error> {
+        self.connection
+            .query_page::<V, Key>(self.key, self.sort, self.limit, after, self.access_policy)
+            .await
+    }
+
     /// Executes the query and retrieves the results with the associated [`Document`s](crate::document::OwnedDocument).
     ///
     /// ```rust
@@ -2194,27 +3068,42 @@ where
             .await
     }
 
-    /// Executes a reduce over the results of the query
+    /// Executes the query, returning a stream of the results with their
+    /// associated [`Document`s](crate::document::OwnedDocument). The view
+    /// entries and documents are loaded in batches of `batch_size`, allowing
+    /// large result sets to be processed without loading them entirely into
+    /// memory.
+    ///
+    /// The limit set on this query is ignored.
     ///
     /// ```rust
     /// # bonsaidb_core::__doctest_prelude!();
     /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # use futures::TryStreamExt;
     /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
     /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
-    /// // score is an f32 in this example
-    /// let score = ScoresByRank::entries_async(&db).reduce().await?;
-    /// println!("Average score: {:3}", score);
+    /// let mut mappings = ScoresByRank::entries_async(&db)
+    ///     .with_key_range(42..)
+    ///     .query_stream(100);
+    /// while let Some(mapping) = mappings.try_next().await? {
+    ///     println!(
+    ///         "Mapping from #{} with rank: {} and score: {}. Document bytes: {:?}",
+    ///         mapping.document.header.id, mapping.key, mapping.value, mapping.document.contents
+    ///     );
+    /// }
     /// # Ok(())
     /// # })
     /// # }
     /// ```
-    pub async fn reduce(self) -> Result<V::Value, Error> {
+    pub fn query_stream(
+        self,
+        batch_size: u32,
+    ) -> BoxStream<'a, Result<OwnedMappedDocument<OwnedDocument, V::Key, V::Value>, Error>> {
         self.connection
-            .reduce::<V, _>(self.key, self.access_policy)
-            .await
+            .query_stream::<V, _>(self.key, self.sort, batch_size, self.access_policy)
     }
 
-    /// Executes a reduce over the results of the query, grouping by key.
+    /// Executes a reduce over the results of the query
     ///
     /// ```rust
     /// # bonsaidb_core::__doctest_prelude!();
@@ -2222,39 +3111,503 @@ where
     /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
     /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
     /// // score is an f32 in this example
-    /// for mapping in ScoresByRank::entries_async(&db).reduce_grouped().await? {
-    ///     println!(
-    ///         "Rank {} has an average score of {:3}",
-    ///         mapping.key, mapping.value
-    ///     );
-    /// }
+    /// let score = ScoresByRank::entries_async(&db).reduce().await?;
+    /// println!("Average score: {:3}", score);
     /// # Ok(())
     /// # })
     /// # }
     /// ```
-    pub async fn reduce_grouped(self) -> Result<Vec<MappedValue<V::Key, V::Value>>, Error> {
+    pub async fn reduce(self) -> Result<V::Value, Error> {
         self.connection
-            .reduce_grouped::<V, _>(self.key, self.access_policy)
+            .reduce::<V, _>(self.key, self.access_policy)
             .await
     }
 
-    /// Deletes all of the associated documents that match this view query.
+    /// Executes a reduce over the results of the query, returning whether the
+    /// view had changes that were not yet reflected in the reduced value.
     ///
     /// ```rust
     /// # bonsaidb_core::__doctest_prelude!();
     /// # use bonsaidb_core::connection::AsyncConnection;
     /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
     /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
-    /// ScoresByRank::entries_async(&db).delete_docs().await?;
+    /// let reduction = ScoresByRank::entries_async(&db)
+    ///     .with_access_policy(AccessPolicy::NoUpdate)
+    ///     .reduce_with_staleness()
+    ///     .await?;
+    /// if reduction.was_stale {
+    ///     println!("Average score (may be outdated): {:3}", reduction.value);
+    /// } else {
+    ///     println!("Average score: {:3}", reduction.value);
+    /// }
     /// # Ok(())
     /// # })
     /// # }
     /// ```
-    pub async fn delete_docs(self) -> Result<u64, Error> {
+    pub async fn reduce_with_staleness(self) -> Result<Reduction<V::Value>, Error> {
         self.connection
-            .delete_docs::<V, _>(self.key, self.access_policy)
+            .reduce_with_staleness::<V, _>(self.key, self.access_policy)
             .await
     }
+
+    /// Executes a reduce over the results of the query, combining the result
+    /// with `initial`. This allows folding newly matched entries into a
+    /// previously reduced value. If no entries match, `initial` is returned.
+    ///
+    /// The view's reduce function must be associative, and should not depend
+    /// on the keys it is given when `rereduce` is true. See
+    /// [`AsyncLowLevelConnection::reduce_with_initial()`] for more
+    /// information.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C, previous_score: f32) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// let score = ScoresByRank::entries_async(&db)
+    ///     .with_key_range(42..)
+    ///     .reduce_with_initial(previous_score)
+    ///     .await?;
+    /// println!("Combined score: {:3}", score);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn reduce_with_initial(self, initial: V::Value) -> Result<V::Value, Error> {
+        self.connection
+            .reduce_with_initial::<V, _>(self.key, initial, self.access_policy)
+            .await
+    }
+
+    /// Executes a reduce over the results of the query, grouping by key.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// // score is an f32 in this example
+    /// for mapping in ScoresByRank::entries_async(&db).reduce_grouped().await? {
+    ///     println!(
+    ///         "Rank {} has an average score of {:3}",
+    ///         mapping.key, mapping.value
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn reduce_grouped(self) -> Result<Vec<MappedValue<V::Key, V::Value>>, Error> {
+        self.connection
+            .reduce_grouped::<V, _>(self.key, self.group_level, self.access_policy)
+            .await
+    }
+
+    /// Counts the number of mappings that match this view query, without
+    /// loading the mappings themselves.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let count = ScoresByRank::entries_async(&db)
+    ///     .with_key_range(42..)
+    ///     .count()
+    ///     .await?;
+    /// println!("{count} entries have a rank of 42 or higher");
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn count(self) -> Result<u64, Error> {
+        self.connection
+            .count_view::<V, _>(self.key, self.access_policy)
+            .await
+    }
+
+    /// Counts the number of mappings for each distinct key that matches this
+    /// view query. The counts are returned in ascending key order. The view
+    /// does not need to implement a reduce function.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// for (rank, count) in ScoresByRank::entries_async(&db).facet_counts().await? {
+    ///     println!("{count} entries have a rank of {rank}");
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn facet_counts(self) -> Result<Vec<(V::Key, u64)>, Error> {
+        self.connection
+            .facet_counts::<V, _>(self.key, self.access_policy)
+            .await
+    }
+
+    /// Deletes all of the associated documents that match this view query.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// ScoresByRank::entries_async(&db).delete_docs().await?;
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn delete_docs(self) -> Result<u64, Error> {
+        self.connection
+            .delete_docs::<V, _>(self.key, self.access_policy)
+            .await
+    }
+
+    /// Deletes all of the associated documents that match this view query,
+    /// skipping any documents that are changed or deleted between querying the
+    /// view and deleting the documents. Returns the number of documents
+    /// deleted.
+    ///
+    /// [`delete_docs()`](Self::delete_docs) aborts the entire deletion when
+    /// such a conflict is encountered.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let deleted = ScoresByRank::entries_async(&db)
+    ///     .with_key(&42)
+    ///     .delete_docs_skipping_conflicts()
+    ///     .await?;
+    /// println!("Deleted {deleted} documents");
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub async fn delete_docs_skipping_conflicts(self) -> Result<u64, Error> {
+        let mappings = self
+            .connection
+            .query::<V, _>(self.key, Sort::Ascending, None, self.access_policy)
+            .await?;
+        let mut deletes = PendingDeletes::from_mappings(mappings);
+        loop {
+            if deletes.is_empty() {
+                return Ok(0);
+            }
+
+            match self
+                .connection
+                .apply_transaction(deletes.transaction::<V::Collection>())
+                .await
+            {
+                Ok(results) => return Ok(results.len() as u64),
+                Err(err) => deletes.skip_conflicting(err)?,
+            }
+        }
+    }
+}
+
+/// A batch of view queries that are executed together. Created using
+/// [`AsyncConnection::multi_query()`].
+///
+/// Networked connections send all of the queries to the server in a single
+/// request, avoiding a round trip per query.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::connection::AsyncConnection;
+/// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mut queries = db.multi_query();
+/// let low = queries.add(ScoresByRank::entries_async(&db).with_key_range(..10))?;
+/// let high = queries.add(ScoresByRank::entries_async(&db).with_key_range(90..))?;
+/// let mut results = queries.execute().await?;
+/// println!("{} low ranks", results.take(low)?.len());
+/// println!("{} high ranks", results.take(high)?.len());
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[must_use]
+pub struct AsyncMultiQuery<'a, Cn> {
+    connection: &'a Cn,
+    batch: u64,
+    queries: Vec<SerializedViewQuery>,
+}
+
+impl<'a, Cn> AsyncMultiQuery<'a, Cn>
+where
+    Cn: AsyncConnection,
+{
+    fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            batch: next_multi_query_batch(),
+            queries: Vec::new(),
+        }
+    }
+
+    /// Adds `query` to this batch. The returned [`MultiQueryIndex`] retrieves
+    /// the query's results from the [`MultiQueryResults`] returned by
+    /// [`Self::execute()`].
+    pub fn add<V, Key>(
+        &mut self,
+        query: AsyncView<'_, Cn, V, Key>,
+    ) -> Result<MultiQueryIndex<V>, Error>
+    where
+        V: schema::SerializedView,
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        self.queries.push(SerializedViewQuery::for_view::<V, Key>(
+            self.connection.schematic(),
+            query.key,
+            query.sort,
+            query.limit,
+            query.access_policy,
+        )?);
+        Ok(MultiQueryIndex::new(self.batch, self.queries.len() - 1))
+    }
+
+    /// Executes all of the queries in this batch.
+    pub async fn execute(self) -> Result<MultiQueryResults, Error> {
+        let batch = self.batch;
+        self.connection
+            .query_multiple_by_name(self.queries)
+            .await
+            .map(|results| MultiQueryResults::new(batch, results))
+    }
+}
+
+/// A builder for a [`Transaction`](transaction::Transaction) that is applied
+/// to a connection. Created using [`AsyncConnection::transaction()`].
+///
+/// Each function infers the [`CollectionName`](schema::CollectionName) from its
+/// collection type parameter and serializes the contents it is given. If
+/// serializing an operation fails, the error is returned by
+/// [`Self::apply()`]. As with any transaction, either all operations are
+/// applied or none are.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::connection::AsyncConnection;
+/// # fn test_fn<C: AsyncConnection>(db: &C) -> Result<(), Error> {
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let renamed = MyCollection::get_async(&1, db)
+///     .await?
+///     .expect("document missing");
+/// let removed = MyCollection::get_async(&2, db)
+///     .await?
+///     .expect("document missing");
+/// let results = db
+///     .transaction()
+///     .push::<MyCollection>(&MyCollection::named("new"))
+///     .update::<MyCollection>(renamed.header, &MyCollection::named("renamed"))
+///     .delete::<MyCollection, _>(&removed)
+///     .apply()
+///     .await?;
+/// assert_eq!(results.len(), 3);
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[must_use]
+pub struct AsyncTransactionBuilder<'a, Cn> {
+    connection: &'a Cn,
+    transaction: Result<transaction::Transaction, Error>,
+}
+
+impl<'a, Cn> AsyncTransactionBuilder<'a, Cn>
+where
+    Cn: AsyncConnection,
+{
+    fn new(connection: &'a Cn) -> Self {
+        Self {
+            connection,
+            transaction: Ok(transaction::Transaction::new()),
+        }
+    }
+
+    /// Adds `operation` to the transaction.
+    pub fn with(self, operation: transaction::Operation) -> Self {
+        self.with_operation(|| Ok(operation))
+    }
+
+    /// Adds an operation pushing a new document containing `contents` into
+    /// `C`. See [`Operation::push_serialized()`](transaction::Operation::push_serialized)
+    /// for how the document's id is assigned.
+    pub fn push<C: SerializedCollection>(self, contents: &C::Contents) -> Self {
+        self.with_operation(|| transaction::Operation::push_serialized::<C>(contents))
+    }
+
+    /// Adds an operation inserting a new document containing `contents` into
+    /// `C` with `id`. If a document already exists with `id`, the transaction
+    /// will fail with a conflict error.
+    pub fn insert<C, PrimaryKey>(self, id: &PrimaryKey, contents: &C::Contents) -> Self
+    where
+        C: SerializedCollection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.with_operation(|| {
+            Ok(transaction::Operation::insert(
+                C::collection_name(),
+                Some(DocumentId::new(id)?),
+                C::serialize(contents)?,
+            ))
+        })
+    }
+
+    /// Adds an operation updating the document identified by `header` in `C`
+    /// to contain `contents`. If `header`'s revision is not the document's
+    /// current revision, the transaction will fail with a conflict error.
+    pub fn update<C: SerializedCollection>(
+        self,
+        header: CollectionHeader<C::PrimaryKey>,
+        contents: &C::Contents,
+    ) -> Self {
+        self.with_operation(|| transaction::Operation::update_serialized::<C>(header, contents))
+    }
+
+    /// Adds an operation overwriting the document with `id` in `C` to contain
+    /// `contents`. If no document exists with `id`, it will be created.
+    pub fn overwrite<C, PrimaryKey>(self, id: &PrimaryKey, contents: &C::Contents) -> Self
+    where
+        C: SerializedCollection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.with_operation(|| transaction::Operation::overwrite_serialized::<C, _>(id, contents))
+    }
+
+    /// Adds an operation deleting the document identified by `doc_or_header`
+    /// from `C`. If the header's revision is not the document's current
+    /// revision, the transaction will fail with a conflict error.
+    pub fn delete<C: schema::Collection, H: HasHeader>(self, doc_or_header: &H) -> Self {
+        self.with_operation(|| {
+            Ok(transaction::Operation::delete(
+                C::collection_name(),
+                doc_or_header.header()?,
+            ))
+        })
+    }
+
+    fn with_operation(
+        mut self,
+        operation: impl FnOnce() -> Result<transaction::Operation, Error>,
+    ) -> Self {
+        if let Ok(transaction) = &mut self.transaction {
+            match operation() {
+                Ok(operation) => transaction.push(operation),
+                Err(err) => self.transaction = Err(err),
+            }
+        }
+        self
+    }
+
+    /// Applies the transaction, returning the results of the operations. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    pub async fn apply(self) -> Result<Vec<transaction::OperationResult>, Error> {
+        self.connection.apply_transaction(self.transaction?).await
+    }
+}
+
+/// Returns a transaction inserting each entry in `contents` into `C` without
+/// an id.
+fn push_all_transaction<C, Contents, B>(contents: Contents) -> transaction::Transaction
+where
+    C: schema::Collection,
+    Contents: IntoIterator<Item = B>,
+    B: Into<Bytes>,
+{
+    let collection = C::collection_name();
+    let mut transaction = transaction::Transaction::new();
+    for contents in contents {
+        transaction.push(transaction::Operation::insert(
+            collection.clone(),
+            None,
+            contents,
+        ));
+    }
+    transaction
+}
+
+/// Converts the results of a transaction created by
+/// `push_all_transaction()` into the inserted documents' headers.
+fn pushed_headers<PrimaryKey>(
+    results: Vec<transaction::OperationResult>,
+) -> Result<Vec<CollectionHeader<PrimaryKey>>, Error>
+where
+    PrimaryKey: for<'k> Key<'k>,
+{
+    results
+        .into_iter()
+        .map(|result| match result {
+            transaction::OperationResult::DocumentUpdated { header, .. } => {
+                CollectionHeader::try_from(header)
+            }
+            _ => unreachable!("invalid result from transaction"),
+        })
+        .collect()
+}
+
+/// The state of the stream returned from
+/// [`AsyncConnection::watch_transactions()`].
+struct TransactionWatchState<S> {
+    subscriber: Option<S>,
+    next_id: Option<u64>,
+    pending: VecDeque<transaction::Executed>,
+}
+
+/// The documents remaining to be deleted by
+/// `delete_docs_skipping_conflicts()`.
+struct PendingDeletes {
+    headers: BTreeMap<DocumentId, Header>,
+}
+
+impl PendingDeletes {
+    fn from_mappings<K, V>(mappings: Vec<Map<K, V>>) -> Self {
+        Self {
+            headers: mappings
+                .into_iter()
+                .map(|mapping| (mapping.source.id, mapping.source))
+                .collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    fn transaction<C: schema::Collection>(&self) -> transaction::Transaction {
+        let mut transaction = transaction::Transaction::new();
+        for header in self.headers.values() {
+            transaction.push(transaction::Operation::delete(
+                C::collection_name(),
+                header.clone(),
+            ));
+        }
+        transaction
+    }
+
+    /// Removes the document that caused `error` from the pending deletes.
+    /// Returns `error` if it was not caused by a conflicting document.
+    fn skip_conflicting(&mut self, error: Error) -> Result<(), Error> {
+        let removed = match error.operation_error() {
+            Error::DocumentConflict(_, header) => self.headers.remove(&header.id),
+            Error::DocumentNotFound(_, id) => self.headers.remove(id),
+            _ => None,
+        };
+        if removed.is_some() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
 }
 
 /// A sort order.
@@ -2281,6 +3634,16 @@ where
 
     /// Matches all entries that have keys that are included in the set provided.
     Multiple(Vec<MaybeOwned<'k, KOwned, KBorrowed>>),
+
+    /// Matches all entries whose serialized keys begin with the bytes
+    /// provided.
+    ///
+    /// Unlike [`View::with_key_prefix()`], the key type doesn't need to
+    /// implement [`IntoPrefixRange`]. This allows matching the leading fields
+    /// of a composite key by serializing only those fields. Because variable
+    /// length fields store their lengths at the end of a composite key, only
+    /// leading fields with a fixed length can be matched this way.
+    Prefix(Bytes),
 }
 
 impl<'a, KOwned, KBorrowed> QueryKey<'a, KOwned, KBorrowed>
@@ -2288,18 +3651,20 @@ where
     KBorrowed: KeyEncoding<KOwned> + PartialEq + ?Sized,
     KOwned: for<'k> Key<'k> + Borrow<KBorrowed> + PartialEq<KBorrowed>,
 {
-    /// Converts this key to a serialized format using the [`Key`] trait.
+    /// Converts this key to a serialized format using the [`Key`] trait. The
+    /// result includes a description of `KOwned`, allowing the view being
+    /// queried to reject keys encoded by a different type.
     pub fn serialized(&self) -> Result<SerializedQueryKey, Error> {
-        match self {
+        let keys = match self {
             Self::Matches(key) => key
                 .as_ord_bytes()
                 .map_err(|err| Error::other("key serialization", err))
-                .map(|v| SerializedQueryKey::Matches(Bytes::from(v.to_vec()))),
-            Self::Range(range) => Ok(SerializedQueryKey::Range(
+                .map(|v| QueryKeyBytes::Matches(Bytes::from(v.to_vec())))?,
+            Self::Range(range) => QueryKeyBytes::Range(
                 range
                     .as_ord_bytes()
                     .map_err(|err| Error::other("key serialization", err))?,
-            )),
+            ),
             Self::Multiple(keys) => {
                 let keys = keys
                     .iter()
@@ -2310,15 +3675,206 @@ where
                     })
                     .collect::<Result<Vec<_>, Error>>()?;
 
-                Ok(SerializedQueryKey::Multiple(keys))
+                QueryKeyBytes::Multiple(keys)
             }
-        }
+            Self::Prefix(prefix) => {
+                // The range ends before the first byte sequence that is
+                // ordered after every key beginning with `prefix`. If the
+                // prefix is only 0xFF bytes, no such sequence exists.
+                let end = next_byte_sequence(prefix)
+                    .map_or(Bound::Unbounded, |end| Bound::Excluded(Bytes::from(end)));
+                QueryKeyBytes::Range(Range {
+                    start: Bound::Included(prefix.clone()),
+                    end,
+                })
+            }
+        };
+        Ok(SerializedQueryKey {
+            keys,
+            description: Some(KeyDescription::for_key::<KOwned>()),
+        })
     }
 }
 
 /// A [`QueryKey`] that has had its keys serialized.
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub enum SerializedQueryKey {
+pub struct SerializedQueryKey {
+    /// The serialized keys.
+    pub keys: QueryKeyBytes,
+    /// A description of the [`Key`] type used to serialize `keys`. If present,
+    /// the view being queried verifies that its key type has the same
+    /// description before executing the query.
+    pub description: Option<KeyDescription>,
+}
+
+impl SerializedQueryKey {
+    /// Returns an error if this key was serialized by a [`Key`] type whose
+    /// description differs from `expected`, the description of `view`'s key.
+    pub fn check_description(
+        &self,
+        view: &ViewName,
+        expected: &KeyDescription,
+    ) -> Result<(), Error> {
+        match &self.description {
+            Some(received) if received != expected => Err(Error::QueryKeyMismatch {
+                view: view.clone(),
+                expected: Box::new(expected.clone()),
+                received: Box::new(received.clone()),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deserializes the bytes into `K` via the [`Key`] trait.
+    pub fn deserialized<K: for<'k> Key<'k> + PartialEq>(
+        &self,
+    ) -> Result<QueryKey<'static, K>, Error> {
+        self.keys.deserialized()
+    }
+}
+
+impl From<QueryKeyBytes> for SerializedQueryKey {
+    fn from(keys: QueryKeyBytes) -> Self {
+        Self {
+            keys,
+            description: None,
+        }
+    }
+}
+
+/// A view query whose key has been serialized. Used to execute multiple
+/// queries at once using
+/// [`LowLevelConnection::query_multiple_by_name()`].
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SerializedViewQuery {
+    /// The name of the view.
+    pub view: ViewName,
+    /// The filter for the view.
+    pub key: Option<SerializedQueryKey>,
+    /// The order for the query into the view.
+    pub order: Sort,
+    /// The maximum number of results to return.
+    pub limit: Option<u32>,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+}
+
+impl SerializedViewQuery {
+    fn for_view<V, Key>(
+        schematic: &schema::Schematic,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Self, Error>
+    where
+        V: schema::SerializedView,
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        Ok(Self {
+            view: schematic.view::<V>()?.view_name(),
+            key: key.map(|key| key.serialized()).transpose()?,
+            order,
+            limit,
+            access_policy,
+        })
+    }
+}
+
+/// The position of a mapping within a view, used to resume a paginated query
+/// after that mapping. Paginated queries order mappings by their serialized
+/// key and then by the id of their source document.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct ViewCursor {
+    /// The serialized key of the mapping.
+    pub key: Bytes,
+    /// The id of the document that emitted the mapping.
+    pub source: DocumentId,
+}
+
+impl ViewCursor {
+    /// Returns the position of `mapping`.
+    #[must_use]
+    pub fn for_mapping(mapping: &schema::view::map::Serialized) -> Self {
+        Self {
+            key: mapping.key.clone(),
+            source: mapping.source.id.clone(),
+        }
+    }
+
+    /// Returns true if `mapping` is ordered after this cursor when sorting in
+    /// `order`.
+    #[must_use]
+    pub fn is_before(&self, mapping: &schema::view::map::Serialized, order: Sort) -> bool {
+        let ordering = compare_mappings(mapping, &self.key, &self.source);
+        match order {
+            Sort::Ascending => ordering.is_gt(),
+            Sort::Descending => ordering.is_lt(),
+        }
+    }
+}
+
+/// A page of mappings returned from
+/// [`LowLevelConnection::query_page_by_name()`].
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SerializedViewPage {
+    /// The mappings in this page.
+    pub mappings: Vec<schema::view::map::Serialized>,
+    /// The cursor to continue the query from. `None` if no more mappings
+    /// match the query.
+    pub next_cursor: Option<ViewCursor>,
+}
+
+impl SerializedViewPage {
+    /// Returns a page containing at most `limit` of `mappings` that are
+    /// ordered after `after`. `mappings` may be provided in any order, and
+    /// must include every mapping that could belong in the page, plus at least
+    /// one additional mapping if more mappings match the query.
+    #[must_use]
+    pub fn paginate(
+        mut mappings: Vec<schema::view::map::Serialized>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<&ViewCursor>,
+    ) -> Self {
+        if let Some(after) = after {
+            mappings.retain(|mapping| after.is_before(mapping, order));
+        }
+        mappings.sort_by(|a, b| {
+            let ordering = compare_mappings(a, &b.key, &b.source.id);
+            match order {
+                Sort::Ascending => ordering,
+                Sort::Descending => ordering.reverse(),
+            }
+        });
+
+        let next_cursor = match limit {
+            Some(limit) if mappings.len() > limit as usize => {
+                mappings.truncate(limit as usize);
+                mappings.last().map(ViewCursor::for_mapping)
+            }
+            _ => None,
+        };
+
+        Self {
+            mappings,
+            next_cursor,
+        }
+    }
+}
+
+fn compare_mappings(
+    mapping: &schema::view::map::Serialized,
+    key: &[u8],
+    source: &DocumentId,
+) -> std::cmp::Ordering {
+    (&mapping.key[..], &mapping.source.id).cmp(&(key, source))
+}
+
+/// The serialized keys of a [`SerializedQueryKey`].
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum QueryKeyBytes {
     /// Matches all entries with the key provided.
     Matches(Bytes),
 
@@ -2329,7 +3885,7 @@ pub enum SerializedQueryKey {
     Multiple(Vec<Bytes>),
 }
 
-impl SerializedQueryKey {
+impl QueryKeyBytes {
     /// Deserializes the bytes into `K` via the [`Key`] trait.
     pub fn deserialized<K: for<'k> Key<'k> + PartialEq>(
         &self,
@@ -2489,6 +4045,43 @@ fn range_constructors() {
     );
 }
 
+#[test]
+fn prefix_query_keys() {
+    fn prefix_range(prefix: &[u8]) -> Range<Bytes> {
+        let key = QueryKey::<Vec<u8>>::Prefix(Bytes::from(prefix.to_vec()))
+            .serialized()
+            .unwrap();
+        match key.keys {
+            QueryKeyBytes::Range(range) => range,
+            other => unreachable!("unexpected keys: {other:?}"),
+        }
+    }
+
+    assert_eq!(
+        prefix_range(&[1, 2]),
+        Range {
+            start: Bound::Included(Bytes::from(vec![1, 2])),
+            end: Bound::Excluded(Bytes::from(vec![1, 3])),
+        }
+    );
+    // Incrementing 0xFF carries into the preceding byte.
+    assert_eq!(
+        prefix_range(&[1, 0xFF, 0xFF]),
+        Range {
+            start: Bound::Included(Bytes::from(vec![1, 0xFF, 0xFF])),
+            end: Bound::Excluded(Bytes::from(vec![2])),
+        }
+    );
+    // Every key beginning with only 0xFF bytes is ordered last.
+    assert_eq!(
+        prefix_range(&[0xFF, 0xFF]),
+        Range {
+            start: Bound::Included(Bytes::from(vec![0xFF, 0xFF])),
+            end: Bound::Unbounded,
+        }
+    );
+}
+
 impl<'a, TOwned, TBorrowed> RangeRef<'a, TOwned, TBorrowed>
 where
     TOwned: Borrow<TBorrowed> + PartialEq<TBorrowed>,
@@ -2953,6 +4546,16 @@ pub enum AccessPolicy {
     /// updating in the background.
     UpdateAfter,
 
+    /// If the view has finished updating within the provided duration, behave
+    /// like [`UpdateAfter`](Self::UpdateAfter). Otherwise, behave like
+    /// [`UpdateBefore`](Self::UpdateBefore).
+    ///
+    /// This bounds how out-of-date the returned results can be while still
+    /// avoiding waiting on view updates for most queries. The time of a view's
+    /// last update is tracked in memory, so the first query of a view after
+    /// the database is opened always waits for the view to be updated.
+    UpdateAfterWithTimeout(Duration),
+
     /// Returns the results, which may be out-of-date, and do not start any
     /// background jobs. This mode is useful if you're using a view as a cache
     /// and have a background process that is responsible for controlling when
@@ -2962,6 +4565,26 @@ pub enum AccessPolicy {
     NoUpdate,
 }
 
+fn verify_schema_summary<DB: Schema>(
+    available_schemas: Vec<SchemaSummary>,
+) -> Result<(), crate::Error> {
+    let local = SchemaSummary::from(&DB::schematic()?);
+    let stored = available_schemas
+        .into_iter()
+        .find(|summary| summary.name == local.name)
+        .ok_or_else(|| crate::Error::SchemaNotRegistered(local.name.clone()))?;
+    let (local_fingerprint, stored_fingerprint) = (local.fingerprint(), stored.fingerprint());
+    if local_fingerprint == stored_fingerprint {
+        Ok(())
+    } else {
+        Err(crate::Error::SchemaFingerprintMismatch {
+            schema: local.name,
+            local: local_fingerprint,
+            stored: stored_fingerprint,
+        })
+    }
+}
+
 /// Functions for interacting with a multi-database BonsaiDb instance.
 #[async_trait]
 pub trait StorageConnection: HasSession + Sized + Send + Sync {
@@ -3018,12 +4641,55 @@ pub trait StorageConnection: HasSession + Sized + Send + Sync {
     /// * [`Error::Other`]: an error occurred while deleting files.
     fn delete_database(&self, name: &str) -> Result<(), crate::Error>;
 
+    /// Renames the database named `name` to `new_name`. The database's data is
+    /// kept in place rather than being copied. Renaming a database fails while
+    /// other handles to the database are open.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::DatabaseNotFound`]: database `name` does not exist.
+    /// * [`Error::InvalidDatabaseName`]: `new_name` must begin with an
+    ///   alphanumeric character (`[a-zA-Z0-9]`), and all remaining characters
+    ///   must be alphanumeric, a period (`.`), or a hyphen (`-`).
+    /// * [`Error::DatabaseNameAlreadyTaken`]: `new_name` is already used by
+    ///   another database.
+    fn rename_database(&self, name: &str, new_name: &str) -> Result<(), crate::Error>;
+
     /// Lists the databases in this storage.
     fn list_databases(&self) -> Result<Vec<Database>, crate::Error>;
 
+    /// Returns the name of the schema of the database named `name`, or `None`
+    /// if no database with that name exists. Unlike
+    /// [`list_databases()`](Self::list_databases), only the single database is
+    /// looked up.
+    fn database_schema(&self, name: &str) -> Result<Option<SchemaName>, crate::Error>;
+
+    /// Returns true if a database named `name` exists.
+    fn database_exists(&self, name: &str) -> Result<bool, crate::Error> {
+        Ok(self.database_schema(name)?.is_some())
+    }
+
     /// Lists the [`SchemaName`]s registered with this storage.
     fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, crate::Error>;
 
+    /// Verifies that `DB` has the same structure as the schema with the same
+    /// name registered in this storage.
+    ///
+    /// The [`SchemaFingerprint`](crate::schema::SchemaFingerprint) of `DB` is
+    /// compared against the fingerprint of the registered schema. Calling this
+    /// after connecting to a server catches a client that was built against a
+    /// different version of a schema than the server is hosting.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::SchemaNotRegistered`]: no schema named
+    ///   [`DB::schema_name()`](Schema::schema_name) is registered.
+    /// * [`Error::SchemaFingerprintMismatch`]: the registered schema's
+    ///   structure differs from `DB`.
+    fn verify_schema<DB: Schema>(&self) -> Result<(), crate::Error> {
+        verify_schema_summary::<DB>(self.list_available_schemas()?)
+    }
+
     /// Creates a user.
     fn create_user(&self, username: &str) -> Result<u64, crate::Error>;
 
@@ -3208,12 +4874,55 @@ pub trait AsyncStorageConnection: HasSession + Sized + Send + Sync {
     /// * [`Error::Other`]: an error occurred while deleting files.
     async fn delete_database(&self, name: &str) -> Result<(), crate::Error>;
 
+    /// Renames the database named `name` to `new_name`. The database's data is
+    /// kept in place rather than being copied. Renaming a database fails while
+    /// other handles to the database are open.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::DatabaseNotFound`]: database `name` does not exist.
+    /// * [`Error::InvalidDatabaseName`]: `new_name` must begin with an
+    ///   alphanumeric character (`[a-zA-Z0-9]`), and all remaining characters
+    ///   must be alphanumeric, a period (`.`), or a hyphen (`-`).
+    /// * [`Error::DatabaseNameAlreadyTaken`]: `new_name` is already used by
+    ///   another database.
+    async fn rename_database(&self, name: &str, new_name: &str) -> Result<(), crate::Error>;
+
     /// Lists the databases in this storage.
     async fn list_databases(&self) -> Result<Vec<Database>, crate::Error>;
 
+    /// Returns the name of the schema of the database named `name`, or `None`
+    /// if no database with that name exists. Unlike
+    /// [`list_databases()`](Self::list_databases), only the single database is
+    /// looked up.
+    async fn database_schema(&self, name: &str) -> Result<Option<SchemaName>, crate::Error>;
+
+    /// Returns true if a database named `name` exists.
+    async fn database_exists(&self, name: &str) -> Result<bool, crate::Error> {
+        Ok(self.database_schema(name).await?.is_some())
+    }
+
     /// Lists the [`SchemaName`]s registered with this storage.
     async fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, crate::Error>;
 
+    /// Verifies that `DB` has the same structure as the schema with the same
+    /// name registered in this storage.
+    ///
+    /// The [`SchemaFingerprint`](crate::schema::SchemaFingerprint) of `DB` is
+    /// compared against the fingerprint of the registered schema. Calling this
+    /// after connecting to a server catches a client that was built against a
+    /// different version of a schema than the server is hosting.
+    ///
+    /// ## Errors
+    ///
+    /// * [`Error::SchemaNotRegistered`]: no schema named
+    ///   [`DB::schema_name()`](Schema::schema_name) is registered.
+    /// * [`Error::SchemaFingerprintMismatch`]: the registered schema's
+    ///   structure differs from `DB`.
+    async fn verify_schema<DB: Schema>(&self) -> Result<(), crate::Error> {
+        verify_schema_summary::<DB>(self.list_available_schemas().await?)
+    }
+
     /// Creates a user.
     async fn create_user(&self, username: &str) -> Result<u64, crate::Error>;
 
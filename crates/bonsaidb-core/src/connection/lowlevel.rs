@@ -1,23 +1,31 @@
 use std::borrow::Borrow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use arc_bytes::serde::Bytes;
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
 
 use super::GroupedReductions;
 use crate::connection::{
-    AccessPolicy, HasSession, QueryKey, Range, RangeRef, SerializedQueryKey, Sort, ViewMappings,
+    AccessPolicy, Bound, GroupedMappings, HasSession, QueryKey, QueryKeyBytes, Range, RangeRef,
+    SerializedQueryKey, SerializedViewPage, SerializedViewQuery, Sort, ViewCursor, ViewMappings,
+    ViewPage,
 };
 use crate::document::{
     CollectionDocument, CollectionHeader, Document, DocumentId, HasHeader, Header, OwnedDocument,
+    Revision,
 };
 use crate::key::{self, ByteSource, Key, KeyEncoding};
-use crate::schema::view::map::{MappedDocuments, MappedSerializedValue};
+use crate::schema::view::map::{
+    EmittedKey, MappedDocuments, MappedSerializedValue, OwnedMappedDocument, Reduction,
+};
 use crate::schema::view::{self};
 use crate::schema::{
     self, CollectionName, Map, MappedValue, Schematic, SerializedCollection, ViewName,
 };
-use crate::transaction::{OperationResult, Transaction};
+use crate::transaction::{AppliedTransaction, OperationResult, Transaction};
 use crate::Error;
 
 /// The low-level interface to a database's [`schema::Schema`], giving access to
@@ -140,6 +148,40 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         self.get_from_collection(DocumentId::new(id)?, &C::collection_name())
     }
 
+    /// Returns the headers of the previous revisions of the document with `id`
+    /// that are retained in [`Collection`](schema::Collection) `C`'s history,
+    /// ordered from oldest to newest. The current revision is not included.
+    ///
+    /// History is only retained for collections that return a non-zero
+    /// [`Collection::history_limit()`](schema::Collection::history_limit).
+    fn history<C, PrimaryKey>(&self, id: &PrimaryKey) -> Result<Vec<Header>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.history_from_collection(DocumentId::new(id)?, &C::collection_name())
+    }
+
+    /// Retrieves `revision` of the document with `id` from
+    /// [`Collection`](schema::Collection) `C`. The revision can either be the
+    /// current revision of the document or a revision retained in the
+    /// collection's history. If the revision can't be found, `None` is
+    /// returned.
+    ///
+    /// If `C` does not retain history and `revision` is not the document's
+    /// current revision, [`Error::HistoryNotRetained`] is returned.
+    fn get_revision<C, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        revision: Revision,
+    ) -> Result<Option<OwnedDocument>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.get_revision_from_collection(DocumentId::new(id)?, revision, &C::collection_name())
+    }
+
     /// Retrieves all documents matching `ids`. Documents that are not found are
     /// not returned, but no error will be generated.
     ///
@@ -164,6 +206,55 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         self.get_multiple_from_collection(&ids, &C::collection_name())
     }
 
+    /// Retrieves the document for each id in `ids`. The returned list
+    /// contains one entry per id in the same order as `ids`, with `None` for
+    /// each id whose document was not found.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`SerializedCollection::get_multiple_optional()`].
+    fn get_multiple_optional<'id, C, PrimaryKey, DocumentIds, I>(
+        &self,
+        ids: DocumentIds,
+    ) -> Result<Vec<Option<OwnedDocument>>, Error>
+    where
+        C: schema::Collection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + 'id + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(|id| DocumentId::new(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let documents = self.get_multiple_from_collection(&ids, &C::collection_name())?;
+        Ok(align_documents(&ids, documents))
+    }
+
+    /// Returns the ids from `ids` whose documents exist in
+    /// [`Collection`](schema::Collection) `C`, in the order they were
+    /// requested. This is more efficient than [`Self::get_multiple()`] when
+    /// only the presence of each document is needed, as no documents are
+    /// loaded.
+    fn existing_ids<'id, C, PrimaryKey, DocumentIds, I>(
+        &self,
+        ids: DocumentIds,
+    ) -> Result<Vec<C::PrimaryKey>, Error>
+    where
+        C: schema::Collection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + 'id + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(|id| DocumentId::new(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.existing_ids_in_collection(&ids, &C::collection_name())?
+            .iter()
+            .map(DocumentId::deserialize)
+            .collect()
+    }
+
     /// Retrieves all documents within the range of `ids`. To retrieve all
     /// documents, pass in `..` for `ids`.
     ///
@@ -285,18 +376,73 @@ pub trait LowLevelConnection: HasSchema + HasSession {
             limit,
             access_policy,
         )?;
-        mappings
-            .into_iter()
-            .map(|mapping| {
-                Ok(Map {
-                    key: <V::Key as key::Key>::from_ord_bytes(ByteSource::Borrowed(&mapping.key))
-                        .map_err(view::Error::key_serialization)
-                        .map_err(Error::from)?,
-                    value: V::deserialize(&mapping.value)?,
-                    source: mapping.source,
-                })
-            })
-            .collect::<Result<Vec<_>, Error>>()
+        deserialize_mappings::<V>(mappings)
+    }
+
+    /// Queries for view entries matching [`View`](schema::View), grouped by
+    /// key. Each distinct key is returned once, in `order`, along with every
+    /// mapping that was emitted with that key. `limit` limits the number of
+    /// mappings, not the number of keys.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_grouped()`](super::View::query_grouped)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from
+    /// [`SerializedView::entries()`](schema::SerializedView::entries),
+    /// [`SerializedView::entries_async()`](schema::SerializedView::entries_async),
+    /// or [`Connection::view()`](super::Connection::view).
+    fn query_grouped<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<GroupedMappings<V>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        self.query::<V, Key>(key, order, limit, access_policy)
+            .map(group_mappings::<V>)
+    }
+
+    /// Queries for a page of at most `limit` view entries matching
+    /// [`View`](schema::View) that are ordered after `after`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_page()`](super::View::query_page)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from
+    /// [`SerializedView::entries()`](schema::SerializedView::entries),
+    /// [`SerializedView::entries_async()`](schema::SerializedView::entries_async),
+    /// or [`Connection::view()`](super::Connection::view).
+    fn query_page<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<ViewPage<V>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        let page = self.query_page_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            order,
+            limit,
+            after,
+            access_policy,
+        )?;
+        Ok(ViewPage {
+            mappings: deserialize_mappings::<V>(page.mappings)?,
+            next_cursor: page.next_cursor,
+        })
     }
 
     /// Queries for view entries matching [`View`](schema::View) with their
@@ -321,20 +467,15 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
         V::Key: Borrow<Key> + PartialEq<Key>,
     {
-        // Query permission is checked by the query call
-        let results = self.query::<V, Key>(key, order, limit, access_policy)?;
-
-        // Verify that there is permission to fetch each document
-        let documents = self
-            .get_multiple::<V::Collection, _, _, _>(results.iter().map(|m| &m.source.id))?
-            .into_iter()
-            .map(|doc| (doc.header.id.clone(), doc))
-            .collect::<BTreeMap<_, _>>();
-
-        Ok(MappedDocuments {
-            mappings: results,
-            documents,
-        })
+        let view = self.schematic().view::<V>()?;
+        self.query_by_name_with_docs(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            order,
+            limit,
+            access_policy,
+        )?
+        .deserialized::<V>()
     }
 
     /// Queries for view entries matching [`View`](schema::View) with their
@@ -400,8 +541,89 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         .and_then(|value| V::deserialize(&value))
     }
 
+    /// Reduces the view entries matching [`View`](schema::View), returning
+    /// whether the view had changes that were not yet reflected in its entries
+    /// when it was read.
+    ///
+    /// When using [`AccessPolicy::NoUpdate`] or [`AccessPolicy::UpdateAfter`],
+    /// [`Reduction::was_stale`] can be used to tell whether the reduced value
+    /// may be missing recent changes.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider reducing the
+    /// view using
+    /// [`View::entries(self).reduce_with_staleness()`](super::View::reduce_with_staleness)
+    /// instead.
+    fn reduce_with_staleness<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<V::Value>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        let reduction = self.reduce_with_staleness_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            access_policy,
+        )?;
+        Ok(Reduction {
+            value: V::deserialize(&reduction.value)?,
+            was_stale: reduction.was_stale,
+        })
+    }
+
+    /// Reduces the view entries matching [`View`](schema::View), combining the
+    /// result with `initial`. If no entries match, `initial` is returned.
+    ///
+    /// The matching entries are reduced once, and the result is passed to the
+    /// view's reduce function with `rereduce` set to true, together with
+    /// `initial`. The reduce function must be associative for the result to be
+    /// equivalent to reducing all of the values that produced `initial`
+    /// together with the matching entries. Because neither value was produced
+    /// from a single key, both are given the key of the first matching entry,
+    /// so the reduce function should not depend on the keys of the mappings it
+    /// receives when rereducing.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider reducing the
+    /// view using
+    /// [`View::entries(self).reduce_with_initial()`](super::View::reduce_with_initial)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from
+    /// [`SerializedView::entries()`](schema::SerializedView::entries),
+    /// [`SerializedView::entries_async()`](schema::SerializedView::entries_async),
+    /// or [`Connection::view()`](super::Connection::view).
+    fn reduce_with_initial<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        initial: V::Value,
+        access_policy: AccessPolicy,
+    ) -> Result<V::Value, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        let view_name = view.view_name();
+        let key = key.map(|key| key.serialized()).transpose()?;
+        let mappings = self.query_by_name(
+            &view_name,
+            key.clone(),
+            Sort::Ascending,
+            Some(1),
+            access_policy,
+        )?;
+        let Some(first) = mappings.first() else {
+            return Ok(initial);
+        };
+        let reduced = self.reduce_by_name(&view_name, key, access_policy)?;
+        reduce_onto_initial::<V>(view, initial, &first.key, &reduced)
+    }
+
     /// Reduces the view entries matching [`View`](schema::View), reducing the
-    /// values by each unique key.
+    /// values by each unique key. If `group_level` is provided, the values are
+    /// instead reduced by the first `group_level` components of each key.
     ///
     /// This is a lower-level API. For better ergonomics, consider reducing the
     /// view using
@@ -414,6 +636,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
     fn reduce_grouped<V: schema::SerializedView, Key>(
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<GroupedReductions<V>, Error>
     where
@@ -424,6 +647,7 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         self.reduce_grouped_by_name(
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
+            group_level,
             access_policy,
         )?
         .into_iter()
@@ -437,6 +661,122 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         .collect::<Result<Vec<_>, Error>>()
     }
 
+    /// Counts the number of mappings matching `key` in this view, without
+    /// deserializing the values or loading the source documents.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).count()`](super::View::count())
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from
+    /// [`SerializedView::entries()`](schema::SerializedView::entries),
+    /// [`SerializedView::entries_async()`](schema::SerializedView::entries_async),
+    /// or [`Connection::view()`](super::Connection::view).
+    fn count_view<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.count_view_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            access_policy,
+        )
+    }
+
+    /// Counts the number of mappings for each distinct key matching `key` in
+    /// this view, without deserializing the values or loading the source
+    /// documents. The counts are returned in ascending key order.
+    ///
+    /// Unlike [`reduce_grouped()`](Self::reduce_grouped), the view does not
+    /// need to implement a reduce function.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).facet_counts()`](super::View::facet_counts())
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from
+    /// [`SerializedView::entries()`](schema::SerializedView::entries),
+    /// [`SerializedView::entries_async()`](schema::SerializedView::entries_async),
+    /// or [`Connection::view()`](super::Connection::view).
+    fn facet_counts<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(V::Key, u64)>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.facet_counts_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            access_policy,
+        )?
+        .into_iter()
+        .map(|(key, count)| {
+            Ok((
+                V::Key::from_ord_bytes(ByteSource::Borrowed(&key))
+                    .map_err(view::Error::key_serialization)?,
+                count,
+            ))
+        })
+        .collect()
+    }
+
+    /// Retrieves the document identified by `id` from the collection of view
+    /// `V`, along with the value of the mapping the document emitted into the
+    /// view, in a single request.
+    ///
+    /// Returns `None` if the document doesn't exist. If the document exists
+    /// but didn't emit any mappings, the document is returned with a value of
+    /// `None`. If the document emitted multiple mappings, the value of the
+    /// mapping with the lowest key is returned.
+    fn get_with_view_value<V, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<V::Value>)>, Error>
+    where
+        V: schema::SerializedView,
+        PrimaryKey: KeyEncoding<<V::Collection as schema::Collection>::PrimaryKey> + ?Sized,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.get_with_view_value_by_name(&view.view_name(), DocumentId::new(id)?, access_policy)?
+            .map(|(document, value)| {
+                let value = value.map(|value| V::deserialize(&value)).transpose()?;
+                Ok((document, value))
+            })
+            .transpose()
+    }
+
+    /// Retrieves the document identified by `id` from the collection `C`,
+    /// along with every key the document emitted into a view, in a single
+    /// request. This includes the keys emitted into views of other collections
+    /// that relate to `C`.
+    ///
+    /// Returns `None` if the document doesn't exist. Keys are grouped by view
+    /// and sorted within each view. Each view is updated according to
+    /// `access_policy` before its keys are read. Because each view has its own
+    /// key type, the keys are returned serialized.
+    fn get_with_mappings<C, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.get_with_mappings_by_name(&C::collection_name(), DocumentId::new(id)?, access_policy)
+    }
+
     /// Deletes all of the documents associated with this view.
     ///
     /// This is a lower-level API. For better ergonomics, consider querying the
@@ -467,7 +807,19 @@ pub trait LowLevelConnection: HasSchema + HasSession {
     /// Applies a [`Transaction`] to the [`schema::Schema`]. If any operation in the
     /// [`Transaction`] fails, none of the operations will be applied to the
     /// [`schema::Schema`].
-    fn apply_transaction(&self, transaction: Transaction) -> Result<Vec<OperationResult>, Error>;
+    fn apply_transaction(&self, transaction: Transaction) -> Result<Vec<OperationResult>, Error> {
+        self.apply_transaction_with_id(transaction)
+            .map(|applied| applied.results)
+    }
+
+    /// Applies a [`Transaction`] to the [`schema::Schema`], returning the id
+    /// the transaction was committed with along with the result of each
+    /// operation. If any operation in the [`Transaction`] fails, none of the
+    /// operations will be applied to the [`schema::Schema`].
+    fn apply_transaction_with_id(
+        &self,
+        transaction: Transaction,
+    ) -> Result<AppliedTransaction, Error>;
 
     /// Retrieves the document with `id` stored within the named `collection`.
     ///
@@ -496,6 +848,43 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         collection: &CollectionName,
     ) -> Result<Vec<OwnedDocument>, Error>;
 
+    /// Returns the ids from `ids` whose documents exist in the named
+    /// `collection`, in the order they were requested. Documents are not
+    /// loaded.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`LowLevelConnection::existing_ids()`].
+    fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, Error>;
+
+    /// Returns the headers of the previous revisions of the document with `id`
+    /// that are retained in the named `collection`'s history, ordered from
+    /// oldest to newest.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`LowLevelConnection::history()`].
+    fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, Error>;
+
+    /// Retrieves `revision` of the document with `id` from the named
+    /// `collection`, checking both the current document and the collection's
+    /// history.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`LowLevelConnection::get_revision()`].
+    fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, Error>;
+
     /// Retrieves all documents within the range of `ids` from the named
     /// `collection`. To retrieve all documents, pass in `..` for `ids`.
     ///
@@ -573,38 +962,98 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         access_policy: AccessPolicy,
     ) -> Result<Vec<schema::view::map::Serialized>, Error>;
 
-    /// Queries for view entries from the named `view` with their source
-    /// documents.
+    /// Queries for a page of at most `limit` view entries from the named
+    /// `view` that are ordered after `after`. Mappings are ordered by their
+    /// key and then by the id of their source document.
     ///
     /// This is a lower-level API. For better ergonomics, consider querying the
-    /// view using
-    /// [`View::entries(self).query_with_docs()`](super::View::query_with_docs)
+    /// view using [`View::entries(self).query_page()`](super::View::query_page)
     /// instead. The parameters for the query can be customized on the builder
     /// returned from [`Connection::view()`](super::Connection::view).
-    fn query_by_name_with_docs(
+    fn query_page_by_name(
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
         order: Sort,
         limit: Option<u32>,
+        after: Option<ViewCursor>,
         access_policy: AccessPolicy,
-    ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
+    ) -> Result<SerializedViewPage, Error>;
 
-    /// Reduces the view entries from the named `view`.
+    /// Executes each query in `queries`, returning the results in the same
+    /// order as the queries.
     ///
-    /// This is a lower-level API. For better ergonomics, consider reducing the
-    /// view using [`View::entries(self).reduce()`](super::View::reduce)
-    /// instead. The parameters for the query can be customized on the builder
-    /// returned from [`Connection::view()`](super::Connection::view).
-    fn reduce_by_name(
+    /// The default implementation executes each query using
+    /// [`Self::query_by_name()`]. Networked connections send all of the
+    /// queries in a single request.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying
+    /// using [`Connection::multi_query()`](super::Connection::multi_query)
+    /// instead.
+    fn query_multiple_by_name(
         &self,
-        view: &ViewName,
-        key: Option<SerializedQueryKey>,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, Error> {
+        queries
+            .into_iter()
+            .map(|query| {
+                self.query_by_name(
+                    &query.view,
+                    query.key,
+                    query.order,
+                    query.limit,
+                    query.access_policy,
+                )
+            })
+            .collect()
+    }
+
+    /// Queries for view entries from the named `view` with their source
+    /// documents.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).query_with_docs()`](super::View::query_with_docs)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn query_by_name_with_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, Error>;
+
+    /// Reduces the view entries from the named `view`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider reducing the
+    /// view using [`View::entries(self).reduce()`](super::View::reduce)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn reduce_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<u8>, Error>;
 
+    /// Reduces the view entries from the named `view`, returning whether the
+    /// view had changes that were not yet reflected in its entries when it was
+    /// read.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`reduce_with_staleness()`](Self::reduce_with_staleness) instead.
+    fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, Error>;
+
     /// Reduces the view entries from the named `view`, reducing the values by each
-    /// unique key.
+    /// unique key. If `group_level` is provided, the values are instead reduced
+    /// by the first `group_level` components of each key.
     ///
     /// This is a lower-level API. For better ergonomics, consider reducing
     /// the view using
@@ -615,9 +1064,65 @@ pub trait LowLevelConnection: HasSchema + HasSession {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, Error>;
 
+    /// Counts the number of mappings for entries that match within the named
+    /// `view`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).count()`](super::View::count())
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error>;
+
+    /// Counts the number of mappings for each distinct serialized key of the
+    /// entries that match within the named `view`.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying the
+    /// view using
+    /// [`View::entries(self).facet_counts()`](super::View::facet_counts())
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`Connection::view()`](super::Connection::view).
+    fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, Error>;
+
+    /// Retrieves the document identified by `id` from the collection of the
+    /// named `view`, along with the serialized value of the mapping the
+    /// document emitted into the view.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`get_with_view_value()`](Self::get_with_view_value) instead.
+    fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, Error>;
+
+    /// Retrieves the document identified by `id` from `collection`, along
+    /// with every serialized key the document emitted into a view.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`get_with_mappings()`](Self::get_with_mappings) instead.
+    fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, Error>;
+
     /// Deletes all source documents for entries that match within the named
     /// `view`.
     ///
@@ -760,6 +1265,42 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
             .await
     }
 
+    /// Returns the headers of the previous revisions of the document with `id`
+    /// that are retained in [`Collection`](schema::Collection) `C`'s history,
+    /// ordered from oldest to newest. The current revision is not included.
+    ///
+    /// History is only retained for collections that return a non-zero
+    /// [`Collection::history_limit()`](schema::Collection::history_limit).
+    async fn history<C, PrimaryKey>(&self, id: &PrimaryKey) -> Result<Vec<Header>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.history_from_collection(DocumentId::new(id)?, &C::collection_name())
+            .await
+    }
+
+    /// Retrieves `revision` of the document with `id` from
+    /// [`Collection`](schema::Collection) `C`. The revision can either be the
+    /// current revision of the document or a revision retained in the
+    /// collection's history. If the revision can't be found, `None` is
+    /// returned.
+    ///
+    /// If `C` does not retain history and `revision` is not the document's
+    /// current revision, [`Error::HistoryNotRetained`] is returned.
+    async fn get_revision<C, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        revision: Revision,
+    ) -> Result<Option<OwnedDocument>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.get_revision_from_collection(DocumentId::new(id)?, revision, &C::collection_name())
+            .await
+    }
+
     /// Retrieves all documents matching `ids`. Documents that are not found
     /// are not returned, but no error will be generated.
     ///
@@ -786,6 +1327,58 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
             .await
     }
 
+    /// Retrieves the document for each id in `ids`. The returned list
+    /// contains one entry per id in the same order as `ids`, with `None` for
+    /// each id whose document was not found.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider using
+    /// [`SerializedCollection::get_multiple_optional_async()`].
+    async fn get_multiple_optional<'id, C, PrimaryKey, DocumentIds, I>(
+        &self,
+        ids: DocumentIds,
+    ) -> Result<Vec<Option<OwnedDocument>>, Error>
+    where
+        C: schema::Collection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + 'id + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(DocumentId::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let documents = self
+            .get_multiple_from_collection(&ids, &C::collection_name())
+            .await?;
+        Ok(align_documents(&ids, documents))
+    }
+
+    /// Returns the ids from `ids` whose documents exist in
+    /// [`Collection`](schema::Collection) `C`, in the order they were
+    /// requested. This is more efficient than [`Self::get_multiple()`] when
+    /// only the presence of each document is needed, as no documents are
+    /// loaded.
+    async fn existing_ids<'id, C, PrimaryKey, DocumentIds, I>(
+        &self,
+        ids: DocumentIds,
+    ) -> Result<Vec<C::PrimaryKey>, Error>
+    where
+        C: schema::Collection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + 'id + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(DocumentId::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.existing_ids_in_collection(&ids, &C::collection_name())
+            .await?
+            .iter()
+            .map(DocumentId::deserialize)
+            .collect()
+    }
+
     /// Retrieves all documents within the range of `ids`. To retrieve all
     /// documents, pass in `..` for `ids`.
     ///
@@ -912,18 +1505,70 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
                 access_policy,
             )
             .await?;
-        mappings
-            .into_iter()
-            .map(|mapping| {
-                Ok(Map {
-                    key: <V::Key as key::Key>::from_ord_bytes(ByteSource::Borrowed(&mapping.key))
-                        .map_err(view::Error::key_serialization)
-                        .map_err(Error::from)?,
-                    value: V::deserialize(&mapping.value)?,
-                    source: mapping.source,
-                })
-            })
-            .collect::<Result<Vec<_>, Error>>()
+        deserialize_mappings::<V>(mappings)
+    }
+
+    /// Queries for view entries matching [`View`](schema::View), grouped by
+    /// key. Each distinct key is returned once, in `order`, along with every
+    /// mapping that was emitted with that key. `limit` limits the number of
+    /// mappings, not the number of keys.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).query_grouped()`](super::AsyncView::query_grouped)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn query_grouped<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<GroupedMappings<V>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        self.query::<V, Key>(key, order, limit, access_policy)
+            .await
+            .map(group_mappings::<V>)
+    }
+
+    /// Queries for a page of at most `limit` view entries matching
+    /// [`View`](schema::View) that are ordered after `after`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).query_page()`](super::AsyncView::query_page)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn query_page<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<ViewPage<V>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        let page = self
+            .query_page_by_name(
+                &view.view_name(),
+                key.map(|key| key.serialized()).transpose()?,
+                order,
+                limit,
+                after,
+                access_policy,
+            )
+            .await?;
+        Ok(ViewPage {
+            mappings: deserialize_mappings::<V>(page.mappings)?,
+            next_cursor: page.next_cursor,
+        })
     }
 
     /// Queries for view entries matching [`View`](schema::View) with their source documents.
@@ -944,23 +1589,16 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
         V::Key: Borrow<Key> + PartialEq<Key>,
     {
-        // Query permission is checked by the query call
-        let results = self
-            .query::<V, Key>(key, order, limit, access_policy)
-            .await?;
-
-        // Verify that there is permission to fetch each document
-        let documents = self
-            .get_multiple::<V::Collection, _, _, _>(results.iter().map(|m| &m.source.id))
-            .await?
-            .into_iter()
-            .map(|doc| (doc.header.id.clone(), doc))
-            .collect::<BTreeMap<_, _>>();
-
-        Ok(MappedDocuments {
-            mappings: results,
-            documents,
-        })
+        let view = self.schematic().view::<V>()?;
+        self.query_by_name_with_docs(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            order,
+            limit,
+            access_policy,
+        )
+        .await?
+        .deserialized::<V>()
     }
 
     /// Queries for view entries matching [`View`](schema::View) with their source documents,
@@ -999,6 +1637,51 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         })
     }
 
+    /// Queries for view entries matching [`View`](schema::View) with their
+    /// source documents, returning a stream that loads the results in batches.
+    ///
+    /// Unlike [`query_with_docs()`](Self::query_with_docs), the full result set
+    /// is never held in memory. When querying a range or a set of keys, at
+    /// most `batch_size` keys are read from the view per request. A set of
+    /// keys is queried in ascending key order. The source
+    /// documents are loaded using
+    /// [`get_multiple_from_collection()`](Self::get_multiple_from_collection)
+    /// at most `batch_size` documents at a time. Mappings whose source
+    /// document no longer exists are skipped.
+    ///
+    /// `access_policy` is only applied to the first batch. Subsequent batches
+    /// use [`AccessPolicy::NoUpdate`].
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using [`View::entries(self).query_stream()`](super::AsyncView::query_stream) instead.
+    /// The parameters for the query can be customized on the builder returned
+    /// from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    fn query_stream<'s, V: schema::SerializedView, Key>(
+        &'s self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        order: Sort,
+        batch_size: u32,
+        access_policy: AccessPolicy,
+    ) -> BoxStream<'s, Result<OwnedMappedDocument<OwnedDocument, V::Key, V::Value>, Error>>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let prepared = self
+            .schematic()
+            .view::<V>()
+            .map_err(Error::from)
+            .and_then(|view| {
+                let key = key.map(|key| key.serialized()).transpose()?;
+                Ok((view.view_name(), key))
+            });
+        let (view_name, key) = match prepared {
+            Ok(prepared) => prepared,
+            Err(err) => return stream::once(async move { Err(err) }).boxed(),
+        };
+        stream_mappings_with_docs::<Self, V>(self, view_name, key, order, batch_size, access_policy)
+    }
+
     /// Reduces the view entries matching [`View`](schema::View).
     ///
     /// This is the lower-level API. For better ergonomics, consider querying
@@ -1026,8 +1709,90 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         .and_then(|value| V::deserialize(&value))
     }
 
+    /// Reduces the view entries matching [`View`](schema::View), returning
+    /// whether the view had changes that were not yet reflected in its entries
+    /// when it was read.
+    ///
+    /// When using [`AccessPolicy::NoUpdate`] or [`AccessPolicy::UpdateAfter`],
+    /// [`Reduction::was_stale`] can be used to tell whether the reduced value
+    /// may be missing recent changes.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).reduce_with_staleness()`](super::AsyncView::reduce_with_staleness)
+    /// instead.
+    async fn reduce_with_staleness<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<V::Value>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        let reduction = self
+            .reduce_with_staleness_by_name(
+                &view.view_name(),
+                key.map(|key| key.serialized()).transpose()?,
+                access_policy,
+            )
+            .await?;
+        Ok(Reduction {
+            value: V::deserialize(&reduction.value)?,
+            was_stale: reduction.was_stale,
+        })
+    }
+
+    /// Reduces the view entries matching [`View`](schema::View), combining the
+    /// result with `initial`. If no entries match, `initial` is returned.
+    ///
+    /// The matching entries are reduced once, and the result is passed to the
+    /// view's reduce function with `rereduce` set to true, together with
+    /// `initial`. The reduce function must be associative for the result to be
+    /// equivalent to reducing all of the values that produced `initial`
+    /// together with the matching entries. Because neither value was produced
+    /// from a single key, both are given the key of the first matching entry,
+    /// so the reduce function should not depend on the keys of the mappings it
+    /// receives when rereducing.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).reduce_with_initial()`](super::AsyncView::reduce_with_initial)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn reduce_with_initial<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        initial: V::Value,
+        access_policy: AccessPolicy,
+    ) -> Result<V::Value, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view_name = self.schematic().view::<V>()?.view_name();
+        let key = key.map(|key| key.serialized()).transpose()?;
+        let mappings = self
+            .query_by_name(
+                &view_name,
+                key.clone(),
+                Sort::Ascending,
+                Some(1),
+                access_policy,
+            )
+            .await?;
+        let Some(first) = mappings.first() else {
+            return Ok(initial);
+        };
+        let reduced = self.reduce_by_name(&view_name, key, access_policy).await?;
+        let view = self.schematic().view::<V>()?;
+        reduce_onto_initial::<V>(view, initial, &first.key, &reduced)
+    }
+
     /// Reduces the view entries matching [`View`](schema::View), reducing the values by each
-    /// unique key.
+    /// unique key. If `group_level` is provided, the values are instead reduced
+    /// by the first `group_level` components of each key.
     ///
     /// This is the lower-level API. For better ergonomics, consider querying
     /// the view using
@@ -1038,6 +1803,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
     async fn reduce_grouped<V: schema::SerializedView, Key>(
         &self,
         key: Option<QueryKey<'_, V::Key, Key>>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<GroupedReductions<V>, Error>
     where
@@ -1048,6 +1814,7 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         self.reduce_grouped_by_name(
             &view.view_name(),
             key.map(|key| key.serialized()).transpose()?,
+            group_level,
             access_policy,
         )
         .await?
@@ -1062,6 +1829,120 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         .collect::<Result<Vec<_>, Error>>()
     }
 
+    /// Counts the number of mappings matching `key` in this view, without
+    /// deserializing the values or loading the source documents.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).count()`](super::AsyncView::count)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn count_view<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.count_view_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            access_policy,
+        )
+        .await
+    }
+
+    /// Counts the number of mappings for each distinct key matching `key` in
+    /// this view, without deserializing the values or loading the source
+    /// documents. The counts are returned in ascending key order.
+    ///
+    /// Unlike [`reduce_grouped()`](Self::reduce_grouped), the view does not
+    /// need to implement a reduce function.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).facet_counts()`](super::AsyncView::facet_counts)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn facet_counts<V: schema::SerializedView, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(V::Key, u64)>, Error>
+    where
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+        V::Key: Borrow<Key> + PartialEq<Key>,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.facet_counts_by_name(
+            &view.view_name(),
+            key.map(|key| key.serialized()).transpose()?,
+            access_policy,
+        )
+        .await?
+        .into_iter()
+        .map(|(key, count)| {
+            Ok((
+                V::Key::from_ord_bytes(ByteSource::Borrowed(&key))
+                    .map_err(view::Error::key_serialization)?,
+                count,
+            ))
+        })
+        .collect()
+    }
+
+    /// Retrieves the document identified by `id` from the collection of view
+    /// `V`, along with the value of the mapping the document emitted into the
+    /// view, in a single request.
+    ///
+    /// Returns `None` if the document doesn't exist. If the document exists
+    /// but didn't emit any mappings, the document is returned with a value of
+    /// `None`. If the document emitted multiple mappings, the value of the
+    /// mapping with the lowest key is returned.
+    async fn get_with_view_value<V, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<V::Value>)>, Error>
+    where
+        V: schema::SerializedView,
+        PrimaryKey: KeyEncoding<<V::Collection as schema::Collection>::PrimaryKey> + ?Sized,
+    {
+        let view = self.schematic().view::<V>()?;
+        self.get_with_view_value_by_name(&view.view_name(), DocumentId::new(id)?, access_policy)
+            .await?
+            .map(|(document, value)| {
+                let value = value.map(|value| V::deserialize(&value)).transpose()?;
+                Ok((document, value))
+            })
+            .transpose()
+    }
+
+    /// Retrieves the document identified by `id` from the collection `C`,
+    /// along with every key the document emitted into a view, in a single
+    /// request. This includes the keys emitted into views of other collections
+    /// that relate to `C`.
+    ///
+    /// Returns `None` if the document doesn't exist. Keys are grouped by view
+    /// and sorted within each view. Each view is updated according to
+    /// `access_policy` before its keys are read. Because each view has its own
+    /// key type, the keys are returned serialized.
+    async fn get_with_mappings<C, PrimaryKey>(
+        &self,
+        id: &PrimaryKey,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, Error>
+    where
+        C: schema::Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        self.get_with_mappings_by_name(&C::collection_name(), DocumentId::new(id)?, access_policy)
+            .await
+    }
+
     /// Deletes all of the documents associated with this view.
     ///
     /// This is the lower-level API. For better ergonomics, consider querying
@@ -1094,7 +1975,20 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
     async fn apply_transaction(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, Error>;
+    ) -> Result<Vec<OperationResult>, Error> {
+        self.apply_transaction_with_id(transaction)
+            .await
+            .map(|applied| applied.results)
+    }
+
+    /// Applies a [`Transaction`] to the [`Schema`](schema::Schema), returning
+    /// the id the transaction was committed with along with the result of
+    /// each operation. If any operation in the [`Transaction`] fails, none of
+    /// the operations will be applied to the [`Schema`](schema::Schema).
+    async fn apply_transaction_with_id(
+        &self,
+        transaction: Transaction,
+    ) -> Result<AppliedTransaction, Error>;
 
     /// Retrieves the document with `id` stored within the named `collection`.
     ///
@@ -1122,6 +2016,43 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         collection: &CollectionName,
     ) -> Result<Vec<OwnedDocument>, Error>;
 
+    /// Returns the ids from `ids` whose documents exist in the named
+    /// `collection`, in the order they were requested. Documents are not
+    /// loaded.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`AsyncLowLevelConnection::existing_ids()`].
+    async fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, Error>;
+
+    /// Returns the headers of the previous revisions of the document with `id`
+    /// that are retained in the named `collection`'s history, ordered from
+    /// oldest to newest.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`AsyncLowLevelConnection::history()`].
+    async fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, Error>;
+
+    /// Retrieves `revision` of the document with `id` from the named
+    /// `collection`, checking both the current document and the collection's
+    /// history.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`AsyncLowLevelConnection::get_revision()`].
+    async fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, Error>;
+
     /// Retrieves all documents within the range of `ids` from the named
     /// `collection`. To retrieve all documents, pass in `..` for `ids`.
     ///
@@ -1199,6 +2130,56 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         access_policy: AccessPolicy,
     ) -> Result<Vec<schema::view::map::Serialized>, Error>;
 
+    /// Queries for a page of at most `limit` view entries from the named
+    /// `view` that are ordered after `after`. Mappings are ordered by their
+    /// key and then by the id of their source document.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).query_page()`](super::AsyncView::query_page)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, Error>;
+
+    /// Executes each query in `queries`, returning the results in the same
+    /// order as the queries.
+    ///
+    /// The default implementation executes each query using
+    /// [`Self::query_by_name()`]. Networked connections send all of the
+    /// queries in a single request.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider querying
+    /// using
+    /// [`AsyncConnection::multi_query()`](super::AsyncConnection::multi_query)
+    /// instead.
+    async fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, Error> {
+        let mut results = Vec::with_capacity(queries.len());
+        for query in queries {
+            results.push(
+                self.query_by_name(
+                    &query.view,
+                    query.key,
+                    query.order,
+                    query.limit,
+                    query.access_policy,
+                )
+                .await?,
+            );
+        }
+        Ok(results)
+    }
+
     /// Queries for view entries from the named `view` with their source
     /// documents.
     ///
@@ -1229,8 +2210,22 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         access_policy: AccessPolicy,
     ) -> Result<Vec<u8>, Error>;
 
+    /// Reduces the view entries from the named `view`, returning whether the
+    /// view had changes that were not yet reflected in its entries when it was
+    /// read.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`reduce_with_staleness()`](Self::reduce_with_staleness) instead.
+    async fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, Error>;
+
     /// Reduces the view entries from the named `view`, reducing the values by each
-    /// unique key.
+    /// unique key. If `group_level` is provided, the values are instead reduced
+    /// by the first `group_level` components of each key.
     ///
     /// This is the lower-level API. For better ergonomics, consider querying
     /// the view using
@@ -1241,9 +2236,65 @@ pub trait AsyncLowLevelConnection: HasSchema + HasSession + Send + Sync {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, Error>;
 
+    /// Counts the number of mappings for entries that match within the named
+    /// `view`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).count()`](super::AsyncView::count)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error>;
+
+    /// Counts the number of mappings for each distinct serialized key of the
+    /// entries that match within the named `view`.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider querying
+    /// the view using
+    /// [`View::entries(self).facet_counts()`](super::AsyncView::facet_counts)
+    /// instead. The parameters for the query can be customized on the builder
+    /// returned from [`AsyncConnection::view()`](super::AsyncConnection::view).
+    async fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, Error>;
+
+    /// Retrieves the document identified by `id` from the collection of the
+    /// named `view`, along with the serialized value of the mapping the
+    /// document emitted into the view.
+    ///
+    /// This is the lower-level API. For better ergonomics, consider using
+    /// [`get_with_view_value()`](Self::get_with_view_value) instead.
+    async fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, Error>;
+
+    /// Retrieves the document identified by `id` from `collection`, along
+    /// with every serialized key the document emitted into a view.
+    ///
+    /// This is a lower-level API. For better ergonomics, consider using
+    /// [`get_with_mappings()`](Self::get_with_mappings) instead.
+    async fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, Error>;
+
     /// Deletes all source documents for entries that match within the named
     /// `view`.
     ///
@@ -1265,3 +2316,193 @@ pub trait HasSchema {
     /// Returns the schema for the database.
     fn schematic(&self) -> &Schematic;
 }
+
+struct QueryStreamState {
+    next_query: Option<SerializedQueryKey>,
+    pending: VecDeque<schema::view::map::Serialized>,
+    access_policy: AccessPolicy,
+}
+
+pub(crate) fn deserialize_mappings<V: schema::SerializedView>(
+    mappings: Vec<schema::view::map::Serialized>,
+) -> Result<ViewMappings<V>, Error> {
+    mappings
+        .into_iter()
+        .map(|mapping| {
+            Ok(Map {
+                key: <V::Key as key::Key>::from_ord_bytes(ByteSource::Borrowed(&mapping.key))
+                    .map_err(view::Error::key_serialization)
+                    .map_err(Error::from)?,
+                value: V::deserialize(&mapping.value)?,
+                source: mapping.source,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()
+}
+
+/// Rereduces `initial` with `reduced`, the reduced value of the entries
+/// matching a query whose first entry has the serialized key `key`.
+fn reduce_onto_initial<V: schema::SerializedView>(
+    view: &dyn view::Serialized,
+    initial: V::Value,
+    key: &[u8],
+    reduced: &[u8],
+) -> Result<V::Value, Error> {
+    let initial = V::serialize(&initial)?;
+    let reduced = view.reduce(&[(key, initial.as_slice()), (key, reduced)], true)?;
+    V::deserialize(&reduced)
+}
+
+fn stream_mappings_with_docs<'s, Cn, V>(
+    connection: &'s Cn,
+    view_name: ViewName,
+    key: Option<SerializedQueryKey>,
+    order: Sort,
+    batch_size: u32,
+    access_policy: AccessPolicy,
+) -> BoxStream<'s, Result<OwnedMappedDocument<OwnedDocument, V::Key, V::Value>, Error>>
+where
+    Cn: AsyncLowLevelConnection + ?Sized,
+    V: schema::SerializedView,
+{
+    let batch_size = batch_size.max(1);
+    let state = QueryStreamState {
+        next_query: Some(key.unwrap_or_else(|| {
+            SerializedQueryKey::from(QueryKeyBytes::Range(Range {
+                start: Bound::Unbounded,
+                end: Bound::Unbounded,
+            }))
+        })),
+        pending: VecDeque::new(),
+        access_policy,
+    };
+    stream::try_unfold(state, move |mut state| {
+        let view_name = view_name.clone();
+        async move {
+            while state.pending.is_empty() {
+                let Some(query) = state.next_query.take() else {
+                    return Ok(None);
+                };
+                let access_policy =
+                    std::mem::replace(&mut state.access_policy, AccessPolicy::NoUpdate);
+                // Sets of keys are queried at most `batch_size` keys at a time,
+                // in ascending order.
+                let query = if let QueryKeyBytes::Multiple(mut keys) = query.keys {
+                    keys.sort();
+                    keys.dedup();
+                    let remaining = keys.split_off(keys.len().min(batch_size as usize));
+                    if !remaining.is_empty() {
+                        state.next_query = Some(SerializedQueryKey {
+                            keys: QueryKeyBytes::Multiple(remaining),
+                            description: query.description.clone(),
+                        });
+                    }
+                    SerializedQueryKey {
+                        keys: QueryKeyBytes::Multiple(keys),
+                        description: query.description,
+                    }
+                } else {
+                    query
+                };
+                let mappings = if let QueryKeyBytes::Range(range) = &query.keys {
+                    let mappings = connection
+                        .query_by_name(
+                            &view_name,
+                            Some(query.clone()),
+                            order,
+                            Some(batch_size),
+                            access_policy,
+                        )
+                        .await?;
+                    // The limit applies to the number of keys rather than the
+                    // number of mappings. If fewer keys than requested were
+                    // returned, the range has been exhausted.
+                    let keys_returned = mappings.iter().map(|m| &m.key).dedup().count();
+                    if let Some(last_key) = mappings
+                        .last()
+                        .filter(|_| keys_returned >= batch_size as usize)
+                        .map(|m| m.key.clone())
+                    {
+                        state.next_query = Some(SerializedQueryKey {
+                            keys: QueryKeyBytes::Range(match order {
+                                Sort::Ascending => Range {
+                                    start: Bound::Excluded(last_key),
+                                    end: range.end.clone(),
+                                },
+                                Sort::Descending => Range {
+                                    start: range.start.clone(),
+                                    end: Bound::Excluded(last_key),
+                                },
+                            }),
+                            description: query.description.clone(),
+                        });
+                    }
+                    mappings
+                } else {
+                    connection
+                        .query_by_name(&view_name, Some(query), order, None, access_policy)
+                        .await?
+                };
+                state.pending.extend(mappings);
+            }
+
+            let batch_len = state.pending.len().min(batch_size as usize);
+            let batch = state.pending.drain(..batch_len).collect::<Vec<_>>();
+            let ids = batch
+                .iter()
+                .map(|mapping| mapping.source.id.clone())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let documents = connection
+                .get_multiple_from_collection(&ids, &V::Collection::collection_name())
+                .await?
+                .into_iter()
+                .map(|doc| (doc.header.id.clone(), doc))
+                .collect::<BTreeMap<_, _>>();
+
+            let mut results = Vec::with_capacity(batch.len());
+            for mapping in batch {
+                // Mappings whose source document is missing are skipped, the
+                // same as query_with_docs.
+                if let Some(document) = documents.get(&mapping.source.id) {
+                    let mapping = mapping.deserialized::<V>()?;
+                    results.push(Ok(OwnedMappedDocument {
+                        key: mapping.key,
+                        value: mapping.value,
+                        document: document.clone(),
+                    }));
+                }
+            }
+
+            Ok::<_, Error>(Some((stream::iter(results), state)))
+        }
+    })
+    .try_flatten()
+    .boxed()
+}
+
+/// Returns the document from `documents` for each id in `ids`, in the same
+/// order as `ids`.
+fn align_documents(
+    ids: &[DocumentId],
+    documents: Vec<OwnedDocument>,
+) -> Vec<Option<OwnedDocument>> {
+    let documents = documents
+        .into_iter()
+        .map(|doc| (doc.header.id.clone(), doc))
+        .collect::<BTreeMap<_, _>>();
+    ids.iter().map(|id| documents.get(id).cloned()).collect()
+}
+
+/// Groups consecutive mappings in `mappings` that share the same key.
+fn group_mappings<V: schema::View>(mappings: ViewMappings<V>) -> GroupedMappings<V> {
+    let mut grouped: GroupedMappings<V> = Vec::new();
+    for mapping in mappings {
+        match grouped.last_mut() {
+            Some((key, group)) if key == &mapping.key => group.push(mapping),
+            _ => grouped.push((mapping.key.clone(), vec![mapping])),
+        }
+    }
+    grouped
+}
@@ -0,0 +1,696 @@
+use std::time::Duration;
+
+use arc_bytes::serde::Bytes;
+
+use super::{
+    AccessPolicy, Authentication, Connection, Database, HasSchema, HasSession, IdentityReference,
+    LowLevelConnection, Range, SensitiveString, SerializedQueryKey, SerializedViewPage,
+    SerializedViewQuery, Session, Sort, StorageConnection, ViewCursor,
+};
+use crate::document::{DocumentId, Header, OwnedDocument, Revision};
+use crate::keyvalue::{Command, KeyOperation, KeyValue, Output};
+use crate::pubsub::PubSub;
+use crate::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
+use crate::schema::{self, CollectionName, Nameable, Schema, SchemaName, SchemaSummary, ViewName};
+use crate::transaction::{self, AppliedTransaction, Transaction};
+use crate::Error;
+
+/// Controls how often and how quickly [`RetryingConnection`] retries
+/// operations that fail with a [transient error](Error::is_transient).
+///
+/// The delay between attempts starts at `initial_backoff` and doubles after
+/// each failed attempt, up to `max_backoff`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[must_use]
+pub struct RetryPolicy {
+    /// The maximum number of times an operation is attempted, including the
+    /// first attempt.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sets [`Self::max_attempts`] to `max_attempts` and returns self.
+    pub const fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets [`Self::initial_backoff`] to `initial_backoff` and returns self.
+    pub const fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets [`Self::max_backoff`] to `max_backoff` and returns self.
+    pub const fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Returns the delay to wait after `failed_attempts` attempts have failed.
+    #[must_use]
+    pub fn backoff(&self, failed_attempts: u32) -> Duration {
+        let multiplier = 1_u32 << failed_attempts.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(multiplier)
+            .min(self.max_backoff)
+    }
+
+    /// Invokes `operation` until it succeeds, returns an error that is not
+    /// transient, or has been attempted [`Self::max_attempts`] times. The
+    /// current thread sleeps between attempts. If the operation was
+    /// [rate limited](Error::RateLimited), the delay is extended to the
+    /// duration requested by the server.
+    ///
+    /// `operation` must be safe to perform more than once.
+    pub fn retry<T>(&self, mut operation: impl FnMut() -> Result<T, Error>) -> Result<T, Error> {
+        let mut attempts = 1;
+        loop {
+            match operation() {
+                Err(err) if err.is_transient() && attempts < self.max_attempts => {
+                    let mut delay = self.backoff(attempts);
+                    if let Error::RateLimited { retry_after } = &err {
+                        delay = delay.max(*retry_after);
+                    }
+                    std::thread::sleep(delay);
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A connection that retries operations that fail with a [transient
+/// error](Error::is_transient), such as a disconnection from the server.
+///
+/// Only operations that are safe to perform more than once are retried:
+/// reads, compaction, and writes that have the same effect when repeated.
+/// Operations that are not idempotent, such as applying a transaction,
+/// deleting documents or databases, and publishing messages, are attempted
+/// once and any error is returned.
+///
+/// This type wraps blocking connections. Between attempts, the current thread
+/// sleeps for the delay specified by the [`RetryPolicy`].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct RetryingConnection<C> {
+    connection: C,
+    policy: RetryPolicy,
+}
+
+impl<C> RetryingConnection<C> {
+    /// Returns `connection` wrapped to retry operations using the default
+    /// [`RetryPolicy`].
+    pub fn new(connection: C) -> Self {
+        Self::with_policy(connection, RetryPolicy::default())
+    }
+
+    /// Returns `connection` wrapped to retry operations using `policy`.
+    pub const fn with_policy(connection: C, policy: RetryPolicy) -> Self {
+        Self { connection, policy }
+    }
+
+    /// Returns the policy used to retry operations.
+    #[must_use]
+    pub const fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Returns the wrapped connection.
+    #[must_use]
+    pub const fn inner(&self) -> &C {
+        &self.connection
+    }
+
+    /// Returns the wrapped connection, consuming self.
+    #[must_use]
+    pub fn into_inner(self) -> C {
+        self.connection
+    }
+
+    fn wrap<T>(&self, connection: T) -> RetryingConnection<T> {
+        RetryingConnection::with_policy(connection, self.policy)
+    }
+}
+
+impl<C: HasSession> HasSession for RetryingConnection<C> {
+    fn session(&self) -> Option<&Session> {
+        self.connection.session()
+    }
+}
+
+impl<C: HasSchema> HasSchema for RetryingConnection<C> {
+    fn schematic(&self) -> &schema::Schematic {
+        self.connection.schematic()
+    }
+}
+
+impl<C: Connection> Connection for RetryingConnection<C> {
+    type Storage = RetryingConnection<C::Storage>;
+
+    fn storage(&self) -> Self::Storage {
+        self.wrap(self.connection.storage())
+    }
+
+    fn list_executed_transactions(
+        &self,
+        starting_id: Option<u64>,
+        result_limit: Option<u32>,
+    ) -> Result<Vec<transaction::Executed>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .list_executed_transactions(starting_id, result_limit)
+        })
+    }
+
+    fn last_transaction_id(&self) -> Result<Option<u64>, Error> {
+        self.policy.retry(|| self.connection.last_transaction_id())
+    }
+
+    fn compact(&self) -> Result<(), Error> {
+        self.policy.retry(|| self.connection.compact())
+    }
+
+    fn compact_key_value_store(&self) -> Result<(), Error> {
+        self.policy
+            .retry(|| self.connection.compact_key_value_store())
+    }
+}
+
+impl<C: LowLevelConnection> LowLevelConnection for RetryingConnection<C> {
+    fn apply_transaction_with_id(
+        &self,
+        transaction: Transaction,
+    ) -> Result<AppliedTransaction, Error> {
+        self.connection.apply_transaction_with_id(transaction)
+    }
+
+    fn get_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, Error> {
+        self.policy
+            .retry(|| self.connection.get_from_collection(id.clone(), collection))
+    }
+
+    fn get_multiple_from_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<OwnedDocument>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .get_multiple_from_collection(ids, collection)
+        })
+    }
+
+    fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, Error> {
+        self.policy
+            .retry(|| self.connection.existing_ids_in_collection(ids, collection))
+    }
+
+    fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .history_from_collection(id.clone(), collection)
+        })
+    }
+
+    fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .get_revision_from_collection(id.clone(), revision, collection)
+        })
+    }
+
+    fn list_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+    ) -> Result<Vec<OwnedDocument>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .list_from_collection(ids.clone(), order, limit, collection)
+        })
+    }
+
+    fn list_headers_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        order: Sort,
+        limit: Option<u32>,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .list_headers_from_collection(ids.clone(), order, limit, collection)
+        })
+    }
+
+    fn count_from_collection(
+        &self,
+        ids: Range<DocumentId>,
+        collection: &CollectionName,
+    ) -> Result<u64, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .count_from_collection(ids.clone(), collection)
+        })
+    }
+
+    fn compact_collection_by_name(&self, collection: CollectionName) -> Result<(), Error> {
+        self.policy.retry(|| {
+            self.connection
+                .compact_collection_by_name(collection.clone())
+        })
+    }
+
+    fn query_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<schema::view::map::Serialized>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .query_by_name(view, key.clone(), order, limit, access_policy)
+        })
+    }
+
+    fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, Error> {
+        self.policy.retry(|| {
+            self.connection.query_page_by_name(
+                view,
+                key.clone(),
+                order,
+                limit,
+                after.clone(),
+                access_policy,
+            )
+        })
+    }
+
+    fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, Error> {
+        self.policy
+            .retry(|| self.connection.query_multiple_by_name(queries.clone()))
+    }
+
+    fn query_by_name_with_docs(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+    ) -> Result<schema::view::map::MappedSerializedDocuments, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .query_by_name_with_docs(view, key.clone(), order, limit, access_policy)
+        })
+    }
+
+    fn reduce_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<u8>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .reduce_by_name(view, key.clone(), access_policy)
+        })
+    }
+
+    fn reduce_grouped_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<MappedSerializedValue>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .reduce_grouped_by_name(view, key.clone(), group_level, access_policy)
+        })
+    }
+
+    fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .reduce_with_staleness_by_name(view, key.clone(), access_policy)
+        })
+    }
+
+    fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .count_view_by_name(view, key.clone(), access_policy)
+        })
+    }
+
+    fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .facet_counts_by_name(view, key.clone(), access_policy)
+        })
+    }
+
+    fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .get_with_view_value_by_name(view, id.clone(), access_policy)
+        })
+    }
+
+    fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, Error> {
+        self.policy.retry(|| {
+            self.connection
+                .get_with_mappings_by_name(collection, id.clone(), access_policy)
+        })
+    }
+
+    fn delete_docs_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, Error> {
+        self.connection
+            .delete_docs_by_name(view, key, access_policy)
+    }
+}
+
+impl<C: StorageConnection> StorageConnection for RetryingConnection<C> {
+    type Authenticated = RetryingConnection<C::Authenticated>;
+    type Database = RetryingConnection<C::Database>;
+
+    fn admin(&self) -> Self::Database {
+        self.wrap(self.connection.admin())
+    }
+
+    fn database<DB: Schema>(&self, name: &str) -> Result<Self::Database, Error> {
+        self.policy
+            .retry(|| self.connection.database::<DB>(name))
+            .map(|database| self.wrap(database))
+    }
+
+    fn create_database_with_schema(
+        &self,
+        name: &str,
+        schema: SchemaName,
+        only_if_needed: bool,
+    ) -> Result<(), Error> {
+        if only_if_needed {
+            self.policy.retry(|| {
+                self.connection
+                    .create_database_with_schema(name, schema.clone(), true)
+            })
+        } else {
+            self.connection
+                .create_database_with_schema(name, schema, false)
+        }
+    }
+
+    fn delete_database(&self, name: &str) -> Result<(), Error> {
+        self.connection.delete_database(name)
+    }
+
+    fn rename_database(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.connection.rename_database(name, new_name)
+    }
+
+    fn list_databases(&self) -> Result<Vec<Database>, Error> {
+        self.policy.retry(|| self.connection.list_databases())
+    }
+
+    fn database_schema(&self, name: &str) -> Result<Option<SchemaName>, Error> {
+        self.policy.retry(|| self.connection.database_schema(name))
+    }
+
+    fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, Error> {
+        self.policy
+            .retry(|| self.connection.list_available_schemas())
+    }
+
+    fn create_user(&self, username: &str) -> Result<u64, Error> {
+        self.connection.create_user(username)
+    }
+
+    fn delete_user<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+    ) -> Result<(), Error> {
+        self.connection.delete_user(user)
+    }
+
+    fn set_user_password<'user, U: Nameable<'user, u64> + Send + Sync>(
+        &self,
+        user: U,
+        password: SensitiveString,
+    ) -> Result<(), Error> {
+        let user = user.name()?;
+        self.policy.retry(|| {
+            self.connection
+                .set_user_password(user.clone(), password.clone())
+        })
+    }
+
+    fn authenticate(&self, authentication: Authentication) -> Result<Self::Authenticated, Error> {
+        self.policy
+            .retry(|| self.connection.authenticate(authentication.clone()))
+            .map(|authenticated| self.wrap(authenticated))
+    }
+
+    fn assume_identity(
+        &self,
+        identity: IdentityReference<'_>,
+    ) -> Result<Self::Authenticated, Error> {
+        self.policy
+            .retry(|| self.connection.assume_identity(identity.clone()))
+            .map(|authenticated| self.wrap(authenticated))
+    }
+
+    fn add_permission_group_to_user<
+        'user,
+        'group,
+        U: Nameable<'user, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        user: U,
+        permission_group: G,
+    ) -> Result<(), Error> {
+        let user = user.name()?;
+        let permission_group = permission_group.name()?;
+        self.policy.retry(|| {
+            self.connection
+                .add_permission_group_to_user(user.clone(), permission_group.clone())
+        })
+    }
+
+    fn remove_permission_group_from_user<
+        'user,
+        'group,
+        U: Nameable<'user, u64> + Send + Sync,
+        G: Nameable<'group, u64> + Send + Sync,
+    >(
+        &self,
+        user: U,
+        permission_group: G,
+    ) -> Result<(), Error> {
+        let user = user.name()?;
+        let permission_group = permission_group.name()?;
+        self.policy.retry(|| {
+            self.connection
+                .remove_permission_group_from_user(user.clone(), permission_group.clone())
+        })
+    }
+
+    fn add_role_to_user<
+        'user,
+        'role,
+        U: Nameable<'user, u64> + Send + Sync,
+        R: Nameable<'role, u64> + Send + Sync,
+    >(
+        &self,
+        user: U,
+        role: R,
+    ) -> Result<(), Error> {
+        let user = user.name()?;
+        let role = role.name()?;
+        self.policy
+            .retry(|| self.connection.add_role_to_user(user.clone(), role.clone()))
+    }
+
+    fn remove_role_from_user<
+        'user,
+        'role,
+        U: Nameable<'user, u64> + Send + Sync,
+        R: Nameable<'role, u64> + Send + Sync,
+    >(
+        &self,
+        user: U,
+        role: R,
+    ) -> Result<(), Error> {
+        let user = user.name()?;
+        let role = role.name()?;
+        self.policy.retry(|| {
+            self.connection
+                .remove_role_from_user(user.clone(), role.clone())
+        })
+    }
+}
+
+impl<C: KeyValue> KeyValue for RetryingConnection<C> {
+    fn execute_key_operation(&self, op: KeyOperation) -> Result<Output, Error> {
+        match op.command {
+            Command::Get { delete: false } | Command::List { .. } => self
+                .policy
+                .retry(|| self.connection.execute_key_operation(op.clone())),
+            _ => self.connection.execute_key_operation(op),
+        }
+    }
+}
+
+impl<C: PubSub> PubSub for RetryingConnection<C> {
+    type Subscriber = C::Subscriber;
+
+    fn create_subscriber(&self) -> Result<Self::Subscriber, Error> {
+        self.policy.retry(|| self.connection.create_subscriber())
+    }
+
+    fn publish_bytes(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error> {
+        self.connection.publish_bytes(topic, payload)
+    }
+
+    fn publish_bytes_and_flush(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<u64, Error> {
+        self.connection.publish_bytes_and_flush(topic, payload)
+    }
+
+    fn publish_bytes_retained(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error> {
+        self.connection.publish_bytes_retained(topic, payload)
+    }
+
+    fn publish_bytes_to_all(
+        &self,
+        topics: impl IntoIterator<Item = Vec<u8>> + Send,
+        payload: Vec<u8>,
+    ) -> Result<(), Error> {
+        self.connection.publish_bytes_to_all(topics, payload)
+    }
+}
+
+#[test]
+fn retry_policy_backoff() {
+    let policy = RetryPolicy::default()
+        .with_initial_backoff(Duration::from_millis(10))
+        .with_max_backoff(Duration::from_millis(50));
+    assert_eq!(policy.backoff(1), Duration::from_millis(10));
+    assert_eq!(policy.backoff(2), Duration::from_millis(20));
+    assert_eq!(policy.backoff(3), Duration::from_millis(40));
+    assert_eq!(policy.backoff(4), Duration::from_millis(50));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(50));
+}
+
+#[test]
+fn retry_policy_retries_transient_errors() {
+    let policy = RetryPolicy::default()
+        .with_max_attempts(3)
+        .with_initial_backoff(Duration::ZERO);
+
+    let mut attempts = 0;
+    let result: Result<(), Error> = policy.retry(|| {
+        attempts += 1;
+        Err(Error::Networking(crate::networking::Error::Disconnected))
+    });
+    assert!(matches!(result, Err(err) if err.is_transient()));
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let result = policy.retry(|| {
+        attempts += 1;
+        if attempts < 2 {
+            Err(Error::Networking(crate::networking::Error::RequestTimeout))
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(result.unwrap(), 2);
+
+    let mut attempts = 0;
+    let result: Result<(), Error> = policy.retry(|| {
+        attempts += 1;
+        Err(Error::CollectionNotFound)
+    });
+    assert!(matches!(result, Err(Error::CollectionNotFound)));
+    assert_eq!(attempts, 1);
+}
@@ -32,10 +32,13 @@ use crate::key::KeyEncoding;
 use crate::schema::{Collection, SerializedCollection};
 
 mod collection;
+mod diff;
 mod header;
 mod id;
 mod revision;
 pub use self::collection::{CollectionDocument, OwnedDocuments};
+pub(crate) use self::diff::contents_value;
+pub use self::diff::{ChangeKind, DocumentDiff, FieldChange, PathSegment};
 pub use self::header::{AnyHeader, CollectionHeader, Emit, HasHeader, Header};
 pub use self::id::{DocumentId, InvalidHexadecimal};
 pub use self::revision::Revision;
@@ -0,0 +1,247 @@
+use itertools::{EitherOrBoth, Itertools};
+use pot::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::document::Revision;
+
+/// The differences between the contents of two revisions of a document,
+/// returned from
+/// [`SerializedCollection::diff_revisions()`](crate::schema::SerializedCollection::diff_revisions).
+///
+/// Each revision's contents are converted to a [`Value`] before being
+/// compared, which allows any serializable type to be compared field by
+/// field:
+///
+/// - Structures and maps are compared by key. Keys only present in the newer
+///   revision are reported as [`ChangeKind::Added`], and keys only present in
+///   the older revision are reported as [`ChangeKind::Removed`]. Values
+///   present in both revisions are compared recursively.
+/// - Sequences are compared by index. If the sequences have different
+///   lengths, the extra elements are reported as added or removed.
+/// - Any other values, including values whose types differ between the two
+///   revisions, are reported as [`ChangeKind::Changed`] if they are not equal.
+///
+/// `Option` fields are serialized as the contained value or `None`, so a field
+/// changing from `None` to `Some` is reported as changed rather than added.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DocumentDiff {
+    /// The older revision that was compared.
+    pub from: Revision,
+    /// The newer revision that was compared.
+    pub to: Revision,
+    /// The changes made between the two revisions, in the order the fields
+    /// appear in the older revision followed by fields added in the newer
+    /// revision.
+    pub changes: Vec<FieldChange>,
+}
+
+impl DocumentDiff {
+    /// Compares `from_contents`, the contents of revision `from`, with
+    /// `to_contents`, the contents of revision `to`.
+    #[must_use]
+    pub fn between(
+        from: Revision,
+        from_contents: &Value<'_>,
+        to: Revision,
+        to_contents: &Value<'_>,
+    ) -> Self {
+        let mut changes = Vec::new();
+        diff_values(&mut Vec::new(), from_contents, to_contents, &mut changes);
+        Self { from, to, changes }
+    }
+
+    /// Returns true if the contents of both revisions are equal.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// A change to a single field between two revisions of a document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// The location of the field within the document's contents. An empty
+    /// path refers to the contents as a whole.
+    pub path: Vec<PathSegment>,
+    /// How the field was changed.
+    pub kind: ChangeKind,
+}
+
+/// A single step in a [`FieldChange::path`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PathSegment {
+    /// A field of a structure, or an entry of a map with a string key.
+    Field(String),
+    /// An entry of a map whose key is not a string.
+    Key(Value<'static>),
+    /// An element of a sequence.
+    Index(usize),
+}
+
+impl PathSegment {
+    fn from_key(key: &Value<'_>) -> Self {
+        match key {
+            Value::String(name) => Self::Field(name.to_string()),
+            other => Self::Key(other.clone().into_static()),
+        }
+    }
+}
+
+/// How a field was changed between two revisions of a document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// The field is only present in the newer revision.
+    Added(Value<'static>),
+    /// The field is only present in the older revision.
+    Removed(Value<'static>),
+    /// The field is present in both revisions with different values.
+    Changed {
+        /// The value in the older revision.
+        from: Value<'static>,
+        /// The value in the newer revision.
+        to: Value<'static>,
+    },
+}
+
+/// Converts `contents` into a [`Value`] that can be compared using
+/// [`DocumentDiff::between()`].
+pub(crate) fn contents_value<T: Serialize>(contents: &T) -> Result<Value<'static>, pot::Error> {
+    let bytes = pot::to_vec(contents)?;
+    Ok(pot::from_slice::<Value<'_>>(&bytes)?.into_static())
+}
+
+fn diff_values<'a>(
+    path: &mut Vec<PathSegment>,
+    from: &Value<'a>,
+    to: &Value<'a>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (from, to) {
+        (Value::Mappings(from_entries), Value::Mappings(to_entries)) => {
+            for (key, from_value) in from_entries {
+                path.push(PathSegment::from_key(key));
+                match to_entries.iter().find(|(to_key, _)| to_key == key) {
+                    Some((_, to_value)) => diff_values(path, from_value, to_value, changes),
+                    None => push_change(
+                        path,
+                        ChangeKind::Removed(from_value.clone().into_static()),
+                        changes,
+                    ),
+                }
+                path.pop();
+            }
+            for (key, to_value) in to_entries {
+                if !from_entries.iter().any(|(from_key, _)| from_key == key) {
+                    path.push(PathSegment::from_key(key));
+                    push_change(
+                        path,
+                        ChangeKind::Added(to_value.clone().into_static()),
+                        changes,
+                    );
+                    path.pop();
+                }
+            }
+        }
+        (Value::Sequence(from_items), Value::Sequence(to_items)) => {
+            for (index, items) in from_items.iter().zip_longest(to_items).enumerate() {
+                path.push(PathSegment::Index(index));
+                match items {
+                    EitherOrBoth::Both(from_item, to_item) => {
+                        diff_values(path, from_item, to_item, changes);
+                    }
+                    EitherOrBoth::Left(from_item) => push_change(
+                        path,
+                        ChangeKind::Removed(from_item.clone().into_static()),
+                        changes,
+                    ),
+                    EitherOrBoth::Right(to_item) => push_change(
+                        path,
+                        ChangeKind::Added(to_item.clone().into_static()),
+                        changes,
+                    ),
+                }
+                path.pop();
+            }
+        }
+        _ if from == to => {}
+        _ => push_change(
+            path,
+            ChangeKind::Changed {
+                from: from.clone().into_static(),
+                to: to.clone().into_static(),
+            },
+            changes,
+        ),
+    }
+}
+
+fn push_change(path: &[PathSegment], kind: ChangeKind, changes: &mut Vec<FieldChange>) {
+    changes.push(FieldChange {
+        path: path.to_vec(),
+        kind,
+    });
+}
+
+#[test]
+fn nested_changes() {
+    #[derive(Serialize)]
+    struct Inner {
+        enabled: bool,
+    }
+
+    #[derive(Serialize)]
+    struct Contents {
+        title: String,
+        tags: Vec<String>,
+        inner: Inner,
+    }
+
+    let first = contents_value(&Contents {
+        title: String::from("first"),
+        tags: vec![String::from("a"), String::from("b")],
+        inner: Inner { enabled: false },
+    })
+    .unwrap();
+    let second = contents_value(&Contents {
+        title: String::from("second"),
+        tags: vec![String::from("a")],
+        inner: Inner { enabled: true },
+    })
+    .unwrap();
+
+    let revision = Revision::new(b"first");
+    let diff = DocumentDiff::between(revision, &first, revision, &first);
+    assert!(diff.is_empty());
+
+    let next_revision = revision.next_revision(b"second").unwrap();
+    let diff = DocumentDiff::between(revision, &first, next_revision, &second);
+    assert_eq!(
+        diff.changes,
+        vec![
+            FieldChange {
+                path: vec![PathSegment::Field(String::from("title"))],
+                kind: ChangeKind::Changed {
+                    from: Value::String("first".into()),
+                    to: Value::String("second".into()),
+                },
+            },
+            FieldChange {
+                path: vec![
+                    PathSegment::Field(String::from("tags")),
+                    PathSegment::Index(1)
+                ],
+                kind: ChangeKind::Removed(Value::String("b".into())),
+            },
+            FieldChange {
+                path: vec![
+                    PathSegment::Field(String::from("inner")),
+                    PathSegment::Field(String::from("enabled")),
+                ],
+                kind: ChangeKind::Changed {
+                    from: Value::Bool(false),
+                    to: Value::Bool(true),
+                },
+            },
+        ]
+    );
+}
@@ -4,7 +4,32 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 /// Information about a `Document`'s revision history.
-#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Hash)]
+///
+/// ## Ordering
+///
+/// Revisions are ordered by [`id`](Self::id) first. Because the id is
+/// incremented each time a document's contents change, a revision with a
+/// greater id was produced by more updates than a revision with a lesser id.
+/// For two revisions of the same document, the greater revision is therefore
+/// the newer one, as long as both revisions share the same history.
+///
+/// Revisions with equal ids are ordered by their [`sha256`](Self::sha256)
+/// digests. This ordering has no temporal meaning. Two revisions with equal
+/// ids but different digests are conflicting edits of the same document, and
+/// comparing their digests only provides a deterministic way to choose
+/// between them. [`Revision::conflicts_with()`] detects this situation.
+///
+/// ```rust
+/// use bonsaidb_core::document::Revision;
+///
+/// let first = Revision::new(b"one");
+/// let second = first.next_revision(b"two").unwrap();
+/// assert!(second > first);
+///
+/// let conflicting = first.next_revision(b"three").unwrap();
+/// assert!(second.conflicts_with(&conflicting));
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Revision {
     /// The current revision id of the document. This value is sequentially incremented on each document update.
     pub id: u32,
@@ -29,6 +54,14 @@ impl Revision {
         }
     }
 
+    /// Returns true if `other` has the same [`id`](Self::id) as this revision
+    /// but different contents. This indicates that both revisions were
+    /// produced by updating the same previous revision independently.
+    #[must_use]
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.id == other.id && self.sha256 != other.sha256
+    }
+
     /// Creates the next revision in sequence with an updated digest. If the digest doesn't change, None is returned.
     ///
     /// # Panics
@@ -113,6 +146,27 @@ fn revision_tests() {
     );
 }
 
+#[test]
+fn revision_ordering_test() {
+    let first_revision = Revision::new(b"one");
+    let second_revision = first_revision.next_revision(b"two").unwrap();
+    let third_revision = second_revision.next_revision(b"one").unwrap();
+    assert!(first_revision < second_revision);
+    assert!(second_revision < third_revision);
+    // The id takes precedence over the digest.
+    assert!(first_revision < third_revision);
+    assert_eq!(first_revision.sha256, third_revision.sha256);
+    assert!(!first_revision.conflicts_with(&second_revision));
+    assert!(!first_revision.conflicts_with(&first_revision));
+
+    let conflicting_revision = first_revision.next_revision(b"three").unwrap();
+    assert!(second_revision.conflicts_with(&conflicting_revision));
+    assert_eq!(
+        second_revision.cmp(&conflicting_revision),
+        second_revision.sha256.cmp(&conflicting_revision.sha256)
+    );
+}
+
 #[test]
 fn revision_display_test() {
     let original_contents = b"one";
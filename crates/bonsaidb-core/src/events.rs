@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::connection::{
+    AsyncConnection, AsyncLowLevelConnection, Bound, Connection, LowLevelConnection, Range, Sort,
+};
+use crate::document::{CollectionDocument, DocumentId};
+use crate::schema::{IdStrategy, SerializedCollection, SerializedView, View};
+use crate::transaction::{
+    AppliedTransaction, Operation, OperationResult, ProposedChange, Transaction,
+};
+use crate::Error;
+
+/// The number of events read at a time by
+/// [`EventCollection::replay_projection()`].
+const REPLAY_BATCH_SIZE: u32 = 1_000;
+
+/// An append-only [`Collection`](crate::schema::Collection) of events that
+/// drive a projection [`View`].
+///
+/// Events are appended using [`Self::append_event()`] and should never be
+/// updated or deleted. Implement
+/// [`Collection::validate_changes()`](crate::schema::Collection::validate_changes)
+/// by calling [`Self::validate_append_only()`] to have the database reject
+/// transactions that modify existing events. The current state of the projection is read using
+/// [`Self::projection()`], which reduces [`Self::Projection`]. Projections
+/// stored outside of the database can be caught up using
+/// [`Self::replay_projection()`].
+///
+/// ## Ordering
+///
+/// Each event is appended in its own transaction and is assigned the next
+/// sequential id, so the collection must use the default
+/// [`IdStrategy::Sequential`]. Appending an event to a collection that uses
+/// another strategy returns an error. Ids are
+/// assigned while the transaction is being applied, which guarantees that an
+/// event committed by a transaction with a greater transaction id has a
+/// greater event id. Replaying events in id order therefore replays them in
+/// the order they were committed, and [`AppendedEvent::transaction_id`] can be
+/// used to locate an event in the
+/// [transaction log](crate::connection::Connection::list_executed_transactions).
+///
+/// ## Replaying
+///
+/// [`Self::replay_projection()`] reads events directly from the collection,
+/// not from [`Self::Projection`]. Replaying is not affected by the view being
+/// re-indexed, which happens automatically the first time the view is
+/// accessed after its [`version()`](crate::schema::ViewSchema::version)
+/// changes. After changing how events are projected, bump the view's version
+/// to rebuild the projection view, and replay from `None` to rebuild
+/// projections stored elsewhere.
+#[async_trait]
+pub trait EventCollection: SerializedCollection<PrimaryKey = u64> {
+    /// The view that projects the events in this collection.
+    type Projection: SerializedView<Collection = Self>;
+
+    /// Returns an error if `changes` update or delete an existing event.
+    /// Call this function from
+    /// [`Collection::validate_changes()`](crate::schema::Collection::validate_changes)
+    /// to enforce that events are only ever appended.
+    fn validate_append_only(changes: &[ProposedChange<'_>]) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        if changes.iter().all(|change| change.inserted) {
+            Ok(())
+        } else {
+            Err(Error::other(
+                Self::collection_name(),
+                "events can not be updated or deleted",
+            ))
+        }
+    }
+
+    /// Appends `event` to this collection in a new transaction.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if this collection does not use
+    /// [`IdStrategy::Sequential`].
+    fn append_event<Cn: Connection>(
+        event: &Self::Contents,
+        connection: &Cn,
+    ) -> Result<AppendedEvent, Error>
+    where
+        Self: Sized,
+    {
+        check_id_strategy::<Self>()?;
+        Transaction::from(Operation::insert_serialized::<Self>(None, event)?)
+            .apply_with_id(connection)
+            .and_then(AppendedEvent::try_from)
+    }
+
+    /// Appends `event` to this collection in a new transaction.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if this collection does not use
+    /// [`IdStrategy::Sequential`].
+    async fn append_event_async<Cn: AsyncConnection>(
+        event: &Self::Contents,
+        connection: &Cn,
+    ) -> Result<AppendedEvent, Error>
+    where
+        Self: Sized,
+    {
+        check_id_strategy::<Self>()?;
+        Transaction::from(Operation::insert_serialized::<Self>(None, event)?)
+            .apply_with_id_async(connection)
+            .await
+            .and_then(AppendedEvent::try_from)
+    }
+
+    /// Returns the reduced value of [`Self::Projection`], after updating the
+    /// view with every event committed before this call.
+    fn projection<Cn: Connection>(
+        connection: &Cn,
+    ) -> Result<<Self::Projection as View>::Value, Error>
+    where
+        Self: Sized,
+    {
+        connection.view::<Self::Projection>().reduce()
+    }
+
+    /// Returns the reduced value of [`Self::Projection`], after updating the
+    /// view with every event committed before this call.
+    async fn projection_async<Cn: AsyncConnection>(
+        connection: &Cn,
+    ) -> Result<<Self::Projection as View>::Value, Error>
+    where
+        Self: Sized,
+    {
+        connection.view::<Self::Projection>().reduce().await
+    }
+
+    /// Invokes `project` with each event whose id is greater than `after`, in
+    /// the order the events were committed. Pass `None` to replay every
+    /// event.
+    ///
+    /// Returns the id of the last event replayed, or `after` if no events were
+    /// replayed. Passing the returned id to a later call replays only the
+    /// events appended in the meantime. If `project` returns an error,
+    /// replaying stops and the error is returned.
+    fn replay_projection<Cn, F>(
+        after: Option<u64>,
+        connection: &Cn,
+        mut project: F,
+    ) -> Result<Option<u64>, Error>
+    where
+        Cn: Connection,
+        F: FnMut(CollectionDocument<Self>) -> Result<(), Error>,
+        Self: Sized,
+    {
+        let collection = Self::collection_name();
+        let mut last_event = after;
+        loop {
+            let events = connection.list_from_collection(
+                replay_range(last_event)?,
+                Sort::Ascending,
+                Some(REPLAY_BATCH_SIZE),
+                &collection,
+            )?;
+            let Some(last) = events.last() else {
+                return Ok(last_event);
+            };
+            let last_id = last.header.id.deserialize()?;
+
+            for event in &events {
+                project(CollectionDocument::try_from(event)?)?;
+            }
+            last_event = Some(last_id);
+        }
+    }
+
+    /// Invokes `project` with each event whose id is greater than `after`, in
+    /// the order the events were committed. Pass `None` to replay every
+    /// event. See [`Self::replay_projection()`] for more information.
+    async fn replay_projection_async<Cn, F>(
+        after: Option<u64>,
+        connection: &Cn,
+        mut project: F,
+    ) -> Result<Option<u64>, Error>
+    where
+        Cn: AsyncConnection,
+        F: FnMut(CollectionDocument<Self>) -> Result<(), Error> + Send,
+        Self: Sized,
+    {
+        let collection = Self::collection_name();
+        let mut last_event = after;
+        loop {
+            let events = connection
+                .list_from_collection(
+                    replay_range(last_event)?,
+                    Sort::Ascending,
+                    Some(REPLAY_BATCH_SIZE),
+                    &collection,
+                )
+                .await?;
+            let Some(last) = events.last() else {
+                return Ok(last_event);
+            };
+            let last_id = last.header.id.deserialize()?;
+
+            for event in &events {
+                project(CollectionDocument::try_from(event)?)?;
+            }
+            last_event = Some(last_id);
+        }
+    }
+}
+
+/// Returns an error if `C` does not assign ids sequentially, which would break
+/// the ordering guarantees of [`EventCollection`].
+fn check_id_strategy<C: EventCollection>() -> Result<(), Error> {
+    if C::id_strategy() == IdStrategy::Sequential {
+        Ok(())
+    } else {
+        Err(Error::other(
+            C::collection_name(),
+            "event collections must use IdStrategy::Sequential",
+        ))
+    }
+}
+
+/// Returns the range of event ids after `last_event`.
+fn replay_range(last_event: Option<u64>) -> Result<Range<DocumentId>, Error> {
+    let start = match last_event {
+        Some(id) => Bound::Excluded(DocumentId::new(&id)?),
+        None => Bound::Unbounded,
+    };
+    Ok(Range {
+        start,
+        end: Bound::Unbounded,
+    })
+}
+
+/// An event appended to an [`EventCollection`].
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct AppendedEvent {
+    /// The id of the event's document.
+    pub id: u64,
+    /// The id of the transaction that appended the event.
+    pub transaction_id: u64,
+}
+
+impl TryFrom<AppliedTransaction> for AppendedEvent {
+    type Error = Error;
+
+    fn try_from(applied: AppliedTransaction) -> Result<Self, Self::Error> {
+        match applied.results.first() {
+            Some(OperationResult::DocumentUpdated { header, .. }) => Ok(Self {
+                id: header.id.deserialize()?,
+                transaction_id: applied.id,
+            }),
+            _ => Err(Error::other(
+                "bonsaidb-core",
+                "appending an event did not insert a document",
+            )),
+        }
+    }
+}
@@ -205,6 +205,22 @@ pub trait Key<'k>: KeyEncoding<Self> + Clone + Send + Sync {
     fn next_value(&self) -> Result<Self, NextValueError> {
         Err(NextValueError::Unsupported)
     }
+
+    /// Returns the encoded bytes of the first `group_level` components of this
+    /// key. Keys that share the same group prefix are reduced together when a
+    /// view is reduced with a group level.
+    ///
+    /// Tuples return their first `group_level` fields encoded as a composite
+    /// key. All other types are treated as a single component: a
+    /// `group_level` of 0 returns an empty prefix, and any other level returns
+    /// the entire encoded value.
+    fn group_prefix(&self, group_level: usize) -> Result<Vec<u8>, Self::Error> {
+        if group_level == 0 {
+            Ok(Vec::new())
+        } else {
+            self.as_ord_bytes().map(Cow::into_owned)
+        }
+    }
 }
 
 impl<'a, 'k, K, KE> KeyEncoding<K> for &'a KE
@@ -584,7 +600,7 @@ where
     fn to_prefix_range(&'a self) -> RangeRef<'a, TOwned, Self>;
 }
 
-fn next_byte_sequence(start: &[u8]) -> Option<Vec<u8>> {
+pub(crate) fn next_byte_sequence(start: &[u8]) -> Option<Vec<u8>> {
     let mut end = start.to_vec();
     // Modify the last byte by adding one. If it would wrap, we proceed to the
     // next byte.
@@ -1221,6 +1237,16 @@ macro_rules! impl_key_for_tuple {
 
                 Ok(($($varname),+,))
             }
+
+            fn group_prefix(&self, group_level: usize) -> Result<Vec<u8>, Self::Error> {
+                let mut encoder = CompositeKeyEncoder::default();
+
+                $(if $index < group_level {
+                    encoder.encode(&self.$index)?;
+                })+
+
+                Ok(encoder.finish())
+            }
         }
 
         impl<$($generic),+> KeyEncoding<Self> for ($($generic),+,)
@@ -1722,6 +1748,35 @@ where
     }
 }
 
+#[test]
+fn group_prefix_tests() {
+    let key = (1_u32, String::from("a"), 2_u64);
+    let other = (1_u32, String::from("b"), 2_u64);
+
+    assert_eq!(key.group_prefix(0).unwrap(), other.group_prefix(0).unwrap());
+    assert_eq!(key.group_prefix(1).unwrap(), other.group_prefix(1).unwrap());
+    assert_eq!(
+        key.group_prefix(1).unwrap(),
+        (1_u32,).as_ord_bytes().unwrap().as_ref()
+    );
+    assert_ne!(key.group_prefix(2).unwrap(), other.group_prefix(2).unwrap());
+    assert_eq!(
+        key.group_prefix(3).unwrap(),
+        key.as_ord_bytes().unwrap().as_ref()
+    );
+    assert_eq!(
+        key.group_prefix(4).unwrap(),
+        key.as_ord_bytes().unwrap().as_ref()
+    );
+
+    // Non-tuple keys are a single component.
+    assert!(42_u32.group_prefix(0).unwrap().is_empty());
+    assert_eq!(
+        42_u32.group_prefix(1).unwrap(),
+        42_u32.as_ord_bytes().unwrap().as_ref()
+    );
+}
+
 #[test]
 fn composite_key_null_check_test() {
     let mut encoder = CompositeKeyEncoder::denying_null_bytes();
@@ -19,6 +19,8 @@ mod implementation {
     pub mod get;
     /// Types for executing increment/decrement operations.
     pub mod increment;
+    /// Types for executing list operations.
+    pub mod list;
     /// Types for handling key namespaces.
     pub mod namespaced;
     /// Types for executing set operations.
@@ -102,10 +104,11 @@ mod implementation {
         }
 
         /// Increments `key` by `value`. The value stored must be a `Numeric`,
-        /// otherwise an error will be returned. The result of the increment
-        /// will be the `value`'s type. For example, if the stored value is
-        /// currently a `u64`, but `value` is a `f64`, the current value will be
-        /// converted to an `f64`, and the stored value will be an `f64`.
+        /// otherwise [`Error::ValueNotNumeric`] will be returned. The result of
+        /// the increment will be the `value`'s type. For example, if the stored
+        /// value is currently a `u64`, but `value` is a `f64`, the current value
+        /// will be converted to an `f64`, and the stored value will be an
+        /// `f64`.
         fn increment_key_by<
             S: Into<String> + Send + Sync,
             V: Into<Numeric> + TryFrom<Numeric, Error = IncompatibleTypeError> + Send + Sync,
@@ -124,10 +127,11 @@ mod implementation {
         }
 
         /// Decrements `key` by `value`. The value stored must be a `Numeric`,
-        /// otherwise an error will be returned. The result of the decrement
-        /// will be the `value`'s type. For example, if the stored value is
-        /// currently a `u64`, but `value` is a `f64`, the current value will be
-        /// converted to an `f64`, and the stored value will be an `f64`.
+        /// otherwise [`Error::ValueNotNumeric`] will be returned. The result of
+        /// the decrement will be the `value`'s type. For example, if the stored
+        /// value is currently a `u64`, but `value` is a `f64`, the current value
+        /// will be converted to an `f64`, and the stored value will be an
+        /// `f64`.
         fn decrement_key_by<
             S: Into<String> + Send + Sync,
             V: Into<Numeric> + TryFrom<Numeric, Error = IncompatibleTypeError> + Send + Sync,
@@ -152,6 +156,14 @@ mod implementation {
             get::Builder::new(self, self.key_namespace().map(Into::into), key.into())
         }
 
+        /// Lists the keys stored in the current namespace. This function
+        /// returns a builder that can restrict the results to keys beginning
+        /// with a prefix. Querying the builder will execute [`Command::List`]
+        /// with the options given.
+        fn list_keys(&'_ self) -> list::Builder<'_, Self> {
+            list::Builder::new(self, self.key_namespace().map(Into::into))
+        }
+
         /// Deletes the value stored at `key`.
         fn delete_key<S: Into<String> + Send>(&'_ self, key: S) -> Result<KeyStatus, Error> {
             match self.execute_key_operation(KeyOperation {
@@ -160,7 +172,9 @@ mod implementation {
                 command: Command::Delete,
             })? {
                 Output::Status(status) => Ok(status),
-                Output::Value(_) => unreachable!("invalid output from delete operation"),
+                Output::Value(_) | Output::Keys(_) => {
+                    unreachable!("invalid output from delete operation")
+                }
             }
         }
 
@@ -253,10 +267,11 @@ mod implementation {
         }
 
         /// Increments `key` by `value`. The value stored must be a `Numeric`,
-        /// otherwise an error will be returned. The result of the increment
-        /// will be the `value`'s type. For example, if the stored value is
-        /// currently a `u64`, but `value` is a `f64`, the current value will be
-        /// converted to an `f64`, and the stored value will be an `f64`.
+        /// otherwise [`Error::ValueNotNumeric`] will be returned. The result of
+        /// the increment will be the `value`'s type. For example, if the stored
+        /// value is currently a `u64`, but `value` is a `f64`, the current value
+        /// will be converted to an `f64`, and the stored value will be an
+        /// `f64`.
         fn increment_key_by<
             S: Into<String> + Send + Sync,
             V: Into<Numeric> + TryFrom<Numeric, Error = IncompatibleTypeError> + Send + Sync,
@@ -275,10 +290,11 @@ mod implementation {
         }
 
         /// Decrements `key` by `value`. The value stored must be a `Numeric`,
-        /// otherwise an error will be returned. The result of the decrement
-        /// will be the `value`'s type. For example, if the stored value is
-        /// currently a `u64`, but `value` is a `f64`, the current value will be
-        /// converted to an `f64`, and the stored value will be an `f64`.
+        /// otherwise [`Error::ValueNotNumeric`] will be returned. The result of
+        /// the decrement will be the `value`'s type. For example, if the stored
+        /// value is currently a `u64`, but `value` is a `f64`, the current value
+        /// will be converted to an `f64`, and the stored value will be an
+        /// `f64`.
         fn decrement_key_by<
             S: Into<String> + Send + Sync,
             V: Into<Numeric> + TryFrom<Numeric, Error = IncompatibleTypeError> + Send + Sync,
@@ -303,6 +319,13 @@ mod implementation {
             get::AsyncBuilder::new(self, self.key_namespace().map(Into::into), key.into())
         }
 
+        /// Lists the keys stored in the current namespace. This function
+        /// returns a builder that is also a Future. Awaiting the builder will
+        /// execute [`Command::List`] with the options given.
+        fn list_keys(&'_ self) -> list::AsyncBuilder<'_, Self> {
+            list::AsyncBuilder::new(self, self.key_namespace().map(Into::into))
+        }
+
         /// Deletes the value stored at `key`.
         async fn delete_key<S: Into<String> + Send>(&'_ self, key: S) -> Result<KeyStatus, Error> {
             match self
@@ -314,7 +337,9 @@ mod implementation {
                 .await?
             {
                 Output::Status(status) => Ok(status),
-                Output::Value(_) => unreachable!("invalid output from delete operation"),
+                Output::Value(_) | Output::Keys(_) => {
+                    unreachable!("invalid output from delete operation")
+                }
             }
         }
 
@@ -413,6 +438,32 @@ pub enum Command {
     },
     /// Delete a key.
     Delete,
+    /// List the keys in the namespace that begin with the operation's `key`.
+    /// An empty `key` lists every key in the namespace.
+    ///
+    /// Keys are returned in ascending lexicographic order of their UTF-8
+    /// bytes.
+    List {
+        /// The maximum number of keys to return. If not specified,
+        /// [`LIST_KEYS_DEFAULT_RESULT_COUNT`](crate::limits::LIST_KEYS_DEFAULT_RESULT_COUNT)
+        /// is used. This value is capped at
+        /// [`LIST_KEYS_MAX_RESULTS`](crate::limits::LIST_KEYS_MAX_RESULTS).
+        limit: Option<u32>,
+        /// If true, each key's value is returned alongside the key.
+        include_values: bool,
+    },
+}
+
+impl Command {
+    /// Returns true if executing this command can modify the key-value store.
+    #[must_use]
+    pub const fn modifies(&self) -> bool {
+        match self {
+            Self::Set(_) | Self::Increment { .. } | Self::Decrement { .. } | Self::Delete => true,
+            Self::Get { delete } => *delete,
+            Self::List { .. } => false,
+        }
+    }
 }
 
 /// Set a key/value pair.
@@ -727,6 +778,17 @@ pub enum Output {
     Status(KeyStatus),
     /// A value was returned.
     Value(Option<Value>),
+    /// A list of keys was returned.
+    Keys(Vec<ListedKey>),
+}
+
+/// A key returned from [`Command::List`].
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ListedKey {
+    /// The key, without its namespace.
+    pub key: String,
+    /// The value stored in the key, if values were requested.
+    pub value: Option<Value>,
 }
 /// The status of an operation on a Key.
 #[derive(Copy, Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
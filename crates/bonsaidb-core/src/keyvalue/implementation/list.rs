@@ -0,0 +1,179 @@
+use futures::{Future, FutureExt};
+
+use super::{BuilderState, Command, KeyOperation, KeyValue, Output};
+use crate::keyvalue::{AsyncKeyValue, ListedKey};
+use crate::Error;
+
+/// Builder for a [`Command::List`] key-value operation.
+#[must_use = "the key-value operation is not performed until query() is called"]
+pub struct Builder<'a, KeyValue> {
+    kv: &'a KeyValue,
+    namespace: Option<String>,
+    prefix: String,
+    limit: Option<u32>,
+    include_values: bool,
+}
+
+impl<'a, K> Builder<'a, K>
+where
+    K: KeyValue,
+{
+    pub(crate) const fn new(kv: &'a K, namespace: Option<String>) -> Self {
+        Self {
+            kv,
+            namespace,
+            prefix: String::new(),
+            limit: None,
+            include_values: false,
+        }
+    }
+
+    /// Only return keys that begin with `prefix`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Return at most `limit` keys. The limit is capped at
+    /// [`LIST_KEYS_MAX_RESULTS`](crate::limits::LIST_KEYS_MAX_RESULTS).
+    pub const fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Return each key's value alongside the key.
+    pub const fn with_values(mut self) -> Self {
+        self.include_values = true;
+        self
+    }
+
+    /// Retrieves the matching keys in ascending order, using the configured
+    /// options.
+    pub fn query(self) -> Result<Vec<ListedKey>, Error> {
+        let Self {
+            kv,
+            namespace,
+            prefix,
+            limit,
+            include_values,
+        } = self;
+        let result = kv.execute_key_operation(KeyOperation {
+            namespace,
+            key: prefix,
+            command: Command::List {
+                limit,
+                include_values,
+            },
+        })?;
+        if let Output::Keys(keys) = result {
+            Ok(keys)
+        } else {
+            unreachable!("Unexpected result from list")
+        }
+    }
+}
+
+/// Builder for a [`Command::List`] key-value operation. Queries the keys when
+/// awaited.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AsyncBuilder<'a, KeyValue> {
+    state: BuilderState<'a, Options<'a, KeyValue>, Result<Vec<ListedKey>, Error>>,
+}
+
+struct Options<'a, KeyValue> {
+    kv: &'a KeyValue,
+    namespace: Option<String>,
+    prefix: String,
+    limit: Option<u32>,
+    include_values: bool,
+}
+
+impl<'a, K> AsyncBuilder<'a, K>
+where
+    K: AsyncKeyValue,
+{
+    pub(crate) const fn new(kv: &'a K, namespace: Option<String>) -> Self {
+        Self {
+            state: BuilderState::Pending(Some(Options {
+                kv,
+                namespace,
+                prefix: String::new(),
+                limit: None,
+                include_values: false,
+            })),
+        }
+    }
+
+    fn options(&mut self) -> &mut Options<'a, K> {
+        if let BuilderState::Pending(Some(options)) = &mut self.state {
+            options
+        } else {
+            unreachable!("Attempted to use after retrieving the result")
+        }
+    }
+
+    /// Only return keys that begin with `prefix`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options().prefix = prefix.into();
+        self
+    }
+
+    /// Return at most `limit` keys. The limit is capped at
+    /// [`LIST_KEYS_MAX_RESULTS`](crate::limits::LIST_KEYS_MAX_RESULTS).
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.options().limit = Some(limit);
+        self
+    }
+
+    /// Return each key's value alongside the key.
+    pub fn with_values(mut self) -> Self {
+        self.options().include_values = true;
+        self
+    }
+}
+
+impl<'a, K> Future for AsyncBuilder<'a, K>
+where
+    K: AsyncKeyValue,
+{
+    type Output = Result<Vec<ListedKey>, Error>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        match &mut self.state {
+            BuilderState::Executing(future) => future.as_mut().poll(cx),
+            BuilderState::Pending(builder) => {
+                let Options {
+                    kv,
+                    namespace,
+                    prefix,
+                    limit,
+                    include_values,
+                } = builder.take().expect("expected builder to have options");
+                let future = async move {
+                    let result = kv
+                        .execute_key_operation(KeyOperation {
+                            namespace,
+                            key: prefix,
+                            command: Command::List {
+                                limit,
+                                include_values,
+                            },
+                        })
+                        .await?;
+                    if let Output::Keys(keys) = result {
+                        Ok(keys)
+                    } else {
+                        unreachable!("Unexpected result from list")
+                    }
+                }
+                .boxed();
+
+                self.state = BuilderState::Executing(future);
+                self.poll(cx)
+            }
+        }
+    }
+}
@@ -45,21 +45,24 @@ pub mod networking;
 /// Types for Publish/Subscribe (`PubSub`) messaging.
 pub mod pubsub;
 
+/// Helpers for storing append-only events that drive projections.
+pub mod events;
+
 use std::fmt::Display;
 use std::string::FromUtf8Error;
 
-use schema::{view, CollectionName, SchemaName, ViewName};
+use schema::{view, CollectionName, SchemaFingerprint, SchemaName, ViewName};
 use serde::{Deserialize, Serialize};
 pub use {
-    actionable, arc_bytes, async_trait, circulate, num_traits, ordered_varint, transmog,
+    actionable, arc_bytes, async_trait, circulate, num_traits, ordered_varint, pot, transmog,
     transmog_pot,
 };
 
 use crate::api::ApiName;
 use crate::connection::HasSchema;
-use crate::document::{DocumentId, Header, InvalidHexadecimal};
+use crate::document::{DocumentId, Header, InvalidHexadecimal, Revision};
 use crate::key::time::TimeError;
-use crate::key::NextValueError;
+use crate::key::{KeyDescription, NextValueError};
 use crate::schema::InsertError;
 
 /// an enumeration of errors that this crate can produce
@@ -81,6 +84,20 @@ pub enum Error {
         stored_schema: SchemaName,
     },
 
+    /// The schema compiled into this program has a different structure than
+    /// the schema with the same name registered in the storage being accessed.
+    #[error("schema '{schema}' does not match the registered schema ({local} vs {stored})")]
+    SchemaFingerprintMismatch {
+        /// The name of the schema being verified.
+        schema: SchemaName,
+
+        /// The fingerprint of the schema compiled into this program.
+        local: SchemaFingerprint,
+
+        /// The fingerprint of the schema registered in the storage.
+        stored: SchemaFingerprint,
+    },
+
     /// The [`SchemaName`] returned has already been registered.
     #[error("schema '{0}' was already registered")]
     SchemaAlreadyRegistered(SchemaName),
@@ -93,6 +110,18 @@ pub enum Error {
     #[error("view '{0}' was already registered")]
     ViewAlreadyRegistered(ViewName),
 
+    /// A view declared a [related
+    /// collection](schema::ViewSchema::related_collections) that is the view's
+    /// own collection or that was declared more than once, or an eagerly
+    /// updated view declared related collections.
+    #[error("view '{view}' cannot map documents from related collection '{collection}'")]
+    InvalidRelatedCollection {
+        /// The name of the view.
+        view: ViewName,
+        /// The related collection that was rejected.
+        collection: CollectionName,
+    },
+
     /// An invalid database name was specified. See
     /// [`StorageConnection::create_database()`](connection::StorageConnection::create_database)
     /// for database name requirements.
@@ -131,6 +160,18 @@ pub enum Error {
     #[error("the requested document id {1} from collection {0} was not found")]
     DocumentNotFound(CollectionName, Box<DocumentId>),
 
+    /// A revision of a document was requested that is neither the document's
+    /// current revision nor retained in its collection's history.
+    #[error("revision {revision} of document {id} from collection {collection} was not found")]
+    RevisionNotFound {
+        /// The collection of the document.
+        collection: CollectionName,
+        /// The id of the document.
+        id: Box<DocumentId>,
+        /// The revision that was requested.
+        revision: Revision,
+    },
+
     /// A value provided as a [`DocumentId`] exceeded [`DocumentId::MAX_LENGTH`].
     #[error(
         "an value was provided for a `DocumentId` that was larger than `DocumentId::MAX_LENGTH`"
@@ -156,6 +197,57 @@ pub enum Error {
         existing_document: Box<Header>,
     },
 
+    /// An operation within a transaction containing multiple operations
+    /// failed, aborting the transaction. Transactions containing a single
+    /// operation return the operation's error directly.
+    #[error("operation {index} of the transaction failed: {error}")]
+    TransactionOperationFailed {
+        /// The index of the failing operation within
+        /// [`Transaction::operations`](transaction::Transaction::operations).
+        index: usize,
+        /// The error returned by the operation.
+        error: Box<Error>,
+    },
+
+    /// A batch of a transaction applied using
+    /// [`Transaction::apply_in_batches()`](transaction::Transaction::apply_in_batches)
+    /// failed. The batches before the failing batch remain applied.
+    #[error(
+        "transaction batch failed after {applied_operations} operations were applied: {error}"
+    )]
+    TransactionBatchFailed {
+        /// The number of operations from the start of the transaction that
+        /// were applied before the failing batch.
+        applied_operations: usize,
+        /// The error returned when applying the failing batch.
+        error: Box<Error>,
+    },
+
+    /// A view was queried using a key that was serialized by a different
+    /// [`Key`](key::Key) type than the view's key type. This can happen when a
+    /// client and server disagree on a view's definition.
+    #[error(
+        "the query key for {view} was encoded as {received:?}, but the view's key is {expected:?}"
+    )]
+    QueryKeyMismatch {
+        /// The name of the view that was queried.
+        view: ViewName,
+        /// The description of the view's key type.
+        expected: Box<KeyDescription>,
+        /// The description of the key type used to serialize the query.
+        received: Box<KeyDescription>,
+    },
+
+    /// The contents of a document being inserted or updated were rejected by
+    /// [`Collection::validate_document()`](schema::Collection::validate_document).
+    #[error("a document in {collection} failed validation: {details}")]
+    ValidationFailed {
+        /// The collection containing the document.
+        collection: CollectionName,
+        /// The reason the document was rejected.
+        details: String,
+    },
+
     /// When pushing a document, an error occurred while generating the next unique id.
     #[error("an error occurred generating a new unique id for {0}: {1}")]
     DocumentPush(CollectionName, NextValueError),
@@ -194,6 +286,52 @@ pub enum Error {
     #[error("floating point operation yielded NaN")]
     NotANumber,
 
+    /// A numeric key-value operation was attempted on a key whose stored value
+    /// is not numeric.
+    #[error("key-value operation requires a numeric value, but the stored value is not numeric")]
+    ValueNotNumeric,
+
+    /// A PubSub topic pattern was invalid. Multi-level wildcards (`#`) may
+    /// only appear as the final level of a pattern, and wildcards must occupy
+    /// an entire level.
+    #[error("invalid topic pattern: {0}")]
+    InvalidTopicPattern(String),
+
+    /// A deletion cascaded through more than
+    /// [`MAX_DELETE_CASCADE_DEPTH`](schema::MAX_DELETE_CASCADE_DEPTH) levels of
+    /// collections. The contained collection is the collection whose documents
+    /// would have been deleted next.
+    #[error("deletion cascade into '{0}' exceeded the maximum depth")]
+    DeleteCascadeTooDeep(CollectionName),
+
+    /// A previous revision of a document was requested from a collection that
+    /// does not retain history. See
+    /// [`Collection::history_limit()`](schema::Collection::history_limit).
+    #[error("collection '{0}' does not retain document history")]
+    HistoryNotRetained(CollectionName),
+
+    /// The server rejected the request because the client has exceeded its
+    /// rate limit.
+    #[error("rate limit exceeded, retry after {retry_after:?}")]
+    RateLimited {
+        /// The amount of time to wait before the request may succeed.
+        retry_after: std::time::Duration,
+    },
+
+    /// A write was attempted through a read-only connection.
+    #[error("the database is read-only")]
+    ReadOnly,
+
+    /// A message was published to a topic beginning with
+    /// [`RESERVED_TOPIC_PREFIX`](pubsub::RESERVED_TOPIC_PREFIX).
+    #[error("topics beginning with '$bonsaidb/' are reserved")]
+    ReservedTopic,
+
+    /// A [`MultiQueryIndex`](connection::MultiQueryIndex) was used with the
+    /// results of a different batch of queries.
+    #[error("the query index belongs to a different batch of queries")]
+    InvalidMultiQueryIndex,
+
     /// An error while operating with a time
     #[error("time error: {0}")]
     Time(#[from] TimeError),
@@ -218,9 +356,10 @@ impl Error {
     }
 
     /// Returns true if this error is a [`Error::UniqueKeyViolation`] from
-    /// `View`.
+    /// `View`. Errors wrapped in [`Error::TransactionOperationFailed`] are also
+    /// checked.
     pub fn is_unique_key_error<View: schema::View, C: HasSchema>(&self, connection: &C) -> bool {
-        if let Self::UniqueKeyViolation { view, .. } = self {
+        if let Self::UniqueKeyViolation { view, .. } = self.operation_error() {
             if let Ok(schema_view) = connection.schematic().view::<View>() {
                 return view == &schema_view.view_name();
             }
@@ -229,11 +368,65 @@ impl Error {
         false
     }
 
+    /// Returns the index of the transaction operation that caused this error,
+    /// if this error is a [`Error::TransactionOperationFailed`].
+    #[must_use]
+    pub const fn operation_index(&self) -> Option<usize> {
+        match self {
+            Self::TransactionOperationFailed { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Returns the error returned by the failing transaction operation if this
+    /// error is a [`Error::TransactionOperationFailed`]. Otherwise, `self` is
+    /// returned.
+    #[must_use]
+    pub fn operation_error(&self) -> &Self {
+        match self {
+            Self::TransactionOperationFailed { error, .. } => error,
+            other => other,
+        }
+    }
+
+    /// Returns this error as the error of the operation at `index` in a
+    /// transaction containing `operation_count` operations. If the transaction
+    /// contains more than one operation, the error is wrapped in
+    /// [`Error::TransactionOperationFailed`]. Otherwise, `self` is returned.
+    #[must_use]
+    pub fn for_transaction_operation(self, index: usize, operation_count: usize) -> Self {
+        if operation_count > 1 {
+            Self::TransactionOperationFailed {
+                index,
+                error: Box::new(self),
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Returns true if this error was caused by a condition that may resolve
+    /// itself, such as the connection to the server being interrupted or a
+    /// rate limit being exceeded. The operation that produced this error may
+    /// succeed if attempted again.
+    #[must_use]
+    pub const fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Networking(
+                networking::Error::ConnectTimeout
+                    | networking::Error::RequestTimeout
+                    | networking::Error::Disconnected
+            ) | Self::RateLimited { .. }
+        )
+    }
+
     /// Returns the header of the conflicting document if this error is a
-    /// [`Error::DocumentConflict`] from `Collection`.
+    /// [`Error::DocumentConflict`] from `Collection`. Errors wrapped in
+    /// [`Error::TransactionOperationFailed`] are also checked.
     #[must_use]
     pub fn conflicting_document<Collection: schema::Collection>(&self) -> Option<Header> {
-        match self {
+        match self.operation_error() {
             Self::DocumentConflict(collection, header)
                 if collection == &Collection::collection_name() =>
             {
@@ -13,6 +13,12 @@
 //! [`Connection::list_executed_transactions()`](crate::connection::Connection::list_executed_transactions),
 //! the result set will be limited to [`LIST_TRANSACTIONS_MAX_RESULTS`] entries.
 //!
+//! # Key-Value Limits
+//!
+//! When listing keys using
+//! [`KeyValue::list_keys()`](crate::keyvalue::KeyValue::list_keys), the result
+//! set will be limited to [`LIST_KEYS_MAX_RESULTS`] entries.
+//!
 //! # Document Limits
 //!
 //! ## Primary Key Limits
@@ -52,3 +58,9 @@
 pub const LIST_TRANSACTIONS_MAX_RESULTS: u32 = 1000;
 /// If no `result_limit` is specified, this value is the limit used by default.
 pub const LIST_TRANSACTIONS_DEFAULT_RESULT_COUNT: u32 = 100;
+
+/// The maximum number of results allowed to be returned from `list_keys`.
+pub const LIST_KEYS_MAX_RESULTS: u32 = 1000;
+/// If no limit is specified when listing keys, this value is the limit used by
+/// default.
+pub const LIST_KEYS_DEFAULT_RESULT_COUNT: u32 = 100;
@@ -3,16 +3,25 @@ use serde::{Deserialize, Serialize};
 
 use crate::api::{Api, ApiName};
 use crate::connection::{
-    AccessPolicy, Database, IdentityReference, Range, SerializedQueryKey, Session, SessionId, Sort,
+    AccessPolicy, Database, IdentityReference, Range, SerializedQueryKey, SerializedViewPage,
+    SerializedViewQuery, Session, SessionId, Sort, ViewCursor,
 };
-use crate::document::{DocumentId, Header, OwnedDocument};
+use crate::document::{DocumentId, Header, OwnedDocument, Revision};
 use crate::keyvalue::{KeyOperation, Output};
 use crate::schema::view::map::{self, MappedSerializedDocuments};
-use crate::schema::{CollectionName, NamedReference, Qualified, SchemaSummary, ViewName};
-use crate::transaction::{Executed, OperationResult, Transaction};
+use crate::schema::{
+    CollectionName, NamedReference, Qualified, SchemaName, SchemaSummary, ViewName,
+};
+use crate::transaction::{AppliedTransaction, Executed, Transaction};
 
 /// The current protocol version.
-pub const CURRENT_PROTOCOL_VERSION: &str = "bonsai/pre/0";
+pub const CURRENT_PROTOCOL_VERSION: &str = "bonsai/pre/1";
+
+/// The default maximum number of bytes that may be allocated while decoding a
+/// single payload, 64 megabytes. This matches the largest websocket message
+/// accepted by default, which means any request that can be received over a
+/// websocket can also be decoded.
+pub const DEFAULT_DESERIALIZATION_LIMIT: usize = 64 * 1024 * 1024;
 
 /// A payload with an associated id.
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -25,6 +34,134 @@ pub struct Payload {
     pub name: ApiName,
     /// The payload
     pub value: Result<Bytes, crate::Error>,
+    /// The compression applied to `value`, if any.
+    pub compression: Option<PayloadCompression>,
+    /// The compression the sender of a request is able to decompress. If
+    /// present, the response may be compressed using this algorithm.
+    pub accept_compression: Option<PayloadCompression>,
+}
+
+impl Payload {
+    /// Compresses `value` using `compression` if it is larger than `threshold`
+    /// bytes. Nothing is done if `compression` is not available in this build,
+    /// if `value` is an error, or if the payload is already compressed.
+    pub fn compress(
+        &mut self,
+        compression: PayloadCompression,
+        threshold: usize,
+    ) -> Result<(), crate::Error> {
+        if self.compression.is_none() && compression.is_available() {
+            if let Ok(value) = &self.value {
+                if value.len() > threshold {
+                    self.value = Ok(Bytes::from(compression.compress(value)?));
+                    self.compression = Some(compression);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decompresses `value` if it was compressed. Returns
+    /// [`Error::PayloadTooLarge`] if the decompressed value would be larger
+    /// than `limit` bytes.
+    pub fn decompress(&mut self, limit: usize) -> Result<(), crate::Error> {
+        if let Some(compression) = self.compression.take() {
+            if let Ok(value) = &self.value {
+                self.value = Ok(Bytes::from(compression.decompress(value, limit)?));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A compression algorithm that can be applied to a [`Payload`]'s value.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub enum PayloadCompression {
+    /// LZ4 compression. Requires the `transport-lz4` feature.
+    Lz4,
+    /// Zstandard compression. Requires the `transport-zstd` feature.
+    Zstd,
+}
+
+impl PayloadCompression {
+    /// Returns true if this algorithm was enabled when this crate was
+    /// compiled.
+    #[must_use]
+    pub const fn is_available(self) -> bool {
+        match self {
+            Self::Lz4 => cfg!(feature = "transport-lz4"),
+            Self::Zstd => cfg!(feature = "transport-zstd"),
+        }
+    }
+
+    /// Compresses `data` using this algorithm.
+    #[cfg_attr(
+        not(any(feature = "transport-lz4", feature = "transport-zstd")),
+        allow(unused_variables)
+    )]
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, crate::Error> {
+        match self {
+            #[cfg(feature = "transport-lz4")]
+            Self::Lz4 => Ok(lz4_flex::block::compress_prepend_size(data)),
+            #[cfg(feature = "transport-zstd")]
+            Self::Zstd => {
+                zstd::stream::encode_all(data, 0).map_err(|err| crate::Error::other("zstd", err))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(crate::Error::Networking(Error::UnsupportedCompression(
+                self,
+            ))),
+        }
+    }
+
+    /// Decompresses `data`, which was previously compressed using this
+    /// algorithm. Returns [`Error::PayloadTooLarge`] instead of allocating
+    /// more than `limit` bytes for the decompressed data.
+    #[cfg_attr(
+        not(any(feature = "transport-lz4", feature = "transport-zstd")),
+        allow(unused_variables)
+    )]
+    pub fn decompress(self, data: &[u8], limit: usize) -> Result<Vec<u8>, crate::Error> {
+        match self {
+            #[cfg(feature = "transport-lz4")]
+            Self::Lz4 => {
+                // The decompressed size is prepended as a little-endian u32.
+                let (size, compressed) = data
+                    .get(..4)
+                    .map(|size| (u32::from_le_bytes(size.try_into().unwrap()), &data[4..]))
+                    .ok_or_else(|| crate::Error::other("lz4", "missing decompressed size"))?;
+                let size = usize::try_from(size).unwrap_or(usize::MAX);
+                if size > limit {
+                    return Err(crate::Error::Networking(Error::PayloadTooLarge(limit)));
+                }
+                lz4_flex::block::decompress(compressed, size)
+                    .map_err(|err| crate::Error::other("lz4", err))
+            }
+            #[cfg(feature = "transport-zstd")]
+            Self::Zstd => {
+                use std::io::Read;
+
+                // Reading one byte beyond the limit detects oversized payloads
+                // without decompressing the remainder.
+                let mut decompressed = Vec::new();
+                zstd::stream::read::Decoder::new(data)
+                    .and_then(|decoder| {
+                        decoder
+                            .take(u64::try_from(limit).unwrap_or(u64::MAX).saturating_add(1))
+                            .read_to_end(&mut decompressed)
+                    })
+                    .map_err(|err| crate::Error::other("zstd", err))?;
+                if decompressed.len() > limit {
+                    return Err(crate::Error::Networking(Error::PayloadTooLarge(limit)));
+                }
+                Ok(decompressed)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(crate::Error::Networking(Error::UnsupportedCompression(
+                self,
+            ))),
+        }
+    }
 }
 
 /// Creates a database.
@@ -61,6 +198,24 @@ impl Api for DeleteDatabase {
     }
 }
 
+/// Renames the database named `name` to `new_name`.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RenameDatabase {
+    /// The name of the database to rename.
+    pub name: String,
+    /// The new name of the database.
+    pub new_name: String,
+}
+
+impl Api for RenameDatabase {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "RenameDatabase")
+    }
+}
+
 /// Lists all databases.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct ListDatabases;
@@ -74,6 +229,22 @@ impl Api for ListDatabases {
     }
 }
 
+/// Looks up the schema of a single database.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct DatabaseSchema {
+    /// The name of the database.
+    pub name: String,
+}
+
+impl Api for DatabaseSchema {
+    type Error = crate::Error;
+    type Response = Option<SchemaName>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "DatabaseSchema")
+    }
+}
+
 /// Lists available schemas.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct ListAvailableSchemas;
@@ -267,6 +438,69 @@ impl Api for GetMultiple {
     }
 }
 
+/// Checks which documents exist.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ExistingIds {
+    /// The name of the database.
+    pub database: String,
+    /// The collection of the documents.
+    pub collection: CollectionName,
+    /// The ids of the documents to check.
+    pub ids: Vec<DocumentId>,
+}
+
+impl Api for ExistingIds {
+    type Error = crate::Error;
+    type Response = Vec<DocumentId>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ExistingIds")
+    }
+}
+
+/// Lists the previous revisions of a document retained in its collection's
+/// history.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct History {
+    /// The name of the database.
+    pub database: String,
+    /// The collection of the document.
+    pub collection: CollectionName,
+    /// The id of the document.
+    pub id: DocumentId,
+}
+
+impl Api for History {
+    type Error = crate::Error;
+    type Response = Vec<Header>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "History")
+    }
+}
+
+/// Retrieve a specific revision of a document.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct GetRevision {
+    /// The name of the database.
+    pub database: String,
+    /// The collection of the document.
+    pub collection: CollectionName,
+    /// The id of the document.
+    pub id: DocumentId,
+    /// The revision to retrieve.
+    pub revision: Revision,
+}
+
+impl Api for GetRevision {
+    type Error = crate::Error;
+    type Response = Option<OwnedDocument>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "GetRevision")
+    }
+}
+
 /// Retrieve multiple documents.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct List {
@@ -350,6 +584,24 @@ impl Api for Query {
     }
 }
 
+/// Queries multiple views in a single request.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct QueryMultiple {
+    /// The name of the database.
+    pub database: String,
+    /// The queries to execute.
+    pub queries: Vec<SerializedViewQuery>,
+}
+
+impl Api for QueryMultiple {
+    type Error = crate::Error;
+    type Response = Vec<Vec<map::Serialized>>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "QueryMultiple")
+    }
+}
+
 /// Queries a view with the associated documents.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct QueryWithDocs(pub Query);
@@ -363,6 +615,25 @@ impl Api for QueryWithDocs {
     }
 }
 
+/// Queries a page of a view, resuming after a cursor.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct QueryPage {
+    /// The query to execute. The query's limit is the maximum number of
+    /// mappings in the page.
+    pub query: Query,
+    /// The position of the last mapping of the previous page.
+    pub after: Option<ViewCursor>,
+}
+
+impl Api for QueryPage {
+    type Error = crate::Error;
+    type Response = SerializedViewPage;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "QueryPage")
+    }
+}
+
 /// Reduces a view.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct Reduce {
@@ -387,7 +658,13 @@ impl Api for Reduce {
 
 /// Reduces a view, grouping the reduced values by key.
 #[derive(Clone, Deserialize, Serialize, Debug)]
-pub struct ReduceGrouped(pub Reduce);
+pub struct ReduceGrouped {
+    /// The view and query to reduce.
+    pub reduce: Reduce,
+    /// The number of key components to group by. `None` groups by the entire
+    /// key.
+    pub group_level: Option<usize>,
+}
 
 impl Api for ReduceGrouped {
     type Error = crate::Error;
@@ -398,6 +675,112 @@ impl Api for ReduceGrouped {
     }
 }
 
+/// Reduces a view, returning whether the view had changes that were not yet
+/// reflected in its entries.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ReduceWithStaleness {
+    /// The view and query to reduce.
+    pub reduce: Reduce,
+}
+
+impl Api for ReduceWithStaleness {
+    type Error = crate::Error;
+    type Response = map::Reduction<Bytes>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ReduceWithStaleness")
+    }
+}
+
+/// Counts the number of mappings resulting from the view query.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct CountView {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The filter for the view.
+    pub key: Option<SerializedQueryKey>,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+}
+
+impl Api for CountView {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "CountView")
+    }
+}
+
+/// Counts the number of mappings for each distinct key resulting from the view
+/// query.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct FacetCounts {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The filter for the view.
+    pub key: Option<SerializedQueryKey>,
+    /// The access policy for the query.
+    pub access_policy: AccessPolicy,
+}
+
+impl Api for FacetCounts {
+    type Error = crate::Error;
+    type Response = Vec<(Bytes, u64)>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "FacetCounts")
+    }
+}
+
+/// Retrieves a document along with the value it emitted into a view.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct GetWithViewValue {
+    /// The name of the database.
+    pub database: String,
+    /// The name of the view.
+    pub view: ViewName,
+    /// The id of the document.
+    pub id: DocumentId,
+    /// The access policy for the view.
+    pub access_policy: AccessPolicy,
+}
+
+impl Api for GetWithViewValue {
+    type Error = crate::Error;
+    type Response = Option<(OwnedDocument, Option<Bytes>)>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "GetWithViewValue")
+    }
+}
+
+/// Retrieves a document along with the keys it emitted into views.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct GetWithMappings {
+    /// The name of the database.
+    pub database: String,
+    /// The collection of the document.
+    pub collection: CollectionName,
+    /// The id of the document.
+    pub id: DocumentId,
+    /// The access policy for the views.
+    pub access_policy: AccessPolicy,
+}
+
+impl Api for GetWithMappings {
+    type Error = crate::Error;
+    type Response = Option<(OwnedDocument, Vec<map::EmittedKey>)>;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "GetWithMappings")
+    }
+}
+
 /// Deletes the associated documents resulting from the view query.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct DeleteDocs {
@@ -431,7 +814,7 @@ pub struct ApplyTransaction {
 
 impl Api for ApplyTransaction {
     type Error = crate::Error;
-    type Response = Vec<OperationResult>;
+    type Response = AppliedTransaction;
 
     fn name() -> ApiName {
         ApiName::new("bonsaidb", "ApplyTransaction")
@@ -510,6 +893,48 @@ impl Api for Publish {
     }
 }
 
+/// Publishes `payload` to all subscribers of `topic`, returning the number of
+/// subscribers the message was delivered to.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct PublishAndFlush {
+    /// The name of the database.
+    pub database: String,
+    /// The topic to publish to.
+    pub topic: Bytes,
+    /// The payload to publish.
+    pub payload: Bytes,
+}
+
+impl Api for PublishAndFlush {
+    type Error = crate::Error;
+    type Response = u64;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "PublishAndFlush")
+    }
+}
+
+/// Publishes `payload` to all subscribers of `topic`, retaining it for future
+/// subscribers. An empty `payload` clears the retained message instead.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct PublishRetained {
+    /// The name of the database.
+    pub database: String,
+    /// The topic to publish to.
+    pub topic: Bytes,
+    /// The payload to publish and retain.
+    pub payload: Bytes,
+}
+
+impl Api for PublishRetained {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "PublishRetained")
+    }
+}
+
 /// Publishes `payload` to all subscribers of all `topics`.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct PublishToAll {
@@ -570,6 +995,26 @@ impl Api for MessageReceived {
     }
 }
 
+/// The server has begun shutting down gracefully. This is sent to every
+/// connected client when the shutdown begins. Requests that have already been
+/// received will still be answered, but new requests will not be processed.
+/// Clients should reconnect, potentially to another server.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct ServerShuttingDown {
+    /// The maximum amount of time the server will wait for outstanding
+    /// requests to complete before closing all connections.
+    pub timeout: std::time::Duration,
+}
+
+impl Api for ServerShuttingDown {
+    type Error = crate::Error;
+    type Response = Self;
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "ServerShuttingDown")
+    }
+}
+
 /// Unsubscribes `subscriber_id` from messages for `topic`.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct UnsubscribeFrom {
@@ -590,6 +1035,46 @@ impl Api for UnsubscribeFrom {
     }
 }
 
+/// Subscribes `subscriber_id` to messages for topics matching `pattern`.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct SubscribeToPattern {
+    /// The name of the database.
+    pub database: String,
+    /// The id of the [`Subscriber`](crate::pubsub::Subscriber).
+    pub subscriber_id: u64,
+    /// The [`TopicPattern`](crate::pubsub::TopicPattern) to subscribe to.
+    pub pattern: String,
+}
+
+impl Api for SubscribeToPattern {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "SubscribeToPattern")
+    }
+}
+
+/// Unsubscribes `subscriber_id` from messages for topics matching `pattern`.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct UnsubscribeFromPattern {
+    /// The name of the database.
+    pub database: String,
+    /// The id of the [`Subscriber`](crate::pubsub::Subscriber).
+    pub subscriber_id: u64,
+    /// The [`TopicPattern`](crate::pubsub::TopicPattern) to unsubscribe from.
+    pub pattern: String,
+}
+
+impl Api for UnsubscribeFromPattern {
+    type Error = crate::Error;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::new("bonsaidb", "UnsubscribeFromPattern")
+    }
+}
+
 /// Unregisters the subscriber.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct UnregisterSubscriber {
@@ -695,4 +1180,51 @@ pub enum Error {
     /// The connection was interrupted.
     #[error("unexpected disconnection")]
     Disconnected,
+
+    /// A payload was compressed using an algorithm that isn't enabled in this
+    /// build.
+    #[error("unsupported payload compression: {0:?}")]
+    UnsupportedCompression(PayloadCompression),
+
+    /// A payload was larger than the contained limit, in bytes.
+    #[error("payload exceeds the limit of {0} bytes")]
+    PayloadTooLarge(usize),
+}
+
+#[test]
+#[cfg(any(feature = "transport-lz4", feature = "transport-zstd"))]
+fn payload_compression_round_trip() {
+    let value = b"compressible ".repeat(100);
+    for compression in [PayloadCompression::Lz4, PayloadCompression::Zstd] {
+        if !compression.is_available() {
+            continue;
+        }
+
+        let mut payload = Payload {
+            session_id: None,
+            id: Some(1),
+            name: ApiName::new("bonsaidb", "Test"),
+            value: Ok(Bytes::from(value.clone())),
+            compression: None,
+            accept_compression: Some(compression),
+        };
+        // Values at or below the threshold are left alone.
+        payload.compress(compression, value.len()).unwrap();
+        assert_eq!(payload.compression, None);
+
+        payload.compress(compression, 0).unwrap();
+        assert_eq!(payload.compression, Some(compression));
+        let compressed = payload.value.as_ref().unwrap().to_vec();
+        assert!(compressed.len() < value.len());
+
+        payload.decompress(value.len()).unwrap();
+        assert_eq!(payload.compression, None);
+        assert_eq!(payload.value.as_ref().unwrap().to_vec(), value);
+
+        // Decompressing beyond the limit is rejected.
+        assert!(matches!(
+            compression.decompress(&compressed, value.len() - 1),
+            Err(crate::Error::Networking(Error::PayloadTooLarge(_)))
+        ));
+    }
 }
@@ -125,11 +125,17 @@ pub enum ServerAction {
     /// Permits [`StorageConnection::list_available_schemas`](crate::connection::StorageConnection::list_available_schemas).
     ListAvailableSchemas,
     /// Permits [`StorageConnection::list_databases`](crate::connection::StorageConnection::list_databases).
+    /// Also permits
+    /// [`StorageConnection::database_schema`](crate::connection::StorageConnection::database_schema)
+    /// and
+    /// [`StorageConnection::database_exists`](crate::connection::StorageConnection::database_exists).
     ListDatabases,
     /// Permits [`StorageConnection::create_database`](crate::connection::StorageConnection::create_database).
     CreateDatabase,
     /// Permits [`StorageConnection::delete_database`](crate::connection::StorageConnection::delete_database).
     DeleteDatabase,
+    /// Permits [`StorageConnection::rename_database`](crate::connection::StorageConnection::rename_database).
+    RenameDatabase,
     /// Permits [`StorageConnection::create_user`](crate::connection::StorageConnection::create_user).
     CreateUser,
     /// Permits [`StorageConnection::delete_user`](crate::connection::StorageConnection::delete_user).
@@ -215,12 +221,25 @@ pub enum DocumentAction {
 #[derive(Action, Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum ViewAction {
     /// Allows querying a view with
-    /// [`Connection::query()`](crate::connection::LowLevelConnection::query). See
-    /// [`view_resource_name`] for the format of view resource names.
+    /// [`Connection::query()`](crate::connection::LowLevelConnection::query)
+    /// or
+    /// [`Connection::query_page()`](crate::connection::LowLevelConnection::query_page),
+    /// or counting its entries with
+    /// [`Connection::count_view()`](crate::connection::LowLevelConnection::count_view)
+    /// or
+    /// [`Connection::facet_counts()`](crate::connection::LowLevelConnection::facet_counts).
+    /// Also required by
+    /// [`Connection::get_with_view_value()`](crate::connection::LowLevelConnection::get_with_view_value)
+    /// and, for each view of the collection,
+    /// [`Connection::get_with_mappings()`](crate::connection::LowLevelConnection::get_with_mappings),
+    /// in addition to [`DocumentAction::Get`] for the document.
+    /// See [`view_resource_name`] for the format of view resource names.
     Query,
     /// Allows reducing a view with
-    /// [`Connection::reduce()`](crate::connection::LowLevelConnection::reduce). See
-    /// [`view_resource_name`] for the format of view resource names.
+    /// [`Connection::reduce()`](crate::connection::LowLevelConnection::reduce)
+    /// or
+    /// [`Connection::reduce_with_staleness()`](crate::connection::LowLevelConnection::reduce_with_staleness).
+    /// See [`view_resource_name`] for the format of view resource names.
     Reduce,
     /// Allows deleting associated docs with
     /// [`Connection::delete_docs()`](crate::connection::LowLevelConnection::delete_docs).
@@ -284,4 +303,7 @@ pub enum EncryptionKeyAction {
     Encrypt,
     /// Uses a key to decrypt data.
     Decrypt,
+    /// Replaces a key with a newly generated key that is used to encrypt all
+    /// future data.
+    Rotate,
 }
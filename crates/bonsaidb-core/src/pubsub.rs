@@ -1,7 +1,14 @@
+use std::fmt::Display;
+
 use async_trait::async_trait;
 use circulate::{flume, Message};
-use serde::Serialize;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
+use crate::document::{DocumentId, Revision};
+use crate::schema::CollectionName;
 use crate::Error;
 
 /// Publishes and Subscribes to messages on topics.
@@ -21,9 +28,64 @@ pub trait PubSub {
         self.publish_bytes(pot::to_vec(topic)?, pot::to_vec(payload)?)
     }
 
+    /// Publishes a `payload` to all subscribers of the typed `topic`.
+    fn publish_to_topic<T: Topic>(&self, topic: &T, payload: &T::Payload) -> Result<(), Error> {
+        self.publish_bytes(topic.to_bytes()?, pot::to_vec(payload)?)
+    }
+
     /// Publishes a `payload` to all subscribers of `topic`.
     fn publish_bytes(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error>;
 
+    /// Publishes a `payload` to all subscribers of `topic`, returning once
+    /// every subscriber's channel has accepted the message. Returns the number
+    /// of subscribers the message was delivered to, including subscribers
+    /// whose patterns match `topic`.
+    ///
+    /// For subscribers connected over a network, the message has been
+    /// accepted by the server-side subscriber, but may not yet have been
+    /// received by the client.
+    fn publish_and_flush<Topic: Serialize, Payload: Serialize>(
+        &self,
+        topic: &Topic,
+        payload: &Payload,
+    ) -> Result<u64, Error> {
+        self.publish_bytes_and_flush(pot::to_vec(topic)?, pot::to_vec(payload)?)
+    }
+
+    /// Publishes a `payload` to all subscribers of `topic`, returning the
+    /// number of subscribers the message was delivered to. See
+    /// [`Self::publish_and_flush()`] for more information.
+    fn publish_bytes_and_flush(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<u64, Error>;
+
+    /// Publishes a `payload` to all subscribers of `topic`, and retains it as
+    /// the most recent message for `topic`. Retained messages are delivered to
+    /// each subscriber immediately when it subscribes to `topic`, or to a
+    /// pattern that matches `topic`.
+    ///
+    /// Only one message is retained per topic. Retained messages are kept in
+    /// memory until they are cleared or their database is deleted or renamed.
+    /// To clear the retained message without publishing a new one, see
+    /// [`Self::clear_retained()`].
+    fn publish_retained<Topic: Serialize, Payload: Serialize>(
+        &self,
+        topic: &Topic,
+        payload: &Payload,
+    ) -> Result<(), Error> {
+        self.publish_bytes_retained(pot::to_vec(topic)?, pot::to_vec(payload)?)
+    }
+
+    /// Clears the retained message for `topic`, if one has been published.
+    fn clear_retained<Topic: Serialize>(&self, topic: &Topic) -> Result<(), Error> {
+        self.publish_bytes_retained(pot::to_vec(topic)?, Vec::new())
+    }
+
+    /// Publishes a `payload` to all subscribers of `topic`, and retains it as
+    /// the most recent message for `topic`.
+    ///
+    /// If `payload` is empty, the retained message for `topic` is cleared and
+    /// nothing is published.
+    fn publish_bytes_retained(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error>;
+
     /// Publishes a `payload` to all subscribers of all `topics`.
     fn publish_to_all<
         'topics,
@@ -57,6 +119,11 @@ pub trait Subscriber {
         self.subscribe_to_bytes(pot::to_vec(topic)?)
     }
 
+    /// Subscribe to [`Message`]s published to the typed `topic`.
+    fn subscribe_to_topic<T: Topic>(&self, topic: &T) -> Result<(), Error> {
+        self.subscribe_to_bytes(topic.to_bytes()?)
+    }
+
     /// Subscribe to [`Message`]s published to `topic`.
     fn subscribe_to_bytes(&self, topic: Vec<u8>) -> Result<(), Error>;
 
@@ -65,11 +132,36 @@ pub trait Subscriber {
         self.unsubscribe_from_bytes(&pot::to_vec(topic)?)
     }
 
+    /// Unsubscribe from [`Message`]s published to the typed `topic`.
+    fn unsubscribe_from_topic<T: Topic>(&self, topic: &T) -> Result<(), Error> {
+        self.unsubscribe_from_bytes(&topic.to_bytes()?)
+    }
+
     /// Unsubscribe from [`Message`]s published to `topic`.
     fn unsubscribe_from_bytes(&self, topic: &[u8]) -> Result<(), Error>;
 
+    /// Subscribe to [`Message`]s published to any topic matching `pattern`.
+    /// See [`TopicPattern`] for the supported syntax.
+    fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), Error>;
+
+    /// Unsubscribe from a `pattern` previously passed to
+    /// [`Self::subscribe_to_pattern()`].
+    fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), Error>;
+
     /// Returns the receiver to receive [`Message`]s.
     fn receiver(&self) -> &Receiver;
+
+    /// Receive the next [`Message`]. Blocks the current thread until a
+    /// message is available. See [`Receiver::receive()`].
+    fn next_message(&self) -> Result<Message, Disconnected> {
+        self.receiver().receive()
+    }
+
+    /// Returns all [`Message`]s that have already been received, without
+    /// blocking. See [`Receiver::drain_available()`].
+    fn drain_available(&self) -> Vec<Message> {
+        self.receiver().drain_available()
+    }
 }
 
 /// Publishes and Subscribes to messages on topics.
@@ -92,9 +184,79 @@ pub trait AsyncPubSub: Send + Sync {
         self.publish_bytes(topic, payload).await
     }
 
+    /// Publishes a `payload` to all subscribers of the typed `topic`.
+    async fn publish_to_topic<T: Topic>(
+        &self,
+        topic: &T,
+        payload: &T::Payload,
+    ) -> Result<(), Error> {
+        let topic = topic.to_bytes()?;
+        let payload = pot::to_vec(payload)?;
+        self.publish_bytes(topic, payload).await
+    }
+
     /// Publishes a `payload` to all subscribers of `topic`.
     async fn publish_bytes(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error>;
 
+    /// Publishes a `payload` to all subscribers of `topic`, returning once
+    /// every subscriber's channel has accepted the message. Returns the number
+    /// of subscribers the message was delivered to, including subscribers
+    /// whose patterns match `topic`.
+    ///
+    /// For subscribers connected over a network, the message has been
+    /// accepted by the server-side subscriber, but may not yet have been
+    /// received by the client.
+    async fn publish_and_flush<Topic: Serialize + Send + Sync, Payload: Serialize + Send + Sync>(
+        &self,
+        topic: &Topic,
+        payload: &Payload,
+    ) -> Result<u64, Error> {
+        let topic = pot::to_vec(topic)?;
+        let payload = pot::to_vec(payload)?;
+        self.publish_bytes_and_flush(topic, payload).await
+    }
+
+    /// Publishes a `payload` to all subscribers of `topic`, returning the
+    /// number of subscribers the message was delivered to. See
+    /// [`Self::publish_and_flush()`] for more information.
+    async fn publish_bytes_and_flush(&self, topic: Vec<u8>, payload: Vec<u8>)
+        -> Result<u64, Error>;
+
+    /// Publishes a `payload` to all subscribers of `topic`, and retains it as
+    /// the most recent message for `topic`. Retained messages are delivered to
+    /// each subscriber immediately when it subscribes to `topic`, or to a
+    /// pattern that matches `topic`.
+    ///
+    /// Only one message is retained per topic. Retained messages are kept in
+    /// memory until they are cleared or their database is deleted or renamed.
+    /// To clear the retained message without publishing a new one, see
+    /// [`Self::clear_retained()`].
+    async fn publish_retained<Topic: Serialize + Send + Sync, Payload: Serialize + Send + Sync>(
+        &self,
+        topic: &Topic,
+        payload: &Payload,
+    ) -> Result<(), Error> {
+        let topic = pot::to_vec(topic)?;
+        let payload = pot::to_vec(payload)?;
+        self.publish_bytes_retained(topic, payload).await
+    }
+
+    /// Clears the retained message for `topic`, if one has been published.
+    async fn clear_retained<Topic: Serialize + Send + Sync>(
+        &self,
+        topic: &Topic,
+    ) -> Result<(), Error> {
+        let topic = pot::to_vec(topic)?;
+        self.publish_bytes_retained(topic, Vec::new()).await
+    }
+
+    /// Publishes a `payload` to all subscribers of `topic`, and retains it as
+    /// the most recent message for `topic`.
+    ///
+    /// If `payload` is empty, the retained message for `topic` is cleared and
+    /// nothing is published.
+    async fn publish_bytes_retained(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), Error>;
+
     /// Publishes a `payload` to all subscribers of all `topics`.
     async fn publish_to_all<
         'topics,
@@ -133,6 +295,11 @@ pub trait AsyncSubscriber: Send + Sync {
         self.subscribe_to_bytes(pot::to_vec(topic)?).await
     }
 
+    /// Subscribe to [`Message`]s published to the typed `topic`.
+    async fn subscribe_to_topic<T: Topic>(&self, topic: &T) -> Result<(), Error> {
+        self.subscribe_to_bytes(topic.to_bytes()?).await
+    }
+
     /// Subscribe to [`Message`]s published to `topic`.
     async fn subscribe_to_bytes(&self, topic: Vec<u8>) -> Result<(), Error>;
 
@@ -144,11 +311,151 @@ pub trait AsyncSubscriber: Send + Sync {
         self.unsubscribe_from_bytes(&pot::to_vec(topic)?).await
     }
 
+    /// Unsubscribe from [`Message`]s published to the typed `topic`.
+    async fn unsubscribe_from_topic<T: Topic>(&self, topic: &T) -> Result<(), Error> {
+        self.unsubscribe_from_bytes(&topic.to_bytes()?).await
+    }
+
     /// Unsubscribe from [`Message`]s published to `topic`.
     async fn unsubscribe_from_bytes(&self, topic: &[u8]) -> Result<(), Error>;
 
+    /// Subscribe to [`Message`]s published to any topic matching `pattern`.
+    /// See [`TopicPattern`] for the supported syntax.
+    async fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), Error>;
+
+    /// Unsubscribe from a `pattern` previously passed to
+    /// [`Self::subscribe_to_pattern()`].
+    async fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), Error>;
+
     /// Returns the receiver to receive [`Message`]s.
     fn receiver(&self) -> &Receiver;
+
+    /// Receive the next [`Message`]. Waits until a message is available. See
+    /// [`Receiver::receive_async()`].
+    async fn next_message(&self) -> Result<Message, Disconnected> {
+        self.receiver().receive_async().await
+    }
+
+    /// Returns all [`Message`]s that have already been received, without
+    /// waiting. See [`Receiver::drain_available()`].
+    fn drain_available(&self) -> Vec<Message> {
+        self.receiver().drain_available()
+    }
+
+    /// Returns a stream of the [`Message`]s received by this subscriber. The
+    /// stream ends when the receiver is disconnected.
+    fn messages_stream(&self) -> BoxStream<'static, Message> {
+        self.receiver().clone().into_stream().boxed()
+    }
+}
+
+/// A topic with an associated payload type.
+///
+/// The typed methods, such as [`PubSub::publish_to_topic()`] and
+/// [`Subscriber::subscribe_to_topic()`], only accept payloads of
+/// [`Self::Payload`], ensuring publishers and subscribers agree on the type
+/// sent over the topic.
+///
+/// Typed topics are encoded the same way as passing [`Self::name()`] to
+/// [`PubSub::publish()`], which allows them to be mixed with the untyped
+/// methods and to be matched by a [`TopicPattern`].
+pub trait Topic: Send + Sync {
+    /// The type of payload published to this topic.
+    type Payload: Serialize + DeserializeOwned + Send + Sync;
+
+    /// Returns the name of this topic.
+    fn name(&self) -> String;
+
+    /// Returns the serialized topic that is published and subscribed to.
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(pot::to_vec(&self.name())?)
+    }
+
+    /// Deserializes the payload of `message` if it was published to this
+    /// topic. Returns `None` if `message` was published to another topic.
+    fn payload_of(&self, message: &Message) -> Result<Option<Self::Payload>, Error> {
+        if message.topic.iter().eq(self.to_bytes()?) {
+            Ok(Some(message.payload()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A hierarchical topic pattern, using the same wildcards as MQTT.
+///
+/// Patterns only match topics that were published as strings. Topics and
+/// patterns are split into levels on `/`:
+///
+/// - `+` matches exactly one level: `orders/+/created` matches
+///   `orders/42/created`, but not `orders/created` or `orders/1/2/created`.
+/// - `#` matches zero or more trailing levels, and may only appear as the last
+///   level: `orders/#` matches `orders`, `orders/42`, and `orders/42/created`.
+///
+/// Wildcards must occupy an entire level. All other levels must match exactly.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TopicPattern {
+    pattern: String,
+}
+
+impl TopicPattern {
+    /// Parses `pattern`, returning an error if it is not a valid pattern.
+    pub fn new(pattern: impl Into<String>) -> Result<Self, Error> {
+        let pattern = pattern.into();
+        let mut levels = pattern.split('/').peekable();
+        while let Some(level) = levels.next() {
+            let is_valid = match level {
+                "#" => levels.peek().is_none(),
+                "+" => true,
+                level => !level.contains(['+', '#']),
+            };
+            if !is_valid {
+                return Err(Error::InvalidTopicPattern(pattern));
+            }
+        }
+
+        Ok(Self { pattern })
+    }
+
+    /// Returns the pattern as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns true if `topic` matches this pattern.
+    #[must_use]
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut topic_levels = topic.split('/');
+        for pattern_level in self.pattern.split('/') {
+            match (pattern_level, topic_levels.next()) {
+                ("#", _) => return true,
+                (_, None) => return false,
+                ("+", Some(_)) => {}
+                (pattern_level, Some(topic_level)) => {
+                    if pattern_level != topic_level {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        topic_levels.next().is_none()
+    }
+
+    /// Returns true if `topic`, a serialized topic as passed to
+    /// [`Subscriber::subscribe_to_bytes()`], is a string that matches this
+    /// pattern.
+    #[must_use]
+    pub fn matches_bytes(&self, topic: &[u8]) -> bool {
+        pot::from_slice::<String>(topic).map_or(false, |topic| self.matches(&topic))
+    }
+}
+
+impl Display for TopicPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
 }
 
 /// Receiver of PubSub [`Message`]s.
@@ -156,14 +463,19 @@ pub trait AsyncSubscriber: Send + Sync {
 #[must_use]
 pub struct Receiver {
     receiver: flume::Receiver<Message>,
+    retained: Option<flume::Receiver<Message>>,
     strip_database: bool,
 }
 
 impl Receiver {
     #[doc(hidden)]
-    pub fn new_stripping_prefixes(receiver: flume::Receiver<Message>) -> Self {
+    pub fn new_stripping_prefixes(
+        receiver: flume::Receiver<Message>,
+        retained: flume::Receiver<Message>,
+    ) -> Self {
         Self {
             receiver,
+            retained: Some(retained),
             strip_database: true,
         }
     }
@@ -172,6 +484,7 @@ impl Receiver {
     pub fn new(receiver: flume::Receiver<Message>) -> Self {
         Self {
             receiver,
+            retained: None,
             strip_database: false,
         }
     }
@@ -180,6 +493,21 @@ impl Receiver {
     /// is available. If the receiver becomes disconnected, an error will be
     /// returned.
     pub fn receive(&self) -> Result<Message, Disconnected> {
+        if let Some(retained) = &self.retained {
+            // Retained messages are replayed when subscribing, before any
+            // newer message for the same topic can be published, so they are
+            // always received first.
+            let message = retained.try_recv().or_else(|_| {
+                flume::Selector::new()
+                    .recv(retained, |message| message)
+                    .recv(&self.receiver, |message| message)
+                    .wait()
+            });
+            if let Ok(message) = message {
+                return Ok(self.remove_database_prefix(message));
+            }
+        }
+
         self.receiver
             .recv()
             .map(|message| self.remove_database_prefix(message))
@@ -190,6 +518,21 @@ impl Receiver {
     /// message is available. If the receiver becomes disconnected, an error
     /// will be returned.
     pub async fn receive_async(&self) -> Result<Message, Disconnected> {
+        if let Some(retained) = &self.retained {
+            let message = match retained.try_recv() {
+                Ok(message) => Ok(message),
+                Err(_) => {
+                    futures::future::select(retained.recv_async(), self.receiver.recv_async())
+                        .await
+                        .factor_first()
+                        .0
+                }
+            };
+            if let Ok(message) = message {
+                return Ok(self.remove_database_prefix(message));
+            }
+        }
+
         self.receiver
             .recv_async()
             .await
@@ -200,12 +543,37 @@ impl Receiver {
     /// Try to receive the next [`Message`]. This function will not block, and
     /// only returns a message if one is already available.
     pub fn try_receive(&self) -> Result<Message, TryReceiveError> {
+        if let Some(message) = self
+            .retained
+            .as_ref()
+            .and_then(|retained| retained.try_recv().ok())
+        {
+            return Ok(self.remove_database_prefix(message));
+        }
+
         self.receiver
             .try_recv()
             .map(|message| self.remove_database_prefix(message))
             .map_err(TryReceiveError::from)
     }
 
+    /// Returns all [`Message`]s that are already available, without blocking.
+    /// If no messages are available, or the receiver is disconnected, an empty
+    /// collection is returned.
+    #[must_use]
+    pub fn drain_available(&self) -> Vec<Message> {
+        std::iter::from_fn(|| self.try_receive().ok()).collect()
+    }
+
+    /// Returns a stream of the [`Message`]s received. The stream ends when the
+    /// receiver is disconnected.
+    pub fn into_stream(self) -> impl Stream<Item = Message> + Send + 'static {
+        futures::stream::unfold(self, |receiver| async move {
+            let message = receiver.receive_async().await.ok()?;
+            Some((message, receiver))
+        })
+    }
+
     fn remove_database_prefix(&self, mut message: Message) -> Message {
         if self.strip_database {
             if let Some(database_length) = message.topic.iter().position(|b| b == 0) {
@@ -250,6 +618,90 @@ impl From<flume::TryRecvError> for TryReceiveError {
     }
 }
 
+/// The prefix of the topics that BonsaiDb publishes its own events to, such as
+/// [`document_changes_topic()`]. Messages can't be published to topics
+/// beginning with this prefix through [`PubSub`], which returns
+/// [`Error::ReservedTopic`] instead.
+pub const RESERVED_TOPIC_PREFIX: &str = "$bonsaidb/";
+
+/// Returns true if `topic`, a serialized topic, begins with
+/// [`RESERVED_TOPIC_PREFIX`].
+#[must_use]
+pub fn is_reserved_topic(topic: &[u8]) -> bool {
+    pot::from_slice::<String>(topic).map_or(false, |topic| topic.starts_with(RESERVED_TOPIC_PREFIX))
+}
+
+/// Returns the topic that [`DocumentChange`]s are published to for documents
+/// stored in `collection`. Changes are only published for collections whose
+/// [`Collection::publish_changes()`](crate::schema::Collection::publish_changes)
+/// returns true.
+///
+/// The topic is reserved, so changes can only be published by the database.
+/// Subscribing to the topic requires permission to
+/// [list the headers](crate::permissions::bonsai::DocumentAction::ListHeaders)
+/// of `collection` in addition to the usual PubSub permissions.
+#[must_use]
+pub fn document_changes_topic(collection: &CollectionName) -> String {
+    format!("{RESERVED_TOPIC_PREFIX}changes/{collection:#}")
+}
+
+/// Returns the collection whose [`DocumentChange`]s are published to `topic`,
+/// a serialized topic, if it is a [`document_changes_topic()`].
+#[must_use]
+pub fn document_changes_topic_collection(topic: &[u8]) -> Option<CollectionName> {
+    let topic = pot::from_slice::<String>(topic).ok()?;
+    topic
+        .strip_prefix(RESERVED_TOPIC_PREFIX)?
+        .strip_prefix("changes/")?
+        .parse()
+        .ok()
+}
+
+/// A document change published to [`document_changes_topic()`] after the
+/// transaction containing the change has been committed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DocumentChange {
+    /// The id of the transaction that changed the document.
+    pub transaction_id: u64,
+    /// The id of the changed document.
+    pub id: DocumentId,
+    /// The revision of the document after it was changed. For deleted
+    /// documents, this is the revision that was deleted.
+    pub revision: Revision,
+    /// If true, the document was deleted.
+    pub deleted: bool,
+}
+
+/// The typed [`Topic`] that [`DocumentChange`]s for the contained collection
+/// are published to. See [`document_changes_topic()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DocumentChangesTopic(pub CollectionName);
+
+impl Topic for DocumentChangesTopic {
+    type Payload = DocumentChange;
+
+    fn name(&self) -> String {
+        document_changes_topic(&self.0)
+    }
+}
+
+/// The typed [`Topic`] that the id of each transaction is published to after
+/// the transaction has been committed. Unlike [`DocumentChangesTopic`], every
+/// transaction is published, including transactions that only modified the
+/// key-value store. The topic is reserved, so transaction ids can only be
+/// published by the database. See
+/// [`AsyncConnection::watch_transactions()`](crate::connection::AsyncConnection::watch_transactions).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TransactionsTopic;
+
+impl Topic for TransactionsTopic {
+    type Payload = u64;
+
+    fn name(&self) -> String {
+        format!("{RESERVED_TOPIC_PREFIX}transactions")
+    }
+}
+
 /// Creates a topic for use in a server. This is an internal API, which is why
 /// the documentation is hidden. This is an implementation detail, but both
 /// Client and Server must agree on this format, which is why it lives in core.
@@ -445,6 +897,214 @@ macro_rules! define_async_pubsub_test_suite {
 
                 Ok(())
             }
+
+            #[tokio::test]
+            async fn pattern_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubPatterns).await?;
+                let pubsub = harness.connect().await?;
+                let single_level = AsyncPubSub::create_subscriber(&pubsub).await?;
+                let multi_level = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to_pattern(&single_level, "orders/+/created").await?;
+                // Subscribing to a topic that a pattern also matches should
+                // only deliver each message once.
+                AsyncSubscriber::subscribe_to(&single_level, &"orders/1/created").await?;
+                AsyncSubscriber::subscribe_to_pattern(&multi_level, "orders/#").await?;
+                // Wildcards must occupy an entire level, and `#` must be last.
+                assert!(matches!(
+                    AsyncSubscriber::subscribe_to_pattern(&single_level, "orders/#/created").await,
+                    Err($crate::Error::InvalidTopicPattern(_))
+                ));
+                assert!(matches!(
+                    AsyncSubscriber::subscribe_to_pattern(&single_level, "orders/a+").await,
+                    Err($crate::Error::InvalidTopicPattern(_))
+                ));
+
+                for topic in [
+                    "orders",
+                    "orders/1/created",
+                    "orders/1/shipped",
+                    "orders/created",
+                    "orders/1/2/created",
+                    "invoices/1/created",
+                    "orders/2/created",
+                ] {
+                    AsyncPubSub::publish(&pubsub, &topic, &()).await?;
+                }
+
+                for expected in ["orders/1/created", "orders/2/created"] {
+                    let message = single_level.receiver().receive_async().await?;
+                    assert_eq!(message.topic::<String>()?, expected);
+                }
+                for expected in [
+                    "orders",
+                    "orders/1/created",
+                    "orders/1/shipped",
+                    "orders/created",
+                    "orders/1/2/created",
+                    "orders/2/created",
+                ] {
+                    let message = multi_level.receiver().receive_async().await?;
+                    assert_eq!(message.topic::<String>()?, expected);
+                }
+
+                // After unsubscribing from the pattern, only the explicitly
+                // subscribed topic should be received.
+                AsyncSubscriber::unsubscribe_from_pattern(&single_level, "orders/+/created")
+                    .await?;
+                AsyncPubSub::publish(&pubsub, &"orders/2/created", &()).await?;
+                AsyncPubSub::publish(&pubsub, &"orders/1/created", &()).await?;
+                let message = single_level.receiver().receive_async().await?;
+                assert_eq!(message.topic::<String>()?, "orders/1/created");
+                assert!(matches!(
+                    single_level.receiver().try_receive(),
+                    Err($crate::pubsub::TryReceiveError::Empty)
+                ));
+
+                Ok(())
+            }
+
+            #[tokio::test]
+            async fn retained_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubRetained).await?;
+                let pubsub = harness.connect().await?;
+                let existing = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to(&existing, &"status").await?;
+                AsyncPubSub::publish_retained(&pubsub, &"status", &String::from("online")).await?;
+                AsyncPubSub::publish_retained(&pubsub, &"status", &String::from("busy")).await?;
+                for expected in ["online", "busy"] {
+                    let message = existing.receiver().receive_async().await?;
+                    assert_eq!(message.payload::<String>()?, expected);
+                }
+
+                // New subscribers immediately receive only the latest retained
+                // message, whether subscribing to the topic or to a pattern.
+                let late = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to(&late, &"status").await?;
+                let message = late.receiver().receive_async().await?;
+                assert_eq!(message.topic::<String>()?, "status");
+                assert_eq!(message.payload::<String>()?, "busy");
+                let pattern = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to_pattern(&pattern, "#").await?;
+                let message = pattern.receiver().receive_async().await?;
+                assert_eq!(message.topic::<String>()?, "status");
+                assert_eq!(message.payload::<String>()?, "busy");
+
+                // Clearing the retained message publishes nothing, and new
+                // subscribers only receive subsequently published messages.
+                AsyncPubSub::clear_retained(&pubsub, &"status").await?;
+                let cleared = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to(&cleared, &"status").await?;
+                AsyncPubSub::publish(&pubsub, &"status", &String::from("offline")).await?;
+                for subscriber in [&existing, &late, &pattern, &cleared] {
+                    let message = subscriber.receiver().receive_async().await?;
+                    assert_eq!(message.payload::<String>()?, "offline");
+                }
+
+                Ok(())
+            }
+
+            #[tokio::test]
+            async fn publish_and_flush_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubFlush).await?;
+                let pubsub = harness.connect().await?;
+                let subscriber_a = AsyncPubSub::create_subscriber(&pubsub).await?;
+                let subscriber_ab = AsyncPubSub::create_subscriber(&pubsub).await?;
+                let _unsubscribed = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to(&subscriber_a, &"a").await?;
+                AsyncSubscriber::subscribe_to(&subscriber_ab, &"a").await?;
+                AsyncSubscriber::subscribe_to_pattern(&subscriber_ab, "+").await?;
+
+                assert_eq!(
+                    AsyncPubSub::publish_and_flush(&pubsub, &"a", &1_u32).await?,
+                    2
+                );
+                assert_eq!(
+                    AsyncPubSub::publish_and_flush(&pubsub, &"b", &2_u32).await?,
+                    1
+                );
+                assert_eq!(
+                    AsyncPubSub::publish_and_flush(&pubsub, &"c/d", &3_u32).await?,
+                    0
+                );
+
+                let message = subscriber_a.receiver().receive_async().await?;
+                assert_eq!(message.payload::<u32>()?, 1);
+                for expected in [1, 2] {
+                    let message = subscriber_ab.receiver().receive_async().await?;
+                    assert_eq!(message.payload::<u32>()?, expected);
+                }
+
+                Ok(())
+            }
+
+            #[tokio::test]
+            async fn message_helpers_test() -> anyhow::Result<()> {
+                use futures::StreamExt;
+
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubHelpers).await?;
+                let pubsub = harness.connect().await?;
+                let subscriber = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to(&subscriber, &"a").await?;
+                for value in 1_u32..=3 {
+                    AsyncPubSub::publish(&pubsub, &"a", &value).await?;
+                }
+
+                let message = AsyncSubscriber::next_message(&subscriber).await?;
+                assert_eq!(message.payload::<u32>()?, 1);
+                let mut remaining = Vec::new();
+                let mut stream = AsyncSubscriber::messages_stream(&subscriber);
+                while remaining.len() < 2 {
+                    let message = stream.next().await.expect("stream ended");
+                    remaining.push(message.payload::<u32>()?);
+                }
+                assert_eq!(remaining, [2, 3]);
+                assert!(AsyncSubscriber::drain_available(&subscriber).is_empty());
+
+                Ok(())
+            }
+
+            #[tokio::test]
+            async fn typed_topic_test() -> anyhow::Result<()> {
+                use $crate::pubsub::Topic;
+
+                struct Temperature(&'static str);
+
+                impl Topic for Temperature {
+                    type Payload = i32;
+
+                    fn name(&self) -> String {
+                        format!("temperature/{}", self.0)
+                    }
+                }
+
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubTyped).await?;
+                let pubsub = harness.connect().await?;
+                let subscriber = AsyncPubSub::create_subscriber(&pubsub).await?;
+                AsyncSubscriber::subscribe_to_topic(&subscriber, &Temperature("kitchen")).await?;
+                AsyncSubscriber::subscribe_to(&subscriber, &"temperature/garage").await?;
+
+                AsyncPubSub::publish_to_topic(&pubsub, &Temperature("kitchen"), &21).await?;
+                AsyncPubSub::publish_to_topic(&pubsub, &Temperature("garage"), &12).await?;
+
+                let message = subscriber.receiver().receive_async().await?;
+                assert_eq!(Temperature("kitchen").payload_of(&message)?, Some(21));
+                assert_eq!(Temperature("garage").payload_of(&message)?, None);
+                let message = subscriber.receiver().receive_async().await?;
+                assert_eq!(message.topic::<String>()?, "temperature/garage");
+                assert_eq!(Temperature("garage").payload_of(&message)?, Some(12));
+
+                AsyncSubscriber::unsubscribe_from_topic(&subscriber, &Temperature("kitchen"))
+                    .await?;
+                AsyncPubSub::publish_to_topic(&pubsub, &Temperature("kitchen"), &22).await?;
+                assert_eq!(
+                    AsyncPubSub::publish_and_flush(&pubsub, &"temperature/garage", &13_i32).await?,
+                    1
+                );
+                let message = subscriber.receiver().receive_async().await?;
+                assert_eq!(Temperature("garage").payload_of(&message)?, Some(13));
+
+                Ok(())
+            }
         }
     };
 }
@@ -594,6 +1254,196 @@ macro_rules! define_blocking_pubsub_test_suite {
 
                 Ok(())
             }
+
+            #[test]
+            fn pattern_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubPatterns)?;
+                let pubsub = harness.connect()?;
+                let single_level = PubSub::create_subscriber(&pubsub)?;
+                let multi_level = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to_pattern(&single_level, "orders/+/created")?;
+                // Subscribing to a topic that a pattern also matches should
+                // only deliver each message once.
+                Subscriber::subscribe_to(&single_level, &"orders/1/created")?;
+                Subscriber::subscribe_to_pattern(&multi_level, "orders/#")?;
+                // Wildcards must occupy an entire level, and `#` must be last.
+                assert!(matches!(
+                    Subscriber::subscribe_to_pattern(&single_level, "orders/#/created"),
+                    Err($crate::Error::InvalidTopicPattern(_))
+                ));
+                assert!(matches!(
+                    Subscriber::subscribe_to_pattern(&single_level, "orders/a+"),
+                    Err($crate::Error::InvalidTopicPattern(_))
+                ));
+
+                for topic in [
+                    "orders",
+                    "orders/1/created",
+                    "orders/1/shipped",
+                    "orders/created",
+                    "orders/1/2/created",
+                    "invoices/1/created",
+                    "orders/2/created",
+                ] {
+                    PubSub::publish(&pubsub, &topic, &())?;
+                }
+
+                for expected in ["orders/1/created", "orders/2/created"] {
+                    let message = single_level.receiver().receive()?;
+                    assert_eq!(message.topic::<String>()?, expected);
+                }
+                for expected in [
+                    "orders",
+                    "orders/1/created",
+                    "orders/1/shipped",
+                    "orders/created",
+                    "orders/1/2/created",
+                    "orders/2/created",
+                ] {
+                    let message = multi_level.receiver().receive()?;
+                    assert_eq!(message.topic::<String>()?, expected);
+                }
+
+                // After unsubscribing from the pattern, only the explicitly
+                // subscribed topic should be received.
+                Subscriber::unsubscribe_from_pattern(&single_level, "orders/+/created")?;
+                PubSub::publish(&pubsub, &"orders/2/created", &())?;
+                PubSub::publish(&pubsub, &"orders/1/created", &())?;
+                let message = single_level.receiver().receive()?;
+                assert_eq!(message.topic::<String>()?, "orders/1/created");
+                assert!(matches!(
+                    single_level.receiver().try_receive(),
+                    Err($crate::pubsub::TryReceiveError::Empty)
+                ));
+
+                Ok(())
+            }
+
+            #[test]
+            fn retained_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubRetained)?;
+                let pubsub = harness.connect()?;
+                let existing = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to(&existing, &"status")?;
+                PubSub::publish_retained(&pubsub, &"status", &String::from("online"))?;
+                PubSub::publish_retained(&pubsub, &"status", &String::from("busy"))?;
+                for expected in ["online", "busy"] {
+                    let message = existing.receiver().receive()?;
+                    assert_eq!(message.payload::<String>()?, expected);
+                }
+
+                // New subscribers immediately receive only the latest retained
+                // message, whether subscribing to the topic or to a pattern.
+                let late = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to(&late, &"status")?;
+                let message = late.receiver().receive()?;
+                assert_eq!(message.topic::<String>()?, "status");
+                assert_eq!(message.payload::<String>()?, "busy");
+                let pattern = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to_pattern(&pattern, "#")?;
+                let message = pattern.receiver().receive()?;
+                assert_eq!(message.topic::<String>()?, "status");
+                assert_eq!(message.payload::<String>()?, "busy");
+
+                // Clearing the retained message publishes nothing, and new
+                // subscribers only receive subsequently published messages.
+                PubSub::clear_retained(&pubsub, &"status")?;
+                let cleared = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to(&cleared, &"status")?;
+                PubSub::publish(&pubsub, &"status", &String::from("offline"))?;
+                for subscriber in [&existing, &late, &pattern, &cleared] {
+                    let message = subscriber.receiver().receive()?;
+                    assert_eq!(message.payload::<String>()?, "offline");
+                }
+
+                Ok(())
+            }
+
+            #[test]
+            fn publish_and_flush_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubFlush)?;
+                let pubsub = harness.connect()?;
+                let subscriber_a = PubSub::create_subscriber(&pubsub)?;
+                let subscriber_ab = PubSub::create_subscriber(&pubsub)?;
+                let _unsubscribed = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to(&subscriber_a, &"a")?;
+                Subscriber::subscribe_to(&subscriber_ab, &"a")?;
+                Subscriber::subscribe_to_pattern(&subscriber_ab, "+")?;
+
+                assert_eq!(PubSub::publish_and_flush(&pubsub, &"a", &1_u32)?, 2);
+                assert_eq!(PubSub::publish_and_flush(&pubsub, &"b", &2_u32)?, 1);
+                assert_eq!(PubSub::publish_and_flush(&pubsub, &"c/d", &3_u32)?, 0);
+
+                let message = subscriber_a.receiver().receive()?;
+                assert_eq!(message.payload::<u32>()?, 1);
+                for expected in [1, 2] {
+                    let message = subscriber_ab.receiver().receive()?;
+                    assert_eq!(message.payload::<u32>()?, expected);
+                }
+
+                Ok(())
+            }
+
+            #[test]
+            fn message_helpers_test() -> anyhow::Result<()> {
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubHelpers)?;
+                let pubsub = harness.connect()?;
+                let subscriber = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to(&subscriber, &"a")?;
+                for value in 1_u32..=3 {
+                    PubSub::publish(&pubsub, &"a", &value)?;
+                }
+
+                for expected in 1_u32..=3 {
+                    let message = Subscriber::next_message(&subscriber)?;
+                    assert_eq!(message.payload::<u32>()?, expected);
+                }
+                assert!(Subscriber::drain_available(&subscriber).is_empty());
+
+                Ok(())
+            }
+
+            #[test]
+            fn typed_topic_test() -> anyhow::Result<()> {
+                use $crate::pubsub::Topic;
+
+                struct Temperature(&'static str);
+
+                impl Topic for Temperature {
+                    type Payload = i32;
+
+                    fn name(&self) -> String {
+                        format!("temperature/{}", self.0)
+                    }
+                }
+
+                let harness = $harness::new($crate::test_util::HarnessTest::PubSubTyped)?;
+                let pubsub = harness.connect()?;
+                let subscriber = PubSub::create_subscriber(&pubsub)?;
+                Subscriber::subscribe_to_topic(&subscriber, &Temperature("kitchen"))?;
+                Subscriber::subscribe_to(&subscriber, &"temperature/garage")?;
+
+                PubSub::publish_to_topic(&pubsub, &Temperature("kitchen"), &21)?;
+                PubSub::publish_to_topic(&pubsub, &Temperature("garage"), &12)?;
+
+                let message = subscriber.receiver().receive()?;
+                assert_eq!(Temperature("kitchen").payload_of(&message)?, Some(21));
+                assert_eq!(Temperature("garage").payload_of(&message)?, None);
+                let message = subscriber.receiver().receive()?;
+                assert_eq!(message.topic::<String>()?, "temperature/garage");
+                assert_eq!(Temperature("garage").payload_of(&message)?, Some(12));
+
+                Subscriber::unsubscribe_from_topic(&subscriber, &Temperature("kitchen"))?;
+                PubSub::publish_to_topic(&pubsub, &Temperature("kitchen"), &22)?;
+                assert_eq!(
+                    PubSub::publish_and_flush(&pubsub, &"temperature/garage", &13_i32)?,
+                    1
+                );
+                let message = subscriber.receiver().receive()?;
+                assert_eq!(Temperature("garage").payload_of(&message)?, Some(13));
+
+                Ok(())
+            }
         }
     };
 }
@@ -1,5 +1,7 @@
 mod collection;
+mod migration;
 mod names;
+mod natural_key;
 mod schematic;
 mod summary;
 /// Types for defining map/reduce-powered `View`s.
@@ -8,20 +10,23 @@ pub mod view;
 pub use bonsaidb_macros::{Collection, Schema, View, ViewSchema};
 
 pub use self::collection::{
-    AsyncEntry, AsyncList, Collection, DefaultSerialization, InsertError, List, Nameable,
-    NamedCollection, NamedReference, SerializedCollection,
+    AsyncEntry, AsyncList, Collection, DefaultSerialization, IdStrategy, InsertError, List,
+    Nameable, NamedCollection, NamedReference, SerializedCollection,
 };
+pub use self::migration::Migrations;
 pub use self::names::{
     Authority, CollectionName, InvalidNameError, Name, Qualified, QualifiedName, SchemaName,
     ViewName,
 };
-pub use self::schematic::Schematic;
-pub use self::summary::{CollectionSummary, SchemaSummary, ViewSummary};
-pub use self::view::map::{Map, MappedValue, ViewMappedValue};
+pub use self::natural_key::{ByNaturalKey, NaturalKey};
+pub use self::schematic::{Schematic, MAX_DELETE_CASCADE_DEPTH};
+pub use self::summary::{CollectionSummary, SchemaFingerprint, SchemaSummary, ViewSummary};
+pub use self::view::map::{Map, MappedValue, Reduction, ViewMappedValue};
 pub use self::view::{
     CollectionMapReduce, DefaultViewSerialization, MapReduce, ReduceResult, SerializedView, View,
     ViewMapResult, ViewSchema,
 };
+use crate::connection::Connection;
 use crate::Error;
 
 /// Defines a group of collections that are stored into a single database.
@@ -80,6 +85,11 @@ pub trait Schema: Send + Sync + 'static {
     fn schematic() -> Result<Schematic, Error> {
         Schematic::from_schema::<Self>()
     }
+
+    /// Defines the steps that upgrade databases created by older versions of
+    /// this schema. See [`Migrations`] for more information. By default, no
+    /// steps are defined.
+    fn define_migrations<C: Connection>(_migrations: &mut Migrations<C>) {}
 }
 
 /// This implementation is for accessing databases when interacting with
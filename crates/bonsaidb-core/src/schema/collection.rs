@@ -13,12 +13,12 @@ use transmog_pot::Pot;
 
 use crate::connection::{self, AsyncConnection, Connection, RangeRef};
 use crate::document::{
-    BorrowedDocument, CollectionDocument, CollectionHeader, Document, DocumentId, Header, KeyId,
-    OwnedDocument, OwnedDocuments, Revision,
+    contents_value, BorrowedDocument, CollectionDocument, CollectionHeader, Document, DocumentDiff,
+    DocumentId, Header, KeyId, OwnedDocument, OwnedDocuments, Revision,
 };
 use crate::key::{IntoPrefixRange, Key, KeyEncoding};
 use crate::schema::{CollectionName, Schematic};
-use crate::transaction::{Operation, OperationResult, Transaction};
+use crate::transaction::{Operation, OperationResult, ProposedChange, Transaction};
 use crate::Error;
 
 /// A namespaced collection of `Document<Self>` items and views.
@@ -194,6 +194,49 @@ use crate::Error;
 /// pub struct MyCollection;
 /// ```
 ///
+/// ### Retaining document history
+///
+/// To retain previous revisions of each document when it is updated or
+/// deleted, provide the maximum number of revisions to keep using
+/// `history_limit`. See [`Collection::history_limit()`] for more information.
+///
+/// ```rust
+/// use bonsaidb_core::schema::Collection;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Default, Collection)]
+/// #[collection(name = "MyCollection", history_limit = 10)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct MyCollection;
+/// ```
+///
+/// ### Validating documents
+///
+/// To reject documents that don't meet your application's requirements when
+/// they are inserted or updated, provide a function that accepts a reference
+/// to the collection's type and returns `Result<(), String>` using `validate`.
+/// See [`Collection::validate_document()`] for more information.
+///
+/// ```rust
+/// use bonsaidb_core::schema::Collection;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Default, Collection)]
+/// #[collection(name = "MyCollection", validate = validate_my_collection)]
+/// # #[collection(core = bonsaidb_core)]
+/// pub struct MyCollection {
+///     pub name: String,
+/// }
+///
+/// fn validate_my_collection(contents: &MyCollection) -> Result<(), String> {
+///     if contents.name.is_empty() {
+///         Err(String::from("name must not be empty"))
+///     } else {
+///         Ok(())
+///     }
+/// }
+/// ```
+///
 /// ### Changing the serialization strategy
 ///
 /// BonsaiDb uses [`transmog`](https://github.com/khonsulabs/transmog) to allow
@@ -248,10 +291,126 @@ pub trait Collection: Send + Sync {
 
     /// If a [`KeyId`] is returned, this collection will be stored encrypted
     /// at-rest using the key specified.
+    ///
+    /// The entries of this collection's views are encrypted using the same
+    /// key. Encryption is applied to the stored pages rather than to each key
+    /// individually, so the ordering of view keys is preserved and encrypted
+    /// views support range queries.
     #[must_use]
     fn encryption_key() -> Option<KeyId> {
         None
     }
+
+    /// The maximum number of previous revisions of each document to retain.
+    /// When a document is updated or deleted, its previous revision is
+    /// retained in this collection's history, and the oldest revisions beyond
+    /// this limit are removed. Past revisions can be retrieved using
+    /// [`LowLevelConnection::history()`](crate::connection::LowLevelConnection::history)
+    /// and
+    /// [`LowLevelConnection::get_revision()`](crate::connection::LowLevelConnection::get_revision).
+    ///
+    /// The default implementation returns 0, which disables history
+    /// retention.
+    #[must_use]
+    fn history_limit() -> u32 {
+        0
+    }
+
+    /// The strategy used to assign ids to documents that are inserted without
+    /// an id. See [`IdStrategy`] for the available strategies and the primary
+    /// key types they support.
+    ///
+    /// The default implementation returns [`IdStrategy::Sequential`].
+    #[must_use]
+    fn id_strategy() -> IdStrategy {
+        IdStrategy::Sequential
+    }
+
+    /// If true, a [`DocumentChange`](crate::pubsub::DocumentChange) is
+    /// published to
+    /// [`document_changes_topic()`](crate::pubsub::document_changes_topic) for
+    /// each document in this collection that a transaction changes. Changes
+    /// are published after the transaction has been committed.
+    ///
+    /// The default implementation returns false.
+    #[must_use]
+    fn publish_changes() -> bool {
+        false
+    }
+
+    /// Validates the changes a transaction is about to make to documents in
+    /// this collection. If an error is returned, the transaction is aborted
+    /// and the error is returned to the caller. [`Error::other()`] can be used
+    /// to return an application-specific error.
+    ///
+    /// This function is invoked once per transaction with every change the
+    /// transaction makes to this collection, after all operations have been
+    /// executed but before any changes are committed. This allows enforcing
+    /// invariants that [unique
+    /// views](crate::schema::view::ViewUpdatePolicy::Unique) cannot express.
+    ///
+    /// The default implementation accepts all changes.
+    #[allow(unused_variables)]
+    fn validate_changes(changes: &[ProposedChange<'_>]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Validates the serialized `contents` of a document being inserted or
+    /// updated in this collection. If an error is returned, the write is
+    /// rejected with [`Error::ValidationFailed`] containing the returned
+    /// details, and the transaction is aborted.
+    ///
+    /// Unlike [`validate_changes()`](Self::validate_changes), this function is
+    /// invoked for each document as it is written, which makes it suitable
+    /// for checking that each document is well-formed. To validate the
+    /// deserialized contents, use
+    /// [`SerializedCollection::deserialize()`], or derive this function using
+    /// the `validate` parameter of the `Collection` derive macro.
+    ///
+    /// The default implementation accepts all documents.
+    #[allow(unused_variables)]
+    fn validate_document(contents: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A strategy for assigning ids to documents inserted without an id. See
+/// [`Collection::id_strategy()`].
+///
+/// Documents are stored ordered by the [`Key`] encoding of their ids. Because
+/// integers are encoded in big-endian byte order, the order of documents
+/// returned from functions such as [`SerializedCollection::all()`] and
+/// [`SerializedCollection::list()`] follows the numeric order of their ids.
+/// With [`IdStrategy::Sequential`], this is the order the documents were
+/// inserted in. With the other strategies, this order no longer reveals the
+/// order in which documents were inserted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum IdStrategy {
+    /// Ids are assigned by calling [`Key::next_value()`] on the largest id
+    /// currently stored in the collection, or [`Key::first_value()`] if the
+    /// collection is empty. Ids are monotonically increasing, which reveals
+    /// the order in which documents were inserted.
+    ///
+    /// This strategy supports any primary key that implements
+    /// [`Key::next_value()`].
+    #[default]
+    Sequential,
+    /// Ids are chosen at random.
+    ///
+    /// This strategy supports `u64` primary keys and, if the `uuid` feature is
+    /// enabled, `uuid::Uuid` primary keys, which are assigned version 4 UUIDs.
+    /// Defining a collection with any other primary key returns an error.
+    Random,
+    /// Ids are `u64`s containing the number of milliseconds since the Unix
+    /// epoch in the upper 42 bits, followed by 22 random bits. Ids are ordered
+    /// by the millisecond they were created in, which keeps recently inserted
+    /// documents near each other, but documents created within the same
+    /// millisecond are ordered randomly. This is similar to the "Snowflake"
+    /// and ULID schemes.
+    ///
+    /// This strategy only supports `u64` primary keys. Defining a collection
+    /// with any other primary key returns an error.
+    TimeOrderedRandom,
 }
 
 /// A collection that knows how to serialize and deserialize documents to an associated type.
@@ -384,6 +543,87 @@ pub trait SerializedCollection: Collection {
         Ok(possible_doc.as_ref().map(TryInto::try_into).transpose()?)
     }
 
+    /// Gets `revision` of the [`CollectionDocument`] with `id` from
+    /// `connection`. The revision can either be the document's current
+    /// revision or a revision retained in this collection's history. See
+    /// [`Collection::history_limit()`] for more information.
+    ///
+    /// Returns `None` if the revision is no longer retained. If this
+    /// collection does not retain history and `revision` is not the current
+    /// revision, [`Error::HistoryNotRetained`] is returned.
+    fn get_revision<C, PrimaryKey>(
+        id: &PrimaryKey,
+        revision: Revision,
+        connection: &C,
+    ) -> Result<Option<CollectionDocument<Self>>, Error>
+    where
+        C: Connection,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey>,
+        Self: Sized,
+    {
+        let possible_doc = connection.get_revision::<Self, _>(id, revision)?;
+        possible_doc.as_ref().map(TryInto::try_into).transpose()
+    }
+
+    /// Gets `revision` of the [`CollectionDocument`] with `id` from
+    /// `connection`. See [`Self::get_revision()`] for more information.
+    async fn get_revision_async<C, PrimaryKey>(
+        id: &PrimaryKey,
+        revision: Revision,
+        connection: &C,
+    ) -> Result<Option<CollectionDocument<Self>>, Error>
+    where
+        C: AsyncConnection,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey>,
+        Self: Sized,
+    {
+        let possible_doc = connection.get_revision::<Self, _>(id, revision).await?;
+        Ok(possible_doc.as_ref().map(TryInto::try_into).transpose()?)
+    }
+
+    /// Compares the contents of revisions `from` and `to` of the document with
+    /// `id`, returning the fields that were added, removed, or changed. See
+    /// [`DocumentDiff`] for how the contents are compared.
+    ///
+    /// Each revision must either be the document's current revision or be
+    /// retained in this collection's history. If either revision can't be
+    /// found, [`Error::RevisionNotFound`] is returned.
+    fn diff_revisions<C, PrimaryKey>(
+        id: &PrimaryKey,
+        from: Revision,
+        to: Revision,
+        connection: &C,
+    ) -> Result<DocumentDiff, Error>
+    where
+        C: Connection,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey>,
+        Self::Contents: Serialize,
+        Self: Sized,
+    {
+        let from_doc = connection.get_revision::<Self, _>(id, from)?;
+        let to_doc = connection.get_revision::<Self, _>(id, to)?;
+        diff_documents::<Self, _>(id, from, from_doc, to, to_doc)
+    }
+
+    /// Compares the contents of revisions `from` and `to` of the document with
+    /// `id`. See [`Self::diff_revisions()`] for more information.
+    async fn diff_revisions_async<C, PrimaryKey>(
+        id: &PrimaryKey,
+        from: Revision,
+        to: Revision,
+        connection: &C,
+    ) -> Result<DocumentDiff, Error>
+    where
+        C: AsyncConnection,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey>,
+        Self::Contents: Serialize,
+        Self: Sized,
+    {
+        let from_doc = connection.get_revision::<Self, _>(id, from).await?;
+        let to_doc = connection.get_revision::<Self, _>(id, to).await?;
+        diff_documents::<Self, _>(id, from, from_doc, to, to_doc)
+    }
+
     /// Retrieves all documents matching `ids`. Documents that are not found
     /// are not returned, but no error will be generated.
     ///
@@ -417,6 +657,44 @@ pub trait SerializedCollection: Collection {
             .and_then(|docs| docs.collection_documents())
     }
 
+    /// Retrieves the document for each id in `ids`. Unlike
+    /// [`Self::get_multiple()`], the returned list contains one entry per id
+    /// in the same order as `ids`, with `None` for each id whose document was
+    /// not found.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::Connection;
+    /// # fn test_fn<C: Connection>(db: C) -> Result<(), Error> {
+    /// let ids = [42, 43];
+    /// let docs = MyCollection::get_multiple_optional(&ids, &db)?;
+    /// for (id, doc) in ids.iter().zip(docs) {
+    ///     match doc {
+    ///         Some(doc) => println!("Retrieved #{id}: {:?}", doc.contents),
+    ///         None => println!("#{id} was not found"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn get_multiple_optional<'id, C, DocumentIds, PrimaryKey, I>(
+        ids: DocumentIds,
+        connection: &C,
+    ) -> Result<Vec<Option<CollectionDocument<Self>>>, Error>
+    where
+        C: Connection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey> + 'id,
+        Self: Sized,
+    {
+        connection
+            .get_multiple_optional::<Self, _, _, _>(ids)?
+            .iter()
+            .map(|doc| doc.as_ref().map(TryInto::try_into).transpose())
+            .collect()
+    }
+
     /// Retrieves all documents matching `ids`. Documents that are not found
     /// are not returned, but no error will be generated.
     ///
@@ -453,6 +731,47 @@ pub trait SerializedCollection: Collection {
             .and_then(|docs| docs.collection_documents())
     }
 
+    /// Retrieves the document for each id in `ids`. Unlike
+    /// [`Self::get_multiple_async()`], the returned list contains one entry
+    /// per id in the same order as `ids`, with `None` for each id whose
+    /// document was not found.
+    ///
+    /// ```rust
+    /// # bonsaidb_core::__doctest_prelude!();
+    /// # use bonsaidb_core::connection::AsyncConnection;
+    /// # fn test_fn<C: AsyncConnection>(db: C) -> Result<(), Error> {
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let ids = [42, 43];
+    /// let docs = MyCollection::get_multiple_optional_async(&ids, &db).await?;
+    /// for (id, doc) in ids.iter().zip(docs) {
+    ///     match doc {
+    ///         Some(doc) => println!("Retrieved #{id}: {:?}", doc.contents),
+    ///         None => println!("#{id} was not found"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    async fn get_multiple_optional_async<'id, C, DocumentIds, PrimaryKey, I>(
+        ids: DocumentIds,
+        connection: &C,
+    ) -> Result<Vec<Option<CollectionDocument<Self>>>, Error>
+    where
+        C: AsyncConnection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey, IntoIter = I> + Send + Sync,
+        I: Iterator<Item = &'id PrimaryKey> + Send + Sync,
+        PrimaryKey: KeyEncoding<Self::PrimaryKey> + 'id,
+        Self: Sized,
+    {
+        connection
+            .get_multiple_optional::<Self, _, _, _>(ids)
+            .await?
+            .iter()
+            .map(|doc| doc.as_ref().map(TryInto::try_into).transpose())
+            .collect()
+    }
+
     /// Retrieves all documents matching the range of `ids`.
     ///
     /// ```rust
@@ -1283,6 +1602,39 @@ where
     }
 }
 
+/// Compares the contents of two revisions of the document with `id`, which
+/// were retrieved by [`SerializedCollection::diff_revisions()`].
+fn diff_documents<Cl, PrimaryKey>(
+    id: &PrimaryKey,
+    from: Revision,
+    from_doc: Option<OwnedDocument>,
+    to: Revision,
+    to_doc: Option<OwnedDocument>,
+) -> Result<DocumentDiff, Error>
+where
+    Cl: SerializedCollection,
+    Cl::Contents: Serialize,
+    PrimaryKey: KeyEncoding<Cl::PrimaryKey>,
+{
+    let contents = |revision: Revision, doc: Option<OwnedDocument>| -> Result<_, Error> {
+        match doc {
+            Some(doc) => Ok(contents_value(&Cl::document_contents(&doc)?)?),
+            None => Err(Error::RevisionNotFound {
+                collection: Cl::collection_name(),
+                id: Box::new(DocumentId::new(id)?),
+                revision,
+            }),
+        }
+    };
+
+    Ok(DocumentDiff::between(
+        from,
+        &contents(from, from_doc)?,
+        to,
+        &contents(to, to_doc)?,
+    ))
+}
+
 /// An error from inserting a [`CollectionDocument`].
 #[derive(thiserror::Error, Debug)]
 #[error("{error}")]
@@ -0,0 +1,154 @@
+use std::fmt::Debug;
+
+use crate::Error;
+
+/// A function that upgrades the documents stored in a database.
+type MigrationFn<C> = Box<dyn Fn(&C) -> Result<(), Error> + Send + Sync>;
+
+/// An ordered list of steps that upgrade databases created by older versions
+/// of a [`Schema`](super::Schema). Steps are defined by implementing
+/// [`Schema::define_migrations()`](super::Schema::define_migrations).
+///
+/// Each step upgrades a database to a schema version. The current version of
+/// a schema is the version of its last step, or 0 if it has no steps. A
+/// database's version is stored alongside its name and schema when it is
+/// created, so new databases never run any steps. Databases that were created
+/// before any steps were defined are at version 0.
+///
+/// When a database is opened at an older version, each step with a newer
+/// version is run in order. The database's stored version is updated after
+/// each step completes, so if a step fails, only that step and the steps
+/// after it are run the next time the database is opened.
+///
+/// ```rust
+/// use bonsaidb_core::connection::Connection;
+/// use bonsaidb_core::schema::{Migrations, Schema, SchemaName, Schematic};
+/// # use bonsaidb_core::Error;
+///
+/// pub struct MySchema;
+///
+/// impl Schema for MySchema {
+///     fn schema_name() -> SchemaName {
+///         SchemaName::new("khonsulabs", "my-schema")
+///     }
+///
+///     fn define_collections(schema: &mut Schematic) -> Result<(), Error> {
+///         Ok(())
+///     }
+///
+///     fn define_migrations<C: Connection>(migrations: &mut Migrations<C>) {
+///         migrations.step(1, |db: &C| {
+///             // Rewrite the documents whose layout changed in version 1.
+///             Ok(())
+///         });
+///     }
+/// }
+/// ```
+pub struct Migrations<C> {
+    steps: Vec<(u32, MigrationFn<C>)>,
+}
+
+impl<C> Migrations<C> {
+    /// Returns the migrations defined by `S`.
+    #[must_use]
+    pub fn for_schema<S: super::Schema>() -> Self
+    where
+        C: crate::connection::Connection,
+    {
+        let mut migrations = Self::default();
+        S::define_migrations(&mut migrations);
+        migrations
+    }
+
+    /// Adds a step that upgrades a database to `version` by invoking
+    /// `migrate`. Each step should make its changes in one or more
+    /// transactions using the provided connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is 0 or is not greater than the version of the
+    /// previous step.
+    pub fn step<F>(&mut self, version: u32, migrate: F) -> &mut Self
+    where
+        F: Fn(&C) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        assert!(
+            version > self.version(),
+            "migration versions must be greater than 0 and strictly increasing"
+        );
+        self.steps.push((version, Box::new(migrate)));
+        self
+    }
+
+    /// Returns the version of the last step, or 0 if there are no steps.
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.steps.last().map_or(0, |(version, _)| *version)
+    }
+
+    /// Runs each step whose version is greater than `current_version` in
+    /// order. After each step completes, `completed` is invoked with the
+    /// step's version so that the new version can be recorded.
+    ///
+    /// Returns the version the database was migrated to.
+    pub fn migrate<F>(
+        &self,
+        current_version: u32,
+        connection: &C,
+        mut completed: F,
+    ) -> Result<u32, Error>
+    where
+        F: FnMut(u32) -> Result<(), Error>,
+    {
+        let mut migrated_to = current_version;
+        for (version, migrate) in &self.steps {
+            if *version > current_version {
+                migrate(connection)?;
+                completed(*version)?;
+                migrated_to = *version;
+            }
+        }
+        Ok(migrated_to)
+    }
+}
+
+impl<C> Default for Migrations<C> {
+    fn default() -> Self {
+        Self { steps: Vec::new() }
+    }
+}
+
+impl<C> Debug for Migrations<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.steps.iter().map(|(version, _)| version))
+            .finish()
+    }
+}
+
+#[test]
+fn migrate_runs_pending_steps() {
+    let mut migrations = Migrations::<std::cell::Cell<u32>>::default();
+    migrations
+        .step(1, |runs| {
+            runs.set(runs.get() + 1);
+            Ok(())
+        })
+        .step(3, |runs| {
+            runs.set(runs.get() + 10);
+            Ok(())
+        });
+    assert_eq!(migrations.version(), 3);
+
+    let runs = std::cell::Cell::new(0);
+    let mut completed = Vec::new();
+    let version = migrations
+        .migrate(1, &runs, |version| {
+            completed.push(version);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(version, 3);
+    assert_eq!(runs.get(), 10);
+    assert_eq!(completed, vec![3]);
+}
@@ -0,0 +1,106 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::document::{CollectionDocument, Emit};
+use crate::key::Key;
+use crate::schema::view::map::Mappings;
+use crate::schema::view::{DefaultViewSerialization, ViewUpdatePolicy};
+use crate::schema::{
+    CollectionMapReduce, Name, SerializedCollection, View, ViewMapResult, ViewSchema,
+};
+
+/// A [`SerializedCollection`] whose documents have a natural unique key, such
+/// as an email address or SKU, that is separate from the document's id.
+///
+/// Uniqueness is enforced by [`ByNaturalKey`], a
+/// [unique](ViewUpdatePolicy::Unique) view of each document's natural key.
+/// Inserting or updating a document whose natural key is already used by
+/// another document fails with
+/// [`Error::UniqueKeyViolation`](crate::Error::UniqueKeyViolation).
+///
+/// When deriving [`Collection`](crate::schema::Collection), marking a field
+/// with `#[natural_key]` implements this trait using the field and defines
+/// [`ByNaturalKey`] automatically:
+///
+/// ```rust
+/// use bonsaidb_core::schema::Collection;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, Collection)]
+/// #[collection(name = "users")]
+/// # #[collection(core = bonsaidb_core)]
+/// struct User {
+///     #[natural_key]
+///     email: String,
+///     name: String,
+/// }
+/// ```
+///
+/// When implementing this trait manually, the view must be defined in
+/// [`Collection::define_views()`](crate::schema::Collection::define_views)
+/// using `schema.define_view(ByNaturalKey::<Self>::default())`.
+pub trait NaturalKey: SerializedCollection + Sized {
+    /// The type of the natural key.
+    type NaturalKey: for<'k> Key<'k> + PartialEq + Send + Sync + 'static;
+
+    /// Returns the natural key of `contents`. Documents that return `None`
+    /// are not checked for uniqueness.
+    fn natural_key(contents: &Self::Contents) -> Option<Self::NaturalKey>;
+}
+
+/// A [unique](ViewUpdatePolicy::Unique) view of the natural keys of the
+/// documents in the [`NaturalKey`] collection `C`. Querying this view by key
+/// finds the document with that natural key.
+pub struct ByNaturalKey<C>(PhantomData<fn() -> C>);
+
+impl<C> Default for ByNaturalKey<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C> Clone for ByNaturalKey<C> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<C> Debug for ByNaturalKey<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ByNaturalKey").finish()
+    }
+}
+
+impl<C: NaturalKey> View for ByNaturalKey<C> {
+    type Collection = C;
+    type Key = C::NaturalKey;
+    type Value = ();
+
+    fn name(&self) -> Name {
+        Name::new("natural-key")
+    }
+}
+
+impl<C: NaturalKey> ViewSchema for ByNaturalKey<C> {
+    type MappedKey<'doc> = C::NaturalKey;
+    type View = Self;
+
+    fn update_policy(&self) -> ViewUpdatePolicy {
+        ViewUpdatePolicy::Unique
+    }
+
+    fn version(&self) -> u64 {
+        1
+    }
+}
+
+impl<C: NaturalKey> CollectionMapReduce for ByNaturalKey<C> {
+    fn map<'doc>(&self, document: CollectionDocument<C>) -> ViewMapResult<'doc, Self> {
+        match C::natural_key(&document.contents) {
+            Some(key) => document.header.emit_key(key),
+            None => Ok(Mappings::none()),
+        }
+    }
+}
+
+impl<C: NaturalKey> DefaultViewSerialization for ByNaturalKey<C> {}
@@ -1,20 +1,29 @@
 use std::any::TypeId;
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use derive_where::derive_where;
 
 use crate::document::{BorrowedDocument, DocumentId, KeyId};
 use crate::key::{ByteSource, Key, KeyDescription};
-use crate::schema::collection::Collection;
+use crate::schema::collection::{Collection, IdStrategy};
 use crate::schema::view::map::{self, MappedValue};
 use crate::schema::view::{
     self, MapReduce, Serialized, SerializedView, ViewSchema, ViewUpdatePolicy,
 };
 use crate::schema::{CollectionName, Schema, SchemaName, View, ViewName};
+use crate::transaction::ProposedChange;
 use crate::Error;
 
+/// The maximum number of levels a deletion can cascade through. See
+/// [`Schematic::define_delete_cascade()`].
+pub const MAX_DELETE_CASCADE_DEPTH: usize = 16;
+
+type ChangeValidator = fn(&[ProposedChange<'_>]) -> Result<(), Error>;
+type DocumentValidator = fn(&[u8]) -> Result<(), String>;
+
 /// A collection of defined collections and views.
 pub struct Schematic {
     /// The name of the schema this was built from.
@@ -23,10 +32,17 @@ pub struct Schematic {
     collections_by_type_id: HashMap<TypeId, CollectionName>,
     collection_encryption_keys: HashMap<CollectionName, KeyId>,
     collection_id_generators: HashMap<CollectionName, Box<dyn IdGenerator>>,
+    collection_id_strategies: HashMap<CollectionName, IdStrategy>,
+    collection_validators: HashMap<CollectionName, ChangeValidator>,
+    collection_document_validators: HashMap<CollectionName, DocumentValidator>,
+    collection_history_limits: HashMap<CollectionName, u32>,
+    collections_publishing_changes: HashSet<CollectionName>,
     views: HashMap<TypeId, Box<dyn view::Serialized>>,
     views_by_name: HashMap<ViewName, TypeId>,
     views_by_collection: HashMap<CollectionName, Vec<TypeId>>,
+    related_views_by_collection: HashMap<CollectionName, Vec<TypeId>>,
     eager_views_by_collection: HashMap<CollectionName, Vec<TypeId>>,
+    delete_cascades: HashMap<CollectionName, Vec<ViewName>>,
 }
 
 impl Schematic {
@@ -38,10 +54,17 @@ impl Schematic {
             collections_by_type_id: HashMap::new(),
             collection_encryption_keys: HashMap::new(),
             collection_id_generators: HashMap::new(),
+            collection_id_strategies: HashMap::new(),
+            collection_validators: HashMap::new(),
+            collection_document_validators: HashMap::new(),
+            collection_history_limits: HashMap::new(),
+            collections_publishing_changes: HashSet::new(),
             views: HashMap::new(),
             views_by_name: HashMap::new(),
             views_by_collection: HashMap::new(),
+            related_views_by_collection: HashMap::new(),
             eager_views_by_collection: HashMap::new(),
+            delete_cascades: HashMap::new(),
         };
         S::define_collections(&mut schematic)?;
         Ok(schematic)
@@ -57,8 +80,34 @@ impl Schematic {
                 if let Some(key) = C::encryption_key() {
                     self.collection_encryption_keys.insert(name.clone(), key);
                 }
-                self.collection_id_generators
-                    .insert(name, Box::<KeyIdGenerator<C>>::default());
+                let history_limit = C::history_limit();
+                if history_limit > 0 {
+                    self.collection_history_limits
+                        .insert(name.clone(), history_limit);
+                }
+                if C::publish_changes() {
+                    self.collections_publishing_changes.insert(name.clone());
+                }
+                match C::id_strategy() {
+                    IdStrategy::Sequential => {
+                        self.collection_id_generators
+                            .insert(name.clone(), Box::<KeyIdGenerator<C>>::default());
+                    }
+                    strategy => {
+                        let generator = RandomIdGenerator::new(
+                            &name,
+                            &KeyDescription::for_key::<C::PrimaryKey>(),
+                            strategy,
+                        )?;
+                        self.collection_id_generators
+                            .insert(name.clone(), Box::new(generator));
+                        self.collection_id_strategies.insert(name.clone(), strategy);
+                    }
+                }
+                self.collection_validators
+                    .insert(name.clone(), C::validate_changes);
+                self.collection_document_validators
+                    .insert(name, C::validate_document);
                 entry.insert(KeyDescription::for_key::<C::PrimaryKey>());
                 C::define_views(self)
             }
@@ -91,9 +140,25 @@ impl Schematic {
 
         let collection = instance.collection();
         let eager = instance.update_policy().is_eager();
+        let related_collections = instance.related_collections();
+        for (index, related) in related_collections.iter().enumerate() {
+            if eager || related == &collection || related_collections[..index].contains(related) {
+                return Err(Error::InvalidRelatedCollection {
+                    view: name,
+                    collection: related.clone(),
+                });
+            }
+        }
         self.views.insert(TypeId::of::<V>(), Box::new(instance));
         self.views_by_name.insert(name, TypeId::of::<V>());
 
+        for related in related_collections {
+            self.related_views_by_collection
+                .entry(related)
+                .or_insert_with(Vec::new)
+                .push(TypeId::of::<V>());
+        }
+
         if eager {
             let unique_views = self
                 .eager_views_by_collection
@@ -110,6 +175,44 @@ impl Schematic {
         Ok(())
     }
 
+    /// Configures deleting a document in `Parent` to also delete all
+    /// documents that `view` maps to the deleted document's primary key.
+    ///
+    /// The cascaded deletions are performed in the same transaction as the
+    /// original deletion, and cascade further if the deleted documents'
+    /// collection has cascades defined. Each cascaded deletion requires
+    /// permission to delete the document, and is not included in the
+    /// transaction's results. Each document is deleted at most once,
+    /// which prevents cycles from cascading forever. Cascades that nest deeper
+    /// than [`MAX_DELETE_CASCADE_DEPTH`] cause the transaction to fail with
+    /// [`Error::DeleteCascadeTooDeep`].
+    ///
+    /// `view` must also be defined in this schema using
+    /// [`Self::define_view()`].
+    pub fn define_delete_cascade<Parent, V>(&mut self, view: &V) -> Result<(), Error>
+    where
+        Parent: Collection + 'static,
+        V: View<Key = Parent::PrimaryKey>,
+    {
+        let cascades = self
+            .delete_cascades
+            .entry(Parent::collection_name())
+            .or_default();
+        let view_name = view.view_name();
+        if cascades.contains(&view_name) {
+            return Err(Error::ViewAlreadyRegistered(view_name));
+        }
+        cascades.push(view_name);
+        Ok(())
+    }
+
+    /// Returns the views whose mapped documents should be deleted when a
+    /// document in `collection` is deleted. See
+    /// [`Self::define_delete_cascade()`].
+    pub fn delete_cascades(&self, collection: &CollectionName) -> impl Iterator<Item = &ViewName> {
+        self.delete_cascades.get(collection).into_iter().flatten()
+    }
+
     /// Returns `true` if this schema contains the collection `C`.
     #[must_use]
     pub fn contains_collection<C: Collection + 'static>(&self) -> bool {
@@ -177,6 +280,22 @@ impl Schematic {
             })
     }
 
+    /// Iterates over all views that map documents from `collection` as one of
+    /// their [related collections](ViewSchema::related_collections).
+    pub fn views_related_to_collection(
+        &self,
+        collection: &CollectionName,
+    ) -> impl Iterator<Item = &'_ dyn view::Serialized> {
+        self.related_views_by_collection
+            .get(collection)
+            .into_iter()
+            .flat_map(|view_ids| {
+                view_ids
+                    .iter()
+                    .filter_map(|id| self.views.get(id).map(AsRef::as_ref))
+            })
+    }
+
     /// Iterates over all views that are eagerly updated that belong to
     /// `collection`.
     pub fn eager_views_in_collection(
@@ -193,12 +312,71 @@ impl Schematic {
             })
     }
 
+    /// Invokes [`Collection::validate_changes()`] for the collection with the
+    /// given name.
+    pub fn validate_changes(
+        &self,
+        collection: &CollectionName,
+        changes: &[ProposedChange<'_>],
+    ) -> Result<(), Error> {
+        let validator = self
+            .collection_validators
+            .get(collection)
+            .ok_or(Error::CollectionNotFound)?;
+        validator(changes)
+    }
+
+    /// Invokes [`Collection::validate_document()`] for the collection with
+    /// the given name, returning [`Error::ValidationFailed`] if `contents` is
+    /// rejected.
+    pub fn validate_document(
+        &self,
+        collection: &CollectionName,
+        contents: &[u8],
+    ) -> Result<(), Error> {
+        let validator = self
+            .collection_document_validators
+            .get(collection)
+            .ok_or(Error::CollectionNotFound)?;
+        validator(contents).map_err(|details| Error::ValidationFailed {
+            collection: collection.clone(),
+            details,
+        })
+    }
+
     /// Returns a collection's default encryption key, if one was defined.
     #[must_use]
     pub fn encryption_key_for_collection(&self, collection: &CollectionName) -> Option<&KeyId> {
         self.collection_encryption_keys.get(collection)
     }
 
+    /// Returns the strategy used to assign ids to documents in `collection`.
+    /// See [`Collection::id_strategy()`].
+    #[must_use]
+    pub fn id_strategy(&self, collection: &CollectionName) -> IdStrategy {
+        self.collection_id_strategies
+            .get(collection)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of previous revisions retained for each document in
+    /// `collection`. See [`Collection::history_limit()`].
+    #[must_use]
+    pub fn history_limit(&self, collection: &CollectionName) -> u32 {
+        self.collection_history_limits
+            .get(collection)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns true if changes to documents in `collection` are published. See
+    /// [`Collection::publish_changes()`].
+    #[must_use]
+    pub fn publishes_changes(&self, collection: &CollectionName) -> bool {
+        self.collections_publishing_changes.contains(collection)
+    }
+
     /// Returns a list of all collections contained in this schematic.
     pub fn collections(&self) -> impl Iterator<Item = &CollectionName> {
         self.contained_collections.keys()
@@ -223,10 +401,21 @@ impl Debug for Schematic {
                 &self.collection_encryption_keys,
             )
             .field("collection_id_generators", &self.collection_id_generators)
+            .field("collection_id_strategies", &self.collection_id_strategies)
+            .field("collection_history_limits", &self.collection_history_limits)
+            .field(
+                "collections_publishing_changes",
+                &self.collections_publishing_changes,
+            )
             .field("views", &views)
             .field("views_by_name", &self.views_by_name)
             .field("views_by_collection", &self.views_by_collection)
+            .field(
+                "related_views_by_collection",
+                &self.related_views_by_collection,
+            )
             .field("eager_views_by_collection", &self.eager_views_by_collection)
+            .field("delete_cascades", &self.delete_cascades)
             .finish()
     }
 }
@@ -262,6 +451,10 @@ where
         self.view.view_name()
     }
 
+    fn related_collections(&self) -> Vec<CollectionName> {
+        self.schema.related_collections()
+    }
+
     fn map(&self, document: &BorrowedDocument<'_>) -> Result<Vec<map::Serialized>, view::Error> {
         let mappings = self.schema.map(document)?;
 
@@ -272,6 +465,20 @@ where
             .map_err(view::Error::key_serialization)
     }
 
+    fn map_related(
+        &self,
+        collection: &CollectionName,
+        document: &BorrowedDocument<'_>,
+    ) -> Result<Vec<map::Serialized>, view::Error> {
+        let mappings = self.schema.map_related(collection, document)?;
+
+        mappings
+            .iter()
+            .map(map::Map::serialized::<V>)
+            .collect::<Result<_, _>>()
+            .map_err(view::Error::key_serialization)
+    }
+
     fn reduce(&self, mappings: &[(&[u8], &[u8])], rereduce: bool) -> Result<Vec<u8>, view::Error> {
         let mappings = mappings
             .iter()
@@ -290,6 +497,12 @@ where
 
         V::serialize(&reduced_value).map_err(view::Error::from)
     }
+
+    fn group_prefix(&self, key: &[u8], group_level: usize) -> Result<Vec<u8>, view::Error> {
+        <V as View>::Key::from_ord_bytes(ByteSource::Borrowed(key))
+            .and_then(|key| key.group_prefix(group_level))
+            .map_err(view::Error::key_serialization)
+    }
 }
 
 pub trait IdGenerator: Debug + Send + Sync {
@@ -318,6 +531,74 @@ where
     }
 }
 
+/// Generates ids for collections using [`IdStrategy::Random`] or
+/// [`IdStrategy::TimeOrderedRandom`].
+#[derive(Debug)]
+pub struct RandomIdGenerator {
+    kind: RandomIdKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RandomIdKind {
+    U64,
+    TimeOrderedU64,
+    #[cfg(feature = "uuid")]
+    Uuid,
+}
+
+impl RandomIdGenerator {
+    /// Returns a generator of `strategy` ids for `collection`, or an error if
+    /// `strategy` doesn't support the collection's primary key.
+    pub fn new(
+        collection: &CollectionName,
+        key: &KeyDescription,
+        strategy: IdStrategy,
+    ) -> Result<Self, Error> {
+        let time_ordered = strategy == IdStrategy::TimeOrderedRandom;
+        if *key == KeyDescription::for_key::<u64>() {
+            let kind = if time_ordered {
+                RandomIdKind::TimeOrderedU64
+            } else {
+                RandomIdKind::U64
+            };
+            return Ok(Self { kind });
+        }
+
+        #[cfg(feature = "uuid")]
+        if !time_ordered && *key == KeyDescription::for_key::<uuid::Uuid>() {
+            return Ok(Self {
+                kind: RandomIdKind::Uuid,
+            });
+        }
+
+        Err(Error::other(
+            collection,
+            format!("IdStrategy::{strategy:?} does not support primary keys of type {key:?}"),
+        ))
+    }
+}
+
+impl IdGenerator for RandomIdGenerator {
+    fn next_id(&self, _id: Option<DocumentId>) -> Result<DocumentId, Error> {
+        match self.kind {
+            RandomIdKind::U64 => DocumentId::new::<u64, _>(&rand::random::<u64>()),
+            RandomIdKind::TimeOrderedU64 => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+                // Truncating the timestamp to 42 bits is intentional.
+                #[allow(clippy::cast_possible_truncation)]
+                let timestamp = (timestamp as u64) & ((1 << 42) - 1);
+                let id = timestamp << 22 | (rand::random::<u64>() >> 42);
+                DocumentId::new::<u64, _>(&id)
+            }
+            #[cfg(feature = "uuid")]
+            RandomIdKind::Uuid => DocumentId::new::<uuid::Uuid, _>(&uuid::Uuid::new_v4()),
+        }
+    }
+}
+
 #[test]
 fn schema_tests() -> anyhow::Result<()> {
     use crate::test_util::{Basic, BasicCount};
@@ -328,7 +609,7 @@ fn schema_tests() -> anyhow::Result<()> {
         schema.collections_by_type_id[&TypeId::of::<Basic>()],
         Basic::collection_name()
     );
-    assert_eq!(schema.views.len(), 6);
+    assert_eq!(schema.views.len(), 7);
     assert_eq!(
         schema.views[&TypeId::of::<BasicCount>()].view_name(),
         View::view_name(&BasicCount)
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::fmt::{Debug, Display};
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::key::KeyDescription;
 use crate::schema::view::ViewUpdatePolicy;
@@ -30,6 +32,77 @@ impl SchemaSummary {
     pub fn collections(&self) -> impl Iterator<Item = &CollectionSummary> {
         self.collections.values()
     }
+
+    /// Returns the summary of the named view, if the schema contains it.
+    #[must_use]
+    pub fn view(&self, name: &ViewName) -> Option<&ViewSummary> {
+        self.collection(&name.collection)
+            .and_then(|collection| collection.view(name))
+    }
+
+    /// Returns a stable fingerprint of this schema's structure.
+    ///
+    /// The fingerprint is computed from the schema's name, the names and
+    /// primary key types of its collections, and the names, versions, and key
+    /// types of its views. Two summaries with equal fingerprints describe
+    /// schemas that store and query data in the same way. Comparing the
+    /// fingerprint of a client's compiled schema against the one reported by a
+    /// server detects when the two were built from different definitions.
+    #[must_use]
+    pub fn fingerprint(&self) -> SchemaFingerprint {
+        let mut hasher = Sha256::default();
+        hash_str(&mut hasher, &self.name.to_string());
+
+        let mut collections = self.collections.values().collect::<Vec<_>>();
+        collections.sort_by(|a, b| a.name.cmp(&b.name));
+        hasher.update((collections.len() as u64).to_be_bytes());
+        for collection in collections {
+            hash_str(&mut hasher, &collection.name.to_string());
+            hash_key_description(&mut hasher, &collection.primary_key);
+
+            let mut views = collection.views.values().collect::<Vec<_>>();
+            views.sort_by(|a, b| a.name.cmp(&b.name));
+            hasher.update((views.len() as u64).to_be_bytes());
+            for view in views {
+                hash_str(&mut hasher, &view.name.to_string());
+                hasher.update(view.version.to_be_bytes());
+                hash_key_description(&mut hasher, &view.key);
+            }
+        }
+
+        SchemaFingerprint(hasher.finalize().into())
+    }
+}
+
+fn hash_str(hasher: &mut Sha256, value: &str) {
+    hasher.update((value.len() as u64).to_be_bytes());
+    hasher.update(value.as_bytes());
+}
+
+fn hash_key_description(hasher: &mut Sha256, key: &KeyDescription) {
+    let encoded = pot::to_vec(key).expect("key descriptions are always serializable");
+    hasher.update((encoded.len() as u64).to_be_bytes());
+    hasher.update(encoded);
+}
+
+/// A stable fingerprint of a schema's structure, returned from
+/// [`SchemaSummary::fingerprint()`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SchemaFingerprint(pub [u8; 32]);
+
+impl Debug for SchemaFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SchemaFingerprint({self})")
+    }
+}
+
+impl Display for SchemaFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            f.write_fmt(format_args!("{byte:02x}"))?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a> From<&'a Schematic> for SchemaSummary {
@@ -103,6 +103,9 @@ pub trait View: Sized + Send + Sync + 'static {
 /// - [`version()`](Self::version): An integer representing the view's version.
 ///   Changing this number will cause the view to be re-indexed. This is useful
 ///   when there are fundamental changes in how the view is implemented.
+/// - [`related_collections()`](Self::related_collections): Additional
+///   collections whose documents are mapped into this view using
+///   [`MapReduce::map_related()`].
 ///
 /// ## Where is this trait used?
 ///
@@ -131,6 +134,9 @@ pub trait View: Sized + Send + Sync + 'static {
 ///   - [`Unique`](ViewUpdatePolicy::Unique)
 ///
 ///   If not provided, the [`Lazy`](ViewUpdatePolicy::Lazy) policy will be used.
+/// - `related`: Sets the [related collections](Self::related_collections) of
+///   the view, such as `related = [Customer]`. If not provided, the view only
+///   maps documents from its own collection.
 ///
 /// Here is an example that showcases most of the options:
 /// ```rust
@@ -196,6 +202,18 @@ pub trait ViewSchema: Send + Sync + 'static {
     fn version(&self) -> u64 {
         0
     }
+
+    /// Returns the collections, in addition to [`View::Collection`], whose
+    /// documents are mapped into this view by [`MapReduce::map_related()`].
+    /// The provided implementation returns an empty list.
+    ///
+    /// Only [lazily updated](ViewUpdatePolicy::Lazy) views can have related
+    /// collections. Changing the related collections of an existing view
+    /// requires changing [`version()`](Self::version) so that the documents of
+    /// the new collections are mapped.
+    fn related_collections(&self) -> Vec<CollectionName> {
+        Vec::new()
+    }
 }
 
 /// The policy under which a [`View`] is updated when documents are saved.
@@ -248,6 +266,27 @@ pub trait MapReduce: ViewSchema {
     /// works](https://dev.bonsaidb.io/main/guide/about/concepts/view.html#map).
     fn map<'doc>(&self, document: &'doc BorrowedDocument<'_>) -> ViewMapResult<'doc, Self>;
 
+    /// The map function for documents from the view's [related
+    /// collections](ViewSchema::related_collections). `collection` is the name
+    /// of the collection `document` was stored in.
+    ///
+    /// Mappings emitted by related documents are stored alongside the mappings
+    /// of the view's own documents, using the related document's header as
+    /// their source. They are returned when querying, counting, and reducing
+    /// the view, but are skipped by queries that load the source documents and
+    /// by [`delete_docs()`](crate::connection::View::delete_docs), which only
+    /// operate on documents in [`View::Collection`].
+    ///
+    /// The provided implementation emits no mappings.
+    #[allow(unused_variables)]
+    fn map_related<'doc>(
+        &self,
+        collection: &CollectionName,
+        document: &'doc BorrowedDocument<'_>,
+    ) -> ViewMapResult<'doc, Self> {
+        Ok(Mappings::none())
+    }
+
     /// Returns a value that is produced by reducing a list of `mappings` into a
     /// single value. If `rereduce` is true, the values contained in the
     /// mappings have already been reduced at least one time. If an error of
@@ -338,6 +377,19 @@ where
     where
         CollectionDocument<<Self::View as View>::Collection>: 'doc;
 
+    /// The map function for documents from the view's [related
+    /// collections](ViewSchema::related_collections). See
+    /// [`MapReduce::map_related()`] for more information. The provided
+    /// implementation emits no mappings.
+    #[allow(unused_variables)]
+    fn map_related<'doc>(
+        &self,
+        collection: &CollectionName,
+        document: &'doc BorrowedDocument<'_>,
+    ) -> ViewMapResult<'doc, Self> {
+        Ok(Mappings::none())
+    }
+
     /// The reduce function for this view. If `Err(Error::ReduceUnimplemented)`
     /// is returned, queries that ask for a reduce operation will return an
     /// error. See [`CouchDB`'s Reduce/Rereduce
@@ -363,6 +415,14 @@ where
         T::map(self, CollectionDocument::try_from(document)?)
     }
 
+    fn map_related<'doc>(
+        &self,
+        collection: &CollectionName,
+        document: &'doc BorrowedDocument<'_>,
+    ) -> ViewMapResult<'doc, Self> {
+        T::map_related(self, collection, document)
+    }
+
     fn reduce(
         &self,
         mappings: &[ViewMappedValue<'_, Self>],
@@ -385,10 +445,21 @@ pub trait Serialized: Send + Sync {
     fn version(&self) -> u64;
     /// Wraps [`View::view_name`]
     fn view_name(&self) -> ViewName;
+    /// Wraps [`ViewSchema::related_collections`]
+    fn related_collections(&self) -> Vec<CollectionName>;
     /// Wraps [`MapReduce::map`]
     fn map(&self, document: &BorrowedDocument<'_>) -> Result<Vec<map::Serialized>, Error>;
+    /// Wraps [`MapReduce::map_related`]
+    fn map_related(
+        &self,
+        collection: &CollectionName,
+        document: &BorrowedDocument<'_>,
+    ) -> Result<Vec<map::Serialized>, Error>;
     /// Wraps [`MapReduce::reduce`]
     fn reduce(&self, mappings: &[(&[u8], &[u8])], rereduce: bool) -> Result<Vec<u8>, Error>;
+    /// Wraps [`Key::group_prefix`](crate::key::Key::group_prefix) for the
+    /// serialized `key`.
+    fn group_prefix(&self, key: &[u8], group_level: usize) -> Result<Vec<u8>, Error>;
 }
 
 /// Defines an unique view named `$view_name` for `$collection` with the
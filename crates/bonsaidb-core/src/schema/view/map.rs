@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 
 use arc_bytes::serde::Bytes;
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::document::{DocumentId, Header, OwnedDocument};
 use crate::schema::view::{self, ByteSource, Key, SerializedView, View, ViewSchema};
+use crate::schema::ViewName;
 
 /// A document's entry in a View's mappings.
 #[derive(Eq, PartialEq, Debug)]
@@ -228,6 +229,12 @@ impl<D, V: View> MappedDocuments<D, V> {
 
     /// Returns the mapped document at`index`, or `None` if `index >=
     /// self.len()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source document of the mapping at `index` is not
+    /// present. Use [`Self::missing_documents()`] to detect mappings whose
+    /// source documents could not be found.
     #[must_use]
     pub fn get(&self, index: usize) -> Option<MappedDocument<'_, D, V::Key, V::Value>> {
         if index < self.len() {
@@ -245,6 +252,20 @@ impl<D, V: View> MappedDocuments<D, V> {
             None
         }
     }
+
+    /// Returns the ids of the source documents that are referenced by one or
+    /// more mappings but were not found.
+    ///
+    /// This can happen when a document has been deleted and the view has not
+    /// been updated yet, such as when querying with
+    /// [`AccessPolicy::NoUpdate`](crate::connection::AccessPolicy::NoUpdate).
+    #[must_use]
+    pub fn missing_documents(&self) -> BTreeSet<DocumentId> {
+        missing_documents(
+            self.mappings.iter().map(|mapping| &mapping.source.id),
+            &self.documents,
+        )
+    }
 }
 
 /// An iterator of mapped documents.
@@ -285,16 +306,31 @@ pub struct MappedDocument<'a, D, K, V> {
     pub document: &'a D,
 }
 
+/// An owned mapped document returned from a streaming view query.
+#[derive(Clone, Debug)]
+pub struct OwnedMappedDocument<D, K, V> {
+    /// The key that this document mapped to.
+    pub key: K,
+    /// The associated value of this key.
+    pub value: V,
+    /// The source document of this mapping.
+    pub document: D,
+}
+
 /// Represents a document's entry in a View's mappings, serialized and ready to store.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Serialized {
-    /// The header of the document that emitted this entry.
+    /// The header of the document that emitted this entry. The revision is
+    /// the revision of the document when it was mapped, which allows the
+    /// document to be updated without retrieving it first. If the view was
+    /// queried with [`AccessPolicy::NoUpdate`](crate::connection::AccessPolicy::NoUpdate),
+    /// the revision may be outdated.
     pub source: Header,
 
-    /// The key used to index the View.Operation
+    /// The key used to index the View.
     pub key: Bytes,
 
-    /// An associated value stored in the view.Operation
+    /// An associated value stored in the view.
     pub value: Bytes,
 }
 
@@ -337,6 +373,26 @@ impl MappedSerializedDocuments {
             documents: self.documents,
         })
     }
+
+    /// Returns the ids of the source documents that are referenced by one or
+    /// more mappings but were not found.
+    #[must_use]
+    pub fn missing_documents(&self) -> BTreeSet<DocumentId> {
+        missing_documents(
+            self.mappings.iter().map(|mapping| &mapping.source.id),
+            &self.documents,
+        )
+    }
+}
+
+fn missing_documents<'a, D>(
+    mapped_ids: impl Iterator<Item = &'a DocumentId>,
+    documents: &BTreeMap<DocumentId, D>,
+) -> BTreeSet<DocumentId> {
+    mapped_ids
+        .filter(|id| !documents.contains_key(id))
+        .cloned()
+        .collect()
 }
 
 /// A key value pair
@@ -368,3 +424,24 @@ pub struct MappedSerializedValue {
     /// The serialized value.
     pub value: Bytes,
 }
+
+/// The result of reducing a view, along with whether the view had pending
+/// changes when it was read.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct Reduction<V> {
+    /// The reduced value.
+    pub value: V,
+    /// True if documents had been changed that the view had not yet mapped
+    /// when it was read. If true, `value` may not reflect the most recent
+    /// changes.
+    pub was_stale: bool,
+}
+
+/// A serialized key that a document emitted into a view.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct EmittedKey {
+    /// The name of the view.
+    pub view: ViewName,
+    /// The serialized key.
+    pub key: Bytes,
+}
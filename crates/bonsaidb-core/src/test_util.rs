@@ -9,17 +9,20 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use futures::TryStreamExt;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use transmog_pot::Pot;
 
 use crate::admin::{PermissionGroup, Role, User};
 use crate::connection::{
-    AccessPolicy, AsyncConnection, AsyncStorageConnection, Connection, StorageConnection,
+    AccessPolicy, AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection, Connection,
+    LowLevelConnection, StorageConnection,
 };
 use crate::document::{
     BorrowedDocument, CollectionDocument, CollectionHeader, DocumentId, Emit, Header, KeyId,
 };
+use crate::key::KeyEncoding;
 use crate::keyvalue::{AsyncKeyValue, KeyValue};
 use crate::limits::{LIST_TRANSACTIONS_DEFAULT_RESULT_COUNT, LIST_TRANSACTIONS_MAX_RESULTS};
 use crate::schema::view::map::{Mappings, ViewMappedValue};
@@ -39,7 +42,7 @@ use crate::{
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Default, Clone, Collection)]
 // This collection purposely uses names with characters that need
 // escaping, since it's used in backup/restore.
-#[collection(name = "_basic", authority = "khonsulabs_", views = [BasicCount, BasicByParentId, BasicByParentIdEager, BasicByTag, BasicByCategory, BasicByCategoryCow], core = crate)]
+#[collection(name = "_basic", authority = "khonsulabs_", views = [BasicCount, BasicByParentId, BasicByParentIdEager, BasicByParentIdAndValue, BasicByTag, BasicByCategory, BasicByCategoryCow], core = crate)]
 #[must_use]
 pub struct Basic {
     pub value: String,
@@ -141,6 +144,22 @@ impl MapReduce for BasicByParentIdEager {
     }
 }
 
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Basic, key = (u64, String), value = (), name = "by-parent-id-and-value", core = crate)]
+#[view_schema(core = crate)]
+pub struct BasicByParentIdAndValue;
+
+impl MapReduce for BasicByParentIdAndValue {
+    fn map<'doc>(&self, document: &'doc BorrowedDocument<'_>) -> ViewMapResult<'doc, Self> {
+        let contents = Basic::document_contents(document)?;
+        if let Some(parent_id) = contents.parent_id {
+            document.header.emit_key((parent_id, contents.value))
+        } else {
+            Ok(Mappings::none())
+        }
+    }
+}
+
 #[derive(Debug, Clone, View, ViewSchema)]
 #[view(collection = Basic, key = String, value = usize, name = "by-category", core = crate)]
 #[view_schema(core = crate)]
@@ -337,6 +356,12 @@ impl MapReduce for EncryptedBasicByCategory {
 #[schema(name = "basic", collections = [Basic, EncryptedBasic, Unique], core = crate)]
 pub struct BasicSchema;
 
+/// A schema sharing [`BasicSchema`]'s name but missing some of its
+/// collections, as if it were compiled from an older definition.
+#[derive(Debug, Schema)]
+#[schema(name = "basic", collections = [Basic], core = crate)]
+pub struct OutdatedBasicSchema;
+
 #[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, Default, Collection)]
 #[collection(name = "unique", authority = "khonsulabs", views = [UniqueValue], core = crate)]
 pub struct Unique {
@@ -495,6 +520,12 @@ pub enum HarnessTest {
     KvExpiration,
     KvDeleteExpire,
     KvTransactions,
+    KvList,
+    PubSubPatterns,
+    PubSubRetained,
+    PubSubFlush,
+    PubSubTyped,
+    PubSubHelpers,
 }
 
 impl HarnessTest {
@@ -1444,6 +1475,21 @@ pub async fn get_multiple_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()
     let out_of_order = Basic::get_multiple_async([&doc2.id, &doc1.id], db).await?;
     assert_eq!(out_of_order.len(), 2);
 
+    // get_multiple_optional returns one entry per requested id, in order.
+    let missing_id = doc2.id + 1;
+    let optional =
+        Basic::get_multiple_optional_async([&doc2.id, &missing_id, &doc1.id], db).await?;
+    let ids = optional
+        .iter()
+        .map(|doc| doc.as_ref().map(|doc| doc.header.id))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![Some(doc2.id), None, Some(doc1.id)]);
+
+    let existing = db
+        .existing_ids::<Basic, _, _, _>([&doc2.id, &missing_id, &doc1.id])
+        .await?;
+    assert_eq!(existing, vec![doc2.id, doc1.id]);
+
     // The order of get_multiple isn't guaranteed, so these two checks are done
     // with iterators instead of direct indexing
     let doc1 = both_docs
@@ -1474,6 +1520,18 @@ pub fn blocking_get_multiple_tests<C: Connection>(db: &C) -> anyhow::Result<()>
     let out_of_order = Basic::get_multiple([&doc2.id, &doc1.id], db)?;
     assert_eq!(out_of_order.len(), 2);
 
+    // get_multiple_optional returns one entry per requested id, in order.
+    let missing_id = doc2.id + 1;
+    let optional = Basic::get_multiple_optional([&doc2.id, &missing_id, &doc1.id], db)?;
+    let ids = optional
+        .iter()
+        .map(|doc| doc.as_ref().map(|doc| doc.header.id))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![Some(doc2.id), None, Some(doc1.id)]);
+
+    let existing = db.existing_ids::<Basic, _, _, _>([&doc2.id, &missing_id, &doc1.id])?;
+    assert_eq!(existing, vec![doc2.id, doc1.id]);
+
     // The order of get_multiple isn't guaranteed, so these two checks are done
     // with iterators instead of direct indexing
     let doc1 = both_docs
@@ -1698,6 +1756,25 @@ pub async fn transaction_tests<C: AsyncConnection + 'static>(db: &C) -> anyhow::
     let doc = Basic::get_async(&id, db).await?.expect("doc not found");
     assert_eq!(doc.contents.category.as_deref(), Some("cat"));
 
+    // Update the doc, reading it back within the same transaction.
+    let mut tx = Transaction::new();
+    let mut doc = Basic::get_async(&id, db).await?.expect("doc not found");
+    doc.contents.category = Some(String::from("dog"));
+    doc.update_in_transaction(&mut tx)?;
+    tx.push(Operation::get_document::<Basic>(&id)?);
+    let results = tx.apply_async(db).await?;
+    let OperationResult::DocumentRead {
+        document: Some(read),
+        ..
+    } = &results[1]
+    else {
+        unreachable!("unexpected tx result")
+    };
+    let read = Basic::document_contents(read)?;
+    assert_eq!(read.category.as_deref(), Some("dog"));
+    let doc = Basic::get_async(&id, db).await?.expect("doc not found");
+    assert_eq!(doc.contents.category.as_deref(), Some("dog"));
+
     // Overwrite the document.
     let mut tx = Transaction::new();
     Basic::new("test").overwrite_in_transaction(&id, &mut tx)?;
@@ -1705,10 +1782,76 @@ pub async fn transaction_tests<C: AsyncConnection + 'static>(db: &C) -> anyhow::
     let doc = Basic::get_async(&id, db).await?.expect("doc not found");
     assert_eq!(doc.contents.category, None);
 
+    // Applying a transaction can return the id it was committed with.
+    let mut tx = Transaction::new();
+    Basic::new("overwritten").overwrite_in_transaction(&id, &mut tx)?;
+    let applied = tx.apply_with_id_async(db).await?;
+    assert_eq!(applied.results.len(), 1);
+    assert_eq!(db.last_transaction_id().await?, Some(applied.id));
+    let doc = Basic::get_async(&id, db).await?.expect("doc not found");
+
     // Delete the document
     let mut tx = Transaction::new();
     doc.delete_in_transaction(&mut tx)?;
     tx.apply_async(db).await?;
+    assert!(Basic::get_async(&id, db).await?.is_none());
+
+    // Read back the results of a transaction.
+    let doc = Basic::new("results").push_into_async(db).await?;
+    let mut tx = Transaction::new();
+    doc.delete_in_transaction(&mut tx)?;
+    let results = tx.apply_with_results_async(db).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results.deleted_ids().collect::<Vec<_>>(),
+        vec![(&Basic::collection_name(), &DocumentId::new(&doc.header.id)?)]
+    );
+
+    // Apply a transaction in batches.
+    let mut tx = Transaction::new();
+    for index in 0..5 {
+        Basic::new(format!("batch {index}")).push_in_transaction(&mut tx)?;
+    }
+    let results = tx.apply_in_batches_async(db, 2).await?;
+    assert_eq!(results.len(), 5);
+    let OperationResult::DocumentUpdated { header, .. } = &results[0] else { unreachable!("unexpected tx result") };
+    let existing_id: u64 = header.id.deserialize()?;
+
+    // A failing batch reports how many operations were applied before it.
+    let mut tx = Transaction::new();
+    Basic::new("applied").push_in_transaction(&mut tx)?;
+    Basic::new("applied").push_in_transaction(&mut tx)?;
+    tx.push(Operation::insert_serialized::<Basic>(
+        Some(&existing_id),
+        &Basic::new("conflict"),
+    )?);
+    let result = tx.apply_in_batches_async(db, 2).await.unwrap_err();
+    assert!(matches!(
+        result,
+        Error::TransactionBatchFailed {
+            applied_operations: 2,
+            ..
+        }
+    ));
+
+    // Build a transaction from typed operations.
+    let renamed = Basic::get_async(&existing_id, db)
+        .await?
+        .expect("doc not found");
+    let removed = Basic::new("removed").push_into_async(db).await?;
+    let results = db
+        .transaction()
+        .push::<Basic>(&Basic::new("pushed"))
+        .update::<Basic>(renamed.header, &Basic::new("renamed"))
+        .delete::<Basic, _>(&removed)
+        .apply()
+        .await?;
+    assert_eq!(results.len(), 3);
+    let renamed = Basic::get_async(&existing_id, db)
+        .await?
+        .expect("doc not found");
+    assert_eq!(renamed.contents.value, "renamed");
+    assert!(Basic::get_async(&removed.header.id, db).await?.is_none());
 
     Ok(())
 }
@@ -1729,6 +1872,25 @@ pub fn blocking_transaction_tests<C: Connection + 'static>(db: &C) -> anyhow::Re
     let doc = Basic::get(&id, db)?.expect("doc not found");
     assert_eq!(doc.contents.category.as_deref(), Some("cat"));
 
+    // Update the doc, reading it back within the same transaction.
+    let mut tx = Transaction::new();
+    let mut doc = Basic::get(&id, db)?.expect("doc not found");
+    doc.contents.category = Some(String::from("dog"));
+    doc.update_in_transaction(&mut tx)?;
+    tx.push(Operation::get_document::<Basic>(&id)?);
+    let results = tx.apply(db)?;
+    let OperationResult::DocumentRead {
+        document: Some(read),
+        ..
+    } = &results[1]
+    else {
+        unreachable!("unexpected tx result")
+    };
+    let read = Basic::document_contents(read)?;
+    assert_eq!(read.category.as_deref(), Some("dog"));
+    let doc = Basic::get(&id, db)?.expect("doc not found");
+    assert_eq!(doc.contents.category.as_deref(), Some("dog"));
+
     // Overwrite the document.
     let mut tx = Transaction::new();
     Basic::new("test").overwrite_in_transaction(&id, &mut tx)?;
@@ -1736,10 +1898,71 @@ pub fn blocking_transaction_tests<C: Connection + 'static>(db: &C) -> anyhow::Re
     let doc = Basic::get(&id, db)?.expect("doc not found");
     assert_eq!(doc.contents.category, None);
 
+    // Applying a transaction can return the id it was committed with.
+    let mut tx = Transaction::new();
+    Basic::new("overwritten").overwrite_in_transaction(&id, &mut tx)?;
+    let applied = tx.apply_with_id(db)?;
+    assert_eq!(applied.results.len(), 1);
+    assert_eq!(db.last_transaction_id()?, Some(applied.id));
+    let doc = Basic::get(&id, db)?.expect("doc not found");
+
     // Delete the document
     let mut tx = Transaction::new();
     doc.delete_in_transaction(&mut tx)?;
     tx.apply(db)?;
+    assert!(Basic::get(&id, db)?.is_none());
+
+    // Read back the results of a transaction.
+    let doc = Basic::new("results").push_into(db)?;
+    let mut tx = Transaction::new();
+    doc.delete_in_transaction(&mut tx)?;
+    let results = tx.apply_with_results(db)?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results.deleted_ids().collect::<Vec<_>>(),
+        vec![(&Basic::collection_name(), &DocumentId::new(&doc.header.id)?)]
+    );
+
+    // Apply a transaction in batches.
+    let mut tx = Transaction::new();
+    for index in 0..5 {
+        Basic::new(format!("batch {index}")).push_in_transaction(&mut tx)?;
+    }
+    let results = tx.apply_in_batches(db, 2)?;
+    assert_eq!(results.len(), 5);
+    let OperationResult::DocumentUpdated { header, .. } = &results[0] else { unreachable!("unexpected tx result") };
+    let existing_id: u64 = header.id.deserialize()?;
+
+    // A failing batch reports how many operations were applied before it.
+    let mut tx = Transaction::new();
+    Basic::new("applied").push_in_transaction(&mut tx)?;
+    Basic::new("applied").push_in_transaction(&mut tx)?;
+    tx.push(Operation::insert_serialized::<Basic>(
+        Some(&existing_id),
+        &Basic::new("conflict"),
+    )?);
+    let result = tx.apply_in_batches(db, 2).unwrap_err();
+    assert!(matches!(
+        result,
+        Error::TransactionBatchFailed {
+            applied_operations: 2,
+            ..
+        }
+    ));
+
+    // Build a transaction from typed operations.
+    let renamed = Basic::get(&existing_id, db)?.expect("doc not found");
+    let removed = Basic::new("removed").push_into(db)?;
+    let results = db
+        .transaction()
+        .push::<Basic>(&Basic::new("pushed"))
+        .update::<Basic>(renamed.header, &Basic::new("renamed"))
+        .delete::<Basic, _>(&removed)
+        .apply()?;
+    assert_eq!(results.len(), 3);
+    let renamed = Basic::get(&existing_id, db)?.expect("doc not found");
+    assert_eq!(renamed.contents.value, "renamed");
+    assert!(Basic::get(&removed.header.id, db)?.is_none());
 
     Ok(())
 }
@@ -1767,7 +1990,11 @@ pub async fn transaction_check_tests<C: AsyncConnection + 'static>(db: &C) -> an
     )?);
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
     let result = tx.apply_async(db).await.unwrap_err();
-    assert!(matches!(result, Error::DocumentConflict(_, _)));
+    assert_eq!(result.operation_index(), Some(0));
+    assert!(matches!(
+        result.operation_error(),
+        Error::DocumentConflict(_, _)
+    ));
 
     let mut tx = Transaction::new();
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
@@ -1775,6 +2002,31 @@ pub async fn transaction_check_tests<C: AsyncConnection + 'static>(db: &C) -> an
         &initial_header,
     )?);
     let result = tx.apply_async(db).await.unwrap_err();
+    assert_eq!(result.operation_index(), Some(0));
+    assert!(matches!(
+        result.operation_error(),
+        Error::DocumentNotFound(_, _)
+    ));
+
+    // A failure in the middle of a transaction reports the operation's index.
+    let mut tx = Transaction::new();
+    tx.push(Operation::check_document_is_current::<Basic, _>(&doc)?);
+    tx.push(Operation::check_document_is_current::<Basic, _>(
+        &initial_header,
+    )?);
+    tx.push(Operation::check_document_exists::<Basic>(&doc.header.id)?);
+    let result = tx.apply_async(db).await.unwrap_err();
+    assert_eq!(result.operation_index(), Some(1));
+    assert_eq!(
+        result.conflicting_document::<Basic>(),
+        Some(Header::try_from(initial_header)?)
+    );
+
+    // Transactions with a single operation return the error directly.
+    let mut tx = Transaction::new();
+    tx.push(Operation::check_document_exists::<Basic>(&42)?);
+    let result = tx.apply_async(db).await.unwrap_err();
+    assert_eq!(result.operation_index(), None);
     assert!(matches!(result, Error::DocumentNotFound(_, _)));
 
     Ok(())
@@ -1803,7 +2055,11 @@ pub fn blocking_transaction_check_tests<C: Connection + 'static>(db: &C) -> anyh
     )?);
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
     let result = tx.apply(db).unwrap_err();
-    assert!(matches!(result, Error::DocumentConflict(_, _)));
+    assert_eq!(result.operation_index(), Some(0));
+    assert!(matches!(
+        result.operation_error(),
+        Error::DocumentConflict(_, _)
+    ));
 
     let mut tx = Transaction::new();
     tx.push(Operation::check_document_exists::<Basic>(&42)?);
@@ -1811,6 +2067,31 @@ pub fn blocking_transaction_check_tests<C: Connection + 'static>(db: &C) -> anyh
         &initial_header,
     )?);
     let result = tx.apply(db).unwrap_err();
+    assert_eq!(result.operation_index(), Some(0));
+    assert!(matches!(
+        result.operation_error(),
+        Error::DocumentNotFound(_, _)
+    ));
+
+    // A failure in the middle of a transaction reports the operation's index.
+    let mut tx = Transaction::new();
+    tx.push(Operation::check_document_is_current::<Basic, _>(&doc)?);
+    tx.push(Operation::check_document_is_current::<Basic, _>(
+        &initial_header,
+    )?);
+    tx.push(Operation::check_document_exists::<Basic>(&doc.header.id)?);
+    let result = tx.apply(db).unwrap_err();
+    assert_eq!(result.operation_index(), Some(1));
+    assert_eq!(
+        result.conflicting_document::<Basic>(),
+        Some(Header::try_from(initial_header)?)
+    );
+
+    // Transactions with a single operation return the error directly.
+    let mut tx = Transaction::new();
+    tx.push(Operation::check_document_exists::<Basic>(&42)?);
+    let result = tx.apply(db).unwrap_err();
+    assert_eq!(result.operation_index(), None);
     assert!(matches!(result, Error::DocumentNotFound(_, _)));
 
     Ok(())
@@ -1856,6 +2137,20 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         .await?;
     assert_eq!(b_children.len(), 2);
 
+    // Query a composite key by its leading field.
+    let b_children = db
+        .view::<BasicByParentIdAndValue>()
+        .with_key_bytes_prefix((b.id,).as_ord_bytes()?.to_vec())
+        .query()
+        .await?;
+    assert_eq!(
+        b_children
+            .iter()
+            .map(|mapping| mapping.key.clone())
+            .collect::<Vec<_>>(),
+        [(b.id, String::from("B.1")), (b.id, String::from("B.2"))]
+    );
+
     let a_and_b_children = db
         .view::<BasicByParentId>()
         .with_keys([&Some(a.id), &Some(b.id)])
@@ -1871,6 +2166,163 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         .await?;
     assert_eq!(a_and_b_children.len(), 3);
 
+    // Test counting without loading the mappings
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(b.id))
+            .count()
+            .await?,
+        2
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_keys([&Some(a.id), &Some(b.id)])
+            .count()
+            .await?,
+        3
+    );
+    assert_eq!(db.view::<BasicByParentId>().count().await?, 5);
+
+    // Test counting the mappings of each key without a reduce function
+    assert_eq!(
+        db.view::<BasicByParentId>().facet_counts().await?,
+        vec![(None, 2), (Some(a.id), 1), (Some(b.id), 2)]
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_keys([&Some(a.id), &Some(b.id)])
+            .facet_counts()
+            .await?,
+        vec![(Some(a.id), 1), (Some(b.id), 2)]
+    );
+
+    // Test retrieving the mappings grouped by key
+    let grouped = db.view::<BasicByParentId>().query_grouped().await?;
+    assert_eq!(
+        grouped
+            .iter()
+            .map(|(key, mappings)| (*key, mappings.len()))
+            .collect::<Vec<_>>(),
+        vec![(None, 2), (Some(a.id), 1), (Some(b.id), 2)]
+    );
+    assert!(grouped
+        .iter()
+        .all(|(key, mappings)| mappings.iter().all(|mapping| &mapping.key == key)));
+    let grouped = db
+        .view::<BasicByParentId>()
+        .descending()
+        .query_grouped()
+        .await?;
+    assert_eq!(
+        grouped.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+        vec![Some(b.id), Some(a.id), None]
+    );
+
+    // Test retrieving documents along with the value they emitted
+    let (document, value) = db
+        .get_with_view_value::<BasicByCategory, _>(&a_child.id, AccessPolicy::UpdateBefore)
+        .await?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a_child.id));
+    assert_eq!(value, Some(1));
+    let (document, value) = db
+        .get_with_view_value::<BasicByCategory, _>(&a.id, AccessPolicy::UpdateBefore)
+        .await?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a.id));
+    assert_eq!(value, None);
+    assert!(db
+        .get_with_view_value::<BasicByCategory, _>(&u64::MAX, AccessPolicy::UpdateBefore)
+        .await?
+        .is_none());
+
+    // Test retrieving documents along with the keys they emitted
+    let (document, emitted) = db
+        .get_with_mappings::<Basic, _>(&a_child.id, AccessPolicy::UpdateBefore)
+        .await?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a_child.id));
+    let parent_key = Some(a.id).as_ord_bytes()?;
+    assert!(emitted.iter().any(|emitted| {
+        emitted.view == BasicByParentId.view_name() && emitted.key[..] == parent_key[..]
+    }));
+    assert!(!emitted
+        .iter()
+        .any(|emitted| emitted.view == BasicByTag.view_name()));
+    assert!(db
+        .get_with_mappings::<Basic, _>(&u64::MAX, AccessPolicy::UpdateBefore)
+        .await?
+        .is_none());
+
+    // Test paginating through the mappings using cursors
+    let mut all = db
+        .view::<BasicByParentId>()
+        .query()
+        .await?
+        .into_iter()
+        .map(|mapping| (mapping.key, mapping.source.id))
+        .collect::<Vec<_>>();
+    all.sort();
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = db
+            .view::<BasicByParentId>()
+            .limit(2)
+            .query_page(cursor)
+            .await?;
+        assert!(page.mappings.len() <= 2);
+        paged.extend(
+            page.mappings
+                .into_iter()
+                .map(|mapping| (mapping.key, mapping.source.id)),
+        );
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(paged, all);
+
+    let first_page = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..)
+        .descending()
+        .limit(2)
+        .query_page(None)
+        .await?;
+    assert_eq!(first_page.mappings.len(), 2);
+    assert_eq!(first_page.mappings[0].key, Some(b.id));
+    assert_eq!(first_page.mappings[1].key, Some(b.id));
+    let second_page = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..)
+        .descending()
+        .limit(2)
+        .query_page(first_page.next_cursor)
+        .await?;
+    assert_eq!(second_page.mappings.len(), 1);
+    assert_eq!(second_page.mappings[0].key, Some(a.id));
+    assert!(second_page.next_cursor.is_none());
+
+    // Test executing multiple queries in a single batch
+    let mut queries = db.multi_query();
+    let b_children = queries.add(db.view::<BasicByParentId>().with_key(&Some(b.id)))?;
+    let beta = queries.add(db.view::<BasicByCategory>().with_key("beta"))?;
+    let mut results = queries.execute().await?;
+    assert_eq!(results.take(b_children)?.len(), 2);
+    let beta = results.take(beta)?;
+    assert_eq!(beta.len(), 2);
+    assert!(beta.iter().all(|mapping| mapping.key == "beta"));
+    // Indexes from another batch of queries are rejected.
+    let foreign = db
+        .multi_query()
+        .add(db.view::<BasicByCategory>().with_key("beta"))?;
+    assert!(matches!(
+        results.take(foreign),
+        Err(Error::InvalidMultiQueryIndex)
+    ));
+
     let has_parent = db
         .view::<BasicByParentId>()
         .with_key_range(Some(0)..=Some(u64::MAX))
@@ -1893,6 +2345,50 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
     assert_eq!(last_with_parent.iter().map(|m| m.key).unique().count(), 1);
     assert_eq!(last_with_parent[0].key, has_parent[2].key);
 
+    // Test streaming with a batch size smaller than the result set
+    let streamed = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..=Some(u64::MAX))
+        .query_stream(1)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(
+        streamed.iter().map(|m| m.key).collect::<Vec<_>>(),
+        has_parent.iter().map(|m| m.key).collect::<Vec<_>>()
+    );
+    assert!(streamed
+        .iter()
+        .zip(&has_parent)
+        .all(|(streamed, mapping)| streamed.document.header.id == mapping.source.id));
+    let streamed = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..=Some(u64::MAX))
+        .descending()
+        .query_stream(1)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed[0].key, has_parent[2].key);
+    let streamed = db
+        .view::<BasicByParentId>()
+        .with_keys([&Some(a.id), &Some(b.id)])
+        .query_stream(2)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(streamed.len(), 3);
+    // Sets of keys are queried in batches too, including batches where no
+    // keys match.
+    let streamed = db
+        .view::<BasicByParentId>()
+        .with_keys([&Some(u64::MAX), &Some(b.id), &Some(a_child.id), &Some(a.id)])
+        .query_stream(1)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(
+        streamed.iter().map(|m| m.key).collect::<Vec<_>>(),
+        has_parent.iter().map(|m| m.key).collect::<Vec<_>>()
+    );
+
     let items_with_categories = db.view::<BasicByCategory>().query().await?;
     assert_eq!(items_with_categories.len(), 3);
 
@@ -1912,6 +2408,37 @@ pub async fn view_query_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         0
     );
 
+    // Test detecting mappings whose source documents are missing
+    collection.delete(&a_child).await?;
+    let stale = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .with_access_policy(AccessPolicy::NoUpdate)
+        .query_with_docs()
+        .await?;
+    assert_eq!(stale.len(), 1);
+    assert!(stale.documents.is_empty());
+    assert_eq!(
+        stale.missing_documents().into_iter().collect::<Vec<_>>(),
+        vec![DocumentId::from_u64(a_child.id)]
+    );
+    // Documents deleted after being mapped are skipped rather than aborting.
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(a.id))
+            .with_access_policy(AccessPolicy::NoUpdate)
+            .delete_docs_skipping_conflicts()
+            .await?,
+        0
+    );
+    let updated = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .query_with_docs()
+        .await?;
+    assert!(updated.is_empty());
+    assert!(updated.missing_documents().is_empty());
+
     Ok(())
 }
 
@@ -1940,6 +2467,19 @@ pub fn blocking_view_query_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
     let b_children = db.view::<BasicByParentId>().with_key(&Some(b.id)).query()?;
     assert_eq!(b_children.len(), 2);
 
+    // Query a composite key by its leading field.
+    let b_children = db
+        .view::<BasicByParentIdAndValue>()
+        .with_key_bytes_prefix((b.id,).as_ord_bytes()?.to_vec())
+        .query()?;
+    assert_eq!(
+        b_children
+            .iter()
+            .map(|mapping| mapping.key.clone())
+            .collect::<Vec<_>>(),
+        [(b.id, String::from("B.1")), (b.id, String::from("B.2"))]
+    );
+
     let a_and_b_children = db
         .view::<BasicByParentId>()
         .with_keys(&[Some(a.id), Some(b.id)])
@@ -1953,6 +2493,142 @@ pub fn blocking_view_query_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
         .query()?;
     assert_eq!(a_and_b_children.len(), 3);
 
+    // Test counting without loading the mappings
+    assert_eq!(
+        db.view::<BasicByParentId>().with_key(&Some(b.id)).count()?,
+        2
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_keys(&[Some(a.id), Some(b.id)])
+            .count()?,
+        3
+    );
+    assert_eq!(db.view::<BasicByParentId>().count()?, 5);
+
+    // Test counting the mappings of each key without a reduce function
+    assert_eq!(
+        db.view::<BasicByParentId>().facet_counts()?,
+        vec![(None, 2), (Some(a.id), 1), (Some(b.id), 2)]
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_keys(&[Some(a.id), Some(b.id)])
+            .facet_counts()?,
+        vec![(Some(a.id), 1), (Some(b.id), 2)]
+    );
+
+    // Test retrieving the mappings grouped by key
+    let grouped = db.view::<BasicByParentId>().query_grouped()?;
+    assert_eq!(
+        grouped
+            .iter()
+            .map(|(key, mappings)| (*key, mappings.len()))
+            .collect::<Vec<_>>(),
+        vec![(None, 2), (Some(a.id), 1), (Some(b.id), 2)]
+    );
+    assert!(grouped
+        .iter()
+        .all(|(key, mappings)| mappings.iter().all(|mapping| &mapping.key == key)));
+    let grouped = db.view::<BasicByParentId>().descending().query_grouped()?;
+    assert_eq!(
+        grouped.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+        vec![Some(b.id), Some(a.id), None]
+    );
+
+    // Test retrieving documents along with the value they emitted
+    let (document, value) = db
+        .get_with_view_value::<BasicByCategory, _>(&a_child.id, AccessPolicy::UpdateBefore)?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a_child.id));
+    assert_eq!(value, Some(1));
+    let (document, value) = db
+        .get_with_view_value::<BasicByCategory, _>(&a.id, AccessPolicy::UpdateBefore)?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a.id));
+    assert_eq!(value, None);
+    assert!(db
+        .get_with_view_value::<BasicByCategory, _>(&u64::MAX, AccessPolicy::UpdateBefore)?
+        .is_none());
+
+    // Test retrieving documents along with the keys they emitted
+    let (document, emitted) = db
+        .get_with_mappings::<Basic, _>(&a_child.id, AccessPolicy::UpdateBefore)?
+        .expect("document missing");
+    assert_eq!(document.header.id, DocumentId::from_u64(a_child.id));
+    let parent_key = Some(a.id).as_ord_bytes()?;
+    assert!(emitted.iter().any(|emitted| {
+        emitted.view == BasicByParentId.view_name() && emitted.key[..] == parent_key[..]
+    }));
+    assert!(!emitted
+        .iter()
+        .any(|emitted| emitted.view == BasicByTag.view_name()));
+    assert!(db
+        .get_with_mappings::<Basic, _>(&u64::MAX, AccessPolicy::UpdateBefore)?
+        .is_none());
+
+    // Test paginating through the mappings using cursors
+    let mut all = db
+        .view::<BasicByParentId>()
+        .query()?
+        .into_iter()
+        .map(|mapping| (mapping.key, mapping.source.id))
+        .collect::<Vec<_>>();
+    all.sort();
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = db.view::<BasicByParentId>().limit(2).query_page(cursor)?;
+        assert!(page.mappings.len() <= 2);
+        paged.extend(
+            page.mappings
+                .into_iter()
+                .map(|mapping| (mapping.key, mapping.source.id)),
+        );
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(paged, all);
+
+    let first_page = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..)
+        .descending()
+        .limit(2)
+        .query_page(None)?;
+    assert_eq!(first_page.mappings.len(), 2);
+    assert_eq!(first_page.mappings[0].key, Some(b.id));
+    assert_eq!(first_page.mappings[1].key, Some(b.id));
+    let second_page = db
+        .view::<BasicByParentId>()
+        .with_key_range(Some(0)..)
+        .descending()
+        .limit(2)
+        .query_page(first_page.next_cursor)?;
+    assert_eq!(second_page.mappings.len(), 1);
+    assert_eq!(second_page.mappings[0].key, Some(a.id));
+    assert!(second_page.next_cursor.is_none());
+
+    // Test executing multiple queries in a single batch
+    let mut queries = db.multi_query();
+    let b_children = queries.add(db.view::<BasicByParentId>().with_key(&Some(b.id)))?;
+    let beta = queries.add(db.view::<BasicByCategory>().with_key("beta"))?;
+    let mut results = queries.execute()?;
+    assert_eq!(results.take(b_children)?.len(), 2);
+    let beta = results.take(beta)?;
+    assert_eq!(beta.len(), 2);
+    assert!(beta.iter().all(|mapping| mapping.key == "beta"));
+    // Indexes from another batch of queries are rejected.
+    let foreign = db
+        .multi_query()
+        .add(db.view::<BasicByCategory>().with_key("beta"))?;
+    assert!(matches!(
+        results.take(foreign),
+        Err(Error::InvalidMultiQueryIndex)
+    ));
+
     let has_parent = db
         .view::<BasicByParentId>()
         .with_key_range(Some(0)..=Some(u64::MAX))
@@ -1989,6 +2665,11 @@ pub fn blocking_view_query_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
             .len(),
         0
     );
+    let deleted_count = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .delete_docs_skipping_conflicts()?;
+    assert_eq!(deleted_count, 1);
 
     Ok(())
 }
@@ -2079,6 +2760,24 @@ pub async fn view_update_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         )
         .await?;
 
+    // The view hasn't mapped the new record, which is reported when reducing
+    // without updating the view.
+    let reduction = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .with_access_policy(AccessPolicy::NoUpdate)
+        .reduce_with_staleness()
+        .await?;
+    assert_eq!(reduction.value, 0);
+    assert!(reduction.was_stale);
+    let reduction = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .reduce_with_staleness()
+        .await?;
+    assert_eq!(reduction.value, 1);
+    assert!(!reduction.was_stale);
+
     let a_children = db
         .view::<BasicByParentId>()
         .with_key(&Some(a.id))
@@ -2098,6 +2797,21 @@ pub async fn view_update_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         db.view::<BasicByParentId>().reduce_grouped().await?,
         vec![MappedValue::new(None, 1,), MappedValue::new(Some(a.id), 1,),]
     );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .group_level(1)
+            .reduce_grouped()
+            .await?,
+        vec![MappedValue::new(None, 1,), MappedValue::new(Some(a.id), 1,),]
+    );
+    // A group level of 0 places every entry in a single group.
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .group_level(0)
+            .reduce_grouped()
+            .await?,
+        vec![MappedValue::new(None, 2,)]
+    );
 
     // Test updating the record and the view being updated appropriately
     let b = collection.push(&Basic::new("B")).await?;
@@ -2122,6 +2836,19 @@ pub async fn view_update_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         0
     );
     assert_eq!(db.view::<BasicByParentId>().reduce().await?, 3);
+    // An initial value is folded into the reduced result, and is returned
+    // unchanged when nothing matches.
+    assert_eq!(
+        db.view::<BasicByParentId>().reduce_with_initial(10).await?,
+        13
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(a.id))
+            .reduce_with_initial(10)
+            .await?,
+        10
+    );
 
     // Update the record, but don't change its mapping. Ensure the source's
     // header is updated.
@@ -2197,6 +2924,22 @@ pub fn blocking_view_update_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
             .with_category("Alpha"),
     )?;
 
+    // The view hasn't mapped the new record, which is reported when reducing
+    // without updating the view.
+    let reduction = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .with_access_policy(AccessPolicy::NoUpdate)
+        .reduce_with_staleness()?;
+    assert_eq!(reduction.value, 0);
+    assert!(reduction.was_stale);
+    let reduction = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(a.id))
+        .reduce_with_staleness()?;
+    assert_eq!(reduction.value, 1);
+    assert!(!reduction.was_stale);
+
     let a_children = db.view::<BasicByParentId>().with_key(&Some(a.id)).query()?;
     assert_eq!(a_children.len(), 1);
     assert_eq!(
@@ -2211,6 +2954,19 @@ pub fn blocking_view_update_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
         db.view::<BasicByParentId>().reduce_grouped()?,
         vec![MappedValue::new(None, 1,), MappedValue::new(Some(a.id), 1,),]
     );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .group_level(1)
+            .reduce_grouped()?,
+        vec![MappedValue::new(None, 1,), MappedValue::new(Some(a.id), 1,),]
+    );
+    // A group level of 0 places every entry in a single group.
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .group_level(0)
+            .reduce_grouped()?,
+        vec![MappedValue::new(None, 2,)]
+    );
 
     // Test updating the record and the view being updated appropriately
     let b = collection.push(&Basic::new("B"))?;
@@ -2230,6 +2986,15 @@ pub fn blocking_view_update_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
         0
     );
     assert_eq!(db.view::<BasicByParentId>().reduce()?, 3);
+    // An initial value is folded into the reduced result, and is returned
+    // unchanged when nothing matches.
+    assert_eq!(db.view::<BasicByParentId>().reduce_with_initial(10)?, 13);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key(&Some(a.id))
+            .reduce_with_initial(10)?,
+        10
+    );
 
     // Update the record, but don't change its mapping. Ensure the source's
     // header is updated.
@@ -2551,6 +3316,36 @@ pub async fn unique_view_tests<C: AsyncConnection>(db: &C) -> anyhow::Result<()>
         unreachable!("unique key violation not triggered");
     }
 
+    // Batch inserts check each document against the unique view, including
+    // the documents inserted earlier in the same transaction.
+    let contents = [Unique::new("3"), Unique::new("4")]
+        .iter()
+        .map(Unique::serialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let headers = db.collection::<Unique>().push_all_bytes(contents).await?;
+    assert_eq!(headers.len(), 2);
+    let third_doc = db
+        .collection::<Unique>()
+        .get(&headers[0].id)
+        .await?
+        .unwrap();
+    assert_eq!(Unique::document_contents(&third_doc)?.value, "3");
+    let count = Unique::all_async(db).count().await?;
+    let contents = [Unique::new("5"), Unique::new("5")]
+        .iter()
+        .map(Unique::serialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let result = db.collection::<Unique>().push_all_bytes(contents).await;
+    // The second insert conflicts, so the error is reported for that
+    // operation.
+    let error = result.unwrap_err();
+    assert_eq!(error.operation_index(), Some(1));
+    assert!(matches!(
+        error.operation_error(),
+        Error::UniqueKeyViolation { .. }
+    ));
+    assert_eq!(Unique::all_async(db).count().await?, count);
+
     Ok(())
 }
 
@@ -2591,6 +3386,32 @@ pub fn blocking_unique_view_tests<C: Connection>(db: &C) -> anyhow::Result<()> {
         unreachable!("unique key violation not triggered");
     }
 
+    // Batch inserts check each document against the unique view, including
+    // the documents inserted earlier in the same transaction.
+    let contents = [Unique::new("3"), Unique::new("4")]
+        .iter()
+        .map(Unique::serialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let headers = db.collection::<Unique>().push_all_bytes(contents)?;
+    assert_eq!(headers.len(), 2);
+    let third_doc = db.collection::<Unique>().get(&headers[0].id)?.unwrap();
+    assert_eq!(Unique::document_contents(&third_doc)?.value, "3");
+    let count = Unique::all(db).count()?;
+    let contents = [Unique::new("5"), Unique::new("5")]
+        .iter()
+        .map(Unique::serialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let result = db.collection::<Unique>().push_all_bytes(contents);
+    // The second insert conflicts, so the error is reported for that
+    // operation.
+    let error = result.unwrap_err();
+    assert_eq!(error.operation_index(), Some(1));
+    assert!(matches!(
+        error.operation_error(),
+        Error::UniqueKeyViolation { .. }
+    ));
+    assert_eq!(Unique::all(db).count()?, count);
+
     Ok(())
 }
 
@@ -3047,6 +3868,45 @@ macro_rules! define_async_kv_test_suite {
                 Ok(())
             }
 
+            #[tokio::test]
+            async fn kv_list_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::{AsyncKeyValue, Numeric, Value};
+                let harness = $harness::new($crate::test_util::HarnessTest::KvList).await?;
+                let db = harness.connect().await?;
+                let kv = db.with_key_namespace("list");
+
+                kv.set_key("b", &2_u32).await?;
+                kv.set_key("a", &1_u32).await?;
+                kv.set_numeric_key("ab", 3_u64).await?;
+                // Keys outside of the namespace should not be listed.
+                db.set_key("a", &0_u32).await?;
+
+                let keys = kv.list_keys().await?;
+                assert_eq!(
+                    keys.iter()
+                        .map(|listed| listed.key.as_str())
+                        .collect::<Vec<_>>(),
+                    ["a", "ab", "b"]
+                );
+                assert!(keys.iter().all(|listed| listed.value.is_none()));
+
+                let keys = kv.list_keys().with_prefix("a").with_values().await?;
+                assert_eq!(keys.len(), 2);
+                assert_eq!(keys[0].value.as_ref().unwrap().deserialize::<u32>()?, 1);
+                assert_eq!(
+                    keys[1].value,
+                    Some(Value::Numeric(Numeric::UnsignedInteger(3)))
+                );
+
+                let keys = kv.list_keys().limit(1).await?;
+                assert_eq!(keys.len(), 1);
+                assert_eq!(keys[0].key, "a");
+
+                harness.shutdown().await?;
+
+                Ok(())
+            }
+
             #[tokio::test]
             async fn kv_increment_decrement_tests() -> anyhow::Result<()> {
                 use $crate::keyvalue::{AsyncKeyValue, KeyStatus};
@@ -3198,11 +4058,11 @@ macro_rules! define_async_kv_test_suite {
                 kv.set_key("non-numeric", &String::from("test")).await?;
                 assert!(matches!(
                     kv.increment_key_by("non-numeric", 1_i64).await,
-                    Err(_)
+                    Err(bonsaidb_core::Error::ValueNotNumeric)
                 ));
                 assert!(matches!(
                     kv.decrement_key_by("non-numeric", 1_i64).await,
-                    Err(_)
+                    Err(bonsaidb_core::Error::ValueNotNumeric)
                 ));
                 assert_eq!(
                     kv.get_key("non-numeric").into::<String>().await?.unwrap(),
@@ -3524,6 +4384,45 @@ macro_rules! define_blocking_kv_test_suite {
                 Ok(())
             }
 
+            #[test]
+            fn kv_list_tests() -> anyhow::Result<()> {
+                use $crate::keyvalue::{KeyValue, Numeric, Value};
+                let harness = $harness::new($crate::test_util::HarnessTest::KvList)?;
+                let db = harness.connect()?;
+                let kv = db.with_key_namespace("list");
+
+                kv.set_key("b", &2_u32).execute()?;
+                kv.set_key("a", &1_u32).execute()?;
+                kv.set_numeric_key("ab", 3_u64).execute()?;
+                // Keys outside of the namespace should not be listed.
+                db.set_key("a", &0_u32).execute()?;
+
+                let keys = kv.list_keys().query()?;
+                assert_eq!(
+                    keys.iter()
+                        .map(|listed| listed.key.as_str())
+                        .collect::<Vec<_>>(),
+                    ["a", "ab", "b"]
+                );
+                assert!(keys.iter().all(|listed| listed.value.is_none()));
+
+                let keys = kv.list_keys().with_prefix("a").with_values().query()?;
+                assert_eq!(keys.len(), 2);
+                assert_eq!(keys[0].value.as_ref().unwrap().deserialize::<u32>()?, 1);
+                assert_eq!(
+                    keys[1].value,
+                    Some(Value::Numeric(Numeric::UnsignedInteger(3)))
+                );
+
+                let keys = kv.list_keys().limit(1).query()?;
+                assert_eq!(keys.len(), 1);
+                assert_eq!(keys[0].key, "a");
+
+                harness.shutdown()?;
+
+                Ok(())
+            }
+
             #[test]
             fn kv_increment_decrement_tests() -> anyhow::Result<()> {
                 use $crate::keyvalue::{KeyStatus, KeyValue};
@@ -3658,11 +4557,11 @@ macro_rules! define_blocking_kv_test_suite {
                 kv.set_key("non-numeric", &String::from("test")).execute()?;
                 assert!(matches!(
                     kv.increment_key_by("non-numeric", 1_i64).execute(),
-                    Err(_)
+                    Err(bonsaidb_core::Error::ValueNotNumeric)
                 ));
                 assert!(matches!(
                     kv.decrement_key_by("non-numeric", 1_i64).execute(),
-                    Err(_)
+                    Err(bonsaidb_core::Error::ValueNotNumeric)
                 ));
                 assert_eq!(
                     kv.get_key("non-numeric").into::<String>()?.unwrap(),
@@ -3947,6 +4846,20 @@ pub async fn basic_server_connection_tests<C: AsyncStorageConnection>(
         .any(|v| v.name == BasicByParentId.view_name()));
     let by_parent_id = basic_collection.view(&BasicByParentId.view_name()).unwrap();
     assert_eq!(by_parent_id.policy, ViewUpdatePolicy::Lazy);
+    assert_eq!(
+        basic_schema.view(&BasicByParentId.view_name()),
+        Some(by_parent_id)
+    );
+
+    server.verify_schema::<BasicSchema>().await?;
+    assert!(matches!(
+        server.verify_schema::<OutdatedBasicSchema>().await,
+        Err(Error::SchemaFingerprintMismatch { .. })
+    ));
+    assert!(matches!(
+        server.verify_schema::<UnassociatedCollection>().await,
+        Err(Error::SchemaNotRegistered(_))
+    ));
 
     assert!(schemas
         .iter()
@@ -3954,6 +4867,13 @@ pub async fn basic_server_connection_tests<C: AsyncStorageConnection>(
 
     let databases = server.list_databases().await?;
     assert!(databases.iter().any(|db| db.name == "tests"));
+    assert!(server.database_exists("tests").await?);
+    assert_eq!(
+        server.database_schema("tests").await?,
+        Some(BasicSchema::schema_name())
+    );
+    assert!(!server.database_exists(newdb_name).await?);
+    assert_eq!(server.database_schema(newdb_name).await?, None);
 
     server
         .create_database::<BasicSchema>(newdb_name, false)
@@ -3965,6 +4885,21 @@ pub async fn basic_server_connection_tests<C: AsyncStorageConnection>(
         Err(Error::DatabaseNotFound(_))
     ));
 
+    assert!(matches!(
+        server.rename_database(newdb_name, "renamed").await,
+        Err(Error::DatabaseNotFound(_))
+    ));
+
+    assert!(matches!(
+        server.rename_database("tests", "tests").await,
+        Err(Error::DatabaseNameAlreadyTaken(_))
+    ));
+
+    assert!(matches!(
+        server.rename_database("tests", "|invalidname").await,
+        Err(Error::InvalidDatabaseName(_))
+    ));
+
     assert!(matches!(
         server.create_database::<BasicSchema>("tests", false).await,
         Err(Error::DatabaseNameAlreadyTaken(_))
@@ -4010,6 +4945,20 @@ pub fn blocking_basic_server_connection_tests<C: StorageConnection>(
         .any(|v| v.name == BasicByParentId.view_name()));
     let by_parent_id = basic_collection.view(&BasicByParentId.view_name()).unwrap();
     assert_eq!(by_parent_id.policy, ViewUpdatePolicy::Lazy);
+    assert_eq!(
+        basic_schema.view(&BasicByParentId.view_name()),
+        Some(by_parent_id)
+    );
+
+    server.verify_schema::<BasicSchema>()?;
+    assert!(matches!(
+        server.verify_schema::<OutdatedBasicSchema>(),
+        Err(Error::SchemaFingerprintMismatch { .. })
+    ));
+    assert!(matches!(
+        server.verify_schema::<UnassociatedCollection>(),
+        Err(Error::SchemaNotRegistered(_))
+    ));
 
     assert!(schemas
         .iter()
@@ -4017,6 +4966,13 @@ pub fn blocking_basic_server_connection_tests<C: StorageConnection>(
 
     let databases = server.list_databases()?;
     assert!(databases.iter().any(|db| db.name == "tests"));
+    assert!(server.database_exists("tests")?);
+    assert_eq!(
+        server.database_schema("tests")?,
+        Some(BasicSchema::schema_name())
+    );
+    assert!(!server.database_exists(newdb_name)?);
+    assert_eq!(server.database_schema(newdb_name)?, None);
 
     server.create_database::<BasicSchema>(newdb_name, false)?;
     server.delete_database(newdb_name)?;
@@ -4026,6 +4982,21 @@ pub fn blocking_basic_server_connection_tests<C: StorageConnection>(
         Err(Error::DatabaseNotFound(_))
     ));
 
+    assert!(matches!(
+        server.rename_database(newdb_name, "renamed"),
+        Err(Error::DatabaseNotFound(_))
+    ));
+
+    assert!(matches!(
+        server.rename_database("tests", "tests"),
+        Err(Error::DatabaseNameAlreadyTaken(_))
+    ));
+
+    assert!(matches!(
+        server.rename_database("tests", "|invalidname"),
+        Err(Error::InvalidDatabaseName(_))
+    ));
+
     assert!(matches!(
         server.create_database::<BasicSchema>("tests", false),
         Err(Error::DatabaseNameAlreadyTaken(_))
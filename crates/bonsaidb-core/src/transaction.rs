@@ -2,7 +2,7 @@ use arc_bytes::serde::Bytes;
 use serde::{Deserialize, Serialize};
 
 use crate::connection::{AsyncLowLevelConnection, LowLevelConnection};
-use crate::document::{CollectionHeader, DocumentId, HasHeader, Header, Revision};
+use crate::document::{CollectionHeader, DocumentId, HasHeader, Header, OwnedDocument, Revision};
 use crate::key::KeyEncoding;
 use crate::schema::{Collection, CollectionName, SerializedCollection};
 use crate::Error;
@@ -61,6 +61,15 @@ use crate::Error;
 /// # Ok(())
 /// # }
 /// ```
+///
+/// ## Errors
+///
+/// If an operation fails, the transaction is aborted. When the transaction
+/// contains a single operation, the operation's error is returned as-is.
+/// Otherwise, the operation's error is wrapped in
+/// [`Error::TransactionOperationFailed`] along with the index of the failing
+/// operation. [`Error::operation_error()`] returns the operation's error in
+/// either case.
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
 #[must_use]
 pub struct Transaction {
@@ -104,6 +113,152 @@ impl Transaction {
     ) -> Result<Vec<OperationResult>, Error> {
         db.apply_transaction(self).await
     }
+
+    /// Applies the transaction to the `database`, returning the id the
+    /// transaction was committed with and the results of the operations. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    pub fn apply_with_id<Connection: LowLevelConnection>(
+        self,
+        db: &Connection,
+    ) -> Result<AppliedTransaction, Error> {
+        db.apply_transaction_with_id(self)
+    }
+
+    /// Applies the transaction to the `database`, returning the id the
+    /// transaction was committed with and the results of the operations. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    pub async fn apply_with_id_async<Connection: AsyncLowLevelConnection>(
+        self,
+        db: &Connection,
+    ) -> Result<AppliedTransaction, Error> {
+        db.apply_transaction_with_id(self).await
+    }
+
+    /// Splits this transaction into transactions containing at most
+    /// `operations_per_batch` operations each, preserving the order of the
+    /// operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `operations_per_batch` is 0.
+    pub fn into_batches(self, operations_per_batch: usize) -> impl Iterator<Item = Self> {
+        assert!(
+            operations_per_batch > 0,
+            "operations_per_batch must be non-zero"
+        );
+        let mut operations = self.operations.into_iter();
+        std::iter::from_fn(move || {
+            let operations = operations
+                .by_ref()
+                .take(operations_per_batch)
+                .collect::<Vec<_>>();
+            (!operations.is_empty()).then_some(Self { operations })
+        })
+    }
+
+    /// Applies the transaction to the `database` as a series of transactions
+    /// containing at most `operations_per_batch` operations each, returning the
+    /// results of all operations.
+    ///
+    /// Applying a transaction requires memory proportional to its number of
+    /// operations, and the affected collections remain locked until the
+    /// transaction is committed. Splitting a very large transaction into
+    /// batches bounds both.
+    ///
+    /// **This function does not apply the transaction atomically.** Each batch
+    /// is applied atomically, but if a batch fails,
+    /// [`Error::TransactionBatchFailed`] is returned and all previous batches
+    /// remain applied. Operations that depend on each other should be placed
+    /// within the same batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `operations_per_batch` is 0.
+    pub fn apply_in_batches<Connection: LowLevelConnection>(
+        self,
+        db: &Connection,
+        operations_per_batch: usize,
+    ) -> Result<Vec<OperationResult>, Error> {
+        let mut results = Vec::with_capacity(self.operations.len());
+        let mut applied_operations = 0;
+        for batch in self.into_batches(operations_per_batch) {
+            let batch_operations = batch.operations.len();
+            let batch_results =
+                db.apply_transaction(batch)
+                    .map_err(|error| Error::TransactionBatchFailed {
+                        applied_operations,
+                        error: Box::new(error),
+                    })?;
+            results.extend(batch_results);
+            applied_operations += batch_operations;
+        }
+        Ok(results)
+    }
+
+    /// Applies the transaction to the `database` as a series of transactions
+    /// containing at most `operations_per_batch` operations each, returning the
+    /// results of all operations. See
+    /// [`apply_in_batches()`](Self::apply_in_batches) for more information.
+    ///
+    /// **This function does not apply the transaction atomically.**
+    ///
+    /// # Panics
+    ///
+    /// Panics if `operations_per_batch` is 0.
+    pub async fn apply_in_batches_async<Connection: AsyncLowLevelConnection>(
+        self,
+        db: &Connection,
+        operations_per_batch: usize,
+    ) -> Result<Vec<OperationResult>, Error> {
+        let mut results = Vec::with_capacity(self.operations.len());
+        let mut applied_operations = 0;
+        for batch in self.into_batches(operations_per_batch) {
+            let batch_operations = batch.operations.len();
+            let batch_results = db.apply_transaction(batch).await.map_err(|error| {
+                Error::TransactionBatchFailed {
+                    applied_operations,
+                    error: Box::new(error),
+                }
+            })?;
+            results.extend(batch_results);
+            applied_operations += batch_operations;
+        }
+        Ok(results)
+    }
+
+    /// Applies the transaction to the `database`, returning the results of the
+    /// operations paired with the operations that produced them. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    ///
+    /// The operations are cloned before the transaction is applied. When this
+    /// is undesirable, use [`apply()`](Self::apply) instead.
+    pub fn apply_with_results<Connection: LowLevelConnection>(
+        self,
+        db: &Connection,
+    ) -> Result<TransactionResults, Error> {
+        let operations = self.operations.clone();
+        let results = db.apply_transaction(self)?;
+        Ok(TransactionResults::new(operations, results))
+    }
+
+    /// Applies the transaction to the `database`, returning the results of the
+    /// operations paired with the operations that produced them. All
+    /// operations will succeed or none will be performed and an error will be
+    /// returned.
+    ///
+    /// The operations are cloned before the transaction is applied. When this
+    /// is undesirable, use [`apply_async()`](Self::apply_async) instead.
+    pub async fn apply_with_results_async<Connection: AsyncLowLevelConnection>(
+        self,
+        db: &Connection,
+    ) -> Result<TransactionResults, Error> {
+        let operations = self.operations.clone();
+        let results = db.apply_transaction(self).await?;
+        Ok(TransactionResults::new(operations, results))
+    }
 }
 
 impl From<Operation> for Transaction {
@@ -277,7 +432,8 @@ impl Operation {
 
     /// Check that the document `id` still exists in `collection`. If a document
     /// with that id is not present, the transaction will not be applied and
-    /// [`Error::DocumentNotFound`] will be returned.
+    /// this operation will fail with [`Error::DocumentNotFound`]. See
+    /// [`Transaction`] for how operation errors are returned.
     ///
     /// Upon success, [`OperationResult::Success`] will be included in the
     /// transaction's results.
@@ -290,7 +446,8 @@ impl Operation {
 
     /// Check that the document `id` still exists in [`Collection`] `C`. If a
     /// document with that id is not present, the transaction will not be
-    /// applied and [`Error::DocumentNotFound`] will be returned.
+    /// applied and this operation will fail with [`Error::DocumentNotFound`].
+    /// See [`Transaction`] for how operation errors are returned.
     ///
     /// Upon success, [`OperationResult::Success`] will be included in the
     /// transaction's results.
@@ -303,10 +460,12 @@ impl Operation {
 
     /// Check that the header of `doc_or_header` is the current revision of the
     /// stored document in [`Collection`] `C`. If a document with the header's
-    /// id is not present, the transaction will not be applied and
-    /// [`Error::DocumentNotFound`] will be returned. If a document with the
-    /// header's id is present and the revision does not match, the transaction
-    /// will not be applied and [`Error::DocumentConflict`] will be returned.
+    /// id is not present, the transaction will not be applied and this
+    /// operation will fail with [`Error::DocumentNotFound`]. If a document with
+    /// the header's id is present and the revision does not match, the
+    /// transaction will not be applied and this operation will fail with
+    /// [`Error::DocumentConflict`]. See [`Transaction`] for how operation errors
+    /// are returned.
     ///
     /// Upon success, [`OperationResult::Success`] will be included in the
     /// transaction's results.
@@ -322,6 +481,31 @@ impl Operation {
             },
         })
     }
+
+    /// Retrieves the document `id` from `collection` as it exists at this
+    /// point in the transaction. Changes made by earlier operations in the
+    /// same transaction are visible.
+    ///
+    /// Upon success, [`OperationResult::DocumentRead`] will be included in the
+    /// transaction's results. If the document does not exist, the result's
+    /// `document` will be `None`.
+    pub const fn get(collection: CollectionName, id: DocumentId) -> Self {
+        Self {
+            collection,
+            command: Command::Get { id },
+        }
+    }
+
+    /// Retrieves the document `id` from [`Collection`] `C` as it exists at
+    /// this point in the transaction. Changes made by earlier operations in the
+    /// same transaction are visible.
+    ///
+    /// Upon success, [`OperationResult::DocumentRead`] will be included in the
+    /// transaction's results. If the document does not exist, the result's
+    /// `document` will be `None`.
+    pub fn get_document<C: Collection>(id: &C::PrimaryKey) -> Result<Self, Error> {
+        Ok(Self::get(C::collection_name(), DocumentId::new(id)?))
+    }
 }
 
 /// A command to execute within a `Collection`.
@@ -339,7 +523,9 @@ pub enum Command {
 
     /// Update an existing `Document` identified by `header`. `header.revision` must match
     /// the currently stored revision on the `Document`. If it does not, the
-    /// command fill fail with a `DocumentConflict` error.
+    /// command fill fail with a `DocumentConflict` error, which is wrapped in
+    /// `TransactionOperationFailed` if the transaction contains other
+    /// operations.
     Update {
         /// The header of the `Document`. The revision must match the current
         /// document.
@@ -362,22 +548,34 @@ pub enum Command {
 
     /// Delete an existing `Document` identified by `id`. `revision` must match
     /// the currently stored revision on the `Document`. If it does not, the
-    /// command fill fail with a `DocumentConflict` error.
+    /// command fill fail with a `DocumentConflict` error, which is wrapped in
+    /// `TransactionOperationFailed` if the transaction contains other
+    /// operations.
     Delete {
         /// The current header of the `Document`.
         header: Header,
     },
 
     /// Checks whether a document exists, and optionally whether its revision is
-    /// still current. If the document is not found, a `DocumentNotFound` error
-    /// will be returned.  If the document revision is provided and does not
-    /// match, a `DocumentConflict` error will be returned.
+    /// still current. If the document is not found, the command fails with a
+    /// `DocumentNotFound` error.  If the document revision is provided and does
+    /// not match, the command fails with a `DocumentConflict` error. Either
+    /// error is wrapped in `TransactionOperationFailed` if the transaction
+    /// contains other operations.
     Check {
         /// The id of the document to check.
         id: DocumentId,
         /// The revision of the document to check.
         revision: Option<Revision>,
     },
+
+    /// Retrieves a document as it exists at this point in the transaction.
+    /// Reads observe the changes made by earlier operations in the same
+    /// transaction.
+    Get {
+        /// The id of the document to retrieve.
+        id: DocumentId,
+    },
 }
 
 /// Information about the result of each `Operation` in a transaction.
@@ -403,6 +601,226 @@ pub enum OperationResult {
         /// The id of the deleted `Document`.
         id: DocumentId,
     },
+
+    /// A `Document` was read.
+    DocumentRead {
+        /// The id of the `Collection` of the read `Document`.
+        collection: CollectionName,
+
+        /// The `Document`, or `None` if it was not found.
+        document: Option<OwnedDocument>,
+    },
+}
+
+/// The result of applying a [`Transaction`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppliedTransaction {
+    /// The id the transaction was committed with. This is the same id that
+    /// the transaction's [`Executed`] entry will have when listing executed
+    /// transactions.
+    pub id: u64,
+    /// The result of each operation in the transaction.
+    pub results: Vec<OperationResult>,
+}
+
+impl OperationResult {
+    /// Returns the collection and header of the document, if this result is
+    /// [`OperationResult::DocumentUpdated`].
+    #[must_use]
+    pub const fn updated_header(&self) -> Option<(&CollectionName, &Header)> {
+        match self {
+            Self::DocumentUpdated { collection, header } => Some((collection, header)),
+            _ => None,
+        }
+    }
+
+    /// Returns the collection and id of the document, if this result is
+    /// [`OperationResult::DocumentDeleted`].
+    #[must_use]
+    pub const fn deleted_id(&self) -> Option<(&CollectionName, &DocumentId)> {
+        match self {
+            Self::DocumentDeleted { collection, id } => Some((collection, id)),
+            _ => None,
+        }
+    }
+}
+
+/// A change to a document that a transaction is about to commit. Changes are
+/// passed to [`Collection::validate_changes()`] before the transaction is
+/// committed.
+#[derive(Clone, Copy, Debug)]
+pub struct ProposedChange<'a> {
+    /// The id of the changed document.
+    pub id: &'a DocumentId,
+
+    /// The serialized contents of the document after the change, or `None` if
+    /// the document is being deleted.
+    pub contents: Option<&'a [u8]>,
+
+    /// True if the document did not exist before this change.
+    pub inserted: bool,
+}
+
+impl<'a> ProposedChange<'a> {
+    /// Returns the deserialized primary key of the changed document.
+    pub fn primary_key<C: Collection>(&self) -> Result<C::PrimaryKey, Error> {
+        self.id.deserialize()
+    }
+
+    /// Returns the deserialized contents of the document after the change, or
+    /// `None` if the document is being deleted.
+    pub fn deserialized_contents<C: SerializedCollection>(
+        &self,
+    ) -> Result<Option<C::Contents>, Error> {
+        self.contents.map(C::deserialize).transpose()
+    }
+
+    /// Returns true if the document is being deleted.
+    #[must_use]
+    pub const fn is_deleted(&self) -> bool {
+        self.contents.is_none()
+    }
+}
+
+/// The results of an applied [`Transaction`], paired with the [`Operation`]s
+/// that produced them.
+///
+/// ```rust
+/// # bonsaidb_core::__doctest_prelude!();
+/// # use bonsaidb_core::connection::Connection;
+/// # fn test_fn<C: Connection>(db: &C) -> Result<(), Error> {
+/// use bonsaidb_core::transaction::{Operation, Transaction};
+/// let results = Transaction::new()
+///     .with(Operation::push_serialized::<MyCollection>(
+///         &MyCollection::default(),
+///     )?)
+///     .apply_with_results(db)?;
+/// for (collection, header) in results.inserted_headers() {
+///     println!("Inserted {} into {collection}", header.id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TransactionResults {
+    operations: Vec<Operation>,
+    results: Vec<OperationResult>,
+}
+
+impl TransactionResults {
+    /// Returns a new instance pairing each of `operations` with the result at
+    /// the same index in `results`.
+    #[must_use]
+    pub fn new(operations: Vec<Operation>, results: Vec<OperationResult>) -> Self {
+        Self {
+            operations,
+            results,
+        }
+    }
+
+    /// Returns the operation at `index` and its result.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<(&Operation, &OperationResult)> {
+        self.operations.get(index).zip(self.results.get(index))
+    }
+
+    /// Returns the number of results.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.results.len().min(self.operations.len())
+    }
+
+    /// Returns true if there are no results.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over each operation and its result.
+    pub const fn iter(&self) -> TransactionResultsIter<'_> {
+        TransactionResultsIter {
+            results: self,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator over the headers of all documents inserted by
+    /// [`Command::Insert`] operations.
+    pub fn inserted_headers(&self) -> impl Iterator<Item = (&CollectionName, &Header)> {
+        self.iter()
+            .filter(|(operation, _)| matches!(operation.command, Command::Insert { .. }))
+            .filter_map(|(_, result)| result.updated_header())
+    }
+
+    /// Returns an iterator over the headers of all documents changed by
+    /// [`Command::Update`] or [`Command::Overwrite`] operations.
+    pub fn updated_headers(&self) -> impl Iterator<Item = (&CollectionName, &Header)> {
+        self.iter()
+            .filter(|(operation, _)| {
+                matches!(
+                    operation.command,
+                    Command::Update { .. } | Command::Overwrite { .. }
+                )
+            })
+            .filter_map(|(_, result)| result.updated_header())
+    }
+
+    /// Returns an iterator over all documents read by [`Command::Get`]
+    /// operations that were found.
+    pub fn read_documents(&self) -> impl Iterator<Item = (&CollectionName, &OwnedDocument)> {
+        self.results.iter().filter_map(|result| match result {
+            OperationResult::DocumentRead {
+                collection,
+                document: Some(document),
+            } => Some((collection, document)),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the ids of all deleted documents.
+    pub fn deleted_ids(&self) -> impl Iterator<Item = (&CollectionName, &DocumentId)> {
+        self.results.iter().filter_map(OperationResult::deleted_id)
+    }
+
+    /// Returns the results, in the same order as the operations.
+    #[must_use]
+    pub fn results(&self) -> &[OperationResult] {
+        &self.results
+    }
+
+    /// Returns the results, in the same order as the operations.
+    #[must_use]
+    pub fn into_results(self) -> Vec<OperationResult> {
+        self.results
+    }
+}
+
+impl<'a> IntoIterator for &'a TransactionResults {
+    type IntoIter = TransactionResultsIter<'a>;
+    type Item = (&'a Operation, &'a OperationResult);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over [`TransactionResults`].
+#[must_use]
+pub struct TransactionResultsIter<'a> {
+    results: &'a TransactionResults,
+    index: usize,
+}
+
+impl<'a> Iterator for TransactionResultsIter<'a> {
+    type Item = (&'a Operation, &'a OperationResult);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.results.get(self.index);
+        if result.is_some() {
+            self.index += 1;
+        }
+        result
+    }
 }
 
 /// Details about an executed transaction.
@@ -606,6 +1024,61 @@ fn document_changes_iter() {
     assert_eq!(b_changes, 1);
 }
 
+#[test]
+fn transaction_results_accessors() {
+    use crate::schema::Qualified;
+
+    let collection = CollectionName::private("a");
+    let header = |id| Header {
+        id: DocumentId::from_u64(id),
+        revision: Revision::new(b"contents"),
+    };
+    let results = TransactionResults::new(
+        vec![
+            Operation::insert(collection.clone(), None, b"a".to_vec()),
+            Operation::update(collection.clone(), header(1), b"b".to_vec()),
+            Operation::check_document_id_exists(collection.clone(), DocumentId::from_u64(1)),
+            Operation::delete(collection.clone(), header(2)),
+        ],
+        vec![
+            OperationResult::DocumentUpdated {
+                collection: collection.clone(),
+                header: header(0),
+            },
+            OperationResult::DocumentUpdated {
+                collection: collection.clone(),
+                header: header(1),
+            },
+            OperationResult::Success,
+            OperationResult::DocumentDeleted {
+                collection: collection.clone(),
+                id: DocumentId::from_u64(2),
+            },
+        ],
+    );
+
+    assert_eq!(results.len(), 4);
+    assert!(!results.is_empty());
+    assert_eq!(
+        results.inserted_headers().collect::<Vec<_>>(),
+        vec![(&collection, &header(0))]
+    );
+    assert_eq!(
+        results.updated_headers().collect::<Vec<_>>(),
+        vec![(&collection, &header(1))]
+    );
+    assert_eq!(
+        results.deleted_ids().collect::<Vec<_>>(),
+        vec![(&collection, &DocumentId::from_u64(2))]
+    );
+
+    let (operation, result) = results.get(2).unwrap();
+    assert!(matches!(operation.command, Command::Check { .. }));
+    assert!(matches!(result, OperationResult::Success));
+    assert!(results.get(4).is_none());
+    assert_eq!(results.iter().count(), 4);
+}
+
 /// A record of a changed document.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangedDocument {
@@ -1,25 +1,34 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
     self, AccessPolicy, AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection,
     Connection, HasSchema, HasSession, IdentityReference, LowLevelConnection, Range,
-    SerializedQueryKey, Session, Sort, StorageConnection,
+    SerializedQueryKey, SerializedViewPage, SerializedViewQuery, Session, Sort, StorageConnection,
+    ViewCursor,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
 use bonsaidb_core::keyvalue::{AsyncKeyValue, KeyOperation, KeyValue, Output};
 use bonsaidb_core::permissions::Permissions;
 use bonsaidb_core::pubsub::{self, AsyncPubSub, AsyncSubscriber, PubSub, Receiver};
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
 use bonsaidb_core::schema::{
     self, CollectionName, Nameable, Schema, SchemaName, SchemaSummary, Schematic, ViewName,
 };
-use bonsaidb_core::transaction::{self, OperationResult, Transaction};
+use bonsaidb_core::transaction::{self, AppliedTransaction, Transaction};
+use futures::Stream;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-use crate::config::StorageConfiguration;
+use crate::config::{SchemaSet, StorageConfiguration};
 use crate::database::DatabaseNonBlocking;
-use crate::storage::{AnyBackupLocation, StorageNonBlocking};
-use crate::{Database, Error, Storage, Subscriber};
+use crate::storage::{AnyBackupLocation, ArchiveEncryption, StorageNonBlocking};
+use crate::{
+    CollectionStats, Database, DocumentCacheStatistics, Error, Storage, Subscriber, ViewStatus,
+    ViewVerificationReport,
+};
 
 /// A file-based, multi-database, multi-user database engine. This type is
 /// designed for use with [Tokio](https://tokio.rs). For blocking
@@ -135,6 +144,18 @@ impl AsyncStorage {
             .map(Storage::into_async)
     }
 
+    /// Creates or opens a multi-database [`AsyncStorage`] with its data stored
+    /// in `directory`, registering every schema in `schemas`. See
+    /// [`Storage::open_with_schemas()`] for more information.
+    pub async fn open_with_schemas(
+        configuration: StorageConfiguration,
+        schemas: SchemaSet,
+    ) -> Result<Self, Error> {
+        tokio::task::spawn_blocking(move || Storage::open_with_schemas(configuration, schemas))
+            .await?
+            .map(Storage::into_async)
+    }
+
     /// Restores all data from a previously stored backup `location`.
     pub async fn restore<L: AnyBackupLocation + 'static>(&self, location: L) -> Result<(), Error> {
         let task_self = self.clone();
@@ -151,6 +172,76 @@ impl AsyncStorage {
             .await?
     }
 
+    /// Writes a copy of every database in this instance to `writer` as a
+    /// single, versioned archive. See [`Storage::export_to()`] for more
+    /// information.
+    pub async fn export_to<W: Write + Send + 'static>(
+        &self,
+        writer: W,
+        encryption: ArchiveEncryption,
+    ) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.export_to(writer, &encryption))
+            .await?
+    }
+
+    /// Restores every database contained in an archive previously written by
+    /// [`Self::export_to()`]. See [`Storage::import_from()`] for more
+    /// information.
+    pub async fn import_from<R: Read + Send + 'static>(
+        &self,
+        reader: R,
+        encryption: ArchiveEncryption,
+    ) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.import_from(reader, &encryption))
+            .await?
+    }
+
+    /// Generates a new master key for the vault. See
+    /// [`Storage::rotate_master_key()`] for more information.
+    #[cfg(feature = "encryption")]
+    pub async fn rotate_master_key(&self) -> Result<u32, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.rotate_master_key())
+            .await?
+    }
+
+    /// Writes any key-value changes that have not been persisted yet in all
+    /// open databases. See [`Storage::flush_key_value_stores()`] for more
+    /// information.
+    pub async fn flush_key_value_stores(&self) -> Result<(), Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.flush_key_value_stores())
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes the trees in every database that don't belong to a collection
+    /// or view in the database's schema. See
+    /// [`Storage::prune_orphaned_trees()`] for more information.
+    pub async fn prune_orphaned_trees(
+        &self,
+        dry_run: bool,
+    ) -> Result<BTreeMap<String, Vec<String>>, Error> {
+        let task_self = self.clone();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.prune_orphaned_trees(dry_run))
+            .await?
+    }
+
+    /// Returns the number of document reads that were and were not served by
+    /// the document cache. See [`Storage::document_cache_statistics()`] for
+    /// more information.
+    #[must_use]
+    pub fn document_cache_statistics(&self) -> DocumentCacheStatistics {
+        self.storage.document_cache_statistics()
+    }
+
     /// Restricts an unauthenticated instance to having `effective_permissions`.
     /// Returns `None` if a session has already been established.
     #[must_use]
@@ -301,6 +392,22 @@ impl AsyncDatabase {
             })
     }
 
+    /// Returns a handle to this database that rejects every attempt to modify
+    /// it. See [`Database::read_only()`] for more information.
+    #[must_use]
+    pub fn read_only(&self) -> Self {
+        Self {
+            database: self.database.read_only(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Returns true if this handle was returned from [`Self::read_only()`].
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.database.is_read_only()
+    }
+
     /// Converts this instance into its blocking version, which is able to be
     /// used without async.
     #[must_use]
@@ -321,6 +428,306 @@ impl AsyncDatabase {
     pub fn as_blocking(&self) -> &Database {
         &self.database
     }
+
+    /// Returns the number of operations currently running for this database
+    /// and the maximum allowed. Returns `None` if
+    /// [`StorageConfiguration::database_concurrency_limit`](StorageConfiguration#structfield.database_concurrency_limit)
+    /// was not set.
+    #[must_use]
+    pub fn concurrency_usage(&self) -> Option<ConcurrencyUsage> {
+        self.database
+            .data
+            .concurrency_limit
+            .as_ref()
+            .map(ConcurrencyLimit::usage)
+    }
+
+    /// Runs `function` on the blocking thread pool, waiting for this
+    /// database's concurrency limit if one is configured.
+    pub(crate) async fn spawn_blocking<F, R>(
+        &self,
+        function: F,
+    ) -> Result<R, tokio::task::JoinError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let permit = match &self.database.data.concurrency_limit {
+            Some(limit) => Some(limit.acquire().await),
+            None => None,
+        };
+        self.runtime
+            .spawn_blocking(move || {
+                let result = function();
+                drop(permit);
+                result
+            })
+            .await
+    }
+
+    /// Verifies that the stored entries of view `V` match the mappings its
+    /// source documents currently produce. See [`Database::verify_view()`]
+    /// for more information.
+    pub async fn verify_view<V: schema::SerializedView>(
+        &self,
+    ) -> Result<ViewVerificationReport, bonsaidb_core::Error> {
+        let view_name = self.schematic().view::<V>()?.view_name();
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.verify_view_by_name(&view_name))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Waits until view `V` has mapped every document changed by transactions
+    /// up to and including `transaction_id`. See
+    /// [`Database::wait_for_view()`] for more information.
+    pub async fn wait_for_view<V: schema::SerializedView>(
+        &self,
+        transaction_id: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let view_name = self.schematic().view::<V>()?.view_name();
+        let task_self = self.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .wait_for_view_by_name(&view_name, transaction_id)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    /// Returns the indexing status of view `V`. See
+    /// [`Database::view_status()`] for more information.
+    pub async fn view_status<V: schema::SerializedView>(
+        &self,
+    ) -> Result<ViewStatus, bonsaidb_core::Error> {
+        let view_name = self.schematic().view::<V>()?.view_name();
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.view_status_by_name(&view_name))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Reads the documents and view entries of `collections` so that they are
+    /// loaded into the storage's cache before they are queried. See
+    /// [`Database::warm_up()`] for more information.
+    pub async fn warm_up(
+        &self,
+        collections: &[CollectionName],
+        byte_budget: u64,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let collections = collections.to_vec();
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.warm_up(&collections, byte_budget))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Compacts the entire database, returning the number of bytes of disk
+    /// space reclaimed. See [`Database::reclaim_space()`] for more
+    /// information.
+    pub async fn reclaim_space(&self) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.reclaim_space())
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Compacts the trees that store every view's data. See
+    /// [`Database::compact_views()`] for more information.
+    pub async fn compact_views(&self) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.compact_views())
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Returns the id and serialized size in bytes of each executed
+    /// transaction in this database's transaction log. See
+    /// [`Database::transaction_log_summary()`] for more information.
+    pub async fn transaction_log_summary(&self) -> Result<Vec<(u64, usize)>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.transaction_log_summary())
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Returns a stream of this database's executed transactions, starting
+    /// with the transaction whose id is `starting_id` or the first transaction
+    /// after it. Pass `None` to start from the beginning of the transaction
+    /// log.
+    ///
+    /// The stream returns the transactions already in the transaction log, and
+    /// then continues returning new transactions as they are committed. The
+    /// stream never ends. See [`TransactionFeed`](crate::TransactionFeed) for
+    /// how a consumer that falls behind is handled.
+    pub async fn transaction_feed(
+        &self,
+        starting_id: Option<u64>,
+    ) -> Result<
+        impl Stream<Item = Result<transaction::Executed, bonsaidb_core::Error>> + Send + 'static,
+        bonsaidb_core::Error,
+    > {
+        let feed = self.database.transaction_feed(starting_id)?;
+        Ok(futures::stream::unfold(
+            (feed, self.clone()),
+            |(mut feed, database)| async move {
+                loop {
+                    if let Some(executed) = feed.pop_pending() {
+                        return Some((Ok(executed), (feed, database)));
+                    }
+
+                    let (returned_feed, next) = database
+                        .spawn_blocking(move || {
+                            let next = feed.try_next();
+                            (feed, next)
+                        })
+                        .await
+                        .ok()?;
+                    feed = returned_feed;
+                    if let Some(next) = next {
+                        return Some((next, (feed, database)));
+                    }
+
+                    feed.committed().recv_async().await.ok()?;
+                }
+            },
+        ))
+    }
+
+    /// Returns statistics about each collection in this database's schema.
+    /// See [`Database::collection_stats()`] for more information.
+    pub async fn collection_stats(&self) -> Result<Vec<CollectionStats>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.collection_stats())
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Deletes the trees in this database that don't belong to a collection or
+    /// view in the database's schema. See
+    /// [`Database::prune_orphaned_trees()`] for more information.
+    pub async fn prune_orphaned_trees(
+        &self,
+        dry_run: bool,
+    ) -> Result<Vec<String>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.prune_orphaned_trees(dry_run))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Re-encrypts every document and view entry of the collection `C` using
+    /// the vault's current master key. See
+    /// [`Database::reencrypt_collection()`] for more information.
+    #[cfg(feature = "encryption")]
+    pub async fn reencrypt_collection<C: schema::Collection>(
+        &self,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.reencrypt_collection::<C>())
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// Writes every document in the collection `C` to `writer` as
+    /// newline-delimited JSON. See [`Database::export_collection_json()`] for
+    /// more information.
+    #[cfg(feature = "json")]
+    pub async fn export_collection_json<C, W>(&self, writer: W) -> Result<u64, Error>
+    where
+        C: schema::SerializedCollection,
+        C::PrimaryKey: serde::Serialize,
+        C::Contents: serde::Serialize,
+        W: Write + Send + 'static,
+    {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.export_collection_json::<C>(writer))
+            .await?
+    }
+
+    /// Inserts the newline-delimited JSON documents in `reader` into the
+    /// collection `C`. See [`Database::import_collection_json()`] for more
+    /// information.
+    #[cfg(feature = "json")]
+    pub async fn import_collection_json<C, R>(&self, reader: R) -> Result<u64, Error>
+    where
+        C: schema::SerializedCollection,
+        C::PrimaryKey: serde::de::DeserializeOwned,
+        C::Contents: serde::de::DeserializeOwned,
+        R: std::io::BufRead + Send + 'static,
+    {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.import_collection_json::<C>(reader))
+            .await?
+    }
+
+    /// Queries the view `V` and writes the matching entries to `writer` as
+    /// CSV. See [`Database::query_to_csv()`] for more information.
+    #[cfg(feature = "json")]
+    pub async fn query_to_csv<V, Key>(
+        &self,
+        key: Option<connection::QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+        writer: impl Write + Send,
+    ) -> Result<u64, Error>
+    where
+        V: schema::SerializedView,
+        V::Key: serde::Serialize + std::borrow::Borrow<Key> + PartialEq<Key>,
+        V::Value: serde::Serialize,
+        Key: bonsaidb_core::key::KeyEncoding<V::Key> + PartialEq + ?Sized,
+    {
+        let mappings = AsyncLowLevelConnection::query::<V, Key>(
+            self,
+            key,
+            Sort::Ascending,
+            None,
+            access_policy,
+        )
+        .await?;
+        crate::database::csv::write_mappings_csv::<V>(&mappings, writer)
+    }
+}
+
+/// The number of operations running for an [`AsyncDatabase`] with a
+/// concurrency limit.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ConcurrencyUsage {
+    /// The number of operations currently running.
+    pub running: usize,
+    /// The maximum number of operations allowed to run at once.
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimit {
+    limit: usize,
+    permits: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    pub(crate) fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            limit,
+            permits: Arc::new(Semaphore::new(limit)),
+        }
+    }
+
+    async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed")
+    }
+
+    fn usage(&self) -> ConcurrencyUsage {
+        ConcurrencyUsage {
+            running: self.limit - self.permits.available_permits(),
+            limit: self.limit,
+        }
+    }
 }
 
 impl From<AsyncDatabase> for Database {
@@ -409,6 +816,20 @@ impl AsyncStorageConnection for AsyncStorage {
             .map_err(Error::from)?
     }
 
+    async fn rename_database(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_owned();
+        let new_name = new_name.to_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.rename_database(&name, &new_name))
+            .await
+            .map_err(Error::from)?
+    }
+
     async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         let task_self = self.clone();
         self.runtime
@@ -417,6 +838,18 @@ impl AsyncStorageConnection for AsyncStorage {
             .map_err(Error::from)?
     }
 
+    async fn database_schema(
+        &self,
+        name: &str,
+    ) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let name = name.to_owned();
+        self.runtime
+            .spawn_blocking(move || task_self.storage.database_schema(&name))
+            .await
+            .map_err(Error::from)?
+    }
+
     async fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         let task_self = self.clone();
         self.runtime
@@ -598,14 +1031,13 @@ impl AsyncConnection for AsyncDatabase {
         result_limit: Option<u32>,
     ) -> Result<Vec<transaction::Executed>, bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .list_executed_transactions(starting_id, result_limit)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .list_executed_transactions(starting_id, result_limit)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn last_transaction_id(&self) -> Result<Option<u64>, bonsaidb_core::Error> {
@@ -618,24 +1050,21 @@ impl AsyncConnection for AsyncDatabase {
 
     async fn compact(&self) -> Result<(), bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || Connection::compact(&task_self.database))
+        self.spawn_blocking(move || Connection::compact(&task_self.database))
             .await
             .map_err(Error::from)?
     }
 
     async fn compact_collection<C: schema::Collection>(&self) -> Result<(), bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || Connection::compact_collection::<C>(&task_self.database))
+        self.spawn_blocking(move || Connection::compact_collection::<C>(&task_self.database))
             .await
             .map_err(Error::from)?
     }
 
     async fn compact_key_value_store(&self) -> Result<(), bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || Connection::compact_key_value_store(&task_self.database))
+        self.spawn_blocking(move || Connection::compact_key_value_store(&task_self.database))
             .await
             .map_err(Error::from)?
     }
@@ -648,8 +1077,7 @@ impl AsyncKeyValue for AsyncDatabase {
         op: KeyOperation,
     ) -> Result<Output, bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || KeyValue::execute_key_operation(&task_self.database, op))
+        self.spawn_blocking(move || KeyValue::execute_key_operation(&task_self.database, op))
             .await
             .map_err(Error::from)?
     }
@@ -671,6 +1099,22 @@ impl AsyncPubSub for AsyncDatabase {
         PubSub::publish_bytes(&self.database, topic, payload)
     }
 
+    async fn publish_bytes_and_flush(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        PubSub::publish_bytes_and_flush(&self.database, topic, payload)
+    }
+
+    async fn publish_bytes_retained(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        PubSub::publish_bytes_retained(&self.database, topic, payload)
+    }
+
     async fn publish_bytes_to_all(
         &self,
         topics: impl IntoIterator<Item = Vec<u8>> + Send + 'async_trait,
@@ -690,6 +1134,14 @@ impl AsyncSubscriber for Subscriber {
         pubsub::Subscriber::unsubscribe_from_bytes(self, topic)
     }
 
+    async fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        pubsub::Subscriber::subscribe_to_pattern(self, pattern)
+    }
+
+    async fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), bonsaidb_core::Error> {
+        pubsub::Subscriber::unsubscribe_from_pattern(self, pattern)
+    }
+
     fn receiver(&self) -> &Receiver {
         pubsub::Subscriber::receiver(self)
     }
@@ -703,13 +1155,12 @@ impl HasSchema for AsyncDatabase {
 
 #[async_trait]
 impl AsyncLowLevelConnection for AsyncDatabase {
-    async fn apply_transaction(
+    async fn apply_transaction_with_id(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+    ) -> Result<AppliedTransaction, bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || task_self.database.apply_transaction(transaction))
+        self.spawn_blocking(move || task_self.database.apply_transaction_with_id(transaction))
             .await
             .map_err(Error::from)?
     }
@@ -721,12 +1172,40 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let collection = collection.clone();
-        self.runtime
-            .spawn_blocking(move || task_self.database.get_from_collection(id, &collection))
+        self.spawn_blocking(move || task_self.database.get_from_collection(id, &collection))
+            .await
+            .map_err(Error::from)?
+    }
+
+    async fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let collection = collection.clone();
+        self.spawn_blocking(move || task_self.database.history_from_collection(id, &collection))
             .await
             .map_err(Error::from)?
     }
 
+    async fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let collection = collection.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .get_revision_from_collection(id, revision, &collection)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
     async fn list_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -736,14 +1215,13 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<Vec<OwnedDocument>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let collection = collection.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .list_from_collection(ids, order, limit, &collection)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .list_from_collection(ids, order, limit, &collection)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn list_headers_from_collection(
@@ -755,14 +1233,13 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<Vec<Header>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let collection = collection.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .list_headers_from_collection(ids, order, limit, &collection)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .list_headers_from_collection(ids, order, limit, &collection)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn count_from_collection(
@@ -772,8 +1249,7 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<u64, bonsaidb_core::Error> {
         let task_self = self.clone();
         let collection = collection.clone();
-        self.runtime
-            .spawn_blocking(move || task_self.database.count_from_collection(ids, &collection))
+        self.spawn_blocking(move || task_self.database.count_from_collection(ids, &collection))
             .await
             .map_err(Error::from)?
     }
@@ -787,14 +1263,30 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         // TODO avoid the allocation here, switch to IntoIterator.
         let ids = ids.to_vec();
         let collection = collection.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .get_multiple_from_collection(&ids, &collection)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .get_multiple_from_collection(&ids, &collection)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let ids = ids.to_vec();
+        let collection = collection.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .existing_ids_in_collection(&ids, &collection)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn compact_collection_by_name(
@@ -802,8 +1294,7 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         collection: CollectionName,
     ) -> Result<(), bonsaidb_core::Error> {
         let task_self = self.clone();
-        self.runtime
-            .spawn_blocking(move || task_self.database.compact_collection_by_name(collection))
+        self.spawn_blocking(move || task_self.database.compact_collection_by_name(collection))
             .await
             .map_err(Error::from)?
     }
@@ -818,12 +1309,41 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .query_by_name(&view, key, order, limit, access_policy)
-            })
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .query_by_name(&view, key, order, limit, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .query_page_by_name(&view, key, order, limit, after, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        self.spawn_blocking(move || task_self.database.query_multiple_by_name(queries))
             .await
             .map_err(Error::from)?
     }
@@ -838,14 +1358,13 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .query_by_name_with_docs(&view, key, order, limit, access_policy)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .query_by_name_with_docs(&view, key, order, limit, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn reduce_by_name(
@@ -856,8 +1375,7 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
-        self.runtime
-            .spawn_blocking(move || task_self.database.reduce_by_name(&view, key, access_policy))
+        self.spawn_blocking(move || task_self.database.reduce_by_name(&view, key, access_policy))
             .await
             .map_err(Error::from)?
     }
@@ -866,18 +1384,103 @@ impl AsyncLowLevelConnection for AsyncDatabase {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .reduce_grouped_by_name(&view, key, access_policy)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .reduce_grouped_by_name(&view, key, group_level, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .reduce_with_staleness_by_name(&view, key, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .count_view_by_name(&view, key, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .facet_counts_by_name(&view, key, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let view = view.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .get_with_view_value_by_name(&view, id, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
+    }
+
+    async fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, bonsaidb_core::Error> {
+        let task_self = self.clone();
+        let collection = collection.clone();
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .get_with_mappings_by_name(&collection, id, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
     }
 
     async fn delete_docs_by_name(
@@ -888,13 +1491,12 @@ impl AsyncLowLevelConnection for AsyncDatabase {
     ) -> Result<u64, bonsaidb_core::Error> {
         let task_self = self.clone();
         let view = view.clone();
-        self.runtime
-            .spawn_blocking(move || {
-                task_self
-                    .database
-                    .delete_docs_by_name(&view, key, access_policy)
-            })
-            .await
-            .map_err(Error::from)?
+        self.spawn_blocking(move || {
+            task_self
+                .database
+                .delete_docs_by_name(&view, key, access_policy)
+        })
+        .await
+        .map_err(Error::from)?
     }
 }
@@ -3,10 +3,11 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
+use bonsaidb_core::admin::Admin;
 #[cfg(feature = "encryption")]
 use bonsaidb_core::document::KeyId;
 use bonsaidb_core::permissions::Permissions;
-use bonsaidb_core::schema::{Schema, SchemaName};
+use bonsaidb_core::schema::{Schema, SchemaFingerprint, SchemaName, SchemaSummary};
 use sysinfo::{CpuRefreshKind, RefreshKind, System, SystemExt};
 
 use crate::storage::{DatabaseOpener, StorageSchemaOpener};
@@ -62,6 +63,20 @@ pub struct StorageConfiguration {
     /// Configuration options related to views.
     pub views: Views,
 
+    /// Configuration options related to the in-memory cache.
+    pub cache: Cache,
+
+    /// The maximum number of operations that
+    /// [`AsyncDatabase`](crate::AsyncDatabase) runs at once for each database.
+    /// Operations beyond this limit wait for a running operation to complete
+    /// before being spawned onto Tokio's blocking thread pool. This prevents
+    /// a single busy database from monopolizing the thread pool in shared
+    /// deployments. A limit of 0 is treated as 1.
+    ///
+    /// When `None`, operations are not limited.
+    #[cfg(feature = "async")]
+    pub database_concurrency_limit: Option<usize>,
+
     /// Controls how the key-value store persists keys, on a per-database basis.
     pub key_value_persistence: KeyValuePersistence,
 
@@ -98,6 +113,9 @@ impl Default for StorageConfiguration {
             default_compression: None,
             workers: Tasks::default_for(&system),
             views: Views::default(),
+            cache: Cache::default(),
+            #[cfg(feature = "async")]
+            database_concurrency_limit: None,
             key_value_persistence: KeyValuePersistence::default(),
             authenticated_permissions: Permissions::default(),
             #[cfg(feature = "password-hashing")]
@@ -117,6 +135,7 @@ impl std::fmt::Debug for StorageConfiguration {
             .field("unique_id", &self.unique_id)
             .field("workers", &self.workers)
             .field("views", &self.views)
+            .field("cache", &self.cache)
             .field("key_value_persistence", &self.key_value_persistence)
             .field("authenticated_permissions", &self.authenticated_permissions)
             .field("initial_schemas", &schemas);
@@ -128,6 +147,12 @@ impl std::fmt::Debug for StorageConfiguration {
         #[cfg(feature = "compression")]
         f.field("default_compression", &self.default_compression);
 
+        #[cfg(feature = "async")]
+        f.field(
+            "database_concurrency_limit",
+            &self.database_concurrency_limit,
+        );
+
         #[cfg(feature = "password-hashing")]
         f.field("argon", &self.argon);
 
@@ -145,6 +170,109 @@ impl StorageConfiguration {
     }
 }
 
+/// A set of schemas that are validated together and registered when opening
+/// [`Storage`](crate::Storage) using
+/// [`Storage::open_with_schemas()`](crate::Storage::open_with_schemas).
+///
+/// Each schema is identified by its [`SchemaName`] and the
+/// [fingerprint](bonsaidb_core::schema::SchemaSummary::fingerprint) of its
+/// structure. Adding the same schema more than once is allowed, but two
+/// different schemas that share a name cause opening the storage to fail with
+/// [`bonsaidb_core::Error::SchemaAlreadyRegistered`] before any schema is
+/// registered.
+#[derive(Default)]
+#[must_use]
+pub struct SchemaSet {
+    schemas: Vec<SchemaRegistration>,
+}
+
+struct SchemaRegistration {
+    name: SchemaName,
+    fingerprint: SchemaFingerprint,
+    opener: Arc<dyn DatabaseOpener>,
+}
+
+impl SchemaSet {
+    /// Returns an empty set of schemas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the schema `S` to this set.
+    pub fn with_schema<S: Schema>(mut self) -> Result<Self, Error> {
+        self.add::<S>()?;
+        Ok(self)
+    }
+
+    /// Adds the schema `S` to this set.
+    pub fn add<S: Schema>(&mut self) -> Result<(), Error> {
+        let opener = StorageSchemaOpener::<S>::new()?;
+        self.schemas.push(SchemaRegistration {
+            name: S::schema_name(),
+            fingerprint: SchemaSummary::from(opener.schematic()).fingerprint(),
+            opener: Arc::new(opener),
+        });
+        Ok(())
+    }
+
+    /// Validates this set against the schemas already registered in
+    /// `configuration` and the schemas reserved by BonsaiDb. If no conflicts
+    /// are found, every schema in this set is registered.
+    pub(crate) fn register_into(
+        self,
+        configuration: &mut StorageConfiguration,
+    ) -> Result<(), Error> {
+        let mut fingerprints = configuration
+            .initial_schemas
+            .iter()
+            .map(|(name, opener)| {
+                (
+                    name.clone(),
+                    SchemaSummary::from(opener.schematic()).fingerprint(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let reserved = Admin::schema_name();
+
+        let mut openers = Vec::with_capacity(self.schemas.len());
+        for schema in self.schemas {
+            if schema.name == reserved {
+                return Err(Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(
+                    schema.name,
+                )));
+            }
+
+            match fingerprints.get(&schema.name) {
+                Some(fingerprint) if fingerprint == &schema.fingerprint => {}
+                Some(_) => {
+                    return Err(Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(
+                        schema.name,
+                    )));
+                }
+                None => {
+                    fingerprints.insert(schema.name.clone(), schema.fingerprint);
+                    openers.push((schema.name, schema.opener));
+                }
+            }
+        }
+
+        configuration.initial_schemas.extend(openers);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SchemaSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.schemas
+                    .iter()
+                    .map(|schema| (&schema.name, &schema.fingerprint)),
+            )
+            .finish()
+    }
+}
+
 /// Configuration options for background tasks.
 #[derive(Debug, Clone)]
 pub struct Tasks {
@@ -182,6 +310,67 @@ pub struct Views {
     /// be checked. However, for faster startup time, you may wish to delay the
     /// integrity scan. Default value is `false`.
     pub check_integrity_on_open: bool,
+
+    /// If true, querying a view with its source documents will queue any
+    /// documents that could not be found to be re-mapped. The next time the
+    /// view is updated, the stale mappings will be removed. This allows views
+    /// that have become out of sync with their collection to repair themselves
+    /// during normal use. Default value is `false`.
+    pub repair_stale_mappings: bool,
+
+    /// If true, applying a transaction will update every lazy view affected
+    /// by the transaction before returning. This allows queries using
+    /// [`AccessPolicy::NoUpdate`](bonsaidb_core::connection::AccessPolicy::NoUpdate)
+    /// to immediately observe the results of a write, at the cost of each
+    /// write waiting for the affected views to be mapped. Unlike eager views,
+    /// the views are updated after the transaction is committed, so an error
+    /// while updating a view is logged rather than returned, as the
+    /// transaction has already been applied. Default value is `false`.
+    pub update_on_write: bool,
+}
+
+/// Configuration options for the cache of recently read data. A single cache is
+/// shared by every database opened by a storage instance.
+///
+/// The cache stores chunks of the underlying files. The maximum amount of
+/// memory the cache can use is approximately `capacity * max_chunk_size`
+/// bytes, although typical chunks are much smaller than `max_chunk_size`.
+#[derive(Clone, Copy, Debug)]
+pub struct Cache {
+    /// The maximum number of chunks stored in the cache. Default value is
+    /// `2000`.
+    pub capacity: usize,
+
+    /// The size in bytes of the largest chunk that will be cached. Larger
+    /// chunks are always read from disk. Default value is `160384`.
+    pub max_chunk_size: usize,
+
+    /// The maximum number of deserialized documents stored in a separate
+    /// cache of recently read documents. When a document is retrieved by its
+    /// id, this cache is checked before reading the document from disk.
+    /// Documents are removed from the cache when they are changed. Default
+    /// value is `0`, which disables the cache.
+    ///
+    /// The cache's effectiveness can be measured using
+    /// [`Storage::document_cache_statistics()`](crate::Storage::document_cache_statistics).
+    pub documents: usize,
+
+    /// The number of opened databases to keep in memory. Opening a database
+    /// that is kept in memory reuses its existing state instead of creating
+    /// it again. When more databases are kept, the databases that no longer
+    /// have any handles are closed. Default value is `100`.
+    pub databases: usize,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            capacity: 2000,
+            max_chunk_size: 160_384,
+            documents: 0,
+            databases: 100,
+        }
+    }
 }
 
 /// Rules for persisting key-value changes. Default persistence is to
@@ -380,6 +569,28 @@ pub trait Builder: Sized {
     /// Sets [`Views::check_integrity_on_open`] to `check` and returns self.
     #[must_use]
     fn check_view_integrity_on_open(self, check: bool) -> Self;
+    /// Sets [`Views::repair_stale_mappings`] to `repair` and returns self.
+    #[must_use]
+    fn repair_stale_view_mappings(self, repair: bool) -> Self;
+    /// Sets [`Views::update_on_write`] to `update` and returns self.
+    #[must_use]
+    fn update_views_on_write(self, update: bool) -> Self;
+    /// Sets [`Cache::capacity`] to `capacity` and returns self.
+    #[must_use]
+    fn cache_capacity(self, capacity: usize) -> Self;
+    /// Sets [`Cache::max_chunk_size`] to `max_chunk_size` and returns self.
+    #[must_use]
+    fn cache_max_chunk_size(self, max_chunk_size: usize) -> Self;
+    /// Sets [`Cache::documents`] to `capacity` and returns self.
+    #[must_use]
+    fn document_cache_capacity(self, capacity: usize) -> Self;
+    /// Sets [`Cache::databases`] to `capacity` and returns self.
+    #[must_use]
+    fn database_cache_capacity(self, capacity: usize) -> Self;
+    /// Sets [`StorageConfiguration::database_concurrency_limit`](StorageConfiguration#structfield.database_concurrency_limit) to `limit` and returns self.
+    #[cfg(feature = "async")]
+    #[must_use]
+    fn database_concurrency_limit(self, limit: usize) -> Self;
     /// Sets [`StorageConfiguration::default_compression`](StorageConfiguration#structfield.default_compression) to `path` and returns self.
     #[cfg(feature = "compression")]
     #[must_use]
@@ -453,6 +664,42 @@ impl Builder for StorageConfiguration {
         self
     }
 
+    fn repair_stale_view_mappings(mut self, repair: bool) -> Self {
+        self.views.repair_stale_mappings = repair;
+        self
+    }
+
+    fn update_views_on_write(mut self, update: bool) -> Self {
+        self.views.update_on_write = update;
+        self
+    }
+
+    fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache.capacity = capacity;
+        self
+    }
+
+    fn cache_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.cache.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    fn document_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache.documents = capacity;
+        self
+    }
+
+    fn database_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache.databases = capacity;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    fn database_concurrency_limit(mut self, limit: usize) -> Self {
+        self.database_concurrency_limit = Some(limit);
+        self
+    }
+
     fn key_value_persistence(mut self, persistence: KeyValuePersistence) -> Self {
         self.key_value_persistence = persistence;
         self
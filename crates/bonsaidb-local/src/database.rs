@@ -1,15 +1,20 @@
 use std::borrow::{Borrow, Cow};
+use std::cell::Cell;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::Infallible;
+use std::io::ErrorKind;
 use std::ops::{self, Deref};
+use std::path::Path;
 use std::sync::Arc;
-use std::u8;
+use std::{fs, u8};
 
-use bonsaidb_core::arc_bytes::serde::CowBytes;
-use bonsaidb_core::arc_bytes::ArcBytes;
+use bincode::Options;
+use bonsaidb_core::arc_bytes::serde::{Bytes, CowBytes};
+use bonsaidb_core::arc_bytes::{ArcBytes, OwnedBytes};
 use bonsaidb_core::connection::{
-    self, AccessPolicy, Connection, HasSchema, HasSession, LowLevelConnection, Range,
-    SerializedQueryKey, Session, Sort, StorageConnection,
+    self, AccessPolicy, Bound, Connection, HasSchema, HasSession, LowLevelConnection,
+    QueryKeyBytes, Range, SerializedQueryKey, SerializedViewPage, Session, Sort, StorageConnection,
+    ViewCursor,
 };
 #[cfg(any(feature = "encryption", feature = "compression"))]
 use bonsaidb_core::document::KeyId;
@@ -24,12 +29,13 @@ use bonsaidb_core::permissions::bonsai::{
     ViewAction,
 };
 use bonsaidb_core::permissions::Permissions;
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::pubsub::{document_changes_topic, DocumentChange};
+use bonsaidb_core::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
 use bonsaidb_core::schema::view::{self};
-use bonsaidb_core::schema::{self, CollectionName, Schema, Schematic, ViewName};
+use bonsaidb_core::schema::{self, CollectionName, IdStrategy, Schema, Schematic, ViewName};
 use bonsaidb_core::transaction::{
-    self, ChangedDocument, Changes, Command, DocumentChanges, Operation, OperationResult,
-    Transaction,
+    self, AppliedTransaction, ChangedDocument, Changes, Command, DocumentChanges, Operation,
+    OperationResult, ProposedChange, Transaction,
 };
 use itertools::Itertools;
 use nebari::io::any::AnyFile;
@@ -42,6 +48,7 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use watchable::Watchable;
 
+use self::transaction_feed::{TransactionFeed, TransactionPublisher, TransactionWatchers};
 use crate::config::{Builder, KeyValuePersistence, StorageConfiguration};
 use crate::database::keyvalue::BackgroundWorkerProcessTarget;
 use crate::error::Error;
@@ -49,16 +56,29 @@ use crate::open_trees::OpenTrees;
 use crate::storage::StorageLock;
 #[cfg(feature = "encryption")]
 use crate::storage::TreeVault;
+use crate::views::reduce_cache::{ReduceCache, ReduceCacheKey};
 use crate::views::{
     mapper, view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
-    ViewEntry,
+    view_related_document_map_tree_name, view_related_invalidated_docs_tree_name,
+    view_versions_tree_name, ViewEntry,
 };
 use crate::Storage;
 
 pub mod keyvalue;
 
+/// The number of entries rewritten in each transaction by
+/// [`Database::reencrypt_collection()`].
+#[cfg(feature = "encryption")]
+const REENCRYPTION_BATCH_SIZE: usize = 1_000;
+
 pub(crate) mod compat;
+#[cfg(feature = "json")]
+pub(crate) mod csv;
+pub(crate) mod document_cache;
+#[cfg(feature = "json")]
+mod json;
 pub mod pubsub;
+pub(crate) mod transaction_feed;
 
 /// A database stored in BonsaiDb. This type blocks the current thread when
 /// used. See [`AsyncDatabase`](crate::AsyncDatabase) for this type's async counterpart.
@@ -114,6 +134,45 @@ pub mod pubsub;
 pub struct Database {
     pub(crate) data: Arc<Data>,
     pub(crate) storage: Storage,
+    pub(crate) read_only: bool,
+}
+
+/// Statistics about a collection, returned from
+/// [`Database::collection_stats()`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CollectionStats {
+    /// The name of the collection.
+    pub name: CollectionName,
+    /// The number of documents stored in the collection.
+    pub document_count: u64,
+    /// The number of views defined for the collection.
+    pub view_count: usize,
+}
+
+/// The indexing status of a view, returned from [`Database::view_status()`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ViewStatus {
+    /// The number of documents that have been inserted, updated, or deleted
+    /// since the view last mapped them. This includes documents in the view's
+    /// related collections.
+    pub pending_documents: u64,
+    /// The number of documents in the view's collection and its related
+    /// collections.
+    pub total_documents: u64,
+    /// True once the view's integrity has been checked since the database was
+    /// opened. Until then, documents invalidated by a change to the view's
+    /// [version](bonsaidb_core::schema::ViewSchema::version) may not be
+    /// included in [`Self::pending_documents`].
+    pub integrity_checked: bool,
+}
+
+impl ViewStatus {
+    /// Returns true if the view has mapped every document. Querying the view
+    /// with [`AccessPolicy::NoUpdate`] will return up-to-date results.
+    #[must_use]
+    pub const fn is_up_to_date(&self) -> bool {
+        self.integrity_checked && self.pending_documents == 0
+    }
 }
 
 #[derive(Debug)]
@@ -121,6 +180,8 @@ pub struct Data {
     pub name: Arc<Cow<'static, str>>,
     context: Context,
     pub(crate) schema: Arc<Schematic>,
+    #[cfg(feature = "async")]
+    pub(crate) concurrency_limit: Option<crate::r#async::ConcurrencyLimit>,
 }
 
 impl Database {
@@ -138,7 +199,13 @@ impl Database {
                 name: Arc::new(name),
                 context,
                 schema,
+                #[cfg(feature = "async")]
+                concurrency_limit: storage
+                    .instance
+                    .database_concurrency_limit()
+                    .map(crate::r#async::ConcurrencyLimit::new),
             }),
+            read_only: false,
         };
 
         if storage.instance.check_view_integrity_on_database_open() {
@@ -164,9 +231,44 @@ impl Database {
             .map(|storage| Self {
                 storage,
                 data: self.data.clone(),
+                read_only: self.read_only,
             })
     }
 
+    /// Returns a handle to this database that rejects every attempt to modify
+    /// it. Transactions and key-value operations that write data, pruning
+    /// orphaned trees, and re-encrypting collections return
+    /// [`Error::ReadOnly`](bonsaidb_core::Error::ReadOnly) without being
+    /// executed, while reading documents, querying views, and reading keys
+    /// continue to work. Transactions that only check or get documents are
+    /// also allowed.
+    ///
+    /// The returned handle shares the underlying storage with `self`, so it
+    /// observes changes made through other handles. This is useful for
+    /// handing a database to code that should only ever read from it.
+    #[must_use]
+    pub fn read_only(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            data: self.data.clone(),
+            read_only: true,
+        }
+    }
+
+    /// Returns true if this handle was returned from [`Self::read_only()`].
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<(), bonsaidb_core::Error> {
+        if self.read_only {
+            Err(bonsaidb_core::Error::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Creates a `Storage` with a single-database named "default" with its data
     /// stored at `path`. This requires exclusive access to the storage location
     /// configured. Attempting to open the same path multiple times concurrently
@@ -193,10 +295,510 @@ impl Database {
         &self.data.schema
     }
 
+    /// Reads the documents and view entries of `collections` so that they are
+    /// loaded into the storage's cache before they are queried. This allows
+    /// latency-sensitive services to avoid the cost of a cold cache when they
+    /// begin serving requests.
+    ///
+    /// Reading stops once at least `byte_budget` bytes have been read. The
+    /// cache has a fixed capacity, so reading more data than it can hold will
+    /// evict entries that were previously loaded. Returns the number of bytes
+    /// read.
+    ///
+    /// The session must be allowed to list the documents in each collection.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, collections),
+        fields(
+            database = self.name(),
+        )
+    ))]
+    pub fn warm_up(
+        &self,
+        collections: &[CollectionName],
+        byte_budget: u64,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let bytes_read = Cell::new(0);
+        for collection in collections {
+            self.check_permission(
+                collection_resource_name(self.name(), collection),
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::List)),
+            )?;
+            if self
+                .data
+                .schema
+                .collection_primary_key_description(collection)
+                .is_none()
+            {
+                return Err(bonsaidb_core::Error::CollectionNotFound);
+            }
+
+            self.warm_up_tree(
+                self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?,
+                &bytes_read,
+                byte_budget,
+            )?;
+            for view in self.data.schema.views_in_collection(collection) {
+                self.warm_up_tree(
+                    self.collection_tree::<Unversioned, _>(
+                        collection,
+                        view_entries_tree_name(&view.view_name()),
+                    )?,
+                    &bytes_read,
+                    byte_budget,
+                )?;
+            }
+        }
+
+        Ok(bytes_read.get())
+    }
+
+    fn warm_up_tree<R: Root>(
+        &self,
+        tree: TreeRoot<R, AnyFile>,
+        bytes_read: &Cell<u64>,
+        byte_budget: u64,
+    ) -> Result<(), Error> {
+        if bytes_read.get() >= byte_budget {
+            return Ok(());
+        }
+
+        self.roots().tree(tree)?.scan::<Infallible, _, _, _, _>(
+            &(..),
+            true,
+            |_, _, _| ScanEvaluation::ReadData,
+            |_, _| {
+                if bytes_read.get() >= byte_budget {
+                    ScanEvaluation::Stop
+                } else {
+                    ScanEvaluation::ReadData
+                }
+            },
+            |_, _, value| {
+                bytes_read.set(bytes_read.get() + value.len() as u64);
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Waits until view `V` has mapped every document changed by transactions
+    /// up to and including `transaction_id`.
+    ///
+    /// This allows a client that applied a transaction to query the view with
+    /// [`AccessPolicy::NoUpdate`] and observe its changes, without requiring
+    /// every query to update the view. If `transaction_id` is newer than the
+    /// last transaction in this database, this function waits for the last
+    /// transaction instead.
+    ///
+    /// The session must be allowed to query the view.
+    pub fn wait_for_view<V: schema::SerializedView>(
+        &self,
+        transaction_id: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let view = self.schematic().view::<V>()?;
+        self.wait_for_view_by_name(&view.view_name(), transaction_id)
+    }
+
+    /// Waits until the view named `view_name` has mapped every document
+    /// changed by transactions up to and including `transaction_id`. See
+    /// [`Self::wait_for_view()`] for more information.
+    pub fn wait_for_view_by_name(
+        &self,
+        view_name: &ViewName,
+        transaction_id: u64,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+
+        let tasks = self.storage.instance.tasks();
+        if let Some(integrity_check) = tasks.spawn_integrity_check(view, self) {
+            integrity_check
+                .receive()
+                .map_err(Error::from)?
+                .map_err(Error::from)?;
+        }
+
+        // If there is no transaction id, there is no data to wait for.
+        if let Some(last_transaction_id) = self.last_transaction_id()? {
+            tasks.update_view_through(view, self, transaction_id.min(last_transaction_id), true)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the indexing status of view `V`, which reports how many
+    /// documents remain to be mapped while the view is being updated or
+    /// rebuilt in the background.
+    ///
+    /// Checking the status neither updates the view nor waits for it to be
+    /// updated, so it can be polled to show the progress of a rebuild. Use
+    /// [`Self::wait_for_view()`] to wait for the view to be updated.
+    ///
+    /// The session must be allowed to query the view.
+    pub fn view_status<V: schema::SerializedView>(
+        &self,
+    ) -> Result<ViewStatus, bonsaidb_core::Error> {
+        let view = self.schematic().view::<V>()?;
+        self.view_status_by_name(&view.view_name())
+    }
+
+    /// Returns the indexing status of the view named `view_name`. See
+    /// [`Self::view_status()`] for more information.
+    pub fn view_status_by_name(
+        &self,
+        view_name: &ViewName,
+    ) -> Result<ViewStatus, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+
+        let mut pending_documents = 0;
+        for tree in self.view_invalidated_trees(view)? {
+            self.roots()
+                .tree(tree)
+                .map_err(Error::from)?
+                .scan::<Infallible, _, _, _, _>(
+                    &(..),
+                    true,
+                    |_, _, _| ScanEvaluation::ReadData,
+                    |_, _| {
+                        pending_documents += 1;
+                        ScanEvaluation::Skip
+                    },
+                    |_, _, _| Ok(()),
+                )
+                .map_err(Error::from)?;
+        }
+
+        let mut total_documents = 0;
+        for collection in std::iter::once(view.collection()).chain(view.related_collections()) {
+            let documents = self
+                .roots()
+                .tree(self.collection_tree::<Versioned, _>(
+                    &collection,
+                    document_tree_name(&collection),
+                )?)
+                .map_err(Error::from)?;
+            total_documents += documents.reduce(&(..)).map_err(Error::from)?.alive_keys;
+        }
+
+        Ok(ViewStatus {
+            pending_documents,
+            total_documents,
+            integrity_checked: self.storage.instance.tasks().view_integrity_checked(
+                self.data.name.clone(),
+                view.collection(),
+                view.view_name(),
+            ),
+        })
+    }
+
+    /// Compacts the entire database, returning the number of bytes of disk
+    /// space reclaimed. See [`Connection::compact()`] for more information.
+    ///
+    /// Compaction can run while the database is being read from. Because
+    /// other writes may occur while compacting, the returned value is an
+    /// approximation. Memory-only databases always report 0 bytes.
+    pub fn reclaim_space(&self) -> Result<u64, bonsaidb_core::Error> {
+        let size_before = directory_size(self.roots().path())?;
+        self.compact()?;
+        let size_after = directory_size(self.roots().path())?;
+        Ok(size_before.saturating_sub(size_after))
+    }
+
+    /// Compacts the trees that store every view's entries, document mappings,
+    /// and pending invalidations, without compacting the collections' documents.
+    ///
+    /// Each tree is rewritten into a new file containing only its current data,
+    /// which is then swapped in atomically. This reclaims the space used by
+    /// removed and replaced mappings, and places the remaining entries next to
+    /// each other, which improves range queries of views that have been
+    /// updated many times.
+    pub fn compact_views(&self) -> Result<(), bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Compact),
+        )?;
+        self.storage()
+            .instance
+            .tasks()
+            .compact_views(self.clone())?;
+        Ok(())
+    }
+
+    /// Returns the id and serialized size in bytes of each executed
+    /// transaction in this database's transaction log, ordered by id.
+    ///
+    /// This is intended for diagnosing the growth of the transaction log. Only
+    /// transactions that recorded changes are included.
+    pub fn transaction_log_summary(&self) -> Result<Vec<(u64, usize)>, bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Transaction(TransactionAction::ListExecuted)),
+        )?;
+        let mut summary = Vec::new();
+        self.roots()
+            .transactions()
+            .scan(Range::from(..), |entry| {
+                if let Some(data) = entry.data() {
+                    summary.push((entry.id, data.len()));
+                }
+                true
+            })
+            .map_err(Error::from)?;
+        Ok(summary)
+    }
+
+    /// Returns a feed of this database's executed transactions, starting with
+    /// the transaction whose id is `starting_id` or the first transaction
+    /// after it. Pass `None` to start from the beginning of the transaction
+    /// log.
+    ///
+    /// The feed returns the transactions already in the transaction log, and
+    /// then continues returning new transactions as they are committed. See
+    /// [`TransactionFeed`] for how a consumer that falls behind is handled.
+    pub fn transaction_feed(
+        &self,
+        starting_id: Option<u64>,
+    ) -> Result<TransactionFeed, bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Transaction(TransactionAction::ListExecuted)),
+        )?;
+        Ok(TransactionFeed::new(self.clone(), starting_id))
+    }
+
+    /// Returns statistics about each collection in this database's schema,
+    /// ordered by collection name.
+    ///
+    /// The session must be allowed to count the documents in each collection.
+    pub fn collection_stats(&self) -> Result<Vec<CollectionStats>, bonsaidb_core::Error> {
+        let mut collections = self.data.schema.collections().cloned().collect::<Vec<_>>();
+        collections.sort();
+        collections
+            .into_iter()
+            .map(|name| {
+                let document_count = self.count_from_collection(Range::from(..), &name)?;
+                let view_count = self.data.schema.views_in_collection(&name).count();
+                Ok(CollectionStats {
+                    name,
+                    document_count,
+                    view_count,
+                })
+            })
+            .collect()
+    }
+
+    /// Deletes the trees in this database that don't belong to a collection or
+    /// view in the database's schema, returning the names of the deleted
+    /// trees. If `dry_run` is true, the names of the trees that would be
+    /// deleted are returned without deleting them.
+    ///
+    /// Trees are left behind when a collection or view is removed from a
+    /// schema. Only trees named like collection and view trees are
+    /// considered, so other data, such as the key-value store, is never
+    /// pruned.
+    pub fn prune_orphaned_trees(&self, dry_run: bool) -> Result<Vec<String>, bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(self.name()),
+            &BonsaiAction::Database(DatabaseAction::Compact),
+        )?;
+        if !dry_run {
+            self.check_writable()?;
+        }
+        let mut expected = HashSet::new();
+        for collection in self.data.schema.collections() {
+            expected.insert(document_tree_name(collection));
+            expected.insert(history_tree_name(collection));
+            expected.insert(view_versions_tree_name(collection));
+        }
+        for view in self.data.schema.views() {
+            let name = view.view_name();
+            expected.insert(view_entries_tree_name(&name));
+            expected.insert(view_document_map_tree_name(&name));
+            expected.insert(view_invalidated_docs_tree_name(&name));
+            for related in view.related_collections() {
+                expected.insert(view_related_document_map_tree_name(&name, &related));
+                expected.insert(view_related_invalidated_docs_tree_name(&name, &related));
+            }
+        }
+
+        let mut orphaned = self
+            .roots()
+            .tree_names()
+            .map_err(Error::from)?
+            .into_iter()
+            .filter(|name| is_schema_tree_name(name) && !expected.contains(name))
+            .collect::<Vec<_>>();
+        orphaned.sort();
+        if !dry_run {
+            for name in &orphaned {
+                self.roots()
+                    .delete_tree(name.clone())
+                    .map_err(Error::from)?;
+            }
+        }
+
+        Ok(orphaned)
+    }
+
+    /// Re-encrypts every document and view entry of the collection `C` using
+    /// the vault's current master key, returning the number of entries
+    /// rewritten.
+    ///
+    /// After rotating the master key with
+    /// [`Storage::rotate_master_key()`](crate::Storage::rotate_master_key),
+    /// existing data remains encrypted with the previous key until it is
+    /// rewritten. This function rewrites the collection's trees in batches,
+    /// committing each batch in its own transaction, and then compacts the
+    /// collection so that data encrypted with the previous key is removed from
+    /// disk.
+    ///
+    /// Previous master keys are retained by the vault, and rewriting an entry
+    /// does not change its contents. If this operation is interrupted, calling
+    /// it again will finish re-encrypting the collection.
+    ///
+    /// Only the collection's trees are rewritten. The database's key-value
+    /// store and transaction log are not re-encrypted, and remain readable
+    /// using the master key they were written with.
+    #[cfg(feature = "encryption")]
+    pub fn reencrypt_collection<C: schema::Collection>(&self) -> Result<u64, bonsaidb_core::Error> {
+        let collection = C::collection_name();
+        self.check_permission(
+            collection_resource_name(self.name(), &collection),
+            &BonsaiAction::Database(DatabaseAction::Compact),
+        )?;
+        self.check_writable()?;
+        if self
+            .data
+            .schema
+            .collection_primary_key_description(&collection)
+            .is_none()
+        {
+            return Err(bonsaidb_core::Error::CollectionNotFound);
+        }
+
+        let mut rewritten =
+            self.reencrypt_tree::<Versioned>(&collection, document_tree_name(&collection))?;
+        rewritten +=
+            self.reencrypt_tree::<Unversioned>(&collection, view_versions_tree_name(&collection))?;
+        if self.data.schema.history_limit(&collection) > 0 {
+            rewritten +=
+                self.reencrypt_tree::<Unversioned>(&collection, history_tree_name(&collection))?;
+        }
+        for view in self.data.schema.views_in_collection(&collection) {
+            let name = view.view_name();
+            rewritten +=
+                self.reencrypt_tree::<Unversioned>(&collection, view_entries_tree_name(&name))?;
+            rewritten += self
+                .reencrypt_tree::<Unversioned>(&collection, view_document_map_tree_name(&name))?;
+            rewritten += self.reencrypt_tree::<Unversioned>(
+                &collection,
+                view_invalidated_docs_tree_name(&name),
+            )?;
+        }
+        for view in self.data.schema.views_related_to_collection(&collection) {
+            let name = view.view_name();
+            rewritten += self.reencrypt_tree::<Unversioned>(
+                &collection,
+                view_related_document_map_tree_name(&name, &collection),
+            )?;
+            rewritten += self.reencrypt_tree::<Unversioned>(
+                &collection,
+                view_related_invalidated_docs_tree_name(&name, &collection),
+            )?;
+        }
+
+        self.compact_collection_by_name(collection)?;
+        Ok(rewritten)
+    }
+
+    #[cfg(feature = "encryption")]
+    fn reencrypt_tree<R: Root, S: Into<Cow<'static, str>>>(
+        &self,
+        collection: &CollectionName,
+        name: S,
+    ) -> Result<u64, Error> {
+        let name = name.into();
+        let tree = self
+            .roots()
+            .tree(self.collection_tree::<R, _>(collection, name.clone())?)?;
+        let mut rewritten = 0;
+        let mut last_key: Option<ArcBytes<'static>> = None;
+        loop {
+            let range = match &last_key {
+                Some(last_key) => Range {
+                    start: connection::Bound::Excluded(&last_key[..]),
+                    end: connection::Bound::Unbounded,
+                },
+                None => Range::from(..),
+            };
+            let mut keys = Vec::with_capacity(REENCRYPTION_BATCH_SIZE);
+            tree.scan::<Infallible, _, _, _, _>(
+                &range,
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |key, _| {
+                    keys.push(key.clone());
+                    if keys.len() < REENCRYPTION_BATCH_SIZE {
+                        ScanEvaluation::Skip
+                    } else {
+                        ScanEvaluation::Stop
+                    }
+                },
+                |_, _, _| Ok(()),
+            )?;
+            let Some(batch_last_key) = keys.last().cloned() else {
+                break;
+            };
+            rewritten += keys.len() as u64;
+
+            // Setting each entry to its current value causes it to be written
+            // again, which encrypts it using the current master key.
+            let transaction = self
+                .roots()
+                .transaction(&[self.collection_tree::<R, _>(collection, name.clone())?])?;
+            transaction.tree::<R>(0).unwrap().modify(
+                keys,
+                nebari::tree::Operation::CompareSwap(CompareSwap::new(&mut |_key, value| {
+                    value.map_or(
+                        nebari::tree::KeyOperation::Skip,
+                        nebari::tree::KeyOperation::Set,
+                    )
+                })),
+            )?;
+            transaction.commit()?;
+            last_key = Some(batch_last_key);
+        }
+        Ok(rewritten)
+    }
+
     pub(crate) fn roots(&self) -> &'_ nebari::Roots<AnyFile> {
         &self.data.context.roots
     }
 
+    /// Discards the cached reductions of `view` that include any of `keys`.
+    /// Must be called after committing changes to the view's entries.
+    pub(crate) fn invalidate_cached_reductions<'k>(
+        &self,
+        view: &ViewName,
+        keys: impl IntoIterator<Item = &'k [u8]>,
+    ) {
+        self.data.context.reduce_cache.invalidate_keys(view, keys);
+    }
+
+    /// Discards all cached reductions of `view`. Must be called after
+    /// committing changes to the view's entries whose keys are not known.
+    pub(crate) fn invalidate_all_cached_reductions(&self, view: &ViewName) {
+        self.data.context.reduce_cache.invalidate_view(view);
+    }
+
     fn for_each_in_view<F: FnMut(ViewEntry) -> Result<(), bonsaidb_core::Error> + Send + Sync>(
         &self,
         view: &dyn view::Serialized,
@@ -206,7 +808,46 @@ impl Database {
         access_policy: AccessPolicy,
         mut callback: F,
     ) -> Result<(), bonsaidb_core::Error> {
-        if matches!(access_policy, AccessPolicy::UpdateBefore) {
+        if let Some(key) = &key {
+            key.check_description(&view.view_name(), &view.key_description())?;
+        }
+
+        let updated_before = self.update_view_before_access(view, access_policy)?;
+
+        let view_entries = self
+            .roots()
+            .tree(self.collection_tree(
+                &view.collection(),
+                view_entries_tree_name(&view.view_name()),
+            )?)
+            .map_err(Error::from)?;
+
+        {
+            for entry in Self::create_view_iterator(&view_entries, key, order, limit)? {
+                callback(entry)?;
+            }
+        }
+
+        self.update_view_after_access(view, access_policy, updated_before)
+    }
+
+    /// Prepares `view` to be read using `access_policy`, returning true if the
+    /// view was updated.
+    fn update_view_before_access(
+        &self,
+        view: &dyn view::Serialized,
+        access_policy: AccessPolicy,
+    ) -> Result<bool, bonsaidb_core::Error> {
+        let update_before = match access_policy {
+            AccessPolicy::UpdateBefore => true,
+            AccessPolicy::UpdateAfterWithTimeout(timeout) => !self
+                .storage
+                .instance
+                .tasks()
+                .view_updated_within(view, self, timeout),
+            AccessPolicy::UpdateAfter | AccessPolicy::NoUpdate => false,
+        };
+        if update_before {
             self.storage
                 .instance
                 .tasks()
@@ -223,21 +864,23 @@ impl Database {
                 .map_err(Error::from)?;
         }
 
-        let view_entries = self
-            .roots()
-            .tree(self.collection_tree(
-                &view.collection(),
-                view_entries_tree_name(&view.view_name()),
-            )?)
-            .map_err(Error::from)?;
-
-        {
-            for entry in Self::create_view_iterator(&view_entries, key, order, limit)? {
-                callback(entry)?;
-            }
-        }
+        Ok(update_before)
+    }
 
-        if matches!(access_policy, AccessPolicy::UpdateAfter) {
+    /// Queues an update of `view` after it has been read, if required by
+    /// `access_policy`.
+    fn update_view_after_access(
+        &self,
+        view: &dyn view::Serialized,
+        access_policy: AccessPolicy,
+        updated_before: bool,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let update_after = match access_policy {
+            AccessPolicy::UpdateAfter => true,
+            AccessPolicy::UpdateAfterWithTimeout(_) => !updated_before,
+            AccessPolicy::UpdateBefore | AccessPolicy::NoUpdate => false,
+        };
+        if update_after {
             let db = self.clone();
             let view_name = view.view_name();
             let view = db
@@ -254,46 +897,360 @@ impl Database {
         Ok(())
     }
 
-    fn open_trees_for_transaction(&self, transaction: &Transaction) -> Result<OpenTrees, Error> {
+    /// Returns the trees that track the documents `view` has not yet mapped,
+    /// including those of the view's related collections.
+    fn view_invalidated_trees(
+        &self,
+        view: &dyn view::Serialized,
+    ) -> Result<Vec<TreeRoot<Unversioned, AnyFile>>, Error> {
+        let collection = view.collection();
+        let view_name = view.view_name();
+        let mut invalidated_trees = vec![self.collection_tree::<Unversioned, _>(
+            &collection,
+            view_invalidated_docs_tree_name(&view_name),
+        )?];
+        for related in view.related_collections() {
+            invalidated_trees.push(self.collection_tree::<Unversioned, _>(
+                &related,
+                view_related_invalidated_docs_tree_name(&view_name, &related),
+            )?);
+        }
+        Ok(invalidated_trees)
+    }
+
+    /// Returns true if any documents have been changed that `view` has not
+    /// yet mapped.
+    fn view_has_pending_changes(&self, view: &dyn view::Serialized) -> Result<bool, Error> {
+        for tree in self.view_invalidated_trees(view)? {
+            let mut pending = false;
+            self.roots().tree(tree)?.scan::<Infallible, _, _, _, _>(
+                &(..),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |_, _| {
+                    pending = true;
+                    ScanEvaluation::Stop
+                },
+                |_, _, _| Ok(()),
+            )?;
+            if pending {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Queries the mappings of the view named `view_name`. If
+    /// `include_related` is false, mappings emitted by documents in the view's
+    /// related collections are skipped, leaving only mappings whose source is
+    /// a document in the view's own collection.
+    fn query_mappings(
+        &self,
+        view_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        access_policy: AccessPolicy,
+        include_related: bool,
+    ) -> Result<Vec<view::map::Serialized>, bonsaidb_core::Error> {
+        let view = self.schematic().view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        let mut results = Vec::new();
+        self.for_each_in_view(view, key, order, limit, access_policy, |entry| {
+            for mapping in entry.mappings {
+                if include_related || mapping.collection.is_none() {
+                    results.push(view::map::Serialized {
+                        source: mapping.source,
+                        key: entry.key.clone(),
+                        value: mapping.value,
+                    });
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(results)
+    }
+
+    /// Queues the source documents of any `mappings` that are not present in
+    /// `documents` to be re-mapped, removing the stale mappings from `view`.
+    fn invalidate_stale_mappings(
+        &self,
+        view: &dyn view::Serialized,
+        mappings: &[view::map::Serialized],
+        documents: &BTreeMap<DocumentId, OwnedDocument>,
+    ) -> Result<(), Error> {
+        let mut stale_ids = mappings
+            .iter()
+            .filter(|mapping| !documents.contains_key(&mapping.source.id))
+            .map(|mapping| ArcBytes::from(mapping.source.id.as_ref().to_vec()))
+            .collect::<Vec<_>>();
+        if stale_ids.is_empty() {
+            return Ok(());
+        }
+
+        stale_ids.sort();
+        stale_ids.dedup();
+        let invalidated_docs = self.collection_tree::<Unversioned, _>(
+            &view.collection(),
+            view_invalidated_docs_tree_name(&view.view_name()),
+        )?;
+        let transaction = self.roots().transaction(&[invalidated_docs])?;
+        transaction
+            .tree::<Unversioned>(0)
+            .unwrap()
+            .modify(stale_ids, nebari::tree::Operation::Set(ArcBytes::default()))?;
+        transaction.commit()?;
+
+        // The view may already be considered up-to-date, so the mapper must be
+        // queued explicitly.
+        self.storage
+            .instance
+            .tasks()
+            .jobs
+            .lookup_or_enqueue(mapper::Mapper {
+                database: self.clone(),
+                map: mapper::Map {
+                    database: self.data.name.clone(),
+                    collection: view.collection(),
+                    view_name: view.view_name(),
+                },
+            });
+
+        Ok(())
+    }
+
+    /// Returns every view that a deletion in `transaction` can cascade through.
+    /// See [`Schematic::define_delete_cascade()`].
+    fn delete_cascade_views(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Vec<&'_ dyn view::Serialized>, bonsaidb_core::Error> {
+        let mut views = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = transaction
+            .operations
+            .iter()
+            .filter(|op| matches!(op.command, Command::Delete { .. }))
+            .map(|op| op.collection.clone())
+            .collect::<Vec<_>>();
+        while let Some(collection) = pending.pop() {
+            for view_name in self.data.schema.delete_cascades(&collection) {
+                if visited.insert(view_name) {
+                    let view = self.data.schema.view_by_name(view_name)?;
+                    pending.push(view.collection());
+                    views.push(view);
+                }
+            }
+        }
+
+        Ok(views)
+    }
+
+    /// Deletes the documents that the deletions in `results` cascade to,
+    /// returning the delete operations and their results.
+    ///
+    /// The cascades are resolved within `roots_transaction`, which holds the
+    /// locks of every collection a deletion can cascade into, so no other
+    /// transaction can change the documents being resolved.
+    fn execute_delete_cascades(
+        &self,
+        results: &[OperationResult],
+        roots_transaction: &mut ExecutingTransaction<AnyFile>,
+        open_trees: &OpenTrees,
+    ) -> Result<(Vec<Operation>, Vec<OperationResult>), Error> {
+        let mut changed = HashSet::new();
+        let mut deleted = HashSet::new();
+        let mut pending = HashMap::<CollectionName, Vec<DocumentId>>::new();
+        for result in results {
+            match result {
+                OperationResult::DocumentUpdated { header, collection } => {
+                    changed.insert((collection.clone(), header.id.clone()));
+                }
+                OperationResult::DocumentDeleted { id, collection } => {
+                    changed.insert((collection.clone(), id.clone()));
+                    if deleted.insert((collection.clone(), id.clone())) {
+                        pending
+                            .entry(collection.clone())
+                            .or_default()
+                            .push(id.clone());
+                    }
+                }
+                OperationResult::Success | OperationResult::DocumentRead { .. } => {}
+            }
+        }
+
+        let mut operations = Vec::new();
+        let mut cascaded_results = Vec::new();
+        let mut depth = 0;
+        while !pending.is_empty() {
+            let mut next = HashMap::<CollectionName, Vec<DocumentId>>::new();
+            for (collection, ids) in pending {
+                for view_name in self.data.schema.delete_cascades(&collection) {
+                    let view = self.data.schema.view_by_name(view_name)?;
+                    let child_collection = view.collection();
+                    let headers = self.cascaded_documents(
+                        view,
+                        &ids,
+                        &changed,
+                        roots_transaction,
+                        open_trees,
+                    )?;
+                    for header in headers {
+                        if !deleted.insert((child_collection.clone(), header.id.clone())) {
+                            continue;
+                        }
+                        if depth == schema::MAX_DELETE_CASCADE_DEPTH {
+                            return Err(Error::Core(bonsaidb_core::Error::DeleteCascadeTooDeep(
+                                child_collection,
+                            )));
+                        }
+
+                        self.check_permission(
+                            document_resource_name(self.name(), &child_collection, &header.id),
+                            &BonsaiAction::Database(DatabaseAction::Document(
+                                DocumentAction::Delete,
+                            )),
+                        )?;
+                        changed.insert((child_collection.clone(), header.id.clone()));
+                        next.entry(child_collection.clone())
+                            .or_default()
+                            .push(header.id.clone());
+                        let operation = Operation {
+                            collection: child_collection.clone(),
+                            command: Command::Delete { header },
+                        };
+                        cascaded_results.push(self.execute_operation(
+                            &operation,
+                            roots_transaction,
+                            &open_trees.trees_index_by_name,
+                        )?);
+                        operations.push(operation);
+                    }
+                }
+            }
+            pending = next;
+            depth += 1;
+        }
+
+        Ok((operations, cascaded_results))
+    }
+
+    /// Returns the headers of the documents that `view` maps to any of `keys`.
+    ///
+    /// Lazy views are updated before the transaction begins, but documents may
+    /// have been invalidated since. Those documents, and the documents in
+    /// `changed` by this transaction, are mapped again using their contents
+    /// within `roots_transaction` instead of using the view's entries.
+    fn cascaded_documents(
+        &self,
+        view: &dyn view::Serialized,
+        keys: &[DocumentId],
+        changed: &HashSet<(CollectionName, DocumentId)>,
+        roots_transaction: &mut ExecutingTransaction<AnyFile>,
+        open_trees: &OpenTrees,
+    ) -> Result<Vec<Header>, Error> {
+        let collection = view.collection();
+        let view_name = view.view_name();
+        let mut remapped = changed
+            .iter()
+            .filter(|(changed_collection, _)| changed_collection == &collection)
+            .map(|(_, id)| id.clone())
+            .collect::<HashSet<_>>();
+        if !view.update_policy().is_eager() {
+            let mut invalidated_docs = roots_transaction
+                .tree::<Unversioned>(
+                    open_trees.trees_index_by_name[&view_invalidated_docs_tree_name(&view_name)],
+                )
+                .unwrap();
+            for (id, _) in invalidated_docs.get_range(&(..))? {
+                remapped.insert(DocumentId::try_from(id.as_slice())?);
+            }
+        }
+
+        let view_entries = self
+            .roots()
+            .tree(self.collection_tree(&collection, view_entries_tree_name(&view_name))?)?;
+        let key = SerializedQueryKey::from(QueryKeyBytes::Multiple(
+            keys.iter().map(|id| Bytes::from(id.to_vec())).collect(),
+        ));
+        let mut headers = Vec::new();
+        for entry in Self::create_view_iterator(&view_entries, Some(key), Sort::Ascending, None)? {
+            for mapping in entry.mappings {
+                if mapping.collection.is_none() && !remapped.contains(&mapping.source.id) {
+                    headers.push(mapping.source);
+                }
+            }
+        }
+
+        if !remapped.is_empty() {
+            let mut documents = roots_transaction
+                .tree::<Versioned>(open_trees.trees_index_by_name[&document_tree_name(&collection)])
+                .unwrap();
+            for id in remapped {
+                let Some(contents) = documents.get(id.as_ref())? else {
+                    continue;
+                };
+                let document = deserialize_document(&contents)?;
+                let maps_to_key = view
+                    .map(&document)?
+                    .iter()
+                    .any(|mapping| keys.iter().any(|key| key.as_ref() == &mapping.key[..]));
+                if maps_to_key {
+                    headers.push(document.header.clone());
+                }
+            }
+        }
+
+        Ok(headers)
+    }
+
+    fn open_trees_for_collections<'a>(
+        &self,
+        collections: impl IntoIterator<Item = &'a CollectionName>,
+    ) -> Result<OpenTrees, Error> {
         let mut open_trees = OpenTrees::default();
-        for op in &transaction.operations {
+        for collection in collections {
             if self
                 .data
                 .schema
-                .collection_primary_key_description(&op.collection)
+                .collection_primary_key_description(collection)
                 .is_none()
             {
                 return Err(Error::Core(bonsaidb_core::Error::CollectionNotFound));
             }
 
             #[cfg(any(feature = "encryption", feature = "compression"))]
-            let vault = if let Some(encryption_key) =
-                self.collection_encryption_key(&op.collection).cloned()
-            {
-                #[cfg(feature = "encryption")]
-                if let Some(mut vault) = self.storage().tree_vault().cloned() {
-                    vault.key = Some(encryption_key);
-                    Some(vault)
-                } else {
-                    TreeVault::new_if_needed(
-                        Some(encryption_key),
-                        self.storage().vault(),
-                        #[cfg(feature = "compression")]
-                        None,
-                    )
-                }
+            let vault =
+                if let Some(encryption_key) = self.collection_encryption_key(collection).cloned() {
+                    #[cfg(feature = "encryption")]
+                    if let Some(mut vault) = self.storage().tree_vault().cloned() {
+                        vault.key = Some(encryption_key);
+                        Some(vault)
+                    } else {
+                        TreeVault::new_if_needed(
+                            Some(encryption_key),
+                            self.storage().vault(),
+                            #[cfg(feature = "compression")]
+                            None,
+                        )
+                    }
 
-                #[cfg(not(feature = "encryption"))]
-                {
-                    drop(encryption_key);
-                    return Err(Error::EncryptionDisabled);
-                }
-            } else {
-                self.storage().tree_vault().cloned()
-            };
+                    #[cfg(not(feature = "encryption"))]
+                    {
+                        drop(encryption_key);
+                        return Err(Error::EncryptionDisabled);
+                    }
+                } else {
+                    self.storage().tree_vault().cloned()
+                };
 
             open_trees.open_trees_for_document_change(
-                &op.collection,
+                collection,
                 &self.data.schema,
                 #[cfg(any(feature = "encryption", feature = "compression"))]
                 vault,
@@ -303,11 +1260,26 @@ impl Database {
         Ok(open_trees)
     }
 
+    /// Applies `transaction`, along with the deletions it cascades to through
+    /// `cascade_views`. The results of the cascaded deletions are returned
+    /// separately, so that the transaction's results correspond to its
+    /// operations.
     fn apply_transaction_to_roots(
         &self,
         transaction: &Transaction,
-    ) -> Result<Vec<OperationResult>, Error> {
-        let open_trees = self.open_trees_for_transaction(transaction)?;
+        cascade_views: &[&dyn view::Serialized],
+    ) -> Result<(AppliedTransaction, Vec<OperationResult>), Error> {
+        let cascade_collections = cascade_views
+            .iter()
+            .map(|view| view.collection())
+            .collect::<Vec<_>>();
+        let open_trees = self.open_trees_for_collections(
+            transaction
+                .operations
+                .iter()
+                .map(|op| &op.collection)
+                .chain(&cascade_collections),
+        )?;
 
         let mut roots_transaction = self
             .data
@@ -319,21 +1291,34 @@ impl Database {
         let mut changed_documents = Vec::new();
         let mut collection_indexes = HashMap::new();
         let mut collections = Vec::new();
-        for op in &transaction.operations {
-            let result = self.execute_operation(
-                op,
-                &mut roots_transaction,
-                &open_trees.trees_index_by_name,
-            )?;
+        let operation_count = transaction.operations.len();
+        for (index, op) in transaction.operations.iter().enumerate() {
+            let result = self
+                .execute_operation(op, &mut roots_transaction, &open_trees.trees_index_by_name)
+                .map_err(|err| {
+                    Error::Core(
+                        bonsaidb_core::Error::from(err)
+                            .for_transaction_operation(index, operation_count),
+                    )
+                })?;
+            results.push(result);
+        }
+
+        let (cascaded_operations, cascaded_results) = if cascade_views.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            self.execute_delete_cascades(&results, &mut roots_transaction, &open_trees)?
+        };
 
-            if let Some((collection, id, deleted)) = match &result {
+        for result in results.iter().chain(&cascaded_results) {
+            if let Some((collection, id, deleted)) = match result {
                 OperationResult::DocumentUpdated { header, collection } => {
                     Some((collection, header.id.clone(), false))
                 }
                 OperationResult::DocumentDeleted { id, collection } => {
                     Some((collection, id.clone(), true))
                 }
-                OperationResult::Success => None,
+                OperationResult::Success | OperationResult::DocumentRead { .. } => None,
             } {
                 let collection = match collection_indexes.get(collection) {
                     Some(index) => *index,
@@ -353,28 +1338,191 @@ impl Database {
                     deleted,
                 });
             }
-            results.push(result);
         }
 
-        self.invalidate_changed_documents(
-            &mut roots_transaction,
-            &open_trees,
-            &collections,
-            &changed_documents,
-        )?;
+        self.validate_changes(
+            transaction
+                .operations
+                .iter()
+                .chain(&cascaded_operations)
+                .zip(results.iter().chain(&cascaded_results)),
+        )?;
+
+        self.invalidate_changed_documents(
+            &mut roots_transaction,
+            &open_trees,
+            &collections,
+            &changed_documents,
+        )?;
+
+        let updated_eager_views = collections
+            .iter()
+            .flat_map(|collection| self.data.schema.eager_views_in_collection(collection))
+            .map(|view| view.view_name())
+            .collect::<Vec<_>>();
+
+        let changes = self.serialize_transaction_changes(&Changes::Documents(DocumentChanges {
+            collections,
+            documents: changed_documents,
+        }))?;
+        roots_transaction.entry_mut().set_data(changes)?;
+
+        let id = roots_transaction.entry().id;
+        roots_transaction.commit()?;
+        self.invalidate_cached_documents(results.iter().chain(&cascaded_results));
+        self.data.context.transaction_watchers.notify(id);
+
+        for view in &updated_eager_views {
+            self.invalidate_all_cached_reductions(view);
+        }
+
+        self.publish_document_changes(
+            id,
+            transaction
+                .operations
+                .iter()
+                .chain(&cascaded_operations)
+                .zip(results.iter().chain(&cascaded_results)),
+        );
+
+        Ok((AppliedTransaction { id, results }, cascaded_results))
+    }
+
+    /// Removes the documents changed by `results` from the document cache.
+    fn invalidate_cached_documents<'a>(
+        &self,
+        results: impl IntoIterator<Item = &'a OperationResult>,
+    ) {
+        let document_cache = self.storage.instance.document_cache();
+        if document_cache.enabled() {
+            document_cache.invalidate(
+                self.name(),
+                results.into_iter().filter_map(|result| match result {
+                    OperationResult::DocumentUpdated { header, collection } => {
+                        Some((collection, &header.id))
+                    }
+                    OperationResult::DocumentDeleted { id, collection } => Some((collection, id)),
+                    OperationResult::Success | OperationResult::DocumentRead { .. } => None,
+                }),
+            );
+        }
+    }
+
+    /// Updates every lazy view that maps documents from a collection changed
+    /// by `results`, waiting for each update to complete.
+    fn update_views_affected_by<'a>(
+        &self,
+        results: impl IntoIterator<Item = &'a OperationResult>,
+    ) -> Result<(), Error> {
+        let changed_collections = results
+            .into_iter()
+            .filter_map(|result| match result {
+                OperationResult::DocumentUpdated { collection, .. }
+                | OperationResult::DocumentDeleted { collection, .. } => Some(collection),
+                OperationResult::Success | OperationResult::DocumentRead { .. } => None,
+            })
+            .collect::<HashSet<_>>();
+        for collection in changed_collections {
+            let views = self
+                .data
+                .schema
+                .views_in_collection(collection)
+                .chain(self.data.schema.views_related_to_collection(collection));
+            for view in views {
+                // Eager views were updated within the transaction.
+                if !view.update_policy().is_eager() {
+                    self.storage
+                        .instance
+                        .tasks()
+                        .update_view_if_needed(view, self, true)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 
-        roots_transaction
-            .entry_mut()
-            .set_data(compat::serialize_executed_transaction_changes(
-                &Changes::Documents(DocumentChanges {
-                    collections,
-                    documents: changed_documents,
-                }),
-            )?)?;
+    /// Publishes a [`DocumentChange`] for each document changed by the
+    /// committed operations in collections that publish their changes.
+    fn publish_document_changes<'a>(
+        &self,
+        transaction_id: u64,
+        operations: impl IntoIterator<Item = (&'a Operation, &'a OperationResult)>,
+    ) {
+        for (operation, result) in operations {
+            if !self.data.schema.publishes_changes(&operation.collection) {
+                continue;
+            }
 
-        roots_transaction.commit()?;
+            let change = match (&operation.command, result) {
+                (_, OperationResult::DocumentUpdated { header, .. }) => DocumentChange {
+                    transaction_id,
+                    id: header.id.clone(),
+                    revision: header.revision,
+                    deleted: false,
+                },
+                (Command::Delete { header }, OperationResult::DocumentDeleted { .. }) => {
+                    DocumentChange {
+                        transaction_id,
+                        id: header.id.clone(),
+                        revision: header.revision,
+                        deleted: true,
+                    }
+                }
+                _ => continue,
+            };
+            let topic = pot::to_vec(&document_changes_topic(&operation.collection))
+                .expect("strings are always serializable");
+            let payload = pot::to_vec(&change).expect("document changes are always serializable");
+            self.storage
+                .instance
+                .publish_to_topics(&self.data.name, vec![topic], payload);
+        }
+    }
 
-        Ok(results)
+    fn validate_changes<'a>(
+        &self,
+        operations: impl IntoIterator<Item = (&'a Operation, &'a OperationResult)>,
+    ) -> Result<(), Error> {
+        let mut changes_by_collection = HashMap::<&CollectionName, Vec<ProposedChange<'_>>>::new();
+        for (operation, result) in operations {
+            let change = match (&operation.command, result) {
+                (
+                    Command::Insert { contents, .. }
+                    | Command::Update { contents, .. }
+                    | Command::Overwrite { contents, .. },
+                    OperationResult::DocumentUpdated { header, .. },
+                ) => ProposedChange {
+                    id: &header.id,
+                    contents: Some(&contents[..]),
+                    // Overwriting a document that doesn't exist creates it with
+                    // the first revision.
+                    inserted: match &operation.command {
+                        Command::Insert { .. } => true,
+                        Command::Overwrite { .. } => header.revision.id == 0,
+                        _ => false,
+                    },
+                },
+                (Command::Delete { .. }, OperationResult::DocumentDeleted { id, .. }) => {
+                    ProposedChange {
+                        id,
+                        contents: None,
+                        inserted: false,
+                    }
+                }
+                _ => continue,
+            };
+            changes_by_collection
+                .entry(&operation.collection)
+                .or_default()
+                .push(change);
+        }
+
+        for (collection, changes) in changes_by_collection {
+            self.data.schema.validate_changes(collection, &changes)?;
+        }
+
+        Ok(())
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
@@ -389,17 +1537,24 @@ impl Database {
             .iter()
             .group_by(|doc| &collections[usize::from(doc.collection)])
         {
-            let mut views = self
+            let mut invalidated_trees = self
                 .data
                 .schema
                 .views_in_collection(collection)
                 .filter(|view| !view.update_policy().is_eager())
+                .map(|view| view_invalidated_docs_tree_name(&view.view_name()))
+                .chain(
+                    self.data
+                        .schema
+                        .views_related_to_collection(collection)
+                        .map(|view| {
+                            view_related_invalidated_docs_tree_name(&view.view_name(), collection)
+                        }),
+                )
                 .peekable();
-            if views.peek().is_some() {
+            if invalidated_trees.peek().is_some() {
                 let changed_documents = changed_documents.collect::<Vec<_>>();
-                for view in views {
-                    let view_name = view.view_name();
-                    let tree_name = view_invalidated_docs_tree_name(&view_name);
+                for tree_name in invalidated_trees {
                     for changed_document in &changed_documents {
                         let mut invalidated_docs = roots_transaction
                             .tree::<Unversioned>(open_trees.trees_index_by_name[&tree_name])
@@ -443,6 +1598,7 @@ impl Database {
                 id.clone(),
                 *revision,
             ),
+            Command::Get { id } => Self::execute_get(operation, transaction, tree_index_map, id),
         }
     }
 
@@ -467,12 +1623,17 @@ impl Database {
         check_revision: Option<&Revision>,
         contents: &[u8],
     ) -> Result<OperationResult, crate::Error> {
+        self.data
+            .schema
+            .validate_document(&operation.collection, contents)?;
         let mut documents = transaction
             .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
         let document_id = ArcBytes::from(id.to_vec());
+        let history_limit = self.data.schema.history_limit(&operation.collection);
         let mut result = None;
         let mut updated = false;
+        let mut previous = None;
         documents.modify(
             vec![document_id.clone()],
             nebari::tree::Operation::CompareSwap(CompareSwap::new(&mut |_key,
@@ -509,6 +1670,9 @@ impl Database {
                                 header: updated_header,
                             }));
                             updated = true;
+                            if history_limit > 0 {
+                                previous = Some((doc.header.clone(), old.to_vec()));
+                            }
                             return nebari::tree::KeyOperation::Set(ArcBytes::from(serialized_doc));
                         }
 
@@ -552,6 +1716,17 @@ impl Database {
         )?;
         drop(documents);
 
+        if let Some((previous_header, previous_contents)) = previous {
+            Self::record_history(
+                operation,
+                transaction,
+                tree_index_map,
+                &previous_header,
+                previous_contents,
+                history_limit,
+            )?;
+        }
+
         if updated {
             self.update_eager_views(&document_id, operation, transaction, tree_index_map)?;
         }
@@ -579,11 +1754,26 @@ impl Database {
         id: Option<DocumentId>,
         contents: &[u8],
     ) -> Result<OperationResult, Error> {
+        self.data
+            .schema
+            .validate_document(&operation.collection, contents)?;
         let mut documents = transaction
             .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
             .unwrap();
         let id = if let Some(id) = id {
             id
+        } else if self.data.schema.id_strategy(&operation.collection) != IdStrategy::Sequential {
+            // Randomly generated ids may collide with an existing document, in
+            // which case another id is generated.
+            loop {
+                let id = self
+                    .data
+                    .schema
+                    .next_id_for_collection(&operation.collection, None)?;
+                if documents.get(id.as_ref())?.is_none() {
+                    break id;
+                }
+            }
         } else if let Some(last_key) = documents.last_key()? {
             let id = DocumentId::try_from(last_key.as_slice())?;
             self.data
@@ -638,6 +1828,18 @@ impl Database {
             drop(documents);
             let doc = deserialize_document(&vec)?;
             if &doc.header == header {
+                let history_limit = self.data.schema.history_limit(&operation.collection);
+                if history_limit > 0 {
+                    Self::record_history(
+                        operation,
+                        transaction,
+                        tree_index_map,
+                        header,
+                        vec.to_vec(),
+                        history_limit,
+                    )?;
+                }
+
                 self.update_eager_views(
                     &ArcBytes::from(doc.header.id.to_vec()),
                     operation,
@@ -663,6 +1865,49 @@ impl Database {
         }
     }
 
+    /// Stores `contents`, the serialized document with `header`, in the
+    /// collection's history and removes the oldest revisions of the document
+    /// that exceed `history_limit`.
+    fn record_history(
+        operation: &Operation,
+        transaction: &mut ExecutingTransaction<AnyFile>,
+        tree_index_map: &HashMap<String, usize>,
+        header: &Header,
+        contents: Vec<u8>,
+        history_limit: u32,
+    ) -> Result<(), Error> {
+        let transaction_id = transaction.entry().id;
+        let mut history = transaction
+            .tree::<Unversioned>(tree_index_map[&history_tree_name(&operation.collection)])
+            .unwrap();
+        history.set(
+            history_key(&header.id, transaction_id, header.revision.id),
+            contents,
+        )?;
+
+        let (start, end) = history_key_range(&header.id);
+        let mut revisions = Vec::new();
+        history.scan::<Infallible, _, _, _, _>(
+            &(&start[..]..=&end[..]),
+            true,
+            |_, _, _| ScanEvaluation::ReadData,
+            |key, _| {
+                revisions.push(key.clone());
+                ScanEvaluation::Skip
+            },
+            |_, _, _| Ok(()),
+        )?;
+        // Keys are ordered by the transaction that recorded them, so the
+        // oldest revisions are at the start of the scan.
+        let excess = revisions.len().saturating_sub(history_limit as usize);
+        if excess > 0 {
+            revisions.truncate(excess);
+            history.modify(revisions, nebari::tree::Operation::Remove)?;
+        }
+
+        Ok(())
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
         skip(self, operation, transaction, tree_index_map),
@@ -708,6 +1953,7 @@ impl Database {
                     documents,
                     view_entries,
                     view,
+                    related: None,
                 }
                 .map()?;
             }
@@ -756,6 +2002,34 @@ impl Database {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(operation, transaction, tree_index_map),
+        fields(
+            collection.name = operation.collection.name.as_ref(),
+            collection.authority = operation.collection.authority.as_ref(),
+        ),
+    ))]
+    fn execute_get(
+        operation: &Operation,
+        transaction: &mut ExecutingTransaction<AnyFile>,
+        tree_index_map: &HashMap<String, usize>,
+        id: &DocumentId,
+    ) -> Result<OperationResult, Error> {
+        let mut documents = transaction
+            .tree::<Versioned>(tree_index_map[&document_tree_name(&operation.collection)])
+            .unwrap();
+        let document = documents
+            .get(id.as_ref())?
+            .map(|vec| deserialize_document(&vec).map(BorrowedDocument::into_owned))
+            .transpose()?;
+
+        Ok(OperationResult::DocumentRead {
+            collection: operation.collection.clone(),
+            document,
+        })
+    }
+
     fn create_view_iterator(
         view_entries: &Tree<Unversioned, AnyFile>,
         key: Option<SerializedQueryKey>,
@@ -769,8 +2043,8 @@ impl Database {
         };
         let mut values_read = 0;
         if let Some(key) = key {
-            match key {
-                SerializedQueryKey::Range(range) => {
+            match key.keys {
+                QueryKeyBytes::Range(range) => {
                     view_entries.scan::<Infallible, _, _, _, _>(
                         &range.map_ref(|bytes| &bytes[..]),
                         forwards,
@@ -790,10 +2064,10 @@ impl Database {
                         },
                     )?;
                 }
-                SerializedQueryKey::Matches(key) => {
+                QueryKeyBytes::Matches(key) => {
                     values.extend(view_entries.get(&key)?);
                 }
-                SerializedQueryKey::Multiple(mut list) => {
+                QueryKeyBytes::Multiple(mut list) => {
                     list.sort();
 
                     values.extend(
@@ -838,6 +2112,42 @@ impl Database {
             .or_else(|| self.storage.default_encryption_key())
     }
 
+    /// Serializes `changes` to be stored in the transaction log. If any of the
+    /// changed collections are encrypted, the changes are encrypted using the
+    /// first encrypted collection's key.
+    fn serialize_transaction_changes(&self, changes: &Changes) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "encryption")]
+        if let Changes::Documents(documents) = changes {
+            if let Some(key) = documents
+                .collections
+                .iter()
+                .find_map(|collection| self.collection_encryption_key(collection))
+            {
+                return compat::serialize_encrypted_transaction_changes(changes, |payload| {
+                    self.storage.vault().encrypt_payload(key, payload, None)
+                });
+            }
+        }
+
+        compat::serialize_executed_transaction_changes(changes)
+    }
+
+    #[cfg_attr(
+        not(feature = "encryption"),
+        allow(unused_variables, clippy::unused_self)
+    )]
+    fn decrypt_transaction_changes(&self, encrypted: &[u8]) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "encryption")]
+        {
+            self.storage.vault().decrypt_payload(encrypted, None)
+        }
+
+        #[cfg(not(feature = "encryption"))]
+        {
+            Err(Error::EncryptionDisabled)
+        }
+    }
+
     #[cfg_attr(
         not(feature = "encryption"),
         allow(
@@ -965,10 +2275,23 @@ struct LegacyDocument<'a> {
     contents: &'a [u8],
 }
 
+/// Deserializes a stored document.
+///
+/// A document never needs to allocate more bytes than it occupies on disk, so
+/// the decoders are limited to `bytes.len()`. A corrupt length prefix results
+/// in an error rather than an attempt to allocate an arbitrarily large buffer.
 pub(crate) fn deserialize_document(bytes: &[u8]) -> Result<BorrowedDocument<'_>, Error> {
-    match pot::from_slice::<BorrowedDocument<'_>>(bytes) {
+    let document = pot::Config::default()
+        .allocation_budget(bytes.len())
+        .deserialize::<BorrowedDocument<'_>>(bytes);
+    match document {
         Ok(document) => Ok(document),
-        Err(err) => match bincode::deserialize::<LegacyDocument<'_>>(bytes) {
+        Err(err) => match bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(bytes.len() as u64)
+            .deserialize::<LegacyDocument<'_>>(bytes)
+        {
             Ok(legacy_doc) => Ok(BorrowedDocument {
                 header: Header {
                     id: DocumentId::from_u64(legacy_doc.header.id),
@@ -987,6 +2310,110 @@ fn serialize_document(document: &BorrowedDocument<'_>) -> Result<Vec<u8>, bonsai
         .map_err(bonsaidb_core::Error::from)
 }
 
+/// The number of values [`ReduceAccumulator`] buffers before rereducing them
+/// into a single value.
+const REDUCE_BATCH_SIZE: usize = 256;
+
+/// Incrementally rereduces the entries of a view. At most
+/// [`REDUCE_BATCH_SIZE`] values are buffered at once, regardless of how many
+/// entries are reduced.
+struct ReduceAccumulator<'a> {
+    view: &'a dyn view::Serialized,
+    pending: Vec<MappedSerializedValue>,
+}
+
+impl<'a> ReduceAccumulator<'a> {
+    fn new(view: &'a dyn view::Serialized) -> Self {
+        Self {
+            view,
+            pending: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, mapping: MappedSerializedValue) -> Result<(), bonsaidb_core::Error> {
+        self.pending.push(mapping);
+        if self.pending.len() >= REDUCE_BATCH_SIZE {
+            let reduced = reduce_group(self.view, &mut self.pending)?;
+            self.pending.extend(reduced);
+        }
+        Ok(())
+    }
+
+    /// Returns the reduced value keyed by the first key pushed, or `None` if
+    /// no entries were pushed.
+    fn finish(mut self) -> Result<Option<MappedSerializedValue>, bonsaidb_core::Error> {
+        reduce_group(self.view, &mut self.pending)
+    }
+}
+
+/// Merges entries, which must be pushed in order of their keys, into groups
+/// of entries that share the first `group_level` components of their keys.
+/// Each group is reduced as its entries are pushed, so only the group being
+/// built is buffered.
+struct GroupedReducer<'a> {
+    view: &'a dyn view::Serialized,
+    group_level: usize,
+    current_prefix: Option<Vec<u8>>,
+    group: ReduceAccumulator<'a>,
+    grouped: Vec<MappedSerializedValue>,
+}
+
+impl<'a> GroupedReducer<'a> {
+    fn new(view: &'a dyn view::Serialized, group_level: usize) -> Self {
+        Self {
+            view,
+            group_level,
+            current_prefix: None,
+            group: ReduceAccumulator::new(view),
+            grouped: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, mapping: MappedSerializedValue) -> Result<(), bonsaidb_core::Error> {
+        let prefix = self
+            .view
+            .group_prefix(&mapping.key, self.group_level)
+            .map_err(Error::from)?;
+        if self.current_prefix.as_ref() != Some(&prefix) {
+            let group = std::mem::replace(&mut self.group, ReduceAccumulator::new(self.view));
+            self.grouped.extend(group.finish()?);
+            self.current_prefix = Some(prefix);
+        }
+        self.group.push(mapping)
+    }
+
+    fn finish(mut self) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
+        self.grouped.extend(self.group.finish()?);
+        Ok(self.grouped)
+    }
+}
+
+/// Rereduces and empties `group`. The first key in the group is used as the
+/// key of the reduced value.
+fn reduce_group(
+    view: &dyn view::Serialized,
+    group: &mut Vec<MappedSerializedValue>,
+) -> Result<Option<MappedSerializedValue>, bonsaidb_core::Error> {
+    if group.len() < 2 {
+        return Ok(group.pop());
+    }
+
+    let value = view
+        .reduce(
+            &group
+                .iter()
+                .map(|map| (map.key.as_ref(), map.value.as_ref()))
+                .collect::<Vec<_>>(),
+            true,
+        )
+        .map_err(Error::from)?;
+    let first = group.drain(..).next().expect("group is not empty");
+    Ok(Some(MappedSerializedValue {
+        key: first.key,
+        value: Bytes::from(value),
+    }))
+}
+
 impl HasSession for Database {
     fn session(&self) -> Option<&Session> {
         self.storage.session()
@@ -1044,7 +2471,10 @@ impl Connection for Database {
                 .into_iter()
                 .map(|entry| {
                     if let Some(data) = entry.data() {
-                        let changes = compat::deserialize_executed_transaction_changes(data)?;
+                        let changes =
+                            compat::deserialize_executed_transaction_changes(data, |encrypted| {
+                                self.decrypt_transaction_changes(encrypted)
+                            })?;
                         Ok(Some(transaction::Executed {
                             id: entry.id,
                             changes,
@@ -1123,13 +2553,27 @@ impl LowLevelConnection for Database {
         skip(self,  transaction),
         fields(
             database = self.name(),
+            operations = transaction.operations.len(),
+            transaction.id = tracing::field::Empty,
+            error = tracing::field::Empty,
         )
     ))]
-    fn apply_transaction(
+    fn apply_transaction_with_id(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
-        for op in &transaction.operations {
+    ) -> Result<AppliedTransaction, bonsaidb_core::Error> {
+        // Transactions that only check or read documents are allowed through
+        // read-only handles.
+        if transaction
+            .operations
+            .iter()
+            .any(|op| !matches!(op.command, Command::Check { .. } | Command::Get { .. }))
+        {
+            self.check_writable()?;
+        }
+
+        let operation_count = transaction.operations.len();
+        for (index, op) in transaction.operations.iter().enumerate() {
             let (resource, action) = match &op.command {
                 Command::Insert { .. } => (
                     collection_resource_name(self.name(), &op.collection),
@@ -1147,19 +2591,38 @@ impl LowLevelConnection for Database {
                     document_resource_name(self.name(), &op.collection, &header.id),
                     BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Delete)),
                 ),
-                Command::Check { id, .. } => (
+                Command::Check { id, .. } | Command::Get { id } => (
                     document_resource_name(self.name(), &op.collection, id),
                     BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
                 ),
             };
-            self.check_permission(resource, &action)?;
+            self.check_permission(resource, &action)
+                .map_err(|err| err.for_transaction_operation(index, operation_count))?;
+        }
+
+        // Views can't be updated while the transaction holds the locks of
+        // their collections, so the lazy views that deletions cascade through
+        // are updated beforehand.
+        let cascade_views = self.delete_cascade_views(&transaction)?;
+        for view in &cascade_views {
+            if !view.update_policy().is_eager() {
+                self.storage
+                    .instance
+                    .tasks()
+                    .update_view_if_needed(*view, self, true)?;
+            }
         }
+        let cascade_collections = cascade_views
+            .iter()
+            .map(|view| view.collection())
+            .collect::<Vec<_>>();
 
         let mut eager_view_tasks = Vec::new();
         for collection_name in transaction
             .operations
             .iter()
             .map(|op| &op.collection)
+            .chain(&cascade_collections)
             .collect::<HashSet<_>>()
         {
             for view in self.data.schema.eager_views_in_collection(collection_name) {
@@ -1188,8 +2651,36 @@ impl LowLevelConnection for Database {
             }
         }
 
-        self.apply_transaction_to_roots(&transaction)
-            .map_err(bonsaidb_core::Error::from)
+        let applied = self.apply_transaction_to_roots(&transaction, &cascade_views);
+        // Conflicts and other errors that abort the transaction are recorded
+        // on the span, as they would otherwise only be visible to the caller.
+        #[cfg(feature = "tracing")]
+        match &applied {
+            Ok((applied, _)) => {
+                tracing::Span::current().record("transaction.id", applied.id);
+            }
+            Err(err) => {
+                tracing::Span::current().record("error", tracing::field::display(err));
+            }
+        }
+        let (applied, cascaded_results) = applied.map_err(bonsaidb_core::Error::from)?;
+
+        // The transaction has been committed, so failing to update a view
+        // must not be reported as the transaction failing. The view will be
+        // updated again the next time it is queried.
+        if self.storage.instance.update_views_on_write() {
+            if let Err(err) =
+                self.update_views_affected_by(applied.results.iter().chain(&cascaded_results))
+            {
+                log::error!(
+                    "error updating views after transaction {} in {}: {err}",
+                    applied.id,
+                    self.name()
+                );
+            }
+        }
+
+        Ok(applied)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1210,6 +2701,15 @@ impl LowLevelConnection for Database {
             document_resource_name(self.name(), collection, &id),
             &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
         )?;
+        let document_cache = self.storage.instance.document_cache();
+        let generation = if document_cache.enabled() {
+            match document_cache.get(self.name(), collection, &id) {
+                Ok(document) => return Ok(Some(document)),
+                Err(generation) => Some(generation),
+            }
+        } else {
+            None
+        };
         let tree = self
             .data
             .context
@@ -1217,12 +2717,108 @@ impl LowLevelConnection for Database {
             .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
             .map_err(Error::from)?;
         if let Some(vec) = tree.get(id.as_ref()).map_err(Error::from)? {
-            Ok(Some(deserialize_document(&vec)?.into_owned()))
+            let document = deserialize_document(&vec)?.into_owned();
+            if let Some(generation) = generation {
+                document_cache.insert(self.name(), collection, document.clone(), generation);
+            }
+            Ok(Some(document))
         } else {
             Ok(None)
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, collection),
+        fields(
+            database = self.name(),
+            collection.name = collection.name.as_ref(),
+            collection.authority = collection.authority.as_ref(),
+        )
+    ))]
+    fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        self.check_permission(
+            document_resource_name(self.name(), collection, &id),
+            &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+        )?;
+        if self.data.schema.history_limit(collection) == 0 {
+            return Ok(Vec::new());
+        }
+
+        let tree = self
+            .data
+            .context
+            .roots
+            .tree(
+                self.collection_tree::<Unversioned, _>(collection, history_tree_name(collection))?,
+            )
+            .map_err(Error::from)?;
+        let (start, end) = history_key_range(&id);
+        tree.get_range(&(&start[..]..=&end[..]))
+            .map_err(Error::from)?
+            .into_iter()
+            .map(|(_, contents)| {
+                deserialize_document(&contents)
+                    .map(|doc| doc.header)
+                    .map_err(bonsaidb_core::Error::from)
+            })
+            .collect()
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, collection),
+        fields(
+            database = self.name(),
+            collection.name = collection.name.as_ref(),
+            collection.authority = collection.authority.as_ref(),
+        )
+    ))]
+    fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        if let Some(current) = self.get_from_collection(id.clone(), collection)? {
+            if current.header.revision == revision {
+                return Ok(Some(current));
+            }
+        }
+        if self.data.schema.history_limit(collection) == 0 {
+            return Err(bonsaidb_core::Error::HistoryNotRetained(collection.clone()));
+        }
+
+        let tree = self
+            .data
+            .context
+            .roots
+            .tree(
+                self.collection_tree::<Unversioned, _>(collection, history_tree_name(collection))?,
+            )
+            .map_err(Error::from)?;
+        // A document that was deleted and recreated can have more than one
+        // retained revision with the same id, so the most recent match wins.
+        let (start, end) = history_key_range(&id);
+        for (_, contents) in tree
+            .get_range(&(&start[..]..=&end[..]))
+            .map_err(Error::from)?
+            .into_iter()
+            .rev()
+        {
+            let doc = deserialize_document(&contents)?;
+            if doc.header.revision == revision {
+                return Ok(Some(doc.into_owned()));
+            }
+        }
+
+        Ok(None)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(
         level = "trace",
         skip(self, collection),
@@ -1414,11 +3010,84 @@ impl LowLevelConnection for Database {
             .get_multiple(ids.iter().map(|id| id.as_ref()))
             .map_err(Error::from)?;
 
-        keys_and_values
-            .into_iter()
-            .map(|(_, value)| deserialize_document(&value).map(BorrowedDocument::into_owned))
-            .collect::<Result<Vec<_>, Error>>()
-            .map_err(bonsaidb_core::Error::from)
+        keys_and_values
+            .into_iter()
+            .map(|(_, value)| deserialize_document(&value).map(BorrowedDocument::into_owned))
+            .collect::<Result<Vec<_>, Error>>()
+            .map_err(bonsaidb_core::Error::from)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, ids, collection),
+        fields(
+            database = self.name(),
+            collection.name = collection.name.as_ref(),
+            collection.authority = collection.authority.as_ref(),
+        )
+    ))]
+    fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        for id in ids {
+            self.check_permission(
+                document_resource_name(self.name(), collection, id),
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::Get)),
+            )?;
+        }
+        let mut sorted = ids.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        let (Some(first), Some(last)) = (sorted.first(), sorted.last()) else {
+            return Ok(Vec::new());
+        };
+        let tree = self
+            .data
+            .context
+            .roots
+            .tree(self.collection_tree::<Versioned, _>(collection, document_tree_name(collection))?)
+            .map_err(Error::from)?;
+
+        // Walk the keys between the first and last requested ids without
+        // reading any documents. Nodes whose keys are all less than the next
+        // requested id are skipped entirely.
+        let next = Cell::new(0);
+        let mut existing = HashSet::new();
+        tree.scan::<Infallible, _, _, _, _>(
+            &(first.as_ref()..=last.as_ref()),
+            true,
+            |max_key, _, _| match sorted.get(next.get()) {
+                Some(id) if max_key.as_slice() < id.as_ref() => ScanEvaluation::Skip,
+                Some(_) => ScanEvaluation::ReadData,
+                None => ScanEvaluation::Stop,
+            },
+            |key, _| {
+                while let Some(id) = sorted.get(next.get()) {
+                    if id.as_ref() > key.as_slice() {
+                        break;
+                    } else if id.as_ref() == key.as_slice() {
+                        existing.insert(id);
+                    }
+                    next.set(next.get() + 1);
+                }
+
+                if next.get() < sorted.len() {
+                    ScanEvaluation::Skip
+                } else {
+                    ScanEvaluation::Stop
+                }
+            },
+            |_, _, _| unreachable!(),
+        )
+        .map_err(Error::from)?;
+
+        Ok(ids
+            .iter()
+            .filter(|id| existing.contains(id))
+            .cloned()
+            .collect())
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1453,6 +3122,7 @@ impl LowLevelConnection for Database {
             view.collection.name = view.collection.name.as_ref(),
             view.collection.authority = view.collection.authority.as_ref(),
             view.name = view.name.as_ref(),
+            mappings = tracing::field::Empty,
         )
     ))]
     fn query_by_name(
@@ -1463,15 +3133,47 @@ impl LowLevelConnection for Database {
         limit: Option<u32>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<schema::view::map::Serialized>, bonsaidb_core::Error> {
+        let mappings = self.query_mappings(view, key, order, limit, access_policy, true)?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("mappings", mappings.len());
+        Ok(mappings)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view),
+        fields(
+            database = self.name(),
+            view.collection.name = view.collection.name.as_ref(),
+            view.collection.authority = view.collection.authority.as_ref(),
+            view.name = view.name.as_ref(),
+        )
+    ))]
+    fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
         let view = self.schematic().view_by_name(view)?;
         self.check_permission(
             view_resource_name(self.name(), &view.view_name()),
             &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
         )?;
-        let mut results = Vec::new();
-        self.for_each_in_view(view, key, order, limit, access_policy, |entry| {
+        let key = match &after {
+            Some(after) => Some(query_key_resuming_at(key, &after.key, order)),
+            None => key,
+        };
+        // The entry containing the cursor may not contribute any mappings, and
+        // one extra mapping is needed to know whether another page exists.
+        let entry_limit = limit.map(|limit| limit.saturating_add(2));
+        let mut mappings = Vec::new();
+        self.for_each_in_view(view, key, order, entry_limit, access_policy, |entry| {
             for mapping in entry.mappings {
-                results.push(bonsaidb_core::schema::view::map::Serialized {
+                mappings.push(bonsaidb_core::schema::view::map::Serialized {
                     source: mapping.source,
                     key: entry.key.clone(),
                     value: mapping.value,
@@ -1480,7 +3182,12 @@ impl LowLevelConnection for Database {
             Ok(())
         })?;
 
-        Ok(results)
+        Ok(SerializedViewPage::paginate(
+            mappings,
+            order,
+            limit,
+            after.as_ref(),
+        ))
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1501,7 +3208,9 @@ impl LowLevelConnection for Database {
         limit: Option<u32>,
         access_policy: AccessPolicy,
     ) -> Result<schema::view::map::MappedSerializedDocuments, bonsaidb_core::Error> {
-        let results = self.query_by_name(view, key, order, limit, access_policy)?;
+        // Mappings emitted by documents in related collections have no source
+        // document in the view's collection to load.
+        let results = self.query_mappings(view, key, order, limit, access_policy, false)?;
         let view = self.schematic().view_by_name(view).unwrap(); // query() will fail if it's not present
 
         let documents = self
@@ -1516,6 +3225,10 @@ impl LowLevelConnection for Database {
             .map(|doc| (doc.header.id.clone(), doc))
             .collect::<BTreeMap<_, _>>();
 
+        if self.storage.instance.repair_stale_view_mappings() {
+            self.invalidate_stale_mappings(view, &results, &documents)?;
+        }
+
         Ok(
             bonsaidb_core::schema::view::map::MappedSerializedDocuments {
                 mappings: results,
@@ -1540,22 +3253,61 @@ impl LowLevelConnection for Database {
         key: Option<SerializedQueryKey>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<u8>, bonsaidb_core::Error> {
-        let mut mappings = self.reduce_grouped_by_name(view_name, key, access_policy)?;
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
+        )?;
 
-        let result = if mappings.len() == 1 {
-            mappings.pop().unwrap().value.into_vec()
-        } else {
-            let view = self.data.schema.view_by_name(view_name)?;
-            view.reduce(
-                &mappings
-                    .iter()
-                    .map(|map| (map.key.as_ref(), map.value.as_ref()))
-                    .collect::<Vec<_>>(),
-                true,
-            )
-            .map_err(Error::from)?
+        // UpdateAfter and UpdateAfterWithTimeout must still reach
+        // for_each_in_view to queue the update, so only the other policies can
+        // be served from the cache. UpdateBefore updates the view before the
+        // cache is read, which leaves nothing for for_each_in_view to update.
+        let (cache_key, access_policy) = match access_policy {
+            AccessPolicy::UpdateAfter | AccessPolicy::UpdateAfterWithTimeout(_) => {
+                (None, access_policy)
+            }
+            AccessPolicy::UpdateBefore => {
+                self.storage
+                    .instance
+                    .tasks()
+                    .update_view_if_needed(view, self, true)?;
+                (
+                    ReduceCacheKey::new(view.view_name(), key.as_ref()),
+                    AccessPolicy::NoUpdate,
+                )
+            }
+            AccessPolicy::NoUpdate => (
+                ReduceCacheKey::new(view.view_name(), key.as_ref()),
+                AccessPolicy::NoUpdate,
+            ),
+        };
+        let reduced_keys = key.as_ref().map(|key| key.keys.clone());
+        let cache = &self.data.context.reduce_cache;
+        let (generation, cached) = cache_key
+            .as_ref()
+            .map_or((0, None), |cache_key| cache.get(cache_key));
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+
+        let mut accumulator = ReduceAccumulator::new(view);
+        self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
+            accumulator.push(MappedSerializedValue {
+                key: entry.key,
+                value: entry.reduced_value,
+            })
+        })?;
+
+        let result = match accumulator.finish()? {
+            Some(reduced) => reduced.value.into_vec(),
+            None => view.reduce(&[], true).map_err(Error::from)?,
         };
 
+        if let Some(cache_key) = cache_key {
+            cache.insert(generation, cache_key, reduced_keys, result.clone());
+        }
+
         Ok(result)
     }
 
@@ -1573,6 +3325,7 @@ impl LowLevelConnection for Database {
         &self,
         view_name: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         let view = self.data.schema.view_by_name(view_name)?;
@@ -1580,16 +3333,284 @@ impl LowLevelConnection for Database {
             view_resource_name(self.name(), &view.view_name()),
             &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
         )?;
-        let mut mappings = Vec::new();
+        if let Some(group_level) = group_level {
+            let mut reducer = GroupedReducer::new(view, group_level);
+            self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
+                reducer.push(MappedSerializedValue {
+                    key: entry.key,
+                    value: entry.reduced_value,
+                })
+            })?;
+            reducer.finish()
+        } else {
+            let mut mappings = Vec::new();
+            self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
+                mappings.push(MappedSerializedValue {
+                    key: entry.key,
+                    value: entry.reduced_value,
+                });
+                Ok(())
+            })?;
+            Ok(mappings)
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view_name),
+        fields(
+            database = self.name(),
+            view.collection.name = view_name.collection.name.as_ref(),
+            view.collection.authority = view_name.collection.authority.as_ref(),
+            view.name = view_name.name.as_ref(),
+        )
+    ))]
+    fn reduce_with_staleness_by_name(
+        &self,
+        view_name: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Reduce)),
+        )?;
+
+        // UpdateBefore brings the view up-to-date before it is read. For all
+        // other policies, the view is checked before it is read, which may
+        // report a view updated by UpdateAfterWithTimeout as stale.
+        let was_stale = if matches!(access_policy, AccessPolicy::UpdateBefore) {
+            false
+        } else {
+            if let Some(integrity_check) = self
+                .storage
+                .instance
+                .tasks()
+                .spawn_integrity_check(view, self)
+            {
+                integrity_check
+                    .receive()
+                    .map_err(Error::from)?
+                    .map_err(Error::from)?;
+            }
+            self.view_has_pending_changes(view)?
+        };
+        let value = self.reduce_by_name(view_name, key, access_policy)?;
+
+        Ok(Reduction {
+            value: Bytes::from(value),
+            was_stale,
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view),
+        fields(
+            database = self.name(),
+            view.collection.name = view.collection.name.as_ref(),
+            view.collection.authority = view.collection.authority.as_ref(),
+            view.name = view.name.as_ref(),
+        )
+    ))]
+    fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        let mut count = 0;
         self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
-            mappings.push(MappedSerializedValue {
-                key: entry.key,
-                value: entry.reduced_value,
-            });
+            count += entry.mappings.len() as u64;
             Ok(())
         })?;
 
-        Ok(mappings)
+        Ok(count)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view),
+        fields(
+            database = self.name(),
+            view.collection.name = view.collection.name.as_ref(),
+            view.collection.authority = view.collection.authority.as_ref(),
+            view.name = view.name.as_ref(),
+        )
+    ))]
+    fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        let mut counts = Vec::new();
+        self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
+            if !entry.mappings.is_empty() {
+                counts.push((entry.key, entry.mappings.len() as u64));
+            }
+            Ok(())
+        })?;
+
+        Ok(counts)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view, id),
+        fields(
+            database = self.name(),
+            view.collection.name = view.collection.name.as_ref(),
+            view.collection.authority = view.collection.authority.as_ref(),
+            view.name = view.name.as_ref(),
+        )
+    ))]
+    fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        let view = self.data.schema.view_by_name(view)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        let updated_before = self.update_view_before_access(view, access_policy)?;
+
+        let collection = view.collection();
+        let Some(document) = self.get_from_collection(id, &collection)? else {
+            return Ok(None);
+        };
+
+        // The document map contains the keys of the mappings each document
+        // emitted, which are then used to find the document's mapping.
+        let emitted_keys = self
+            .roots()
+            .tree(self.collection_tree::<Unversioned, _>(
+                &collection,
+                view_document_map_tree_name(&view.view_name()),
+            )?)
+            .map_err(Error::from)?
+            .get(document.header.id.as_ref())
+            .map_err(Error::from)?;
+        let mut value = None;
+        if let Some(emitted_keys) = emitted_keys {
+            let mut emitted_keys = bincode::deserialize::<HashSet<OwnedBytes>>(&emitted_keys)
+                .map_err(Error::from)?
+                .into_iter()
+                .collect::<Vec<_>>();
+            emitted_keys.sort();
+            let view_entries = self
+                .roots()
+                .tree(self.collection_tree::<Unversioned, _>(
+                    &collection,
+                    view_entries_tree_name(&view.view_name()),
+                )?)
+                .map_err(Error::from)?;
+            for key in emitted_keys {
+                let Some(entry) = view_entries.get(&key.0).map_err(Error::from)? else {
+                    continue;
+                };
+                let entry = bincode::deserialize::<ViewEntry>(&entry).map_err(Error::from)?;
+                if let Some(mapping) = entry.mappings.into_iter().find(|mapping| {
+                    mapping.collection.is_none() && mapping.source.id == document.header.id
+                }) {
+                    value = Some(mapping.value);
+                    break;
+                }
+            }
+        }
+
+        self.update_view_after_access(view, access_policy, updated_before)?;
+
+        Ok(Some((document, value)))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, collection),
+        fields(
+            database = self.name(),
+            collection.name = collection.name.as_ref(),
+            collection.authority = collection.authority.as_ref(),
+        )
+    ))]
+    fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, bonsaidb_core::Error> {
+        // The views of the collection store their document maps under the
+        // view's name, while related views store a separate document map for
+        // each related collection.
+        let views = self
+            .data
+            .schema
+            .views_in_collection(collection)
+            .map(|view| (view, view_document_map_tree_name(&view.view_name())))
+            .chain(
+                self.data
+                    .schema
+                    .views_related_to_collection(collection)
+                    .map(|view| {
+                        let document_map =
+                            view_related_document_map_tree_name(&view.view_name(), collection);
+                        (view, document_map)
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        let mut updated_before = Vec::with_capacity(views.len());
+        for (view, _) in &views {
+            self.check_permission(
+                view_resource_name(self.name(), &view.view_name()),
+                &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+            )?;
+            updated_before.push(self.update_view_before_access(*view, access_policy)?);
+        }
+
+        let Some(document) = self.get_from_collection(id, collection)? else {
+            return Ok(None);
+        };
+
+        let mut emitted = Vec::new();
+        for ((view, document_map), updated_before) in views.into_iter().zip(updated_before) {
+            let emitted_keys = self
+                .roots()
+                .tree(self.collection_tree::<Unversioned, _>(collection, document_map)?)
+                .map_err(Error::from)?
+                .get(document.header.id.as_ref())
+                .map_err(Error::from)?;
+            if let Some(emitted_keys) = emitted_keys {
+                let mut emitted_keys = bincode::deserialize::<HashSet<OwnedBytes>>(&emitted_keys)
+                    .map_err(Error::from)?
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                emitted_keys.sort();
+                let view_name = view.view_name();
+                emitted.extend(emitted_keys.into_iter().map(|key| EmittedKey {
+                    view: view_name.clone(),
+                    key: Bytes::from(key.0.to_vec()),
+                }));
+            }
+
+            self.update_view_after_access(view, access_policy, updated_before)?;
+        }
+
+        Ok(Some((document, emitted)))
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(
@@ -1613,7 +3634,10 @@ impl LowLevelConnection for Database {
         let mut transaction = Transaction::default();
         self.for_each_in_view(view, key, Sort::Ascending, None, access_policy, |entry| {
             for mapping in entry.mappings {
-                transaction.push(Operation::delete(collection.clone(), mapping.source));
+                // Documents in related collections are never deleted.
+                if mapping.collection.is_none() {
+                    transaction.push(Operation::delete(collection.clone(), mapping.source));
+                }
             }
 
             Ok(())
@@ -1665,6 +3689,8 @@ impl Deref for Context {
 #[derive(Debug)]
 pub(crate) struct ContextData {
     pub(crate) roots: Roots<AnyFile>,
+    pub(crate) reduce_cache: ReduceCache,
+    pub(crate) transaction_watchers: TransactionWatchers,
     key_value_state: Arc<Mutex<keyvalue::KeyValueState>>,
 }
 
@@ -1679,18 +3705,23 @@ impl Context {
         roots: Roots<AnyFile>,
         key_value_persistence: KeyValuePersistence,
         storage_lock: Option<StorageLock>,
+        transaction_publisher: Option<TransactionPublisher>,
     ) -> Self {
         let background_worker_target = Watchable::new(BackgroundWorkerProcessTarget::Never);
         let mut background_worker_target_watcher = background_worker_target.watch();
+        let transaction_watchers = TransactionWatchers::new(transaction_publisher);
         let key_value_state = Arc::new(Mutex::new(keyvalue::KeyValueState::new(
             key_value_persistence,
             roots.clone(),
             background_worker_target,
+            transaction_watchers.clone(),
         )));
         let background_worker_state = Arc::downgrade(&key_value_state);
         let context = Self {
             data: Arc::new(ContextData {
                 roots,
+                reduce_cache: ReduceCache::default(),
+                transaction_watchers,
                 key_value_state,
             }),
         };
@@ -1724,6 +3755,11 @@ impl Context {
         state.update_key_expiration(tree_key, expiration);
     }
 
+    /// Returns the number of handles to this context.
+    pub(crate) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.data)
+    }
+
     #[cfg(test)]
     pub(crate) fn kv_persistence_watcher(&self) -> watchable::Watcher<Timestamp> {
         let state = self.data.key_value_state.lock();
@@ -1731,8 +3767,10 @@ impl Context {
     }
 }
 
-impl Drop for ContextData {
-    fn drop(&mut self) {
+impl ContextData {
+    /// Persists any dirty key-value entries, blocking until they have been
+    /// written.
+    pub(crate) fn flush_key_value_store(&self) {
         if let Some(shutdown) = {
             let mut state = self.key_value_state.lock();
             state.shutdown(&self.key_value_state)
@@ -1742,10 +3780,102 @@ impl Drop for ContextData {
     }
 }
 
+impl Drop for ContextData {
+    fn drop(&mut self) {
+        self.flush_key_value_store();
+    }
+}
+
+fn directory_size(path: &Path) -> Result<u64, Error> {
+    let mut size = 0;
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if metadata.is_dir() {
+                    size += directory_size(&entry.path())?;
+                } else {
+                    size += metadata.len();
+                }
+            }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(Error::from(err)),
+    }
+    Ok(size)
+}
+
 pub fn document_tree_name(collection: &CollectionName) -> String {
     format!("collection.{collection:#}")
 }
 
+pub fn history_tree_name(collection: &CollectionName) -> String {
+    format!("collection.{collection:#}.history")
+}
+
+/// Narrows `key` so that iterating in `order` begins at `cursor_key`, skipping
+/// the entries that precede it. Queries for specific keys are left unchanged.
+fn query_key_resuming_at(
+    key: Option<SerializedQueryKey>,
+    cursor_key: &Bytes,
+    order: Sort,
+) -> SerializedQueryKey {
+    let mut key = key.unwrap_or_else(|| {
+        SerializedQueryKey::from(QueryKeyBytes::Range(Range {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+        }))
+    });
+    if let QueryKeyBytes::Range(range) = &mut key.keys {
+        let bound = match order {
+            Sort::Ascending => &mut range.start,
+            Sort::Descending => &mut range.end,
+        };
+        let already_resumes = match bound {
+            Bound::Included(existing) | Bound::Excluded(existing) => match order {
+                Sort::Ascending => existing[..] >= cursor_key[..],
+                Sort::Descending => existing[..] <= cursor_key[..],
+            },
+            Bound::Unbounded => false,
+        };
+        if !already_resumes {
+            *bound = Bound::Included(cursor_key.clone());
+        }
+    }
+    key
+}
+
+/// Returns true if `name` is named like a tree that stores the data of a
+/// collection or view.
+fn is_schema_tree_name(name: &str) -> bool {
+    name.starts_with("collection.")
+        || name.starts_with("view.")
+        || name.starts_with("view-versions.")
+}
+
+/// Returns the key a revision of a document is stored under in its
+/// collection's history tree. The id is length-prefixed so that the revisions
+/// of a document are stored contiguously, ordered by the id of the
+/// transaction that replaced them. Revision ids restart when a document is
+/// deleted and recreated, so they are only used to order revisions recorded
+/// by the same transaction.
+fn history_key(id: &DocumentId, transaction_id: u64, revision: u32) -> Vec<u8> {
+    let id_length = u16::try_from(id.len()).expect("document ids are limited in length");
+    let mut key = Vec::with_capacity(id.len() + 14);
+    key.extend_from_slice(&id_length.to_be_bytes());
+    key.extend_from_slice(id);
+    key.extend_from_slice(&transaction_id.to_be_bytes());
+    key.extend_from_slice(&revision.to_be_bytes());
+    key
+}
+
+/// Returns the inclusive range of keys containing every revision of `id`
+/// in a history tree.
+fn history_key_range(id: &DocumentId) -> (Vec<u8>, Vec<u8>) {
+    (history_key(id, 0, 0), history_key(id, u64::MAX, u32::MAX))
+}
+
 pub struct DocumentIdRange(Range<DocumentId>);
 
 impl<'a> BorrowByteRange<'a> for DocumentIdRange {
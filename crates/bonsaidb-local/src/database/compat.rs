@@ -36,6 +36,7 @@ impl<T> std::fmt::Display for UnknownVersion<T> {
 enum ChangesVersions {
     Legacy = 0,
     V1 = 1,
+    EncryptedV1 = 2,
 }
 
 impl Versioned for ChangesVersions {
@@ -51,12 +52,21 @@ impl TryFrom<u64> for ChangesVersions {
         match value {
             0 => Ok(ChangesVersions::Legacy),
             1 => Ok(ChangesVersions::V1),
+            2 => Ok(ChangesVersions::EncryptedV1),
             _ => Err(UnknownVersion::default()),
         }
     }
 }
 
-pub fn deserialize_executed_transaction_changes(data: &[u8]) -> Result<Changes, crate::Error> {
+/// Deserializes the changes stored in a transaction log entry. `decrypt` is
+/// invoked if the changes were encrypted.
+pub fn deserialize_executed_transaction_changes<Decrypt>(
+    data: &[u8],
+    decrypt: Decrypt,
+) -> Result<Changes, crate::Error>
+where
+    Decrypt: FnOnce(&[u8]) -> Result<Vec<u8>, crate::Error>,
+{
     let (version, data) = transmog_versions::unwrap_version(data);
     match ChangesVersions::try_from(version)? {
         ChangesVersions::Legacy => {
@@ -68,6 +78,9 @@ pub fn deserialize_executed_transaction_changes(data: &[u8]) -> Result<Changes,
             Changes::try_from(legacy).map_err(crate::Error::from)
         }
         ChangesVersions::V1 => pot::from_slice(data).map_err(crate::Error::from),
+        ChangesVersions::EncryptedV1 => {
+            pot::from_slice(&decrypt(data)?).map_err(crate::Error::from)
+        }
     }
 }
 
@@ -78,6 +91,22 @@ pub fn serialize_executed_transaction_changes(changes: &Changes) -> Result<Vec<u
     Ok(serialized)
 }
 
+/// Serializes `changes` for a transaction log entry, encrypting the
+/// serialized changes using `encrypt`.
+#[cfg(feature = "encryption")]
+pub fn serialize_encrypted_transaction_changes<Encrypt>(
+    changes: &Changes,
+    encrypt: Encrypt,
+) -> Result<Vec<u8>, crate::Error>
+where
+    Encrypt: FnOnce(&[u8]) -> Result<Vec<u8>, crate::Error>,
+{
+    let mut serialized = Vec::new();
+    transmog_versions::write_header(&ChangesVersions::EncryptedV1, &mut serialized)?;
+    serialized.extend(encrypt(&pot::to_vec(changes)?)?);
+    Ok(serialized)
+}
+
 /// A list of changes.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ChangesV0 {
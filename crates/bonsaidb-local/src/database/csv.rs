@@ -0,0 +1,236 @@
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Write;
+
+use bonsaidb_core::connection::{AccessPolicy, LowLevelConnection, QueryKey, Sort, ViewMappings};
+use bonsaidb_core::key::KeyEncoding;
+use bonsaidb_core::schema::SerializedView;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{Database, Error};
+
+impl Database {
+    /// Queries the view `V` and writes the matching entries to `writer` as
+    /// CSV, returning the number of rows written, excluding the header row.
+    ///
+    /// Each row contains the columns of an entry's key followed by the
+    /// columns of its value:
+    ///
+    /// - Structures are flattened one level, producing a column for each
+    ///   top-level field, such as `key.name` or `value.total`.
+    /// - Tuples and sequences produce a column for each element, such as
+    ///   `key.0` and `key.1` for a composite key.
+    /// - All other values produce a single `key` or `value` column.
+    ///
+    /// Values nested below the top level are written as JSON. If entries
+    /// produce different columns, such as when a field is skipped during
+    /// serialization, the header contains every column and missing cells are
+    /// left empty. Fields are quoted as described in RFC 4180.
+    pub fn query_to_csv<V, Key>(
+        &self,
+        key: Option<QueryKey<'_, V::Key, Key>>,
+        access_policy: AccessPolicy,
+        writer: impl Write,
+    ) -> Result<u64, Error>
+    where
+        V: SerializedView,
+        V::Key: Serialize + Borrow<Key> + PartialEq<Key>,
+        V::Value: Serialize,
+        Key: KeyEncoding<V::Key> + PartialEq + ?Sized,
+    {
+        let mappings = self.query::<V, Key>(key, Sort::Ascending, None, access_policy)?;
+        write_mappings_csv::<V>(&mappings, writer)
+    }
+}
+
+/// Writes `mappings` to `writer` as CSV. See [`Database::query_to_csv()`] for
+/// the format.
+pub(crate) fn write_mappings_csv<V>(
+    mappings: &ViewMappings<V>,
+    mut writer: impl Write,
+) -> Result<u64, Error>
+where
+    V: SerializedView,
+    V::Key: Serialize,
+    V::Value: Serialize,
+{
+    let mut columns = Vec::new();
+    let mut known_columns = HashSet::new();
+    let mut rows = Vec::with_capacity(mappings.len());
+    for mapping in mappings {
+        let mut row = Vec::new();
+        flatten_into("key", OrderedValue::from_serialize(&mapping.key)?, &mut row);
+        flatten_into(
+            "value",
+            OrderedValue::from_serialize(&mapping.value)?,
+            &mut row,
+        );
+        for (column, _) in &row {
+            if known_columns.insert(column.clone()) {
+                columns.push(column.clone());
+            }
+        }
+        rows.push(row);
+    }
+
+    write_record(&mut writer, columns.iter().map(String::as_str))?;
+    for row in &rows {
+        write_record(
+            &mut writer,
+            columns.iter().map(|column| {
+                row.iter()
+                    .find(|(name, _)| name == column)
+                    .map_or("", |(_, cell)| cell.as_str())
+            }),
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(rows.len() as u64)
+}
+
+/// Pushes the columns of `value` to `row`, naming each column starting with
+/// `prefix`.
+fn flatten_into(prefix: &str, value: OrderedValue, row: &mut Vec<(String, String)>) {
+    match value {
+        OrderedValue::Object(fields) => {
+            for (field, value) in fields {
+                row.push((format!("{prefix}.{field}"), cell_text(value)));
+            }
+        }
+        OrderedValue::Array(elements) => {
+            for (index, value) in elements.into_iter().enumerate() {
+                row.push((format!("{prefix}.{index}"), cell_text(value)));
+            }
+        }
+        value => row.push((prefix.to_string(), cell_text(value))),
+    }
+}
+
+fn cell_text(value: OrderedValue) -> String {
+    match value {
+        OrderedValue::Scalar(Value::Null) => String::new(),
+        OrderedValue::Scalar(Value::String(text)) => text,
+        OrderedValue::Scalar(other) => other.to_string(),
+        nested => serde_json::to_string(&nested).expect("objects always have string keys"),
+    }
+}
+
+/// A JSON value whose objects keep their fields in the order they were
+/// serialized. [`Value`] sorts the fields of objects, which would otherwise
+/// reorder the columns of structures.
+enum OrderedValue {
+    Scalar(Value),
+    Array(Vec<OrderedValue>),
+    Object(Vec<(String, OrderedValue)>),
+}
+
+impl OrderedValue {
+    fn from_serialize(value: &impl Serialize) -> Result<Self, Error> {
+        // Serializing to JSON text preserves the order of the fields, which
+        // is then retained while deserializing.
+        Ok(serde_json::from_slice(&serde_json::to_vec(value)?)?)
+    }
+}
+
+impl Serialize for OrderedValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Scalar(value) => value.serialize(serializer),
+            Self::Array(elements) => serializer.collect_seq(elements),
+            Self::Object(fields) => {
+                serializer.collect_map(fields.iter().map(|(field, value)| (field, value)))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderedValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(OrderedValueVisitor)
+    }
+}
+
+struct OrderedValueVisitor;
+
+impl<'de> Visitor<'de> for OrderedValueVisitor {
+    type Value = OrderedValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::Bool(v)))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::from(v)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::from(v)))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::from(v)))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::from(v)))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::String(v)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::Null))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(OrderedValue::Scalar(Value::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        OrderedValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(OrderedValue::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+        while let Some(field) = map.next_entry()? {
+            fields.push(field);
+        }
+        Ok(OrderedValue::Object(fields))
+    }
+}
+
+fn write_record<'a>(
+    writer: &mut impl Write,
+    fields: impl IntoIterator<Item = &'a str>,
+) -> Result<(), Error> {
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+
+        if field.contains([',', '"', '\r', '\n']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")?;
+    Ok(())
+}
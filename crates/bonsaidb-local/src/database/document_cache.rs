@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bonsaidb_core::document::{DocumentId, OwnedDocument};
+use bonsaidb_core::schema::CollectionName;
+use parking_lot::Mutex;
+
+type CacheKey = (String, CollectionName, DocumentId);
+
+/// A bounded cache of recently read documents, shared by every database
+/// opened by a storage instance. The least recently used document is evicted
+/// when the cache is full.
+#[derive(Debug)]
+pub(crate) struct DocumentCache {
+    capacity: usize,
+    state: Mutex<State>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<CacheKey, (OwnedDocument, u64)>,
+    /// The keys of `entries`, ordered from least to most recently used.
+    recency: BTreeMap<u64, CacheKey>,
+    last_use: u64,
+    /// Incremented each time a document is invalidated. A document read from
+    /// disk is only cached if no invalidation occurred while it was being
+    /// read, otherwise a write that committed during the read could be
+    /// replaced by the document's previous contents.
+    generation: u64,
+}
+
+impl DocumentCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the cached document, or the current generation of the cache if
+    /// the document is not cached. The generation must be passed to
+    /// [`Self::insert()`] after reading the document from disk.
+    pub fn get(
+        &self,
+        database: &str,
+        collection: &CollectionName,
+        id: &DocumentId,
+    ) -> Result<OwnedDocument, u64> {
+        let key = (database.to_string(), collection.clone(), id.clone());
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        if let Some((document, last_use)) = state.entries.get_mut(&key) {
+            let key = state.recency.remove(last_use).expect("recency out of sync");
+            state.last_use += 1;
+            *last_use = state.last_use;
+            state.recency.insert(state.last_use, key);
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            Ok(document.clone())
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            Err(state.generation)
+        }
+    }
+
+    /// Caches `document`, which was read after [`Self::get()`] returned
+    /// `generation`.
+    pub fn insert(
+        &self,
+        database: &str,
+        collection: &CollectionName,
+        document: OwnedDocument,
+        generation: u64,
+    ) {
+        let mut state = self.state.lock();
+        if state.generation != generation {
+            return;
+        }
+
+        let key = (
+            database.to_string(),
+            collection.clone(),
+            document.header.id.clone(),
+        );
+        state.last_use += 1;
+        let last_use = state.last_use;
+        if let Some((_, previous_use)) = state.entries.insert(key.clone(), (document, last_use)) {
+            state.recency.remove(&previous_use);
+        }
+        state.recency.insert(last_use, key);
+
+        while state.entries.len() > self.capacity {
+            let Some(oldest) = state.recency.keys().next().copied() else {
+                break;
+            };
+            if let Some(evicted) = state.recency.remove(&oldest) {
+                state.entries.remove(&evicted);
+            }
+        }
+    }
+
+    /// Removes the documents `ids` from the cache.
+    pub fn invalidate<'a>(
+        &self,
+        database: &str,
+        ids: impl IntoIterator<Item = (&'a CollectionName, &'a DocumentId)>,
+    ) {
+        let mut state = self.state.lock();
+        state.generation += 1;
+        for (collection, id) in ids {
+            let key = (database.to_string(), collection.clone(), id.clone());
+            if let Some((_, last_use)) = state.entries.remove(&key) {
+                state.recency.remove(&last_use);
+            }
+        }
+    }
+
+    /// Removes every document in `database` from the cache.
+    pub fn invalidate_database(&self, database: &str) {
+        let mut state = self.state.lock();
+        state.generation += 1;
+        let state = &mut *state;
+        let recency = &mut state.recency;
+        state.entries.retain(|(entry_database, ..), (_, last_use)| {
+            if entry_database == database {
+                recency.remove(last_use);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn statistics(&self) -> DocumentCacheStatistics {
+        DocumentCacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The number of document reads that were and were not served by the
+/// document cache, returned from
+/// [`Storage::document_cache_statistics()`](crate::Storage::document_cache_statistics).
+///
+/// Reads are only counted while the cache is enabled by setting
+/// [`Cache::documents`](crate::config::Cache::documents).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DocumentCacheStatistics {
+    /// The number of documents that were returned from the cache.
+    pub hits: u64,
+    /// The number of documents that were read from disk because they were
+    /// not cached.
+    pub misses: u64,
+}
+
+#[test]
+fn least_recently_used_is_evicted() {
+    use bonsaidb_core::arc_bytes::serde::Bytes;
+    use bonsaidb_core::document::{Header, Revision};
+    use bonsaidb_core::schema::Qualified;
+
+    fn document(id: u64) -> OwnedDocument {
+        OwnedDocument {
+            header: Header {
+                id: DocumentId::from_u64(id),
+                revision: Revision::new(b"contents"),
+            },
+            contents: Bytes::from(b"contents".to_vec()),
+        }
+    }
+
+    let collection = CollectionName::private("documents");
+    let cache = DocumentCache::new(2);
+    let generation = cache
+        .get("db", &collection, &DocumentId::from_u64(1))
+        .unwrap_err();
+    cache.insert("db", &collection, document(1), generation);
+    cache.insert("db", &collection, document(2), generation);
+    // Reading the first document makes the second the least recently used.
+    assert!(cache
+        .get("db", &collection, &DocumentId::from_u64(1))
+        .is_ok());
+    cache.insert("db", &collection, document(3), generation);
+    assert!(cache
+        .get("db", &collection, &DocumentId::from_u64(2))
+        .is_err());
+    assert!(cache
+        .get("db", &collection, &DocumentId::from_u64(3))
+        .is_ok());
+
+    // Documents read before an invalidation are not cached.
+    cache.invalidate("db", [(&collection, &DocumentId::from_u64(1))]);
+    cache.insert("db", &collection, document(2), generation);
+    assert!(cache
+        .get("db", &collection, &DocumentId::from_u64(1))
+        .is_err());
+    assert!(cache
+        .get("db", &collection, &DocumentId::from_u64(2))
+        .is_err());
+
+    assert_eq!(
+        cache.statistics(),
+        DocumentCacheStatistics { hits: 2, misses: 4 }
+    );
+}
@@ -0,0 +1,120 @@
+use std::io::{BufRead, Write};
+
+use bonsaidb_core::connection::{Bound, LowLevelConnection, Range, Sort};
+use bonsaidb_core::document::{CollectionDocument, DocumentId};
+use bonsaidb_core::schema::SerializedCollection;
+use bonsaidb_core::transaction::{Operation, Transaction};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Database, Error};
+
+/// The number of documents read or inserted at a time while exporting or
+/// importing a collection as JSON.
+const JSON_BATCH_SIZE: u32 = 1_000;
+
+/// A single line of a collection exported as JSON.
+#[derive(Serialize, Deserialize)]
+struct JsonDocument<PrimaryKey, Contents> {
+    id: PrimaryKey,
+    /// The revision of the document when it was exported. Imported documents
+    /// are assigned new revisions, so this field is ignored when importing.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    revision: Option<String>,
+    contents: Contents,
+}
+
+impl Database {
+    /// Writes every document in the collection `C` to `writer` as
+    /// newline-delimited JSON, returning the number of documents written.
+    ///
+    /// Each line is an object containing the document's `id`, its `revision`
+    /// formatted as `{revision id}-{sha256 hex}`, and its deserialized
+    /// `contents`. Documents are read using the permissions of
+    /// this instance, and documents in encrypted collections are decrypted
+    /// before being written.
+    pub fn export_collection_json<C>(&self, mut writer: impl Write) -> Result<u64, Error>
+    where
+        C: SerializedCollection,
+        C::PrimaryKey: Serialize,
+        C::Contents: Serialize,
+    {
+        let collection = C::collection_name();
+        let mut exported = 0;
+        let mut last_id: Option<DocumentId> = None;
+        loop {
+            let range = Range {
+                start: last_id.map_or(Bound::Unbounded, Bound::Excluded),
+                end: Bound::Unbounded,
+            };
+            let documents = self.list_from_collection(
+                range,
+                Sort::Ascending,
+                Some(JSON_BATCH_SIZE),
+                &collection,
+            )?;
+            let Some(last) = documents.last() else { break };
+            last_id = Some(last.header.id.clone());
+
+            for document in &documents {
+                let document = CollectionDocument::<C>::try_from(document)?;
+                serde_json::to_writer(
+                    &mut writer,
+                    &JsonDocument {
+                        id: &document.header.id,
+                        revision: Some(document.header.revision.to_string()),
+                        contents: &document.contents,
+                    },
+                )?;
+                writer.write_all(b"\n")?;
+                exported += 1;
+            }
+        }
+
+        writer.flush()?;
+        Ok(exported)
+    }
+
+    /// Inserts every document in `reader`, which contains newline-delimited
+    /// JSON in the format written by [`Self::export_collection_json()`], into
+    /// the collection `C`. Returns the number of documents inserted.
+    ///
+    /// Documents are inserted using transactions, which causes the views of
+    /// `C` to be updated and documents to be encrypted according to the
+    /// collection's configuration. Exported revisions are ignored, as each
+    /// inserted document is given a new revision. Empty lines are ignored. If a
+    /// document with the same id already exists, a conflict error is returned,
+    /// and documents inserted by previous batches remain in the collection.
+    pub fn import_collection_json<C>(&self, reader: impl BufRead) -> Result<u64, Error>
+    where
+        C: SerializedCollection,
+        C::PrimaryKey: DeserializeOwned,
+        C::Contents: DeserializeOwned,
+    {
+        let mut imported = 0;
+        let mut transaction = Transaction::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let document: JsonDocument<C::PrimaryKey, C::Contents> = serde_json::from_str(&line)?;
+            transaction.push(Operation::insert_serialized::<C>(
+                Some(&document.id),
+                &document.contents,
+            )?);
+            if transaction.operations.len() >= JSON_BATCH_SIZE as usize {
+                imported += self
+                    .apply_transaction(std::mem::take(&mut transaction))?
+                    .len() as u64;
+            }
+        }
+
+        if !transaction.operations.is_empty() {
+            imported += self.apply_transaction(transaction)?.len() as u64;
+        }
+
+        Ok(imported)
+    }
+}
@@ -1,13 +1,15 @@
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::{btree_map, BTreeMap, VecDeque};
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 use bonsaidb_core::connection::{Connection, HasSession};
 use bonsaidb_core::keyvalue::{
-    Command, KeyCheck, KeyOperation, KeyStatus, KeyValue, Numeric, Output, SetCommand, Timestamp,
-    Value,
+    Command, KeyCheck, KeyOperation, KeyStatus, KeyValue, ListedKey, Numeric, Output, SetCommand,
+    Timestamp, Value,
 };
+use bonsaidb_core::limits::{LIST_KEYS_DEFAULT_RESULT_COUNT, LIST_KEYS_MAX_RESULTS};
 use bonsaidb_core::permissions::bonsai::{
     keyvalue_key_resource_name, BonsaiAction, DatabaseAction, KeyValueAction,
 };
@@ -21,6 +23,7 @@ use watchable::{Watchable, Watcher};
 
 use crate::config::KeyValuePersistence;
 use crate::database::compat;
+use crate::database::transaction_feed::TransactionWatchers;
 use crate::storage::StorageLock;
 use crate::tasks::{Job, Keyed, Task};
 use crate::{Database, DatabaseNonBlocking, Error};
@@ -34,6 +37,11 @@ pub struct Entry {
 }
 
 impl Entry {
+    pub(crate) fn is_expired(&self, now: Timestamp) -> bool {
+        self.expiration
+            .map_or(false, |expiration| expiration <= now)
+    }
+
     pub(crate) fn restore(
         self,
         namespace: Option<String>,
@@ -61,6 +69,9 @@ impl KeyValue for Database {
             keyvalue_key_resource_name(self.name(), op.namespace.as_deref(), &op.key),
             &BonsaiAction::Database(DatabaseAction::KeyValue(KeyValueAction::ExecuteOperation)),
         )?;
+        if self.read_only && op.command.modifies() {
+            return Err(bonsaidb_core::Error::ReadOnly);
+        }
         self.data.context.perform_kv_operation(op)
     }
 }
@@ -215,6 +226,7 @@ pub struct KeyValueState {
     dirty_keys: BTreeMap<String, Option<Entry>>,
     keys_being_persisted: Option<Arc<BTreeMap<String, Option<Entry>>>>,
     last_persistence: Watchable<Timestamp>,
+    transaction_watchers: TransactionWatchers,
     shutdown: Option<flume::Sender<()>>,
 }
 
@@ -223,6 +235,7 @@ impl KeyValueState {
         persistence: KeyValuePersistence,
         roots: Roots<AnyFile>,
         background_worker_target: Watchable<BackgroundWorkerProcessTarget>,
+        transaction_watchers: TransactionWatchers,
     ) -> Self {
         Self {
             roots,
@@ -234,6 +247,7 @@ impl KeyValueState {
             dirty_keys: BTreeMap::new(),
             keys_being_persisted: None,
             last_persistence: Watchable::new(Timestamp::MIN),
+            transaction_watchers,
             shutdown: None,
         }
     }
@@ -264,6 +278,16 @@ impl KeyValueState {
                 self.execute_get_operation(op.namespace.as_deref(), &op.key, delete)
             }
             Command::Delete => self.execute_delete_operation(op.namespace.as_deref(), &op.key),
+            Command::List {
+                limit,
+                include_values,
+            } => self.execute_list_operation(
+                op.namespace.as_deref(),
+                &op.key,
+                limit,
+                include_values,
+                now,
+            ),
             Command::Increment { amount, saturating } => self.execute_increment_operation(
                 op.namespace.as_deref(),
                 &op.key,
@@ -451,6 +475,90 @@ impl KeyValueState {
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, now))
+    )]
+    fn execute_list_operation(
+        &self,
+        namespace: Option<&str>,
+        prefix: &str,
+        limit: Option<u32>,
+        include_values: bool,
+        now: Timestamp,
+    ) -> Result<Output, bonsaidb_core::Error> {
+        let limit = usize::try_from(
+            limit
+                .unwrap_or(LIST_KEYS_DEFAULT_RESULT_COUNT)
+                .min(LIST_KEYS_MAX_RESULTS),
+        )
+        .unwrap();
+        let full_prefix = full_key(namespace, prefix);
+
+        // Changes that haven't been persisted yet take precedence over the
+        // entries stored on disk.
+        let mut entries = BTreeMap::new();
+        let in_memory = self
+            .keys_being_persisted
+            .iter()
+            .flat_map(|keys| keys.iter())
+            .chain(self.dirty_keys.iter());
+        for (key, entry) in in_memory {
+            if key.starts_with(&full_prefix) {
+                entries.insert(key.clone(), entry.clone());
+            }
+        }
+
+        // Only the first `limit` keys on disk that aren't overridden by an
+        // in-memory change can be part of the result.
+        let keys_read = Cell::new(0);
+        let mut stored_entries = Vec::new();
+        self.roots
+            .tree(Unversioned::tree(KEY_TREE))
+            .map_err(Error::from)?
+            .scan::<Error, _, _, _, _>(
+                &(full_prefix.as_bytes()..),
+                true,
+                |_, _, _| ScanEvaluation::ReadData,
+                |key, _| {
+                    if keys_read.get() >= limit || !key.starts_with(full_prefix.as_bytes()) {
+                        ScanEvaluation::Stop
+                    } else {
+                        ScanEvaluation::ReadData
+                    }
+                },
+                |key, _, entry: ArcBytes<'static>| {
+                    let key = std::str::from_utf8(&key)
+                        .map_err(|err| AbortError::Other(Error::from(err)))?;
+                    if !entries.contains_key(key) {
+                        let entry = bincode::deserialize::<Entry>(&entry)
+                            .map_err(|err| AbortError::Other(Error::from(err)))?;
+                        if !entry.is_expired(now) {
+                            keys_read.set(keys_read.get() + 1);
+                            stored_entries.push((key.to_string(), Some(entry)));
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(Error::from)?;
+        entries.extend(stored_entries);
+
+        let keys = entries
+            .into_iter()
+            .filter_map(|(key, entry)| {
+                let entry = entry?;
+                let (_, key) = split_key(&key)?;
+                Some(ListedKey {
+                    key,
+                    value: include_values.then_some(entry.value),
+                })
+            })
+            .take(limit)
+            .collect();
+        Ok(Output::Keys(keys))
+    }
+
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip(self, amount, saturating, now))
@@ -506,10 +614,7 @@ impl KeyValueState {
                 self.set(full_key, entry);
                 Ok(Output::Value(Some(value)))
             }
-            Value::Bytes(_) => Err(bonsaidb_core::Error::other(
-                "bonsaidb-local",
-                "type of stored `Value` is not `Numeric`",
-            )),
+            Value::Bytes(_) => Err(bonsaidb_core::Error::ValueNotNumeric),
         }
     }
 
@@ -585,10 +690,19 @@ impl KeyValueState {
         roots: &Roots<AnyFile>,
         key: &str,
     ) -> Result<Option<Entry>, nebari::Error> {
+        let now = Timestamp::now();
         roots
             .tree(Unversioned::tree(KEY_TREE))?
             .get(key.as_bytes())
-            .map(|current| current.and_then(|current| bincode::deserialize::<Entry>(&current).ok()))
+            .map(|current| {
+                current
+                    .and_then(|current| bincode::deserialize::<Entry>(&current).ok())
+                    // Keys loaded from disk may have expired before the
+                    // expiration loader has had a chance to track them. These
+                    // keys will be removed once the loader finishes, but they
+                    // must be treated as already removed.
+                    .filter(|entry| !entry.is_expired(now))
+            })
     }
 
     fn update_background_worker_target(&mut self) {
@@ -732,7 +846,12 @@ impl KeyValueState {
                     &Changes::Keys(changed_keys),
                 )?)
                 .map_err(Error::from)?;
+            let transaction_id = transaction.entry().id;
             transaction.commit().map_err(Error::from)?;
+            key_value_state
+                .lock()
+                .transaction_watchers
+                .notify(transaction_id);
         }
 
         // If we are shutting down, check if we still have dirty keys.
@@ -891,7 +1010,7 @@ mod tests {
             .file_manager(AnyFileManager::std())
             .open()?;
 
-        let context = Context::new(sled.clone(), persistence, None);
+        let context = Context::new(sled.clone(), persistence, None, None);
 
         test_contents(context, sled)?;
 
@@ -1176,6 +1295,7 @@ mod tests {
             sled,
             KeyValuePersistence::lazy([PersistenceThreshold::after_changes(2)]),
             None,
+            None,
         );
         context
             .perform_kv_operation(KeyOperation {
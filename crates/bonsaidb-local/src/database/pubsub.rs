@@ -1,11 +1,14 @@
-use bonsaidb_core::arc_bytes::OwnedBytes;
 pub use bonsaidb_core::circulate::Relay;
+use bonsaidb_core::circulate::{self, flume, Message};
 use bonsaidb_core::connection::{Connection, HasSession};
 use bonsaidb_core::permissions::bonsai::{
-    database_resource_name, pubsub_topic_resource_name, BonsaiAction, DatabaseAction, PubSubAction,
+    collection_resource_name, database_resource_name, pubsub_topic_resource_name, BonsaiAction,
+    DatabaseAction, DocumentAction, PubSubAction,
 };
-use bonsaidb_core::pubsub::{self, database_topic, PubSub, Receiver};
-use bonsaidb_core::{circulate, Error};
+use bonsaidb_core::pubsub::{
+    self, document_changes_topic_collection, PubSub, Receiver, TopicPattern,
+};
+use bonsaidb_core::Error;
 
 use crate::{Database, DatabaseNonBlocking};
 
@@ -24,14 +27,34 @@ impl PubSub for super::Database {
     }
 
     fn publish_bytes(&self, topic: Vec<u8>, payload: Vec<u8>) -> Result<(), bonsaidb_core::Error> {
-        self.check_permission(
-            pubsub_topic_resource_name(self.name(), &topic),
-            &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::Publish)),
-        )?;
+        check_publish_permission(self, &topic)?;
         self.storage
             .instance
-            .relay()
-            .publish_raw(database_topic(&self.data.name, &topic), payload);
+            .publish_to_topics(&self.data.name, vec![topic], payload);
+        Ok(())
+    }
+
+    fn publish_bytes_and_flush(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        check_publish_permission(self, &topic)?;
+        Ok(self
+            .storage
+            .instance
+            .publish_and_count(&self.data.name, topic, payload))
+    }
+
+    fn publish_bytes_retained(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        check_publish_permission(self, &topic)?;
+        self.storage
+            .instance
+            .publish_retained(&self.data.name, topic, payload);
         Ok(())
     }
 
@@ -40,16 +63,11 @@ impl PubSub for super::Database {
         topics: impl IntoIterator<Item = Vec<u8>> + Send,
         payload: Vec<u8>,
     ) -> Result<(), bonsaidb_core::Error> {
-        self.storage.instance.relay().publish_raw_to_all(
+        self.storage.instance.publish_to_topics(
+            &self.data.name,
             topics
                 .into_iter()
-                .map(|topic| {
-                    self.check_permission(
-                        pubsub_topic_resource_name(self.name(), &topic),
-                        &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::Publish)),
-                    )
-                    .map(|_| OwnedBytes::from(database_topic(&self.data.name, &topic)))
-                })
+                .map(|topic| check_publish_permission(self, &topic).map(|_| topic))
                 .collect::<Result<Vec<_>, _>>()?,
             payload,
         );
@@ -57,6 +75,19 @@ impl PubSub for super::Database {
     }
 }
 
+/// Checks that `topic` may be published to through `database`. Topics in the
+/// reserved namespace are only published to by the database itself.
+fn check_publish_permission(database: &Database, topic: &[u8]) -> Result<(), Error> {
+    if pubsub::is_reserved_topic(topic) {
+        return Err(Error::ReservedTopic);
+    }
+
+    database.check_permission(
+        pubsub_topic_resource_name(database.name(), topic),
+        &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::Publish)),
+    )
+}
+
 /// A subscriber for `PubSub` messages.
 #[derive(Debug, Clone)]
 pub struct Subscriber {
@@ -64,6 +95,7 @@ pub struct Subscriber {
     pub(crate) database: Database,
     pub(crate) subscriber: circulate::Subscriber,
     pub(crate) receiver: Receiver,
+    pub(crate) retained: flume::Sender<Message>,
 }
 
 impl Subscriber {
@@ -86,8 +118,16 @@ impl pubsub::Subscriber for Subscriber {
             pubsub_topic_resource_name(self.database.name(), &topic),
             &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::SubscribeTo)),
         )?;
-        self.subscriber
-            .subscribe_to_raw(database_topic(self.database.name(), &topic));
+        if let Some(collection) = document_changes_topic_collection(&topic) {
+            self.database.check_permission(
+                collection_resource_name(self.database.name(), &collection),
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::ListHeaders)),
+            )?;
+        }
+        self.database
+            .storage()
+            .instance
+            .subscribe_to_topic(self, topic);
         Ok(())
     }
 
@@ -96,8 +136,36 @@ impl pubsub::Subscriber for Subscriber {
             pubsub_topic_resource_name(self.database.name(), topic),
             &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::UnsubscribeFrom)),
         )?;
-        self.subscriber
-            .unsubscribe_from_raw(&database_topic(self.database.name(), topic));
+        self.database
+            .storage()
+            .instance
+            .unsubscribe_from_topic(self, topic);
+        Ok(())
+    }
+
+    fn subscribe_to_pattern(&self, pattern: &str) -> Result<(), Error> {
+        let pattern = TopicPattern::new(pattern)?;
+        self.database.check_permission(
+            pubsub_topic_resource_name(self.database.name(), &pot::to_vec(pattern.as_str())?),
+            &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::SubscribeTo)),
+        )?;
+        self.database
+            .storage()
+            .instance
+            .subscribe_to_pattern(self, pattern);
+        Ok(())
+    }
+
+    fn unsubscribe_from_pattern(&self, pattern: &str) -> Result<(), Error> {
+        let pattern = TopicPattern::new(pattern)?;
+        self.database.check_permission(
+            pubsub_topic_resource_name(self.database.name(), &pot::to_vec(pattern.as_str())?),
+            &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::UnsubscribeFrom)),
+        )?;
+        self.database
+            .storage()
+            .instance
+            .unsubscribe_from_pattern(self, &pattern);
         Ok(())
     }
 
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use bonsaidb_core::connection::LowLevelConnection;
+use bonsaidb_core::transaction::Executed;
+use parking_lot::Mutex;
+
+use crate::Database;
+
+/// Publishes the id of a committed transaction to
+/// [`TransactionsTopic`](bonsaidb_core::pubsub::TransactionsTopic).
+pub(crate) type TransactionPublisher = Box<dyn Fn(u64) + Send + Sync>;
+
+/// Notifies [`TransactionFeed`]s when a transaction is committed.
+#[derive(Clone, Default)]
+pub(crate) struct TransactionWatchers {
+    watchers: Arc<Mutex<Vec<flume::Sender<()>>>>,
+    publisher: Option<Arc<TransactionPublisher>>,
+}
+
+impl TransactionWatchers {
+    pub fn new(publisher: Option<TransactionPublisher>) -> Self {
+        Self {
+            watchers: Arc::default(),
+            publisher: publisher.map(Arc::new),
+        }
+    }
+
+    /// Returns a receiver that is notified after each transaction is
+    /// committed. Notifications are coalesced: at most one notification is
+    /// pending at a time, regardless of how many transactions were committed.
+    pub fn watch(&self) -> flume::Receiver<()> {
+        let (sender, receiver) = flume::bounded(1);
+        self.watchers.lock().push(sender);
+        receiver
+    }
+
+    /// Notifies every watcher that the transaction `transaction_id` has been
+    /// committed.
+    pub fn notify(&self, transaction_id: u64) {
+        let mut watchers = self.watchers.lock();
+        watchers.retain(|watcher| {
+            !matches!(
+                watcher.try_send(()),
+                Err(flume::TrySendError::Disconnected(_))
+            )
+        });
+        drop(watchers);
+
+        if let Some(publisher) = &self.publisher {
+            publisher(transaction_id);
+        }
+    }
+}
+
+impl Debug for TransactionWatchers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionWatchers")
+            .field("watchers", &self.watchers)
+            .field("publisher", &self.publisher.is_some())
+            .finish()
+    }
+}
+
+/// An ongoing feed of a [`Database`]'s executed transactions, returned from
+/// [`Database::transaction_feed()`].
+///
+/// The feed first yields the transactions that were already in the
+/// transaction log, then waits for new transactions to be committed and yields
+/// them in order. Iterating never ends: once every committed transaction has
+/// been returned, [`Iterator::next()`] blocks until another transaction is
+/// committed. Use [`Self::try_next()`] to read without blocking.
+///
+/// ## Buffering
+///
+/// Transactions are read from the transaction log in batches, not pushed to
+/// the feed as they are committed. A consumer that falls behind does not cause
+/// committed transactions to be buffered in memory; at most one batch is
+/// held by the feed at a time. When the consumer resumes, it catches up by
+/// reading the log from where it left off, so no transactions are skipped
+/// regardless of how far behind it is.
+#[derive(Debug)]
+pub struct TransactionFeed {
+    database: Database,
+    next_id: Option<u64>,
+    pending: VecDeque<Executed>,
+    committed: flume::Receiver<()>,
+}
+
+impl TransactionFeed {
+    pub(crate) fn new(database: Database, starting_id: Option<u64>) -> Self {
+        let committed = database.data.context.transaction_watchers.watch();
+        Self {
+            database,
+            next_id: starting_id,
+            pending: VecDeque::new(),
+            committed,
+        }
+    }
+
+    /// Returns the next executed transaction if one has been committed, or
+    /// `None` if every committed transaction has already been returned.
+    pub fn try_next(&mut self) -> Option<Result<Executed, bonsaidb_core::Error>> {
+        if self.pending.is_empty() {
+            if let Err(err) = self.read_batch() {
+                return Some(Err(err));
+            }
+        }
+
+        self.pop_pending().map(Ok)
+    }
+
+    pub(crate) fn pop_pending(&mut self) -> Option<Executed> {
+        self.pending.pop_front()
+    }
+
+    pub(crate) fn committed(&self) -> &flume::Receiver<()> {
+        &self.committed
+    }
+
+    fn read_batch(&mut self) -> Result<(), bonsaidb_core::Error> {
+        let batch = self
+            .database
+            .list_executed_transactions(self.next_id, None)?;
+        if let Some(last) = batch.last() {
+            self.next_id = Some(last.id + 1);
+        }
+        self.pending.extend(batch);
+        Ok(())
+    }
+}
+
+impl Iterator for TransactionFeed {
+    type Item = Result<Executed, bonsaidb_core::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(next) = self.try_next() {
+                return Some(next);
+            }
+
+            // The feed holds a reference to the database, so the sender can
+            // only be disconnected if the database is being torn down.
+            if self.committed.recv().is_err() {
+                return None;
+            }
+        }
+    }
+}
@@ -71,6 +71,16 @@ pub enum Error {
     #[error("a backup error: {0}")]
     Backup(Box<dyn AnyError>),
 
+    /// A document read through a [`Snapshot`](crate::Snapshot) was changed
+    /// in the named database after the snapshot was taken.
+    #[error("a document in database {0} was changed after the snapshot was taken")]
+    SnapshotInvalidated(String),
+
+    /// A consistent [`Snapshot`](crate::Snapshot) could not be captured
+    /// because the databases were continuously being modified.
+    #[error("databases were modified during every attempt to take a snapshot")]
+    SnapshotContention,
+
     /// An error occurred with a password hash.
     #[cfg(all(feature = "password-hashing", feature = "cli"))]
     #[error("error reading password: {0}")]
@@ -119,6 +129,13 @@ impl<T> From<UnknownVersion<T>> for Error {
     }
 }
 
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::other("serde_json", err)
+    }
+}
+
 #[cfg(feature = "password-hashing")]
 impl From<argon2::Error> for Error {
     fn from(err: argon2::Error) -> Self {
@@ -25,6 +25,7 @@ mod error;
 mod open_trees;
 mod storage;
 mod tasks;
+mod tenants;
 #[cfg(feature = "encryption")]
 pub mod vault;
 mod views;
@@ -34,10 +35,19 @@ pub use argon2;
 #[cfg(not(feature = "included-from-omnibus"))]
 pub use bonsaidb_core as core;
 
+pub use self::database::document_cache::DocumentCacheStatistics;
 pub use self::database::pubsub::Subscriber;
-pub use self::database::{Database, DatabaseNonBlocking};
+pub use self::database::transaction_feed::TransactionFeed;
+pub use self::database::{CollectionStats, Database, DatabaseNonBlocking, ViewStatus};
 pub use self::error::Error;
-pub use self::storage::{BackupLocation, Storage, StorageId, StorageNonBlocking};
+#[cfg(feature = "encryption")]
+pub use self::storage::ArchiveKey;
+pub use self::storage::{
+    ArchiveEncryption, ArchiveError, BackupLocation, Snapshot, SnapshotDatabase, Storage,
+    StorageId, StorageNonBlocking, MAX_SNAPSHOT_ATTEMPTS,
+};
+pub use self::tenants::TenantManager;
+pub use self::views::verification::{ViewMappingDiscrepancy, ViewVerificationReport};
 
 #[cfg(feature = "async")]
 mod r#async;
@@ -4,11 +4,12 @@ use bonsaidb_core::schema::{CollectionName, Schematic};
 use nebari::io::any::AnyFile;
 use nebari::tree::{AnyTreeRoot, Root, Unversioned, Versioned};
 
-use crate::database::document_tree_name;
+use crate::database::{document_tree_name, history_tree_name};
 #[cfg(any(feature = "encryption", feature = "compression"))]
 use crate::storage::TreeVault;
 use crate::views::{
     view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
+    view_related_invalidated_docs_tree_name,
 };
 
 #[derive(Default)]
@@ -52,6 +53,14 @@ impl OpenTrees {
             vault.clone(),
         );
 
+        if schema.history_limit(collection) > 0 {
+            self.open_tree::<Unversioned>(
+                &history_tree_name(collection),
+                #[cfg(any(feature = "encryption", feature = "compression"))]
+                vault.clone(),
+            );
+        }
+
         for view in schema.views_in_collection(collection) {
             let view_name = view.view_name();
             if view.update_policy().is_eager() {
@@ -73,5 +82,13 @@ impl OpenTrees {
                 );
             }
         }
+
+        for view in schema.views_related_to_collection(collection) {
+            self.open_tree::<Unversioned>(
+                &view_related_invalidated_docs_tree_name(&view.view_name(), collection),
+                #[cfg(any(feature = "encryption", feature = "compression"))]
+                vault.clone(),
+            );
+        }
     }
 }
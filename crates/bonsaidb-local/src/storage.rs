@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Display};
 use std::fs::{self, File};
 use std::io::{Read, Write};
@@ -10,6 +10,7 @@ use std::sync::{Arc, Weak};
 use bonsaidb_core::admin::database::{self, ByName, Database as DatabaseRecord};
 use bonsaidb_core::admin::user::User;
 use bonsaidb_core::admin::{self, Admin, PermissionGroup, Role, ADMIN_DATABASE_NAME};
+use bonsaidb_core::arc_bytes::OwnedBytes;
 use bonsaidb_core::circulate;
 pub use bonsaidb_core::circulate::Relay;
 use bonsaidb_core::connection::{
@@ -23,9 +24,12 @@ use bonsaidb_core::permissions::bonsai::{
     bonsaidb_resource_name, database_resource_name, role_resource_name, user_resource_name,
     BonsaiAction, ServerAction,
 };
+#[cfg(feature = "encryption")]
+use bonsaidb_core::permissions::bonsai::{encryption_key_resource_name, EncryptionKeyAction};
 use bonsaidb_core::permissions::Permissions;
+use bonsaidb_core::pubsub::{database_topic, Topic, TopicPattern, TransactionsTopic};
 use bonsaidb_core::schema::{
-    Nameable, NamedCollection, Schema, SchemaName, SchemaSummary, Schematic,
+    Migrations, Nameable, NamedCollection, Schema, SchemaName, SchemaSummary, Schematic,
 };
 use fs2::FileExt;
 use itertools::Itertools;
@@ -37,22 +41,30 @@ use rand::{thread_rng, Rng};
 
 #[cfg(feature = "compression")]
 use crate::config::Compression;
-use crate::config::{KeyValuePersistence, StorageConfiguration};
+use crate::config::{KeyValuePersistence, SchemaSet, StorageConfiguration};
+use crate::database::document_cache::DocumentCache;
+use crate::database::transaction_feed::TransactionPublisher;
 use crate::database::Context;
 use crate::tasks::manager::Manager;
 use crate::tasks::TaskManager;
 #[cfg(feature = "encryption")]
 use crate::vault::{self, LocalVaultKeyStorage, Vault};
-use crate::{Database, Error};
+use crate::{Database, DocumentCacheStatistics, Error};
 
 #[cfg(feature = "password-hashing")]
 mod argon;
 #[cfg(feature = "token-authentication")]
 mod token_authentication;
 
+mod archive;
 mod backup;
 mod pubsub;
+mod snapshot;
+#[cfg(feature = "encryption")]
+pub use archive::ArchiveKey;
+pub use archive::{ArchiveEncryption, ArchiveError};
 pub use backup::{AnyBackupLocation, BackupLocation};
+pub use snapshot::{Snapshot, SnapshotDatabase, MAX_SNAPSHOT_ATTEMPTS};
 
 /// A file-based, multi-database, multi-user database engine. This type blocks
 /// the current thread when used. See [`AsyncStorage`](crate::AsyncStorage) for
@@ -166,26 +178,53 @@ pub struct SessionSubscribers {
     pub subscribers: HashMap<u64, SessionSubscriber>,
     pub subscribers_by_session: HashMap<SessionId, HashSet<u64>>,
     pub last_id: u64,
+    /// The ids of the subscribers with at least one pattern, keyed by the
+    /// name of the subscriber's database.
+    pub pattern_subscribers: HashMap<String, HashSet<u64>>,
+    /// Held while subscribers are temporarily subscribed to a topic matching
+    /// one of their patterns, ensuring concurrent publishes don't remove each
+    /// other's temporary subscriptions.
+    pub pattern_delivery: Mutex<()>,
+    /// The most recent retained message of each topic, keyed by the topic
+    /// including its database prefix.
+    pub retained: HashMap<Vec<u8>, OwnedBytes>,
 }
 
 impl SessionSubscribers {
     pub fn unregister(&mut self, subscriber_id: u64) {
-        if let Some(session_id) = self
-            .subscribers
-            .remove(&subscriber_id)
-            .and_then(|sub| sub.session_id)
-        {
-            if let Some(session_subscribers) = self.subscribers_by_session.get_mut(&session_id) {
+        if let Some(subscriber) = self.subscribers.remove(&subscriber_id) {
+            if let Some(session_subscribers) = subscriber
+                .session_id
+                .and_then(|session_id| self.subscribers_by_session.get_mut(&session_id))
+            {
                 session_subscribers.remove(&subscriber_id);
             }
+            if let Some(pattern_subscribers) =
+                self.pattern_subscribers.get_mut(&subscriber.database)
+            {
+                pattern_subscribers.remove(&subscriber_id);
+            }
         }
     }
+
+    /// Removes the retained messages of every topic in `database`.
+    pub fn clear_retained(&mut self, database: &str) {
+        let prefix = database_topic(database, b"");
+        self.retained.retain(|topic, _| !topic.starts_with(&prefix));
+    }
 }
 
 #[derive(Debug)]
 pub struct SessionSubscriber {
     pub session_id: Option<SessionId>,
+    /// The session of the database the subscriber was created from, whose
+    /// permissions are checked before delivering a message matched by a
+    /// pattern.
+    pub session: Option<Session>,
+    pub database: String,
     pub subscriber: circulate::Subscriber,
+    pub topics: HashSet<Vec<u8>>,
+    pub patterns: HashSet<TopicPattern>,
 }
 
 impl Drop for AuthenticatedSession {
@@ -205,7 +244,7 @@ impl Drop for AuthenticatedSession {
                     .into_iter()
                     .flatten()
                 {
-                    sessions.subscribers.remove(&id);
+                    sessions.unregister(id);
                 }
             }
         }
@@ -239,10 +278,18 @@ struct Data {
     parallelization: usize,
     threadpool: ThreadPool<AnyFile>,
     file_manager: AnyFileManager,
+    memory_only: bool,
     pub(crate) tasks: TaskManager,
     schemas: RwLock<HashMap<SchemaName, Arc<dyn DatabaseOpener>>>,
     available_databases: RwLock<HashMap<String, SchemaName>>,
     open_roots: Mutex<HashMap<String, Context>>,
+    /// The shared state of each database that has been opened, allowing
+    /// repeated lookups of the same database to reuse it rather than
+    /// re-creating it for every request.
+    open_databases: Mutex<HashMap<String, Arc<crate::database::Data>>>,
+    /// The number of entries in `open_databases` above which unused databases
+    /// are evicted.
+    open_databases_capacity: usize,
     // cfg check matches `Connection::authenticate`
     authenticated_permissions: Permissions,
     sessions: RwLock<AuthenticatedSessions>,
@@ -257,11 +304,33 @@ struct Data {
     tree_vault: Option<TreeVault>,
     pub(crate) key_value_persistence: KeyValuePersistence,
     chunk_cache: ChunkCache,
+    document_cache: DocumentCache,
     pub(crate) check_view_integrity_on_database_open: bool,
+    repair_stale_view_mappings: bool,
+    update_views_on_write: bool,
+    #[cfg(feature = "async")]
+    database_concurrency_limit: Option<usize>,
     relay: Relay,
 }
 
 impl Storage {
+    /// Creates or opens a multi-database [`Storage`] with its data stored in
+    /// `directory`, registering every schema in `schemas` in addition to the
+    /// schemas registered in `configuration`.
+    ///
+    /// The schemas are validated together before the storage is opened. If two
+    /// different schemas share a name, or a schema conflicts with one
+    /// registered in `configuration` or reserved by BonsaiDb,
+    /// [`bonsaidb_core::Error::SchemaAlreadyRegistered`] is returned and
+    /// nothing is opened.
+    pub fn open_with_schemas(
+        mut configuration: StorageConfiguration,
+        schemas: SchemaSet,
+    ) -> Result<Self, Error> {
+        schemas.register_into(&mut configuration)?;
+        Self::open(configuration)
+    }
+
     /// Creates or opens a multi-database [`Storage`] with its data stored in `directory`.
     pub fn open(configuration: StorageConfiguration) -> Result<Self, Error> {
         let owned_path = configuration
@@ -303,6 +372,9 @@ impl Storage {
 
         let parallelization = configuration.workers.parallelization;
         let check_view_integrity_on_database_open = configuration.views.check_integrity_on_open;
+        let repair_stale_view_mappings = configuration.views.repair_stale_mappings;
+        let update_views_on_write = configuration.views.update_on_write;
+        let cache = configuration.cache;
         let key_value_persistence = configuration.key_value_persistence;
         #[cfg(feature = "password-hashing")]
         let argon = argon::Hasher::new(configuration.argon);
@@ -340,13 +412,21 @@ impl Storage {
                     tree_vault,
                     path: owned_path,
                     file_manager,
-                    chunk_cache: ChunkCache::new(2000, 160_384),
+                    memory_only: configuration.memory_only,
+                    chunk_cache: ChunkCache::new(cache.capacity, cache.max_chunk_size),
+                    document_cache: DocumentCache::new(cache.documents),
                     threadpool: ThreadPool::new(parallelization),
                     schemas: RwLock::new(configuration.initial_schemas),
                     available_databases: RwLock::default(),
                     open_roots: Mutex::default(),
+                    open_databases: Mutex::default(),
+                    open_databases_capacity: cache.databases,
                     key_value_persistence,
                     check_view_integrity_on_database_open,
+                    repair_stale_view_mappings,
+                    update_views_on_write,
+                    #[cfg(feature = "async")]
+                    database_concurrency_limit: configuration.database_concurrency_limit,
                     relay: Relay::default(),
                 }),
             },
@@ -369,6 +449,29 @@ impl Storage {
             .database_without_schema(&name, Some(self), None)
     }
 
+    /// Deletes the trees in every database that don't belong to a collection
+    /// or view in the database's schema. Returns the names of the deleted
+    /// trees, grouped by database name. If `dry_run` is true, the names of
+    /// the trees that would be deleted are returned without deleting them.
+    ///
+    /// See [`Database::prune_orphaned_trees()`] for more information.
+    pub fn prune_orphaned_trees(
+        &self,
+        dry_run: bool,
+    ) -> Result<BTreeMap<String, Vec<String>>, Error> {
+        let mut pruned = BTreeMap::new();
+        for database in self.list_databases()? {
+            let trees = self
+                .instance
+                .database_without_schema(&database.name, Some(self), None)?
+                .prune_orphaned_trees(dry_run)?;
+            if !trees.is_empty() {
+                pruned.insert(database.name, trees);
+            }
+        }
+        Ok(pruned)
+    }
+
     fn lookup_or_create_id(
         configuration: &StorageConfiguration,
         path: &Path,
@@ -464,6 +567,41 @@ impl Storage {
         self.instance.data.lock.id()
     }
 
+    /// Returns the number of document reads that were and were not served by
+    /// the document cache, which is enabled by setting
+    /// [`Cache::documents`](crate::config::Cache::documents).
+    #[must_use]
+    pub fn document_cache_statistics(&self) -> DocumentCacheStatistics {
+        self.instance.document_cache().statistics()
+    }
+
+    /// Generates a new master key for the vault. All data written after this
+    /// call is encrypted using the new key, while data encrypted with previous
+    /// master keys remains readable. Existing data can be re-encrypted with
+    /// the new key using
+    /// [`Database::reencrypt_collection()`](crate::Database::reencrypt_collection).
+    ///
+    /// Returns the version of the new master key.
+    #[cfg(feature = "encryption")]
+    pub fn rotate_master_key(&self) -> Result<u32, Error> {
+        self.check_permission(
+            encryption_key_resource_name(&KeyId::Master),
+            &EncryptionKeyAction::Rotate,
+        )?;
+        Ok(self.vault().rotate_master_key()?)
+    }
+
+    /// Writes any key-value changes that have not been persisted yet in all
+    /// open databases, blocking until they have been written.
+    ///
+    /// Key-value changes are persisted in the background according to the
+    /// configured [`KeyValuePersistence`](crate::config::KeyValuePersistence)
+    /// and when a database is closed. This function is useful to ensure all
+    /// changes are on disk before shutting down.
+    pub fn flush_key_value_stores(&self) {
+        self.instance.flush_key_value_stores();
+    }
+
     #[must_use]
     pub(crate) fn parallelization(&self) -> usize {
         self.instance.data.parallelization
@@ -512,7 +650,7 @@ impl Storage {
         }
     }
 
-    fn validate_name(name: &str) -> Result<(), Error> {
+    pub(crate) fn validate_name(name: &str) -> Result<(), Error> {
         if name.chars().enumerate().all(|(index, c)| {
             c.is_ascii_alphanumeric()
                 || (index == 0 && c == '_')
@@ -600,17 +738,31 @@ impl Debug for Data {
             .field("tasks", &self.tasks)
             .field("available_databases", &self.available_databases)
             .field("open_roots", &self.open_roots)
+            .field("open_databases", &self.open_databases)
+            .field("open_databases_capacity", &self.open_databases_capacity)
             .field("authenticated_permissions", &self.authenticated_permissions)
             .field("sessions", &self.sessions)
             .field("subscribers", &self.subscribers)
             .field("key_value_persistence", &self.key_value_persistence)
             .field("chunk_cache", &self.chunk_cache)
+            .field("document_cache", &self.document_cache)
             .field(
                 "check_view_integrity_on_database_open",
                 &self.check_view_integrity_on_database_open,
             )
+            .field(
+                "repair_stale_view_mappings",
+                &self.repair_stale_view_mappings,
+            )
+            .field("update_views_on_write", &self.update_views_on_write)
             .field("relay", &self.relay);
 
+        #[cfg(feature = "async")]
+        f.field(
+            "database_concurrency_limit",
+            &self.database_concurrency_limit,
+        );
+
         if let Some(schemas) = self.schemas.try_read() {
             let mut schemas = schemas.keys().collect::<Vec<_>>();
             schemas.sort();
@@ -660,6 +812,7 @@ impl StorageInstance {
                 roots,
                 self.data.key_value_persistence.clone(),
                 Some(self.data.lock.clone()),
+                Some(self.transaction_publisher(name)),
             );
 
             open_roots.insert(name.to_owned(), context.clone());
@@ -668,18 +821,82 @@ impl StorageInstance {
         }
     }
 
+    /// Returns a publisher that publishes the ids of the transactions
+    /// committed in `database` to [`TransactionsTopic`].
+    fn transaction_publisher(&self, database: &str) -> TransactionPublisher {
+        // The publisher is owned by the database's context, which this instance
+        // owns. Holding a weak reference avoids a reference cycle.
+        let instance = Arc::downgrade(&self.data);
+        let database = database.to_owned();
+        let topic = TransactionsTopic
+            .to_bytes()
+            .expect("strings are always serializable");
+        Box::new(move |transaction_id| {
+            if let Some(data) = instance.upgrade() {
+                let payload =
+                    pot::to_vec(&transaction_id).expect("integers are always serializable");
+                Self { data }.publish_to_topics(&database, vec![topic.clone()], payload);
+            }
+        })
+    }
+
+    pub(crate) fn flush_key_value_stores(&self) {
+        let contexts = self
+            .data
+            .open_roots
+            .lock()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for context in contexts {
+            context.flush_key_value_store();
+        }
+    }
+
     pub(crate) fn tasks(&self) -> &'_ TaskManager {
         &self.data.tasks
     }
 
+    pub(crate) fn document_cache(&self) -> &'_ DocumentCache {
+        &self.data.document_cache
+    }
+
     pub(crate) fn check_view_integrity_on_database_open(&self) -> bool {
         self.data.check_view_integrity_on_database_open
     }
 
+    pub(crate) fn repair_stale_view_mappings(&self) -> bool {
+        self.data.repair_stale_view_mappings
+    }
+
+    pub(crate) fn update_views_on_write(&self) -> bool {
+        self.data.update_views_on_write
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn database_concurrency_limit(&self) -> Option<usize> {
+        self.data.database_concurrency_limit
+    }
+
     pub(crate) fn relay(&self) -> &'_ Relay {
         &self.data.relay
     }
 
+    /// Closes the database named `name` if no handles to it remain outside of
+    /// this storage instance. Returns true if the database was closed.
+    pub(crate) fn close_database_if_unused(&self, name: &str) -> bool {
+        let mut open_roots = self.data.open_roots.lock();
+        let mut open_databases = self.data.open_databases.lock();
+        match open_databases.get(name) {
+            Some(data) if Arc::strong_count(data) == 1 => {
+                open_databases.remove(name);
+                open_roots.remove(name);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Opens a database through a generic-free trait.
     pub(crate) fn database_without_schema(
         &self,
@@ -708,12 +925,45 @@ impl StorageInstance {
             }
         }
 
-        let mut schemas = self.data.schemas.write();
         let storage =
             storage.map_or_else(|| Cow::Owned(Storage::from(self.clone())), Cow::Borrowed);
-        if let Some(schema) = schemas.get_mut(&stored_schema) {
+        if let Some(data) = self.data.open_databases.lock().get(name) {
+            return Ok(Database {
+                data: data.clone(),
+                storage: storage.into_owned(),
+                read_only: false,
+            });
+        }
+
+        let schemas = self.data.schemas.read();
+        if let Some(schema) = schemas.get(&stored_schema) {
             let db = schema.open(name.to_string(), storage.as_ref())?;
-            Ok(db)
+            // If another thread opened the database concurrently, keep the
+            // first instance so that all handles continue to share it.
+            let mut open_roots = self.data.open_roots.lock();
+            let mut open_databases = self.data.open_databases.lock();
+            let data = open_databases
+                .entry(name.to_string())
+                .or_insert_with(|| db.data.clone())
+                .clone();
+            if open_databases.len() > self.data.open_databases_capacity {
+                // Close the databases that only this cache refers to. They
+                // will be opened again when they are next requested.
+                open_databases.retain(|name, data| {
+                    let unused = Arc::strong_count(data) == 1;
+                    if unused {
+                        open_roots.remove(name);
+                    }
+                    !unused
+                });
+            }
+            drop(open_databases);
+            drop(open_roots);
+            Ok(Database {
+                data,
+                storage: db.storage,
+                read_only: false,
+            })
         } else {
             // The schema was stored, the user is requesting the same schema,
             // but it isn't registerd with the storage currently.
@@ -911,11 +1161,14 @@ impl StorageInstance {
 
 pub trait DatabaseOpener: Send + Sync {
     fn schematic(&self) -> &'_ Schematic;
+    fn schema_version(&self) -> u32;
     fn open(&self, name: String, storage: &Storage) -> Result<Database, Error>;
 }
 
 pub struct StorageSchemaOpener<DB: Schema> {
     schematic: Schematic,
+    migrations: Migrations<Database>,
+    migration_lock: Mutex<()>,
     _phantom: PhantomData<DB>,
 }
 
@@ -927,9 +1180,39 @@ where
         let schematic = DB::schematic()?;
         Ok(Self {
             schematic,
+            migrations: Migrations::for_schema::<DB>(),
+            migration_lock: Mutex::default(),
             _phantom: PhantomData::default(),
         })
     }
+
+    /// Runs the migration steps that `name` hasn't been upgraded with yet.
+    fn migrate(&self, name: &str, db: &Database) -> Result<(), Error> {
+        if self.migrations.version() == 0 {
+            return Ok(());
+        }
+
+        // Migrations run using a handle that isn't restricted by the
+        // permissions of the session that caused the database to be opened.
+        let _guard = self.migration_lock.lock();
+        let admin = db.storage.instance.admin();
+        let Some(mut record) = DatabaseRecord::load(name, &admin)? else {
+            return Err(Error::Core(bonsaidb_core::Error::DatabaseNotFound(
+                name.to_string(),
+            )));
+        };
+        let db = Database {
+            data: db.data.clone(),
+            storage: Storage::from(db.storage.instance.clone()),
+            read_only: false,
+        };
+        self.migrations
+            .migrate(record.contents.schema_version, &db, |version| {
+                record.contents.schema_version = version;
+                record.update(&admin)
+            })?;
+        Ok(())
+    }
 }
 
 impl<DB> DatabaseOpener for StorageSchemaOpener<DB>
@@ -940,9 +1223,14 @@ where
         &self.schematic
     }
 
+    fn schema_version(&self) -> u32 {
+        self.migrations.version()
+    }
+
     fn open(&self, name: String, storage: &Storage) -> Result<Database, Error> {
         let roots = storage.instance.open_roots(&name)?;
-        let db = Database::new::<DB, _>(name, roots, storage)?;
+        let db = Database::new::<DB, _>(name.clone(), roots, storage)?;
+        self.migrate(&name, &db)?;
         Ok(db)
     }
 }
@@ -982,12 +1270,13 @@ impl StorageConnection for StorageInstance {
     ) -> Result<(), bonsaidb_core::Error> {
         Storage::validate_name(name)?;
 
-        {
+        let schema_version = {
             let schemas = self.data.schemas.read();
-            if !schemas.contains_key(&schema) {
+            let Some(opener) = schemas.get(&schema) else {
                 return Err(bonsaidb_core::Error::SchemaNotRegistered(schema));
-            }
-        }
+            };
+            opener.schema_version()
+        };
 
         let mut available_databases = self.data.available_databases.write();
         let admin = self.admin();
@@ -997,6 +1286,7 @@ impl StorageConnection for StorageInstance {
                 .push(&admin::Database {
                     name: name.to_string(),
                     schema: schema.clone(),
+                    schema_version,
                 })?;
             available_databases.insert(name.to_string(), schema);
         } else if !only_if_needed {
@@ -1021,6 +1311,9 @@ impl StorageConnection for StorageInstance {
 
         let mut open_roots = self.data.open_roots.lock();
         open_roots.remove(name);
+        self.data.open_databases.lock().remove(name);
+        self.data.document_cache.invalidate_database(name);
+        self.data.subscribers.write().clear_retained(name);
 
         let database_folder = self.data.path.join(name);
         if database_folder.exists() {
@@ -1044,6 +1337,97 @@ impl StorageConnection for StorageInstance {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn rename_database(&self, name: &str, new_name: &str) -> Result<(), bonsaidb_core::Error> {
+        Storage::validate_name(new_name)?;
+        if name == ADMIN_DATABASE_NAME || new_name == ADMIN_DATABASE_NAME {
+            return Err(bonsaidb_core::Error::InvalidDatabaseName(
+                ADMIN_DATABASE_NAME.to_string(),
+            ));
+        }
+
+        let admin = self.admin();
+        let mut available_databases = self.data.available_databases.write();
+        if available_databases.contains_key(new_name) {
+            return Err(bonsaidb_core::Error::DatabaseNameAlreadyTaken(
+                new_name.to_string(),
+            ));
+        }
+        let Some(schema) = available_databases.get(name).cloned() else {
+            return Err(bonsaidb_core::Error::DatabaseNotFound(name.to_string()));
+        };
+        let Some(mut record) = admin
+            .view::<database::ByName>()
+            .with_key(name)
+            .query_with_collection_docs()?
+            .into_iter()
+            .next()
+            .map(|mapping| mapping.document.clone())
+        else {
+            return Err(bonsaidb_core::Error::DatabaseNotFound(name.to_string()));
+        };
+
+        // Memory-only files can't be moved, and the database's data is stored
+        // beneath a path derived from its name.
+        if self.data.memory_only {
+            return Err(bonsaidb_core::Error::other(
+                "bonsaidb-local",
+                "memory-only storage does not support renaming databases",
+            ));
+        }
+
+        let database_folder = self.data.path.join(name);
+        let new_database_folder = self.data.path.join(new_name);
+        if database_folder.exists() && new_database_folder.exists() {
+            return Err(bonsaidb_core::Error::DatabaseNameAlreadyTaken(
+                new_name.to_string(),
+            ));
+        }
+
+        // The database's files can't be moved while another handle to the
+        // database could still be reading or writing them. The locks are held
+        // until the rename completes, and opening a database requires reading
+        // `available_databases`, so the database can't be reopened meanwhile.
+        let mut open_roots = self.data.open_roots.lock();
+        let mut open_databases = self.data.open_databases.lock();
+        let cached_data = open_databases.get(name);
+        let in_use = cached_data.map_or(false, |data| Arc::strong_count(data) > 1)
+            || open_roots.get(name).map_or(false, |context| {
+                // The cached database, if any, holds one handle to the context.
+                context.strong_count() > 1 + usize::from(cached_data.is_some())
+            });
+        if in_use {
+            return Err(bonsaidb_core::Error::other(
+                "bonsaidb-local",
+                format!("database {name} can't be renamed while it is in use"),
+            ));
+        }
+
+        // Release the cached handles to the database's files, persisting any
+        // pending key-value changes before the files are moved.
+        open_databases.remove(name);
+        drop(open_roots.remove(name));
+        self.data.document_cache.invalidate_database(name);
+        self.data.subscribers.write().clear_retained(name);
+
+        // The directory will not exist if the database has never been opened,
+        // or if a previous rename was interrupted after moving it. Renaming
+        // the directory is atomic, and until the record below is updated the
+        // database is still known as `name`, allowing an interrupted rename to
+        // be completed by calling this function again.
+        if database_folder.exists() {
+            fs::rename(&database_folder, &new_database_folder).map_err(Error::from)?;
+        }
+
+        record.contents.name = new_name.to_string();
+        record.update(&admin)?;
+
+        available_databases.remove(name);
+        available_databases.insert(new_name.to_string(), schema);
+
+        Ok(())
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         let available_databases = self.data.available_databases.read();
@@ -1056,6 +1440,11 @@ impl StorageConnection for StorageInstance {
             .collect())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn database_schema(&self, name: &str) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        Ok(self.data.available_databases.read().get(name).cloned())
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         let available_databases = self.data.available_databases.read();
@@ -1257,6 +1646,18 @@ impl StorageConnection for Storage {
         self.instance.delete_database(name)
     }
 
+    fn rename_database(&self, name: &str, new_name: &str) -> Result<(), bonsaidb_core::Error> {
+        self.check_permission(
+            database_resource_name(name),
+            &BonsaiAction::Server(ServerAction::RenameDatabase),
+        )?;
+        self.check_permission(
+            database_resource_name(new_name),
+            &BonsaiAction::Server(ServerAction::CreateDatabase),
+        )?;
+        self.instance.rename_database(name, new_name)
+    }
+
     fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         self.check_permission(
             bonsaidb_resource_name(),
@@ -1265,6 +1666,14 @@ impl StorageConnection for Storage {
         self.instance.list_databases()
     }
 
+    fn database_schema(&self, name: &str) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        self.check_permission(
+            bonsaidb_resource_name(),
+            &BonsaiAction::Server(ServerAction::ListDatabases),
+        )?;
+        self.instance.database_schema(name)
+    }
+
     fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         self.check_permission(
             bonsaidb_resource_name(),
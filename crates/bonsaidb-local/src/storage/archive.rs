@@ -0,0 +1,653 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+
+use bonsaidb_core::admin;
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::{Bound, LowLevelConnection, Range, Sort, StorageConnection};
+use bonsaidb_core::document::DocumentId;
+use bonsaidb_core::schema::{Collection, CollectionName, Qualified, SchemaName};
+use bonsaidb_core::transaction::{Operation, Transaction};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "encryption")]
+use chacha20poly1305::aead::generic_array::GenericArray;
+#[cfg(feature = "encryption")]
+use chacha20poly1305::aead::Aead;
+#[cfg(feature = "encryption")]
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+#[cfg(feature = "encryption")]
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::database::keyvalue::Entry;
+use crate::{Database, Error, Storage};
+
+/// The bytes every archive begins with.
+const ARCHIVE_MAGIC: &[u8; 8] = b"BONSAIDB";
+
+/// The version of the archive format written by [`Storage::export_to()`].
+const ARCHIVE_VERSION: u32 = 1;
+
+/// The container used to record each database's existence.
+const DATABASES_CONTAINER: &str = "_database";
+
+/// The container that key-value entries are stored in.
+const KEY_VALUE_CONTAINER: &str = "_kv";
+
+/// The number of documents read from a collection at a time while exporting,
+/// and the number of documents inserted per transaction while importing.
+const ARCHIVE_BATCH_SIZE: u32 = 1_000;
+
+/// The length of the nonce prefixed to each encrypted archive entry.
+#[cfg(feature = "encryption")]
+const NONCE_LENGTH: usize = 24;
+
+/// Controls how the contents of an archive are protected.
+///
+/// Documents in encrypted collections are decrypted by the vault while being
+/// exported, and the vault's keys are never written to an archive. To prevent
+/// sensitive data from being written to a backup in cleartext by accident, the
+/// encryption of an archive must always be chosen explicitly.
+#[derive(Debug, Clone)]
+pub enum ArchiveEncryption {
+    /// The archive's contents are stored in cleartext.
+    Cleartext,
+    /// Each entry in the archive is encrypted with this key using
+    /// XChaCha20Poly1305. The same key must be provided to import the archive.
+    #[cfg(feature = "encryption")]
+    Key(ArchiveKey),
+}
+
+impl ArchiveEncryption {
+    const fn is_encrypted(&self) -> bool {
+        match self {
+            Self::Cleartext => false,
+            #[cfg(feature = "encryption")]
+            Self::Key(_) => true,
+        }
+    }
+
+    fn seal(&self, entry: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::Cleartext => entry,
+            #[cfg(feature = "encryption")]
+            Self::Key(key) => key.encrypt(&entry),
+        }
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn open(&self, entry: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Cleartext => Ok(entry),
+            #[cfg(feature = "encryption")]
+            Self::Key(key) => key.decrypt(&entry),
+        }
+    }
+}
+
+/// A secret key used to encrypt the contents of an archive.
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub struct ArchiveKey(zeroize::Zeroizing<[u8; 32]>);
+
+#[cfg(feature = "encryption")]
+impl ArchiveKey {
+    /// Returns a key using `secret`.
+    #[must_use]
+    pub fn new(secret: [u8; 32]) -> Self {
+        Self(zeroize::Zeroizing::new(secret))
+    }
+
+    /// Returns a new, randomly generated key.
+    #[must_use]
+    pub fn random() -> Self {
+        Self::new(thread_rng().gen())
+    }
+
+    /// Returns the secret bytes of this key. The key must be stored separately
+    /// from the archive to be able to import it later.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(GenericArray::from_slice(self.0.as_slice()))
+    }
+
+    fn encrypt(&self, entry: &[u8]) -> Vec<u8> {
+        let nonce: [u8; NONCE_LENGTH] = thread_rng().gen();
+        let encrypted = self
+            .cipher()
+            .encrypt(GenericArray::from_slice(&nonce), entry)
+            .expect("encryption cannot fail");
+        let mut sealed = Vec::with_capacity(NONCE_LENGTH + encrypted.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend(encrypted);
+        sealed
+    }
+
+    fn decrypt(&self, entry: &[u8]) -> Result<Vec<u8>, Error> {
+        if entry.len() < NONCE_LENGTH {
+            return Err(Error::Backup(Box::new(ArchiveError::Truncated)));
+        }
+        let (nonce, encrypted) = entry.split_at(NONCE_LENGTH);
+        let decrypted = self
+            .cipher()
+            .decrypt(GenericArray::from_slice(nonce), encrypted)
+            .map_err(crate::vault::Error::from)?;
+        Ok(decrypted)
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl std::fmt::Debug for ArchiveKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchiveKey").finish_non_exhaustive()
+    }
+}
+
+/// An error reading an archive created by [`Storage::export_to()`].
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    /// The data does not begin with an archive header.
+    #[error("not a BonsaiDb archive")]
+    NotAnArchive,
+    /// The archive was written using an unsupported version of the format.
+    #[error("unsupported archive version: {0}")]
+    UnsupportedVersion(u32),
+    /// The archive is encrypted, but [`ArchiveEncryption::Cleartext`] was
+    /// provided.
+    #[error("the archive is encrypted, but no key was provided")]
+    KeyRequired,
+    /// The archive is not encrypted, but a key was provided.
+    #[error("the archive is not encrypted, but a key was provided")]
+    NotEncrypted,
+    /// The archive ended unexpectedly.
+    #[error("the archive is truncated")]
+    Truncated,
+    /// The archive contains data for a database that was not declared before
+    /// it.
+    #[error("the archive contains data for an undeclared database: {0}")]
+    UnknownDatabase(String),
+    /// The archive contains a document id or key-value entry name that could
+    /// not be parsed.
+    #[error("the archive contains an invalid object name: {0}")]
+    InvalidObjectName(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveHeader {
+    encrypted: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchivedObject {
+    schema: SchemaName,
+    database: String,
+    container: String,
+    name: String,
+    contents: Bytes,
+}
+
+/// Writes objects to an archive as they are read from the storage.
+struct ArchiveWriter<'a, W> {
+    writer: W,
+    encryption: &'a ArchiveEncryption,
+}
+
+impl<W: Write> ArchiveWriter<'_, W> {
+    fn write_object(
+        &mut self,
+        schema: &SchemaName,
+        database: &str,
+        container: &str,
+        name: &str,
+        contents: &[u8],
+    ) -> Result<(), Error> {
+        let object = pot::to_vec(&ArchivedObject {
+            schema: schema.clone(),
+            database: database.to_string(),
+            container: container.to_string(),
+            name: name.to_string(),
+            contents: Bytes::from(contents.to_vec()),
+        })?;
+        write_entry(&mut self.writer, &self.encryption.seal(object))
+    }
+
+    fn write_database(&mut self, name: &str, database: &Database) -> Result<(), Error> {
+        let schema = &database.schematic().name;
+        // Record the database before its contents, ensuring that databases
+        // without any stored data are still recreated when the archive is
+        // imported.
+        self.write_object(schema, name, DATABASES_CONTAINER, "", &[])?;
+
+        for collection in database.schematic().collections() {
+            let container = collection.encoded();
+            let mut start = Bound::Unbounded;
+            loop {
+                let documents = database.list_from_collection(
+                    Range {
+                        start,
+                        end: Bound::Unbounded,
+                    },
+                    Sort::Ascending,
+                    Some(ARCHIVE_BATCH_SIZE),
+                    collection,
+                )?;
+                let Some(last) = documents.last() else { break };
+                start = Bound::Excluded(last.header.id.clone());
+
+                for document in &documents {
+                    self.write_object(
+                        schema,
+                        name,
+                        &container,
+                        &document.header.id.to_string(),
+                        &document.contents,
+                    )?;
+                }
+            }
+        }
+
+        for ((namespace, key), entry) in database.all_key_value_entries()? {
+            let full_name = format!("{}._key._{key}", namespace.as_deref().unwrap_or(""));
+            let entry = pot::to_vec(&entry)?;
+            self.write_object(schema, name, KEY_VALUE_CONTAINER, &full_name, &entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Restores the objects of a single database as they are read from an
+/// archive.
+struct DatabaseImport {
+    name: String,
+    database: Database,
+    /// The collections to restore, by their encoded names.
+    collections: HashMap<String, CollectionName>,
+    pending: Transaction,
+}
+
+impl DatabaseImport {
+    fn new(name: String, database: Database) -> Self {
+        // The Databases list of the admin database is recreated while
+        // importing, so it is not restored.
+        let database_collection = admin::Database::collection_name();
+        let collections = database
+            .schematic()
+            .collections()
+            .filter(|collection| *collection != &database_collection)
+            .map(|collection| (collection.encoded(), collection.clone()))
+            .collect();
+        Self {
+            name,
+            database,
+            collections,
+            pending: Transaction::new(),
+        }
+    }
+
+    fn restore(&mut self, object: ArchivedObject) -> Result<(), Error> {
+        if object.container == KEY_VALUE_CONTAINER {
+            let Some((namespace, key)) = object.name.split_once("._key._") else {
+                return Err(invalid_object_name(object.name));
+            };
+            let entry = pot::from_slice::<Entry>(&object.contents)?;
+            let namespace = if namespace.is_empty() {
+                None
+            } else {
+                Some(namespace.to_string())
+            };
+            entry.restore(namespace, key.to_string(), &self.database)?;
+        } else if let Some(collection) = self.collections.get(&object.container) {
+            let Ok(id) = object.name.parse::<DocumentId>() else {
+                return Err(invalid_object_name(object.name));
+            };
+            self.pending.push(Operation::insert(
+                collection.clone(),
+                Some(id),
+                object.contents.into_vec(),
+            ));
+            if self.pending.operations.len() >= ARCHIVE_BATCH_SIZE as usize {
+                self.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if !self.pending.operations.is_empty() {
+            self.database
+                .apply_transaction(std::mem::take(&mut self.pending))?;
+        }
+        Ok(())
+    }
+}
+
+impl Storage {
+    /// Writes a copy of every database in this instance to `writer` as a
+    /// single, versioned archive.
+    ///
+    /// The archive contains each database's name and schema along with its
+    /// documents and key-value entries. Views are not archived, as they are
+    /// rebuilt from the restored documents, and the transaction log starts
+    /// over when the archive is imported.
+    ///
+    /// Documents in encrypted collections are exported decrypted. `encryption`
+    /// controls whether the archive is written in cleartext or encrypted with
+    /// a separate [`ArchiveKey`](crate::ArchiveKey).
+    ///
+    /// Documents are read in batches and written to `writer` as they are read,
+    /// so the contents of the storage are never held in memory at once.
+    pub fn export_to<W: Write>(
+        &self,
+        writer: W,
+        encryption: &ArchiveEncryption,
+    ) -> Result<(), Error> {
+        let databases = self
+            .instance
+            .data
+            .available_databases
+            .read()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut archive = ArchiveWriter { writer, encryption };
+        archive.writer.write_all(ARCHIVE_MAGIC)?;
+        archive.writer.write_u32::<BigEndian>(ARCHIVE_VERSION)?;
+        write_entry(
+            &mut archive.writer,
+            &pot::to_vec(&ArchiveHeader {
+                encrypted: encryption.is_encrypted(),
+            })?,
+        )?;
+        for name in databases {
+            let database = self
+                .instance
+                .database_without_schema(&name, Some(self), None)?;
+            archive.write_database(&name, &database)?;
+        }
+        // A zero-length entry marks the end of the archive.
+        archive.writer.write_u64::<BigEndian>(0)?;
+        archive.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Restores every database contained in an archive previously written by
+    /// [`Self::export_to()`]. Each database is created with the schema it was
+    /// exported with, which must be registered with this instance.
+    ///
+    /// The admin database is restored into this instance's existing admin
+    /// database. Every other database must not already exist. Importing stops
+    /// with [`bonsaidb_core::Error::DatabaseNameAlreadyTaken`] when it reaches
+    /// a database that already exists, before any of that database's contents
+    /// are restored.
+    ///
+    /// `encryption` must match the encryption used to export the archive.
+    ///
+    /// Documents are restored in batches as they are read from `reader`. If an
+    /// error occurs, the databases imported up to that point are left in
+    /// place, and the last database may be partially restored.
+    pub fn import_from<R: Read>(
+        &self,
+        mut reader: R,
+        encryption: &ArchiveEncryption,
+    ) -> Result<(), Error> {
+        let mut magic = [0; ARCHIVE_MAGIC.len()];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|err| truncated_or(err, ArchiveError::NotAnArchive))?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(Error::Backup(Box::new(ArchiveError::NotAnArchive)));
+        }
+        let version = reader
+            .read_u32::<BigEndian>()
+            .map_err(|err| truncated_or(err, ArchiveError::NotAnArchive))?;
+        if version != ARCHIVE_VERSION {
+            return Err(Error::Backup(Box::new(ArchiveError::UnsupportedVersion(
+                version,
+            ))));
+        }
+
+        let header = read_entry(&mut reader)?
+            .ok_or_else(|| Error::Backup(Box::new(ArchiveError::Truncated)))?;
+        let header = pot::from_slice::<ArchiveHeader>(&header)?;
+        match (header.encrypted, encryption.is_encrypted()) {
+            (true, false) => return Err(Error::Backup(Box::new(ArchiveError::KeyRequired))),
+            (false, true) => return Err(Error::Backup(Box::new(ArchiveError::NotEncrypted))),
+            _ => {}
+        }
+
+        let mut current = None::<DatabaseImport>;
+        while let Some(entry) = read_entry(&mut reader)? {
+            let object = pot::from_slice::<ArchivedObject>(&encryption.open(entry)?)?;
+            if object.container == DATABASES_CONTAINER {
+                if let Some(mut finished) = current.take() {
+                    finished.flush()?;
+                }
+                // The admin database is already going to be created by the
+                // process of creating a database. Any other existing database
+                // is rejected rather than merged with the archive's contents.
+                let only_if_needed = object.database == admin::ADMIN_DATABASE_NAME;
+                self.create_database_with_schema(&object.database, object.schema, only_if_needed)?;
+                let database =
+                    self.instance
+                        .database_without_schema(&object.database, Some(self), None)?;
+                current = Some(DatabaseImport::new(object.database, database));
+            } else {
+                match &mut current {
+                    Some(import) if import.name == object.database => import.restore(object)?,
+                    _ => {
+                        return Err(Error::Backup(Box::new(ArchiveError::UnknownDatabase(
+                            object.database,
+                        ))))
+                    }
+                }
+            }
+        }
+
+        if let Some(mut finished) = current {
+            finished.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_entry<W: Write>(writer: &mut W, entry: &[u8]) -> Result<(), Error> {
+    writer.write_u64::<BigEndian>(entry.len() as u64)?;
+    writer.write_all(entry)?;
+    Ok(())
+}
+
+fn read_entry<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>, Error> {
+    let length = reader
+        .read_u64::<BigEndian>()
+        .map_err(|err| truncated_or(err, ArchiveError::Truncated))?;
+    if length == 0 {
+        return Ok(None);
+    }
+
+    // Reading through `take()` avoids allocating a buffer for the full length
+    // before verifying that the archive actually contains that much data.
+    let mut entry = Vec::new();
+    reader.take(length).read_to_end(&mut entry)?;
+    if entry.len() as u64 == length {
+        Ok(Some(entry))
+    } else {
+        Err(Error::Backup(Box::new(ArchiveError::Truncated)))
+    }
+}
+
+fn invalid_object_name(name: String) -> Error {
+    Error::Backup(Box::new(ArchiveError::InvalidObjectName(name)))
+}
+
+fn truncated_or(err: std::io::Error, unexpected_eof: ArchiveError) -> Error {
+    if err.kind() == ErrorKind::UnexpectedEof {
+        Error::Backup(Box::new(unexpected_eof))
+    } else {
+        Error::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bonsaidb_core::connection::{Connection as _, StorageConnection as _};
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::{Collection, Qualified, Schema, SerializedCollection};
+    use bonsaidb_core::test_util::{Basic, TestDirectory};
+    use bonsaidb_core::transaction::Transaction;
+    use byteorder::{BigEndian, WriteBytesExt};
+
+    use super::{
+        write_entry, ArchiveEncryption, ArchiveError, ArchiveHeader, ArchiveWriter,
+        ARCHIVE_BATCH_SIZE, ARCHIVE_MAGIC, ARCHIVE_VERSION, DATABASES_CONTAINER,
+        KEY_VALUE_CONTAINER,
+    };
+    use crate::config::{Builder, StorageConfiguration};
+    use crate::{Error, Storage};
+
+    fn export_import(name: &str, encryption: &ArchiveEncryption) -> anyhow::Result<()> {
+        let mut archive = Vec::new();
+        let test_doc = {
+            let database_directory = TestDirectory::new(format!("{name}-source.bonsaidb"));
+            let storage = Storage::open(
+                StorageConfiguration::new(&database_directory).with_schema::<Basic>()?,
+            )?;
+
+            let db = storage.create_database::<Basic>("basic", false)?;
+            let test_doc = db.collection::<Basic>().push(&Basic::new("somevalue"))?;
+            db.set_numeric_key("key1", 1_u64).execute()?;
+            // Store enough documents to be exported and imported in more
+            // than one batch.
+            let mut tx = Transaction::new();
+            for index in 0..ARCHIVE_BATCH_SIZE {
+                Basic::new(format!("batch {index}")).push_in_transaction(&mut tx)?;
+            }
+            tx.apply(&db)?;
+
+            storage.export_to(&mut archive, encryption)?;
+
+            test_doc
+        };
+
+        let database_directory = TestDirectory::new(format!("{name}-restored.bonsaidb"));
+        let restored_storage =
+            Storage::open(StorageConfiguration::new(&database_directory).with_schema::<Basic>()?)?;
+        restored_storage.import_from(archive.as_slice(), encryption)?;
+
+        let db = restored_storage.database::<Basic>("basic")?;
+        let doc = Basic::get(&test_doc.id, &db)?.expect("archived document not found");
+        assert_eq!(doc.contents.value, "somevalue");
+        assert_eq!(db.get_key("key1").into_u64()?, Some(1));
+        assert_eq!(Basic::all(&db).count()?, u64::from(ARCHIVE_BATCH_SIZE) + 1);
+
+        // Importing into a storage that already contains the database fails
+        // without changing the existing database.
+        let Err(Error::Core(bonsaidb_core::Error::DatabaseNameAlreadyTaken(existing))) =
+            restored_storage.import_from(archive.as_slice(), encryption)
+        else {
+            unreachable!("existing database was not rejected")
+        };
+        assert_eq!(existing, "basic");
+        assert_eq!(Basic::all(&db).count()?, u64::from(ARCHIVE_BATCH_SIZE) + 1);
+
+        // Importing a truncated archive should fail.
+        let other_directory = TestDirectory::new(format!("{name}-truncated.bonsaidb"));
+        let other_storage =
+            Storage::open(StorageConfiguration::new(&other_directory).with_schema::<Basic>()?)?;
+        assert!(other_storage
+            .import_from(&archive[..archive.len() - 1], encryption)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn cleartext_archive() -> anyhow::Result<()> {
+        export_import("cleartext-archive", &ArchiveEncryption::Cleartext)
+    }
+
+    /// Returns a cleartext archive containing the `basic` database and a
+    /// single object named `name` in `container`.
+    fn archive_with_object(container: &str, name: &str) -> anyhow::Result<Vec<u8>> {
+        let mut archive = Vec::new();
+        archive.extend_from_slice(ARCHIVE_MAGIC);
+        archive.write_u32::<BigEndian>(ARCHIVE_VERSION)?;
+        write_entry(
+            &mut archive,
+            &pot::to_vec(&ArchiveHeader { encrypted: false })?,
+        )?;
+        let schema = Basic::schema_name();
+        let mut writer = ArchiveWriter {
+            writer: &mut archive,
+            encryption: &ArchiveEncryption::Cleartext,
+        };
+        writer.write_object(&schema, "basic", DATABASES_CONTAINER, "", &[])?;
+        writer.write_object(&schema, "basic", container, name, &[])?;
+        archive.write_u64::<BigEndian>(0)?;
+        Ok(archive)
+    }
+
+    #[test]
+    fn invalid_object_names() -> anyhow::Result<()> {
+        for (index, container) in [
+            Basic::collection_name().encoded(),
+            KEY_VALUE_CONTAINER.into(),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let archive = archive_with_object(&container, "not-hex")?;
+            let directory = TestDirectory::new(format!("invalid-object-names-{index}.bonsaidb"));
+            let storage =
+                Storage::open(StorageConfiguration::new(&directory).with_schema::<Basic>()?)?;
+            let Err(Error::Backup(err)) =
+                storage.import_from(archive.as_slice(), &ArchiveEncryption::Cleartext)
+            else {
+                unreachable!("invalid object name was not rejected")
+            };
+            assert_eq!(
+                err.to_string(),
+                ArchiveError::InvalidObjectName(String::from("not-hex")).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn encrypted_archive() -> anyhow::Result<()> {
+        let encryption = ArchiveEncryption::Key(super::ArchiveKey::random());
+        export_import("encrypted-archive", &encryption)?;
+
+        // An encrypted archive can't be imported without its key.
+        let mut archive = Vec::new();
+        let source_directory = TestDirectory::new("encrypted-archive-key.bonsaidb");
+        let storage =
+            Storage::open(StorageConfiguration::new(&source_directory).with_schema::<Basic>()?)?;
+        storage.create_database::<Basic>("basic", false)?;
+        storage.export_to(&mut archive, &encryption)?;
+
+        let restored_directory = TestDirectory::new("encrypted-archive-key-restored.bonsaidb");
+        let restored_storage =
+            Storage::open(StorageConfiguration::new(&restored_directory).with_schema::<Basic>()?)?;
+        assert!(restored_storage
+            .import_from(archive.as_slice(), &ArchiveEncryption::Cleartext)
+            .is_err());
+        assert!(restored_storage
+            .import_from(
+                archive.as_slice(),
+                &ArchiveEncryption::Key(super::ArchiveKey::random())
+            )
+            .is_err());
+
+        Ok(())
+    }
+}
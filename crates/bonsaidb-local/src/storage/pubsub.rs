@@ -1,9 +1,18 @@
 use std::collections::hash_map::Entry;
+use std::collections::HashSet;
 
-use bonsaidb_core::connection::SessionId;
-use bonsaidb_core::pubsub::Receiver;
+use bonsaidb_core::arc_bytes::OwnedBytes;
+use bonsaidb_core::circulate::{flume, Message};
+use bonsaidb_core::connection::{HasSession, Session, SessionId};
+use bonsaidb_core::permissions::bonsai::{
+    collection_resource_name, pubsub_topic_resource_name, BonsaiAction, DatabaseAction,
+    DocumentAction, PubSubAction,
+};
+use bonsaidb_core::pubsub::{
+    database_topic, document_changes_topic_collection, Receiver, TopicPattern,
+};
 
-use crate::storage::SessionSubscriber;
+use crate::storage::{SessionSubscriber, SessionSubscribers};
 use crate::{Database, Subscriber};
 
 impl crate::storage::StorageInstance {
@@ -14,7 +23,9 @@ impl crate::storage::StorageInstance {
     ) -> Subscriber {
         let subscriber = self.relay().create_subscriber();
         let mut data = self.data.subscribers.write();
-        let receiver = Receiver::new_stripping_prefixes(subscriber.receiver().clone());
+        let (retained_sender, retained_receiver) = flume::unbounded();
+        let receiver =
+            Receiver::new_stripping_prefixes(subscriber.receiver().clone(), retained_receiver);
         let id = loop {
             data.last_id = data.last_id.wrapping_add(1);
             let id = data.last_id;
@@ -22,7 +33,11 @@ impl crate::storage::StorageInstance {
             if matches!(entry, Entry::Vacant(_)) {
                 entry.or_insert(SessionSubscriber {
                     session_id,
+                    session: database.session().cloned(),
+                    database: database.name().to_string(),
                     subscriber: subscriber.clone(),
+                    topics: HashSet::new(),
+                    patterns: HashSet::new(),
                 });
                 break id;
             }
@@ -33,6 +48,7 @@ impl crate::storage::StorageInstance {
             database,
             subscriber,
             receiver,
+            retained: retained_sender,
         }
     }
 
@@ -40,4 +56,201 @@ impl crate::storage::StorageInstance {
         let mut data = self.data.subscribers.write();
         data.unregister(subscriber.id);
     }
+
+    pub(crate) fn subscribe_to_topic(&self, subscriber: &Subscriber, topic: Vec<u8>) {
+        let mut data = self.data.subscribers.write();
+        let namespaced_topic = database_topic(subscriber.database.name(), &topic);
+        // The retained message is delivered before subscribing, ensuring it is
+        // queued ahead of any newer message published to the topic.
+        if let Some(payload) = data.retained.get(&namespaced_topic) {
+            // The receiver disconnecting is not an error.
+            drop(subscriber.retained.send(Message {
+                topic: OwnedBytes::from(namespaced_topic.clone()),
+                payload: payload.clone(),
+            }));
+        }
+        subscriber.subscriber.subscribe_to_raw(namespaced_topic);
+        if let Some(registered) = data.subscribers.get_mut(&subscriber.id) {
+            registered.topics.insert(topic);
+        }
+    }
+
+    pub(crate) fn unsubscribe_from_topic(&self, subscriber: &Subscriber, topic: &[u8]) {
+        let mut data = self.data.subscribers.write();
+        subscriber
+            .subscriber
+            .unsubscribe_from_raw(&database_topic(subscriber.database.name(), topic));
+        if let Some(registered) = data.subscribers.get_mut(&subscriber.id) {
+            registered.topics.remove(topic);
+        }
+    }
+
+    pub(crate) fn subscribe_to_pattern(&self, subscriber: &Subscriber, pattern: TopicPattern) {
+        let mut data = self.data.subscribers.write();
+        let database = subscriber.database.name();
+        let session = data
+            .subscribers
+            .get(&subscriber.id)
+            .and_then(|registered| registered.session.as_ref());
+        for (namespaced_topic, payload) in &data.retained {
+            let matches = namespaced_topic
+                .strip_prefix(database.as_bytes())
+                .and_then(|topic| topic.strip_prefix(b"\0"))
+                .map_or(false, |topic| {
+                    pattern.matches_bytes(topic) && allowed_to_receive(session, database, topic)
+                });
+            if matches {
+                drop(subscriber.retained.send(Message {
+                    topic: OwnedBytes::from(namespaced_topic.clone()),
+                    payload: payload.clone(),
+                }));
+            }
+        }
+        if let Some(registered) = data.subscribers.get_mut(&subscriber.id) {
+            registered.patterns.insert(pattern);
+            data.pattern_subscribers
+                .entry(database.to_string())
+                .or_default()
+                .insert(subscriber.id);
+        }
+    }
+
+    pub(crate) fn unsubscribe_from_pattern(&self, subscriber: &Subscriber, pattern: &TopicPattern) {
+        let mut data = self.data.subscribers.write();
+        if let Some(registered) = data.subscribers.get_mut(&subscriber.id) {
+            registered.patterns.remove(pattern);
+            if registered.patterns.is_empty() {
+                if let Some(pattern_subscribers) =
+                    data.pattern_subscribers.get_mut(subscriber.database.name())
+                {
+                    pattern_subscribers.remove(&subscriber.id);
+                }
+            }
+        }
+    }
+
+    /// Publishes `payload` to each of `topics` within `database`, delivering
+    /// it to subscribers whose patterns match as well as subscribers of the
+    /// exact topic.
+    pub(crate) fn publish_to_topics(&self, database: &str, topics: Vec<Vec<u8>>, payload: Vec<u8>) {
+        let data = self.data.subscribers.read();
+        self.publish_locked(&data, database, topics, payload);
+    }
+
+    /// Publishes `payload` to `topic` within `database`, returning the number
+    /// of subscribers the message was delivered to.
+    pub(crate) fn publish_and_count(
+        &self,
+        database: &str,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> u64 {
+        let data = self.data.subscribers.read();
+        let direct_recipients = data
+            .subscribers
+            .values()
+            .filter(|subscriber| {
+                subscriber.database == database && subscriber.topics.contains(&topic)
+            })
+            .count();
+        let recipients = direct_recipients + pattern_recipients(&data, database, &topic).len();
+        self.publish_locked(&data, database, vec![topic], payload);
+        recipients as u64
+    }
+
+    /// Publishes `payload` to `topic` within `database`, retaining it to be
+    /// delivered to future subscribers of `topic`. If `payload` is empty, the
+    /// retained message is cleared instead.
+    pub(crate) fn publish_retained(&self, database: &str, topic: Vec<u8>, payload: Vec<u8>) {
+        let mut data = self.data.subscribers.write();
+        let namespaced_topic = database_topic(database, &topic);
+        if payload.is_empty() {
+            data.retained.remove(&namespaced_topic);
+        } else {
+            data.retained
+                .insert(namespaced_topic, OwnedBytes::from(payload.clone()));
+            // Publishing while still holding the lock ensures a concurrent
+            // subscriber receives either the retained copy or the published
+            // message, but never both.
+            self.publish_locked(&data, database, vec![topic], payload);
+        }
+    }
+
+    fn publish_locked(
+        &self,
+        data: &SessionSubscribers,
+        database: &str,
+        topics: Vec<Vec<u8>>,
+        payload: Vec<u8>,
+    ) {
+        // The relay only routes exact topics, so any subscriber with a
+        // matching pattern is subscribed to the topic for the duration of the
+        // publish.
+        let mut pattern_subscriptions = Vec::new();
+        for topic in &topics {
+            for subscriber in pattern_recipients(data, database, topic) {
+                pattern_subscriptions
+                    .push((&subscriber.subscriber, database_topic(database, topic)));
+            }
+        }
+
+        let topics = topics
+            .iter()
+            .map(|topic| OwnedBytes::from(database_topic(database, topic)))
+            .collect::<Vec<_>>();
+        if pattern_subscriptions.is_empty() {
+            self.relay().publish_raw_to_all(topics, payload);
+        } else {
+            let _delivering = data.pattern_delivery.lock();
+            for (subscriber, topic) in &pattern_subscriptions {
+                subscriber.subscribe_to_raw(topic.clone());
+            }
+            self.relay().publish_raw_to_all(topics, payload);
+            for (subscriber, topic) in &pattern_subscriptions {
+                subscriber.unsubscribe_from_raw(topic);
+            }
+        }
+    }
+}
+
+/// Returns the subscribers of `database` with a pattern matching `topic` that
+/// are permitted to subscribe to `topic`. Subscribers that are already
+/// subscribed to the topic directly are skipped to avoid delivering the
+/// message twice.
+fn pattern_recipients<'a>(
+    data: &'a SessionSubscribers,
+    database: &str,
+    topic: &[u8],
+) -> Vec<&'a SessionSubscriber> {
+    let Some(ids) = data.pattern_subscribers.get(database) else {
+        return Vec::new();
+    };
+    ids.iter()
+        .filter_map(|id| data.subscribers.get(id))
+        .filter(|subscriber| {
+            !subscriber.topics.contains(topic)
+                && subscriber
+                    .patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_bytes(topic))
+                && allowed_to_receive(subscriber.session.as_ref(), database, topic)
+        })
+        .collect()
+}
+
+/// Returns true if `session` is permitted to subscribe to `topic`. Pattern
+/// subscriptions only check the pattern itself when subscribing, so each topic
+/// matched by a pattern is checked before its message is delivered.
+fn allowed_to_receive(session: Option<&Session>, database: &str, topic: &[u8]) -> bool {
+    session.map_or(true, |session| {
+        session.allowed_to(
+            pubsub_topic_resource_name(database, topic),
+            &BonsaiAction::Database(DatabaseAction::PubSub(PubSubAction::SubscribeTo)),
+        ) && document_changes_topic_collection(topic).map_or(true, |collection| {
+            session.allowed_to(
+                collection_resource_name(database, &collection),
+                &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::ListHeaders)),
+            )
+        })
+    })
 }
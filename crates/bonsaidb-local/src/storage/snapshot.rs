@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use bonsaidb_core::connection::{Connection, LowLevelConnection};
+use bonsaidb_core::document::{DocumentId, OwnedDocument};
+use bonsaidb_core::key::KeyEncoding;
+use bonsaidb_core::schema::{Collection, CollectionName};
+
+use crate::{Database, DatabaseNonBlocking, Error, Storage};
+
+/// The maximum number of attempts [`Storage::snapshot()`] makes to capture a
+/// consistent set of transaction ids before returning
+/// [`Error::SnapshotContention`].
+pub const MAX_SNAPSHOT_ATTEMPTS: usize = 16;
+
+/// A point-in-time view across one or more databases, created with
+/// [`Storage::snapshot()`].
+///
+/// ## Isolation
+///
+/// Nebari keeps a separate transaction log for each database, and it does not
+/// offer reads of previous versions of a tree. Instead of reading historical
+/// data, a snapshot records the last transaction id of each database at a
+/// single moment in time. Reads through a [`SnapshotDatabase`] read the
+/// current data and then verify using the transaction log that none of the
+/// documents read have been changed by a transaction committed after the
+/// snapshot was taken.
+///
+/// This provides snapshot isolation for document reads: every successful
+/// read returns exactly the data that was stored when the snapshot was taken,
+/// across all databases in the snapshot. If a document has since been
+/// changed, [`Error::SnapshotInvalidated`] is returned rather than a mix of
+/// states. Key-value entries and views are not covered by snapshots.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    databases: HashMap<String, SnapshotDatabase>,
+}
+
+impl Snapshot {
+    /// Returns a handle for reading `name` as of this snapshot, or `None` if
+    /// the database wasn't included when the snapshot was taken.
+    #[must_use]
+    pub fn database(&self, name: &str) -> Option<&SnapshotDatabase> {
+        self.databases.get(name)
+    }
+
+    /// Returns the last transaction id of the database `name` when this
+    /// snapshot was taken. The outer option is `None` if the database isn't
+    /// part of this snapshot.
+    #[must_use]
+    pub fn transaction_id(&self, name: &str) -> Option<Option<u64>> {
+        self.databases
+            .get(name)
+            .map(SnapshotDatabase::transaction_id)
+    }
+}
+
+/// A database as of a [`Snapshot`].
+#[derive(Debug, Clone)]
+pub struct SnapshotDatabase {
+    database: Database,
+    transaction_id: Option<u64>,
+}
+
+impl SnapshotDatabase {
+    /// Returns the last transaction id of this database when the snapshot was
+    /// taken.
+    #[must_use]
+    pub const fn transaction_id(&self) -> Option<u64> {
+        self.transaction_id
+    }
+
+    /// Retrieves the document with `id` from collection `C` as it was stored
+    /// when the snapshot was taken.
+    pub fn get<C, PrimaryKey>(&self, id: &PrimaryKey) -> Result<Option<OwnedDocument>, Error>
+    where
+        C: Collection,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + ?Sized,
+    {
+        let id = DocumentId::new(id)?;
+        let collection = C::collection_name();
+        let document = self.database.get_from_collection(id.clone(), &collection)?;
+        self.verify_unchanged(&collection, &[id])?;
+        Ok(document)
+    }
+
+    /// Retrieves all documents matching `ids` from collection `C` as they were
+    /// stored when the snapshot was taken. Documents that are not found are
+    /// not returned, but no error will be generated.
+    pub fn get_multiple<'id, C, PrimaryKey, DocumentIds>(
+        &self,
+        ids: DocumentIds,
+    ) -> Result<Vec<OwnedDocument>, Error>
+    where
+        C: Collection,
+        DocumentIds: IntoIterator<Item = &'id PrimaryKey>,
+        PrimaryKey: KeyEncoding<C::PrimaryKey> + 'id + ?Sized,
+    {
+        let ids = ids
+            .into_iter()
+            .map(|id| DocumentId::new(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let collection = C::collection_name();
+        let documents = self
+            .database
+            .get_multiple_from_collection(&ids, &collection)?;
+        self.verify_unchanged(&collection, &ids)?;
+        Ok(documents)
+    }
+
+    /// Returns an error if any transaction committed after the snapshot was
+    /// taken changed a document in `collection` with one of `ids`.
+    fn verify_unchanged(
+        &self,
+        collection: &CollectionName,
+        ids: &[DocumentId],
+    ) -> Result<(), Error> {
+        let mut starting_id = self.transaction_id.map_or(0, |id| id + 1);
+        loop {
+            let transactions = self
+                .database
+                .list_executed_transactions(Some(starting_id), None)?;
+            let Some(last) = transactions.last() else {
+                return Ok(());
+            };
+            starting_id = last.id + 1;
+
+            for transaction in &transactions {
+                let Some(changes) = transaction.changes.documents() else {
+                    continue;
+                };
+                let changed = changes.documents.iter().any(|changed| {
+                    &changes.collections[usize::from(changed.collection)] == collection
+                        && ids.contains(&changed.id)
+                });
+                if changed {
+                    return Err(Error::SnapshotInvalidated(self.database.name().to_string()));
+                }
+            }
+        }
+    }
+}
+
+impl Storage {
+    /// Captures a [`Snapshot`] of the databases named `databases`. See
+    /// [`Snapshot`] for the isolation guarantees of reads made through it.
+    ///
+    /// The transaction ids of the databases are read repeatedly until two
+    /// consecutive passes agree, which guarantees that the captured ids
+    /// existed together at a single moment. If the databases are modified
+    /// during each of [`MAX_SNAPSHOT_ATTEMPTS`] attempts,
+    /// [`Error::SnapshotContention`] is returned.
+    pub fn snapshot<'a>(
+        &self,
+        databases: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Snapshot, Error> {
+        let databases = databases
+            .into_iter()
+            .map(|name| {
+                self.instance
+                    .database_without_schema(name, Some(self), None)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut transaction_ids = last_transaction_ids(&databases)?;
+        for _ in 0..MAX_SNAPSHOT_ATTEMPTS {
+            let current = last_transaction_ids(&databases)?;
+            if current == transaction_ids {
+                return Ok(Snapshot {
+                    databases: databases
+                        .into_iter()
+                        .zip(transaction_ids)
+                        .map(|(database, transaction_id)| {
+                            (
+                                database.name().to_string(),
+                                SnapshotDatabase {
+                                    database,
+                                    transaction_id,
+                                },
+                            )
+                        })
+                        .collect(),
+                });
+            }
+            transaction_ids = current;
+        }
+
+        Err(Error::SnapshotContention)
+    }
+}
+
+fn last_transaction_ids(databases: &[Database]) -> Result<Vec<Option<u64>>, Error> {
+    databases
+        .iter()
+        .map(|database| database.last_transaction_id().map_err(Error::from))
+        .collect()
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bonsaidb_core::connection::Connection;
 use bonsaidb_core::keyvalue::Timestamp;
@@ -42,6 +43,7 @@ pub struct Statuses {
     completed_integrity_checks: HashSet<ViewKey>,
     key_value_expiration_loads: HashSet<Arc<Cow<'static, str>>>,
     view_update_last_status: HashMap<ViewKey, u64>,
+    view_update_last_started: HashMap<ViewKey, Instant>,
 }
 
 impl TaskManager {
@@ -52,55 +54,79 @@ impl TaskManager {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self, view, database),
+        fields(
+            database = %database.data.name,
+            view.collection.name = view.collection().name.as_ref(),
+            view.collection.authority = view.collection().authority.as_ref(),
+            view.name = view.view_name().name.as_ref(),
+        )
+    ))]
     pub fn update_view_if_needed(
         &self,
         view: &dyn view::Serialized,
         database: &Database,
         block_until_updated: bool,
     ) -> Result<(), crate::Error> {
-        let view_name = view.view_name();
         if let Some(job) = self.spawn_integrity_check(view, database) {
             job.receive()??;
         }
 
         // If there is no transaction id, there is no data, so the view is "up-to-date"
         if let Some(current_transaction_id) = database.last_transaction_id()? {
-            let needs_reindex = {
-                // When views finish updating, they store the last transaction_id
-                // they mapped. If that value is current, we don't need to go
-                // through the jobs system at all.
-                let statuses = self.statuses.read();
-                if let Some(last_transaction_indexed) = statuses.view_update_last_status.get(&(
-                    database.data.name.clone(),
-                    view.collection(),
-                    view.view_name(),
-                )) {
-                    last_transaction_indexed < &current_transaction_id
-                } else {
-                    true
+            self.update_view_through(view, database, current_transaction_id, block_until_updated)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ensures `view` has mapped all changes made through the transaction
+    /// `transaction_id`. If `block_until_updated` is true, this function
+    /// returns once the view has been updated.
+    pub fn update_view_through(
+        &self,
+        view: &dyn view::Serialized,
+        database: &Database,
+        transaction_id: u64,
+        block_until_updated: bool,
+    ) -> Result<(), crate::Error> {
+        let view_name = view.view_name();
+        let needs_reindex = {
+            // When views finish updating, they store the last transaction_id
+            // they mapped. If that value is current, we don't need to go
+            // through the jobs system at all.
+            let statuses = self.statuses.read();
+            if let Some(last_transaction_indexed) = statuses.view_update_last_status.get(&(
+                database.data.name.clone(),
+                view.collection(),
+                view.view_name(),
+            )) {
+                last_transaction_indexed < &transaction_id
+            } else {
+                true
+            }
+        };
+
+        if needs_reindex {
+            loop {
+                let job = self.jobs.lookup_or_enqueue(Mapper {
+                    database: database.clone(),
+                    map: Map {
+                        database: database.data.name.clone(),
+                        collection: view.collection(),
+                        view_name: view_name.clone(),
+                    },
+                });
+
+                if !block_until_updated {
+                    break;
                 }
-            };
-
-            if needs_reindex {
-                let wait_for_transaction = current_transaction_id;
-                loop {
-                    let job = self.jobs.lookup_or_enqueue(Mapper {
-                        database: database.clone(),
-                        map: Map {
-                            database: database.data.name.clone(),
-                            collection: view.collection(),
-                            view_name: view_name.clone(),
-                        },
-                    });
-
-                    if !block_until_updated {
-                        break;
-                    }
-
-                    let id = job.receive()??;
-                    if wait_for_transaction <= id {
-                        break;
-                    }
+
+                let id = job.receive()??;
+                if transaction_id <= id {
+                    break;
                 }
             }
         }
@@ -168,17 +194,42 @@ impl TaskManager {
         statuses.key_value_expiration_loads.insert(database);
     }
 
+    /// Records that the view has been updated through `transaction_id` by an
+    /// update that began at `started_at`.
     pub fn mark_view_updated(
         &self,
         database: Arc<Cow<'static, str>>,
         collection: CollectionName,
         view_name: ViewName,
         transaction_id: u64,
+        started_at: Instant,
     ) {
+        let key = (database, collection, view_name);
         let mut statuses = self.statuses.write();
         statuses
-            .view_update_last_status
-            .insert((database, collection, view_name), transaction_id);
+            .view_update_last_started
+            .insert(key.clone(), started_at);
+        statuses.view_update_last_status.insert(key, transaction_id);
+    }
+
+    /// Returns true if the last completed update of `view` began within
+    /// `duration`. Changes made after an update begins may not be included in
+    /// the update, so its start is used rather than its completion.
+    pub fn view_updated_within(
+        &self,
+        view: &dyn view::Serialized,
+        database: &Database,
+        duration: Duration,
+    ) -> bool {
+        let statuses = self.statuses.read();
+        statuses
+            .view_update_last_started
+            .get(&(
+                database.data.name.clone(),
+                view.collection(),
+                view.view_name(),
+            ))
+            .map_or(false, |started| started.elapsed() <= duration)
     }
 
     pub fn spawn_key_value_expiration_loader(
@@ -222,6 +273,13 @@ impl TaskManager {
             .receive()??)
     }
 
+    pub fn compact_views(&self, database: Database) -> Result<(), Error> {
+        Ok(self
+            .jobs
+            .lookup_or_enqueue(Compactor::views(database))
+            .receive()??)
+    }
+
     pub fn compact_database(&self, database: Database) -> Result<(), Error> {
         Ok(self
             .jobs
@@ -5,10 +5,11 @@ use bonsaidb_core::schema::CollectionName;
 use nebari::tree::{Root, Unversioned, Versioned};
 
 use crate::database::keyvalue::KEY_TREE;
-use crate::database::{document_tree_name, DatabaseNonBlocking};
+use crate::database::{document_tree_name, history_tree_name, DatabaseNonBlocking};
 use crate::tasks::{Job, Keyed, Task};
 use crate::views::{
     view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
+    view_related_document_map_tree_name, view_related_invalidated_docs_tree_name,
     view_versions_tree_name,
 };
 use crate::{Database, Error};
@@ -41,6 +42,10 @@ impl Compactor {
     pub fn keyvalue(database: Database) -> Self {
         Self::target(database, Target::KeyValue)
     }
+
+    pub fn views(database: Database) -> Self {
+        Self::target(database, Target::Views)
+    }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -55,6 +60,7 @@ pub enum Target {
     UnversionedTree(String),
     Collection(CollectionName),
     KeyValue,
+    Views,
     Database,
 }
 
@@ -69,6 +75,13 @@ impl Target {
                 compact_trees(database, trees)
             }
             Target::KeyValue => compact_tree::<Unversioned, _>(database, KEY_TREE),
+            Target::Views => {
+                let mut trees = Vec::new();
+                for collection in database.schematic().collections() {
+                    gather_view_trees(database, collection, &mut trees);
+                }
+                compact_trees(database, trees)
+            }
             Target::Database => {
                 let mut trees = Vec::new();
                 for collection in database.schematic().collections() {
@@ -104,7 +117,14 @@ fn gather_collection_trees(
 ) {
     trees.push(Target::VersionedTree(document_tree_name(collection)));
     trees.push(Target::UnversionedTree(view_versions_tree_name(collection)));
+    if database.data.schema.history_limit(collection) > 0 {
+        trees.push(Target::UnversionedTree(history_tree_name(collection)));
+    }
+
+    gather_view_trees(database, collection, trees);
+}
 
+fn gather_view_trees(database: &Database, collection: &CollectionName, trees: &mut Vec<Target>) {
     for view in database.data.schema.views_in_collection(collection) {
         let name = view.view_name();
         trees.push(Target::UnversionedTree(view_entries_tree_name(&name)));
@@ -113,6 +133,16 @@ fn gather_collection_trees(
             &name,
         )));
     }
+
+    for view in database.data.schema.views_related_to_collection(collection) {
+        let name = view.view_name();
+        trees.push(Target::UnversionedTree(
+            view_related_document_map_tree_name(&name, collection),
+        ));
+        trees.push(Target::UnversionedTree(
+            view_related_invalidated_docs_tree_name(&name, collection),
+        ));
+    }
 }
 
 fn compact_trees(database: &Database, targets: Vec<Target>) -> Result<(), Error> {
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bonsaidb_core::connection::StorageConnection;
+use bonsaidb_core::schema::Schema;
+use parking_lot::Mutex;
+
+use crate::{Database, Storage};
+
+/// Manages a separate database for each tenant of a multi-tenant application.
+///
+/// Each tenant id is mapped to a database name using
+/// [`database_name()`](Self::database_name). The tenant's database is created
+/// the first time it is accessed, and handles to open databases are cached
+/// until they are evicted.
+///
+/// ```rust
+/// # use bonsaidb_core::schema::Schema;
+/// use bonsaidb_local::config::{Builder, StorageConfiguration};
+/// use bonsaidb_local::{DatabaseNonBlocking, Storage, TenantManager};
+/// # fn open<MySchema: Schema>() -> anyhow::Result<()> {
+/// let storage =
+///     Storage::open(StorageConfiguration::new("my-db.bonsaidb").with_schema::<MySchema>()?)?;
+/// let tenants = TenantManager::<MySchema>::new(storage);
+/// let acme = tenants.tenant("Acme Corp")?;
+/// assert_eq!(acme.name(), "tenant-.41cme.20.43orp");
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TenantManager<DB> {
+    storage: Storage,
+    prefix: String,
+    open: Arc<Mutex<HashMap<String, OpenTenant>>>,
+    _schema: PhantomData<DB>,
+}
+
+impl<DB> Clone for TenantManager<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            prefix: self.prefix.clone(),
+            open: self.open.clone(),
+            _schema: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OpenTenant {
+    database: Database,
+    last_accessed: Instant,
+}
+
+impl<DB: Schema> TenantManager<DB> {
+    /// Returns a manager that stores each tenant's database in `storage`,
+    /// naming each database with the prefix `tenant-`.
+    #[must_use]
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            prefix: String::from("tenant-"),
+            open: Arc::default(),
+            _schema: PhantomData,
+        }
+    }
+
+    /// Returns a manager that stores each tenant's database in `storage`,
+    /// naming each database with `prefix`. The prefix must begin with an
+    /// alphanumeric character, and all remaining characters must be
+    /// alphanumeric, a period (`.`), or a hyphen (`-`).
+    ///
+    /// ## Errors
+    ///
+    /// * [`bonsaidb_core::Error::InvalidDatabaseName`]: `prefix` is empty or
+    ///   contains characters that aren't allowed in database names.
+    pub fn with_prefix(
+        storage: Storage,
+        prefix: impl Into<String>,
+    ) -> Result<Self, bonsaidb_core::Error> {
+        let prefix = prefix.into();
+        if prefix.is_empty() {
+            return Err(bonsaidb_core::Error::InvalidDatabaseName(prefix));
+        }
+        Storage::validate_name(&prefix)?;
+
+        Ok(Self {
+            prefix,
+            ..Self::new(storage)
+        })
+    }
+
+    /// Returns the name of the database used for `tenant_id`.
+    ///
+    /// Lowercase ASCII letters, digits, and hyphens are kept as-is. Every
+    /// other byte is written as a period followed by two hexadecimal digits.
+    /// This ensures that each tenant id maps to a unique, valid database name,
+    /// even on case-insensitive file systems.
+    #[must_use]
+    pub fn database_name(&self, tenant_id: &str) -> String {
+        let mut name = self.prefix.clone();
+        for byte in tenant_id.bytes() {
+            if byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' {
+                name.push(char::from(byte));
+            } else {
+                write!(name, ".{byte:02x}").expect("writing to a string cannot fail");
+            }
+        }
+        name
+    }
+
+    /// Returns the database for `tenant_id`, creating it if it does not exist.
+    pub fn tenant(&self, tenant_id: &str) -> Result<Database, bonsaidb_core::Error> {
+        let name = self.database_name(tenant_id);
+        if let Some(tenant) = self.open.lock().get_mut(&name) {
+            tenant.last_accessed = Instant::now();
+            return Ok(tenant.database.clone());
+        }
+
+        // The lock isn't held while the database is created, allowing other
+        // tenants to be accessed in the meantime. If another thread opened
+        // this tenant concurrently, its handle is kept.
+        let database = self.storage.create_database::<DB>(&name, true)?;
+        let mut open = self.open.lock();
+        let tenant = open.entry(name).or_insert(OpenTenant {
+            database,
+            last_accessed: Instant::now(),
+        });
+        tenant.last_accessed = Instant::now();
+        Ok(tenant.database.clone())
+    }
+
+    /// Deletes the database for `tenant_id`, including all of its data.
+    pub fn delete_tenant(&self, tenant_id: &str) -> Result<(), bonsaidb_core::Error> {
+        let name = self.database_name(tenant_id);
+        // The lock is held until the database is deleted so that the tenant
+        // can't be accessed through the cache while it is being deleted.
+        let mut open = self.open.lock();
+        open.remove(&name);
+        self.storage.delete_database(&name)
+    }
+
+    /// Releases the cached handle to the database for `tenant_id`. Returns
+    /// true if the tenant's database was open.
+    pub fn evict(&self, tenant_id: &str) -> bool {
+        let name = self.database_name(tenant_id);
+        let evicted = self.open.lock().remove(&name).is_some();
+        if evicted {
+            self.storage.instance.close_database_if_unused(&name);
+        }
+        evicted
+    }
+
+    /// Releases the cached handles to every tenant database that has not been
+    /// accessed through this manager within `idle_for`. Returns the number of
+    /// tenants evicted.
+    ///
+    /// The underlying database files are only closed once no other handles to
+    /// the database remain.
+    pub fn evict_idle(&self, idle_for: Duration) -> usize {
+        let mut evicted = Vec::new();
+        self.open.lock().retain(|name, tenant| {
+            let is_active = tenant.last_accessed.elapsed() < idle_for;
+            if !is_active {
+                evicted.push(name.clone());
+            }
+            is_active
+        });
+
+        for name in &evicted {
+            self.storage.instance.close_database_if_unused(name);
+        }
+
+        evicted.len()
+    }
+
+    /// Returns the number of tenant databases currently cached.
+    #[must_use]
+    pub fn open_tenants(&self) -> usize {
+        self.open.lock().len()
+    }
+}
@@ -7,8 +7,9 @@ use bonsaidb_core::permissions::{Permissions, Statement};
 #[cfg(feature = "encryption")]
 use bonsaidb_core::test_util::EncryptedBasic;
 use bonsaidb_core::test_util::{
-    Basic, BasicByBrokenParentId, BasicByParentId, BasicCollectionWithNoViews,
-    BasicCollectionWithOnlyBrokenParentId, BasicSchema, HarnessTest, TestDirectory,
+    Basic, BasicByBrokenParentId, BasicByParentId, BasicByParentIdEager,
+    BasicCollectionWithNoViews, BasicCollectionWithOnlyBrokenParentId, BasicSchema, HarnessTest,
+    TestDirectory,
 };
 
 use crate::config::{Builder, StorageConfiguration};
@@ -224,6 +225,1638 @@ fn integrity_checks() -> anyhow::Result<()> {
     unreachable!("Integrity checker didn't run in the allocated time")
 }
 
+#[test]
+#[cfg_attr(not(feature = "compression"), allow(unused_mut))]
+fn stale_mapping_repair() -> anyhow::Result<()> {
+    let path = TestDirectory::new("stale-mapping-repair");
+    let mut config = StorageConfiguration::new(&path);
+    #[cfg(feature = "compression")]
+    {
+        config = config.default_compression(crate::config::Compression::Lz4);
+    }
+
+    // Add a doc and ensure the view has mapped it.
+    let header = {
+        let db = Database::open::<Basic>(config.clone())?;
+        let header = db
+            .collection::<Basic>()
+            .push(&Basic::default().with_parent_id(1))?;
+        let mappings = db.view::<BasicByParentId>().with_key(&Some(1)).query()?;
+        assert_eq!(mappings.len(), 1);
+        header
+    };
+    // Delete the doc without the view installed, leaving a stale mapping.
+    {
+        let db = Database::open::<BasicCollectionWithNoViews>(config.clone())?;
+        db.collection::<BasicCollectionWithNoViews>()
+            .delete(&header)?;
+    }
+
+    let db = Database::open::<Basic>(config.repair_stale_view_mappings(true))?;
+    let stale = db
+        .view::<BasicByParentId>()
+        .with_key(&Some(1))
+        .query_with_docs()?;
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale.missing_documents().len(), 1);
+
+    // The query should have queued the document to be re-mapped.
+    for _ in 0_u8..100 {
+        if db
+            .view::<BasicByParentId>()
+            .with_access_policy(AccessPolicy::NoUpdate)
+            .with_key(&Some(1))
+            .query()?
+            .is_empty()
+        {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    unreachable!("Stale mapping wasn't removed in the allocated time")
+}
+
+#[test]
+fn bounded_staleness_queries() -> anyhow::Result<()> {
+    let path = TestDirectory::new("bounded-staleness-queries");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+
+    // The view has never been updated, so the first query must wait.
+    let recent = AccessPolicy::UpdateAfterWithTimeout(Duration::from_secs(3600));
+    let mappings = db
+        .view::<BasicByParentId>()
+        .with_access_policy(recent)
+        .query()?;
+    assert_eq!(mappings.len(), 1);
+
+    // The view was just updated, so stale results are returned immediately.
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+    let mappings = db
+        .view::<BasicByParentId>()
+        .with_access_policy(recent)
+        .query()?;
+    assert_eq!(mappings.len(), 1);
+
+    // A timeout of zero always waits for the view to be updated.
+    let mappings = db
+        .view::<BasicByParentId>()
+        .with_access_policy(AccessPolicy::UpdateAfterWithTimeout(Duration::ZERO))
+        .query()?;
+    assert_eq!(mappings.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn update_views_on_write() -> anyhow::Result<()> {
+    let path = TestDirectory::new("update-views-on-write");
+    let config = StorageConfiguration::new(&path).update_views_on_write(true);
+    let db = Database::open::<Basic>(config)?;
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+
+    // The lazy view was updated before the write returned.
+    let mappings = db
+        .view::<BasicByParentId>()
+        .with_access_policy(AccessPolicy::NoUpdate)
+        .query()?;
+    assert_eq!(mappings.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn wait_for_view() -> anyhow::Result<()> {
+    let path = TestDirectory::new("wait-for-view");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+    let transaction_id = db.last_transaction_id()?.expect("no transaction");
+
+    db.wait_for_view::<BasicByParentId>(transaction_id)?;
+    let mappings = db
+        .view::<BasicByParentId>()
+        .with_access_policy(AccessPolicy::NoUpdate)
+        .query()?;
+    assert_eq!(mappings.len(), 1);
+
+    // Waiting for a transaction that hasn't happened yet returns once the view
+    // is current.
+    db.wait_for_view::<BasicByParentId>(transaction_id + 1)?;
+
+    Ok(())
+}
+
+#[test]
+fn view_verification() -> anyhow::Result<()> {
+    let path = TestDirectory::new("view-verification");
+    let config = StorageConfiguration::new(&path);
+
+    let header = {
+        let db = Database::open::<Basic>(config.clone())?;
+        let header = db
+            .collection::<Basic>()
+            .push(&Basic::default().with_parent_id(1))?;
+        db.collection::<Basic>()
+            .push(&Basic::default().with_parent_id(2))?;
+        let mappings = db.view::<BasicByParentId>().query()?;
+        assert_eq!(mappings.len(), 2);
+
+        let report = db.verify_view::<BasicByParentId>()?;
+        assert!(report.is_consistent());
+        assert_eq!(report.documents_checked, 2);
+        header
+    };
+    // Delete a doc without the view installed, leaving a stale mapping.
+    {
+        let db = Database::open::<BasicCollectionWithNoViews>(config.clone())?;
+        db.collection::<BasicCollectionWithNoViews>()
+            .delete(&header)?;
+    }
+
+    let db = Database::open::<Basic>(config)?;
+    let report = db.verify_view::<BasicByParentId>()?;
+    assert!(!report.is_consistent());
+    assert_eq!(report.documents_checked, 1);
+    assert!(report.missing.is_empty());
+    assert!(report.mismatched.is_empty());
+    assert_eq!(report.extra.len(), 1);
+    assert_eq!(report.extra[0].source, header.id);
+
+    Ok(())
+}
+
+#[test]
+fn cached_reductions() -> anyhow::Result<()> {
+    let path = TestDirectory::new("cached-reductions");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(2))?;
+
+    // The second reduction of each query is served from the cache.
+    for _ in 0_u8..2 {
+        assert_eq!(db.view::<BasicByParentId>().reduce()?, 2);
+        assert_eq!(
+            db.view::<BasicByParentId>()
+                .with_key_range(Some(1)..Some(2))
+                .reduce()?,
+            1
+        );
+        assert_eq!(db.view::<BasicByParentIdEager>().reduce()?, 2);
+    }
+
+    // Updating the views must discard the cached values.
+    db.collection::<Basic>()
+        .push(&Basic::default().with_parent_id(1))?;
+    assert_eq!(
+        db.view::<BasicByParentIdEager>()
+            .with_access_policy(AccessPolicy::NoUpdate)
+            .reduce()?,
+        3
+    );
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 3);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key_range(Some(1)..Some(2))
+            .reduce()?,
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn warm_up() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::Collection;
+
+    let path = TestDirectory::new("warm-up");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    for parent_id in 0..10 {
+        db.collection::<Basic>()
+            .push(&Basic::default().with_parent_id(parent_id))?;
+    }
+    db.view::<BasicByParentId>().query()?;
+
+    let collections = [Basic::collection_name()];
+    let everything = db.warm_up(&collections, u64::MAX)?;
+    assert!(everything > 0);
+    // A budget stops reading after the first entry that exceeds it.
+    let limited = db.warm_up(&collections, 1)?;
+    assert!(limited > 0 && limited < everything);
+
+    Ok(())
+}
+
+#[test]
+fn database_handles_share_data() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+
+    let path = TestDirectory::new("database-handles-share-data");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    storage.create_database::<BasicSchema>("tests", false)?;
+    let first = storage.database::<BasicSchema>("tests")?;
+    let second = storage.database::<BasicSchema>("tests")?;
+    assert!(std::sync::Arc::ptr_eq(&first.data, &second.data));
+    let restricted = second
+        .with_effective_permissions(Permissions::default())
+        .unwrap();
+    assert!(std::sync::Arc::ptr_eq(&first.data, &restricted.data));
+
+    // Deleting the database must not allow a recreated database to reuse the
+    // previous instance.
+    storage.delete_database("tests")?;
+    storage.create_database::<BasicSchema>("tests", false)?;
+    let recreated = storage.database::<BasicSchema>("tests")?;
+    assert!(!std::sync::Arc::ptr_eq(&first.data, &recreated.data));
+
+    Ok(())
+}
+
+#[test]
+fn unused_databases_are_evicted() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+
+    let path = TestDirectory::new("unused-databases-are-evicted");
+    let storage = Storage::open(
+        StorageConfiguration::new(&path)
+            .with_schema::<BasicSchema>()?
+            .database_cache_capacity(1),
+    )?;
+    storage.create_database::<BasicSchema>("first", false)?;
+    storage.create_database::<BasicSchema>("second", false)?;
+
+    let first = storage.database::<BasicSchema>("first")?;
+    let unused = std::sync::Arc::downgrade(&first.data);
+    drop(first);
+    // Opening another database exceeds the capacity, closing the first
+    // database because no handles to it remain.
+    let second = storage.database::<BasicSchema>("second")?;
+    assert!(unused.upgrade().is_none());
+
+    // Databases that are still in use are kept.
+    let _first = storage.database::<BasicSchema>("first")?;
+    assert!(std::sync::Arc::ptr_eq(
+        &second.data,
+        &storage.database::<BasicSchema>("second")?.data
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn deleting_database_clears_retained_messages() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::pubsub::{PubSub, Subscriber};
+
+    let path = TestDirectory::new("deleting-database-clears-retained");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    let db = storage.create_database::<BasicSchema>("retained", false)?;
+    db.publish_retained(&"status", &String::from("online"))?;
+    drop(db);
+
+    storage.delete_database("retained")?;
+    let db = storage.create_database::<BasicSchema>("retained", false)?;
+    let subscriber = db.create_subscriber()?;
+    subscriber.subscribe_to(&"status")?;
+    assert!(subscriber.receiver().try_receive().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn rename_database() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("rename-database");
+    let header = {
+        let storage =
+            Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+        let db = storage.create_database::<BasicSchema>("original", false)?;
+        let header = db.collection::<Basic>().push(&Basic::new("hello"))?;
+        db.set_numeric_key("key", 1_u64).execute()?;
+
+        // The database can't be renamed while a handle to it is alive.
+        assert!(matches!(
+            storage.rename_database("original", "renamed"),
+            Err(bonsaidb_core::Error::Other { .. })
+        ));
+        assert!(storage.database::<BasicSchema>("original").is_ok());
+        drop(db);
+
+        storage.rename_database("original", "renamed")?;
+        assert!(matches!(
+            storage.database::<BasicSchema>("original"),
+            Err(bonsaidb_core::Error::DatabaseNotFound(_))
+        ));
+        let db = storage.database::<BasicSchema>("renamed")?;
+        let doc = Basic::get(&header.id, &db)?.expect("renamed document not found");
+        assert_eq!(doc.contents.value, "hello");
+        assert_eq!(db.get_key("key").into_u64()?, Some(1));
+
+        // The previous name is available for a new database.
+        let recreated = storage.create_database::<BasicSchema>("original", false)?;
+        assert!(Basic::get(&header.id, &recreated)?.is_none());
+
+        header
+    };
+
+    // The new name must be persisted in the admin database.
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    let databases = storage.list_databases()?;
+    assert!(databases.iter().any(|db| db.name == "renamed"));
+    assert!(databases.iter().any(|db| db.name == "original"));
+    let db = storage.database::<BasicSchema>("renamed")?;
+    assert!(Basic::get(&header.id, &db)?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn tenant_manager() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    use crate::{DatabaseNonBlocking, TenantManager};
+
+    let path = TestDirectory::new("tenant-manager");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    let tenants = TenantManager::<BasicSchema>::new(storage.clone());
+    assert_eq!(tenants.database_name("acme-1"), "tenant-acme-1");
+    assert_eq!(tenants.database_name("Acme.Corp"), "tenant-.41cme.2e.43orp");
+
+    // The database is created the first time the tenant is accessed.
+    let acme = tenants.tenant("Acme.Corp")?;
+    assert_eq!(acme.name(), "tenant-.41cme.2e.43orp");
+    let header = acme.collection::<Basic>().push(&Basic::new("hello"))?;
+    assert!(storage
+        .list_databases()?
+        .iter()
+        .any(|db| db.name == "tenant-.41cme.2e.43orp"));
+
+    // Subsequent accesses return the cached handle.
+    let cached = tenants.tenant("Acme.Corp")?;
+    assert!(std::sync::Arc::ptr_eq(&acme.data, &cached.data));
+    assert_eq!(tenants.open_tenants(), 1);
+
+    // Evicting an idle tenant reopens its database on the next access.
+    drop((acme, cached));
+    assert_eq!(tenants.evict_idle(Duration::from_secs(60)), 0);
+    assert_eq!(tenants.evict_idle(Duration::ZERO), 1);
+    assert_eq!(tenants.open_tenants(), 0);
+    let acme = tenants.tenant("Acme.Corp")?;
+    assert!(Basic::get(&header.id, &acme)?.is_some());
+    drop(acme);
+
+    tenants.delete_tenant("Acme.Corp")?;
+    assert_eq!(tenants.open_tenants(), 0);
+    assert!(matches!(
+        storage.database::<BasicSchema>("tenant-.41cme.2e.43orp"),
+        Err(bonsaidb_core::Error::DatabaseNotFound(_))
+    ));
+
+    // Prefixes must be valid database names.
+    let tenants = TenantManager::<BasicSchema>::with_prefix(storage.clone(), "org.")?;
+    assert_eq!(tenants.database_name("acme"), "org.acme");
+    for prefix in ["", ".org", "org/"] {
+        assert!(matches!(
+            TenantManager::<BasicSchema>::with_prefix(storage.clone(), prefix),
+            Err(bonsaidb_core::Error::InvalidDatabaseName(_))
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reclaim_space() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("reclaim-space");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    let headers = (0..100)
+        .map(|index| {
+            db.collection::<Basic>()
+                .push(&Basic::new(index.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for header in headers {
+        Basic::get(&header.id, &db)?
+            .expect("document not found")
+            .delete(&db)?;
+    }
+
+    assert!(db.reclaim_space()? > 0);
+
+    Ok(())
+}
+
+#[test]
+fn compact_views() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::test_util::BasicByCategory;
+
+    let path = TestDirectory::new("compact-views");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    let headers = (0..20)
+        .map(|index| {
+            db.collection::<Basic>()
+                .push(&Basic::new(index.to_string()).with_category("a"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Move every document between categories repeatedly, leaving the view's
+    // trees full of replaced mappings.
+    for category in ["b", "c", "d", "e"] {
+        for header in &headers {
+            let mut doc = Basic::get(&header.id, &db)?.expect("document not found");
+            doc.contents.category = Some(String::from(category));
+            doc.update(&db)?;
+        }
+        db.view::<BasicByCategory>().query()?;
+    }
+
+    db.compact_views()?;
+
+    let mappings = db.view::<BasicByCategory>().query()?;
+    assert_eq!(mappings.len(), headers.len());
+    assert!(mappings.iter().all(|mapping| mapping.key == "e"));
+    assert!(db
+        .view::<BasicByCategory>()
+        .with_key("a")
+        .query()?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn document_cache() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    use crate::DocumentCacheStatistics;
+
+    let path = TestDirectory::new("document-cache");
+    let db = Database::open::<BasicSchema>(
+        StorageConfiguration::new(&path).document_cache_capacity(10),
+    )?;
+    let header = db.collection::<Basic>().push(&Basic::new("a"))?;
+
+    let mut doc = Basic::get(&header.id, &db)?.expect("document not found");
+    assert_eq!(Basic::get(&header.id, &db)?.unwrap().contents, doc.contents);
+    assert_eq!(
+        db.storage().document_cache_statistics(),
+        DocumentCacheStatistics { hits: 1, misses: 1 }
+    );
+
+    // Updating the document removes it from the cache.
+    doc.contents.value = String::from("b");
+    doc.update(&db)?;
+    assert_eq!(Basic::get(&header.id, &db)?.unwrap().contents.value, "b");
+    assert_eq!(Basic::get(&header.id, &db)?.unwrap().contents.value, "b");
+    assert_eq!(
+        db.storage().document_cache_statistics(),
+        DocumentCacheStatistics { hits: 2, misses: 2 }
+    );
+
+    doc.delete(&db)?;
+    assert!(Basic::get(&header.id, &db)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn transaction_log_summary() -> anyhow::Result<()> {
+    let path = TestDirectory::new("transaction-log-summary");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>().push(&Basic::new("a"))?;
+    db.collection::<Basic>().push(&Basic::new("b"))?;
+
+    let summary = db.transaction_log_summary()?;
+    let last_id = db.last_transaction_id()?.expect("no transactions");
+    let (first, second) = match summary.as_slice() {
+        [.., first, second] => (first, second),
+        _ => unreachable!("expected at least two transactions"),
+    };
+    assert_eq!(second.0, last_id);
+    assert!(first.0 < second.0);
+    assert!(first.1 > 0);
+    assert!(second.1 > 0);
+
+    Ok(())
+}
+
+#[test]
+fn collection_stats() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::HasSchema;
+    use bonsaidb_core::schema::Collection;
+    use bonsaidb_core::test_util::Unique;
+
+    let path = TestDirectory::new("collection-stats");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>().push(&Basic::new("a"))?;
+    db.collection::<Basic>().push(&Basic::new("b"))?;
+    db.collection::<Unique>().push(&Unique::new("a"))?;
+
+    let stats = db.collection_stats()?;
+    let mut collections = db.schematic().collections().cloned().collect::<Vec<_>>();
+    collections.sort();
+    assert_eq!(
+        stats
+            .iter()
+            .map(|collection| collection.name.clone())
+            .collect::<Vec<_>>(),
+        collections
+    );
+    for collection in &stats {
+        let expected_documents = if collection.name == Basic::collection_name() {
+            2
+        } else if collection.name == Unique::collection_name() {
+            1
+        } else {
+            0
+        };
+        assert_eq!(collection.document_count, expected_documents);
+        assert_eq!(
+            collection.view_count,
+            db.schematic().views_in_collection(&collection.name).count()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn prune_orphaned_trees() -> anyhow::Result<()> {
+    use nebari::tree::Unversioned;
+
+    let path = TestDirectory::new("prune-orphaned-trees");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>().push(&Basic::new("a"))?;
+    let orphan = db
+        .roots()
+        .tree(Unversioned::tree("view.khonsulabs.removed-view"))?;
+    orphan.set(b"key", b"value")?;
+    let orphan_name = String::from("view.khonsulabs.removed-view");
+    let trees_before = db.roots().tree_names()?;
+    assert!(trees_before.contains(&orphan_name));
+
+    // A dry run reports the orphaned tree without deleting anything.
+    assert_eq!(db.prune_orphaned_trees(true)?, vec![orphan_name.clone()]);
+    assert!(db.roots().tree_names()?.contains(&orphan_name));
+
+    assert_eq!(db.prune_orphaned_trees(false)?, vec![orphan_name.clone()]);
+    let trees_after = db.roots().tree_names()?;
+    assert!(!trees_after.contains(&orphan_name));
+    assert_eq!(trees_after.len(), trees_before.len() - 1);
+    assert!(db.prune_orphaned_trees(false)?.is_empty());
+    assert_eq!(db.collection::<Basic>().all().count()?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn corrupt_document_lengths() -> anyhow::Result<()> {
+    use bonsaidb_core::arc_bytes::serde::Bytes;
+    use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
+
+    use crate::database::deserialize_document;
+
+    // A document's contents claiming to be longer than the entire stored
+    // document is rejected rather than allocated.
+    let document = pot::to_vec(&OwnedDocument {
+        header: Header {
+            id: DocumentId::from_u64(1),
+            revision: Revision::new(&[0; 200]),
+        },
+        contents: Bytes::from(vec![0; 200]),
+    })?;
+    assert!(deserialize_document(&document).is_ok());
+    assert!(deserialize_document(&document[..document.len() - 190]).is_err());
+
+    // The same applies to documents stored in the legacy format.
+    let mut legacy = Vec::new();
+    legacy.extend_from_slice(&1_u64.to_le_bytes());
+    legacy.extend_from_slice(&bincode::serialize(&Revision::new(&[]))?);
+    legacy.extend_from_slice(&u64::MAX.to_le_bytes());
+    assert!(deserialize_document(&legacy).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn cache_configuration() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("cache-configuration");
+    let config = StorageConfiguration::new(&path)
+        .cache_capacity(10)
+        .cache_max_chunk_size(1024);
+    assert_eq!(config.cache.capacity, 10);
+    assert_eq!(config.cache.max_chunk_size, 1024);
+
+    // Documents are read correctly whether or not they fit within the maximum
+    // chunk size.
+    let db = Database::open::<Basic>(config)?;
+    let small = Basic::new("small").push_into(&db)?;
+    let large = Basic::new("a".repeat(4096)).push_into(&db)?;
+    for _ in 0_u8..2 {
+        let stored = Basic::get(&small.header.id, &db)?.expect("document missing");
+        assert_eq!(stored.contents.value, "small");
+        let stored = Basic::get(&large.header.id, &db)?.expect("document missing");
+        assert_eq!(stored.contents.value.len(), 4096);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn transaction_validation() -> anyhow::Result<()> {
+    use std::sync::Mutex;
+
+    use bonsaidb_core::schema::{
+        Collection, CollectionName, DefaultSerialization, Qualified, Schematic,
+        SerializedCollection,
+    };
+    use bonsaidb_core::transaction::{Operation, ProposedChange, Transaction};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Account {
+        balance: i64,
+    }
+
+    // The ids of the deleted accounts passed to the validator.
+    static DELETED: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+    impl Collection for Account {
+        type PrimaryKey = u64;
+
+        fn collection_name() -> CollectionName {
+            CollectionName::private("accounts")
+        }
+
+        fn define_views(_schema: &mut Schematic) -> Result<(), bonsaidb_core::Error> {
+            Ok(())
+        }
+
+        fn validate_changes(changes: &[ProposedChange<'_>]) -> Result<(), bonsaidb_core::Error> {
+            for change in changes {
+                if change.is_deleted() {
+                    DELETED.lock().unwrap().push(change.primary_key::<Self>()?);
+                } else if let Some(account) = change.deserialized_contents::<Self>()? {
+                    if account.balance < 0 {
+                        return Err(bonsaidb_core::Error::other("accounts", "negative balance"));
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl DefaultSerialization for Account {}
+
+    let path = TestDirectory::new("transaction-validation");
+    let db = Database::open::<Account>(StorageConfiguration::new(&path))?;
+    let mut account = Account { balance: 10 }.push_into(&db)?;
+
+    account.contents.balance = -1;
+    assert!(matches!(
+        account.update(&db),
+        Err(bonsaidb_core::Error::Other { origin, .. }) if origin == "accounts"
+    ));
+
+    // A single invalid change aborts the entire transaction.
+    let result = Transaction::new()
+        .with(Operation::push_serialized::<Account>(&Account {
+            balance: 5,
+        })?)
+        .with(Operation::push_serialized::<Account>(&Account {
+            balance: -5,
+        })?)
+        .apply(&db);
+    assert!(result.is_err());
+    assert_eq!(Account::all(&db).count()?, 1);
+    let stored = Account::get(&account.header.id, &db)?.expect("account missing");
+    assert_eq!(stored.contents.balance, 10);
+
+    // Deletions are passed to the validator without contents.
+    assert!(DELETED.lock().unwrap().is_empty());
+    stored.delete(&db)?;
+    assert_eq!(*DELETED.lock().unwrap(), [stored.header.id]);
+    assert_eq!(Account::all(&db).count()?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn document_validation() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use bonsaidb_core::transaction::{Operation, Transaction};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "contacts", validate = validate_contact, core = bonsaidb_core)]
+    struct Contact {
+        email: String,
+    }
+
+    fn validate_contact(contact: &Contact) -> Result<(), String> {
+        if contact.email.contains('@') {
+            Ok(())
+        } else {
+            Err(format!("invalid email address: {}", contact.email))
+        }
+    }
+
+    let path = TestDirectory::new("document-validation");
+    let db = Database::open::<Contact>(StorageConfiguration::new(&path))?;
+    let mut contact = Contact {
+        email: String::from("ecton@example.com"),
+    }
+    .push_into(&db)?;
+
+    let rejected = Contact {
+        email: String::from("nobody"),
+    }
+    .push_into(&db)
+    .unwrap_err();
+    assert!(matches!(
+        rejected.error,
+        bonsaidb_core::Error::ValidationFailed { collection, details }
+            if collection == Contact::collection_name()
+                && details == "invalid email address: nobody"
+    ));
+
+    contact.contents.email = String::from("ecton");
+    assert!(matches!(
+        contact.update(&db),
+        Err(bonsaidb_core::Error::ValidationFailed { .. })
+    ));
+
+    // An invalid document aborts the entire transaction.
+    let result = Transaction::new()
+        .with(Operation::push_serialized::<Contact>(&Contact {
+            email: String::from("a@example.com"),
+        })?)
+        .with(Operation::push_serialized::<Contact>(&Contact {
+            email: String::from("b"),
+        })?)
+        .apply(&db);
+    assert!(result.is_err());
+    assert_eq!(Contact::all(&db).count()?, 1);
+    let stored = Contact::get(&contact.header.id, &db)?.expect("contact missing");
+    assert_eq!(stored.contents.email, "ecton@example.com");
+
+    Ok(())
+}
+
+#[test]
+fn delete_cascades() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{CollectionDocument, Emit, Header};
+    use bonsaidb_core::schema::view::map::Mappings;
+    use bonsaidb_core::schema::{
+        Collection, CollectionMapReduce, CollectionName, DefaultSerialization, Qualified,
+        ReduceResult, Schema, Schematic, SerializedCollection, View, ViewMapResult,
+        ViewMappedValue, ViewSchema,
+    };
+    use bonsaidb_core::transaction::{Operation, Transaction};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Schema)]
+    #[schema(name = "cascades", collections = [Folder, File], core = bonsaidb_core)]
+    struct CascadeSchema;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Folder {
+        parent: Option<u64>,
+    }
+
+    impl Collection for Folder {
+        type PrimaryKey = u64;
+
+        fn collection_name() -> CollectionName {
+            CollectionName::private("folders")
+        }
+
+        fn define_views(schema: &mut Schematic) -> Result<(), bonsaidb_core::Error> {
+            schema.define_view(FoldersByParent)?;
+            schema.define_delete_cascade::<Self, _>(&FoldersByParent)?;
+            schema.define_delete_cascade::<Self, _>(&FilesByFolder)
+        }
+    }
+
+    impl DefaultSerialization for Folder {}
+
+    #[derive(Debug, Clone, View, ViewSchema)]
+    #[view(collection = Folder, key = u64, name = "by-parent", core = bonsaidb_core)]
+    #[view_schema(core = bonsaidb_core)]
+    struct FoldersByParent;
+
+    impl CollectionMapReduce for FoldersByParent {
+        fn map<'doc>(
+            &self,
+            document: CollectionDocument<<Self::View as View>::Collection>,
+        ) -> ViewMapResult<'doc, Self::View> {
+            match document.contents.parent {
+                Some(parent) => document.header.emit_key(parent),
+                None => Ok(Mappings::none()),
+            }
+        }
+
+        fn reduce(
+            &self,
+            _mappings: &[ViewMappedValue<'_, Self::View>],
+            _rereduce: bool,
+        ) -> ReduceResult<Self::View> {
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "files", views = [FilesByFolder], core = bonsaidb_core)]
+    struct File {
+        folder: u64,
+    }
+
+    #[derive(Debug, Clone, View, ViewSchema)]
+    #[view(collection = File, key = u64, name = "by-folder", core = bonsaidb_core)]
+    #[view_schema(core = bonsaidb_core)]
+    struct FilesByFolder;
+
+    impl CollectionMapReduce for FilesByFolder {
+        fn map<'doc>(
+            &self,
+            document: CollectionDocument<<Self::View as View>::Collection>,
+        ) -> ViewMapResult<'doc, Self::View> {
+            document.header.emit_key(document.contents.folder)
+        }
+
+        fn reduce(
+            &self,
+            _mappings: &[ViewMappedValue<'_, Self::View>],
+            _rereduce: bool,
+        ) -> ReduceResult<Self::View> {
+            Ok(())
+        }
+    }
+
+    let path = TestDirectory::new("delete-cascades");
+    let db = Database::open::<CascadeSchema>(StorageConfiguration::new(&path))?;
+    let root = Folder { parent: None }.push_into(&db)?;
+    let child = Folder {
+        parent: Some(root.header.id),
+    }
+    .push_into(&db)?;
+    let unrelated = Folder { parent: None }.push_into(&db)?;
+    File {
+        folder: root.header.id,
+    }
+    .push_into(&db)?;
+    File {
+        folder: child.header.id,
+    }
+    .push_into(&db)?;
+    let kept = File {
+        folder: unrelated.header.id,
+    }
+    .push_into(&db)?;
+
+    root.delete(&db)?;
+
+    let folders = Folder::all(&db).query()?;
+    assert_eq!(folders.len(), 1);
+    assert_eq!(folders[0].header.id, unrelated.header.id);
+    let files = File::all(&db).query()?;
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].header.id, kept.header.id);
+
+    // Cascades are resolved within the transaction, so a document inserted by
+    // the same transaction is also deleted. The cascaded deletions are not
+    // included in the transaction's results.
+    let mut tx = Transaction::new();
+    tx.push(Operation::push_serialized::<File>(&File {
+        folder: unrelated.header.id,
+    })?);
+    tx.push(Operation::delete(
+        Folder::collection_name(),
+        Header::try_from(unrelated.header)?,
+    ));
+    let results = tx.apply(&db)?;
+    assert_eq!(results.len(), 2);
+    assert!(Folder::all(&db).query()?.is_empty());
+    assert!(File::all(&db).query()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn related_collection_views() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{BorrowedDocument, CollectionDocument, Emit};
+    use bonsaidb_core::schema::view::map::Mappings;
+    use bonsaidb_core::schema::{
+        Collection, CollectionMapReduce, CollectionName, ReduceResult, Schema,
+        SerializedCollection, View, ViewMapResult, ViewMappedValue, ViewSchema,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Schema)]
+    #[schema(name = "related", collections = [Post, Comment], core = bonsaidb_core)]
+    struct RelatedSchema;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "posts", views = [ByTag], core = bonsaidb_core)]
+    struct Post {
+        tag: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "comments", core = bonsaidb_core)]
+    struct Comment {
+        tag: String,
+    }
+
+    #[derive(Debug, Clone, View, ViewSchema)]
+    #[view(collection = Post, key = String, value = u32, name = "by-tag", core = bonsaidb_core)]
+    #[view_schema(related = [Comment], core = bonsaidb_core)]
+    struct ByTag;
+
+    impl CollectionMapReduce for ByTag {
+        fn map<'doc>(
+            &self,
+            document: CollectionDocument<<Self::View as View>::Collection>,
+        ) -> ViewMapResult<'doc, Self::View> {
+            document.header.emit_key_and_value(document.contents.tag, 1)
+        }
+
+        fn map_related<'doc>(
+            &self,
+            collection: &CollectionName,
+            document: &'doc BorrowedDocument<'_>,
+        ) -> ViewMapResult<'doc, Self::View> {
+            assert_eq!(collection, &Comment::collection_name());
+            let comment = Comment::document_contents(document)?;
+            document.header.emit_key_and_value(comment.tag, 1)
+        }
+
+        fn reduce(
+            &self,
+            mappings: &[ViewMappedValue<'_, Self::View>],
+            _rereduce: bool,
+        ) -> ReduceResult<Self::View> {
+            Ok(mappings.iter().map(|mapping| mapping.value).sum())
+        }
+    }
+
+    let path = TestDirectory::new("related-collection-views");
+    let db = Database::open::<RelatedSchema>(StorageConfiguration::new(&path))?;
+    let post = Post {
+        tag: String::from("rust"),
+    }
+    .push_into(&db)?;
+    let mut comment = Comment {
+        tag: String::from("rust"),
+    }
+    .push_into(&db)?;
+
+    // Mappings from both collections are stored in the same view.
+    let rust = db.view::<ByTag>().with_key("rust").query()?;
+    assert_eq!(rust.len(), 2);
+    assert_eq!(db.view::<ByTag>().with_key("rust").reduce()?, 2);
+
+    // Only documents from the view's own collection are returned with
+    // documents.
+    let posts = db
+        .view::<ByTag>()
+        .with_key("rust")
+        .query_with_collection_docs()?;
+    assert_eq!(posts.len(), 1);
+    assert_eq!(posts.get(0).unwrap().document.header.id, post.header.id);
+
+    // Updating a related document updates its mapping.
+    comment.contents.tag = String::from("databases");
+    comment.update(&db)?;
+    assert_eq!(db.view::<ByTag>().with_key("rust").reduce()?, 1);
+    assert_eq!(db.view::<ByTag>().with_key("databases").reduce()?, 1);
+
+    comment.delete(&db)?;
+    assert_eq!(db.view::<ByTag>().reduce()?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn document_history() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::LowLevelConnection;
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "notes", history_limit = 2, core = bonsaidb_core)]
+    struct Note {
+        text: String,
+    }
+
+    let path = TestDirectory::new("document-history");
+    let db = Database::open::<Note>(StorageConfiguration::new(&path))?;
+    let mut note = Note {
+        text: String::from("first"),
+    }
+    .push_into(&db)?;
+    let first = note.header.revision;
+    assert!(db.history::<Note, _>(&note.header.id)?.is_empty());
+
+    note.contents.text = String::from("second");
+    note.update(&db)?;
+    let second = note.header.revision;
+    note.contents.text = String::from("third");
+    note.update(&db)?;
+    let third = note.header.revision;
+
+    // The current revision can be retrieved along with the retained history.
+    let current = db
+        .get_revision::<Note, _>(&note.header.id, third)?
+        .expect("current revision missing");
+    assert_eq!(Note::document_contents(&current)?.text, "third");
+    let history = db.history::<Note, _>(&note.header.id)?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|header| header.revision)
+            .collect::<Vec<_>>(),
+        vec![first, second]
+    );
+    let original = db
+        .get_revision::<Note, _>(&note.header.id, first)?
+        .expect("first revision missing");
+    assert_eq!(Note::document_contents(&original)?.text, "first");
+
+    // Updating again pushes the oldest revision out of the history, and
+    // deleting the document retains its final revision.
+    note.contents.text = String::from("fourth");
+    note.update(&db)?;
+    note.delete(&db)?;
+    let history = db.history::<Note, _>(&note.header.id)?;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].revision, third);
+    assert_eq!(history[1].revision, note.header.revision);
+    assert!(db
+        .get_revision::<Note, _>(&note.header.id, first)?
+        .is_none());
+    let deleted = db
+        .get_revision::<Note, _>(&note.header.id, note.header.revision)?
+        .expect("deleted revision missing");
+    assert_eq!(Note::document_contents(&deleted)?.text, "fourth");
+
+    // Recreating the document restarts its revision ids, but the history
+    // remains ordered by when each revision was replaced.
+    let mut recreated = Note {
+        text: String::from("recreated"),
+    }
+    .insert_into(&note.header.id, &db)?;
+    let recreated_revision = recreated.header.revision;
+    assert!(recreated_revision < third);
+    recreated.contents.text = String::from("fifth");
+    recreated.update(&db)?;
+    let history = db.history::<Note, _>(&note.header.id)?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|header| header.revision)
+            .collect::<Vec<_>>(),
+        vec![note.header.revision, recreated_revision]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diff_revisions() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{ChangeKind, PathSegment, Revision};
+    use bonsaidb_core::pot::Value;
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "notes", history_limit = 2, core = bonsaidb_core)]
+    struct Note {
+        text: String,
+        tags: Vec<String>,
+    }
+
+    let path = TestDirectory::new("diff-revisions");
+    let db = Database::open::<Note>(StorageConfiguration::new(&path))?;
+    let mut note = Note {
+        text: String::from("first"),
+        tags: Vec::new(),
+    }
+    .push_into(&db)?;
+    let first = note.header.revision;
+    note.contents.text = String::from("second");
+    note.contents.tags.push(String::from("draft"));
+    note.update(&db)?;
+    let second = note.header.revision;
+
+    let diff = Note::diff_revisions(&note.header.id, first, second, &db)?;
+    assert_eq!(diff.changes.len(), 2);
+    assert_eq!(
+        diff.changes[0].path,
+        vec![PathSegment::Field(String::from("text"))]
+    );
+    assert_eq!(
+        diff.changes[0].kind,
+        ChangeKind::Changed {
+            from: Value::String("first".into()),
+            to: Value::String("second".into()),
+        }
+    );
+    assert_eq!(
+        diff.changes[1].path,
+        vec![
+            PathSegment::Field(String::from("tags")),
+            PathSegment::Index(0)
+        ]
+    );
+    assert_eq!(
+        diff.changes[1].kind,
+        ChangeKind::Added(Value::String("draft".into()))
+    );
+    assert!(Note::diff_revisions(&note.header.id, second, second, &db)?.is_empty());
+
+    // Revisions that were never stored can't be compared.
+    assert!(matches!(
+        Note::diff_revisions(&note.header.id, first, Revision::new(b"missing"), &db),
+        Err(bonsaidb_core::Error::RevisionNotFound { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn get_revision_without_history() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("get-revision-without-history");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+
+    let mut doc = Basic::new("first").push_into(&db)?;
+    let first = doc.header.revision;
+    doc.contents.value = String::from("second");
+    doc.update(&db)?;
+
+    // The current revision is always available.
+    let current = Basic::get_revision(&doc.header.id, doc.header.revision, &db)?
+        .expect("current revision missing");
+    assert_eq!(current.contents.value, "second");
+
+    // Previous revisions are not retained by this collection.
+    assert!(matches!(
+        Basic::get_revision(&doc.header.id, first, &db),
+        Err(bonsaidb_core::Error::HistoryNotRetained(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn document_id_strategies() -> anyhow::Result<()> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use bonsaidb_core::schema::{Collection, Schema, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Schema)]
+    #[schema(name = "id-strategies", collections = [Event, Token], core = bonsaidb_core)]
+    struct IdStrategies;
+
+    #[derive(Schema)]
+    #[schema(name = "unsupported-id-strategy", collections = [Label], core = bonsaidb_core)]
+    struct UnsupportedIdStrategy;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "events", id_strategy = TimeOrderedRandom, core = bonsaidb_core)]
+    struct Event;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "tokens", id_strategy = Random, core = bonsaidb_core)]
+    struct Token;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "labels", id_strategy = Random, primary_key = String, core = bonsaidb_core)]
+    struct Label;
+
+    fn now_millis() -> u64 {
+        u64::try_from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis(),
+        )
+        .unwrap()
+    }
+
+    let path = TestDirectory::new("document-id-strategies");
+    let db = Database::open::<IdStrategies>(StorageConfiguration::new(&path))?;
+
+    // Time-ordered ids begin with the millisecond they were created in.
+    let before = now_millis();
+    let first = Event.push_into(&db)?.header.id;
+    let second = Event.push_into(&db)?.header.id;
+    let after = now_millis();
+    assert_ne!(first, second);
+    for id in [first, second] {
+        assert!((before..=after).contains(&(id >> 22)));
+    }
+
+    let first = Token.push_into(&db)?.header.id;
+    let second = Token.push_into(&db)?.header.id;
+    assert_ne!(first, second);
+    assert_eq!(Token::all(&db).count()?, 2);
+    assert!(Token::get(&first, &db)?.is_some());
+
+    // Random ids are only supported by some primary key types, which is
+    // checked when the schema is defined.
+    assert!(matches!(
+        UnsupportedIdStrategy::schematic(),
+        Err(bonsaidb_core::Error::Other { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn document_change_events() -> anyhow::Result<()> {
+    use bonsaidb_core::pubsub::{document_changes_topic, DocumentChange, PubSub, Subscriber};
+    use bonsaidb_core::schema::{Collection, Schema, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Schema)]
+    #[schema(name = "change-events", collections = [Watched, Unwatched], core = bonsaidb_core)]
+    struct ChangeEventsSchema;
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "watched", publish_changes, core = bonsaidb_core)]
+    struct Watched {
+        value: u32,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "unwatched", core = bonsaidb_core)]
+    struct Unwatched;
+
+    let path = TestDirectory::new("document-change-events");
+    let db = Database::open::<ChangeEventsSchema>(StorageConfiguration::new(&path))?;
+    let subscriber = db.create_subscriber()?;
+    subscriber.subscribe_to(&document_changes_topic(&Watched::collection_name()))?;
+    subscriber.subscribe_to(&document_changes_topic(&Unwatched::collection_name()))?;
+
+    let doc = Watched { value: 1 }.push_into(&db)?;
+    let inserted = subscriber
+        .receiver()
+        .receive()?
+        .payload::<DocumentChange>()?;
+    assert_eq!(Some(inserted.transaction_id), db.last_transaction_id()?);
+    assert_eq!(inserted.id.deserialize::<u64>()?, doc.header.id);
+    assert_eq!(inserted.revision, doc.header.revision);
+    assert!(!inserted.deleted);
+
+    // Changes to collections that don't publish their changes aren't
+    // published, so the next message should be the deletion.
+    Unwatched.push_into(&db)?;
+    doc.delete(&db)?;
+    let deleted = subscriber
+        .receiver()
+        .receive()?
+        .payload::<DocumentChange>()?;
+    assert_eq!(deleted.id.deserialize::<u64>()?, doc.header.id);
+    assert_eq!(deleted.revision, doc.header.revision);
+    assert!(deleted.deleted);
+    assert!(subscriber.receiver().try_receive().is_err());
+
+    // Change events are published to a reserved topic, which can't be
+    // published to directly.
+    assert!(matches!(
+        db.publish(
+            &document_changes_topic(&Watched::collection_name()),
+            &inserted
+        ),
+        Err(bonsaidb_core::Error::ReservedTopic)
+    ));
+    assert!(subscriber.receiver().try_receive().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn subscriber_drain_available() -> anyhow::Result<()> {
+    use bonsaidb_core::pubsub::{PubSub, Subscriber};
+
+    let path = TestDirectory::new("subscriber-drain-available");
+    let db = Database::open::<Basic>(StorageConfiguration::new(&path))?;
+    let subscriber = db.create_subscriber()?;
+    subscriber.subscribe_to(&"a")?;
+    assert!(subscriber.drain_available().is_empty());
+
+    for value in 1_u32..=3 {
+        db.publish(&"a", &value)?;
+    }
+    let drained = subscriber
+        .drain_available()
+        .into_iter()
+        .map(|message| message.payload::<u32>())
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(drained, [1, 2, 3]);
+    assert!(subscriber.drain_available().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn storage_snapshots() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("storage-snapshots");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    let orders = storage.create_database::<BasicSchema>("orders", false)?;
+    let invoices = storage.create_database::<BasicSchema>("invoices", false)?;
+    let order = Basic::new("order").push_into(&orders)?;
+    let mut invoice = Basic::new("invoice").push_into(&invoices)?;
+    let untouched = Basic::new("untouched").push_into(&invoices)?;
+
+    let snapshot = storage.snapshot(["orders", "invoices"])?;
+    assert_eq!(
+        snapshot.transaction_id("orders"),
+        Some(orders.last_transaction_id()?)
+    );
+    assert!(snapshot.database("other").is_none());
+
+    // Only reads of documents changed after the snapshot was taken fail.
+    invoice.contents.value = String::from("paid");
+    invoice.update(&invoices)?;
+    let snapshot_invoices = snapshot.database("invoices").unwrap();
+    assert!(matches!(
+        snapshot_invoices.get::<Basic, _>(&invoice.header.id),
+        Err(crate::Error::SnapshotInvalidated(name)) if name == "invoices"
+    ));
+    let read = snapshot_invoices
+        .get::<Basic, _>(&untouched.header.id)?
+        .expect("document missing");
+    assert_eq!(Basic::document_contents(&read)?.value, "untouched");
+    let snapshot_orders = snapshot.database("orders").unwrap();
+    assert_eq!(
+        snapshot_orders
+            .get_multiple::<Basic, _, _>([&order.header.id])?
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn collection_json_round_trip() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("collection-json-round-trip");
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<BasicSchema>()?)?;
+    let source = storage.create_database::<BasicSchema>("source", false)?;
+    let destination = storage.create_database::<BasicSchema>("destination", false)?;
+    Basic::new("a").push_into(&source)?;
+    Basic::new("b").with_parent_id(1).push_into(&source)?;
+    Basic::new("c").with_parent_id(1).push_into(&source)?;
+
+    let mut exported = Vec::new();
+    assert_eq!(source.export_collection_json::<Basic>(&mut exported)?, 3);
+    let json = String::from_utf8(exported)?;
+    assert_eq!(json.lines().count(), 3);
+    assert!(json.starts_with(r#"{"id":1,"revision":"0-"#));
+    assert!(json
+        .lines()
+        .all(|line| line.contains(r#""contents":{"value":"#)));
+
+    assert_eq!(
+        destination.import_collection_json::<Basic>(json.as_bytes())?,
+        3
+    );
+    let imported = Basic::all(&destination).query()?;
+    assert_eq!(
+        imported
+            .iter()
+            .map(|doc| (doc.header.id, doc.contents.value.as_str()))
+            .collect::<Vec<_>>(),
+        [(1, "a"), (2, "b"), (3, "c")]
+    );
+    // Views of the imported documents are updated.
+    assert_eq!(
+        destination
+            .view::<BasicByParentId>()
+            .with_key(&Some(1))
+            .query()?
+            .len(),
+        2
+    );
+
+    // Importing the same documents again conflicts with the existing ids.
+    assert!(destination
+        .import_collection_json::<Basic>(json.as_bytes())
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn view_query_csv() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{CollectionDocument, Emit};
+    use bonsaidb_core::schema::{
+        Collection, CollectionMapReduce, SerializedCollection, View, ViewMapResult, ViewSchema,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "sales", views = [SalesByRegion], core = bonsaidb_core)]
+    struct Sale {
+        region: String,
+        month: u8,
+        total: u32,
+        note: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SaleSummary {
+        total: u32,
+        note: String,
+    }
+
+    #[derive(Debug, Clone, View, ViewSchema)]
+    #[view(collection = Sale, key = (String, u8), value = SaleSummary, name = "by-region", core = bonsaidb_core)]
+    #[view_schema(core = bonsaidb_core)]
+    struct SalesByRegion;
+
+    impl CollectionMapReduce for SalesByRegion {
+        fn map<'doc>(
+            &self,
+            document: CollectionDocument<<Self::View as View>::Collection>,
+        ) -> ViewMapResult<'doc, Self::View> {
+            let sale = document.contents;
+            document.header.emit_key_and_value(
+                (sale.region, sale.month),
+                SaleSummary {
+                    total: sale.total,
+                    note: sale.note,
+                },
+            )
+        }
+    }
+
+    let path = TestDirectory::new("view-query-csv");
+    let db = Database::open::<Sale>(StorageConfiguration::new(&path))?;
+    for (region, month, total, note) in [
+        ("north", 2, 150, "plain"),
+        ("east", 1, 100, "has, comma"),
+        ("north", 1, 75, "said \"hi\""),
+    ] {
+        Sale {
+            region: region.to_string(),
+            month,
+            total,
+            note: note.to_string(),
+        }
+        .push_into(&db)?;
+    }
+
+    let mut csv = Vec::new();
+    let rows =
+        db.query_to_csv::<SalesByRegion, (String, u8)>(None, AccessPolicy::UpdateBefore, &mut csv)?;
+    assert_eq!(rows, 3);
+    assert_eq!(
+        String::from_utf8(csv)?,
+        "key.0,key.1,value.total,value.note\r\n\
+         east,1,100,\"has, comma\"\r\n\
+         north,1,75,\"said \"\"hi\"\"\"\r\n\
+         north,2,150,plain\r\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mismatched_query_key() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::{HasSchema, LowLevelConnection, MaybeOwned, QueryKey, Sort};
+    let path = TestDirectory::new("mismatched-query-key");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    db.collection::<Basic>()
+        .push(&Basic::new("a").with_parent_id(1))?;
+    let view = db.schematic().view::<BasicByParentId>()?.view_name();
+
+    // BasicByParentId's key is an Option<u64>, which is encoded differently
+    // than a u64.
+    let key = QueryKey::<u64>::Matches(MaybeOwned::Owned(1)).serialized()?;
+    assert!(matches!(
+        db.query_by_name(
+            &view,
+            Some(key),
+            Sort::Ascending,
+            None,
+            AccessPolicy::UpdateBefore
+        ),
+        Err(bonsaidb_core::Error::QueryKeyMismatch { .. })
+    ));
+
+    let key = QueryKey::<Option<u64>>::Matches(MaybeOwned::Owned(Some(1))).serialized()?;
+    let mappings = db.query_by_name(
+        &view,
+        Some(key),
+        Sort::Ascending,
+        None,
+        AccessPolicy::UpdateBefore,
+    )?;
+    assert_eq!(mappings.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "async")]
+async fn database_concurrency_limit() -> anyhow::Result<()> {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use bonsaidb_core::connection::AsyncStorageConnection;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    use crate::{AsyncStorage, ConcurrencyUsage};
+
+    let path = TestDirectory::new("database-concurrency-limit");
+    let storage = AsyncStorage::open(
+        StorageConfiguration::new(&path)
+            .with_schema::<BasicSchema>()?
+            .database_concurrency_limit(2),
+    )
+    .await?;
+    let db = storage
+        .create_database::<BasicSchema>("limited", false)
+        .await?;
+    assert_eq!(
+        db.concurrency_usage(),
+        Some(ConcurrencyUsage {
+            running: 0,
+            limit: 2,
+        })
+    );
+
+    // Operations beyond the limit wait for their turn rather than failing.
+    let results = futures::future::join_all(
+        (0..10).map(|index| Basic::new(index.to_string()).push_into_async(&db)),
+    )
+    .await;
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(db.concurrency_usage().map(|usage| usage.running), Some(0));
+
+    // Block two operations, and verify a third doesn't start until one of
+    // them finishes.
+    let started = Arc::new(AtomicUsize::new(0));
+    let released = Arc::new(AtomicBool::new(false));
+    let operations = (0..3)
+        .map(|_| {
+            let db = db.clone();
+            let started = started.clone();
+            let released = released.clone();
+            tokio::spawn(async move {
+                db.spawn_blocking(move || {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    while !released.load(Ordering::SeqCst) {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                })
+                .await
+            })
+        })
+        .collect::<Vec<_>>();
+    while started.load(Ordering::SeqCst) < 2 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+    assert_eq!(db.concurrency_usage().map(|usage| usage.running), Some(2));
+
+    released.store(true, Ordering::SeqCst);
+    for operation in operations {
+        operation.await??;
+    }
+    assert_eq!(started.load(Ordering::SeqCst), 3);
+    assert_eq!(db.concurrency_usage().map(|usage| usage.running), Some(0));
+
+    Ok(())
+}
+
 #[test]
 #[cfg(feature = "encryption")]
 fn encryption() -> anyhow::Result<()> {
@@ -266,6 +1899,142 @@ fn encryption() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg(feature = "encryption")]
+fn encrypted_transaction_log() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::LowLevelConnection;
+
+    let path = TestDirectory::new("encrypted-transaction-log");
+    let plaintext_transaction_id = {
+        let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+        db.collection::<EncryptedBasic>()
+            .push(&EncryptedBasic::new("hello"))?;
+        db.collection::<Basic>().push(&Basic::new("hello"))?;
+
+        // Both transactions can be read while the keys are available.
+        let transactions = db.list_executed_transactions(None, None)?;
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions[0].changes.documents().is_some());
+        transactions[1].id
+    };
+
+    // Generate a new master key, which prevents decrypting the first
+    // transaction's changes.
+    std::fs::remove_file(path.join("master-keys"))?;
+
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    if let Err(bonsaidb_core::Error::Other { error, .. }) =
+        db.list_executed_transactions(None, None)
+    {
+        assert!(error.contains("vault"));
+    } else {
+        panic!("successfully read encrypted transaction without keys");
+    }
+
+    // Transactions that only change unencrypted collections are not encrypted.
+    let transactions = db.list_executed_transactions(Some(plaintext_transaction_id), None)?;
+    assert_eq!(transactions.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn encrypted_view_range_query() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::test_util::EncryptedBasicByParentId;
+    let path = TestDirectory::new("encrypted-view-range-query");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    for parent_id in 1..=4 {
+        db.collection::<EncryptedBasic>()
+            .push(&EncryptedBasic::new("child").with_parent_id(parent_id))?;
+    }
+
+    let mappings = db
+        .view::<EncryptedBasicByParentId>()
+        .with_key_range(Some(2)..Some(4))
+        .query()?;
+    assert_eq!(
+        mappings
+            .iter()
+            .map(|mapping| mapping.key)
+            .collect::<Vec<_>>(),
+        vec![Some(2), Some(3)]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn master_key_rotation() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{Collection, SerializedCollection};
+    use bonsaidb_core::test_util::EncryptedBasicCount;
+    use nebari::tree::Versioned;
+
+    use crate::database::document_tree_name;
+    use crate::vault::master_key_version;
+
+    let path = TestDirectory::new("master-key-rotation");
+    let (original, rotated) = {
+        // Chunks are cached after being decrypted, so caching is disabled to
+        // ensure the stored payloads are read from disk.
+        let mut configuration = StorageConfiguration::new(&path);
+        configuration.cache.max_chunk_size = 0;
+        let db = Database::open::<BasicSchema>(configuration)?;
+        let original = db
+            .collection::<EncryptedBasic>()
+            .push(&EncryptedBasic::new("original"))?;
+        assert_eq!(db.view::<EncryptedBasicCount>().reduce()?, 1);
+
+        assert_eq!(db.storage().rotate_master_key()?, 1);
+
+        // Data encrypted with the previous key must still be readable.
+        let doc = db
+            .collection::<EncryptedBasic>()
+            .get(&original.id)?
+            .expect("doc not found");
+        assert_eq!(&EncryptedBasic::document_contents(&doc)?.value, "original");
+
+        let rotated = db
+            .collection::<EncryptedBasic>()
+            .push(&EncryptedBasic::new("rotated"))?;
+        assert_eq!(db.view::<EncryptedBasicCount>().reduce()?, 2);
+
+        assert!(db.reencrypt_collection::<EncryptedBasic>()? > 0);
+
+        // Reading the tree without a vault returns the stored payloads, which
+        // must all be encrypted using the new master key.
+        let documents = db.roots().tree(Versioned::tree(document_tree_name(
+            &EncryptedBasic::collection_name(),
+        )))?;
+        let stored = documents.get_range(&(..))?;
+        assert_eq!(stored.len(), 2);
+        for (_, payload) in stored {
+            // Skip the header identifying the encryption and compression used.
+            let payload = payload
+                .strip_prefix(b"trv")
+                .map_or(&payload[..], |p| &p[1..]);
+            assert_eq!(master_key_version(payload), Some(1));
+        }
+
+        (original, rotated)
+    };
+
+    // Reopening requires the rotated master keys to have been saved.
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    for (header, value) in [(original, "original"), (rotated, "rotated")] {
+        let doc = db
+            .collection::<EncryptedBasic>()
+            .get(&header.id)?
+            .expect("doc not found");
+        assert_eq!(&EncryptedBasic::document_contents(&doc)?.value, value);
+    }
+    assert_eq!(db.view::<EncryptedBasicCount>().reduce()?, 2);
+
+    Ok(())
+}
+
 #[test]
 fn expiration_after_close() -> anyhow::Result<()> {
     use bonsaidb_core::keyvalue::KeyValue;
@@ -318,3 +2087,523 @@ fn expiration_after_close() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn event_collections() -> anyhow::Result<()> {
+    use bonsaidb_core::document::{CollectionDocument, Emit};
+    use bonsaidb_core::events::EventCollection;
+    use bonsaidb_core::schema::{
+        Collection, CollectionMapReduce, CollectionName, DefaultSerialization, Qualified,
+        ReduceResult, Schema, Schematic, SerializedCollection, View, ViewMapResult,
+        ViewMappedValue, ViewSchema,
+    };
+    use bonsaidb_core::transaction::ProposedChange;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Schema)]
+    #[schema(name = "events", collections = [Deposit], core = bonsaidb_core)]
+    struct Events;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Deposit {
+        amount: u64,
+    }
+
+    impl Collection for Deposit {
+        type PrimaryKey = u64;
+
+        fn collection_name() -> CollectionName {
+            CollectionName::private("deposits")
+        }
+
+        fn define_views(schema: &mut Schematic) -> Result<(), bonsaidb_core::Error> {
+            schema.define_view(Balance)
+        }
+
+        fn validate_changes(changes: &[ProposedChange<'_>]) -> Result<(), bonsaidb_core::Error> {
+            Self::validate_append_only(changes)
+        }
+    }
+
+    impl DefaultSerialization for Deposit {}
+
+    impl EventCollection for Deposit {
+        type Projection = Balance;
+    }
+
+    #[derive(Debug, Clone, View, ViewSchema)]
+    #[view(collection = Deposit, key = (), value = u64, name = "balance", core = bonsaidb_core)]
+    #[view_schema(core = bonsaidb_core)]
+    struct Balance;
+
+    impl CollectionMapReduce for Balance {
+        fn map<'doc>(&self, document: CollectionDocument<Deposit>) -> ViewMapResult<'doc, Self> {
+            document
+                .header
+                .emit_key_and_value((), document.contents.amount)
+        }
+
+        fn reduce(
+            &self,
+            mappings: &[ViewMappedValue<'_, Self>],
+            _rereduce: bool,
+        ) -> ReduceResult<Self::View> {
+            Ok(mappings.iter().map(|mapping| mapping.value).sum())
+        }
+    }
+
+    let path = TestDirectory::new("event-collections");
+    let db = Database::open::<Events>(StorageConfiguration::new(&path))?;
+
+    let first = Deposit::append_event(&Deposit { amount: 1 }, &db)?;
+    let second = Deposit::append_event(&Deposit { amount: 2 }, &db)?;
+    assert!(second.id > first.id);
+    assert!(second.transaction_id > first.transaction_id);
+    assert_eq!(db.last_transaction_id()?, Some(second.transaction_id));
+    assert_eq!(Deposit::projection(&db)?, 3);
+
+    // Replaying visits events in the order they were appended.
+    let mut replayed = Vec::new();
+    let last = Deposit::replay_projection(None, &db, |event| {
+        replayed.push(event.contents.amount);
+        Ok(())
+    })?;
+    assert_eq!(last, Some(second.id));
+    assert_eq!(replayed, [1, 2]);
+
+    // Replaying from the last event only visits newer events.
+    let third = Deposit::append_event(&Deposit { amount: 3 }, &db)?;
+    let mut replayed = Vec::new();
+    let last = Deposit::replay_projection(last, &db, |event| {
+        replayed.push(event.contents.amount);
+        Ok(())
+    })?;
+    assert_eq!(last, Some(third.id));
+    assert_eq!(replayed, [3]);
+    assert_eq!(
+        Deposit::replay_projection(last, &db, |_| unreachable!())?,
+        last
+    );
+    assert_eq!(Deposit::projection(&db)?, 6);
+
+    // Existing events can't be updated or deleted.
+    let mut event = Deposit::get(&first.id, &db)?.expect("event missing");
+    event.contents.amount = 10;
+    assert!(matches!(
+        event.update(&db),
+        Err(bonsaidb_core::Error::Other { origin, .. }) if origin == "private.deposits"
+    ));
+    assert!(event.delete(&db).is_err());
+    assert_eq!(Deposit::projection(&db)?, 6);
+
+    Ok(())
+}
+
+#[test]
+fn open_with_schema_set() -> anyhow::Result<()> {
+    use bonsaidb_core::admin::Admin;
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::Schema;
+
+    use crate::config::SchemaSet;
+
+    /// A schema sharing [`BasicSchema`]'s name with a different structure.
+    #[derive(Debug, Schema)]
+    #[schema(name = "basic", collections = [Basic], core = bonsaidb_core)]
+    struct Impostor;
+
+    let path = TestDirectory::new("open-with-schema-set");
+    let storage = Storage::open_with_schemas(
+        StorageConfiguration::new(&path).with_schema::<BasicSchema>()?,
+        SchemaSet::new()
+            .with_schema::<BasicSchema>()?
+            .with_schema::<Basic>()?,
+    )?;
+    storage.create_database::<BasicSchema>("basic", false)?;
+    storage.create_database::<Basic>("only-basic", false)?;
+    drop(storage);
+
+    // Schemas with the same name and different structures conflict.
+    let path = TestDirectory::new("open-with-schema-set-conflict");
+    let error = Storage::open_with_schemas(
+        StorageConfiguration::new(&path),
+        SchemaSet::new()
+            .with_schema::<BasicSchema>()?
+            .with_schema::<Impostor>()?,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(_))
+    ));
+
+    // The admin schema is reserved.
+    let error = Storage::open_with_schemas(
+        StorageConfiguration::new(&path),
+        SchemaSet::new().with_schema::<Admin>()?,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::Core(bonsaidb_core::Error::SchemaAlreadyRegistered(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn reduce_more_entries_than_a_batch() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{MappedValue, SerializedCollection};
+    use bonsaidb_core::transaction::Transaction;
+
+    let path = TestDirectory::new("reduce-more-entries-than-a-batch");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+
+    // Each document has a distinct parent id, producing more entries than
+    // are rereduced at once.
+    let mut tx = Transaction::new();
+    for parent_id in 0..600 {
+        Basic::new("child")
+            .with_parent_id(parent_id)
+            .push_in_transaction(&mut tx)?;
+    }
+    tx.apply(&db)?;
+
+    assert_eq!(db.view::<BasicByParentId>().reduce()?, 600);
+    assert_eq!(db.view::<BasicByParentId>().reduce_grouped()?.len(), 600);
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .group_level(0)
+            .reduce_grouped()?,
+        vec![MappedValue::new(Some(0), 600)]
+    );
+    assert_eq!(
+        db.view::<BasicByParentId>()
+            .with_key_range(Some(100)..Some(400))
+            .reduce()?,
+        300
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transaction_feed() -> anyhow::Result<()> {
+    use bonsaidb_core::document::DocumentId;
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("transaction-feed");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+
+    let first = Basic::new("first").push_into(&db)?;
+    let mut feed = db.transaction_feed(None)?;
+    let historical = feed.next().unwrap()?;
+    assert_eq!(
+        historical.changes.documents().unwrap().documents[0].id,
+        DocumentId::from_u64(first.header.id)
+    );
+    assert!(feed.try_next().is_none());
+
+    // Transactions committed after the feed caught up are returned as they
+    // are committed.
+    let writer = std::thread::spawn({
+        let db = db.clone();
+        move || {
+            std::thread::sleep(Duration::from_millis(100));
+            Basic::new("second").push_into(&db)
+        }
+    });
+    let live = feed.next().unwrap()?;
+    let second = writer.join().unwrap()?;
+    assert!(live.id > historical.id);
+    assert_eq!(
+        live.changes.documents().unwrap().documents[0].id,
+        DocumentId::from_u64(second.header.id)
+    );
+
+    // Starting from an id only returns transactions from that id onward.
+    let mut feed = db.transaction_feed(Some(live.id))?;
+    assert_eq!(feed.next().unwrap()?.id, live.id);
+    assert!(feed.try_next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn read_only() -> anyhow::Result<()> {
+    use bonsaidb_core::keyvalue::KeyValue;
+    use bonsaidb_core::schema::SerializedCollection;
+    use bonsaidb_core::transaction::{Operation, Transaction};
+
+    let path = TestDirectory::new("read-only");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    let existing = Basic::new("existing").push_into(&db)?;
+    db.set_key("key", &1_u64).execute()?;
+
+    let read_only = db.read_only();
+    assert!(read_only.is_read_only());
+    assert!(!db.is_read_only());
+
+    // Reads are unaffected.
+    assert!(Basic::get(&existing.header.id, &read_only)?.is_some());
+    assert_eq!(read_only.get_key("key").into_u64()?, Some(1));
+
+    // Every kind of write is rejected.
+    assert!(matches!(
+        Basic::new("new").push_into(&read_only),
+        Err(bonsaidb_core::schema::InsertError {
+            error: bonsaidb_core::Error::ReadOnly,
+            ..
+        })
+    ));
+    assert!(matches!(
+        read_only.set_key("key", &2_u64).execute(),
+        Err(bonsaidb_core::Error::ReadOnly)
+    ));
+    assert!(matches!(
+        read_only.increment_key_by("key", 1_u64).execute(),
+        Err(bonsaidb_core::Error::ReadOnly)
+    ));
+    assert!(matches!(
+        read_only.get_key("key").and_delete().query(),
+        Err(bonsaidb_core::Error::ReadOnly)
+    ));
+    assert!(matches!(
+        read_only.prune_orphaned_trees(false),
+        Err(bonsaidb_core::Error::ReadOnly)
+    ));
+    assert!(read_only.prune_orphaned_trees(true)?.is_empty());
+
+    // Transactions that don't modify any documents are allowed.
+    let mut tx = Transaction::new();
+    tx.push(Operation::check_document_exists::<Basic>(
+        &existing.header.id,
+    )?);
+    tx.push(Operation::check_document_is_current::<Basic, _>(&existing)?);
+    assert_eq!(tx.apply(&read_only)?.len(), 2);
+
+    // Writes made through other handles are visible.
+    assert_eq!(Basic::all(&read_only).count()?, 1);
+    Basic::new("second").push_into(&db)?;
+    assert_eq!(Basic::all(&read_only).count()?, 2);
+    assert_eq!(db.get_key("key").into_u64()?, Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn view_status() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::SerializedCollection;
+
+    let path = TestDirectory::new("view-status");
+    let db = Database::open::<BasicSchema>(StorageConfiguration::new(&path))?;
+    for value in ["a", "b", "c"] {
+        Basic::new(value).push_into(&db)?;
+    }
+
+    // The view has not been queried, so none of the documents are mapped.
+    let status = db.view_status::<BasicByParentId>()?;
+    assert_eq!(status.pending_documents, 3);
+    assert_eq!(status.total_documents, 3);
+    assert!(!status.is_up_to_date());
+
+    db.view::<BasicByParentId>().query()?;
+    let status = db.view_status::<BasicByParentId>()?;
+    assert_eq!(status.pending_documents, 0);
+    assert_eq!(status.total_documents, 3);
+    assert!(status.integrity_checked);
+    assert!(status.is_up_to_date());
+
+    // Changing a document invalidates only that document.
+    let mut doc = Basic::all(&db).query()?.remove(0);
+    doc.contents.parent_id = Some(1);
+    doc.update(&db)?;
+    let status = db.view_status::<BasicByParentId>()?;
+    assert_eq!(status.pending_documents, 1);
+    assert!(!status.is_up_to_date());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "async")]
+async fn watch_transactions() -> anyhow::Result<()> {
+    use bonsaidb_core::connection::AsyncConnection;
+    use bonsaidb_core::keyvalue::AsyncKeyValue;
+    use bonsaidb_core::pubsub::{AsyncPubSub, TransactionsTopic};
+    use bonsaidb_core::schema::SerializedCollection;
+    use futures::StreamExt;
+
+    use crate::AsyncDatabase;
+
+    let path = TestDirectory::new("watch-transactions");
+    let db = AsyncDatabase::open::<BasicSchema>(StorageConfiguration::new(&path)).await?;
+    let first = Basic::new("first").push_into_async(&db).await?;
+
+    // Transactions already in the log are returned first.
+    let mut transactions = db.watch_transactions(None);
+    let historical = transactions.next().await.unwrap()?;
+    assert_eq!(
+        historical.changes.documents().unwrap().documents[0].id,
+        bonsaidb_core::document::DocumentId::from_u64(first.header.id)
+    );
+
+    // New transactions, including key-value transactions, are returned as they
+    // are committed.
+    let writer = tokio::spawn({
+        let db = db.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            db.set_key("key", &1_u64).await?;
+            Basic::new("second").push_into_async(&db).await?;
+            anyhow::Ok(())
+        }
+    });
+    let mut ids = vec![historical.id];
+    while ids.len() < 3 {
+        let executed = tokio::time::timeout(Duration::from_secs(5), transactions.next())
+            .await?
+            .unwrap()?;
+        ids.push(executed.id);
+    }
+    writer.await??;
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(db.last_transaction_id().await?, ids.last().copied());
+
+    // Transaction ids are published to a reserved topic, preventing clients
+    // from publishing ids that were never committed.
+    assert!(matches!(
+        db.publish_to_topic(&TransactionsTopic, &u64::MAX).await,
+        Err(bonsaidb_core::Error::ReservedTopic)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn schema_migrations() -> anyhow::Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bonsaidb_core::connection::StorageConnection;
+    use bonsaidb_core::schema::{Migrations, Schema, SchemaName, Schematic, SerializedCollection};
+
+    static STEPS_RUN: AtomicUsize = AtomicUsize::new(0);
+
+    /// The schema before any migrations were defined.
+    struct Original;
+
+    impl Schema for Original {
+        fn schema_name() -> SchemaName {
+            SchemaName::new("khonsulabs", "migrations")
+        }
+
+        fn define_collections(schema: &mut Schematic) -> Result<(), bonsaidb_core::Error> {
+            schema.define_collection::<Basic>()
+        }
+    }
+
+    struct Upgraded;
+
+    impl Schema for Upgraded {
+        fn schema_name() -> SchemaName {
+            Original::schema_name()
+        }
+
+        fn define_collections(schema: &mut Schematic) -> Result<(), bonsaidb_core::Error> {
+            Original::define_collections(schema)
+        }
+
+        fn define_migrations<C: Connection>(migrations: &mut Migrations<C>) {
+            migrations
+                .step(1, |db: &C| {
+                    STEPS_RUN.fetch_add(1, Ordering::SeqCst);
+                    for mut doc in Basic::all(db).query()? {
+                        doc.contents.category = Some(String::from("migrated"));
+                        doc.update(db)?;
+                    }
+                    Ok(())
+                })
+                .step(2, |_: &C| {
+                    STEPS_RUN.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                });
+        }
+    }
+
+    let path = TestDirectory::new("schema-migrations");
+    let header = {
+        let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Original>()?)?;
+        let db = storage.create_database::<Original>("existing", false)?;
+        db.collection::<Basic>().push(&Basic::new("hello"))?
+    };
+
+    // Opening the existing database with the upgraded schema runs every step.
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Upgraded>()?)?;
+    let db = storage.database::<Upgraded>("existing")?;
+    assert_eq!(STEPS_RUN.load(Ordering::SeqCst), 2);
+    let doc = Basic::get(&header.id, &db)?.unwrap();
+    assert_eq!(doc.contents.category.as_deref(), Some("migrated"));
+
+    // New databases start at the latest version.
+    storage.create_database::<Upgraded>("new", false)?;
+    drop((db, storage));
+
+    // The upgraded version is persisted, so no steps run again.
+    let storage = Storage::open(StorageConfiguration::new(&path).with_schema::<Upgraded>()?)?;
+    storage.database::<Upgraded>("existing")?;
+    storage.database::<Upgraded>("new")?;
+    assert_eq!(STEPS_RUN.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test]
+fn natural_keys() -> anyhow::Result<()> {
+    use bonsaidb_core::schema::{ByNaturalKey, Collection, SerializedCollection};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, Collection)]
+    #[collection(name = "users", core = bonsaidb_core)]
+    struct User {
+        #[natural_key]
+        email: String,
+        name: String,
+    }
+
+    let path = TestDirectory::new("natural-keys");
+    let db = Database::open::<User>(StorageConfiguration::new(&path))?;
+    let first = User {
+        email: String::from("ecton@example.com"),
+        name: String::from("Jon"),
+    }
+    .push_into(&db)?;
+    let mut second = User {
+        email: String::from("other@example.com"),
+        name: String::from("Other"),
+    }
+    .push_into(&db)?;
+
+    let duplicate = User {
+        email: String::from("ecton@example.com"),
+        name: String::from("Duplicate"),
+    }
+    .push_into(&db)
+    .unwrap_err();
+    assert!(duplicate
+        .error
+        .is_unique_key_error::<ByNaturalKey<User>, _>(&db));
+
+    second.contents.email = String::from("ecton@example.com");
+    let err = second.update(&db).unwrap_err();
+    assert!(err.is_unique_key_error::<ByNaturalKey<User>, _>(&db));
+
+    let mappings = db
+        .view::<ByNaturalKey<User>>()
+        .with_key("ecton@example.com")
+        .query()?;
+    assert_eq!(mappings.len(), 1);
+    assert_eq!(mappings[0].source.id, first.header.id);
+    assert_eq!(User::all(&db).count()?, 2);
+
+    Ok(())
+}
@@ -17,6 +17,27 @@
 //! over a network, the network transport layer's encryption is what ensures
 //! your data's safety.
 //!
+//! ### What is encrypted
+//!
+//! When a collection is encrypted, either by its own encryption key or by the
+//! storage's default encryption key, the following are encrypted:
+//!
+//! - Each document's contents and its retained history.
+//! - Every view's entries, including the keys and values emitted by the view,
+//!   and the trees that track which documents each view has mapped.
+//! - Transaction log entries that change documents in the collection. These
+//!   entries list the changed collections and document ids, which may reveal
+//!   sensitive information if natural keys are used.
+//!
+//! Some metadata remains unencrypted:
+//!
+//! - Transaction ids, and the number and size of transaction log entries.
+//! - The names of databases, their schemas, and the names of the files that
+//!   store each collection and view.
+//! - The sizes of documents and view entries, and the number of each.
+//! - The key-value store, including the transaction log entries that record
+//!   which keys were changed.
+//!
 //! ## Security Best Practices
 //!
 //! ### Vault Key Storage
@@ -67,6 +88,7 @@ use hpke::aead::{AeadTag, ChaCha20Poly1305};
 use hpke::kdf::HkdfSha256;
 use hpke::kem::DhP256HkdfSha256;
 use hpke::{self, Deserializable, Kem, OpModeS, Serializable};
+use parking_lot::RwLock;
 use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, Zeroizing};
@@ -125,22 +147,34 @@ impl<'a> From<&'a KeyPair> for PublicKey {
 use crate::storage::StorageId;
 
 pub(crate) struct Vault {
-    _vault_public_key: PublicKey,
-    master_keys: HashMap<u32, EncryptionKey>,
-    current_master_key_id: u32,
+    vault_public_key: PublicKey,
+    master_keys_path: PathBuf,
+    master_keys: RwLock<MasterKeys>,
     master_key_storage: Arc<dyn AnyVaultKeyStorage>,
 }
 
 impl Debug for Vault {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Vault")
+            .field("master_keys_path", &self.master_keys_path)
             .field("master_keys", &self.master_keys)
-            .field("current_master_key_id", &self.current_master_key_id)
             .field("master_key_storage", &self.master_key_storage)
             .finish_non_exhaustive()
     }
 }
 
+#[derive(Debug)]
+struct MasterKeys {
+    keys: HashMap<u32, EncryptionKey>,
+    current_id: u32,
+}
+
+impl MasterKeys {
+    fn current(&self) -> &EncryptionKey {
+        self.keys.get(&self.current_id).unwrap()
+    }
+}
+
 /// Errors relating to encryption and/or secret storage.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -220,39 +254,15 @@ impl Vault {
             .map(|r| PublicKey::from(&r).to_bytes().ok() == Some(expected_public_key_bytes))
             .unwrap_or_default();
         if retrieved_key_matches {
-            let mut serialized_master_keys = bincode::serialize(&master_keys)?;
-
-            let (encapsulated_key, aead_tag) = hpke::single_shot_seal_in_place_detached::<
-                ChaCha20Poly1305,
-                HkdfSha256,
-                DhP256HkdfSha256,
-                _,
-            >(
-                &OpModeS::Base,
-                &public,
-                b"",
-                &mut serialized_master_keys,
-                b"",
-                &mut thread_rng(),
-            )?;
-            let mut tag = [0_u8; 16];
-            tag.copy_from_slice(&aead_tag.to_bytes());
-
-            let encrypted_master_keys_payload = bincode::serialize(&HpkePayload {
-                encryption: PublicKeyEncryption::DhP256HkdfSha256ChaCha20,
-                payload: Bytes::from(serialized_master_keys),
-                encapsulated_key,
-                tag,
-            })?;
-
-            File::create(master_keys_path)
-                .and_then(move |mut file| file.write_all(&encrypted_master_keys_payload))
-                .map_err(|err| Error::Initializing(format!("error saving vault key: {err:?}")))?;
+            seal_master_keys(master_keys_path, &public, &master_keys)?;
 
             Ok(Self {
-                _vault_public_key: PublicKey::P256(public),
-                master_keys,
-                current_master_key_id: 0,
+                vault_public_key: PublicKey::P256(public),
+                master_keys_path: master_keys_path.to_path_buf(),
+                master_keys: RwLock::new(MasterKeys {
+                    keys: master_keys,
+                    current_id: 0,
+                }),
                 master_key_storage,
             })
         } else {
@@ -303,11 +313,14 @@ impl Vault {
                 }
             };
 
-            let current_master_key_id = *master_keys.keys().max().unwrap();
+            let current_id = *master_keys.keys().max().unwrap();
             Ok(Self {
-                _vault_public_key: PublicKey::from(&vault_key),
-                master_keys,
-                current_master_key_id,
+                vault_public_key: PublicKey::from(&vault_key),
+                master_keys_path: master_keys_path.to_path_buf(),
+                master_keys: RwLock::new(MasterKeys {
+                    keys: master_keys,
+                    current_id,
+                }),
                 master_key_storage,
             })
         } else {
@@ -315,8 +328,23 @@ impl Vault {
         }
     }
 
-    fn current_master_key(&self) -> &EncryptionKey {
-        self.master_keys.get(&self.current_master_key_id).unwrap()
+    /// Generates a new master key and makes it the key used for all future
+    /// encryption. Previous master keys are retained so that existing data can
+    /// still be decrypted. Returns the version of the new master key.
+    pub fn rotate_master_key(&self) -> Result<u32, Error> {
+        let PublicKey::P256(public) = &self.vault_public_key;
+        let mut master_keys = self.master_keys.write();
+        let new_id = master_keys
+            .current_id
+            .checked_add(1)
+            .ok_or_else(|| Error::Encryption(String::from("master key versions exhausted")))?;
+        master_keys.keys.insert(new_id, EncryptionKey::random());
+        if let Err(err) = seal_master_keys(&self.master_keys_path, public, &master_keys.keys) {
+            master_keys.keys.remove(&new_id);
+            return Err(err);
+        }
+        master_keys.current_id = new_id;
+        Ok(new_id)
     }
 
     pub fn encrypt_payload(
@@ -332,8 +360,9 @@ impl Vault {
             )?;
         }
 
+        let master_keys = self.master_keys.read();
         let (key, version) = match key_id {
-            KeyId::Master => (self.current_master_key(), self.current_master_key_id),
+            KeyId::Master => (master_keys.current(), master_keys.current_id),
             KeyId::Id(_) => todo!(),
             KeyId::None => unreachable!(),
         };
@@ -369,9 +398,14 @@ impl Vault {
             )?;
         }
 
-        // TODO handle key version
+        let master_keys = self.master_keys.read();
         let key = match &payload.key_id {
-            KeyId::Master => self.current_master_key(),
+            KeyId::Master => master_keys.keys.get(&payload.key_version).ok_or_else(|| {
+                Error::Encryption(format!(
+                    "master key version {} not found",
+                    payload.key_version
+                ))
+            })?,
             KeyId::Id(_) => todo!(),
             KeyId::None => unreachable!(),
         };
@@ -379,6 +413,49 @@ impl Vault {
     }
 }
 
+/// Seals `master_keys` using the vault's public key and writes them to
+/// `master_keys_path`. The keys are written to a temporary file first so that
+/// an interrupted write never leaves the existing master keys unreadable.
+fn seal_master_keys(
+    master_keys_path: &Path,
+    public: &<DhP256HkdfSha256 as Kem>::PublicKey,
+    master_keys: &HashMap<u32, EncryptionKey>,
+) -> Result<(), Error> {
+    let mut serialized_master_keys = bincode::serialize(master_keys)?;
+
+    let (encapsulated_key, aead_tag) = hpke::single_shot_seal_in_place_detached::<
+        ChaCha20Poly1305,
+        HkdfSha256,
+        DhP256HkdfSha256,
+        _,
+    >(
+        &OpModeS::Base,
+        public,
+        b"",
+        &mut serialized_master_keys,
+        b"",
+        &mut thread_rng(),
+    )?;
+    let mut tag = [0_u8; 16];
+    tag.copy_from_slice(&aead_tag.to_bytes());
+
+    let encrypted_master_keys_payload = bincode::serialize(&HpkePayload {
+        encryption: PublicKeyEncryption::DhP256HkdfSha256ChaCha20,
+        payload: Bytes::from(serialized_master_keys),
+        encapsulated_key,
+        tag,
+    })?;
+
+    let temporary_path = master_keys_path.with_extension("tmp");
+    File::create(&temporary_path)
+        .and_then(|mut file| {
+            file.write_all(&encrypted_master_keys_payload)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temporary_path, master_keys_path))
+        .map_err(|err| Error::Initializing(format!("error saving vault key: {err:?}")))
+}
+
 /// Stores encrypted keys for a vault.
 pub trait VaultKeyStorage: Send + Sync + Debug + 'static {
     /// The error type that the functions return.
@@ -606,6 +683,14 @@ impl<'a> VaultPayload<'a> {
     }
 }
 
+/// Returns the version of the master key that `payload` was encrypted with, or
+/// `None` if it was not encrypted using the master key.
+#[cfg(test)]
+pub(crate) fn master_key_version(payload: &[u8]) -> Option<u32> {
+    let payload = VaultPayload::from_slice(payload).ok()?;
+    matches!(payload.key_id, KeyId::Master).then_some(payload.key_version)
+}
+
 #[derive(Serialize, Deserialize)]
 struct HpkePayload {
     encryption: PublicKeyEncryption,
@@ -653,9 +738,12 @@ mod tests {
         let (_, public_key) = <DhP256HkdfSha256 as Kem>::gen_keypair(&mut thread_rng());
 
         Vault {
-            _vault_public_key: PublicKey::P256(public_key),
-            master_keys,
-            current_master_key_id: 0,
+            vault_public_key: PublicKey::P256(public_key),
+            master_keys_path: PathBuf::new(),
+            master_keys: RwLock::new(MasterKeys {
+                keys: master_keys,
+                current_id: 0,
+            }),
             master_key_storage: Arc::new(NullKeyStorage),
         }
     }
@@ -671,6 +759,30 @@ mod tests {
         assert_eq!(decrypted, b"hello");
     }
 
+    #[test]
+    fn vault_key_version_test() {
+        let vault = random_null_vault();
+        let encrypted = vault
+            .encrypt_payload(&KeyId::Master, b"hello", None)
+            .unwrap();
+        {
+            let mut master_keys = vault.master_keys.write();
+            master_keys.keys.insert(1, EncryptionKey::random());
+            master_keys.current_id = 1;
+        }
+        let reencrypted = vault
+            .encrypt_payload(&KeyId::Master, b"hello", None)
+            .unwrap();
+        assert_eq!(
+            VaultPayload::from_slice(&reencrypted).unwrap().key_version,
+            1
+        );
+
+        // Both payloads must be decrypted using the key they were encrypted with.
+        assert_eq!(vault.decrypt_payload(&encrypted, None).unwrap(), b"hello");
+        assert_eq!(vault.decrypt_payload(&reencrypted, None).unwrap(), b"hello");
+    }
+
     #[test]
     fn vault_permissions_test() {
         let vault = random_null_vault();
@@ -17,11 +17,16 @@ pub struct ViewEntry {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EntryMapping {
     pub source: Header,
+    /// The related collection containing the source document, or `None` if
+    /// the source is in the view's own collection.
+    pub collection: Option<CollectionName>,
     pub value: Bytes,
 }
 
 pub mod integrity_scanner;
 pub mod mapper;
+pub mod reduce_cache;
+pub mod verification;
 
 pub fn view_entries_tree_name(view_name: &impl Display) -> String {
     format!("view.{view_name:#}")
@@ -36,6 +41,22 @@ pub fn view_invalidated_docs_tree_name(view_name: &impl Display) -> String {
     format!("view.{view_name:#}.invalidated")
 }
 
+/// Used to store Document ID -> Key mappings for the documents of one of the
+/// view's related collections.
+pub fn view_related_document_map_tree_name(
+    view_name: &impl Display,
+    collection: &CollectionName,
+) -> String {
+    format!("view.{view_name:#}.document-map.{collection:#}")
+}
+
+pub fn view_related_invalidated_docs_tree_name(
+    view_name: &impl Display,
+    collection: &CollectionName,
+) -> String {
+    format!("view.{view_name:#}.invalidated.{collection:#}")
+}
+
 pub fn view_versions_tree_name(collection: &CollectionName) -> String {
     format!("view-versions.{collection:#}")
 }
@@ -13,7 +13,10 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use super::mapper::{Map, Mapper};
-use super::{view_invalidated_docs_tree_name, view_versions_tree_name};
+use super::{
+    view_invalidated_docs_tree_name, view_related_document_map_tree_name,
+    view_related_invalidated_docs_tree_name, view_versions_tree_name,
+};
 use crate::database::{document_tree_name, Database};
 use crate::tasks::handle::Handle;
 use crate::tasks::{Job, Keyed, Task};
@@ -86,7 +89,39 @@ impl Job for IntegrityScanner {
                 view_invalidated_docs_tree_name(&self.scan.view_name),
             )?;
 
-            let transaction = roots.transaction(&[invalidated_entries_tree, view_versions_tree])?;
+            // Each related collection's documents are queued in their own
+            // invalidated list, which is stored in the related collection's
+            // vault.
+            let related_collections = self
+                .database
+                .data
+                .schema
+                .view_by_name(&self.scan.view_name)?
+                .related_collections();
+            let mut trees = vec![invalidated_entries_tree, view_versions_tree];
+            let mut related_missing_entries = Vec::with_capacity(related_collections.len());
+            for collection in &related_collections {
+                roots.delete_tree(view_related_invalidated_docs_tree_name(
+                    &self.scan.view_name,
+                    collection,
+                ))?;
+                roots.delete_tree(view_related_document_map_tree_name(
+                    &self.scan.view_name,
+                    collection,
+                ))?;
+                let related_documents =
+                    roots.tree(self.database.collection_tree::<Versioned, _>(
+                        collection,
+                        document_tree_name(collection),
+                    )?)?;
+                related_missing_entries.push(tree_keys::<Versioned>(&related_documents)?);
+                trees.push(self.database.collection_tree::<Unversioned, _>(
+                    collection,
+                    view_related_invalidated_docs_tree_name(&self.scan.view_name, collection),
+                )?);
+            }
+
+            let transaction = roots.transaction(&trees)?;
             {
                 let mut view_versions = transaction.tree::<Unversioned>(1).unwrap();
                 view_versions.set(
@@ -100,8 +135,21 @@ impl Job for IntegrityScanner {
                     .collect::<Vec<_>>();
                 missing_entries.sort();
                 invalidated_entries.modify(missing_entries, Operation::Set(ArcBytes::default()))?;
+                for (index, missing_entries) in related_missing_entries.into_iter().enumerate() {
+                    let mut invalidated_entries =
+                        transaction.tree::<Unversioned>(index + 2).unwrap();
+                    let mut missing_entries = missing_entries
+                        .into_iter()
+                        .map(|id| ArcBytes::from(id.to_vec()))
+                        .collect::<Vec<_>>();
+                    missing_entries.sort();
+                    invalidated_entries
+                        .modify(missing_entries, Operation::Set(ArcBytes::default()))?;
+                }
             }
             transaction.commit()?;
+            self.database
+                .invalidate_all_cached_reductions(&self.scan.view_name);
 
             Some(Arc::new(Mutex::new(Some(
                 self.database
@@ -141,7 +189,7 @@ pub struct ViewVersion {
 }
 
 impl ViewVersion {
-    const CURRENT_VERSION: u8 = 3;
+    const CURRENT_VERSION: u8 = 4;
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, crate::Error> {
         match pot::from_slice(bytes) {
@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::collections::hash_map::RandomState;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::arc_bytes::{ArcBytes, OwnedBytes};
@@ -17,7 +18,8 @@ use crate::database::{deserialize_document, document_tree_name, Database};
 use crate::tasks::{Job, Keyed, Task};
 use crate::views::{
     view_document_map_tree_name, view_entries_tree_name, view_invalidated_docs_tree_name,
-    EntryMapping, ViewEntry,
+    view_related_document_map_tree_name, view_related_invalidated_docs_tree_name, EntryMapping,
+    ViewEntry,
 };
 use crate::Error;
 
@@ -41,14 +43,7 @@ impl Job for Mapper {
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     #[allow(clippy::too_many_lines)]
     fn execute(&mut self) -> Result<Self::Output, Error> {
-        let documents =
-            self.database
-                .roots()
-                .tree(self.database.collection_tree::<Versioned, _>(
-                    &self.map.collection,
-                    document_tree_name(&self.map.collection),
-                )?)?;
-
+        let started_at = Instant::now();
         let view_entries =
             self.database
                 .roots()
@@ -57,22 +52,6 @@ impl Job for Mapper {
                     view_entries_tree_name(&self.map.view_name),
                 )?)?;
 
-        let document_map =
-            self.database
-                .roots()
-                .tree(self.database.collection_tree::<Unversioned, _>(
-                    &self.map.collection,
-                    view_document_map_tree_name(&self.map.view_name),
-                )?)?;
-
-        let invalidated_entries =
-            self.database
-                .roots()
-                .tree(self.database.collection_tree::<Unversioned, _>(
-                    &self.map.collection,
-                    view_invalidated_docs_tree_name(&self.map.view_name),
-                )?)?;
-
         let transaction_id = self
             .database
             .last_transaction_id()?
@@ -80,21 +59,64 @@ impl Job for Mapper {
 
         let storage = self.database.clone();
         let map_request = self.map.clone();
+        let related_collections = self
+            .database
+            .data
+            .schema
+            .view_by_name(&self.map.view_name)?
+            .related_collections();
+
+        // Map the invalidated documents of the view's own collection, followed
+        // by those of each related collection.
+        for related in std::iter::once(None).chain(related_collections.iter().map(Some)) {
+            let (collection, document_map_name, invalidated_entries_name) = match related {
+                Some(related) => (
+                    related,
+                    view_related_document_map_tree_name(&self.map.view_name, related),
+                    view_related_invalidated_docs_tree_name(&self.map.view_name, related),
+                ),
+                None => (
+                    &self.map.collection,
+                    view_document_map_tree_name(&self.map.view_name),
+                    view_invalidated_docs_tree_name(&self.map.view_name),
+                ),
+            };
 
-        map_view(
-            &invalidated_entries,
-            &document_map,
-            &documents,
-            &view_entries,
-            &storage,
-            &map_request,
-        )?;
+            let documents =
+                self.database
+                    .roots()
+                    .tree(self.database.collection_tree::<Versioned, _>(
+                        collection,
+                        document_tree_name(collection),
+                    )?)?;
+
+            let document_map = self.database.roots().tree(
+                self.database
+                    .collection_tree::<Unversioned, _>(collection, document_map_name)?,
+            )?;
+
+            let invalidated_entries = self.database.roots().tree(
+                self.database
+                    .collection_tree::<Unversioned, _>(collection, invalidated_entries_name)?,
+            )?;
+
+            map_view(
+                &invalidated_entries,
+                &document_map,
+                &documents,
+                &view_entries,
+                &storage,
+                &map_request,
+                related,
+            )?;
+        }
 
         self.database.storage.instance.tasks().mark_view_updated(
             self.map.database.clone(),
             self.map.collection.clone(),
             self.map.view_name.clone(),
             transaction_id,
+            started_at,
         );
 
         Ok(transaction_id)
@@ -108,6 +130,7 @@ fn map_view(
     view_entries: &Tree<Unversioned, AnyFile>,
     database: &Database,
     map_request: &Map,
+    related: Option<&CollectionName>,
 ) -> Result<(), Error> {
     const CHUNK_SIZE: usize = 100_000;
     // Only do any work if there are invalidated documents to process
@@ -125,7 +148,7 @@ fn map_view(
                 Box::new(documents.clone()),
                 Box::new(view_entries.clone()),
             ])?;
-        {
+        let changed_keys = {
             let view = database
                 .data
                 .schema
@@ -138,7 +161,7 @@ fn map_view(
             let document_map = transaction.unlocked_tree(1).unwrap();
             let documents = transaction.unlocked_tree(2).unwrap();
             let view_entries = transaction.unlocked_tree(3).unwrap();
-            DocumentRequest {
+            let changed_keys = DocumentRequest {
                 document_ids: document_ids.clone(),
                 map_request,
                 database,
@@ -146,13 +169,19 @@ fn map_view(
                 documents,
                 view_entries,
                 view,
+                related,
             }
             .map()?;
 
             let mut invalidated_entries = transaction.tree::<Unversioned>(0).unwrap();
             invalidated_entries.modify(document_ids, nebari::tree::Operation::Remove)?;
-        }
+            changed_keys
+        };
         transaction.commit()?;
+        database.invalidate_cached_reductions(
+            &map_request.view_name,
+            changed_keys.iter().map(ArcBytes::as_slice),
+        );
     }
 
     Ok(())
@@ -167,6 +196,9 @@ pub struct DocumentRequest<'a> {
     pub documents: &'a UnlockedTransactionTree<AnyFile>,
     pub view_entries: &'a UnlockedTransactionTree<AnyFile>,
     pub view: &'a dyn Serialized,
+    /// The related collection the documents belong to, or `None` if they
+    /// belong to the view's own collection.
+    pub related: Option<&'a CollectionName>,
 }
 
 type DocumentIdPayload = (ArcBytes<'static>, Option<ArcBytes<'static>>);
@@ -209,6 +241,7 @@ impl<'a> DocumentRequest<'a> {
         batch_receiver: &flume::Receiver<BatchPayload>,
         mapped_sender: flume::Sender<Batch>,
         view: &dyn Serialized,
+        related: Option<&CollectionName>,
         parallelization: usize,
     ) -> Result<(), Error> {
         // Process batches
@@ -225,7 +258,11 @@ impl<'a> DocumentRequest<'a> {
                             let document = deserialize_document(&document)?;
 
                             // Call the schema map function
-                            view.map(&document).map_err(bonsaidb_core::Error::from)?
+                            match related {
+                                Some(collection) => view.map_related(collection, &document),
+                                None => view.map(&document),
+                            }
+                            .map_err(bonsaidb_core::Error::from)?
                         } else {
                             // Get multiple didn't return this document ID.
                             Vec::new()
@@ -301,6 +338,7 @@ impl<'a> DocumentRequest<'a> {
     fn update_view_entries(
         view: &dyn Serialized,
         map_request: &Map,
+        related: Option<&CollectionName>,
         view_entries: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
         all_keys: BTreeSet<ArcBytes<'static>>,
         view_entries_to_clean: BTreeMap<ArcBytes<'static>, HashSet<ArcBytes<'static>>>,
@@ -309,6 +347,7 @@ impl<'a> DocumentRequest<'a> {
         let mut updater = ViewEntryUpdater {
             view,
             map_request,
+            related,
             view_entries_to_clean,
             new_mappings,
             result: Ok(()),
@@ -329,8 +368,10 @@ impl<'a> DocumentRequest<'a> {
         mapped_receiver: &flume::Receiver<Batch>,
         view: &dyn Serialized,
         map_request: &Map,
+        related: Option<&CollectionName>,
         document_map: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
         view_entries: &mut LockedTransactionTree<'_, Unversioned, AnyFile>,
+        changed_keys: &mut BTreeSet<ArcBytes<'static>>,
     ) -> Result<(), Error> {
         while let Ok(Batch {
             document_ids,
@@ -347,10 +388,12 @@ impl<'a> DocumentRequest<'a> {
                 document_keys,
                 &mut all_keys,
             )?;
+            changed_keys.extend(all_keys.iter().cloned());
 
             Self::update_view_entries(
                 view,
                 map_request,
+                related,
                 view_entries,
                 all_keys,
                 view_entries_to_clean,
@@ -360,7 +403,10 @@ impl<'a> DocumentRequest<'a> {
         Ok(())
     }
 
-    pub fn map(&mut self) -> Result<(), Error> {
+    /// Maps the requested documents, returning the keys of the view entries
+    /// that were modified.
+    pub fn map(&mut self) -> Result<BTreeSet<ArcBytes<'static>>, Error> {
+        let mut changed_keys = BTreeSet::new();
         let (batch_sender, batch_receiver) = flume::bounded(1);
         let (mapped_sender, mapped_receiver) = flume::bounded(1);
 
@@ -371,6 +417,7 @@ impl<'a> DocumentRequest<'a> {
                     &batch_receiver,
                     mapped_sender,
                     self.view,
+                    self.related,
                     self.database.storage().parallelization(),
                 )
             })
@@ -381,8 +428,10 @@ impl<'a> DocumentRequest<'a> {
                     &mapped_receiver,
                     self.view,
                     self.map_request,
+                    self.related,
                     &mut document_map,
                     &mut view_entries,
+                    &mut changed_keys,
                 )
             })
             .run()
@@ -390,7 +439,7 @@ impl<'a> DocumentRequest<'a> {
             result?;
         }
 
-        Ok(())
+        Ok(changed_keys)
     }
 }
 
@@ -412,6 +461,7 @@ impl Keyed<Task> for Mapper {
 struct ViewEntryUpdater<'a> {
     view: &'a dyn Serialized,
     map_request: &'a Map,
+    related: Option<&'a CollectionName>,
     view_entries_to_clean: BTreeMap<ArcBytes<'static>, HashSet<ArcBytes<'static>>>,
     new_mappings: BTreeMap<ArcBytes<'static>, Vec<map::Serialized>>,
     result: Result<(), Error>,
@@ -434,9 +484,10 @@ impl<'a> ViewEntryUpdater<'a> {
             });
         let key = key.to_owned();
         if let Some(document_ids) = self.view_entries_to_clean.remove(&key) {
-            view_entry
-                .mappings
-                .retain(|m| !document_ids.contains(m.source.id.as_ref()));
+            view_entry.mappings.retain(|m| {
+                m.collection.as_ref() != self.related
+                    || !document_ids.contains(m.source.id.as_ref())
+            });
 
             if view_entry.mappings.is_empty() && !self.new_mappings.contains_key(&key[..]) {
                 return KeyOperation::Remove;
@@ -476,14 +527,20 @@ impl<'a> ViewEntryUpdater<'a> {
                     }));
                     return KeyOperation::Skip;
                 }
-                let entry_mapping = EntryMapping { source, value };
+                let entry_mapping = EntryMapping {
+                    source,
+                    collection: self.related.cloned(),
+                    value,
+                };
 
                 // attempt to update an existing
                 // entry for this document, if
                 // present
                 let mut found = false;
                 for mapping in &mut view_entry.mappings {
-                    if mapping.source.id == entry_mapping.source.id {
+                    if mapping.source.id == entry_mapping.source.id
+                        && mapping.collection == entry_mapping.collection
+                    {
                         found = true;
                         mapping.source.revision = entry_mapping.source.revision;
                         mapping.value = entry_mapping.value.clone();
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+
+use bonsaidb_core::connection::{QueryKeyBytes, SerializedQueryKey};
+use bonsaidb_core::schema::ViewName;
+use parking_lot::Mutex;
+
+/// The maximum number of reduced values kept before the cache is cleared.
+const MAX_ENTRIES: usize = 1_024;
+
+/// Caches the reduced values of views, keyed by the view and the query key
+/// that was reduced.
+///
+/// Reducing a range that spans many keys requires re-reducing every key's
+/// stored value. Coarse aggregates over hierarchical keys (e.g., every product
+/// within a region, queried by the region's key prefix) are often requested
+/// repeatedly. Each cached value is kept until an entry within the keys it
+/// reduced is modified, so changing one region's products does not discard
+/// the cached values of the other regions.
+///
+/// Each modification increments a generation counter. Readers note the
+/// generation before reading the view and only store their result if no
+/// modification was committed in the meantime.
+#[derive(Debug, Default)]
+pub struct ReduceCache {
+    state: Mutex<ReduceCacheState>,
+}
+
+#[derive(Debug, Default)]
+struct ReduceCacheState {
+    generation: u64,
+    entries: HashMap<ReduceCacheKey, CachedReduction>,
+}
+
+#[derive(Debug, Hash, Eq, PartialEq)]
+pub struct ReduceCacheKey {
+    view: ViewName,
+    key: Option<Vec<u8>>,
+}
+
+impl ReduceCacheKey {
+    /// Returns a cache key for reducing `view` with `key`, or `None` if the key
+    /// could not be serialized.
+    pub fn new(view: ViewName, key: Option<&SerializedQueryKey>) -> Option<Self> {
+        let key = match key {
+            Some(key) => Some(bincode::serialize(key).ok()?),
+            None => None,
+        };
+        Some(Self { view, key })
+    }
+}
+
+#[derive(Debug)]
+struct CachedReduction {
+    /// The keys that were reduced, or `None` if every key was reduced.
+    keys: Option<QueryKeyBytes>,
+    value: Vec<u8>,
+}
+
+impl CachedReduction {
+    fn includes(&self, key: &[u8]) -> bool {
+        match &self.keys {
+            None => true,
+            Some(QueryKeyBytes::Matches(matches)) => &matches[..] == key,
+            Some(QueryKeyBytes::Multiple(keys)) => keys.iter().any(|k| &k[..] == key),
+            Some(QueryKeyBytes::Range(range)) => range.map_ref(|bound| &bound[..]).contains(&key),
+        }
+    }
+}
+
+impl ReduceCache {
+    /// Returns the current generation and the cached value for `key`, if one
+    /// is present.
+    pub fn get(&self, key: &ReduceCacheKey) -> (u64, Option<Vec<u8>>) {
+        let state = self.state.lock();
+        (
+            state.generation,
+            state.entries.get(key).map(|cached| cached.value.clone()),
+        )
+    }
+
+    /// Stores `value`, the reduction of `keys`, for `key` if no view entries
+    /// have been modified since `generation` was returned from [`Self::get()`].
+    pub fn insert(
+        &self,
+        generation: u64,
+        key: ReduceCacheKey,
+        keys: Option<QueryKeyBytes>,
+        value: Vec<u8>,
+    ) {
+        let mut state = self.state.lock();
+        if state.generation == generation {
+            if state.entries.len() >= MAX_ENTRIES {
+                state.entries.clear();
+            }
+            state.entries.insert(key, CachedReduction { keys, value });
+        }
+    }
+
+    /// Discards the cached values of `view` that reduced any of `keys`. Must
+    /// be called after committing changes to the entries of `view` with `keys`.
+    pub fn invalidate_keys<'k>(&self, view: &ViewName, keys: impl IntoIterator<Item = &'k [u8]>) {
+        let keys = keys.into_iter().collect::<Vec<_>>();
+        let mut state = self.state.lock();
+        state.generation = state.generation.wrapping_add(1);
+        state.entries.retain(|cache_key, cached| {
+            &cache_key.view != view || !keys.iter().any(|key| cached.includes(key))
+        });
+    }
+
+    /// Discards all cached values of `view`. Must be called after committing
+    /// any change to the view's entries whose keys are not known.
+    pub fn invalidate_view(&self, view: &ViewName) {
+        let mut state = self.state.lock();
+        state.generation = state.generation.wrapping_add(1);
+        state.entries.retain(|cache_key, _| &cache_key.view != view);
+    }
+}
+
+#[test]
+fn invalidation_is_limited_to_reduced_keys() {
+    use bonsaidb_core::arc_bytes::serde::Bytes;
+    use bonsaidb_core::connection::{Bound, Range};
+    use bonsaidb_core::schema::CollectionName;
+
+    fn cache_key(keys: Option<&QueryKeyBytes>) -> ReduceCacheKey {
+        let key = keys.map(|keys| SerializedQueryKey {
+            keys: keys.clone(),
+            description: None,
+        });
+        ReduceCacheKey::new(view(), key.as_ref()).unwrap()
+    }
+
+    fn view() -> ViewName {
+        ViewName::new(CollectionName::private("collection"), "view").unwrap()
+    }
+
+    let first = QueryKeyBytes::Range(Range {
+        start: Bound::Included(Bytes::from(vec![1])),
+        end: Bound::Excluded(Bytes::from(vec![2])),
+    });
+    let second = QueryKeyBytes::Matches(Bytes::from(vec![2, 0]));
+
+    let cache = ReduceCache::default();
+    let (generation, _) = cache.get(&cache_key(None));
+    for keys in [None, Some(first.clone()), Some(second.clone())] {
+        cache.insert(
+            generation,
+            cache_key(keys.as_ref()),
+            keys,
+            b"reduced".to_vec(),
+        );
+    }
+
+    // Only the values that reduced the modified key are discarded.
+    cache.invalidate_keys(&view(), [&[1, 5][..]]);
+    assert!(cache.get(&cache_key(None)).1.is_none());
+    assert!(cache.get(&cache_key(Some(&first))).1.is_none());
+    assert!(cache.get(&cache_key(Some(&second))).1.is_some());
+
+    // Values computed before an invalidation are not stored.
+    cache.invalidate_view(&view());
+    assert!(cache.get(&cache_key(Some(&second))).1.is_none());
+    cache.insert(
+        generation,
+        cache_key(Some(&first)),
+        Some(first.clone()),
+        b"stale".to_vec(),
+    );
+    assert!(cache.get(&cache_key(Some(&first))).1.is_none());
+}
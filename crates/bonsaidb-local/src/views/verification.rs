@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, HashSet};
+use std::convert::Infallible;
+
+use bonsaidb_core::arc_bytes::serde::Bytes;
+use bonsaidb_core::connection::HasSession;
+use bonsaidb_core::document::{DocumentId, Revision};
+use bonsaidb_core::permissions::bonsai::{
+    collection_resource_name, view_resource_name, BonsaiAction, DatabaseAction, DocumentAction,
+    ViewAction,
+};
+use bonsaidb_core::schema::{self, view, ViewName};
+use nebari::tree::{ScanEvaluation, Unversioned, Versioned};
+use nebari::AbortError;
+
+use crate::database::{deserialize_document, document_tree_name};
+use crate::views::{view_entries_tree_name, view_invalidated_docs_tree_name, ViewEntry};
+use crate::{Database, DatabaseNonBlocking, Error};
+
+/// The result of comparing a view's stored mappings against the mappings its
+/// source documents currently produce. Returned from
+/// [`Database::verify_view()`].
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[must_use]
+pub struct ViewVerificationReport {
+    /// The number of documents whose mappings were compared.
+    pub documents_checked: u64,
+    /// The number of documents that were skipped because they are waiting to
+    /// be mapped by the next view update.
+    pub documents_pending_update: u64,
+    /// Mappings produced by a document that are not stored in the view.
+    pub missing: Vec<ViewMappingDiscrepancy>,
+    /// Mappings stored in the view that no document currently produces.
+    pub extra: Vec<ViewMappingDiscrepancy>,
+    /// Mappings whose stored value or source revision differ from what the
+    /// document currently produces.
+    pub mismatched: Vec<ViewMappingDiscrepancy>,
+}
+
+impl ViewVerificationReport {
+    /// Returns true if no missing, extra, or mismatched mappings were found.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// A single mapping that differs between a view and its source documents.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ViewMappingDiscrepancy {
+    /// The id of the document the mapping belongs to.
+    pub source: DocumentId,
+    /// The serialized key of the mapping.
+    pub key: Bytes,
+}
+
+impl Database {
+    /// Verifies that the stored entries of view `V` match the mappings its
+    /// source documents currently produce.
+    ///
+    /// This is a read-only, offline check intended for diagnosing view
+    /// corruption: every document in the view's collection is mapped and
+    /// compared against the stored view entries. The view is not updated
+    /// beforehand, and documents that are already waiting to be re-mapped are
+    /// excluded from the comparison. All expected mappings are held in memory
+    /// while the view is scanned. Mappings produced from the view's
+    /// [related collections](schema::view::ViewSchema::related_collections)
+    /// are not verified.
+    ///
+    /// The session must be allowed to query the view and to list the
+    /// documents in its collection.
+    pub fn verify_view<V: schema::SerializedView>(
+        &self,
+    ) -> Result<ViewVerificationReport, bonsaidb_core::Error> {
+        let view = self.schematic().view::<V>()?;
+        self.verify_view_by_name(&view.view_name())
+    }
+
+    /// Verifies that the stored entries of the view named `view_name` match
+    /// the mappings its source documents currently produce. See
+    /// [`Self::verify_view()`] for more information.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        level = "trace",
+        skip(self),
+        fields(
+            database = self.name(),
+        )
+    ))]
+    pub fn verify_view_by_name(
+        &self,
+        view_name: &ViewName,
+    ) -> Result<ViewVerificationReport, bonsaidb_core::Error> {
+        let view = self.schematic().view_by_name(view_name)?;
+        self.check_permission(
+            view_resource_name(self.name(), &view.view_name()),
+            &BonsaiAction::Database(DatabaseAction::View(ViewAction::Query)),
+        )?;
+        self.check_permission(
+            collection_resource_name(self.name(), &view.collection()),
+            &BonsaiAction::Database(DatabaseAction::Document(DocumentAction::List)),
+        )?;
+
+        verify(self, view).map_err(bonsaidb_core::Error::from)
+    }
+}
+
+fn verify(
+    database: &Database,
+    view: &dyn view::Serialized,
+) -> Result<ViewVerificationReport, Error> {
+    let collection = view.collection();
+    let view_name = view.view_name();
+    let mut report = ViewVerificationReport::default();
+
+    let invalidated_docs = database
+        .roots()
+        .tree(database.collection_tree::<Unversioned, _>(
+            &collection,
+            view_invalidated_docs_tree_name(&view_name),
+        )?)?;
+    let mut pending = HashSet::new();
+    invalidated_docs.scan::<Infallible, _, _, _, _>(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |key, _| {
+            if let Ok(id) = DocumentId::try_from(key.as_slice()) {
+                pending.insert(id);
+            }
+            ScanEvaluation::Skip
+        },
+        |_, _, _| Ok(()),
+    )?;
+
+    // Map every document that isn't waiting on the mapper, recording what the
+    // view should contain.
+    let mut expected = BTreeMap::<(Bytes, DocumentId), (Revision, Bytes)>::new();
+    let mut documents_checked = 0;
+    let mut documents_pending_update = 0;
+    let documents = database.roots().tree(
+        database.collection_tree::<Versioned, _>(&collection, document_tree_name(&collection))?,
+    )?;
+    documents.scan(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |key, _| {
+            let is_pending =
+                DocumentId::try_from(key.as_slice()).map_or(false, |id| pending.contains(&id));
+            if is_pending {
+                documents_pending_update += 1;
+                ScanEvaluation::Skip
+            } else {
+                ScanEvaluation::ReadData
+            }
+        },
+        |_, _, document| {
+            let document = deserialize_document(&document).map_err(AbortError::Other)?;
+            let mappings = view
+                .map(&document)
+                .map_err(|err| AbortError::Other(Error::from(bonsaidb_core::Error::from(err))))?;
+            for mapping in mappings {
+                expected.insert(
+                    (mapping.key, mapping.source.id),
+                    (mapping.source.revision, mapping.value),
+                );
+            }
+            documents_checked += 1;
+            Ok(())
+        },
+    )?;
+    report.documents_checked = documents_checked;
+    report.documents_pending_update = documents_pending_update;
+
+    // Walk the stored entries, removing each mapping from the expected set as
+    // it is matched. Whatever remains afterwards was never stored.
+    let view_entries =
+        database
+            .roots()
+            .tree(database.collection_tree::<Unversioned, _>(
+                &collection,
+                view_entries_tree_name(&view_name),
+            )?)?;
+    view_entries.scan(
+        &(..),
+        true,
+        |_, _, _| ScanEvaluation::ReadData,
+        |_, _| ScanEvaluation::ReadData,
+        |_, _, entry| {
+            let entry = bincode::deserialize::<ViewEntry>(&entry)
+                .map_err(|err| AbortError::Other(Error::from(err)))?;
+            for mapping in entry.mappings {
+                if mapping.collection.is_some() || pending.contains(&mapping.source.id) {
+                    continue;
+                }
+
+                let expected_mapping =
+                    expected.remove(&(entry.key.clone(), mapping.source.id.clone()));
+                let discrepancy = ViewMappingDiscrepancy {
+                    source: mapping.source.id,
+                    key: entry.key.clone(),
+                };
+                match expected_mapping {
+                    Some((revision, value)) => {
+                        if revision != mapping.source.revision || value != mapping.value {
+                            report.mismatched.push(discrepancy);
+                        }
+                    }
+                    None => report.extra.push(discrepancy),
+                }
+            }
+            Ok(())
+        },
+    )?;
+
+    report.missing = expected
+        .into_keys()
+        .map(|(key, source)| ViewMappingDiscrepancy { source, key })
+        .collect();
+
+    Ok(report)
+}
@@ -74,6 +74,13 @@ struct CollectionAttribute {
     encryption_key: Option<Expr>,
     encryption_required: bool,
     encryption_optional: bool,
+    #[attribute(example = "10")]
+    history_limit: Option<Expr>,
+    #[attribute(example = "Random")]
+    id_strategy: Option<Ident>,
+    publish_changes: bool,
+    #[attribute(example = "validate_contents")]
+    validate: Option<Expr>,
     #[attribute(example = "u64")]
     primary_key: Option<Type>,
     #[attribute(example = "self.0 or something(self)")]
@@ -85,7 +92,7 @@ struct CollectionAttribute {
 /// Derives the `bonsaidb::core::schema::Collection` trait.
 /// `#[collection(authority = "Authority", name = "Name", views = [a, b, c])]`
 #[manyhow]
-#[proc_macro_derive(Collection, attributes(collection, natural_id))]
+#[proc_macro_derive(Collection, attributes(collection, natural_id, natural_key))]
 pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
     let DeriveInput {
         attrs,
@@ -106,10 +113,16 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
         encryption_key,
         encryption_required,
         encryption_optional,
+        history_limit,
+        id_strategy,
+        publish_changes,
+        validate,
     } = CollectionAttribute::from_attributes(&attrs)?;
 
+    let mut natural_key = None;
     if let Data::Struct(DataStruct { fields, .. }) = data {
         let mut previous: Option<syn::Attribute> = None;
+        let mut previous_natural_key: Option<syn::Attribute> = None;
         for (
             idx,
             Field {
@@ -117,6 +130,26 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
             },
         ) in fields.into_iter().enumerate()
         {
+            let ident = if let Some(ident) = ident {
+                quote!(#ident)
+            } else {
+                let idx = Index::from(idx);
+                quote_spanned!(ty.span()=> #idx)
+            };
+            if let Some(attr) = attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("natural_key"))
+            {
+                if let Some(previous) = &previous_natural_key {
+                    bail!(error_message!(attr,
+                            "marked multiple fields as `natural_key`";
+                            note="currently only one field can be marked as `natural_key`";
+                            help="implement `NaturalKey` manually instead")
+                    .join(error_message!(previous, "previous `natural_key`")));
+                }
+                previous_natural_key = Some(attr.clone());
+                natural_key = Some((ident.clone(), ty.clone()));
+            }
             if let Some(attr) = attrs
                 .into_iter()
                 .find(|attr| attr.path().is_ident("natural_id"))
@@ -134,12 +167,6 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
                         .join(error_message!(natural_id, "`natural_id` expression is specified here")));
                 }
                 previous = Some(attr);
-                let ident = if let Some(ident) = ident {
-                    quote!(#ident)
-                } else {
-                    let idx = Index::from(idx);
-                    quote_spanned!(ty.span()=> #idx)
-                };
                 natural_id = Some(parse_quote!(Some(Clone::clone(&self.#ident))));
                 if primary_key.is_none() {
                     primary_key = Some(ty);
@@ -166,6 +193,12 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
                 "`natural_id` must be manually implemented when using `serialization = None`"
             );
         }
+        if let Some((_, ty)) = &natural_key {
+            bail!(
+                ty,
+                "`natural_key` must be manually implemented when using `serialization = None`"
+            );
+        }
 
         TokenStream::new()
     } else {
@@ -205,6 +238,24 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
         }
     };
 
+    let (natural_key, natural_key_view) = natural_key
+        .map(|(field, ty)| {
+            (
+                quote! {
+                    impl #impl_generics #core::schema::NaturalKey for #ident #ty_generics #where_clause {
+                        type NaturalKey = #ty;
+
+                        fn natural_key(contents: &Self::Contents) -> Option<Self::NaturalKey> {
+                            #[allow(clippy::clone_on_copy)]
+                            Some(Clone::clone(&contents.#field))
+                        }
+                    }
+                },
+                quote!(schema.define_view(#core::schema::ByNaturalKey::<Self>::default())?;),
+            )
+        })
+        .unzip();
+
     let name = authority.map_or_else(
         || quote!(#core::schema::Qualified::private(#name)),
         |authority| quote!(#core::schema::Qualified::new(#authority, #name)),
@@ -229,6 +280,40 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
         }
     });
 
+    let history_limit = history_limit.map(|history_limit| {
+        quote! {
+            fn history_limit() -> u32 {
+                #history_limit
+            }
+        }
+    });
+
+    let id_strategy = id_strategy.map(|id_strategy| {
+        quote! {
+            fn id_strategy() -> #core::schema::IdStrategy {
+                #core::schema::IdStrategy::#id_strategy
+            }
+        }
+    });
+
+    let publish_changes = publish_changes.then(|| {
+        quote! {
+            fn publish_changes() -> bool {
+                true
+            }
+        }
+    });
+
+    let validate = validate.map(|validate| {
+        quote! {
+            fn validate_document(contents: &[u8]) -> Result<(), String> {
+                let contents = <Self as #core::schema::SerializedCollection>::deserialize(contents)
+                    .map_err(|err| err.to_string())?;
+                #validate(&contents)
+            }
+        }
+    });
+
     Ok(quote! {
         impl #impl_generics #core::schema::Collection for #ident #ty_generics #where_clause {
             type PrimaryKey = #primary_key;
@@ -238,11 +323,17 @@ pub fn collection_derive(input: proc_macro::TokenStream) -> Result {
             }
             fn define_views(schema: &mut #core::schema::Schematic) -> Result<(), #core::Error> {
                 #( schema.define_view(#views)?; )*
+                #natural_key_view
                 Ok(())
             }
             #encryption
+            #history_limit
+            #id_strategy
+            #publish_changes
+            #validate
         }
         #serialization
+        #natural_key
     })
 }
 /// Derives the `bonsaidb::core::schema::View` trait.
@@ -256,7 +347,7 @@ pub fn view_derive(input: proc_macro::TokenStream) -> Result {
 }
 /// Derives the `bonsaidb::core::schema::ViewSchema` trait.
 #[manyhow]
-/// `#[view_schema(version = 1, policy = Unique, view=ViewType, mapped_key=KeyType<'doc>)]`
+/// `#[view_schema(version = 1, policy = Unique, view=ViewType, mapped_key=KeyType<'doc>, related = [A, B])]`
 ///
 /// All attributes are optional.
 #[proc_macro_derive(ViewSchema, attributes(view_schema))]
@@ -2,8 +2,8 @@ use core::fmt::Debug;
 
 use bonsaidb::core::document::{CollectionDocument, Emit, KeyId};
 use bonsaidb::core::schema::{
-    Collection, CollectionMapReduce, DefaultSerialization, DefaultViewSerialization, Name,
-    Qualified, Schematic, SerializedCollection, View, ViewMapResult, ViewSchema,
+    Collection, CollectionMapReduce, DefaultSerialization, DefaultViewSerialization, IdStrategy,
+    Name, Qualified, Schematic, SerializedCollection, View, ViewMapResult, ViewSchema,
 };
 use serde::{Deserialize, Serialize};
 
@@ -141,6 +141,58 @@ fn encryption_key() {
     struct Test;
 }
 
+#[test]
+fn history_limit() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", history_limit = 10)]
+    struct Test;
+
+    assert_eq!(Test::history_limit(), 10);
+}
+
+#[test]
+fn id_strategy() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", id_strategy = TimeOrderedRandom)]
+    struct Test;
+
+    assert_eq!(Test::id_strategy(), IdStrategy::TimeOrderedRandom);
+}
+
+#[test]
+fn publish_changes() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", publish_changes)]
+    struct Test;
+
+    assert!(Test::publish_changes());
+}
+
+#[test]
+fn validate() {
+    #[derive(Collection, Debug, Deserialize, Serialize)]
+    #[collection(name = "Name", validate = validate_test)]
+    struct Test {
+        value: u32,
+    }
+
+    fn validate_test(test: &Test) -> Result<(), String> {
+        if test.value > 10 {
+            Err(String::from("too large"))
+        } else {
+            Ok(())
+        }
+    }
+
+    let valid = Test::serialize(&Test { value: 1 }).unwrap();
+    assert_eq!(Test::validate_document(&valid), Ok(()));
+    let invalid = Test::serialize(&Test { value: 11 }).unwrap();
+    assert_eq!(
+        Test::validate_document(&invalid),
+        Err(String::from("too large"))
+    );
+}
+
 #[test]
 fn primary_key() {
     #[derive(Collection, Debug, Deserialize, Serialize)]
@@ -31,3 +31,22 @@ fn serialization_none() {
     #[view(serialization = None)]
     struct TestView;
 }
+
+#[test]
+fn related_collections() {
+    use bonsaidb::core::schema::ViewSchema;
+
+    #[derive(Collection, Debug)]
+    #[collection(name = "related", authority = "authority")]
+    struct RelatedCollection;
+
+    #[derive(View, ViewSchema, Debug)]
+    #[view(collection = TestCollection, key = ())]
+    #[view_schema(related = [RelatedCollection])]
+    struct TestView;
+
+    assert_eq!(
+        TestView.related_collections(),
+        vec![RelatedCollection::collection_name()]
+    );
+}
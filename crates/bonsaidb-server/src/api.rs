@@ -2,11 +2,12 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 
 use async_trait::async_trait;
-use bonsaidb_core::api::{self, Api, ApiError, Infallible};
+use bonsaidb_core::api::{self, Api, ApiError, ApiName, Infallible};
 use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::permissions::PermissionDenied;
 use bonsaidb_core::schema::{InsertError, InvalidNameError};
 
+use crate::dispatch::ServerDispatcher;
 use crate::{Backend, ConnectedClient, CustomServer, Error, NoBackend};
 
 /// A trait that can dispatch requests for a [`Api`].
@@ -60,7 +61,7 @@ where
     A: Api,
 {
     async fn handle(&self, client: HandlerSession<'_, B>, request: &[u8]) -> Result<Bytes, Error> {
-        let request = pot::from_slice(request)?;
+        let request = client.server.deserialize_request(request)?;
         let response = match T::handle(client, request).await {
             Ok(response) => Ok(response),
             Err(HandlerError::Api(err)) => Err(err),
@@ -105,7 +106,7 @@ impl<E: ApiError> From<InvalidNameError> for HandlerError<E> {
     }
 }
 
-#[cfg(feature = "websockets")]
+#[cfg(any(feature = "websockets", feature = "tcp"))]
 impl<E: ApiError> From<bincode::Error> for HandlerError<E> {
     fn from(other: bincode::Error) -> Self {
         Self::Server(Error::from(bonsaidb_local::Error::from(other)))
@@ -133,6 +134,42 @@ where
     }
 }
 
+/// A request received from a client, passed to
+/// [`Backend::dispatch_request()`].
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// The name of the [`Api`] being invoked.
+    pub name: ApiName,
+    /// The serialized [`Api`] request.
+    pub value: Bytes,
+}
+
+/// Executes a [`Request`] using the [`Handler`] registered for its name.
+///
+/// This is passed to [`Backend::dispatch_request()`], allowing a backend to
+/// run its own logic before delegating to the server's handlers.
+pub struct NextHandler<'a, B: Backend = NoBackend> {
+    session: HandlerSession<'a, B>,
+}
+
+impl<'a, B: Backend> NextHandler<'a, B> {
+    pub(crate) const fn new(session: HandlerSession<'a, B>) -> Self {
+        Self { session }
+    }
+
+    /// Returns the session the request will be executed with.
+    #[must_use]
+    pub const fn session(&self) -> &HandlerSession<'a, B> {
+        &self.session
+    }
+
+    /// Executes `request` using the [`Handler`] registered for
+    /// [`Request::name`].
+    pub async fn dispatch(self, request: Request) -> Result<Bytes, Error> {
+        ServerDispatcher::dispatch_api_request(self.session, &request.name, request.value).await
+    }
+}
+
 /// The return type from a [`Handler`]'s [`handle()`](Handler::handle)
 /// function.
 pub type HandlerResult<Api> =
@@ -2,10 +2,12 @@ use std::convert::Infallible;
 use std::fmt::Debug;
 
 use async_trait::async_trait;
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::Session;
 use bonsaidb_core::permissions::PermissionDenied;
 use bonsaidb_core::schema::{InsertError, InvalidNameError};
 
+use crate::api::{NextHandler, Request};
 use crate::server::ConnectedClient;
 use crate::{CustomServer, Error, ServerConfiguration};
 
@@ -105,6 +107,26 @@ pub trait Backend: Debug + Send + Sync + Sized + 'static {
         );
         Ok(())
     }
+
+    /// Invoked for each request a client makes, before it is executed.
+    ///
+    /// `session` is the session the request is being made with, which can be
+    /// used to look up the authenticated identity. This function can log or
+    /// audit `request`, reject it by returning an error, or pass it (or a
+    /// rewritten request) to [`next.dispatch()`](NextHandler::dispatch) to
+    /// execute it using the registered [`Handler`](crate::api::Handler).
+    ///
+    /// The default implementation executes `request` unchanged.
+    #[allow(unused_variables)]
+    async fn dispatch_request(
+        &self,
+        client: &ConnectedClient<Self>,
+        session: &Session,
+        request: Request,
+        next: NextHandler<'_, Self>,
+    ) -> Result<Bytes, Error> {
+        next.dispatch(request).await
+    }
 }
 
 /// A [`Backend`] with no custom functionality.
@@ -165,7 +187,7 @@ impl<E> From<InvalidNameError> for BackendError<E> {
     }
 }
 
-#[cfg(feature = "websockets")]
+#[cfg(any(feature = "websockets", feature = "tcp"))]
 impl<E> From<bincode::Error> for BackendError<E> {
     fn from(other: bincode::Error) -> Self {
         Self::Server(Error::from(bonsaidb_local::Error::from(other)))
@@ -3,11 +3,13 @@ use std::marker::PhantomData;
 use std::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bonsaidb_core::api;
 use bonsaidb_core::api::ApiName;
 #[cfg(feature = "encryption")]
 use bonsaidb_core::document::KeyId;
+pub use bonsaidb_core::networking::DEFAULT_DESERIALIZATION_LIMIT;
 use bonsaidb_core::permissions::{Permissions, Statement};
 use bonsaidb_core::schema::Schema;
 #[cfg(feature = "compression")]
@@ -39,6 +41,23 @@ pub struct ServerConfiguration<B: Backend = NoBackend> {
     pub storage: StorageConfiguration,
     /// The permissions granted to all connections to this server.
     pub default_permissions: DefaultPermissions,
+    /// Controls whether responses sent to clients are compressed. Default
+    /// value is [`CompressionMode::Disabled`].
+    pub compression: CompressionMode,
+    /// The limits on how frequently clients may make requests. Default value
+    /// is [`RateLimits::default()`], which does not limit requests.
+    pub rate_limits: RateLimits,
+    /// The maximum number of bytes that may be allocated while decoding a
+    /// single request received from a client. Requests that would exceed this
+    /// limit are rejected instead of being decoded. Default value is
+    /// [`DEFAULT_DESERIALIZATION_LIMIT`].
+    ///
+    /// The length of collections is not limited separately. A collection only
+    /// preallocates a small, bounded capacity regardless of the length its
+    /// encoding claims, and it grows only as elements are decoded from the
+    /// request. A corrupt or malicious length therefore can't cause a large
+    /// allocation by itself.
+    pub deserialization_limit: usize,
     /// The ACME settings for automatic TLS certificate management.
     #[cfg(feature = "acme")]
     pub acme: AcmeConfiguration,
@@ -58,6 +77,9 @@ impl<B: Backend> ServerConfiguration<B> {
             request_workers: 16,
             storage: bonsaidb_local::config::StorageConfiguration::default(),
             default_permissions: DefaultPermissions::Permissions(Permissions::default()),
+            compression: CompressionMode::Disabled,
+            rate_limits: RateLimits::default(),
+            deserialization_limit: DEFAULT_DESERIALIZATION_LIMIT,
             custom_apis: HashMap::default(),
             #[cfg(feature = "acme")]
             acme: AcmeConfiguration::default(),
@@ -96,6 +118,34 @@ impl<B: Backend> ServerConfiguration<B> {
         self
     }
 
+    /// Sets [`Self::compression`](Self#structfield.compression) to `compression` and returns self.
+    pub const fn compression(mut self, compression: CompressionMode) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets [`RateLimits::default`](RateLimits#structfield.default) to `limit`
+    /// and returns self. This limit applies to all requests that do not have a
+    /// more specific limit configured using [`Self::api_rate_limit()`].
+    pub const fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limits.default = Some(limit);
+        self
+    }
+
+    /// Sets [`Self::deserialization_limit`](Self#structfield.deserialization_limit) to `limit` and returns self.
+    pub const fn deserialization_limit(mut self, limit: usize) -> Self {
+        self.deserialization_limit = limit;
+        self
+    }
+
+    /// Limits requests of type `Api` to `limit` and returns self. Requests
+    /// limited this way do not count towards the limit set by
+    /// [`Self::rate_limit()`].
+    pub fn api_rate_limit<Api: api::Api>(mut self, limit: RateLimit) -> Self {
+        self.rate_limits.apis.insert(Api::name(), limit);
+        self
+    }
+
     /// Sets [`AcmeConfiguration::contact_email`] to `contact_email` and returns self.
     #[cfg(feature = "acme")]
     pub fn acme_contact_email(mut self, contact_email: impl Into<String>) -> Self {
@@ -204,6 +254,88 @@ impl From<Statement> for DefaultPermissions {
     }
 }
 
+/// Controls whether responses sent to clients are compressed.
+///
+/// Clients request compression by specifying a
+/// [`PayloadCompression`](bonsaidb_core::networking::PayloadCompression) when
+/// connecting. If the requested algorithm isn't enabled in this build of the
+/// server, responses are sent uncompressed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompressionMode {
+    /// Responses are never compressed.
+    Disabled,
+    /// Responses larger than `threshold` bytes are compressed using the
+    /// algorithm requested by the client.
+    Enabled {
+        /// The minimum size of a response, in bytes, before it is compressed.
+        threshold: usize,
+    },
+}
+
+/// Limits on how frequently clients may make requests.
+///
+/// Each limit is enforced independently for each authenticated identity. For
+/// clients that have not authenticated, each connected client is limited
+/// independently. When a limit is exceeded, the request fails with
+/// [`bonsaidb_core::Error::RateLimited`].
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct RateLimits {
+    /// The limit applied to all requests that do not have a limit in
+    /// `apis`. If `None`, these requests are not limited.
+    pub default: Option<RateLimit>,
+    /// Limits for specific [`Api`](api::Api) types, keyed by
+    /// [`Api::name()`](api::Api::name).
+    pub apis: HashMap<ApiName, RateLimit>,
+}
+
+impl RateLimits {
+    /// Returns the limit that applies to requests for `api`, and whether the
+    /// limit is specific to `api`.
+    #[must_use]
+    pub fn limit_for(&self, api: &ApiName) -> Option<(RateLimit, bool)> {
+        self.apis
+            .get(api)
+            .map(|limit| (*limit, true))
+            .or_else(|| self.default.map(|limit| (limit, false)))
+    }
+}
+
+/// A token bucket rate limit.
+///
+/// Each client starts with `burst` tokens, and each request consumes one
+/// token. One token is returned every `replenish_interval`, until the bucket
+/// contains `burst` tokens again.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimit {
+    /// The maximum number of requests that can be made at once.
+    pub burst: u32,
+    /// The amount of time it takes to replenish a single request.
+    pub replenish_interval: Duration,
+}
+
+impl RateLimit {
+    /// Returns a limit that allows `burst` requests at once, replenishing one
+    /// request every `replenish_interval`.
+    #[must_use]
+    pub const fn new(burst: u32, replenish_interval: Duration) -> Self {
+        Self {
+            burst,
+            replenish_interval,
+        }
+    }
+
+    /// Returns a limit that allows `requests` requests per second, with
+    /// bursts of up to `requests` requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is 0.
+    #[must_use]
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1) / requests)
+    }
+}
+
 impl<B: Backend> Builder for ServerConfiguration<B> {
     fn with_schema<S: Schema>(mut self) -> Result<Self, bonsaidb_local::Error> {
         self.storage.register_schema::<S>()?;
@@ -255,6 +387,41 @@ impl<B: Backend> Builder for ServerConfiguration<B> {
         self
     }
 
+    fn repair_stale_view_mappings(mut self, repair: bool) -> Self {
+        self.storage.views.repair_stale_mappings = repair;
+        self
+    }
+
+    fn update_views_on_write(mut self, update: bool) -> Self {
+        self.storage.views.update_on_write = update;
+        self
+    }
+
+    fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.storage.cache.capacity = capacity;
+        self
+    }
+
+    fn cache_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.storage.cache.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    fn document_cache_capacity(mut self, capacity: usize) -> Self {
+        self.storage.cache.documents = capacity;
+        self
+    }
+
+    fn database_cache_capacity(mut self, capacity: usize) -> Self {
+        self.storage.cache.databases = capacity;
+        self
+    }
+
+    fn database_concurrency_limit(mut self, limit: usize) -> Self {
+        self.storage.database_concurrency_limit = Some(limit);
+        self
+    }
+
     #[cfg(feature = "compression")]
     fn default_compression(mut self, compression: Compression) -> Self {
         self.storage.default_compression = Some(compression);
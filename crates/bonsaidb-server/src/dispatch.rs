@@ -7,11 +7,14 @@ use bonsaidb_core::connection::{
 use bonsaidb_core::keyvalue::AsyncKeyValue;
 use bonsaidb_core::networking::{
     AlterUserPermissionGroupMembership, AlterUserRoleMembership, ApplyTransaction, AssumeIdentity,
-    Compact, CompactCollection, CompactKeyValueStore, Count, CreateDatabase, CreateSubscriber,
-    CreateUser, DeleteDatabase, DeleteDocs, DeleteUser, ExecuteKeyOperation, Get, GetMultiple,
-    LastTransactionId, List, ListAvailableSchemas, ListDatabases, ListExecutedTransactions,
-    ListHeaders, LogOutSession, Publish, PublishToAll, Query, QueryWithDocs, Reduce, ReduceGrouped,
-    SubscribeTo, UnregisterSubscriber, UnsubscribeFrom,
+    Compact, CompactCollection, CompactKeyValueStore, Count, CountView, CreateDatabase,
+    CreateSubscriber, CreateUser, DatabaseSchema, DeleteDatabase, DeleteDocs, DeleteUser,
+    ExecuteKeyOperation, ExistingIds, FacetCounts, Get, GetMultiple, GetRevision, GetWithMappings,
+    GetWithViewValue, History, LastTransactionId, List, ListAvailableSchemas, ListDatabases,
+    ListExecutedTransactions, ListHeaders, LogOutSession, Publish, PublishAndFlush,
+    PublishRetained, PublishToAll, Query, QueryMultiple, QueryPage, QueryWithDocs, Reduce,
+    ReduceGrouped, ReduceWithStaleness, RenameDatabase, SubscribeTo, SubscribeToPattern,
+    UnregisterSubscriber, UnsubscribeFrom, UnsubscribeFromPattern,
 };
 #[cfg(feature = "password-hashing")]
 use bonsaidb_core::networking::{Authenticate, SetUserPassword};
@@ -33,15 +36,23 @@ pub fn register_api_handlers<B: Backend>(
         .with_api::<ServerDispatcher, CompactCollection>()?
         .with_api::<ServerDispatcher, CompactKeyValueStore>()?
         .with_api::<ServerDispatcher, Count>()?
+        .with_api::<ServerDispatcher, CountView>()?
         .with_api::<ServerDispatcher, CreateDatabase>()?
         .with_api::<ServerDispatcher, CreateSubscriber>()?
         .with_api::<ServerDispatcher, CreateUser>()?
+        .with_api::<ServerDispatcher, DatabaseSchema>()?
         .with_api::<ServerDispatcher, DeleteDatabase>()?
         .with_api::<ServerDispatcher, DeleteDocs>()?
         .with_api::<ServerDispatcher, DeleteUser>()?
         .with_api::<ServerDispatcher, ExecuteKeyOperation>()?
+        .with_api::<ServerDispatcher, ExistingIds>()?
+        .with_api::<ServerDispatcher, FacetCounts>()?
         .with_api::<ServerDispatcher, Get>()?
         .with_api::<ServerDispatcher, GetMultiple>()?
+        .with_api::<ServerDispatcher, GetRevision>()?
+        .with_api::<ServerDispatcher, GetWithMappings>()?
+        .with_api::<ServerDispatcher, GetWithViewValue>()?
+        .with_api::<ServerDispatcher, History>()?
         .with_api::<ServerDispatcher, LastTransactionId>()?
         .with_api::<ServerDispatcher, List>()?
         .with_api::<ServerDispatcher, ListHeaders>()?
@@ -50,14 +61,22 @@ pub fn register_api_handlers<B: Backend>(
         .with_api::<ServerDispatcher, ListExecutedTransactions>()?
         .with_api::<ServerDispatcher, LogOutSession>()?
         .with_api::<ServerDispatcher, Publish>()?
+        .with_api::<ServerDispatcher, PublishAndFlush>()?
+        .with_api::<ServerDispatcher, PublishRetained>()?
         .with_api::<ServerDispatcher, PublishToAll>()?
         .with_api::<ServerDispatcher, Query>()?
+        .with_api::<ServerDispatcher, QueryMultiple>()?
+        .with_api::<ServerDispatcher, QueryPage>()?
         .with_api::<ServerDispatcher, QueryWithDocs>()?
         .with_api::<ServerDispatcher, Reduce>()?
         .with_api::<ServerDispatcher, ReduceGrouped>()?
+        .with_api::<ServerDispatcher, ReduceWithStaleness>()?
+        .with_api::<ServerDispatcher, RenameDatabase>()?
         .with_api::<ServerDispatcher, SubscribeTo>()?
+        .with_api::<ServerDispatcher, SubscribeToPattern>()?
         .with_api::<ServerDispatcher, UnregisterSubscriber>()?
-        .with_api::<ServerDispatcher, UnsubscribeFrom>()?;
+        .with_api::<ServerDispatcher, UnsubscribeFrom>()?
+        .with_api::<ServerDispatcher, UnsubscribeFromPattern>()?;
 
     #[cfg(feature = "password-hashing")]
     {
@@ -114,6 +133,20 @@ impl<B: Backend> Handler<DeleteDatabase, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<RenameDatabase, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: RenameDatabase,
+    ) -> HandlerResult<RenameDatabase> {
+        session
+            .as_client
+            .rename_database(&command.name, &command.new_name)
+            .await?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<ListDatabases, B> for ServerDispatcher {
     async fn handle(
@@ -128,6 +161,20 @@ impl<B: Backend> Handler<ListDatabases, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<DatabaseSchema, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: DatabaseSchema,
+    ) -> HandlerResult<DatabaseSchema> {
+        session
+            .as_client
+            .database_schema(&command.name)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<ListAvailableSchemas, B> for ServerDispatcher {
     async fn handle(
@@ -315,6 +362,54 @@ impl<B: Backend> Handler<GetMultiple, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<ExistingIds, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ExistingIds,
+    ) -> HandlerResult<ExistingIds> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .existing_ids_in_collection(&command.ids, &command.collection)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<GetRevision, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: GetRevision,
+    ) -> HandlerResult<GetRevision> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .get_revision_from_collection(command.id, command.revision, &command.collection)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<History, B> for ServerDispatcher {
+    async fn handle(session: HandlerSession<'_, B>, command: History) -> HandlerResult<History> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .history_from_collection(command.id, &command.collection)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<List, B> for ServerDispatcher {
     async fn handle(session: HandlerSession<'_, B>, command: List) -> HandlerResult<List> {
@@ -390,6 +485,47 @@ impl<B: Backend> Handler<Query, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<QueryMultiple, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: QueryMultiple,
+    ) -> HandlerResult<QueryMultiple> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .query_multiple_by_name(command.queries)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<QueryPage, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: QueryPage,
+    ) -> HandlerResult<QueryPage> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.query.database)
+            .await?;
+        database
+            .query_page_by_name(
+                &command.query.view,
+                command.query.key,
+                command.query.order,
+                command.query.limit,
+                command.after,
+                command.query.access_policy,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<QueryWithDocs, B> for ServerDispatcher {
     async fn handle(
@@ -436,10 +572,36 @@ impl<B: Backend> Handler<ReduceGrouped, B> for ServerDispatcher {
     ) -> HandlerResult<ReduceGrouped> {
         let database = session
             .as_client
-            .database_without_schema(&command.0.database)
+            .database_without_schema(&command.reduce.database)
             .await?;
         database
-            .reduce_grouped_by_name(&command.0.view, command.0.key, command.0.access_policy)
+            .reduce_grouped_by_name(
+                &command.reduce.view,
+                command.reduce.key,
+                command.group_level,
+                command.reduce.access_policy,
+            )
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<ReduceWithStaleness, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: ReduceWithStaleness,
+    ) -> HandlerResult<ReduceWithStaleness> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.reduce.database)
+            .await?;
+        database
+            .reduce_with_staleness_by_name(
+                &command.reduce.view,
+                command.reduce.key,
+                command.reduce.access_policy,
+            )
             .await
             .map_err(HandlerError::from)
     }
@@ -456,7 +618,75 @@ impl<B: Backend> Handler<ApplyTransaction, B> for ServerDispatcher {
             .database_without_schema(&command.database)
             .await?;
         database
-            .apply_transaction(command.transaction)
+            .apply_transaction_with_id(command.transaction)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<CountView, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: CountView,
+    ) -> HandlerResult<CountView> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .count_view_by_name(&command.view, command.key, command.access_policy)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<FacetCounts, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: FacetCounts,
+    ) -> HandlerResult<FacetCounts> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .facet_counts_by_name(&command.view, command.key, command.access_policy)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<GetWithViewValue, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: GetWithViewValue,
+    ) -> HandlerResult<GetWithViewValue> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .get_with_view_value_by_name(&command.view, command.id, command.access_policy)
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<GetWithMappings, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: GetWithMappings,
+    ) -> HandlerResult<GetWithMappings> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .get_with_mappings_by_name(&command.collection, command.id, command.access_policy)
             .await
             .map_err(HandlerError::from)
     }
@@ -549,6 +779,40 @@ impl<B: Backend> Handler<Publish, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<PublishAndFlush, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: PublishAndFlush,
+    ) -> HandlerResult<PublishAndFlush> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .publish_bytes_and_flush(command.topic.into_vec(), command.payload.into_vec())
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<PublishRetained, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: PublishRetained,
+    ) -> HandlerResult<PublishRetained> {
+        let database = session
+            .as_client
+            .database_without_schema(&command.database)
+            .await?;
+        database
+            .publish_bytes_retained(command.topic.into_vec(), command.payload.into_vec())
+            .await
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<PublishToAll, B> for ServerDispatcher {
     async fn handle(
@@ -603,6 +867,40 @@ impl<B: Backend> Handler<UnsubscribeFrom, B> for ServerDispatcher {
     }
 }
 
+#[async_trait]
+impl<B: Backend> Handler<SubscribeToPattern, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: SubscribeToPattern,
+    ) -> HandlerResult<SubscribeToPattern> {
+        session
+            .client
+            .subscribe_to_pattern_by_id(
+                command.subscriber_id,
+                &command.pattern,
+                session.as_client.session().and_then(|session| session.id),
+            )
+            .map_err(HandlerError::from)
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Handler<UnsubscribeFromPattern, B> for ServerDispatcher {
+    async fn handle(
+        session: HandlerSession<'_, B>,
+        command: UnsubscribeFromPattern,
+    ) -> HandlerResult<UnsubscribeFromPattern> {
+        session
+            .client
+            .unsubscribe_from_pattern_by_id(
+                command.subscriber_id,
+                &command.pattern,
+                session.as_client.session().and_then(|session| session.id),
+            )
+            .map_err(HandlerError::from)
+    }
+}
+
 #[async_trait]
 impl<B: Backend> Handler<UnregisterSubscriber, B> for ServerDispatcher {
     async fn handle(
@@ -146,7 +146,7 @@ impl From<pot::Error> for Error {
     }
 }
 
-#[cfg(feature = "websockets")]
+#[cfg(any(feature = "websockets", feature = "tcp"))]
 impl From<bincode::Error> for Error {
     fn from(other: bincode::Error) -> Self {
         Self::Core(bonsaidb_core::Error::other("bincode", other))
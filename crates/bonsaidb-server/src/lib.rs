@@ -34,7 +34,10 @@ pub use config::{
 };
 
 pub use self::backend::{Backend, BackendError, ConnectionHandling, NoBackend};
-pub use self::config::{BonsaiListenConfig, DefaultPermissions, ServerConfiguration};
+pub use self::config::{
+    BonsaiListenConfig, CompressionMode, DefaultPermissions, RateLimit, RateLimits,
+    ServerConfiguration, DEFAULT_DESERIALIZATION_LIMIT,
+};
 pub use self::error::Error;
 pub use self::server::{
     ApplicationProtocols, ConnectedClient, CustomServer, HttpService, LockedClientDataGuard, Peer,
@@ -30,26 +30,32 @@ use futures::{Future, StreamExt};
 use parking_lot::{Mutex, RwLock};
 use rustls::sign::CertifiedKey;
 use schema::SchemaName;
+use serde::de::DeserializeOwned;
 #[cfg(not(windows))]
 use signal_hook::consts::SIGQUIT;
 use signal_hook::consts::{SIGINT, SIGTERM};
-use tokio::sync::{oneshot, Notify};
+use tokio::sync::{oneshot, watch, Notify};
 
-use crate::api::{AnyHandler, HandlerSession};
+use crate::api::{AnyHandler, HandlerSession, NextHandler, Request};
 use crate::backend::ConnectionHandling;
 #[cfg(feature = "acme")]
 use crate::config::AcmeConfiguration;
-use crate::dispatch::{register_api_handlers, ServerDispatcher};
+use crate::dispatch::register_api_handlers;
 use crate::error::Error;
 use crate::hosted::{Hosted, SerializablePrivateKey, TlsCertificate, TlsCertificatesByDomain};
 use crate::server::shutdown::{Shutdown, ShutdownState, ShutdownStateWatcher};
-use crate::{Backend, BackendError, BonsaiListenConfig, NoBackend, ServerConfiguration};
+use crate::{
+    Backend, BackendError, BonsaiListenConfig, CompressionMode, NoBackend, ServerConfiguration,
+};
 
 #[cfg(feature = "acme")]
 pub mod acme;
 mod connected_client;
 mod database;
+#[cfg(feature = "tcp")]
+mod framed_tcp;
 
+mod rate_limiter;
 mod shutdown;
 mod tcp;
 #[cfg(feature = "websockets")]
@@ -58,6 +64,7 @@ mod websockets;
 use self::connected_client::OwnedClient;
 pub use self::connected_client::{ConnectedClient, LockedClientDataGuard, Transport};
 pub use self::database::ServerDatabase;
+use self::rate_limiter::RateLimiter;
 pub use self::tcp::{ApplicationProtocols, HttpService, Peer, StandardTcpProtocols, TcpService};
 
 static CONNECTED_CLIENT_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
@@ -92,7 +99,13 @@ struct Data<B: Backend = NoBackend> {
     request_processor: flume::Sender<ClientRequest<B>>,
     default_session: Session,
     client_simultaneous_request_limit: usize,
+    compression: CompressionMode,
+    rate_limiter: RateLimiter,
+    deserialization_limit: usize,
     primary_tls_key: CachedCertifiedKey,
+    /// Notified each time a certificate is installed, allowing QUIC endpoints
+    /// to be rebound with the new certificate.
+    certificate_installed: watch::Sender<()>,
     primary_domain: String,
     custom_apis: RwLock<HashMap<ApiName, Arc<dyn AnyHandler<B>>>>,
     #[cfg(feature = "acme")]
@@ -102,6 +115,22 @@ struct Data<B: Backend = NoBackend> {
     shutdown: Shutdown,
 }
 
+fn certified_key(
+    certificate_chain: &CertificateChain,
+    private_key: &PrivateKey,
+) -> Result<Arc<CertifiedKey>, Error> {
+    let private_key =
+        rustls::PrivateKey(fabruic::dangerous::PrivateKey::as_ref(private_key).to_vec());
+    let private_key = rustls::sign::any_ecdsa_type(&Arc::new(private_key))?;
+
+    let certificates = certificate_chain
+        .iter()
+        .map(|cert| rustls::Certificate(cert.as_ref().to_vec()))
+        .collect::<Vec<_>>();
+
+    Ok(Arc::new(CertifiedKey::new(certificates, private_key)))
+}
+
 #[derive(Default)]
 struct CachedCertifiedKey(Mutex<Option<Arc<CertifiedKey>>>);
 
@@ -134,7 +163,13 @@ impl<B: Backend> CustomServer<B> {
                     let session = client_request.session.clone();
                     // TODO we should be able to upgrade a session-less Storage to one with a Session.
                     // The Session needs to be looked up from the client based on the request's session id.
-                    let result = match client_request.server.storage.assume_session(session) {
+                    let result = match client_request
+                        .server
+                        .data
+                        .rate_limiter
+                        .check(client_request.client.id(), &session, &request.name)
+                        .and_then(|()| client_request.server.storage.assume_session(session))
+                    {
                         Ok(storage) => {
                             let client = HandlerSession {
                                 server: &client_request.server,
@@ -144,13 +179,21 @@ impl<B: Backend> CustomServer<B> {
                                     storage,
                                 },
                             };
-                            ServerDispatcher::dispatch_api_request(
-                                client,
-                                &request.name,
-                                request.value.unwrap(),
-                            )
-                            .await
-                            .map_err(bonsaidb_core::Error::from)
+                            client_request
+                                .server
+                                .data
+                                .backend
+                                .dispatch_request(
+                                    &client_request.client,
+                                    &client_request.session,
+                                    Request {
+                                        name: request.name.clone(),
+                                        value: request.value.unwrap(),
+                                    },
+                                    NextHandler::new(client),
+                                )
+                                .await
+                                .map_err(bonsaidb_core::Error::from)
                         }
                         Err(err) => Err(err),
                     };
@@ -176,7 +219,11 @@ impl<B: Backend> CustomServer<B> {
                     ..Session::default()
                 },
                 client_simultaneous_request_limit: configuration.client_simultaneous_request_limit,
+                compression: configuration.compression,
+                rate_limiter: RateLimiter::new(configuration.rate_limits),
+                deserialization_limit: configuration.deserialization_limit,
                 primary_tls_key: CachedCertifiedKey::default(),
+                certificate_installed: watch::channel(()).0,
                 primary_domain: configuration.server_name,
                 custom_apis: parking_lot::RwLock::new(configuration.custom_apis),
                 #[cfg(feature = "acme")]
@@ -228,6 +275,18 @@ impl<B: Backend> CustomServer<B> {
         }
     }
 
+    /// Decodes a request received from a client, allocating at most
+    /// [`ServerConfiguration::deserialization_limit`](ServerConfiguration#structfield.deserialization_limit)
+    /// bytes.
+    pub(crate) fn deserialize_request<T: DeserializeOwned>(
+        &self,
+        request: &[u8],
+    ) -> Result<T, pot::Error> {
+        pot::Config::default()
+            .allocation_budget(self.data.deserialization_limit)
+            .deserialize(request)
+    }
+
     pub(crate) fn custom_api_dispatcher(&self, name: &ApiName) -> Option<Arc<dyn AnyHandler<B>>> {
         let dispatchers = self.data.custom_apis.read();
         dispatchers.get(name).cloned()
@@ -297,6 +356,7 @@ impl<B: Backend> CustomServer<B> {
             .await?;
 
         self.refresh_certified_key().await?;
+        self.data.certificate_installed.send_replace(());
 
         let pinned_certificate_path = self.pinned_certificate_path();
         if pinned_certificate_path.exists() {
@@ -306,22 +366,40 @@ impl<B: Backend> CustomServer<B> {
         Ok(())
     }
 
+    /// Replaces the certificate chain and private key used for TLS
+    /// connections without restarting the server.
+    ///
+    /// The certificate chain and private key are validated before anything is
+    /// stored. If they are invalid or do not belong together, an error is
+    /// returned and the previously installed certificate remains in use.
+    ///
+    /// Once installed, the new certificate is used for every new connection
+    /// accepted by [`Self::listen_for_secure_tcp_on()`], which is also used
+    /// for websocket connections when TLS is enabled. Existing TCP and
+    /// websocket connections are unaffected.
+    ///
+    /// QUIC endpoints cannot change their certificate after they have been
+    /// created, so each endpoint started by [`Self::listen_on()`] is closed
+    /// and bound again using the new certificate. Existing QUIC connections
+    /// are closed, and clients reconnect using the new certificate.
+    pub async fn reload_certificate(
+        &self,
+        certificate_chain: &CertificateChain,
+        private_key: &PrivateKey,
+    ) -> Result<(), Error> {
+        KeyPair::from_parts(certificate_chain.clone(), private_key.clone())?;
+        certified_key(certificate_chain, private_key)?;
+
+        self.install_certificate(certificate_chain, private_key)
+            .await
+    }
+
     async fn refresh_certified_key(&self) -> Result<(), Error> {
         let certificate = self.tls_certificate().await?;
+        let certified_key =
+            certified_key(&certificate.certificate_chain, &certificate.private_key.0)?;
 
         let mut cached_key = self.data.primary_tls_key.lock();
-        let private_key = rustls::PrivateKey(
-            fabruic::dangerous::PrivateKey::as_ref(&certificate.private_key.0).to_vec(),
-        );
-        let private_key = rustls::sign::any_ecdsa_type(&Arc::new(private_key))?;
-
-        let certificates = certificate
-            .certificate_chain
-            .iter()
-            .map(|cert| rustls::Certificate(cert.as_ref().to_vec()))
-            .collect::<Vec<_>>();
-
-        let certified_key = Arc::new(CertifiedKey::new(certificates, private_key));
         *cached_key = Some(certified_key);
         Ok(())
     }
@@ -379,16 +457,10 @@ impl<B: Backend> CustomServer<B> {
     /// local IP or localhost in addition to the port number.
     pub async fn listen_on(&self, config: impl Into<BonsaiListenConfig>) -> Result<(), Error> {
         let config = config.into();
-        let certificate = self.tls_certificate().await?;
-        let keypair =
-            KeyPair::from_parts(certificate.certificate_chain, certificate.private_key.0)?;
-        let mut builder = Endpoint::builder();
-        builder.set_protocols([CURRENT_PROTOCOL_VERSION.as_bytes().to_vec()]);
-        builder.set_address(config.address);
-        builder.set_max_idle_timeout(None)?;
-        builder.set_server_key_pair(Some(keypair));
-        builder.set_reuse_address(config.reuse_address);
-        let mut server = builder.build()?;
+        let mut certificate_installed = self.data.certificate_installed.subscribe();
+        let mut server = self
+            .quic_endpoint(config.address, config.reuse_address)
+            .await?;
 
         let mut shutdown_watcher = self
             .data
@@ -397,16 +469,28 @@ impl<B: Backend> CustomServer<B> {
             .await
             .expect("server already shut down");
 
-        while let Some(incoming) = tokio::select! {
-            shutdown_state = shutdown_watcher.wait_for_shutdown() => {
-                drop(server.close_incoming());
-                if matches!(shutdown_state, ShutdownState::GracefulShutdown) {
-                    server.wait_idle().await;
-                }
-                None
-            },
-            msg = server.next() => msg
-        } {
+        loop {
+            let incoming = tokio::select! {
+                shutdown_state = shutdown_watcher.wait_for_shutdown() => {
+                    drop(server.close_incoming());
+                    if matches!(shutdown_state, ShutdownState::GracefulShutdown) {
+                        server.wait_idle().await;
+                    }
+                    break;
+                },
+                Ok(()) = certificate_installed.changed() => {
+                    // The previous endpoint's socket may not be released until
+                    // its connections finish closing, so the address is
+                    // always reused when binding the replacement.
+                    server.close().await;
+                    server = self.quic_endpoint(config.address, true).await?;
+                    continue;
+                },
+                msg = server.next() => match msg {
+                    Some(incoming) => incoming,
+                    None => break,
+                },
+            };
             let address = incoming.remote_address();
             let connection = match incoming.accept::<()>().await {
                 Ok(connection) => connection,
@@ -426,6 +510,25 @@ impl<B: Backend> CustomServer<B> {
         Ok(())
     }
 
+    /// Builds a QUIC endpoint listening on `address` that presents the
+    /// currently installed certificate.
+    async fn quic_endpoint(
+        &self,
+        address: SocketAddr,
+        reuse_address: bool,
+    ) -> Result<Endpoint, Error> {
+        let certificate = self.tls_certificate().await?;
+        let keypair =
+            KeyPair::from_parts(certificate.certificate_chain, certificate.private_key.0)?;
+        let mut builder = Endpoint::builder();
+        builder.set_protocols([CURRENT_PROTOCOL_VERSION.as_bytes().to_vec()]);
+        builder.set_address(address);
+        builder.set_max_idle_timeout(None)?;
+        builder.set_server_key_pair(Some(keypair));
+        builder.set_reuse_address(reuse_address);
+        Ok(builder.build()?)
+    }
+
     /// Returns all of the currently connected clients.
     #[must_use]
     pub fn connected_clients(&self) -> Vec<ConnectedClient<B>> {
@@ -489,6 +592,7 @@ impl<B: Backend> CustomServer<B> {
             let mut clients = self.data.clients.write();
             clients.remove(&id)
         };
+        self.data.rate_limiter.forget_client(id);
 
         if let Some(client) = removed_client {
             for session in client.all_sessions::<Vec<_>>() {
@@ -546,6 +650,8 @@ impl<B: Backend> CustomServer<B> {
                                         session_id,
                                         name,
                                         value: Ok(bytes),
+                                        compression: None,
+                                        accept_compression: None,
                                     })
                                     .is_err()
                                 {
@@ -578,6 +684,41 @@ impl<B: Backend> CustomServer<B> {
         Ok(())
     }
 
+    #[cfg(any(feature = "websockets", feature = "tcp"))]
+    pub(crate) fn deserialize_payload(&self, bytes: &[u8]) -> Result<Payload, bincode::Error> {
+        use bincode::Options;
+
+        bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(self.data.deserialization_limit as u64)
+            .deserialize(bytes)
+    }
+
+    #[cfg(any(feature = "websockets", feature = "tcp"))]
+    fn spawn_client_request_handler(
+        &self,
+        client: OwnedClient<B>,
+        request_receiver: flume::Receiver<Payload>,
+        response_sender: flume::Sender<Payload>,
+        shutdown: &ShutdownStateWatcher,
+    ) {
+        tokio::spawn({
+            let task_self = self.clone();
+            let shutdown = shutdown.clone();
+            async move {
+                task_self
+                    .handle_client_requests(
+                        client.clone(),
+                        request_receiver,
+                        response_sender,
+                        shutdown,
+                    )
+                    .await;
+            }
+        });
+    }
+
     async fn handle_client_requests(
         &self,
         client: ConnectedClient<B>,
@@ -587,7 +728,7 @@ impl<B: Backend> CustomServer<B> {
     ) {
         let notify = Arc::new(Notify::new());
         let requests_in_queue = Arc::new(AtomicUsize::new(0));
-        loop {
+        'requests: loop {
             let current_requests = requests_in_queue.load(Ordering::SeqCst);
             if current_requests == self.data.client_simultaneous_request_limit {
                 // Wait for requests to finish.
@@ -608,17 +749,25 @@ impl<B: Backend> CustomServer<B> {
                                 break 'payload payload
                             }
 
-                            return
+                            break 'requests
                         },
                         state = shutdown.wait_for_shutdown() => {
-                            if matches!(state, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
-                                return
+                            match state {
+                                ShutdownState::GracefulShutdown => break 'requests,
+                                ShutdownState::Shutdown => return,
+                                ShutdownState::Running => {}
                             }
                         }
                     }
                 };
                 let session_id = payload.session_id;
                 let id = payload.id;
+                let compression = match (self.data.compression, payload.accept_compression) {
+                    (CompressionMode::Enabled { threshold }, Some(algorithm)) => {
+                        Some((algorithm, threshold))
+                    }
+                    _ => None,
+                };
                 let task_sender = response_sender.clone();
 
                 let notify = notify.clone();
@@ -626,12 +775,20 @@ impl<B: Backend> CustomServer<B> {
                 self.handle_request_through_worker(
                     payload,
                     move |name, value| async move {
-                        drop(task_sender.send(Payload {
+                        let mut response = Payload {
                             session_id,
                             id,
                             name,
                             value,
-                        }));
+                            compression: None,
+                            accept_compression: None,
+                        };
+                        if let Some((algorithm, threshold)) = compression {
+                            if let Err(err) = response.compress(algorithm, threshold) {
+                                log::error!("[server] Error compressing response: {err:?}");
+                            }
+                        }
+                        drop(task_sender.send(response));
 
                         requests_in_queue.fetch_sub(1, Ordering::SeqCst);
 
@@ -645,6 +802,21 @@ impl<B: Backend> CustomServer<B> {
                 .unwrap();
             }
         }
+
+        // Wait for the requests already being processed to be answered. The
+        // shutdown watcher is held until they complete, which allows a graceful
+        // shutdown to wait for them. The slot reserved for the next request is
+        // still counted in `requests_in_queue`.
+        while requests_in_queue.load(Ordering::SeqCst) > 1 {
+            tokio::select! {
+                _ = notify.notified() => {}
+                state = shutdown.wait_for_shutdown() => {
+                    if matches!(state, ShutdownState::Shutdown) {
+                        return
+                    }
+                }
+            }
+        }
     }
 
     async fn handle_request_through_worker<
@@ -705,7 +877,10 @@ impl<B: Backend> CustomServer<B> {
                                 break 'stream
                             }
                             shutdown = shutdown.wait_for_shutdown() => {
-                                if matches!(shutdown, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
+                                // During a graceful shutdown, responses are
+                                // forwarded until the outstanding requests
+                                // have been answered and the channel closes.
+                                if matches!(shutdown, ShutdownState::Shutdown) {
                                     break 'stream
                                 }
                             }
@@ -759,16 +934,38 @@ impl<B: Backend> CustomServer<B> {
     }
 
     /// Shuts the server down. If a `timeout` is provided, the server will stop
-    /// accepting new connections and attempt to respond to any outstanding
-    /// requests already being processed. After the `timeout` has elapsed or if
-    /// no `timeout` was provided, the server is forcefully shut down.
+    /// accepting new connections and requests, notify each connected client
+    /// by sending [`ServerShuttingDown`](networking::ServerShuttingDown), and
+    /// wait for outstanding requests already being processed to be answered.
+    /// After the `timeout` has elapsed or if no `timeout` was provided, the
+    /// server is forcefully shut down.
+    ///
+    /// Before returning, [`Backend::client_disconnected`] is invoked for each
+    /// client that was still connected, and any pending key-value changes are
+    /// written to disk.
     pub async fn shutdown(&self, timeout: Option<Duration>) -> Result<(), Error> {
         if let Some(timeout) = timeout {
+            for client in self.connected_clients() {
+                drop(client.send::<networking::ServerShuttingDown>(
+                    None,
+                    &networking::ServerShuttingDown { timeout },
+                ));
+            }
             self.data.shutdown.graceful_shutdown(timeout).await;
         } else {
             self.data.shutdown.shutdown().await;
         }
 
+        // Disconnected clients are normally removed by a background task.
+        // Remove the remaining clients now so that the backend has been
+        // notified of every disconnection before returning.
+        let remaining_clients = self.data.clients.read().keys().copied().collect::<Vec<_>>();
+        for id in remaining_clients {
+            self.disconnect_client(id).await;
+        }
+
+        self.storage.flush_key_value_stores().await?;
+
         Ok(())
     }
 
@@ -923,10 +1120,25 @@ impl<B: Backend> AsyncStorageConnection for CustomServer<B> {
         self.storage.delete_database(name).await
     }
 
+    async fn rename_database(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.storage.rename_database(name, new_name).await
+    }
+
     async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         self.storage.list_databases().await
     }
 
+    async fn database_schema(
+        &self,
+        name: &str,
+    ) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        self.storage.database_schema(name).await
+    }
+
     async fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         self.storage.list_available_schemas().await
     }
@@ -1,3 +1,4 @@
+use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::ops::{Deref, DerefMut};
@@ -26,6 +27,9 @@ pub enum Transport {
     /// A connection over WebSockets.
     #[cfg(feature = "websockets")]
     WebSocket,
+    /// A connection over length-prefixed frames on plain TCP.
+    #[cfg(feature = "tcp")]
+    Tcp,
 }
 
 /// A connected database client.
@@ -43,6 +47,7 @@ struct Data<B: Backend = NoBackend> {
     transport: Transport,
     response_sender: Sender<(Option<SessionId>, ApiName, Bytes)>,
     client_data: Mutex<Option<B::ClientData>>,
+    state: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
 }
 
 #[derive(Debug)]
@@ -52,6 +57,10 @@ struct ClientSession {
 }
 
 impl<B: Backend> ConnectedClient<B> {
+    pub(crate) fn id(&self) -> u32 {
+        self.data.id
+    }
+
     /// Returns the address of the connected client.
     #[must_use]
     pub fn address(&self) -> &SocketAddr {
@@ -148,6 +157,45 @@ impl<B: Backend> ConnectedClient<B> {
         *client_data = Some(data);
     }
 
+    /// Stores `value` in this client's typed state, replacing any previously
+    /// stored value of the same type. The previous value is returned, if one
+    /// was set.
+    ///
+    /// Unlike [`Backend::ClientData`], any number of types can be stored, and
+    /// the state can be read without awaiting a lock. This is useful for
+    /// sharing context established in
+    /// [`Backend::client_connected`](crate::Backend::client_connected) with
+    /// later hooks and request handlers.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn set_state<T: Any + Send + Sync>(&self, value: T) -> Option<Arc<T>> {
+        let mut state = self.data.state.write();
+        state
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .map(|previous| previous.downcast::<T>().expect("state stored by TypeId"))
+    }
+
+    /// Returns the value of type `T` stored in this client's typed state, if
+    /// one has been set using [`Self::set_state()`].
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn state<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let state = self.data.state.read();
+        state
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .map(|value| value.downcast::<T>().expect("state stored by TypeId"))
+    }
+
+    /// Removes and returns the value of type `T` stored in this client's typed
+    /// state, if one was set.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn remove_state<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let mut state = self.data.state.write();
+        state
+            .remove(&TypeId::of::<T>())
+            .map(|value| value.downcast::<T>().expect("state stored by TypeId"))
+    }
+
     async fn forward_notifications_for(
         &self,
         session_id: Option<SessionId>,
@@ -216,6 +264,50 @@ impl<B: Backend> ConnectedClient<B> {
         }
     }
 
+    pub(crate) fn subscribe_to_pattern_by_id(
+        &self,
+        subscriber_id: u64,
+        pattern: &str,
+        check_session_id: Option<SessionId>,
+    ) -> Result<(), crate::Error> {
+        let mut sessions = self.data.sessions.write();
+        if let Some(client_session) = sessions.get_mut(&check_session_id) {
+            if let Some(subscriber) = client_session.subscribers.get(&subscriber_id) {
+                subscriber.subscribe_to_pattern(pattern)?;
+                Ok(())
+            } else {
+                Err(Error::other(
+                    "bonsaidb-server pubsub",
+                    "invalid subscriber id",
+                ))
+            }
+        } else {
+            Err(Error::other("bonsaidb-server auth", "invalid session id"))
+        }
+    }
+
+    pub(crate) fn unsubscribe_from_pattern_by_id(
+        &self,
+        subscriber_id: u64,
+        pattern: &str,
+        check_session_id: Option<SessionId>,
+    ) -> Result<(), crate::Error> {
+        let mut sessions = self.data.sessions.write();
+        if let Some(client_session) = sessions.get_mut(&check_session_id) {
+            if let Some(subscriber) = client_session.subscribers.get(&subscriber_id) {
+                subscriber.unsubscribe_from_pattern(pattern)?;
+                Ok(())
+            } else {
+                Err(Error::other(
+                    "bonsaidb-server pubsub",
+                    "invalid subscriber id",
+                ))
+            }
+        } else {
+            Err(Error::other("bonsaidb-server auth", "invalid session id"))
+        }
+    }
+
     pub(crate) fn unregister_subscriber_by_id(
         &self,
         subscriber_id: u64,
@@ -287,6 +379,7 @@ impl<B: Backend> OwnedClient<B> {
                     response_sender,
                     sessions: RwLock::new(session),
                     client_data: Mutex::default(),
+                    state: RwLock::default(),
                 }),
             },
             runtime: Arc::new(tokio::runtime::Handle::current()),
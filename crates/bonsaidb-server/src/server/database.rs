@@ -1,16 +1,18 @@
 use std::ops::Deref;
 
 use async_trait::async_trait;
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::connection::{
-    AccessPolicy, AsyncLowLevelConnection, HasSchema, HasSession, Range, SerializedQueryKey, Sort,
+    AccessPolicy, AsyncLowLevelConnection, HasSchema, HasSession, Range, SerializedQueryKey,
+    SerializedViewPage, SerializedViewQuery, Sort, ViewCursor,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
 use bonsaidb_core::keyvalue::AsyncKeyValue;
 use bonsaidb_core::permissions::Permissions;
 use bonsaidb_core::pubsub::AsyncPubSub;
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
 use bonsaidb_core::schema::{self, CollectionName, Schematic, ViewName};
-use bonsaidb_core::transaction::{OperationResult, Transaction};
+use bonsaidb_core::transaction::{AppliedTransaction, Transaction};
 use bonsaidb_local::{AsyncDatabase, Database};
 use derive_where::derive_where;
 
@@ -75,6 +77,22 @@ impl<B: Backend> AsyncPubSub for ServerDatabase<B> {
         self.db.publish_bytes(topic, payload).await
     }
 
+    async fn publish_bytes_and_flush(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.db.publish_bytes_and_flush(topic, payload).await
+    }
+
+    async fn publish_bytes_retained(
+        &self,
+        topic: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<(), bonsaidb_core::Error> {
+        self.db.publish_bytes_retained(topic, payload).await
+    }
+
     async fn publish_bytes_to_all(
         &self,
         topics: impl IntoIterator<Item = Vec<u8>> + Send + 'async_trait,
@@ -147,6 +165,25 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         self.db.get_from_collection(id, collection).await
     }
 
+    async fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        self.db.history_from_collection(id, collection).await
+    }
+
+    async fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        self.db
+            .get_revision_from_collection(id, revision, collection)
+            .await
+    }
+
     async fn list_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -187,6 +224,14 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         self.db.get_multiple_from_collection(ids, collection).await
     }
 
+    async fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        self.db.existing_ids_in_collection(ids, collection).await
+    }
+
     async fn compact_collection_by_name(
         &self,
         collection: CollectionName,
@@ -207,6 +252,27 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
             .await
     }
 
+    async fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
+        self.db
+            .query_page_by_name(view, key, order, limit, after, access_policy)
+            .await
+    }
+
+    async fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, bonsaidb_core::Error> {
+        self.db.query_multiple_by_name(queries).await
+    }
+
     async fn query_by_name_with_docs(
         &self,
         view: &ViewName,
@@ -233,10 +299,62 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         self.db
-            .reduce_grouped_by_name(view, key, access_policy)
+            .reduce_grouped_by_name(view, key, group_level, access_policy)
+            .await
+    }
+
+    async fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, bonsaidb_core::Error> {
+        self.db
+            .reduce_with_staleness_by_name(view, key, access_policy)
+            .await
+    }
+
+    async fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        self.db.count_view_by_name(view, key, access_policy).await
+    }
+
+    async fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        self.db.facet_counts_by_name(view, key, access_policy).await
+    }
+
+    async fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        self.db
+            .get_with_view_value_by_name(view, id, access_policy)
+            .await
+    }
+
+    async fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, bonsaidb_core::Error> {
+        self.db
+            .get_with_mappings_by_name(collection, id, access_policy)
             .await
     }
 
@@ -249,11 +367,11 @@ impl<B: Backend> AsyncLowLevelConnection for ServerDatabase<B> {
         self.db.delete_docs_by_name(view, key, access_policy).await
     }
 
-    async fn apply_transaction(
+    async fn apply_transaction_with_id(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
-        self.db.apply_transaction(transaction).await
+    ) -> Result<AppliedTransaction, bonsaidb_core::Error> {
+        self.db.apply_transaction_with_id(transaction).await
     }
 }
 
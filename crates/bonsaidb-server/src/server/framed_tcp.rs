@@ -0,0 +1,227 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use bonsaidb_core::networking::{Payload, CURRENT_PROTOCOL_VERSION};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::server::shutdown::ShutdownState;
+use crate::{Backend, CustomServer, Error, Transport};
+
+/// The longest protocol version a client may send when connecting.
+const MAX_PROTOCOL_VERSION_LENGTH: usize = 256;
+
+/// How long a client has to send its protocol version after connecting.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl<B: Backend> CustomServer<B> {
+    /// Listens for clients connecting over plain TCP on `addr`. Does not
+    /// return until the server shuts down. Requires feature `tcp` to be
+    /// enabled.
+    ///
+    /// Clients connect to this listener using the `bonsaidb+tcp` URL scheme.
+    /// Requests and responses are sent as the same `bincode`-encoded
+    /// [`Payload`]s used by `WebSocket` connections, each prefixed by its
+    /// length as a big-endian `u32`.
+    ///
+    /// ## Security
+    ///
+    /// Connections accepted by this listener are not encrypted, and the
+    /// server's identity is not verified by the client. This transport should
+    /// only be used behind a proxy that terminates TLS, or on a network that
+    /// is otherwise trusted.
+    pub async fn listen_for_framed_tcp_on<T: tokio::net::ToSocketAddrs + Send + Sync>(
+        &self,
+        addr: T,
+    ) -> Result<(), Error> {
+        let listener = TcpListener::bind(&addr).await?;
+        let mut shutdown_watcher = self
+            .data
+            .shutdown
+            .watcher()
+            .await
+            .expect("server already shutdown");
+
+        loop {
+            tokio::select! {
+                _ = shutdown_watcher.wait_for_shutdown() => {
+                    break;
+                }
+                incoming = listener.accept() => {
+                    let Ok((connection, remote_addr)) = incoming else { continue };
+
+                    let task_self = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = task_self.handle_framed_tcp_connection(connection, remote_addr).await {
+                            log::error!("[server] closing connection {}: {:?}", remote_addr, err);
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_framed_tcp_connection(
+        &self,
+        connection: TcpStream,
+        peer_address: SocketAddr,
+    ) -> Result<(), Error> {
+        let (reader, writer) = connection.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut writer = BufWriter::new(writer);
+
+        // The client begins by sending the protocol version it speaks. The
+        // server echoes the version back if it is supported, or replies with
+        // an empty frame and disconnects if it is not.
+        let handshake = tokio::time::timeout(
+            HANDSHAKE_TIMEOUT,
+            read_frame(&mut reader, MAX_PROTOCOL_VERSION_LENGTH),
+        )
+        .await
+        .map_err(|_| {
+            Error::Core(bonsaidb_core::Error::other(
+                "tcp",
+                "timed out waiting for the protocol version",
+            ))
+        })?;
+        let Some(protocol_version) = handshake? else {
+            return Ok(());
+        };
+        if protocol_version != CURRENT_PROTOCOL_VERSION.as_bytes() {
+            write_frame(&mut writer, &[]).await?;
+            return Ok(());
+        }
+        write_frame(&mut writer, CURRENT_PROTOCOL_VERSION.as_bytes()).await?;
+
+        let mut shutdown = self
+            .data
+            .shutdown
+            .watcher()
+            .await
+            .expect("watcher shut down");
+
+        let (response_sender, response_receiver) = flume::unbounded::<Payload>();
+        let (api_response_sender, api_response_receiver) = flume::unbounded();
+        let Some(client) = self
+            .initialize_client(Transport::Tcp, peer_address, api_response_sender)
+            .await
+        else {
+            return Ok(());
+        };
+        let task_sender = response_sender.clone();
+        tokio::spawn(async move {
+            while let Ok((session_id, name, value)) = api_response_receiver.recv_async().await {
+                if task_sender
+                    .send(Payload {
+                        id: None,
+                        session_id,
+                        name,
+                        value: Ok(value),
+                        compression: None,
+                        accept_compression: None,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            while let Ok(response) = response_receiver.recv_async().await {
+                let result = match bincode::serialize(&response) {
+                    Ok(frame) => write_frame(&mut writer, &frame).await,
+                    Err(err) => Err(Error::from(err)),
+                };
+                if let Err(err) = result {
+                    log::error!("[server] error writing to {peer_address}: {err:?}");
+                    break;
+                }
+            }
+        });
+
+        let (request_sender, request_receiver) =
+            flume::bounded::<Payload>(self.data.client_simultaneous_request_limit);
+
+        self.spawn_client_request_handler(client, request_receiver, response_sender, &shutdown);
+
+        loop {
+            tokio::select! {
+                frame = read_frame(&mut reader, self.data.deserialization_limit) => {
+                    // The client closed the connection between frames.
+                    let Some(frame) = frame? else {
+                        return Ok(());
+                    };
+                    let payload = self.deserialize_payload(&frame)?;
+                    drop(request_sender.send_async(payload).await);
+                },
+                shutdown = shutdown.wait_for_shutdown() => {
+                    if matches!(shutdown, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads a single length-prefixed frame. Frames longer than `max_length` are
+/// rejected before any space is allocated for them.
+///
+/// Returns `None` if the stream ends before the first byte of a frame. A
+/// stream ending part way through a frame is an error.
+async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_length: usize,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut length = [0; 4];
+    if reader.read(&mut length[..1]).await? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut length[1..]).await?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > max_length {
+        return Err(Error::Core(bonsaidb_core::Error::other(
+            "tcp",
+            format!("frame of {length} bytes exceeds the limit of {max_length} bytes"),
+        )));
+    }
+
+    let mut frame = vec![0; length];
+    reader.read_exact(&mut frame).await?;
+    Ok(Some(frame))
+}
+
+/// Writes `frame` prefixed by its length.
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<(), Error> {
+    let length = u32::try_from(frame.len())
+        .map_err(|_| Error::Core(bonsaidb_core::Error::other("tcp", "frame too long")))?;
+    writer.write_u32(length).await?;
+    writer.write_all(frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_frame_eof() {
+        // Ending the stream between frames closes the connection cleanly.
+        assert!(read_frame(&mut &[][..], 16).await.unwrap().is_none());
+        assert_eq!(
+            read_frame(&mut &[0, 0, 0, 2, 1, 2][..], 16).await.unwrap(),
+            Some(vec![1, 2])
+        );
+
+        // Ending the stream part way through a frame is an error.
+        assert!(read_frame(&mut &[0, 0][..], 16).await.is_err());
+        assert!(read_frame(&mut &[0, 0, 0, 2, 1][..], 16).await.is_err());
+
+        // Frames longer than the limit are rejected.
+        assert!(read_frame(&mut &[0, 0, 0, 17][..], 16).await.is_err());
+    }
+}
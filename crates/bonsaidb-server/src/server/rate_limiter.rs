@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bonsaidb_core::api::ApiName;
+use bonsaidb_core::connection::{Identity, Session, SessionAuthentication};
+use parking_lot::Mutex;
+
+use crate::config::{RateLimit, RateLimits};
+
+/// How often buckets that have replenished all of their tokens are removed.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tracks the token buckets for every client that has made a rate limited
+/// request.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    buckets: HashMap<BucketKey, TokenBucket>,
+    last_pruned: Instant,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct BucketKey {
+    subject: Subject,
+    /// The api the bucket is limiting, or `None` if the bucket is shared by
+    /// all requests using the default limit.
+    api: Option<ApiName>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+enum Subject {
+    Identity(Arc<Identity>),
+    Client(u32),
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            state: Mutex::new(State {
+                buckets: HashMap::new(),
+                last_pruned: Instant::now(),
+            }),
+        }
+    }
+
+    /// Consumes a token for a request to `api` made by `session` on the client
+    /// with id `client_id`. If no tokens are available,
+    /// [`bonsaidb_core::Error::RateLimited`] is returned.
+    pub fn check(
+        &self,
+        client_id: u32,
+        session: &Session,
+        api: &ApiName,
+    ) -> Result<(), bonsaidb_core::Error> {
+        let Some((limit, api_specific)) = self.limits.limit_for(api) else {
+            return Ok(());
+        };
+        if limit.replenish_interval.is_zero() {
+            return Ok(());
+        }
+
+        let subject = match &session.authentication {
+            SessionAuthentication::Identity(identity) => Subject::Identity(identity.clone()),
+            _ => Subject::Client(client_id),
+        };
+        let key = BucketKey {
+            subject,
+            api: api_specific.then(|| api.clone()),
+        };
+
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        if now.saturating_duration_since(state.last_pruned) >= PRUNE_INTERVAL {
+            state.prune(&self.limits, now);
+        }
+        state
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::full(limit, now))
+            .take(limit, now)
+            .map_err(|retry_after| bonsaidb_core::Error::RateLimited { retry_after })
+    }
+
+    /// Removes all buckets belonging to the unauthenticated sessions of the
+    /// client with id `client_id`.
+    pub fn forget_client(&self, client_id: u32) {
+        if self.limits.default.is_none() && self.limits.apis.is_empty() {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        state
+            .buckets
+            .retain(|key, _| key.subject != Subject::Client(client_id));
+    }
+}
+
+impl State {
+    /// Removes the buckets that have replenished all of their tokens. A full
+    /// bucket behaves identically to the bucket created when a subject makes
+    /// its first request, so only the buckets of recently active subjects are
+    /// kept.
+    fn prune(&mut self, limits: &RateLimits, now: Instant) {
+        self.buckets.retain(|key, bucket| {
+            let limit = match &key.api {
+                Some(api) => limits.apis.get(api),
+                None => limits.default.as_ref(),
+            };
+            limit.map_or(false, |limit| !bucket.is_full(*limit, now))
+        });
+        self.last_pruned = now;
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: u32,
+    last_replenished: Instant,
+}
+
+impl TokenBucket {
+    const fn full(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            last_replenished: now,
+        }
+    }
+
+    /// Returns true if all of the bucket's tokens have been replenished by
+    /// `now`.
+    fn is_full(&self, limit: RateLimit, now: Instant) -> bool {
+        let missing = limit.burst.saturating_sub(self.tokens);
+        limit
+            .replenish_interval
+            .checked_mul(missing)
+            .map_or(false, |refill| {
+                now.saturating_duration_since(self.last_replenished) >= refill
+            })
+    }
+
+    /// Consumes a token. If no tokens are available, the amount of time until
+    /// the next token is replenished is returned.
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_replenished);
+        let replenished = elapsed.as_nanos() / limit.replenish_interval.as_nanos();
+        if replenished > 0 {
+            let replenished = u32::try_from(replenished).unwrap_or(u32::MAX);
+            self.tokens = self.tokens.saturating_add(replenished).min(limit.burst);
+            if self.tokens == limit.burst {
+                self.last_replenished = now;
+            } else {
+                self.last_replenished += limit.replenish_interval * replenished;
+            }
+        }
+
+        if self.tokens > 0 {
+            self.tokens -= 1;
+            Ok(())
+        } else {
+            Err(limit
+                .replenish_interval
+                .saturating_sub(now.saturating_duration_since(self.last_replenished)))
+        }
+    }
+}
+
+#[test]
+fn full_buckets_are_pruned() {
+    use bonsaidb_core::schema::Qualified;
+
+    let limit = RateLimit::new(2, Duration::from_secs(1));
+    let limiter = RateLimiter::new(RateLimits {
+        default: Some(limit),
+        apis: HashMap::new(),
+    });
+    let api = ApiName::private("api");
+    let session = Session::default();
+    limiter.check(1, &session, &api).unwrap();
+    limiter.check(1, &session, &api).unwrap();
+    limiter.check(2, &session, &api).unwrap();
+
+    let mut state = limiter.state.lock();
+    let now = state
+        .buckets
+        .values()
+        .map(|bucket| bucket.last_replenished)
+        .max()
+        .unwrap();
+    // After one interval, only the bucket that consumed a single token has
+    // been replenished.
+    state.prune(&limiter.limits, now + limit.replenish_interval);
+    assert_eq!(state.buckets.len(), 1);
+    state.prune(&limiter.limits, now + limit.replenish_interval * 2);
+    assert!(state.buckets.is_empty());
+}
@@ -3,8 +3,7 @@ use futures::{SinkExt, StreamExt};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::server::connected_client::OwnedClient;
-use crate::server::shutdown::{ShutdownState, ShutdownStateWatcher};
+use crate::server::shutdown::ShutdownState;
 use crate::{Backend, CustomServer, Error, Transport};
 
 impl<B: Backend> CustomServer<B> {
@@ -126,6 +125,8 @@ impl<B: Backend> CustomServer<B> {
                         session_id,
                         name,
                         value: Ok(value),
+                        compression: None,
+                        accept_compression: None,
                     })
                     .is_err()
                 {
@@ -168,7 +169,7 @@ impl<B: Backend> CustomServer<B> {
                 payload = receiver.next() => {
                     if let Some(payload) = payload {
                         match payload {
-                            Ok(Message::Binary(binary)) => match bincode::deserialize::<Payload>(&binary) {
+                            Ok(Message::Binary(binary)) => match self.deserialize_payload(&binary) {
                                 Ok(payload) => drop(request_sender.send_async(payload).await),
                                 Err(err) => {
                                     log::error!("[server] error decoding message: {:?}", err);
@@ -189,36 +190,13 @@ impl<B: Backend> CustomServer<B> {
                     }
                 },
                 shutdown = shutdown.wait_for_shutdown() => {
-                    if matches!(shutdown, ShutdownState::Shutdown) {
+                    if matches!(shutdown, ShutdownState::Shutdown | ShutdownState::GracefulShutdown) {
                         return;
                     }
                 }
             }
         }
     }
-
-    fn spawn_client_request_handler(
-        &self,
-        client: OwnedClient<B>,
-        request_receiver: flume::Receiver<Payload>,
-        response_sender: flume::Sender<Payload>,
-        shutdown: &ShutdownStateWatcher,
-    ) {
-        tokio::spawn({
-            let task_self = self.clone();
-            let shutdown = shutdown.clone();
-            async move {
-                task_self
-                    .handle_client_requests(
-                        client.clone(),
-                        request_receiver,
-                        response_sender,
-                        shutdown,
-                    )
-                    .await;
-            }
-        });
-    }
 }
 
 #[cfg(feature = "hyper")]
@@ -1,10 +1,12 @@
 use bonsaidb_core::actionable::{Permissions, Statement};
 use bonsaidb_core::connection::AsyncStorageConnection;
 use bonsaidb_core::test_util::{self, BasicSchema, HarnessTest, TestDirectory};
+use bonsaidb_local::config::Builder;
+use fabruic::KeyPair;
 
 use crate::server::ServerDatabase;
 use crate::test_util::initialize_basic_server;
-use crate::Server;
+use crate::{NoBackend, Server, ServerConfiguration};
 
 #[tokio::test]
 async fn simple_test() -> anyhow::Result<()> {
@@ -14,6 +16,58 @@ async fn simple_test() -> anyhow::Result<()> {
     test_util::store_retrieve_update_delete_tests(&db).await
 }
 
+#[test]
+fn storage_builder_tests() {
+    let config = ServerConfiguration::<NoBackend>::default()
+        .cache_capacity(10)
+        .cache_max_chunk_size(1024);
+    assert_eq!(config.storage.cache.capacity, 10);
+    assert_eq!(config.storage.cache.max_chunk_size, 1024);
+}
+
+#[tokio::test]
+async fn deserialization_limit_tests() -> anyhow::Result<()> {
+    let test_dir = TestDirectory::new("deserialization-limit");
+    let server =
+        Server::open(ServerConfiguration::new(&test_dir).deserialization_limit(1024)).await?;
+
+    // API requests that would allocate more than the limit are rejected.
+    let request = pot::to_vec(&"a".repeat(4096))?;
+    assert!(server.deserialize_request::<String>(&request).is_err());
+    let request = pot::to_vec(&"a".repeat(16))?;
+    assert_eq!(
+        server.deserialize_request::<String>(&request)?,
+        "a".repeat(16)
+    );
+
+    // As are payloads received over websockets.
+    #[cfg(feature = "websockets")]
+    {
+        use bonsaidb_core::api::ApiName;
+        use bonsaidb_core::arc_bytes::serde::Bytes;
+        use bonsaidb_core::networking::Payload;
+        use bonsaidb_core::schema::Qualified;
+
+        let mut payload = Payload {
+            session_id: None,
+            id: Some(1),
+            name: ApiName::private("test"),
+            value: Ok(Bytes::from(vec![0; 4096])),
+            compression: None,
+            accept_compression: None,
+        };
+        assert!(server
+            .deserialize_payload(&bincode::serialize(&payload)?)
+            .is_err());
+        payload.value = Ok(Bytes::from(vec![0; 16]));
+        assert!(server
+            .deserialize_payload(&bincode::serialize(&payload)?)
+            .is_ok());
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn install_self_signed_certificate_tests() -> anyhow::Result<()> {
     let test_dir = TestDirectory::new("cert-install-test");
@@ -35,6 +89,44 @@ async fn install_self_signed_certificate_tests() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn reload_certificate_tests() -> anyhow::Result<()> {
+    let test_dir = TestDirectory::new("cert-reload-test");
+    let server = initialize_basic_server(test_dir.as_ref()).await?;
+    let old_certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+
+    // A private key that doesn't belong to the certificate must be rejected
+    // without replacing the installed certificate.
+    let first = KeyPair::new_self_signed("test");
+    let second = KeyPair::new_self_signed("test");
+    assert!(server
+        .reload_certificate(first.certificate_chain(), second.private_key())
+        .await
+        .is_err());
+    assert_eq!(
+        server
+            .certificate_chain()
+            .await?
+            .into_end_entity_certificate(),
+        old_certificate
+    );
+
+    server
+        .reload_certificate(first.certificate_chain(), first.private_key())
+        .await?;
+    assert_eq!(
+        &server
+            .certificate_chain()
+            .await?
+            .into_end_entity_certificate(),
+        first.end_entity_certificate()
+    );
+    Ok(())
+}
+
 struct TestHarness {
     _directory: TestDirectory,
     server: Server,
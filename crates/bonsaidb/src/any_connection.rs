@@ -1,16 +1,18 @@
 use bonsaidb_client::{AsyncClient, AsyncRemoteDatabase};
+use bonsaidb_core::arc_bytes::serde::Bytes;
 use bonsaidb_core::async_trait::async_trait;
 use bonsaidb_core::connection::{
     self, AccessPolicy, AsyncConnection, AsyncLowLevelConnection, AsyncStorageConnection,
-    HasSchema, HasSession, IdentityReference, Range, SerializedQueryKey, Session, Sort,
+    HasSchema, HasSession, IdentityReference, Range, SerializedQueryKey, SerializedViewPage,
+    SerializedViewQuery, Session, Sort, ViewCursor,
 };
-use bonsaidb_core::document::{DocumentId, Header, OwnedDocument};
-use bonsaidb_core::schema::view::map::MappedSerializedValue;
+use bonsaidb_core::document::{DocumentId, Header, OwnedDocument, Revision};
+use bonsaidb_core::schema::view::map::{EmittedKey, MappedSerializedValue, Reduction};
 use bonsaidb_core::schema::{
     self, Collection, CollectionName, Nameable, Schema, SchemaName, SchemaSummary, Schematic,
     ViewName,
 };
-use bonsaidb_core::transaction::{Executed, OperationResult, Transaction};
+use bonsaidb_core::transaction::{AppliedTransaction, Executed, Transaction};
 use bonsaidb_server::{Backend, CustomServer, NoBackend, ServerDatabase};
 use derive_where::derive_where;
 
@@ -84,6 +86,17 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
         }
     }
 
+    async fn rename_database(
+        &self,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.rename_database(name, new_name).await,
+            Self::Networked(client) => client.rename_database(name, new_name).await,
+        }
+    }
+
     async fn list_databases(&self) -> Result<Vec<connection::Database>, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => server.list_databases().await,
@@ -91,6 +104,16 @@ impl<B: Backend> AsyncStorageConnection for AnyServerConnection<B> {
         }
     }
 
+    async fn database_schema(
+        &self,
+        name: &str,
+    ) -> Result<Option<SchemaName>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.database_schema(name).await,
+            Self::Networked(client) => client.database_schema(name).await,
+        }
+    }
+
     async fn list_available_schemas(&self) -> Result<Vec<SchemaSummary>, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => server.list_available_schemas().await,
@@ -312,13 +335,13 @@ impl<B: Backend> AsyncConnection for AnyDatabase<B> {
 
 #[async_trait]
 impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
-    async fn apply_transaction(
+    async fn apply_transaction_with_id(
         &self,
         transaction: Transaction,
-    ) -> Result<Vec<OperationResult>, bonsaidb_core::Error> {
+    ) -> Result<AppliedTransaction, bonsaidb_core::Error> {
         match self {
-            Self::Local(server) => server.apply_transaction(transaction).await,
-            Self::Networked(client) => client.apply_transaction(transaction).await,
+            Self::Local(server) => server.apply_transaction_with_id(transaction).await,
+            Self::Networked(client) => client.apply_transaction_with_id(transaction).await,
         }
     }
 
@@ -333,6 +356,37 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         }
     }
 
+    async fn history_from_collection(
+        &self,
+        id: DocumentId,
+        collection: &CollectionName,
+    ) -> Result<Vec<Header>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.history_from_collection(id, collection).await,
+            Self::Networked(client) => client.history_from_collection(id, collection).await,
+        }
+    }
+
+    async fn get_revision_from_collection(
+        &self,
+        id: DocumentId,
+        revision: Revision,
+        collection: &CollectionName,
+    ) -> Result<Option<OwnedDocument>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .get_revision_from_collection(id, revision, collection)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .get_revision_from_collection(id, revision, collection)
+                    .await
+            }
+        }
+    }
+
     async fn list_from_collection(
         &self,
         ids: Range<DocumentId>,
@@ -397,6 +451,17 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         }
     }
 
+    async fn existing_ids_in_collection(
+        &self,
+        ids: &[DocumentId],
+        collection: &CollectionName,
+    ) -> Result<Vec<DocumentId>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.existing_ids_in_collection(ids, collection).await,
+            Self::Networked(client) => client.existing_ids_in_collection(ids, collection).await,
+        }
+    }
+
     async fn compact_collection_by_name(
         &self,
         collection: CollectionName,
@@ -429,6 +494,39 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         }
     }
 
+    async fn query_page_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        order: Sort,
+        limit: Option<u32>,
+        after: Option<ViewCursor>,
+        access_policy: AccessPolicy,
+    ) -> Result<SerializedViewPage, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .query_page_by_name(view, key, order, limit, after, access_policy)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .query_page_by_name(view, key, order, limit, after, access_policy)
+                    .await
+            }
+        }
+    }
+
+    async fn query_multiple_by_name(
+        &self,
+        queries: Vec<SerializedViewQuery>,
+    ) -> Result<Vec<Vec<schema::view::map::Serialized>>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.query_multiple_by_name(queries).await,
+            Self::Networked(client) => client.query_multiple_by_name(queries).await,
+        }
+    }
+
     async fn query_by_name_with_docs(
         &self,
         view: &ViewName,
@@ -467,17 +565,102 @@ impl<B: Backend> AsyncLowLevelConnection for AnyDatabase<B> {
         &self,
         view: &ViewName,
         key: Option<SerializedQueryKey>,
+        group_level: Option<usize>,
         access_policy: AccessPolicy,
     ) -> Result<Vec<MappedSerializedValue>, bonsaidb_core::Error> {
         match self {
             Self::Local(server) => {
                 server
-                    .reduce_grouped_by_name(view, key, access_policy)
+                    .reduce_grouped_by_name(view, key, group_level, access_policy)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .reduce_grouped_by_name(view, key, group_level, access_policy)
+                    .await
+            }
+        }
+    }
+
+    async fn reduce_with_staleness_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Reduction<Bytes>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .reduce_with_staleness_by_name(view, key, access_policy)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .reduce_with_staleness_by_name(view, key, access_policy)
+                    .await
+            }
+        }
+    }
+
+    async fn count_view_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<u64, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.count_view_by_name(view, key, access_policy).await,
+            Self::Networked(client) => client.count_view_by_name(view, key, access_policy).await,
+        }
+    }
+
+    async fn facet_counts_by_name(
+        &self,
+        view: &ViewName,
+        key: Option<SerializedQueryKey>,
+        access_policy: AccessPolicy,
+    ) -> Result<Vec<(Bytes, u64)>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => server.facet_counts_by_name(view, key, access_policy).await,
+            Self::Networked(client) => client.facet_counts_by_name(view, key, access_policy).await,
+        }
+    }
+
+    async fn get_with_view_value_by_name(
+        &self,
+        view: &ViewName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Option<Bytes>)>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .get_with_view_value_by_name(view, id, access_policy)
+                    .await
+            }
+            Self::Networked(client) => {
+                client
+                    .get_with_view_value_by_name(view, id, access_policy)
+                    .await
+            }
+        }
+    }
+
+    async fn get_with_mappings_by_name(
+        &self,
+        collection: &CollectionName,
+        id: DocumentId,
+        access_policy: AccessPolicy,
+    ) -> Result<Option<(OwnedDocument, Vec<EmittedKey>)>, bonsaidb_core::Error> {
+        match self {
+            Self::Local(server) => {
+                server
+                    .get_with_mappings_by_name(collection, id, access_policy)
                     .await
             }
             Self::Networked(client) => {
                 client
-                    .reduce_grouped_by_name(view, key, access_policy)
+                    .get_with_mappings_by_name(collection, id, access_policy)
                     .await
             }
         }
@@ -1,26 +1,52 @@
 //! Tests invoking an API defined in a custom backend.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
 use bonsaidb::client::url::Url;
-use bonsaidb::client::AsyncClient;
+use bonsaidb::client::{ApiCallback, AsyncClient, BlockingClient};
 use bonsaidb::core::api::{Api, Infallible};
+use bonsaidb::core::arc_bytes::serde::Bytes;
 use bonsaidb::core::async_trait::async_trait;
+use bonsaidb::core::connection::{
+    AsyncStorageConnection, Connection, RetryPolicy, Session, StorageConnection,
+};
+use bonsaidb::core::networking::{ApplyTransaction, ListDatabases, ServerShuttingDown};
+use bonsaidb::core::schema::SerializedCollection;
 use bonsaidb::core::test_util::{Basic, TestDirectory};
+use bonsaidb::core::transaction::{Operation, Transaction};
 use bonsaidb::local::config::Builder;
 use bonsaidb::server::api::Handler;
-use bonsaidb::server::{Backend, CustomServer, DefaultPermissions, ServerConfiguration};
+use bonsaidb::server::{
+    Backend, BackendError, ConnectedClient, ConnectionHandling, CustomServer, DefaultPermissions,
+    RateLimit, Server, ServerConfiguration,
+};
 use bonsaidb_core::api::ApiName;
 use bonsaidb_core::schema::Qualified;
-use bonsaidb_server::api::{HandlerResult, HandlerSession};
+use bonsaidb_server::api::{HandlerResult, HandlerSession, NextHandler, Request};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default)]
 struct CustomBackend;
 
+#[async_trait]
 impl Backend for CustomBackend {
     type ClientData = u64;
     type Error = Infallible;
+
+    async fn client_connected(
+        &self,
+        client: &ConnectedClient<Self>,
+        _server: &CustomServer<Self>,
+    ) -> Result<ConnectionHandling, BackendError<Self::Error>> {
+        client.set_state(Tenant(String::from("acme")));
+        Ok(ConnectionHandling::Accept)
+    }
 }
 
+#[derive(Debug)]
+struct Tenant(String);
+
 #[tokio::test]
 async fn custom_api() -> anyhow::Result<()> {
     let dir = TestDirectory::new("custom_api.bonsaidb");
@@ -28,6 +54,7 @@ async fn custom_api() -> anyhow::Result<()> {
         ServerConfiguration::new(&dir)
             .default_permissions(DefaultPermissions::AllowAll)
             .with_api::<SetValueHandler, _>()?
+            .with_api::<CurrentTenantHandler, _>()?
             .with_schema::<Basic>()?,
     )
     .await?;
@@ -40,6 +67,7 @@ async fn custom_api() -> anyhow::Result<()> {
 
     let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12346")?)
         .with_api::<SetValue>()
+        .with_api::<CurrentTenant>()
         .with_certificate(certificate)
         .build()?;
 
@@ -48,6 +76,9 @@ async fn custom_api() -> anyhow::Result<()> {
     let old_value = client.send_api_request(&SetValue { new_value: 2 }).await?;
     assert_eq!(old_value, Some(1));
 
+    let tenant = client.send_api_request(&CurrentTenant).await?;
+    assert_eq!(tenant.as_deref(), Some("acme"));
+
     Ok(())
 }
 
@@ -79,3 +110,281 @@ impl Handler<SetValue, CustomBackend> for SetValueHandler {
         Ok(existing_value)
     }
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CurrentTenant;
+
+impl Api for CurrentTenant {
+    type Error = Infallible;
+    type Response = Option<String>;
+
+    fn name() -> ApiName {
+        ApiName::private("current-tenant")
+    }
+}
+
+#[derive(Debug)]
+struct CurrentTenantHandler;
+
+#[async_trait]
+impl Handler<CurrentTenant, CustomBackend> for CurrentTenantHandler {
+    async fn handle(
+        session: HandlerSession<'_, CustomBackend>,
+        _request: CurrentTenant,
+    ) -> HandlerResult<CurrentTenant> {
+        Ok(session
+            .client
+            .state::<Tenant>()
+            .map(|tenant| tenant.0.clone()))
+    }
+}
+
+/// A backend that counts every request and refuses to list databases.
+#[derive(Debug, Default)]
+struct GuardedBackend {
+    requests: AtomicUsize,
+}
+
+#[async_trait]
+impl Backend for GuardedBackend {
+    type ClientData = ();
+    type Error = Infallible;
+
+    async fn dispatch_request(
+        &self,
+        _client: &ConnectedClient<Self>,
+        _session: &Session,
+        request: Request,
+        next: NextHandler<'_, Self>,
+    ) -> Result<Bytes, bonsaidb_server::Error> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        if request.name == ListDatabases::name() {
+            return Err(bonsaidb_server::Error::from(bonsaidb_core::Error::other(
+                "guard",
+                "listing databases is disabled",
+            )));
+        }
+        next.dispatch(request).await
+    }
+}
+
+#[tokio::test]
+async fn request_middleware() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("request_middleware.bonsaidb");
+    let server = CustomServer::<GuardedBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let task_server = server.clone();
+    tokio::spawn(async move { task_server.listen_on(12348).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12348")?)
+        .with_certificate(certificate)
+        .build()?;
+
+    client.create_database::<Basic>("guarded", false).await?;
+    assert!(matches!(
+        client.list_databases().await,
+        Err(bonsaidb_core::Error::Other { origin, .. }) if origin == "guard"
+    ));
+    assert_eq!(server.backend().requests.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limited_api() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("rate_limited_api.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .api_rate_limit::<ListDatabases>(RateLimit::new(2, Duration::from_secs(3600)))
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let task_server = server.clone();
+    tokio::spawn(async move { task_server.listen_on(12349).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12349")?)
+        .with_certificate(certificate)
+        .build()?;
+
+    client.list_databases().await?;
+    client.list_databases().await?;
+    assert!(matches!(
+        client.list_databases().await,
+        Err(bonsaidb_core::Error::RateLimited { retry_after })
+            if retry_after > Duration::ZERO && retry_after <= Duration::from_secs(3600)
+    ));
+
+    // Requests without a limit are unaffected.
+    client.create_database::<Basic>("unlimited", false).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limited_transaction_retry() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("rate_limited_transaction_retry.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .api_rate_limit::<ApplyTransaction>(RateLimit::new(1, Duration::from_millis(100)))
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    server.create_database::<Basic>("retried", false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let task_server = server.clone();
+    tokio::spawn(async move { task_server.listen_on(12351).await });
+
+    tokio::task::spawn_blocking(move || {
+        let client = BlockingClient::build(Url::parse("bonsaidb://localhost:12351")?)
+            .with_certificate(certificate)
+            .build()?;
+        let db = client.database::<Basic>("retried")?;
+        let transaction =
+            Transaction::from(Operation::push_serialized::<Basic>(&Basic::new("retried"))?);
+
+        db.apply_transaction_with_retry(transaction.clone(), &RetryPolicy::default())?;
+        // A single attempt returns the rate limit error.
+        assert!(matches!(
+            db.apply_transaction_with_retry(
+                transaction.clone(),
+                &RetryPolicy::default().with_max_attempts(1),
+            ),
+            Err(bonsaidb_core::Error::RateLimited { .. })
+        ));
+        // Retrying waits for the rate limit to replenish.
+        db.apply_transaction_with_retry(transaction, &RetryPolicy::default())?;
+        assert_eq!(Basic::all(&db).count()?, 2);
+
+        anyhow::Ok(())
+    })
+    .await?
+}
+
+/// A backend that counts disconnected clients.
+#[derive(Debug, Default)]
+struct DrainingBackend {
+    disconnected: AtomicUsize,
+}
+
+#[async_trait]
+impl Backend for DrainingBackend {
+    type ClientData = ();
+    type Error = Infallible;
+
+    async fn client_disconnected(
+        &self,
+        _client: ConnectedClient<Self>,
+        _server: &CustomServer<Self>,
+    ) -> Result<(), BackendError<Self::Error>> {
+        self.disconnected.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Sleep {
+    duration: Duration,
+}
+
+impl Api for Sleep {
+    type Error = Infallible;
+    type Response = ();
+
+    fn name() -> ApiName {
+        ApiName::private("sleep")
+    }
+}
+
+#[derive(Debug)]
+struct SleepHandler;
+
+#[async_trait]
+impl Handler<Sleep, DrainingBackend> for SleepHandler {
+    async fn handle(
+        _session: HandlerSession<'_, DrainingBackend>,
+        request: Sleep,
+    ) -> HandlerResult<Sleep> {
+        tokio::time::sleep(request.duration).await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn graceful_shutdown() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("graceful_shutdown.bonsaidb");
+    let server = CustomServer::<DrainingBackend>::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_api::<SleepHandler, _>()?
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let task_server = server.clone();
+    tokio::spawn(async move { task_server.listen_on(12350).await });
+
+    let (notice_sender, mut notice_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12350")?)
+        .with_api::<Sleep>()
+        .with_api_callback::<ServerShuttingDown>(ApiCallback::new(
+            move |notice: ServerShuttingDown| {
+                let notice_sender = notice_sender.clone();
+                async move {
+                    drop(notice_sender.send(notice));
+                }
+            },
+        ))
+        .with_certificate(certificate)
+        .build()?;
+    client.list_databases().await?;
+
+    let request = tokio::spawn({
+        let client = client.clone();
+        async move {
+            client
+                .send_api_request(&Sleep {
+                    duration: Duration::from_millis(500),
+                })
+                .await
+        }
+    });
+    // Give the request time to reach the server.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    server.shutdown(Some(Duration::from_secs(10))).await?;
+
+    // The outstanding request was answered, and the backend was notified of
+    // the disconnection before shutdown returned.
+    request.await??;
+    assert_eq!(server.backend().disconnected.load(Ordering::SeqCst), 1);
+    let notice = tokio::time::timeout(Duration::from_secs(5), notice_receiver.recv())
+        .await?
+        .expect("no shutdown notice");
+    assert_eq!(notice.timeout, Duration::from_secs(10));
+
+    Ok(())
+}
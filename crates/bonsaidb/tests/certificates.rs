@@ -0,0 +1,67 @@
+//! Tests replacing the server's certificate while it is listening.
+
+use std::time::Duration;
+
+use bonsaidb::client::url::Url;
+use bonsaidb::client::AsyncClient;
+use bonsaidb::core::connection::AsyncStorageConnection;
+use bonsaidb::core::test_util::{Basic, TestDirectory};
+use bonsaidb::local::config::Builder;
+use bonsaidb::server::fabruic::KeyPair;
+use bonsaidb::server::{DefaultPermissions, Server, ServerConfiguration};
+
+#[tokio::test]
+async fn quic_reload_certificate() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("quic-reload-certificate.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let old_certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    let task_server = server.clone();
+    tokio::spawn(async move { task_server.listen_on(12352).await });
+
+    let url = Url::parse("bonsaidb://localhost:12352")?;
+    let client = AsyncClient::build(url.clone())
+        .with_certificate(old_certificate.clone())
+        .build()?;
+    client.list_databases().await?;
+
+    let keypair = KeyPair::new_self_signed("bonsaidb");
+    server
+        .reload_certificate(keypair.certificate_chain(), keypair.private_key())
+        .await?;
+
+    // The endpoint is rebound in the background. A client that only trusts
+    // the new certificate can only connect once the new certificate is being
+    // presented.
+    let mut attempts = 0;
+    loop {
+        let client = AsyncClient::build(url.clone())
+            .with_certificate(keypair.end_entity_certificate().clone())
+            .build()?;
+        match client.list_databases().await {
+            Ok(_) => break,
+            Err(err) if attempts < 50 => {
+                println!("waiting for the new certificate: {err:?}");
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    // The previous certificate is no longer presented.
+    let client = AsyncClient::build(url)
+        .with_certificate(old_certificate)
+        .build()?;
+    assert!(client.list_databases().await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Tests compressing network responses.
+
+use bonsaidb::client::url::Url;
+use bonsaidb::client::AsyncClient;
+use bonsaidb::core::connection::AsyncStorageConnection;
+use bonsaidb::core::networking::PayloadCompression;
+use bonsaidb::core::schema::SerializedCollection;
+use bonsaidb::core::test_util::{Basic, TestDirectory};
+use bonsaidb::local::config::Builder;
+use bonsaidb::server::{CompressionMode, DefaultPermissions, Server, ServerConfiguration};
+
+#[tokio::test]
+async fn compressed_responses() -> anyhow::Result<()> {
+    let dir = TestDirectory::new("compressed-responses.bonsaidb");
+    let server = Server::open(
+        ServerConfiguration::new(&dir)
+            .default_permissions(DefaultPermissions::AllowAll)
+            .compression(CompressionMode::Enabled { threshold: 64 })
+            .with_schema::<Basic>()?,
+    )
+    .await?;
+    server.install_self_signed_certificate(false).await?;
+    let certificate = server
+        .certificate_chain()
+        .await?
+        .into_end_entity_certificate();
+    tokio::spawn(async move { server.listen_on(12347).await });
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://localhost:12347")?)
+        .with_certificate(certificate)
+        .with_compression(PayloadCompression::Lz4)
+        .build()?;
+
+    let db = client.create_database::<Basic>("basic", false).await?;
+    // Both a response below the threshold and one above it should arrive
+    // intact.
+    let small = Basic::new("small").push_into_async(&db).await?;
+    let large = Basic::new("large".repeat(1_000))
+        .push_into_async(&db)
+        .await?;
+    assert_eq!(
+        Basic::get_async(&small.header.id, &db)
+            .await?
+            .unwrap()
+            .contents
+            .value,
+        "small"
+    );
+    assert_eq!(
+        Basic::get_async(&large.header.id, &db)
+            .await?
+            .unwrap()
+            .contents
+            .value,
+        "large".repeat(1_000)
+    );
+
+    Ok(())
+}
@@ -64,6 +64,17 @@ fn run_shared_server(certificate_sender: flume::Sender<Certificate>) -> anyhow::
             });
         }
 
+        #[cfg(feature = "tcp")]
+        {
+            let task_server = server.clone();
+            tokio::spawn(async move {
+                task_server
+                    .listen_for_framed_tcp_on("localhost:6003")
+                    .await
+                    .unwrap();
+            });
+        }
+
         server.listen_on(6000).await.unwrap();
     });
 
@@ -199,6 +210,78 @@ mod websockets {
     bonsaidb_core::define_blocking_kv_test_suite!(BlockingWebsocketTestHarness);
 }
 
+#[cfg(feature = "tcp")]
+mod tcp {
+    use super::*;
+
+    struct TcpTestHarness {
+        client: AsyncClient,
+        url: Url,
+        db: AsyncRemoteDatabase,
+    }
+
+    impl TcpTestHarness {
+        pub async fn new(test: HarnessTest) -> anyhow::Result<Self> {
+            use bonsaidb_core::connection::AsyncStorageConnection;
+
+            initialize_shared_server().await;
+            let url = Url::parse("bonsaidb+tcp://localhost:6003")?;
+            let client = AsyncClient::new(url.clone())?;
+
+            let dbname = format!("tcp-{test}");
+            client
+                .create_database::<BasicSchema>(&dbname, false)
+                .await?;
+            let db = client.database::<BasicSchema>(&dbname).await?;
+
+            Ok(Self { client, url, db })
+        }
+
+        pub const fn server_name() -> &'static str {
+            "tcp"
+        }
+
+        pub fn server(&self) -> &AsyncClient {
+            &self.client
+        }
+
+        pub async fn connect(&self) -> anyhow::Result<AsyncRemoteDatabase> {
+            Ok(self.db.clone())
+        }
+
+        #[allow(dead_code)] // We will want this in the future but it's currently unused
+        pub async fn connect_with_permissions(
+            &self,
+            permissions: Vec<Statement>,
+            label: &str,
+        ) -> anyhow::Result<AsyncRemoteDatabase> {
+            let client = AsyncClient::new(self.url.clone())?;
+            assume_permissions(client, label, self.db.name(), permissions).await
+        }
+
+        pub async fn shutdown(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn incompatible_client_version() -> anyhow::Result<()> {
+        initialize_shared_server().await;
+
+        let url = Url::parse("bonsaidb+tcp://localhost:6003")?;
+        let client = AsyncClient::build(url)
+            .with_protocol_version(INCOMPATIBLE_PROTOCOL_VERSION)
+            .build()?;
+
+        check_incompatible_client(client).await
+    }
+
+    bonsaidb_core::define_async_connection_test_suite!(TcpTestHarness);
+
+    bonsaidb_core::define_async_pubsub_test_suite!(TcpTestHarness);
+    bonsaidb_core::define_async_kv_test_suite!(TcpTestHarness);
+}
+
 mod bonsai {
     use super::*;
     struct BonsaiTestHarness {
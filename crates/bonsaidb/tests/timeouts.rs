@@ -241,3 +241,28 @@ fn blocking_quic_request_timeout() {
         other => unreachable!("expected request timeout, got {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn quic_requests_are_pipelined() {
+    let cert_chain = shared_server();
+    // Give the server a moment to actually start up.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let client = AsyncClient::build(Url::parse("bonsaidb://127.0.0.1:7024").unwrap())
+        .with_certificate(cert_chain.clone())
+        .build()
+        .unwrap();
+    let slow_request = tokio::spawn({
+        let client = client.clone();
+        async move { client.send_api_request(&LongCall).await }
+    });
+    // Give the slow request a head start so that it is in flight before the
+    // fast request is sent.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let start = Instant::now();
+    client.list_databases().await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(!slow_request.is_finished());
+    slow_request.abort();
+}